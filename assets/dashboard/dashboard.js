@@ -0,0 +1,87 @@
+// Опрашивает /api/status и перерисовывает страницу; изображения подгружаются, только
+// когда поменялись
+const STATUS_MS = 2000;
+const WATERFALL_MS = 5000;
+
+let revision = -1;
+let shown = "";
+
+function text(tag, content) {
+  const element = document.createElement(tag);
+  element.textContent = content;
+  return element;
+}
+
+function renderStatus(status) {
+  const line = document.getElementById("status");
+  const parts = [];
+  if (!status.running) {
+    parts.push("Receiver stopped");
+  } else if (status.receiving) {
+    parts.push(`Receiving line ${status.receiving.lines}/${status.receiving.height}`);
+  } else if (!status.squelch_open) {
+    parts.push("Squelch closed");
+  } else {
+    parts.push("Waiting for a VIS header…");
+  }
+  if (status.vis) parts.push(status.vis);
+  parts.push(`${status.level_db.toFixed(1)} dBFS`);
+  parts.push(`${Math.round(status.elapsed_secs)} s`);
+  if (status.location) parts.push(status.location);
+  if (status.error) parts.push(status.error);
+  line.textContent = parts.join(" · ");
+  line.classList.toggle("error", Boolean(status.error));
+
+  const current = document.getElementById("current");
+  if (!status.receiving) {
+    current.hidden = true;
+  } else if (status.revision !== revision) {
+    revision = status.revision;
+    current.src = `/current.png?t=${revision}`;
+    current.hidden = false;
+  }
+
+  const ids = status.images.map((image) => image.id).join(",");
+  if (ids !== shown) {
+    shown = ids;
+    const images = document.getElementById("images");
+    images.replaceChildren(
+      ...status.images.map((image) => {
+        const figure = document.createElement("figure");
+        const link = document.createElement("a");
+        link.href = `/image/${image.id}.png`;
+        const img = document.createElement("img");
+        img.src = link.href;
+        img.alt = image.description;
+        link.append(img);
+        figure.append(link, text("figcaption", image.description));
+        return figure;
+      }),
+    );
+    document.getElementById("none").hidden = status.images.length > 0;
+  }
+
+  const log = document.getElementById("log");
+  log.replaceChildren(...status.log.map((entry) => text("li", entry)));
+  log.scrollTop = log.scrollHeight;
+}
+
+async function poll() {
+  try {
+    const response = await fetch("/api/status");
+    renderStatus(await response.json());
+  } catch (error) {
+    const line = document.getElementById("status");
+    line.textContent = `Dashboard unreachable: ${error}`;
+    line.classList.add("error");
+  }
+}
+
+function refreshWaterfall() {
+  document.getElementById("waterfall").src = `/waterfall.png?t=${Date.now()}`;
+}
+
+poll();
+refreshWaterfall();
+setInterval(poll, STATUS_MS);
+setInterval(refreshWaterfall, WATERFALL_MS);
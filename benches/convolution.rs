@@ -0,0 +1,85 @@
+//! Время прямой и блочной (БПФ) свёртки для разных длин ядра и числа ненулевых отсчётов,
+//! а также выбор `ConvolutionBackend::Auto`. Запуск: `cargo bench --bench convolution`
+
+use sstv_processor::dsp::{self, ConvolutionBackend, SimdLevel};
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+/// Длина сигнала: 10 с при 44.1 кГц
+const SIGNAL_LEN: usize = 441_000;
+
+/// Среднее время вызова `f` (не меньше трёх прогонов и 0.3 с суммарно)
+fn time(mut f: impl FnMut()) -> Duration {
+    let started = Instant::now();
+    let mut runs = 0;
+    while runs < 3 || started.elapsed() < Duration::from_millis(300) {
+        f();
+        runs += 1;
+    }
+    started.elapsed() / runs
+}
+
+/// Ядро длиной `len` с `taps` ненулевыми отсчётами, равномерно расставленными
+fn kernel(len: usize, taps: usize) -> Vec<f32> {
+    let mut kernel = vec![0.0; len];
+    for t in 0..taps {
+        kernel[t * (len - 1) / (taps - 1).max(1)] = 1.0 / (t + 1) as f32;
+    }
+    kernel
+}
+
+/// Набор ядер из `SSTV_BENCH_SIMD` (scalar, sse2, avx2, neon), иначе найденный сам
+fn select_simd() {
+    if let Ok(name) = std::env::var("SSTV_BENCH_SIMD") {
+        let level = <SimdLevel as clap::ValueEnum>::from_str(&name, true)
+            .unwrap_or_else(|e| panic!("SSTV_BENCH_SIMD: {e}"));
+        dsp::set_simd_level(level).unwrap_or_else(|e| panic!("SSTV_BENCH_SIMD: {e}"));
+    }
+    println!("SIMD: {}", dsp::simd_level().name());
+}
+
+fn main() {
+    select_simd();
+    let signal: Vec<f32> = (0..SIGNAL_LEN)
+        .map(|i| (i as f32 * 0.07).sin() + 0.3 * (i as f32 * 0.013).cos())
+        .collect();
+
+    println!(
+        "{:>8} {:>6} {:>12} {:>12} {:>8} {:>8}",
+        "ядро", "ненул.", "прямая, мс", "БПФ, мс", "лучше", "auto"
+    );
+    let cases = [
+        (8, 8),
+        (32, 32),
+        (64, 64),
+        (129, 64),
+        (128, 128),
+        (256, 256),
+        (1024, 1024),
+        (4410, 4),
+        (4410, 32),
+        (4410, 128),
+        (4410, 512),
+        (44100, 8),
+        (44100, 64),
+    ];
+    for (len, taps) in cases {
+        let kernel = kernel(len, taps);
+        let direct = time(|| {
+            black_box(dsp::convolve_direct(black_box(&signal), &kernel, 0));
+        });
+        let fft = time(|| {
+            black_box(dsp::convolve_fft(black_box(&signal), &kernel, 0));
+        });
+        let auto = dsp::choose_backend(ConvolutionBackend::Auto, signal.len(), len, taps);
+        println!(
+            "{:>8} {:>6} {:>12.2} {:>12.2} {:>8} {:>8}",
+            len,
+            taps,
+            direct.as_secs_f64() * 1000.0,
+            fft.as_secs_f64() * 1000.0,
+            if direct < fft { "direct" } else { "fft" },
+            auto.name()
+        );
+    }
+}
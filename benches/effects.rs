@@ -0,0 +1,101 @@
+//! Время шума и ретардера на сигнале длиной с передачу Martin M1 в одном потоке и кусками
+//! в пуле rayon (`ProcessingParams::parallel`). Запуск: `cargo bench --bench effects`
+
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use sstv_processor::dsp::{self, SimdLevel};
+use sstv_processor::{
+    EnvelopeKind, NoiseParams, NoiseProcessor, RetarderParams, RetarderProcessor, SampleBuffer,
+};
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+/// Частота сигнала, Гц
+const RATE: u32 = 44100;
+/// Длина сигнала: передача Martin M1 около 114 с
+const SIGNAL_LEN: usize = 114 * RATE as usize;
+
+/// Среднее время вызова `f` (не меньше трёх прогонов и 1 с суммарно)
+fn time(mut f: impl FnMut()) -> Duration {
+    let started = Instant::now();
+    let mut runs = 0;
+    while runs < 3 || started.elapsed() < Duration::from_secs(1) {
+        f();
+        runs += 1;
+    }
+    started.elapsed() / runs
+}
+
+/// Набор ядер из `SSTV_BENCH_SIMD` (scalar, sse2, avx2, neon), иначе найденный сам
+fn select_simd() {
+    if let Ok(name) = std::env::var("SSTV_BENCH_SIMD") {
+        let level = <SimdLevel as clap::ValueEnum>::from_str(&name, true)
+            .unwrap_or_else(|e| panic!("SSTV_BENCH_SIMD: {e}"));
+        dsp::set_simd_level(level).unwrap_or_else(|e| panic!("SSTV_BENCH_SIMD: {e}"));
+    }
+    println!("SIMD: {}", dsp::simd_level().name());
+}
+
+fn main() {
+    select_simd();
+    let signal: Vec<f32> = (0..SIGNAL_LEN)
+        .map(|i| 0.5 * (i as f32 * 0.07).sin() + 0.3 * (i as f32 * 0.013).cos())
+        .collect();
+    let image = 0..SIGNAL_LEN;
+
+    println!("потоков rayon: {}", rayon::current_num_threads());
+    println!(
+        "{:>10} {:>8} {:>14} {:>14} {:>10}",
+        "эффект", "огиб.", "1 поток, мс", "rayon, мс", "ускорение"
+    );
+    let envelopes = [EnvelopeKind::Const, EnvelopeKind::Sin, EnvelopeKind::Rand];
+    for env in envelopes {
+        let noise = NoiseProcessor::new_with_params(NoiseParams {
+            level: 30,
+            env: env.clone(),
+            ..Default::default()
+        });
+        let retarder = RetarderProcessor::new_with_params(RetarderParams {
+            level: 0.3,
+            env: env.clone(),
+            repeat: 1.5,
+            ..Default::default()
+        });
+
+        let run_noise = |parallel| {
+            time(|| {
+                let mut buffer = SampleBuffer::mono(signal.clone(), RATE);
+                let mut rng = StdRng::seed_from_u64(1);
+                noise
+                    .apply_noise_with_rng(&mut buffer, &image, parallel, &mut rng)
+                    .unwrap();
+                black_box(buffer);
+            })
+        };
+        let run_retarder = |parallel| {
+            time(|| {
+                let mut buffer = SampleBuffer::mono(signal.clone(), RATE);
+                let ghost = SampleBuffer::mono(signal.clone(), RATE);
+                let mut rng = StdRng::seed_from_u64(1);
+                retarder
+                    .apply_retarder_with_rng(&mut buffer, ghost, &image, parallel, &mut rng)
+                    .unwrap();
+                black_box(buffer);
+            })
+        };
+
+        for (name, serial, parallel) in [
+            ("шум", run_noise(false), run_noise(true)),
+            ("ретардер", run_retarder(false), run_retarder(true)),
+        ] {
+            println!(
+                "{:>10} {:>8} {:>14.1} {:>14.1} {:>9.2}×",
+                name,
+                env.name(),
+                serial.as_secs_f64() * 1000.0,
+                parallel.as_secs_f64() * 1000.0,
+                serial.as_secs_f64() / parallel.as_secs_f64()
+            );
+        }
+    }
+}
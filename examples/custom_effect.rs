@@ -0,0 +1,162 @@
+//! Сторонний эффект канала: сетевой фон 50 Гц со случайной фазой. Он работает и по всему
+//! сигналу ([`ChannelEffect::apply`]), и блоками ([`BlockProcessor`]) с контрольной
+//! точкой. Пример прогоняет тестовую таблицу через процессор с этим эффектом, затем
+//! проверяет, что цепочка блоков, продолженная из контрольной точки, даёт те же сэмплы.
+//! Запуск: `cargo run --release --example custom_effect`
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use sstv_processor::error::Result;
+use sstv_processor::{
+    BlockProcessor, ChannelEffect, EffectContext, NoiseParams, SSTVProcessor, SampleBuffer,
+    StreamContext, checkpoint_rng, from_block_state, to_block_state,
+};
+use std::f64::consts::TAU;
+
+const TEST_CARD: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/examples/assets/test_card.png");
+
+/// Сетевой фон: синус `hz` с амплитудой `level` и случайной фазой
+struct Hum {
+    hz: f64,
+    level: f32,
+}
+
+impl ChannelEffect for Hum {
+    fn name(&self) -> &'static str {
+        "Hum"
+    }
+
+    fn describe(&self) -> String {
+        format!("{} Гц, {:.0} %", self.hz, self.level * 100.0)
+    }
+
+    fn apply(&self, buffer: &mut SampleBuffer, ctx: &EffectContext) -> Result<()> {
+        // Фаза из общего генератора прогона: с заданным зерном прогон воспроизводим
+        let phase = ctx.rng.borrow_mut().random::<f64>() * TAU;
+        let step = TAU * self.hz / buffer.rate as f64;
+        for (i, s) in buffer.iter_mut().enumerate() {
+            *s += self.level * (phase + step * i as f64).sin() as f32;
+        }
+        Ok(())
+    }
+
+    fn block_processor(&self, ctx: &StreamContext) -> Result<Box<dyn BlockProcessor>> {
+        let mut rng = ctx.fork_rng();
+        Ok(Box::new(HumBlocks {
+            phase: rng.random::<f64>() * TAU,
+            step: TAU * self.hz / ctx.sample_rate as f64,
+            level: self.level,
+            rng,
+        }))
+    }
+}
+
+/// Фон блоками. Генератор добавляет к фазе дрожание на каждом блоке — только чтобы
+/// показать, как состояние с генератором сохраняется в контрольной точке
+struct HumBlocks {
+    phase: f64,
+    step: f64,
+    level: f32,
+    rng: StdRng,
+}
+
+#[derive(Serialize, Deserialize)]
+struct HumState {
+    phase: f64,
+    seed: u64,
+}
+
+impl BlockProcessor for HumBlocks {
+    fn process_block(&mut self, block: &mut [f32], _start: usize) -> Result<()> {
+        self.phase += self.rng.random_range(-0.01..0.01);
+        for s in block.iter_mut() {
+            *s += self.level * self.phase.sin() as f32;
+            self.phase = (self.phase + self.step) % TAU;
+        }
+        Ok(())
+    }
+
+    fn save_state(&mut self) -> Result<serde_json::Value> {
+        to_block_state(&HumState {
+            phase: self.phase,
+            seed: checkpoint_rng(&mut self.rng),
+        })
+    }
+
+    fn restore_state(&mut self, state: &serde_json::Value) -> Result<()> {
+        let state: HumState = from_block_state(state, "Hum")?;
+        self.phase = state.phase;
+        self.rng = StdRng::seed_from_u64(state.seed);
+        Ok(())
+    }
+}
+
+/// Прогоняет `blocks` блоков случайного входа через свежую цепочку процессора,
+/// сохраняя точку после блока `checkpoint`; возвращает выход после неё и саму точку
+fn run_blocks(
+    processor: &mut SSTVProcessor,
+    input: &[f32],
+    block_len: usize,
+    checkpoint: usize,
+) -> Result<(Vec<f32>, sstv_processor::ChainState)> {
+    let mut chain = processor.block_chain(44100, input.len(), 0.3, None)?;
+    let mut state = None;
+    let mut after = Vec::new();
+    for (i, chunk) in input.chunks(block_len).enumerate() {
+        let mut block = chunk.to_vec();
+        chain.process(&mut block, i * block_len)?;
+        if i >= checkpoint {
+            after.extend_from_slice(&block);
+        }
+        if i + 1 == checkpoint {
+            state = Some(chain.save_state()?);
+        }
+    }
+    Ok((after, state.expect("точка внутри сигнала")))
+}
+
+fn main() -> anyhow::Result<()> {
+    let mut processor = SSTVProcessor::builder()
+        .effect(NoiseParams {
+            level: 15,
+            ..Default::default()
+        })
+        .custom_effect(Hum {
+            hz: 50.0,
+            level: 0.2,
+        })
+        .seed(7)
+        .build()?;
+    println!("{}", processor.describe());
+
+    let image = image::open(TEST_CARD)?;
+    let report = processor.process(&image, None)?;
+    report.image.save("custom_effect.png")?;
+    println!("{} → custom_effect.png", report.describe());
+
+    // Та же цепочка блоками: первый прогон сохраняет точку, второй продолжает из неё
+    let mut rng = StdRng::seed_from_u64(1);
+    let input: Vec<f32> = (0..44100).map(|_| rng.random_range(-0.3..0.3)).collect();
+    let (block_len, checkpoint) = (1024, 20);
+    let (original, state) = run_blocks(&mut processor, &input, block_len, checkpoint)?;
+
+    let mut resumed = processor.block_chain(44100, input.len(), 0.3, None)?;
+    resumed.restore_state(&state)?;
+    let mut replay = Vec::new();
+    for (i, chunk) in input.chunks(block_len).enumerate().skip(checkpoint) {
+        let mut block = chunk.to_vec();
+        resumed.process(&mut block, i * block_len)?;
+        replay.extend_from_slice(&block);
+    }
+    anyhow::ensure!(
+        replay == original,
+        "продолжение из точки разошлось с прогоном"
+    );
+    println!(
+        "Продолжение из точки на {} сэмпле совпало: {} сэмплов",
+        state.position,
+        replay.len()
+    );
+    Ok(())
+}
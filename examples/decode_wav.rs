@@ -0,0 +1,29 @@
+//! Декодирует запись SSTV встроенным декодером и печатает отчёт: строки, синхронизацию,
+//! наклон, С/Ш и VIS. Запуск: `cargo run --example decode_wav [-- ЗАПИСЬ.wav [ВЫХОД.png]]`;
+//! по умолчанию берётся `sstv.wav` из примера `encode_to_wav`
+
+use sstv_processor::{DecoderBackend, DecoderParams, Signal, messages, signal};
+
+fn main() -> anyhow::Result<()> {
+    let mut args = std::env::args().skip(1);
+    let input = args.next().unwrap_or_else(|| "sstv.wav".to_string());
+    let output = args.next().unwrap_or_else(|| "decoded.png".to_string());
+
+    // Любая частота и стерео: сигнал сводится в моно и передискретизируется при декодировании
+    let signal = Signal::from_wav(&input)?;
+    let params = DecoderParams {
+        backend: DecoderBackend::Native,
+        afc: true,
+        ..Default::default()
+    };
+    let report = signal::decode(&signal, &params)?;
+    report.image.save(&output)?;
+
+    println!("{}: {}", input, report.describe());
+    match &report.vis_detected {
+        Some(vis) => println!("{}", vis.describe()),
+        None => println!("{}", messages::cli_vis_missing()),
+    }
+    println!("→ {}", output);
+    Ok(())
+}
@@ -0,0 +1,34 @@
+//! Кодирует тестовую таблицу в Martin M1 и пишет сигнал в WAV без эффектов канала.
+//! Запуск: `cargo run --example encode_to_wav [-- ВЫХОД.wav [КАРТИНКА]]`,
+//! по умолчанию `sstv.wav` из `examples/assets/test_card.png`
+
+use sstv_processor::{EncoderBackend, EncoderParams, MARTIN_M1, Signal, signal};
+
+const TEST_CARD: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/examples/assets/test_card.png");
+
+fn main() -> anyhow::Result<()> {
+    let mut args = std::env::args().skip(1);
+    let output = args.next().unwrap_or_else(|| "sstv.wav".to_string());
+    let input = args.next().unwrap_or_else(|| TEST_CARD.to_string());
+
+    let image = image::open(&input)?;
+    // Встроенный кодер пишет калибровочный заголовок с кодом VIS режима
+    let params = EncoderParams {
+        backend: EncoderBackend::Native,
+        ..Default::default()
+    };
+    let signal: Signal = signal::encode(&image, &params);
+    signal.write_wav(&output)?;
+
+    let span = signal.image_span();
+    println!(
+        "{} → {}: {:.1} с на {} Гц, VIS {}, тело изображения с {:.2} с",
+        input,
+        output,
+        signal.duration_secs(),
+        signal.sample_rate(),
+        MARTIN_M1.vis_code,
+        span.start as f64 / signal.sample_rate() as f64
+    );
+    Ok(())
+}
@@ -0,0 +1,76 @@
+//! Непрерывный приём без окна: звук с устройства ввода или сырой PCM идёт в
+//! [`Monitor`], раз в секунду печатается состояние, принятые изображения сохраняются
+//! в `received_N.png` с отметкой времени приёма в текстовых блоках.
+//! Запуск: `cargo run --release --example live_listen [-- СЕКУНДЫ [УСТРОЙСТВО]]` или
+//! `… -- СЕКУНДЫ --pcm ФАЙЛ ЧАСТОТА` для 16-битного моно PCM (`-` — стандартный ввод):
+//!
+//! ```bash
+//! arecord -f S16_LE -r 48000 -c 1 | cargo run --example live_listen -- 300 --pcm - 48000
+//! ```
+
+use image::DynamicImage;
+use sstv_processor::{Monitor, MonitorParams, MonitorSource, PcmFormat, metadata};
+use std::time::{Duration, Instant};
+
+fn main() -> anyhow::Result<()> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let secs: u64 = args.first().map_or(Ok(120), |s| s.parse())?;
+    let source = match args.get(1).map(String::as_str) {
+        Some("--pcm") => MonitorSource::Pcm {
+            path: args.get(2).map_or("-", String::as_str).into(),
+            sample_rate: args.get(3).map_or(Ok(48000), |s| s.parse())?,
+            format: PcmFormat::S16Le,
+        },
+        device => MonitorSource::Device(device.map(str::to_string)),
+    };
+
+    let monitor = Monitor::start(
+        MonitorParams {
+            source,
+            ..Default::default()
+        },
+        || {},
+    );
+    let deadline = Instant::now() + Duration::from_secs(secs);
+    let mut saved = 0;
+    while monitor.is_running() && Instant::now() < deadline {
+        std::thread::sleep(Duration::from_secs(1));
+        save_received(&monitor, &mut saved)?;
+        let state = monitor.state();
+        let status = match &state.image {
+            Some(image) => format!("строка {}/{}", state.lines, image.height()),
+            None => "ждём заголовок".to_string(),
+        };
+        println!(
+            "{:>6.1} с  {:>6.1} дБFS  {}",
+            state.elapsed_secs, state.level_db, status
+        );
+    }
+
+    monitor.stop();
+    // Конец PCM закрывает последнее изображение уже после выхода из цикла
+    save_received(&monitor, &mut saved)?;
+    if let Some(error) = &monitor.state().error {
+        anyhow::bail!("{error}");
+    }
+    Ok(())
+}
+
+/// Сохраняет законченные изображения в `received_N.png`, продолжая нумерацию `saved`
+fn save_received(monitor: &Monitor, saved: &mut usize) -> anyhow::Result<()> {
+    for received in monitor.take_received() {
+        *saved += 1;
+        let path = format!("received_{saved}.png");
+        let image = DynamicImage::ImageRgb8(received.image.clone());
+        std::fs::write(
+            &path,
+            metadata::png_with_text(&image, &received.text_fields())?,
+        )?;
+        println!(
+            "→ {path}: {} строк, {}",
+            received.lines,
+            received.stamp.describe()
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,68 @@
+//! Перебор уровня шума: тестовая таблица кодируется один раз, копии сигнала портятся
+//! шумом от 0 до 100 % параллельно в пуле rayon с пониженным приоритетом, и для каждого
+//! уровня печатаются оценка С/Ш декодера и PSNR результата.
+//! Запуск: `cargo run --release --example sweep_snr [-- ШАГ]`, шаг по умолчанию 10 %
+
+use rayon::prelude::*;
+use sstv_processor::{
+    DecoderBackend, DecoderParams, EncoderParams, NoiseParams, ProcessingParams, ThreadParams,
+    ThreadPriority, metrics, signal, threads,
+};
+
+const TEST_CARD: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/examples/assets/test_card.png");
+
+fn main() -> anyhow::Result<()> {
+    let step: usize = match std::env::args().nth(1) {
+        Some(step) => step.parse()?,
+        None => 10,
+    };
+    // Перебор не должен отнимать ядра у звука и окна на той же машине
+    threads::configure(ThreadParams {
+        threads: 0,
+        priority: ThreadPriority::Low,
+    })?;
+
+    let image = image::open(TEST_CARD)?;
+    let clean = signal::encode(&image, &EncoderParams::default());
+    let decoder = DecoderParams {
+        backend: DecoderBackend::Native,
+        ..Default::default()
+    };
+
+    let levels: Vec<u8> = (0..=100).step_by(step.max(1)).map(|l| l as u8).collect();
+    let rows = levels
+        .par_iter()
+        .map(|&level| -> anyhow::Result<String> {
+            let params = ProcessingParams {
+                noise: NoiseParams {
+                    level,
+                    ..Default::default()
+                },
+                seed: Some(7),
+                ..Default::default()
+            };
+            let mut noisy = clean.clone();
+            noisy.degrade(&params, None)?;
+            let report = signal::decode(&noisy, &decoder)?;
+            let quality = metrics::compare(&image, &report.image);
+            Ok(format!(
+                "{:>5} {:>10} {:>10.2} {:>8}",
+                level,
+                report
+                    .snr_estimate
+                    .map_or("—".to_string(), |snr| format!("{snr:.1}")),
+                quality.psnr,
+                report.lines_decoded
+            ))
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    println!(
+        "{:>5} {:>10} {:>10} {:>8}",
+        "шум %", "С/Ш, дБ", "PSNR, дБ", "строк"
+    );
+    for row in rows {
+        println!("{}", row);
+    }
+    Ok(())
+}
@@ -0,0 +1,57 @@
+/* C-интерфейс sstv_processor (сборка с --features ffi), см. src/ffi.rs.
+ * Функции возвращают 0 при успехе, SSTV_INVALID_ARGUMENT при неверных аргументах
+ * и SSTV_ERROR при прочих ошибках, текст ошибки — sstv_last_error().
+ * params_json — JSON ProcessingParams; пропущенные поля по умолчанию, NULL — все. */
+#ifndef SSTV_PROCESSOR_H
+#define SSTV_PROCESSOR_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Обработка не удалась */
+#define SSTV_ERROR (-1)
+/* NULL вместо входа, тело изображения за пределами сэмплов или неверный params_json */
+#define SSTV_INVALID_ARGUMENT (-2)
+
+/* PNG результата; освобождается sstv_bytes_free */
+typedef struct SstvBytes {
+    uint8_t *data;
+    size_t len;
+} SstvBytes;
+
+/* Моно-сигнал −1…1; image_start..image_end — тело изображения в сэмплах.
+ * Выход библиотеки освобождается sstv_samples_free */
+typedef struct SstvSamples {
+    float *data;
+    size_t len;
+    uint32_t rate;
+    size_t image_start;
+    size_t image_end;
+} SstvSamples;
+
+const char *sstv_last_error(void);
+
+/* Кодирование, эффекты и декодирование за один вызов: изображение → PNG */
+int sstv_process(const uint8_t *image, size_t image_len, const char *params_json, SstvBytes *out);
+
+/* Изображение (PNG, JPEG, ...) → чистый сигнал SSTV */
+int sstv_encode(const uint8_t *image, size_t image_len, const char *params_json, SstvSamples *out);
+
+/* Сигнал → сигнал после эффектов канала; вход не меняется */
+int sstv_apply_effects(const SstvSamples *input, const char *params_json, SstvSamples *out);
+
+/* Сигнал с любой частотой дискретизации → PNG */
+int sstv_decode(const SstvSamples *input, const char *params_json, SstvBytes *out);
+
+void sstv_bytes_free(SstvBytes *bytes);
+void sstv_samples_free(SstvSamples *samples);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
@@ -0,0 +1,304 @@
+use crate::decoder::{DecoderBackend, create_decoder};
+use crate::encoder::{self, EncoderBackend};
+use crate::error::Result;
+use crate::messages;
+use crate::metrics::{self, QualityReport};
+use crate::modes::{MARTIN_M1, SYNC_HZ, WHITE_HZ};
+use crate::native_decoder::CLOCK_SEARCH_RATIO;
+use crate::noise::NoiseKind;
+use crate::processor::ProcessingParams;
+use crate::retarder::RetarderSource;
+use crate::signal::{self, Signal};
+use image::DynamicImage;
+use rsstv::SAMPLE_RATE;
+
+/// Строк изображения в пробном прогоне
+pub const PROBE_LINES: u32 = 24;
+/// Уровень гауссова шума, выше которого декодер rsstv теряет изображение
+const RSSTV_NOISE_LIMIT: u8 = 70;
+/// То же для встроенного декодера
+const NATIVE_NOISE_LIMIT: u8 = 85;
+/// Задержка призрака, строк, после которой он уходит далеко от своей строки
+const GHOST_DELAY_LINES: f64 = 20.0;
+/// Расстройка, Гц, заметно сдвигающая яркость без АПЧ (800 Гц — вся шкала)
+const BRIGHTNESS_SHIFT_HZ: f32 = 25.0;
+/// Глубина замираний, при которой строки в провалах теряются
+const DEEP_FADING: f32 = 0.9;
+/// Частота дискретизации, ниже которой гармоники ограниченного сигнала rsstv (его размах
+/// больше полной шкалы, шум и призрак обрезают его до ±1) заворачиваются в полосу SSTV
+const CLIP_ALIAS_RATE: u32 = 22050;
+/// SSIM пробы, ниже которого изображение будет сильно искажено. По PSNR не судим:
+/// rsstv и без помех сдвигает цвета, и PSNR чистого прогона около 9 дБ
+const POOR_SSIM: f64 = 0.2;
+
+/// Уровень гауссова шума, выше которого декодер `backend` теряет изображение
+pub fn noise_limit(backend: DecoderBackend) -> u8 {
+    match backend {
+        DecoderBackend::Rsstv => RSSTV_NOISE_LIMIT,
+        DecoderBackend::Native => NATIVE_NOISE_LIMIT,
+    }
+}
+
+/// Важность совета
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Info,
+    Warning,
+    Fatal,
+}
+
+impl Severity {
+    pub const ALL: &'static [Severity] = &[Severity::Info, Severity::Warning, Severity::Fatal];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Severity::Info => messages::severity_info(),
+            Severity::Warning => messages::severity_warning(),
+            Severity::Fatal => messages::severity_fatal(),
+        }
+    }
+}
+
+/// Совет по изменению параметров
+#[derive(Clone, Debug, PartialEq)]
+pub struct Suggestion {
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl std::fmt::Display for Suggestion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.severity.name(), self.message)
+    }
+}
+
+/// Советы по параметрам, от самых важных к менее важным
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Suggestions {
+    items: Vec<Suggestion>,
+}
+
+impl Suggestions {
+    fn push(&mut self, severity: Severity, message: String) {
+        self.items.push(Suggestion { severity, message });
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, Suggestion> {
+        self.items.iter()
+    }
+
+    /// Проба не декодировала изображение: полная обработка, скорее всего, тоже не удастся
+    pub fn will_fail(&self) -> bool {
+        self.items.iter().any(|s| s.severity == Severity::Fatal)
+    }
+}
+
+/// Итог пробного прогона по первым [`PROBE_LINES`] строкам
+#[derive(Clone, Debug, Default)]
+pub struct ProbeResult {
+    /// Строк, декодированных пробой
+    pub lines: usize,
+    /// Средняя уверенность синхронизации, если декодер её даёт
+    pub confidence: Option<f32>,
+    /// Качество декодированных строк относительно исходных
+    pub quality: Option<QualityReport>,
+    /// Ошибка декодера, если проба не нашла изображение
+    pub error: Option<String>,
+    /// Задан ли призрак: картинка или другой источник, см. [`RetarderSource`]
+    pub retarder: bool,
+}
+
+/// Быстрый пробный прогон: кодирует изображение теми же кодером и эффектами, но отдаёт
+/// декодеру только первые [`PROBE_LINES`] строк. Огибающие эффектов укладываются в
+/// укороченный сигнал, поэтому проба сжимает их по времени. Ошибка возвращается только
+/// для неверных параметров эффектов; сбой декодера записывается в результат
+pub fn probe(
+    params: &ProcessingParams,
+    main_image: &DynamicImage,
+    retarder_image: Option<&DynamicImage>,
+) -> Result<ProbeResult> {
+    let main_image = params.overlay_params().apply(main_image)?;
+    let main_image = main_image.as_ref();
+    let mut signal = signal::encode(main_image, &params.encoder);
+    let timing = encoder::timing(&params.encoder, signal.sample_rate(), signal.len());
+    // Одна строка сверх пробы, чтобы декодер дочитал последнюю
+    let len = timing
+        .line_to_sample_range(PROBE_LINES)
+        .end
+        .min(signal.len());
+    let truncate = |signal: Signal| {
+        let span = signal.image_span();
+        let mut samples = signal.into_samples();
+        samples.truncate(len);
+        Signal::with_image_span(samples, SAMPLE_RATE as u32, span)
+    };
+    signal = truncate(signal);
+    // Отражённую или негативную картинку призрака строит источник; эхо и WAV подставляет
+    // `degrade`
+    let source = &params.retarder.source;
+    let derived = source.image_from(main_image);
+    let retarder_image = match source {
+        RetarderSource::Image => retarder_image,
+        _ => derived.as_ref(),
+    };
+    let retarder = retarder_image.map(|image| truncate(signal::encode(image, &params.encoder)));
+    signal.degrade(params, retarder.as_ref())?;
+    // Декодер работает на частоте кодера
+    let signal = signal.resampled_with(SAMPLE_RATE as u32, params.quality);
+    let samples = signal.samples();
+
+    let mut result = ProbeResult {
+        retarder: retarder_image.is_some() || *source != RetarderSource::Image,
+        ..Default::default()
+    };
+    let mut decoder = create_decoder(&params.decoder);
+    match decoder.decode(samples) {
+        Ok(decoded) => {
+            let confidence = decoder.line_confidence().filter(|c| !c.is_empty());
+            result.lines = confidence.map_or(PROBE_LINES as usize, |c| c.len());
+            result.confidence = confidence.map(|c| c.iter().sum::<f32>() / c.len() as f32);
+
+            let lines = (result.lines as u32).min(PROBE_LINES).min(decoded.height());
+            if lines > 0 {
+                let original = encoder::fit_to_mode(main_image, &MARTIN_M1);
+                result.quality = Some(metrics::compare_in(
+                    &original.crop_imm(0, 0, original.width(), lines),
+                    &decoded.crop_imm(0, 0, decoded.width(), lines),
+                    params.metric_space,
+                ));
+            }
+        }
+        Err(e) => result.error = Some(format!("{:#}", e)),
+    }
+    Ok(result)
+}
+
+/// Советы по параметрам на основе самих параметров и пробного прогона
+pub fn recommend(params: &ProcessingParams, probe: &ProbeResult) -> Suggestions {
+    let mut suggestions = Suggestions::default();
+    let failed = probe.error.is_some();
+    // Причина сбоя пробы ставится выше общих предупреждений
+    let cause = if failed {
+        Severity::Fatal
+    } else {
+        Severity::Warning
+    };
+
+    if let Some(error) = &probe.error {
+        suggestions.push(Severity::Fatal, messages::advice_probe_failed(error));
+    }
+
+    let noise_limit = noise_limit(params.decoder.backend);
+    if params.noise.kind == NoiseKind::Gaussian && params.noise.level > noise_limit {
+        let outcome = if failed {
+            messages::advice_noise_will_fail()
+        } else {
+            messages::advice_noise_may_fail()
+        };
+        suggestions.push(
+            cause,
+            messages::advice_noise(
+                params.noise.level,
+                noise_limit,
+                params.decoder.backend.name(),
+                outcome,
+            ),
+        );
+    }
+
+    let line_ms = MARTIN_M1.line_us() / 1000.0;
+    let ghost = probe.retarder && params.retarder.level > 0.0;
+    let delay_lines = params.retarder.delay_ms as f64 / line_ms;
+    if ghost && delay_lines > GHOST_DELAY_LINES {
+        suggestions.push(
+            Severity::Warning,
+            messages::advice_ghost_delay(params.retarder.delay_ms, GHOST_DELAY_LINES, delay_lines),
+        );
+    }
+
+    let clipped = params.noise.level > 0 || ghost;
+    if params.encoder.backend == EncoderBackend::Rsstv
+        && params.sample_rate < CLIP_ALIAS_RATE
+        && clipped
+    {
+        suggestions.push(
+            cause,
+            messages::advice_clip_alias(params.sample_rate, CLIP_ALIAS_RATE),
+        );
+    }
+
+    let ssb = &params.transceiver;
+    if ssb.enabled && (ssb.low_hz > SYNC_HZ || ssb.high_hz < WHITE_HZ) {
+        suggestions.push(
+            cause,
+            messages::advice_passband(ssb.low_hz, ssb.high_hz, SYNC_HZ, WHITE_HZ),
+        );
+    }
+
+    // Наибольшая расстройка за время передачи
+    let drift = &params.drift;
+    let duration_s = (MARTIN_M1.height as f64 * line_ms / 1000.0) as f32;
+    let worst_hz = drift
+        .offset_hz
+        .abs()
+        .max((drift.offset_hz + drift.drift_hz_per_s * duration_s).abs())
+        + drift.wobble_hz.abs();
+    let decoder = &params.decoder;
+    if worst_hz > decoder.max_freq_deviation_hz {
+        suggestions.push(
+            cause,
+            messages::advice_deviation(worst_hz, decoder.max_freq_deviation_hz),
+        );
+    }
+    if worst_hz > BRIGHTNESS_SHIFT_HZ && !decoder.afc {
+        suggestions.push(Severity::Info, messages::advice_afc(worst_hz));
+    }
+    if decoder.afc
+        && decoder.backend == DecoderBackend::Rsstv
+        && (drift.drift_hz_per_s != 0.0 || drift.wobble_hz != 0.0)
+    {
+        suggestions.push(Severity::Info, messages::advice_afc_rsstv().to_string());
+    }
+
+    let timing_error = (params.encoder.timing_scale - 1.0).abs();
+    let slant_fixed = decoder.deskew || decoder.slant_ppm.is_some();
+    if decoder.backend == DecoderBackend::Native
+        && timing_error > CLOCK_SEARCH_RATIO
+        && !slant_fixed
+    {
+        suggestions.push(
+            cause,
+            messages::advice_slant(timing_error * 100.0, CLOCK_SEARCH_RATIO * 100.0),
+        );
+    }
+    if decoder.backend == DecoderBackend::Rsstv && slant_fixed {
+        suggestions.push(Severity::Info, messages::advice_slant_rsstv().to_string());
+    }
+
+    if params.fading.depth >= DEEP_FADING && !decoder.conceal {
+        suggestions.push(Severity::Info, messages::advice_fading(params.fading.depth));
+    }
+
+    if let Some(confidence) = probe.confidence
+        && confidence < decoder.sync_threshold
+    {
+        suggestions.push(
+            Severity::Warning,
+            messages::advice_sync(confidence * 100.0, decoder.sync_threshold * 100.0),
+        );
+    }
+    if let Some(quality) = &probe.quality
+        && quality.ssim < POOR_SSIM
+    {
+        suggestions.push(Severity::Warning, messages::advice_ssim(quality.ssim));
+    }
+
+    suggestions
+        .items
+        .sort_by_key(|s| std::cmp::Reverse(s.severity));
+    suggestions
+}
@@ -0,0 +1,1264 @@
+use crate::blanker::{BlankerFill, BlankerParams};
+use crate::deadline::{self, StageLimit, TimeLimits};
+use crate::decoder::{DecoderBackend, DecoderParams};
+use crate::drift::DriftParams;
+use crate::dropout::{DropoutKind, DropoutParams, DropoutWindow};
+use crate::dsp::ConvolutionBackend;
+use crate::dsp::Sideband;
+use crate::effect::{EffectMix, MixSetting, MixTarget, SanitizeMode};
+use crate::encoder::{EncoderBackend, EncoderParams};
+use crate::envelope::{AdsrEnvelope, BurstEnvelope, CustomEnvelope, EnvelopeAnchor, EnvelopeKind};
+use crate::error::{Result, SstvError};
+use crate::fading::{FadingModel, FadingParams};
+use crate::filters::{AgcParams, FilterKind, FilterParams, MAX_FILTER_STAGES, NotchParams};
+use crate::interference::{Carrier, InterferenceParams, QrmTransmission};
+use crate::loudness::{LoudnessMeasure, LoudnessParams};
+use crate::messages::{self, help};
+use crate::metadata::{MetaField, Metadata};
+use crate::metrics::MetricSpace;
+use crate::multipath::{MultipathParams, MultipathTap};
+use crate::noise::{AmplitudeDistribution, ImpulseParams, NoiseColor, NoiseKind, NoiseParams};
+use crate::overlay::{OverlayColor, OverlayParams};
+use crate::palette::Palette;
+use crate::processor::{MIN_SAMPLE_RATE, ProcessingParams, SSTVProcessor};
+use crate::quality::QualityTier;
+use crate::retarder::{RetarderParams, RetarderSource};
+use crate::scan_converter::{Phosphor, ScanConverterParams};
+use crate::station_id::{IdMode, IdParams};
+use crate::telemetry::{Telemetry, TelemetryStrip};
+use crate::timeline::{EffectEvent, Timeline};
+use crate::transceiver::{TransceiverOutput, TransceiverParams};
+use clap::parser::ValueSource;
+use clap::{ArgMatches, Parser};
+use image::{DynamicImage, ImageReader};
+use rsstv::SAMPLE_RATE;
+use std::time::Duration;
+
+/// Параметры обработки в том виде, в каком их задаёт пользователь: флаги CLI и поля GUI.
+/// Оба интерфейса строят и читают эту структуру, поэтому новый параметр появляется в обоих
+#[derive(clap::Args, Clone, Debug)]
+pub struct AppParams {
+    // ── Пресет ───────────────────────────────────────────────
+    #[arg(long, visible_alias = "params", value_name = "FILE", help = help::preset())]
+    pub preset: Option<String>,
+
+    // ── Шум ──────────────────────────────────────────────────
+    #[arg(short = 'n', long, default_value_t = 0u8, help = help::noise(),
+          value_parser = clap::value_parser!(u8).range(0..=100))]
+    pub noise: u8,
+
+    #[arg(long, default_value = "gaussian", help = help::noise_kind())]
+    pub noise_kind: NoiseKind,
+
+    #[arg(
+        long,
+        default_value = "white",
+        value_name = "white|pink|brown|band:LOW:HIGH",
+        help = help::noise_color()
+    )]
+    pub noise_color: NoiseColor,
+
+    #[arg(long, default_value_t = 2.0, help = help::impulse_rate())]
+    pub impulse_rate: f32,
+
+    #[arg(long, default_value_t = 5.0, help = help::impulse_ms())]
+    pub impulse_ms: f32,
+
+    #[arg(long, default_value = "exp", help = help::impulse_dist())]
+    pub impulse_dist: AmplitudeDistribution,
+
+    #[arg(long, default_value = "const", help = help::noise_env())]
+    pub noise_env: EnvelopeKind,
+
+    #[arg(long, value_name = "FILE", help = help::noise_env_file())]
+    pub noise_env_file: Option<String>,
+
+    #[arg(long, value_name = "A:D:S:R", conflicts_with = "noise_burst", help = help::noise_adsr())]
+    pub noise_adsr: Option<AdsrEnvelope>,
+
+    #[arg(long, value_name = "ON:PERIOD[:JITTER[:RAMP]]", help = help::noise_burst())]
+    pub noise_burst: Option<BurstEnvelope>,
+
+    #[arg(long, default_value_t = 1.0, help = help::noise_repeat())]
+    pub noise_repeat: f32,
+
+    #[arg(long, default_value = "audio", help = help::noise_anchor())]
+    pub noise_anchor: EnvelopeAnchor,
+
+    // ── Ретардер ─────────────────────────────────────────────
+    #[arg(short = 'r', long, help = help::retarder())]
+    pub retarder: Option<String>,
+
+    #[arg(long, default_value_t = 0.3, help = help::level())]
+    pub level: f32,
+
+    #[arg(long, default_value = "const", help = help::ret_env())]
+    pub ret_env: EnvelopeKind,
+
+    #[arg(long, value_name = "FILE", help = help::ret_env_file())]
+    pub ret_env_file: Option<String>,
+
+    #[arg(long, value_name = "A:D:S:R", conflicts_with = "ret_burst", help = help::ret_adsr())]
+    pub ret_adsr: Option<AdsrEnvelope>,
+
+    #[arg(long, value_name = "ON:PERIOD[:JITTER[:RAMP]]", help = help::ret_burst())]
+    pub ret_burst: Option<BurstEnvelope>,
+
+    #[arg(long, default_value_t = 1.0, help = help::ret_repeat())]
+    pub ret_repeat: f32,
+
+    #[arg(long, default_value = "audio", help = help::ret_anchor())]
+    pub ret_anchor: EnvelopeAnchor,
+
+    #[arg(long, default_value_t = 0u32, help = help::delay_ms())]
+    pub delay_ms: u32,
+
+    #[arg(
+        long,
+        default_value = "image",
+        value_name = "image|self|flip|invert|WAV",
+        help = help::ret_source()
+    )]
+    pub ret_source: RetarderSource,
+
+    // ── Многолучёвость ───────────────────────────────────────
+    #[arg(long = "tap", value_name = "DELAY:GAIN[:PHASE]", help = help::tap())]
+    pub taps: Vec<MultipathTap>,
+
+    // ── Замирания ────────────────────────────────────────────
+    #[arg(long, default_value_t = 0.0, help = help::fade_depth())]
+    pub fade_depth: f32,
+
+    #[arg(long, default_value_t = 0.2, help = help::fade_rate())]
+    pub fade_rate: f32,
+
+    #[arg(long, default_value = "rayleigh", help = help::fade_model())]
+    pub fade_model: FadingModel,
+
+    // ── Уход частоты ─────────────────────────────────────────
+    #[arg(long, default_value_t = 0.0, allow_hyphen_values = true, help = help::offset_hz())]
+    pub offset_hz: f32,
+
+    #[arg(long, default_value_t = 0.0, allow_hyphen_values = true, help = help::drift_hz_per_s())]
+    pub drift_hz_per_s: f32,
+
+    #[arg(long, default_value_t = 0.0, help = help::wobble_hz())]
+    pub wobble_hz: f32,
+
+    #[arg(long, default_value_t = 0.5, help = help::wobble_rate())]
+    pub wobble_rate: f32,
+
+    // ── Помехи ───────────────────────────────────────────────
+    #[arg(long = "carrier", value_name = "HZ:LEVEL[:DUTY[:PERIOD_MS]]", help = help::carrier())]
+    pub carriers: Vec<Carrier>,
+
+    #[arg(long, value_name = "FILE", help = help::qrm())]
+    pub qrm: Option<String>,
+
+    #[arg(long, default_value_t = 0.5, help = help::qrm_level())]
+    pub qrm_level: f32,
+
+    #[arg(long, default_value_t = 0.0, allow_hyphen_values = true, help = help::qrm_offset_hz())]
+    pub qrm_offset_hz: f32,
+
+    #[arg(long, default_value_t = 0.0, help = help::qrm_delay_ms())]
+    pub qrm_delay_ms: f32,
+
+    // ── Трансивер SSB ────────────────────────────────────────
+    #[arg(long, help = help::ssb())]
+    pub ssb: bool,
+
+    #[arg(long, default_value_t = 300.0, help = help::ssb_low_hz())]
+    pub ssb_low_hz: f32,
+
+    #[arg(long, default_value_t = 2700.0, help = help::ssb_high_hz())]
+    pub ssb_high_hz: f32,
+
+    #[arg(long, default_value_t = 6.0, allow_hyphen_values = true, help = help::ssb_drive_db())]
+    pub ssb_drive_db: f32,
+
+    #[arg(long, default_value_t = 100.0, help = help::ssb_alc_ms())]
+    pub ssb_alc_ms: f32,
+
+    #[arg(long, default_value_t = 0.0, help = help::ssb_carrier())]
+    pub ssb_carrier: f32,
+
+    #[arg(long, default_value_t = 0.0, allow_hyphen_values = true, help = help::ssb_iq_gain_db())]
+    pub ssb_iq_gain_db: f32,
+
+    #[arg(long, default_value_t = 0.0, allow_hyphen_values = true, help = help::ssb_iq_phase_deg())]
+    pub ssb_iq_phase_deg: f32,
+
+    #[arg(long, default_value = "usb", help = help::ssb_sideband())]
+    pub ssb_sideband: Sideband,
+
+    #[arg(long, default_value = "mono", help = help::ssb_output())]
+    pub ssb_output: TransceiverOutput,
+
+    // ── Подавитель помех ─────────────────────────────────────
+    #[arg(long, help = help::blanker())]
+    pub blanker: bool,
+
+    #[arg(long, default_value_t = 4.0, help = help::blanker_threshold())]
+    pub blanker_threshold: f32,
+
+    #[arg(long, default_value_t = 0.2, help = help::blanker_hold_ms())]
+    pub blanker_hold_ms: f32,
+
+    #[arg(long, default_value_t = 20.0, help = help::blanker_average_ms())]
+    pub blanker_average_ms: f32,
+
+    #[arg(long, default_value = "repeat", help = help::blanker_fill())]
+    pub blanker_fill: BlankerFill,
+
+    // ── Фильтры и АРУ приёмника ──────────────────────────────
+    #[arg(long, help = help::filter())]
+    pub filter: bool,
+
+    #[arg(long, default_value = "bandpass", help = help::filter_kind())]
+    pub filter_kind: FilterKind,
+
+    #[arg(long, default_value_t = 1900.0, help = help::filter_hz())]
+    pub filter_hz: f32,
+
+    #[arg(long, default_value_t = 2.0, help = help::filter_q())]
+    pub filter_q: f32,
+
+    #[arg(long, default_value_t = 2, help = help::filter_stages(),
+          value_parser = clap::value_parser!(u32).range(1..=MAX_FILTER_STAGES as i64))]
+    pub filter_stages: u32,
+
+    #[arg(long, help = help::notch())]
+    pub notch: bool,
+
+    #[arg(long, default_value_t = 1900.0, help = help::notch_hz())]
+    pub notch_hz: f32,
+
+    #[arg(long, default_value_t = 10.0, help = help::notch_q())]
+    pub notch_q: f32,
+
+    #[arg(long, help = help::agc())]
+    pub agc: bool,
+
+    #[arg(long, default_value_t = 0.5, help = help::agc_target())]
+    pub agc_target: f32,
+
+    #[arg(long, default_value_t = 5.0, help = help::agc_attack_ms())]
+    pub agc_attack_ms: f32,
+
+    #[arg(long, default_value_t = 300.0, help = help::agc_release_ms())]
+    pub agc_release_ms: f32,
+
+    #[arg(long, default_value_t = 30.0, help = help::agc_max_gain_db())]
+    pub agc_max_gain_db: f32,
+
+    // ── Выпадения ────────────────────────────────────────────
+    #[arg(
+        long = "dropout",
+        value_name = "START_MS:DURATION_MS",
+        allow_hyphen_values = true,
+        help = help::dropout()
+    )]
+    pub dropouts: Vec<DropoutWindow>,
+
+    #[arg(long, default_value_t = 0.0, help = help::dropout_rate())]
+    pub dropout_rate: f32,
+
+    #[arg(long, default_value_t = 1000.0, help = help::dropout_ms())]
+    pub dropout_ms: f32,
+
+    #[arg(long, default_value = "zero", help = help::dropout_kind())]
+    pub dropout_kind: DropoutKind,
+
+    #[arg(long, default_value_t = 40.0, help = help::dropout_db())]
+    pub dropout_db: f32,
+
+    // ── События ──────────────────────────────────────────────
+    #[arg(long, value_name = "FILE", help = help::timeline())]
+    pub timeline: Option<String>,
+
+    /// События из пресета; сценарий --timeline их заменяет
+    #[arg(skip)]
+    pub events: Vec<EffectEvent>,
+
+    // ── Воспроизводимость ────────────────────────────────────
+    #[arg(long, help = help::seed())]
+    pub seed: Option<u64>,
+
+    // ── Выходной сигнал ──────────────────────────────────────
+    #[arg(long, default_value_t = SAMPLE_RATE as u32, help = help::sample_rate(),
+          value_parser = clap::value_parser!(u32).range(MIN_SAMPLE_RATE as i64..=384_000))]
+    pub sample_rate: u32,
+
+    #[arg(long, default_value_t = 0.0, allow_hyphen_values = true, help = help::output_gain_db())]
+    pub output_gain_db: f32,
+
+    #[arg(long, default_value = "off", help = help::normalize())]
+    pub normalize: LoudnessMeasure,
+
+    #[arg(long, default_value_t = -16.0, allow_hyphen_values = true, help = help::normalize_target())]
+    pub normalize_target: f32,
+
+    #[arg(long, default_value_t = -1.0, allow_hyphen_values = true, help = help::true_peak_db())]
+    pub true_peak_db: f32,
+
+    #[arg(long = "mix", value_name = "EFFECT:WET[:DRY]", help = help::mix())]
+    pub mix: Vec<MixSetting>,
+
+    // ── Производительность ───────────────────────────────────
+    #[arg(long, default_value = "auto", help = help::convolution())]
+    pub convolution: ConvolutionBackend,
+
+    #[arg(long, help = help::parallel())]
+    pub parallel: bool,
+
+    #[arg(long, default_value = "normal", help = help::quality())]
+    pub quality: QualityTier,
+
+    #[arg(long, default_value = "off", help = help::sanitize())]
+    pub sanitize: SanitizeMode,
+
+    #[arg(long, default_value = "srgb", help = help::metric_space())]
+    pub metric_space: MetricSpace,
+
+    // ── Телеметрия ───────────────────────────────────────────
+    #[arg(
+        long,
+        value_name = "TEMP,LAT,LON,ALT",
+        allow_hyphen_values = true,
+        help = help::telemetry()
+    )]
+    pub telemetry: Option<Telemetry>,
+
+    // ── Метаданные ───────────────────────────────────────────
+    #[arg(long = "meta", value_name = "KEY=VALUE", help = help::meta())]
+    pub meta: Vec<MetaField>,
+
+    #[arg(long, help = help::meta_overlay())]
+    pub meta_overlay: bool,
+
+    #[arg(long, help = help::meta_fsk())]
+    pub meta_fsk: bool,
+
+    // ── Пределы времени ──────────────────────────────────────
+    #[arg(long, value_name = "SECS", value_parser = parse_timeout, help = help::timeout())]
+    pub timeout: Option<Duration>,
+
+    #[arg(long = "stage-timeout", value_name = "STAGE:SECS", help = help::stage_timeout())]
+    pub stage_timeouts: Vec<StageLimit>,
+
+    #[command(flatten)]
+    pub encoder: EncoderArgs,
+
+    #[command(flatten)]
+    pub overlay: OverlayArgs,
+
+    #[command(flatten)]
+    pub decoder: DecoderArgs,
+}
+
+impl Default for AppParams {
+    /// Значения по умолчанию берутся из описания флагов, поэтому совпадают с CLI
+    fn default() -> Self {
+        #[derive(Parser)]
+        struct Defaults {
+            #[command(flatten)]
+            params: AppParams,
+        }
+        Defaults::parse_from(["defaults"]).params
+    }
+}
+
+impl AppParams {
+    /// Читает картинку ретардера, если указана
+    pub fn read_retarder(&self) -> Result<Option<DynamicImage>> {
+        self.retarder
+            .as_deref()
+            .map(|path| read_image(path, messages::image_retarder()))
+            .transpose()
+    }
+
+    /// Собирает параметры обработки; картинка мешающей передачи читается здесь
+    pub fn to_params(&self) -> Result<ProcessingParams> {
+        // Полная деструктуризация: новый флаг без соответствующего параметра не скомпилируется
+        let &Self {
+            // Пресет подставляется в поля заранее, см. `apply_preset`
+            preset: _,
+            noise,
+            noise_kind,
+            noise_color,
+            impulse_rate,
+            impulse_ms,
+            impulse_dist,
+            ref noise_env,
+            ref noise_env_file,
+            noise_adsr,
+            noise_burst,
+            noise_repeat,
+            noise_anchor,
+            // Путь ретардера читается отдельно, см. `read_retarder`
+            retarder: _,
+            level,
+            ref ret_env,
+            ref ret_env_file,
+            ret_adsr,
+            ret_burst,
+            ret_repeat,
+            ret_anchor,
+            delay_ms,
+            ref ret_source,
+            ref taps,
+            fade_depth,
+            fade_rate,
+            fade_model,
+            offset_hz,
+            drift_hz_per_s,
+            wobble_hz,
+            wobble_rate,
+            ref carriers,
+            ref qrm,
+            qrm_level,
+            qrm_offset_hz,
+            qrm_delay_ms,
+            ssb,
+            ssb_low_hz,
+            ssb_high_hz,
+            ssb_drive_db,
+            ssb_alc_ms,
+            ssb_carrier,
+            ssb_iq_gain_db,
+            ssb_iq_phase_deg,
+            ssb_sideband,
+            ssb_output,
+            blanker,
+            blanker_threshold,
+            blanker_hold_ms,
+            blanker_average_ms,
+            blanker_fill,
+            filter,
+            filter_kind,
+            filter_hz,
+            filter_q,
+            filter_stages,
+            notch,
+            notch_hz,
+            notch_q,
+            agc,
+            agc_target,
+            agc_attack_ms,
+            agc_release_ms,
+            agc_max_gain_db,
+            ref dropouts,
+            dropout_rate,
+            dropout_ms,
+            dropout_kind,
+            dropout_db,
+            ref timeline,
+            ref events,
+            seed,
+            sample_rate,
+            output_gain_db,
+            normalize,
+            normalize_target,
+            true_peak_db,
+            ref mix,
+            convolution,
+            parallel,
+            quality,
+            sanitize,
+            metric_space,
+            // Телеметрия рисуется обработчиком строк, см. `processor`
+            telemetry: _,
+            // Пары и их надпись собирает `metadata`
+            meta: _,
+            meta_overlay: _,
+            meta_fsk,
+            // Пределы времени задаются процессору, см. `time_limits`
+            timeout: _,
+            stage_timeouts: _,
+            ref encoder,
+            ref overlay,
+            ref decoder,
+        } = self;
+
+        let qrm = match qrm {
+            Some(path) => Some(QrmTransmission {
+                level: qrm_level,
+                offset_hz: qrm_offset_hz,
+                delay_ms: qrm_delay_ms,
+                ..QrmTransmission::new(read_image(path, messages::image_qrm())?)
+            }),
+            None => None,
+        };
+
+        let metadata = self.metadata();
+        // Посылку FSK передаёт кодер вслед за опознавателем
+        let mut encoder = encoder.to_params();
+        if meta_fsk {
+            encoder.id.text = metadata.fsk_text();
+        }
+
+        let events = match timeline {
+            Some(path) => Timeline::from_file(path)?.events,
+            None => events.clone(),
+        };
+
+        // Последняя настройка эффекта важнее, без настройки выход эффекта заменяет вход
+        let mix_of = |target: MixTarget| {
+            mix.iter()
+                .rev()
+                .find(|m| m.target == target)
+                .map_or(EffectMix::default(), |m| m.mix)
+        };
+
+        Ok(ProcessingParams {
+            noise: NoiseParams {
+                level: noise,
+                kind: noise_kind,
+                color: noise_color,
+                impulse: ImpulseParams {
+                    rate_hz: impulse_rate,
+                    duration_ms: impulse_ms,
+                    amplitude: impulse_dist,
+                },
+                env: read_envelope(noise_env, noise_env_file, noise_adsr, noise_burst)?,
+                repeat: noise_repeat,
+                anchor: noise_anchor,
+                mix: mix_of(MixTarget::Noise),
+            },
+            retarder: RetarderParams {
+                level,
+                env: read_envelope(ret_env, ret_env_file, ret_adsr, ret_burst)?,
+                repeat: ret_repeat,
+                delay_ms,
+                anchor: ret_anchor,
+                source: ret_source.clone(),
+                mix: mix_of(MixTarget::Retarder),
+            },
+            multipath: MultipathParams {
+                taps: taps.clone(),
+                mix: mix_of(MixTarget::Multipath),
+            },
+            fading: FadingParams {
+                model: fade_model,
+                depth: fade_depth,
+                rate_hz: fade_rate,
+                mix: mix_of(MixTarget::Fading),
+            },
+            drift: DriftParams {
+                offset_hz,
+                drift_hz_per_s,
+                wobble_hz,
+                wobble_rate_hz: wobble_rate,
+                mix: mix_of(MixTarget::Drift),
+            },
+            interference: InterferenceParams {
+                carriers: carriers.clone(),
+                qrm,
+                mix: mix_of(MixTarget::Interference),
+            },
+            blanker: BlankerParams {
+                enabled: blanker,
+                threshold: blanker_threshold,
+                hold_ms: blanker_hold_ms,
+                average_ms: blanker_average_ms,
+                fill: blanker_fill,
+                mix: mix_of(MixTarget::Blanker),
+            },
+            filter: FilterParams {
+                enabled: filter,
+                kind: filter_kind,
+                freq_hz: filter_hz,
+                q: filter_q,
+                stages: filter_stages,
+                mix: mix_of(MixTarget::Filter),
+            },
+            notch: NotchParams {
+                enabled: notch,
+                freq_hz: notch_hz,
+                q: notch_q,
+                mix: mix_of(MixTarget::Notch),
+            },
+            agc: AgcParams {
+                enabled: agc,
+                target: agc_target,
+                attack_ms: agc_attack_ms,
+                release_ms: agc_release_ms,
+                max_gain_db: agc_max_gain_db,
+                mix: mix_of(MixTarget::Agc),
+            },
+            dropout: DropoutParams {
+                kind: dropout_kind,
+                attenuation_db: dropout_db,
+                windows: dropouts.clone(),
+                rate_per_min: dropout_rate,
+                duration_ms: dropout_ms,
+                mix: mix_of(MixTarget::Dropout),
+            },
+            events,
+            transceiver: TransceiverParams {
+                enabled: ssb,
+                low_hz: ssb_low_hz,
+                high_hz: ssb_high_hz,
+                drive_db: ssb_drive_db,
+                alc_release_ms: ssb_alc_ms,
+                carrier_leak: ssb_carrier,
+                iq_gain_db: ssb_iq_gain_db,
+                iq_phase_deg: ssb_iq_phase_deg,
+                sideband: ssb_sideband,
+                output: ssb_output,
+            },
+            decoder: decoder.to_params(),
+            encoder,
+            overlay: overlay.to_params(),
+            metadata,
+            convolution,
+            parallel,
+            quality,
+            sanitize,
+            output_gain_db,
+            loudness: LoudnessParams {
+                measure: normalize,
+                target_db: normalize_target,
+                true_peak_db,
+            },
+            metric_space,
+            sample_rate,
+            seed,
+        })
+    }
+
+    /// Переносит в поля параметры обработки `params`. Пути к картинкам, телеметрия и пресет
+    /// не меняются; параметры мешающей передачи — только если она задана в `params`.
+    /// Файлы и формы огибающих сбрасываются: огибающие берутся из `params` вместе с точками
+    /// кривых и параметрами формы. Так же и файл сценария: события берутся из `params`
+    pub fn set_params(&mut self, params: &ProcessingParams) {
+        let ProcessingParams {
+            noise,
+            retarder,
+            multipath,
+            fading,
+            drift,
+            interference,
+            blanker,
+            transceiver,
+            filter,
+            notch,
+            agc,
+            dropout,
+            events,
+            decoder,
+            encoder,
+            overlay,
+            metadata,
+            convolution,
+            parallel,
+            quality,
+            sanitize,
+            output_gain_db,
+            loudness,
+            metric_space,
+            sample_rate,
+            seed,
+        } = params;
+
+        self.noise = noise.level;
+        self.noise_kind = noise.kind;
+        self.noise_color = noise.color;
+        self.impulse_rate = noise.impulse.rate_hz;
+        self.impulse_ms = noise.impulse.duration_ms;
+        self.impulse_dist = noise.impulse.amplitude;
+        self.noise_env = noise.env.clone();
+        self.noise_env_file = None;
+        self.noise_adsr = None;
+        self.noise_burst = None;
+        self.noise_repeat = noise.repeat;
+        self.noise_anchor = noise.anchor;
+
+        self.level = retarder.level;
+        self.ret_env = retarder.env.clone();
+        self.ret_env_file = None;
+        self.ret_adsr = None;
+        self.ret_burst = None;
+        self.ret_repeat = retarder.repeat;
+        self.ret_anchor = retarder.anchor;
+        self.delay_ms = retarder.delay_ms;
+        self.ret_source = retarder.source.clone();
+
+        self.taps = multipath.taps.clone();
+
+        self.fade_depth = fading.depth;
+        self.fade_rate = fading.rate_hz;
+        self.fade_model = fading.model;
+
+        self.offset_hz = drift.offset_hz;
+        self.drift_hz_per_s = drift.drift_hz_per_s;
+        self.wobble_hz = drift.wobble_hz;
+        self.wobble_rate = drift.wobble_rate_hz;
+
+        self.carriers = interference.carriers.clone();
+        if let Some(qrm) = &interference.qrm {
+            self.qrm_level = qrm.level;
+            self.qrm_offset_hz = qrm.offset_hz;
+            self.qrm_delay_ms = qrm.delay_ms;
+        }
+
+        self.ssb = transceiver.enabled;
+        self.ssb_low_hz = transceiver.low_hz;
+        self.ssb_high_hz = transceiver.high_hz;
+        self.ssb_drive_db = transceiver.drive_db;
+        self.ssb_alc_ms = transceiver.alc_release_ms;
+        self.ssb_carrier = transceiver.carrier_leak;
+        self.ssb_iq_gain_db = transceiver.iq_gain_db;
+        self.ssb_iq_phase_deg = transceiver.iq_phase_deg;
+        self.ssb_sideband = transceiver.sideband;
+        self.ssb_output = transceiver.output;
+
+        self.blanker = blanker.enabled;
+        self.blanker_threshold = blanker.threshold;
+        self.blanker_hold_ms = blanker.hold_ms;
+        self.blanker_average_ms = blanker.average_ms;
+        self.blanker_fill = blanker.fill;
+
+        self.filter = filter.enabled;
+        self.filter_kind = filter.kind;
+        self.filter_hz = filter.freq_hz;
+        self.filter_q = filter.q;
+        self.filter_stages = filter.stages;
+        self.notch = notch.enabled;
+        self.notch_hz = notch.freq_hz;
+        self.notch_q = notch.q;
+        self.agc = agc.enabled;
+        self.agc_target = agc.target;
+        self.agc_attack_ms = agc.attack_ms;
+        self.agc_release_ms = agc.release_ms;
+        self.agc_max_gain_db = agc.max_gain_db;
+
+        self.dropouts = dropout.windows.clone();
+        self.dropout_rate = dropout.rate_per_min;
+        self.dropout_ms = dropout.duration_ms;
+        self.dropout_kind = dropout.kind;
+        self.dropout_db = dropout.attenuation_db;
+
+        self.events = events.clone();
+        self.timeline = None;
+
+        self.seed = *seed;
+        self.convolution = *convolution;
+        self.parallel = *parallel;
+        self.quality = *quality;
+        self.sanitize = *sanitize;
+        self.metric_space = *metric_space;
+        self.output_gain_db = *output_gain_db;
+        self.normalize = loudness.measure;
+        self.normalize_target = loudness.target_db;
+        self.true_peak_db = loudness.true_peak_db;
+        self.mix = MixTarget::ALL
+            .iter()
+            .map(|&target| MixSetting {
+                target,
+                mix: params.mix(target),
+            })
+            .filter(|m| !m.mix.is_identity())
+            .collect();
+        self.sample_rate = *sample_rate;
+        self.encoder = EncoderArgs::from_params(encoder);
+        self.overlay = OverlayArgs::from_params(overlay);
+        self.meta = metadata
+            .fields
+            .iter()
+            .map(|(key, value)| MetaField {
+                key: key.clone(),
+                value: value.clone(),
+            })
+            .collect();
+        self.meta_overlay = metadata.overlay;
+        self.meta_fsk = !encoder.id.text.trim().is_empty();
+        self.decoder = DecoderArgs::from_params(decoder);
+    }
+
+    /// Подставляет пресет `--preset`, если он указан: поля, флаги которых не заданы в командной
+    /// строке явно, берутся из пресета. `matches` — разбор команды, в которую вложены параметры
+    pub fn apply_preset(&mut self, matches: &ArgMatches) -> Result<()> {
+        let Some(path) = &self.preset else {
+            return Ok(());
+        };
+        let mut preset = self.clone();
+        preset.set_params(&ProcessingParams::from_file(path)?);
+
+        let explicit = |id: &str| matches.value_source(id) == Some(ValueSource::CommandLine);
+        // Значение из командной строки, если флаг задан явно, иначе из пресета
+        macro_rules! pick {
+            ($cli:expr, $preset:expr, $field:ident) => {
+                if explicit(stringify!($field)) {
+                    $cli.$field.clone()
+                } else {
+                    $preset.$field.clone()
+                }
+            };
+        }
+
+        // Полный литерал: новое поле без решения, откуда его брать, не скомпилируется
+        *self = Self {
+            preset: self.preset.clone(),
+            noise: pick!(self, preset, noise),
+            noise_kind: pick!(self, preset, noise_kind),
+            noise_color: pick!(self, preset, noise_color),
+            impulse_rate: pick!(self, preset, impulse_rate),
+            impulse_ms: pick!(self, preset, impulse_ms),
+            impulse_dist: pick!(self, preset, impulse_dist),
+            noise_env: pick!(self, preset, noise_env),
+            noise_env_file: pick!(self, preset, noise_env_file),
+            noise_adsr: pick!(self, preset, noise_adsr),
+            noise_burst: pick!(self, preset, noise_burst),
+            noise_repeat: pick!(self, preset, noise_repeat),
+            noise_anchor: pick!(self, preset, noise_anchor),
+            retarder: self.retarder.clone(),
+            level: pick!(self, preset, level),
+            ret_env: pick!(self, preset, ret_env),
+            ret_env_file: pick!(self, preset, ret_env_file),
+            ret_adsr: pick!(self, preset, ret_adsr),
+            ret_burst: pick!(self, preset, ret_burst),
+            ret_repeat: pick!(self, preset, ret_repeat),
+            ret_anchor: pick!(self, preset, ret_anchor),
+            delay_ms: pick!(self, preset, delay_ms),
+            ret_source: pick!(self, preset, ret_source),
+            taps: pick!(self, preset, taps),
+            fade_depth: pick!(self, preset, fade_depth),
+            fade_rate: pick!(self, preset, fade_rate),
+            fade_model: pick!(self, preset, fade_model),
+            offset_hz: pick!(self, preset, offset_hz),
+            drift_hz_per_s: pick!(self, preset, drift_hz_per_s),
+            wobble_hz: pick!(self, preset, wobble_hz),
+            wobble_rate: pick!(self, preset, wobble_rate),
+            carriers: pick!(self, preset, carriers),
+            qrm: self.qrm.clone(),
+            qrm_level: self.qrm_level,
+            qrm_offset_hz: self.qrm_offset_hz,
+            qrm_delay_ms: self.qrm_delay_ms,
+            ssb: pick!(self, preset, ssb),
+            ssb_low_hz: pick!(self, preset, ssb_low_hz),
+            ssb_high_hz: pick!(self, preset, ssb_high_hz),
+            ssb_drive_db: pick!(self, preset, ssb_drive_db),
+            ssb_alc_ms: pick!(self, preset, ssb_alc_ms),
+            ssb_carrier: pick!(self, preset, ssb_carrier),
+            ssb_iq_gain_db: pick!(self, preset, ssb_iq_gain_db),
+            ssb_iq_phase_deg: pick!(self, preset, ssb_iq_phase_deg),
+            ssb_sideband: pick!(self, preset, ssb_sideband),
+            ssb_output: pick!(self, preset, ssb_output),
+            blanker: pick!(self, preset, blanker),
+            blanker_threshold: pick!(self, preset, blanker_threshold),
+            blanker_hold_ms: pick!(self, preset, blanker_hold_ms),
+            blanker_average_ms: pick!(self, preset, blanker_average_ms),
+            blanker_fill: pick!(self, preset, blanker_fill),
+            filter: pick!(self, preset, filter),
+            filter_kind: pick!(self, preset, filter_kind),
+            filter_hz: pick!(self, preset, filter_hz),
+            filter_q: pick!(self, preset, filter_q),
+            filter_stages: pick!(self, preset, filter_stages),
+            notch: pick!(self, preset, notch),
+            notch_hz: pick!(self, preset, notch_hz),
+            notch_q: pick!(self, preset, notch_q),
+            agc: pick!(self, preset, agc),
+            agc_target: pick!(self, preset, agc_target),
+            agc_attack_ms: pick!(self, preset, agc_attack_ms),
+            agc_release_ms: pick!(self, preset, agc_release_ms),
+            agc_max_gain_db: pick!(self, preset, agc_max_gain_db),
+            dropouts: pick!(self, preset, dropouts),
+            dropout_rate: pick!(self, preset, dropout_rate),
+            dropout_ms: pick!(self, preset, dropout_ms),
+            dropout_kind: pick!(self, preset, dropout_kind),
+            dropout_db: pick!(self, preset, dropout_db),
+            timeline: pick!(self, preset, timeline),
+            events: preset.events.clone(),
+            seed: pick!(self, preset, seed),
+            sample_rate: pick!(self, preset, sample_rate),
+            output_gain_db: pick!(self, preset, output_gain_db),
+            normalize: pick!(self, preset, normalize),
+            normalize_target: pick!(self, preset, normalize_target),
+            true_peak_db: pick!(self, preset, true_peak_db),
+            mix: pick!(self, preset, mix),
+            convolution: pick!(self, preset, convolution),
+            parallel: pick!(self, preset, parallel),
+            quality: pick!(self, preset, quality),
+            sanitize: pick!(self, preset, sanitize),
+            metric_space: pick!(self, preset, metric_space),
+            telemetry: self.telemetry,
+            meta: pick!(self, preset, meta),
+            meta_overlay: pick!(self, preset, meta_overlay),
+            meta_fsk: pick!(self, preset, meta_fsk),
+            timeout: self.timeout,
+            stage_timeouts: self.stage_timeouts.clone(),
+            encoder: EncoderArgs {
+                encoder: pick!(self.encoder, preset.encoder, encoder),
+                no_header: pick!(self.encoder, preset.encoder, no_header),
+                leader_ms: pick!(self.encoder, preset.encoder, leader_ms),
+                vis_code: pick!(self.encoder, preset.encoder, vis_code),
+                vis_attenuation_db: pick!(self.encoder, preset.encoder, vis_attenuation_db),
+                vis_flip_bits: pick!(self.encoder, preset.encoder, vis_flip_bits),
+                timing_scale: pick!(self.encoder, preset.encoder, timing_scale),
+                id: pick!(self.encoder, preset.encoder, id),
+                id_mode: pick!(self.encoder, preset.encoder, id_mode),
+                id_wpm: pick!(self.encoder, preset.encoder, id_wpm),
+                id_tone_hz: pick!(self.encoder, preset.encoder, id_tone_hz),
+                base: preset.encoder.base.clone(),
+            },
+            overlay: OverlayArgs {
+                overlay: pick!(self.overlay, preset.overlay, overlay),
+                overlay_x: pick!(self.overlay, preset.overlay, overlay_x),
+                overlay_y: pick!(self.overlay, preset.overlay, overlay_y),
+                overlay_size: pick!(self.overlay, preset.overlay, overlay_size),
+                overlay_color: pick!(self.overlay, preset.overlay, overlay_color),
+                overlay_font: pick!(self.overlay, preset.overlay, overlay_font),
+                overlay_shadow: pick!(self.overlay, preset.overlay, overlay_shadow),
+            },
+            decoder: DecoderArgs {
+                decoder: pick!(self.decoder, preset.decoder, decoder),
+                sync_threshold: pick!(self.decoder, preset.decoder, sync_threshold),
+                search_window_ms: pick!(self.decoder, preset.decoder, search_window_ms),
+                max_deviation_hz: pick!(self.decoder, preset.decoder, max_deviation_hz),
+                afc: pick!(self.decoder, preset.decoder, afc),
+                conceal: pick!(self.decoder, preset.decoder, conceal),
+                deskew: pick!(self.decoder, preset.decoder, deskew),
+                slant: pick!(self.decoder, preset.decoder, slant),
+                palette: pick!(self.decoder, preset.decoder, palette),
+                blur: pick!(self.decoder, preset.decoder, blur),
+                scanlines: pick!(self.decoder, preset.decoder, scanlines),
+                phosphor: pick!(self.decoder, preset.decoder, phosphor),
+                phosphor_tint: pick!(self.decoder, preset.decoder, phosphor_tint),
+            },
+        };
+        Ok(())
+    }
+
+    /// Доли смешивания эффекта `target` для правки: последняя его настройка `--mix`,
+    /// а без неё — новая, пока не меняющая выход эффекта
+    pub fn mix_mut(&mut self, target: MixTarget) -> &mut EffectMix {
+        let index = match self.mix.iter().rposition(|m| m.target == target) {
+            Some(index) => index,
+            None => {
+                self.mix.push(MixSetting {
+                    target,
+                    mix: EffectMix::default(),
+                });
+                self.mix.len() - 1
+            }
+        };
+        &mut self.mix[index].mix
+    }
+
+    /// Метаданные из `--meta` и `--meta-overlay`; поля с пустым ключом (недописанные в
+    /// интерфейсе) пропускаются
+    pub fn metadata(&self) -> Metadata {
+        let mut metadata = Metadata {
+            overlay: self.meta_overlay,
+            ..Metadata::default()
+        };
+        for field in self.meta.iter().filter(|f| !f.key.trim().is_empty()) {
+            metadata.insert(field.clone());
+        }
+        metadata
+    }
+
+    /// Пределы времени из `--timeout` и `--stage-timeout`
+    pub fn time_limits(&self) -> TimeLimits {
+        let limits = TimeLimits {
+            total: self.timeout,
+            ..TimeLimits::default()
+        };
+        self.stage_timeouts
+            .iter()
+            .fold(limits, |limits, &stage| limits.with_stage(stage))
+    }
+
+    /// Создаёт процессор с параметрами `params`, пределами времени и обработчиком
+    /// телеметрии, если он задан
+    pub fn processor(&self, params: ProcessingParams) -> Result<SSTVProcessor> {
+        let mut builder = SSTVProcessor::builder()
+            .params(params)
+            .time_limits(self.time_limits());
+        if let Some(telemetry) = &self.telemetry {
+            builder = builder.line_hook(TelemetryStrip::new().line_hook(&telemetry.to_bytes())?);
+        }
+        builder.build()
+    }
+}
+
+// ── Кодер ────────────────────────────────────────────────────
+#[derive(clap::Args, Clone, Debug)]
+pub struct EncoderArgs {
+    #[arg(long, default_value = "rsstv", help = help::encoder())]
+    pub encoder: EncoderBackend,
+
+    #[arg(long, help = help::no_header())]
+    pub no_header: bool,
+
+    #[arg(long, default_value_t = 300.0, help = help::leader_ms())]
+    pub leader_ms: f32,
+
+    #[arg(long, default_value_t = 44u8, help = help::vis_code(),
+          value_parser = clap::value_parser!(u8).range(0..=127))]
+    pub vis_code: u8,
+
+    #[arg(
+        long,
+        default_value_t = 0.0,
+        allow_hyphen_values = true,
+        help = help::vis_attenuation_db()
+    )]
+    pub vis_attenuation_db: f32,
+
+    #[arg(long, default_value_t = 0u8, help = help::vis_flip_bits(),
+          value_parser = clap::value_parser!(u8).range(0..=8))]
+    pub vis_flip_bits: u8,
+
+    #[arg(long, default_value_t = 1.0, help = help::timing_scale())]
+    pub timing_scale: f64,
+
+    #[arg(long, value_name = "CALLSIGN", help = help::id())]
+    pub id: Option<String>,
+
+    #[arg(long, default_value = "cw", help = help::id_mode())]
+    pub id_mode: IdMode,
+
+    #[arg(long, default_value_t = 20.0, help = help::id_wpm())]
+    pub id_wpm: f32,
+
+    #[arg(long, default_value_t = 800.0, help = help::id_tone_hz())]
+    pub id_tone_hz: f32,
+
+    /// Параметры кодера без флагов CLI (разрыв лидер-тонов, длительность бита VIS,
+    /// амплитуда); задаются пресетом
+    #[arg(skip)]
+    pub base: EncoderParams,
+}
+
+impl EncoderArgs {
+    pub fn to_params(&self) -> EncoderParams {
+        let &Self {
+            encoder,
+            no_header,
+            leader_ms,
+            vis_code,
+            vis_attenuation_db,
+            vis_flip_bits,
+            timing_scale,
+            ref id,
+            id_mode,
+            id_wpm,
+            id_tone_hz,
+            ref base,
+        } = self;
+        EncoderParams {
+            backend: encoder,
+            header: !no_header,
+            leader_ms,
+            vis_code,
+            vis_attenuation_db,
+            vis_flip_bits,
+            timing_scale,
+            id: IdParams {
+                callsign: id.clone().unwrap_or_default(),
+                mode: id_mode,
+                wpm: id_wpm,
+                tone_hz: id_tone_hz,
+                // Текст посылки берётся из метаданных, см. `AppParams::to_params`
+                text: String::new(),
+            },
+            ..base.clone()
+        }
+    }
+
+    pub fn from_params(params: &EncoderParams) -> Self {
+        Self {
+            encoder: params.backend,
+            no_header: !params.header,
+            leader_ms: params.leader_ms,
+            vis_code: params.vis_code,
+            vis_attenuation_db: params.vis_attenuation_db,
+            vis_flip_bits: params.vis_flip_bits,
+            timing_scale: params.timing_scale,
+            id: params.id.is_enabled().then(|| params.id.callsign.clone()),
+            id_mode: params.id.mode,
+            id_wpm: params.id.wpm,
+            id_tone_hz: params.id.tone_hz,
+            base: params.clone(),
+        }
+    }
+}
+
+// ── Надпись ──────────────────────────────────────────────────
+#[derive(clap::Args, Clone, Debug)]
+pub struct OverlayArgs {
+    #[arg(long = "overlay", value_name = "TEXT", help = help::overlay())]
+    pub overlay: Vec<String>,
+
+    #[arg(long, default_value_t = 8, allow_hyphen_values = true, help = help::overlay_x())]
+    pub overlay_x: i32,
+
+    #[arg(long, default_value_t = 8, allow_hyphen_values = true, help = help::overlay_y())]
+    pub overlay_y: i32,
+
+    #[arg(long, default_value_t = 16.0, help = help::overlay_size())]
+    pub overlay_size: f32,
+
+    #[arg(long, default_value = "#ffffff", help = help::overlay_color())]
+    pub overlay_color: OverlayColor,
+
+    #[arg(long, value_name = "FILE", help = help::overlay_font())]
+    pub overlay_font: Option<String>,
+
+    #[arg(long, help = help::overlay_shadow())]
+    pub overlay_shadow: bool,
+}
+
+impl OverlayArgs {
+    pub fn to_params(&self) -> OverlayParams {
+        OverlayParams {
+            lines: self.overlay.clone(),
+            x: self.overlay_x,
+            y: self.overlay_y,
+            size: self.overlay_size,
+            color: self.overlay_color,
+            font: self.overlay_font.clone(),
+            shadow: self.overlay_shadow,
+        }
+    }
+
+    pub fn from_params(params: &OverlayParams) -> Self {
+        Self {
+            overlay: params.lines.clone(),
+            overlay_x: params.x,
+            overlay_y: params.y,
+            overlay_size: params.size,
+            overlay_color: params.color,
+            overlay_font: params.font.clone(),
+            overlay_shadow: params.shadow,
+        }
+    }
+}
+
+// ── Декодер ──────────────────────────────────────────────────
+#[derive(clap::Args, Clone, Debug)]
+pub struct DecoderArgs {
+    #[arg(long, default_value = "rsstv", help = help::decoder())]
+    pub decoder: DecoderBackend,
+
+    #[arg(long, default_value_t = 0.5, help = help::sync_threshold())]
+    pub sync_threshold: f32,
+
+    #[arg(long, default_value_t = 0u32, help = help::search_window_ms())]
+    pub search_window_ms: u32,
+
+    #[arg(long, default_value_t = 250.0, help = help::max_deviation_hz())]
+    pub max_deviation_hz: f32,
+
+    #[arg(long, help = help::afc())]
+    pub afc: bool,
+
+    #[arg(long, help = help::conceal())]
+    pub conceal: bool,
+
+    #[arg(long, help = help::deskew())]
+    pub deskew: bool,
+
+    #[arg(long, allow_hyphen_values = true, help = help::slant())]
+    pub slant: Option<f64>,
+
+    #[arg(long, default_value = "full", help = help::palette())]
+    pub palette: Palette,
+
+    #[arg(long, default_value_t = 0.0, help = help::blur())]
+    pub blur: f32,
+
+    #[arg(long, default_value_t = 0.0, help = help::scanlines())]
+    pub scanlines: f32,
+
+    #[arg(long, default_value = "p22", help = help::phosphor())]
+    pub phosphor: Phosphor,
+
+    #[arg(long, default_value_t = 0.0, help = help::phosphor_tint())]
+    pub phosphor_tint: f32,
+}
+
+impl DecoderArgs {
+    pub fn to_params(&self) -> DecoderParams {
+        let &Self {
+            decoder,
+            sync_threshold,
+            search_window_ms,
+            max_deviation_hz,
+            afc,
+            conceal,
+            deskew,
+            slant,
+            palette,
+            blur,
+            scanlines,
+            phosphor,
+            phosphor_tint,
+        } = self;
+        DecoderParams {
+            backend: decoder,
+            sync_threshold,
+            search_window_ms,
+            max_freq_deviation_hz: max_deviation_hz,
+            afc,
+            conceal,
+            deskew,
+            slant_ppm: slant,
+            palette,
+            converter: ScanConverterParams {
+                blur,
+                scanlines,
+                phosphor,
+                tint: phosphor_tint,
+            },
+        }
+    }
+
+    pub fn from_params(params: &DecoderParams) -> Self {
+        Self {
+            decoder: params.backend,
+            sync_threshold: params.sync_threshold,
+            search_window_ms: params.search_window_ms,
+            max_deviation_hz: params.max_freq_deviation_hz,
+            afc: params.afc,
+            conceal: params.conceal,
+            deskew: params.deskew,
+            slant: params.slant_ppm,
+            palette: params.palette,
+            blur: params.converter.blur,
+            scanlines: params.converter.scanlines,
+            phosphor: params.converter.phosphor,
+            phosphor_tint: params.converter.tint,
+        }
+    }
+}
+
+fn parse_timeout(s: &str) -> Result<Duration> {
+    deadline::parse_secs(s, "timeout")
+}
+
+/// Читает и декодирует изображение; `what` — что читается, для сообщения об ошибке
+fn read_image(path: &str, what: &str) -> Result<DynamicImage> {
+    ImageReader::open(path)
+        .map_err(|e| SstvError::io(messages::open_failed(path), e))?
+        .decode()
+        .map_err(|e| SstvError::io(messages::decode_failed(what), e))
+}
+
+/// Огибающая из файла, если он указан, затем из формы ADSR или пачек, иначе `kind`
+fn read_envelope(
+    kind: &EnvelopeKind,
+    file: &Option<String>,
+    adsr: Option<AdsrEnvelope>,
+    burst: Option<BurstEnvelope>,
+) -> Result<EnvelopeKind> {
+    Ok(match (file, adsr, burst) {
+        (Some(path), _, _) => EnvelopeKind::Custom(CustomEnvelope::from_file(path)?),
+        (None, Some(adsr), _) => EnvelopeKind::Adsr(adsr),
+        (None, None, Some(burst)) => EnvelopeKind::Burst(burst),
+        (None, None, None) => kind.clone(),
+    })
+}
@@ -0,0 +1,364 @@
+use crate::error::{Result, SstvError};
+use crate::export::RUN_MANIFEST;
+use crate::messages;
+use crate::metadata::crc32;
+use crate::overlay;
+use crate::preset::PresetFormat;
+use crate::processor::ProcessingParams;
+use std::path::{Component, Path, PathBuf};
+use std::time::SystemTime;
+
+/// Пресет, который [`pack`] кладёт в архив рядом с описью
+pub const RUN_PRESET: &str = "preset.toml";
+
+const LOCAL_HEADER: u32 = 0x0403_4b50;
+const CENTRAL_HEADER: u32 = 0x0201_4b50;
+const END_OF_DIRECTORY: u32 = 0x0605_4b50;
+/// Версия ZIP 2.0: хранение без сжатия
+const ZIP_VERSION: u16 = 20;
+/// Флаг имён в UTF-8
+const UTF8_NAMES: u16 = 1 << 11;
+/// Длина записи конца каталога без комментария и самый длинный комментарий
+const END_LEN: usize = 22;
+const MAX_COMMENT: usize = u16::MAX as usize;
+
+/// Упаковывает папку прогона [`RunExporter`](crate::RunExporter) `dir` в ZIP `out`:
+/// изображения, WAV, опись и пресет [`RUN_PRESET`] из её параметров, чтобы прогон
+/// можно было повторить на другой машине. Файлы лежат без сжатия (PNG уже сжат) под
+/// именем папки. Возвращает число записей
+pub fn pack(dir: &Path, out: &Path) -> Result<usize> {
+    let manifest_path = dir.join(RUN_MANIFEST);
+    let manifest = std::fs::read_to_string(&manifest_path).map_err(|e| {
+        SstvError::io(
+            messages::archive_not_run(&dir.display().to_string(), RUN_MANIFEST),
+            e,
+        )
+    })?;
+    let params = preset_of(&manifest).map_err(|e| {
+        SstvError::invalid("archive", messages::archive_bad_manifest(&e.to_string()))
+    })?;
+    let root = dir
+        .canonicalize()
+        .ok()
+        .and_then(|dir| {
+            dir.file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+        })
+        .unwrap_or_else(|| "run".to_string());
+
+    let mut files = Vec::new();
+    collect(dir, &mut files)?;
+    files.sort();
+    let mut zip = ZipWriter::default();
+    for path in &files {
+        let relative = path.strip_prefix(dir).unwrap_or(path);
+        let name = relative
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        let data = std::fs::read(path).map_err(|e| {
+            SstvError::io(
+                messages::archive_read_failed(&path.display().to_string()),
+                e,
+            )
+        })?;
+        let modified = std::fs::metadata(path)
+            .and_then(|m| m.modified())
+            .unwrap_or_else(|_| SystemTime::now());
+        zip.add(&format!("{}/{}", root, name), &data, modified)?;
+    }
+    // Пресет из описи: его берёт `--preset`, не разбирая params.json
+    if !files.iter().any(|path| path.ends_with(RUN_PRESET)) {
+        let preset = params.to_preset(PresetFormat::Toml)?;
+        zip.add(
+            &format!("{}/{}", root, RUN_PRESET),
+            preset.as_bytes(),
+            SystemTime::now(),
+        )?;
+    }
+    let count = zip.entries.len();
+    std::fs::write(out, zip.finish()?).map_err(|e| {
+        SstvError::io(
+            messages::archive_write_failed(&out.display().to_string()),
+            e,
+        )
+    })?;
+    Ok(count)
+}
+
+/// Распаковывает архив [`pack`] в каталог `dest` и возвращает путь папки прогона.
+/// Существующие файлы не перезаписываются, пути с `..` и абсолютные отвергаются
+pub fn unpack(archive: &Path, dest: &Path) -> Result<PathBuf> {
+    let bytes = std::fs::read(archive).map_err(|e| {
+        SstvError::io(
+            messages::archive_read_failed(&archive.display().to_string()),
+            e,
+        )
+    })?;
+    let invalid = |reason: String| {
+        SstvError::invalid("archive", format!("{}: {}", archive.display(), reason))
+    };
+    let entries = read_entries(&bytes).map_err(invalid)?;
+    let mut root = None;
+    for entry in &entries {
+        let relative = safe_path(&entry.name)
+            .ok_or_else(|| invalid(messages::archive_bad_name(&entry.name)))?;
+        root.get_or_insert_with(|| {
+            relative
+                .components()
+                .next()
+                .map_or_else(PathBuf::new, |c| PathBuf::from(c.as_os_str()))
+        });
+        let path = dest.join(&relative);
+        if entry.name.ends_with('/') {
+            continue;
+        }
+        if path.exists() {
+            return Err(invalid(messages::archive_exists(
+                &path.display().to_string(),
+            )));
+        }
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| {
+                SstvError::io(
+                    messages::archive_create_dir_failed(&parent.display().to_string()),
+                    e,
+                )
+            })?;
+        }
+        std::fs::write(&path, entry.data).map_err(|e| {
+            SstvError::io(
+                messages::archive_write_failed(&path.display().to_string()),
+                e,
+            )
+        })?;
+    }
+    let root = root.ok_or_else(|| invalid(messages::archive_empty().to_string()))?;
+    let dir = dest.join(root);
+    if !dir.join(RUN_MANIFEST).exists() {
+        return Err(invalid(messages::archive_no_manifest(RUN_MANIFEST)));
+    }
+    Ok(dir)
+}
+
+/// Параметры из описи прогона
+fn preset_of(manifest: &str) -> std::result::Result<ProcessingParams, serde_json::Error> {
+    let mut manifest: serde_json::Value = serde_json::from_str(manifest)?;
+    serde_json::from_value(manifest["params"].take())
+}
+
+/// Все файлы под `dir`, включая вложенные папки
+fn collect(dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    let read_error =
+        |e| SstvError::io(messages::archive_read_failed(&dir.display().to_string()), e);
+    for entry in std::fs::read_dir(dir).map_err(read_error)? {
+        let path = entry.map_err(read_error)?.path();
+        if path.is_dir() {
+            collect(&path, files)?;
+        } else if path.is_file() {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Относительный путь из имени записи; `None`, если имя выводит из каталога распаковки
+fn safe_path(name: &str) -> Option<PathBuf> {
+    if name.contains('\\') {
+        return None;
+    }
+    let path = PathBuf::from(name.trim_end_matches('/'));
+    path.components()
+        .all(|c| matches!(c, Component::Normal(_)))
+        .then_some(path)
+        .filter(|path| !path.as_os_str().is_empty())
+}
+
+/// Сборщик ZIP в памяти: записи без сжатия, каталог в конце
+#[derive(Default)]
+struct ZipWriter {
+    out: Vec<u8>,
+    /// Заголовки каталога
+    entries: Vec<Vec<u8>>,
+}
+
+impl ZipWriter {
+    fn add(&mut self, name: &str, data: &[u8], modified: SystemTime) -> Result<()> {
+        let too_large = || SstvError::invalid("archive", messages::archive_file_too_large(name));
+        let size = u32::try_from(data.len()).map_err(|_| too_large())?;
+        let offset = u32::try_from(self.out.len()).map_err(|_| too_large())?;
+        let name_len = u16::try_from(name.len()).map_err(|_| {
+            SstvError::invalid("archive", messages::archive_name_too_long(name, name.len()))
+        })?;
+        let (time, date) = dos_time(modified);
+        let crc = crc32(data);
+        // Поля, общие для локального заголовка и заголовка каталога
+        let mut common = Vec::new();
+        for field in [ZIP_VERSION, UTF8_NAMES, 0, time, date] {
+            common.extend_from_slice(&field.to_le_bytes());
+        }
+        for field in [crc, size, size] {
+            common.extend_from_slice(&field.to_le_bytes());
+        }
+        common.extend_from_slice(&name_len.to_le_bytes());
+        common.extend_from_slice(&0u16.to_le_bytes());
+
+        self.out.extend_from_slice(&LOCAL_HEADER.to_le_bytes());
+        self.out.extend_from_slice(&common);
+        self.out.extend_from_slice(name.as_bytes());
+        self.out.extend_from_slice(data);
+
+        let mut central = CENTRAL_HEADER.to_le_bytes().to_vec();
+        central.extend_from_slice(&ZIP_VERSION.to_le_bytes());
+        central.extend_from_slice(&common);
+        // Комментарий, номер диска, внутренние и внешние атрибуты
+        central.extend_from_slice(&[0; 10]);
+        central.extend_from_slice(&offset.to_le_bytes());
+        central.extend_from_slice(name.as_bytes());
+        self.entries.push(central);
+        Ok(())
+    }
+
+    fn finish(mut self) -> Result<Vec<u8>> {
+        let too_large = || SstvError::invalid("archive", messages::archive_too_large());
+        let count = u16::try_from(self.entries.len())
+            .map_err(|_| SstvError::invalid("archive", messages::archive_too_many_files()))?;
+        let offset = u32::try_from(self.out.len()).map_err(|_| too_large())?;
+        let directory: Vec<u8> = self.entries.concat();
+        let size = u32::try_from(directory.len()).map_err(|_| too_large())?;
+        self.out.extend_from_slice(&directory);
+        self.out.extend_from_slice(&END_OF_DIRECTORY.to_le_bytes());
+        self.out.extend_from_slice(&[0; 4]);
+        self.out.extend_from_slice(&count.to_le_bytes());
+        self.out.extend_from_slice(&count.to_le_bytes());
+        self.out.extend_from_slice(&size.to_le_bytes());
+        self.out.extend_from_slice(&offset.to_le_bytes());
+        self.out.extend_from_slice(&0u16.to_le_bytes());
+        Ok(self.out)
+    }
+}
+
+/// Время и дата MS-DOS по UTC; раньше 1980 года не бывает
+fn dos_time(time: SystemTime) -> (u16, u16) {
+    let (year, month, day, secs) = overlay::civil_utc(time);
+    let date = ((year.clamp(1980, 2107) - 1980) << 9) | (month << 5) | day;
+    let time = (secs / 3600) << 11 | (secs % 3600 / 60) << 5 | (secs % 60 / 2);
+    (time as u16, date as u16)
+}
+
+/// Запись архива
+struct Entry<'a> {
+    name: String,
+    data: &'a [u8],
+}
+
+/// Записи по центральному каталогу; сжатые и повреждённые отвергаются
+fn read_entries(bytes: &[u8]) -> std::result::Result<Vec<Entry<'_>>, String> {
+    let u16_at = |at: usize| -> std::result::Result<usize, String> {
+        let b = bytes.get(at..at + 2).ok_or(messages::archive_truncated())?;
+        Ok(u16::from_le_bytes([b[0], b[1]]) as usize)
+    };
+    let u32_at = |at: usize| -> std::result::Result<u32, String> {
+        let b = bytes.get(at..at + 4).ok_or(messages::archive_truncated())?;
+        Ok(u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    };
+    let search_from = bytes.len().saturating_sub(END_LEN + MAX_COMMENT);
+    let end = (search_from..=bytes.len().saturating_sub(END_LEN))
+        .rev()
+        .find(|&at| u32_at(at) == Ok(END_OF_DIRECTORY))
+        .ok_or(messages::archive_not_zip())?;
+    let count = u16_at(end + 10)?;
+    let mut at = u32_at(end + 16)? as usize;
+
+    let mut entries = Vec::with_capacity(count);
+    for _ in 0..count {
+        if u32_at(at)? != CENTRAL_HEADER {
+            return Err(messages::archive_directory_damaged().to_string());
+        }
+        let method = u16_at(at + 10)?;
+        let crc = u32_at(at + 16)?;
+        let size = u32_at(at + 20)? as usize;
+        let name_len = u16_at(at + 28)?;
+        let skip = name_len + u16_at(at + 30)? + u16_at(at + 32)?;
+        let local = u32_at(at + 42)? as usize;
+        let name = bytes
+            .get(at + 46..at + 46 + name_len)
+            .map(|name| String::from_utf8_lossy(name).into_owned())
+            .ok_or(messages::archive_truncated())?;
+        at += 46 + skip;
+
+        if method != 0 {
+            return Err(messages::archive_compressed(&name, method));
+        }
+        if u32_at(local)? != LOCAL_HEADER {
+            return Err(messages::archive_header_damaged(&name));
+        }
+        let start = local + 30 + u16_at(local + 26)? + u16_at(local + 28)?;
+        let data = bytes
+            .get(start..start + size)
+            .ok_or(messages::archive_truncated())?;
+        if crc32(data) != crc {
+            return Err(messages::archive_crc_mismatch(&name));
+        }
+        entries.push(Entry { name, data });
+    }
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pack_and_unpack_round_trip() {
+        let base = std::env::temp_dir().join(format!("sstv_archive_{}", std::process::id()));
+        let run = base.join("run");
+        std::fs::create_dir_all(run.join("frames")).unwrap();
+        let manifest = serde_json::json!({ "params": ProcessingParams::default() });
+        std::fs::write(run.join(RUN_MANIFEST), manifest.to_string()).unwrap();
+        std::fs::write(run.join("frames/0001.png"), b"\x89PNG frame").unwrap();
+
+        let zip = base.join("run.zip");
+        assert_eq!(pack(&run, &zip).unwrap(), 3);
+        let dest = base.join("out");
+        let unpacked = unpack(&zip, &dest).unwrap();
+        assert_eq!(unpacked, dest.join("run"));
+        for name in [RUN_MANIFEST, "frames/0001.png"] {
+            assert_eq!(
+                std::fs::read(unpacked.join(name)).unwrap(),
+                std::fs::read(run.join(name)).unwrap(),
+                "{}",
+                name
+            );
+        }
+        assert!(ProcessingParams::from_file(unpacked.join(RUN_PRESET)).is_ok());
+        // Второй раз поверх тех же файлов не распаковывается
+        assert!(unpack(&zip, &dest).is_err());
+        std::fs::remove_dir_all(&base).unwrap();
+    }
+
+    #[test]
+    fn safe_path_rejects_escapes() {
+        assert_eq!(safe_path("run/a.png"), Some(PathBuf::from("run/a.png")));
+        assert_eq!(safe_path("run/"), Some(PathBuf::from("run")));
+        for name in [
+            "../a.png",
+            "run/../../a.png",
+            "/etc/passwd",
+            "run\\..\\a.png",
+            "",
+            "./a",
+        ] {
+            assert_eq!(safe_path(name), None, "{}", name);
+        }
+    }
+
+    #[test]
+    fn long_names_are_an_error() {
+        let mut zip = ZipWriter::default();
+        let name = "a".repeat(u16::MAX as usize + 1);
+        assert!(zip.add(&name, b"", SystemTime::now()).is_err());
+        assert!(zip.entries.is_empty());
+    }
+}
@@ -0,0 +1,197 @@
+use crate::buffer::SampleBuffer;
+use crate::error::{Result, SstvError};
+use crate::messages;
+use crate::wav;
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use cpal::{Device, FromSample, SampleFormat, SizedSample, StreamConfig};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Пиковый уровень при воспроизведении
+const PLAYBACK_PEAK: f32 = 0.9;
+/// Запас времени после окончания воспроизведения, чтобы буфер устройства успел опустеть
+const PLAYBACK_TAIL: Duration = Duration::from_millis(300);
+
+/// Возвращает имена доступных устройств вывода
+pub fn output_devices() -> Result<Vec<String>> {
+    let host = cpal::default_host();
+    let devices = host
+        .output_devices()
+        .map_err(|e| device_error(messages::device_list_outputs(), e))?;
+    Ok(devices.filter_map(|d| d.name().ok()).collect())
+}
+
+/// Возвращает имена доступных устройств ввода
+pub fn input_devices() -> Result<Vec<String>> {
+    let host = cpal::default_host();
+    let devices = host
+        .input_devices()
+        .map_err(|e| device_error(messages::device_list_inputs(), e))?;
+    Ok(devices.filter_map(|d| d.name().ok()).collect())
+}
+
+/// Воспроизводит буфер на устройстве вывода и ждёт окончания воспроизведения;
+/// многоканальный буфер сводится в моно
+pub fn play(buffer: &SampleBuffer, device: Option<&str>) -> Result<()> {
+    let device = find_output_device(device)?;
+    let supported = device
+        .default_output_config()
+        .map_err(|e| device_error(messages::device_output_config(), e))?;
+    let format = supported.sample_format();
+    let config: StreamConfig = supported.into();
+
+    // Подгоняем частоту и уровень под устройство
+    let mut data = buffer.resampled(config.sample_rate.0).data;
+    let peak = data.iter().fold(0.0f32, |m, s| m.max(s.abs()));
+    if peak > PLAYBACK_PEAK {
+        let gain = PLAYBACK_PEAK / peak;
+        data.iter_mut().for_each(|s| *s *= gain);
+    }
+
+    let data = Arc::new(data);
+    let position = Arc::new(AtomicUsize::new(0));
+
+    let stream = match format {
+        SampleFormat::F32 => build_output::<f32>(&device, &config, &data, &position),
+        SampleFormat::I16 => build_output::<i16>(&device, &config, &data, &position),
+        SampleFormat::U16 => build_output::<u16>(&device, &config, &data, &position),
+        other => Err(SstvError::AudioDevice(messages::device_sample_format(
+            &format!("{:?}", other),
+        ))),
+    }?;
+    stream
+        .play()
+        .map_err(|e| device_error(messages::device_start_playback(), e))?;
+
+    while position.load(Ordering::Relaxed) < data.len() {
+        std::thread::sleep(Duration::from_millis(50));
+    }
+    std::thread::sleep(PLAYBACK_TAIL);
+
+    Ok(())
+}
+
+/// Записывает звук с устройства ввода заданное время в моно-буфер с частотой устройства
+pub fn capture(duration: Duration, device: Option<&str>) -> Result<SampleBuffer> {
+    let device = find_input_device(device)?;
+    let supported = device
+        .default_input_config()
+        .map_err(|e| device_error(messages::device_input_config(), e))?;
+    let format = supported.sample_format();
+    let config: StreamConfig = supported.into();
+
+    let buffer = Arc::new(Mutex::new(Vec::new()));
+
+    let stream = match format {
+        SampleFormat::F32 => build_input::<f32>(&device, &config, &buffer),
+        SampleFormat::I16 => build_input::<i16>(&device, &config, &buffer),
+        SampleFormat::U16 => build_input::<u16>(&device, &config, &buffer),
+        other => Err(SstvError::AudioDevice(messages::device_sample_format(
+            &format!("{:?}", other),
+        ))),
+    }?;
+    stream
+        .play()
+        .map_err(|e| device_error(messages::device_start_recording(), e))?;
+
+    std::thread::sleep(duration);
+    drop(stream);
+
+    let samples = std::mem::take(&mut *buffer.lock().unwrap());
+    Ok(SampleBuffer::mono(samples, config.sample_rate.0))
+}
+
+pub(crate) fn find_output_device(name: Option<&str>) -> Result<Device> {
+    let host = cpal::default_host();
+    match name {
+        Some(name) => host
+            .output_devices()
+            .map_err(|e| device_error(messages::device_list_outputs(), e))?
+            .find(|d| d.name().is_ok_and(|n| n == name))
+            .ok_or_else(|| SstvError::AudioDevice(messages::device_output_not_found(name))),
+        None => host
+            .default_output_device()
+            .ok_or_else(|| SstvError::AudioDevice(messages::device_no_default_output().into())),
+    }
+}
+
+pub(crate) fn find_input_device(name: Option<&str>) -> Result<Device> {
+    let host = cpal::default_host();
+    match name {
+        Some(name) => host
+            .input_devices()
+            .map_err(|e| device_error(messages::device_list_inputs(), e))?
+            .find(|d| d.name().is_ok_and(|n| n == name))
+            .ok_or_else(|| SstvError::AudioDevice(messages::device_input_not_found(name))),
+        None => host
+            .default_input_device()
+            .ok_or_else(|| SstvError::AudioDevice(messages::device_no_default_input().into())),
+    }
+}
+
+fn build_output<T>(
+    device: &Device,
+    config: &StreamConfig,
+    data: &Arc<Vec<f32>>,
+    position: &Arc<AtomicUsize>,
+) -> Result<cpal::Stream>
+where
+    T: SizedSample + FromSample<f32>,
+{
+    let channels = config.channels as usize;
+    let data = Arc::clone(data);
+    let position = Arc::clone(position);
+
+    let stream = device
+        .build_output_stream(
+            config,
+            move |out: &mut [T], _: &cpal::OutputCallbackInfo| {
+                let mut pos = position.load(Ordering::Relaxed);
+                // Один и тот же моно-сэмпл во все каналы кадра
+                for frame in out.chunks_mut(channels) {
+                    let value = data.get(pos).copied().unwrap_or(0.0);
+                    frame.iter_mut().for_each(|s| *s = T::from_sample(value));
+                    pos += 1;
+                }
+                position.store(pos.min(data.len()), Ordering::Relaxed);
+            },
+            |e| tracing::error!("{}", messages::output_stream_failed(&e.to_string())),
+            None,
+        )
+        .map_err(|e| device_error(messages::device_open_output(), e))?;
+    Ok(stream)
+}
+
+fn build_input<T>(
+    device: &Device,
+    config: &StreamConfig,
+    buffer: &Arc<Mutex<Vec<f32>>>,
+) -> Result<cpal::Stream>
+where
+    T: SizedSample,
+    f32: FromSample<T>,
+{
+    let channels = config.channels as usize;
+    let buffer = Arc::clone(buffer);
+
+    let stream = device
+        .build_input_stream(
+            config,
+            move |input: &[T], _: &cpal::InputCallbackInfo| {
+                let mono: Vec<f32> = input.iter().map(|s| s.to_sample::<f32>()).collect();
+                if let Ok(mut buf) = buffer.lock() {
+                    buf.extend(wav::downmix(&mono, channels));
+                }
+            },
+            |e| tracing::error!("{}", messages::input_stream_failed(&e.to_string())),
+            None,
+        )
+        .map_err(|e| device_error(messages::device_open_input(), e))?;
+    Ok(stream)
+}
+
+/// Ошибку cpal дополняет тем, что с устройством пытались сделать
+pub(crate) fn device_error(what: &str, error: impl std::fmt::Display) -> SstvError {
+    SstvError::AudioDevice(format!("{}: {}", what, error))
+}
@@ -1,102 +1,1864 @@
 use anyhow::{Context, Result};
-use clap::Parser;
-use image::ImageReader;
-use sstv_processor::{EnvelopeKind, NoiseParams, ProcessingParams, RetarderParams, SSTVProcessor};
+use clap::{CommandFactory, FromArgMatches, Parser, Subcommand};
+use image::{DynamicImage, ImageFormat, ImageReader};
+use rayon::prelude::*;
+use sstv_processor::messages::help;
+use sstv_processor::{
+    AppParams, CommandPaths, DecodeReport, DecoderArgs, ManifestFormat, ProcessingParams,
+    RunArtifacts, RunExporter, SSTVProcessor, Salvage, SampleBuffer, ScanParams, ServeLimits,
+    Server, Signal, Snapshot, SstvError, StationImport, StreamSink, SweepAxes, SweepRange,
+    SweepRunner, Telemetry, TelemetryStrip, VectorSuite,
+};
+use sstv_processor::{
+    ConformanceParams, ConformanceReport, Dashboard, DegradationCurve, EncoderBackend, FuzzParams,
+    Language, LiveParams, Location, LocationSource, Monitor, MonitorParams, MonitorSource,
+    PcmFormat, Ramp, SAMPLE_RATE, SequenceRamps, SequenceRunner, SimdLevel, SquelchParams,
+    Suggestions, ThreadParams, ThreadPriority, WatermarkParams, advice, archive, audio,
+    conformance, curve, dsp, ensemble, export::RUN_MANIFEST, fuzz, labels, live, location,
+    messages, metadata, metrics, nulling, scan, sequence, sequence::DEFAULT_FRAME_MS, threads,
+    vectors, watermark, wav,
+};
+use std::fs::File;
+use std::io::Write;
+use std::net::TcpStream;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
 
 /// Параметры CLI
 #[derive(Parser)]
-#[command(author, version, about)]
+#[command(
+    author,
+    version,
+    about,
+    args_conflicts_with_subcommands = true,
+    subcommand_negates_reqs = true
+)]
 struct Args {
-    /// Главная картинка PNG/JPG
-    #[arg(short = 'i', long)]
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    #[arg(short = 'i', long, required = true, help = help::input())]
+    input: Option<String>,
+
+    #[arg(short = 'o', long, default_value = "output.png", help = help::output())]
+    output: String,
+
+    #[arg(long, value_name = "FILE|tcp://ADDR", help = help::stream())]
+    stream: Option<String>,
+
+    #[command(flatten)]
+    channel: AppParams,
+
+    #[arg(long, help = help::no_probe())]
+    no_probe: bool,
+
+    #[arg(long, help = help::estimate())]
+    estimate: bool,
+
+    #[arg(long, conflicts_with_all = ["play", "bundle", "estimate", "salvage"], help = help::blocks())]
+    blocks: bool,
+
+    #[arg(long, value_name = "DIR", help = help::bundle())]
+    bundle: Option<PathBuf>,
+
+    #[arg(long, help = help::salvage())]
+    salvage: bool,
+
+    #[arg(
+        long,
+        value_name = "FILE",
+        num_args = 0..=1,
+        default_missing_value = "debug.wav",
+        conflicts_with = "blocks",
+        help = help::debug_wav()
+    )]
+    debug_wav: Option<PathBuf>,
+
+    #[arg(
+        long,
+        value_name = "N",
+        default_value_t = 1,
+        conflicts_with_all = ["blocks", "stream", "bundle", "salvage"],
+        help = help::ensemble()
+    )]
+    ensemble: usize,
+
+    #[arg(long, value_name = "FILE", requires = "ensemble", help = help::ensemble_variance())]
+    ensemble_variance: Option<PathBuf>,
+
+    #[arg(long, global = true, default_value = "en", help = help::lang())]
+    lang: Language,
+
+    #[arg(long, global = true, default_value = "auto", help = help::simd())]
+    simd: SimdLevel,
+
+    #[arg(long, global = true, default_value_t = 0, help = help::threads())]
+    threads: usize,
+
+    #[arg(long, global = true, default_value = "normal", help = help::priority())]
+    priority: ThreadPriority,
+
+    // ── Звук ─────────────────────────────────────────────────
+    #[arg(long, help = help::play())]
+    play: bool,
+
+    #[arg(long, help = help::output_device())]
+    output_device: Option<String>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    #[command(about = help::command_decode())]
+    Decode(DecodeArgs),
+    #[command(about = help::command_batch())]
+    Batch(BatchArgs),
+    #[command(about = help::command_sweep())]
+    Sweep(SweepArgs),
+    #[command(about = help::command_sequence())]
+    Sequence(SequenceArgs),
+    #[cfg(feature = "script")]
+    #[command(about = help::command_script())]
+    Script(ScriptArgs),
+    #[command(about = help::command_null())]
+    Null(NullArgs),
+    #[command(about = help::command_conformance())]
+    Conformance(ConformanceArgs),
+    #[command(about = help::command_fuzz())]
+    Fuzz(FuzzArgs),
+    #[command(about = help::command_snapshot())]
+    Snapshot(SnapshotArgs),
+    #[command(about = help::command_watermark())]
+    Watermark(WatermarkArgs),
+    #[command(about = help::command_channel())]
+    Channel(ChannelArgs),
+    #[command(about = help::command_serve())]
+    Serve(ServeArgs),
+    #[command(about = help::command_monitor())]
+    Monitor(MonitorArgs),
+    #[command(about = help::command_pack())]
+    Pack(PackArgs),
+    #[command(about = help::command_unpack())]
+    Unpack(UnpackArgs),
+    #[command(about = help::command_vectors())]
+    Vectors(VectorsArgs),
+    #[command(about = help::command_import())]
+    Import(ImportArgs),
+}
+
+/// Параметры упаковки папки прогона
+#[derive(clap::Args)]
+struct PackArgs {
+    #[arg(value_name = "DIR", help = help::pack_dir())]
+    dir: PathBuf,
+
+    #[arg(short = 'o', long, help = help::pack_out())]
+    out: Option<PathBuf>,
+}
+
+/// Параметры распаковки архива прогона
+#[derive(clap::Args)]
+struct UnpackArgs {
+    #[arg(value_name = "ZIP", help = help::unpack_archive())]
+    archive: PathBuf,
+
+    #[arg(short = 'o', long, default_value = ".", help = help::unpack_out())]
+    out: PathBuf,
+}
+
+/// Параметры импорта настроек MMSSTV или QSSTV
+#[derive(clap::Args)]
+struct ImportArgs {
+    #[arg(value_name = "FILE", help = help::import_file())]
+    file: PathBuf,
+
+    #[arg(short = 'o', long, default_value = "station.toml", help = help::import_out())]
+    out: PathBuf,
+
+    #[arg(long, value_name = "FILE", help = help::import_base())]
+    base: Option<PathBuf>,
+}
+
+/// Параметры набора тестовых сигналов
+#[derive(clap::Args)]
+struct VectorsArgs {
+    #[arg(short = 'o', long, default_value = "vectors", help = help::vectors_out())]
+    out: PathBuf,
+
+    #[arg(long, value_enum, default_value = "json", help = help::vectors_manifest())]
+    manifest: ManifestFormat,
+
+    #[arg(long, default_value_t = vectors::DEFAULT_SEED, help = help::vectors_seed())]
+    seed: u64,
+
+    #[arg(short = 'j', long, default_value_t = 0, help = help::jobs())]
+    jobs: usize,
+}
+
+/// Параметры сервера заданий; флаги обработки — параметры заданий без своих
+#[derive(clap::Args)]
+struct ServeArgs {
+    #[arg(long, value_name = "ADDR", help = help::serve_listen())]
+    listen: Option<String>,
+
+    #[arg(long, value_name = "DIR", help = help::serve_root())]
+    root: Option<PathBuf>,
+
+    #[arg(
+        long,
+        default_value_t = 1,
+        value_parser = clap::value_parser!(u32).range(1..),
+        help = help::serve_max_jobs()
+    )]
+    max_jobs: u32,
+
+    #[arg(
+        long,
+        default_value_t = ServeLimits::default().max_queue,
+        help = help::serve_max_queue()
+    )]
+    max_queue: usize,
+
+    #[arg(
+        long,
+        default_value_t = ServeLimits::default().max_pixels,
+        help = help::serve_max_pixels()
+    )]
+    max_pixels: u64,
+
+    #[arg(
+        long,
+        default_value_t = ServeLimits::default().max_request_bytes,
+        help = help::serve_max_request_bytes()
+    )]
+    max_request_bytes: usize,
+
+    #[arg(
+        long,
+        default_value_t = ServeLimits::default().max_sample_rate,
+        help = help::serve_max_sample_rate()
+    )]
+    max_sample_rate: u32,
+
+    #[arg(
+        long,
+        default_value_t = ServeLimits::default().max_signal_samples,
+        help = help::serve_max_signal_samples()
+    )]
+    max_signal_samples: u64,
+
+    #[arg(
+        long,
+        default_value_t = ServeLimits::default().max_connections,
+        help = help::serve_max_connections()
+    )]
+    max_connections: usize,
+
+    #[command(flatten)]
+    channel: AppParams,
+}
+
+/// Параметры приёмника без окна
+#[derive(clap::Args)]
+struct MonitorArgs {
+    #[arg(long, conflicts_with = "pcm", help = help::monitor_input_device())]
+    input_device: Option<String>,
+
+    #[arg(long, value_name = "FILE|-", help = help::monitor_pcm())]
+    pcm: Option<PathBuf>,
+
+    #[arg(long, default_value_t = 48000, help = help::monitor_pcm_rate())]
+    pcm_rate: u32,
+
+    #[arg(long, default_value = "s16le", help = help::monitor_pcm_format())]
+    pcm_format: PcmFormat,
+
+    #[arg(short = 'o', long, default_value = "received", help = help::monitor_out())]
+    out: PathBuf,
+
+    #[arg(long, help = help::monitor_secs())]
+    secs: Option<u64>,
+
+    #[arg(long, allow_hyphen_values = true, conflicts_with = "gpsd", help = help::monitor_location())]
+    location: Option<Location>,
+
+    #[arg(
+        long,
+        value_name = "ADDR",
+        num_args = 0..=1,
+        default_missing_value = location::GPSD_DEFAULT,
+        help = help::monitor_gpsd()
+    )]
+    gpsd: Option<String>,
+
+    #[arg(long, value_name = "ADDR", help = help::monitor_http())]
+    http: Option<String>,
+
+    #[arg(long, help = help::monitor_squelch())]
+    squelch: bool,
+
+    #[arg(
+        long,
+        allow_hyphen_values = true,
+        default_value_t = SquelchParams::default().open_db,
+        help = help::monitor_squelch_open_db()
+    )]
+    squelch_open_db: f32,
+
+    #[arg(
+        long,
+        default_value_t = SquelchParams::default().hysteresis_db,
+        help = help::monitor_squelch_hysteresis()
+    )]
+    squelch_hysteresis: f32,
+
+    #[arg(
+        long,
+        allow_hyphen_values = true,
+        default_value_t = SquelchParams::default().min_level_db,
+        help = help::monitor_squelch_min_level()
+    )]
+    squelch_min_level: f32,
+
+    #[arg(
+        long,
+        default_value_t = SquelchParams::default().hold_secs,
+        help = help::monitor_squelch_hold()
+    )]
+    squelch_hold: f32,
+
+    #[arg(long, requires = "squelch", help = help::monitor_squelch_any_vis())]
+    squelch_any_vis: bool,
+
+    #[cfg(feature = "webhook")]
+    #[arg(long, value_name = "URL", help = help::monitor_webhook())]
+    webhook: Option<String>,
+
+    #[cfg(feature = "webhook")]
+    #[arg(long, default_value = "auto", requires = "webhook", help = help::monitor_webhook_kind())]
+    webhook_kind: sstv_processor::WebhookKind,
+
+    #[cfg(feature = "webhook")]
+    #[arg(long, requires = "webhook", help = help::monitor_webhook_chat())]
+    webhook_chat: Option<String>,
+
+    #[command(flatten)]
+    decoder: DecoderArgs,
+}
+
+/// Параметры живого канала между двумя звуковыми устройствами
+#[derive(clap::Args)]
+struct ChannelArgs {
+    #[arg(long, default_value_t = 180, help = help::channel_secs())]
+    secs: u64,
+
+    #[arg(long, help = help::channel_input_device())]
+    input_device: Option<String>,
+
+    #[arg(long, help = help::channel_output_device())]
+    output_device: Option<String>,
+
+    #[arg(long, default_value_t = LiveParams::default().block_ms, help = help::channel_block_ms())]
+    block_ms: f32,
+
+    #[arg(
+        long,
+        default_value_t = LiveParams::default().input_rms,
+        help = help::channel_input_rms()
+    )]
+    input_rms: f32,
+
+    #[arg(long, help = help::channel_checkpoint())]
+    checkpoint: Option<PathBuf>,
+
+    #[arg(long, requires = "checkpoint", help = help::channel_checkpoint_every())]
+    checkpoint_every: Option<f32>,
+
+    #[arg(long, help = help::channel_resume())]
+    resume: Option<PathBuf>,
+
+    #[command(flatten)]
+    channel: AppParams,
+}
+
+/// Параметры снимка сравнения
+#[derive(clap::Args)]
+struct SnapshotArgs {
+    #[arg(short = 'i', long, help = help::input())]
+    input: String,
+
+    #[arg(short = 'o', long, default_value = "snapshot.png", help = help::snapshot_out())]
+    out: String,
+
+    #[command(flatten)]
+    channel: AppParams,
+}
+
+/// Параметры проверки стойкости канала меткой
+#[derive(clap::Args)]
+struct WatermarkArgs {
+    #[arg(short = 'i', long, help = help::input())]
+    input: String,
+
+    #[arg(long, default_value_t = WatermarkParams::default().seed, help = help::watermark_seed())]
+    seed: u64,
+
+    #[arg(long, default_value_t = WatermarkParams::default().bits, help = help::watermark_bits())]
+    bits: usize,
+
+    #[arg(long, default_value_t = WatermarkParams::default().cell, help = help::watermark_cell())]
+    cell: u32,
+
+    #[arg(
+        long,
+        default_value_t = WatermarkParams::default().strength,
+        help = help::watermark_strength()
+    )]
+    strength: f32,
+
+    #[command(flatten)]
+    channel: AppParams,
+}
+
+/// Параметры поиска сбоев случайными параметрами
+#[derive(clap::Args)]
+struct FuzzArgs {
+    #[arg(long, default_value_t = FuzzParams::default().seed, help = help::fuzz_seed())]
+    seed: u64,
+
+    #[arg(
+        long,
+        default_value_t = FuzzParams::default().effect_cases,
+        help = help::fuzz_effect_cases()
+    )]
+    effect_cases: usize,
+
+    #[arg(
+        long,
+        default_value_t = FuzzParams::default().decode_cases,
+        help = help::fuzz_decode_cases()
+    )]
+    decode_cases: usize,
+
+    #[arg(
+        long,
+        default_value_t = FuzzParams::default().process_cases,
+        help = help::fuzz_process_cases()
+    )]
+    process_cases: usize,
+
+    #[arg(
+        long,
+        default_value_t = FuzzParams::default().differential_cases,
+        help = help::fuzz_differential_cases()
+    )]
+    differential_cases: usize,
+
+    #[arg(
+        long,
+        default_value_t = FuzzParams::default().min_agreement_db,
+        help = help::fuzz_min_agreement_db()
+    )]
+    min_agreement_db: f64,
+
+    #[arg(long, default_value_t = FuzzParams::default().max_len, help = help::fuzz_max_len())]
+    max_len: usize,
+}
+
+/// Параметры проверки соответствия
+#[derive(clap::Args)]
+struct ConformanceArgs {
+    #[arg(long, default_value = "native", help = help::conformance_encoder())]
+    encoder: EncoderBackend,
+
+    #[arg(short = 'i', long, help = help::conformance_input())]
+    input: Option<String>,
+
+    #[arg(long, value_name = "DIR", help = help::conformance_write_golden())]
+    write_golden: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "DIR",
+        conflicts_with = "write_golden",
+        help = help::conformance_golden()
+    )]
+    golden: Option<String>,
+
+    #[arg(
+        long,
+        default_value_t = ConformanceParams::default().freq_tolerance_hz,
+        help = help::conformance_freq_tolerance_hz()
+    )]
+    freq_tolerance_hz: f64,
+
+    #[arg(
+        long,
+        default_value_t = ConformanceParams::default().edge_tolerance_us,
+        help = help::conformance_edge_tolerance_us()
+    )]
+    edge_tolerance_us: f64,
+
+    #[arg(
+        long,
+        default_value_t = ConformanceParams::default().period_tolerance_ppm,
+        help = help::conformance_period_tolerance_ppm()
+    )]
+    period_tolerance_ppm: f64,
+}
+
+/// Параметры проверки вычитанием
+#[derive(clap::Args)]
+struct NullArgs {
+    #[arg(short = 'a', long, help = help::null_reference())]
+    reference: String,
+
+    #[arg(short = 'b', long, help = help::null_candidate())]
+    candidate: String,
+
+    #[arg(long, default_value_t = 100.0, help = help::null_max_offset_ms())]
+    max_offset_ms: f32,
+
+    #[arg(long, default_value_t = nulling::TRANSPARENT_DEPTH_DB, help = help::null_min_depth_db())]
+    min_depth_db: f64,
+}
+
+/// Параметры пакетной обработки
+#[derive(clap::Args)]
+struct BatchArgs {
+    #[arg(short = 'i', long, value_name = "DIR|GLOB", help = help::batch_input())]
+    input: String,
+
+    #[arg(
+        short = 'o',
+        long,
+        value_name = "DIR",
+        default_value = "batch_output",
+        help = help::output_dir()
+    )]
+    output_dir: String,
+
+    #[arg(long, help = help::wav())]
+    wav: bool,
+
+    #[arg(long, help = help::masks())]
+    masks: bool,
+
+    #[arg(short = 'j', long, default_value_t = 0, help = help::jobs())]
+    jobs: usize,
+
+    #[command(flatten)]
+    channel: AppParams,
+}
+
+/// Параметры перебора
+#[derive(clap::Args)]
+struct SweepArgs {
+    #[arg(short = 'i', long, help = help::input())]
+    input: String,
+
+    #[arg(
+        short = 'o',
+        long,
+        value_name = "DIR",
+        default_value = "sweep_output",
+        help = help::sweep_output_dir()
+    )]
+    output_dir: String,
+
+    #[arg(long, value_name = "RANGE", help = help::sweep_noise_range())]
+    noise_range: Option<SweepRange>,
+
+    #[arg(long, value_name = "RANGE", help = help::sweep_level_range())]
+    level_range: Option<SweepRange>,
+
+    #[arg(long, value_name = "RANGE", help = help::sweep_delay_range())]
+    delay_range: Option<SweepRange>,
+
+    #[arg(long, value_enum, default_value = "csv", help = help::sweep_manifest())]
+    manifest: ManifestFormat,
+
+    #[arg(long, help = help::wav())]
+    wav: bool,
+
+    #[arg(long, help = help::sweep_coarse())]
+    coarse: bool,
+
+    #[arg(long, requires = "noise_range", help = help::sweep_curve())]
+    curve: bool,
+
+    #[cfg(feature = "plot")]
+    #[arg(long, requires = "noise_range", help = help::sweep_curve_chart())]
+    curve_chart: bool,
+
+    #[arg(short = 'j', long, default_value_t = 0, help = help::jobs())]
+    jobs: usize,
+
+    #[command(flatten)]
+    channel: AppParams,
+}
+
+/// Параметры последовательности кадров
+#[derive(clap::Args)]
+struct SequenceArgs {
+    #[arg(short = 'i', long, value_name = "GIF|DIR", help = help::sequence_input())]
     input: String,
 
-    /// Итоговый файл
-    #[arg(short = 'o', long, default_value = "output.png")]
+    #[arg(
+        short = 'o',
+        long,
+        value_name = "GIF|DIR",
+        default_value = "sequence.gif",
+        help = help::sequence_output()
+    )]
     output: String,
 
-    // ── Шум ──────────────────────────────────────────────────
-    /// Уровень шума 0–100
-    #[arg(short = 'n', long, default_value_t = 0u8,
-          value_parser = clap::value_parser!(u8).range(0..=100))]
-    noise: u8,
+    #[arg(long, value_name = "RAMP", help = help::sequence_noise_ramp())]
+    noise_ramp: Option<Ramp>,
+
+    #[arg(long, value_name = "RAMP", help = help::sequence_level_ramp())]
+    level_ramp: Option<Ramp>,
+
+    #[arg(long, value_name = "RAMP", help = help::sequence_delay_ramp())]
+    delay_ramp: Option<Ramp>,
+
+    #[arg(long, default_value_t = DEFAULT_FRAME_MS, help = help::sequence_frame_ms())]
+    frame_ms: u32,
+
+    #[arg(short = 'j', long, default_value_t = 0, help = help::jobs())]
+    jobs: usize,
 
-    /// Огибающая шума
-    #[arg(long, default_value = "const")]
-    noise_env: EnvelopeKind,
+    #[command(flatten)]
+    channel: AppParams,
+}
+
+/// Параметры выполнения сценария
+#[cfg(feature = "script")]
+#[derive(clap::Args)]
+struct ScriptArgs {
+    #[arg(value_name = "FILE", help = help::script_path())]
+    path: PathBuf,
+}
 
-    /// Коэффициент повторения огибающей шума
-    #[arg(long, default_value_t = 1.0)]
-    noise_repeat: f32,
+/// Параметры декодирования WAV
+#[derive(clap::Args)]
+struct DecodeArgs {
+    #[arg(
+        short = 'i',
+        long,
+        required_unless_present = "listen",
+        conflicts_with = "listen",
+        help = help::decode_input()
+    )]
+    input: Option<String>,
 
-    // ── Ретардер ─────────────────────────────────────────────
-    /// Картинка-"призрак"
-    #[arg(short = 'r', long)]
-    retarder: Option<String>,
+    #[arg(long, help = help::decode_listen())]
+    listen: Option<u64>,
 
-    /// Уровень ретардера 0–1
-    #[arg(long, default_value_t = 0.3)]
-    level: f32,
+    #[arg(long, help = help::decode_input_device())]
+    input_device: Option<String>,
+
+    #[arg(short = 'o', long, default_value = "output.png", help = help::output())]
+    output: String,
 
-    /// Огибающая ретардера
-    #[arg(long, default_value = "const")]
-    ret_env: EnvelopeKind,
+    #[arg(long, value_name = "FILE|tcp://ADDR", conflicts_with = "all", help = help::stream())]
+    stream: Option<String>,
 
-    /// Коэффициент повторения ретардера
-    #[arg(long, default_value_t = 1.0)]
-    ret_repeat: f32,
+    #[arg(long, help = help::decode_telemetry())]
+    telemetry: bool,
 
-    /// Задержка ретардера, мс
-    #[arg(long, default_value_t = 0u32)]
-    delay_ms: u32,
+    #[arg(long, conflicts_with = "all", help = help::decode_salvage())]
+    salvage: bool,
+
+    #[arg(long, help = help::decode_all())]
+    all: bool,
+
+    #[arg(short = 'j', long, default_value_t = 0, requires = "all", help = help::decode_jobs())]
+    jobs: usize,
+
+    #[command(flatten)]
+    decoder: DecoderArgs,
+}
+
+/// Язык из `--lang` в командной строке, до разбора остальных аргументов; английский,
+/// если флага нет или значение неизвестно (тогда ошибку выдаст разбор)
+fn requested_language() -> Language {
+    let args: Vec<String> = std::env::args().collect();
+    args.iter()
+        .enumerate()
+        .find_map(|(i, arg)| match arg.strip_prefix("--lang") {
+            Some("") => args.get(i + 1).map(String::as_str),
+            Some(value) => value.strip_prefix('='),
+            None => None,
+        })
+        .and_then(|value| <Language as clap::ValueEnum>::from_str(value, true).ok())
+        .unwrap_or_default()
 }
 
 fn main() -> Result<()> {
-    let args = Args::parse();
+    // Справка строится на языке --lang, поэтому язык нужен ещё до разбора
+    messages::set_language(requested_language());
+    // Разбор отдельно от сборки аргументов: пресету нужно отличать явные флаги от умолчаний
+    let matches = Args::command().get_matches();
+    let mut args = Args::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    messages::set_language(args.lang);
+    dsp::set_simd_level(args.simd)?;
+    threads::configure(ThreadParams {
+        threads: args.threads,
+        priority: args.priority,
+    })?;
+
+    match (&mut args.command, matches.subcommand()) {
+        (Some(Command::Batch(batch)), Some((_, sub))) => batch.channel.apply_preset(sub)?,
+        (Some(Command::Sweep(sweep)), Some((_, sub))) => sweep.channel.apply_preset(sub)?,
+        (Some(Command::Sequence(sequence)), Some((_, sub))) => {
+            sequence.channel.apply_preset(sub)?
+        }
+        (Some(Command::Snapshot(snapshot)), Some((_, sub))) => {
+            snapshot.channel.apply_preset(sub)?
+        }
+        (Some(Command::Watermark(watermark)), Some((_, sub))) => {
+            watermark.channel.apply_preset(sub)?
+        }
+        (Some(Command::Channel(channel)), Some((_, sub))) => channel.channel.apply_preset(sub)?,
+        (Some(Command::Serve(serve)), Some((_, sub))) => serve.channel.apply_preset(sub)?,
+        (None, _) => args.channel.apply_preset(&matches)?,
+        _ => {}
+    }
+
+    match &args.command {
+        Some(Command::Decode(decode)) => run_decode(decode),
+        Some(Command::Batch(batch)) => run_batch(batch),
+        Some(Command::Sweep(sweep)) => run_sweep(sweep),
+        Some(Command::Sequence(sequence)) => run_sequence(sequence),
+        #[cfg(feature = "script")]
+        Some(Command::Script(script)) => Ok(sstv_processor::script::run_file(&script.path)?),
+        Some(Command::Null(null)) => run_null(null),
+        Some(Command::Conformance(conformance)) => run_conformance(conformance),
+        Some(Command::Fuzz(fuzz)) => run_fuzz(fuzz),
+        Some(Command::Snapshot(snapshot)) => run_snapshot(snapshot),
+        Some(Command::Watermark(watermark)) => run_watermark(watermark),
+        Some(Command::Channel(channel)) => run_channel(channel),
+        Some(Command::Serve(serve)) => run_serve(serve),
+        Some(Command::Monitor(monitor)) => run_monitor(monitor),
+        Some(Command::Pack(pack)) => run_pack(pack),
+        Some(Command::Unpack(unpack)) => run_unpack(unpack),
+        Some(Command::Vectors(vectors)) => run_vectors(vectors),
+        Some(Command::Import(import)) => run_import(import),
+        None => run_process(&args),
+    }
+}
+
+fn run_process(args: &Args) -> Result<()> {
+    let input = args.input.as_deref().context(messages::cli_no_input())?;
 
     // Читаем исходную картинку
-    let main_image = ImageReader::open(&args.input)?
+    let main_image = read_image(input, messages::cli_source_image())?;
+
+    let retarder_image = args.channel.read_retarder()?;
+    let params = args.channel.to_params()?;
+    print_param_warnings(&params);
+    if args.estimate {
+        let mut processor = args.channel.processor(params)?;
+        let summary = processor.analyze(&main_image, retarder_image.as_ref())?;
+        println!("{}", summary.describe());
+        return Ok(());
+    }
+    if !args.no_probe {
+        let probe = advice::probe(&params, &main_image, retarder_image.as_ref())?;
+        print_suggestions(&advice::recommend(&params, &probe));
+    }
+    let mut processor = args.channel.processor(params)?;
+    processor.set_debug_wav(args.debug_wav.clone());
+    if let Some(target) = &args.stream {
+        processor.set_image_sink(open_stream(target)?);
+    }
+
+    if args.ensemble > 1 {
+        return run_ensemble(args, &mut processor, &main_image, retarder_image.as_ref());
+    }
+
+    let result = if args.blocks {
+        processor.process_streaming(
+            &main_image,
+            retarder_image.as_ref(),
+            |_, _| {},
+            &AtomicBool::new(false),
+        )
+    } else {
+        processor.process(&main_image, retarder_image.as_ref())
+    };
+    let report = match result {
+        Ok(report) => report,
+        Err(e) => {
+            // Искажённый сигнал лежит в процессоре: растр, водопад и WAV — рядом с результатом
+            if args.salvage
+                && let Some(salvage) = processor.salvage(&e)
+            {
+                let mut paths = salvage.save(Path::new(&args.output))?;
+                let wav = Path::new(&args.output).with_extension("wav");
+                processor.write_wav(&wav.to_string_lossy(), processor.last_buffer())?;
+                paths.push(wav);
+                print_salvage(&salvage, &paths);
+            }
+            return Err(e.into());
+        }
+    };
+
+    if args.play {
+        println!("{}", messages::cli_playing());
+        audio::play(processor.last_buffer(), args.output_device.as_deref())?;
+    }
+
+    // Сохраняем
+    processor.save_result(&report.image, &args.output)?;
+
+    print_report(&report, processor.params().decoder.conceal);
+    // Надпись передана вместе с изображением: с ней и сравниваем
+    let sent = processor.params().overlay_params().apply(&main_image)?;
+    let space = processor.params().metric_space;
+    let quality = metrics::compare_in(&sent, &report.image, space);
+    println!("{}", messages::cli_quality(&quality.describe()));
+    // Сдвинутое или наклонённое изображение — ещё и оценка после совмещения
+    let (registered, registration) = metrics::compare_registered(&sent, &report.image, space);
+    if !registration.is_identity() {
+        println!(
+            "{}",
+            messages::cli_quality_registered(&registration.describe(), &registered.describe())
+        );
+    }
+    if args.channel.telemetry.is_some() {
+        print_telemetry(&report.image);
+    }
+    if let Some(root) = &args.bundle {
+        let paths = CommandPaths {
+            input: input.to_string(),
+            output: args.output.clone(),
+            retarder: args.channel.retarder.clone(),
+            qrm: args.channel.qrm.clone(),
+            noise_env_file: args.channel.noise_env_file.clone(),
+            ret_env_file: args.channel.ret_env_file.clone(),
+            timeline: args.channel.timeline.clone(),
+        };
+        let dir = RunExporter::new(root).export(&RunArtifacts {
+            processor: &processor,
+            paths: &paths,
+            original: &main_image,
+            retarder: retarder_image.as_ref(),
+            report: &report,
+            degraded: processor.last_buffer(),
+        })?;
+        println!("{}", messages::cli_bundle(&dir.display().to_string()));
+    }
+    println!("{}", messages::cli_done(&args.output));
+    Ok(())
+}
+
+/// Основной режим с `--ensemble`: среднее прогонов вместо одного результата
+fn run_ensemble(
+    args: &Args,
+    processor: &mut SSTVProcessor,
+    image: &DynamicImage,
+    retarder: Option<&DynamicImage>,
+) -> Result<()> {
+    let ensemble = ensemble::run(processor, image, retarder, args.ensemble)?;
+    if args.play {
+        println!("{}", messages::cli_playing());
+        audio::play(processor.last_buffer(), args.output_device.as_deref())?;
+    }
+    processor.save_result(&ensemble.mean, &args.output)?;
+
+    println!("{}", ensemble.describe());
+    println!("{}", messages::cli_quality(&ensemble.combined.describe()));
+    if let Some(path) = &args.ensemble_variance {
+        ensemble
+            .variance_image()
+            .save(path)
+            .with_context(|| messages::save_failed(&path.display().to_string()))?;
+    }
+    println!("{}", messages::cli_done(&args.output));
+    Ok(())
+}
+
+/// Читает и декодирует изображение; `what` — что читается, для сообщения об ошибке
+fn read_image(path: &str, what: &str) -> Result<DynamicImage> {
+    ImageReader::open(path)
+        .with_context(|| messages::open_failed(path))?
         .decode()
-        .with_context(|| "Не смог декодировать исходное изображение")?;
-
-    // Читаем ретардер, если указан
-    let retarder_image = if let Some(path) = &args.retarder {
-        Some(
-            ImageReader::open(path)?
-                .decode()
-                .with_context(|| "Не смог декодировать ретардер-картинку")?,
+        .with_context(|| messages::decode_failed(what))
+}
+
+/// Получатель `--stream`: PPM по строкам в файл или в TCP-соединение
+fn open_stream(target: &str) -> Result<StreamSink<Box<dyn Write + Send>>> {
+    let writer: Box<dyn Write + Send> = match target.strip_prefix("tcp://") {
+        Some(addr) => {
+            Box::new(TcpStream::connect(addr).with_context(|| messages::cli_connect_failed(addr))?)
+        }
+        None => Box::new(File::create(target).with_context(|| messages::create_failed(target))?),
+    };
+    Ok(StreamSink::new(writer))
+}
+
+fn run_decode(args: &DecodeArgs) -> Result<()> {
+    if args.all {
+        return run_decode_all(args);
+    }
+    let params = ProcessingParams {
+        decoder: args.decoder.to_params(),
+        ..Default::default()
+    };
+    let mut processor = SSTVProcessor::builder().params(params).build()?;
+    if let Some(target) = &args.stream {
+        processor.set_image_sink(open_stream(target)?);
+    }
+
+    let signal = match (&args.input, args.listen) {
+        (Some(path), _) => wav::read_wav(path)?,
+        (None, Some(secs)) => {
+            println!("{}", messages::cli_recording(secs));
+            audio::capture(Duration::from_secs(secs), args.input_device.as_deref())?
+        }
+        (None, None) => unreachable!("clap требует --input или --listen"),
+    };
+    let report = match processor.decode_samples(&signal) {
+        Ok(report) => report,
+        Err(e @ SstvError::DecodeNotFound(_)) if args.salvage => {
+            let params = processor.params();
+            let salvage = Salvage::new(&signal, &params.decoder, params.quality, &e);
+            let mut paths = salvage.save(Path::new(&args.output))?;
+            // Запись с микрофона больше нигде не сохранена
+            if args.input.is_none() {
+                let wav = Path::new(&args.output).with_extension("wav");
+                wav::write_wav(&wav.to_string_lossy(), &signal)?;
+                paths.push(wav);
+            }
+            print_salvage(&salvage, &paths);
+            return Err(e.into());
+        }
+        Err(e) => return Err(e.into()),
+    };
+
+    // Принятые метаданные уходят в текстовые блоки PNG
+    processor.update_params(|p| p.metadata.fields = report.metadata.clone())?;
+    processor.save_result(&report.image, &args.output)?;
+
+    print_report(&report, processor.params().decoder.conceal);
+    if args.telemetry {
+        print_telemetry(&report.image);
+    }
+    println!("{}", messages::cli_done(&args.output));
+    Ok(())
+}
+
+/// Декодирует все передачи длинной записи, см. [`scan::decode_all`]
+fn run_decode_all(args: &DecodeArgs) -> Result<()> {
+    let signal = match (&args.input, args.listen) {
+        (Some(path), _) => Signal::from_wav(path)?,
+        (None, Some(secs)) => {
+            println!("{}", messages::cli_recording(secs));
+            Signal::from_buffer(audio::capture(
+                Duration::from_secs(secs),
+                args.input_device.as_deref(),
+            )?)
+        }
+        (None, None) => unreachable!("clap требует --input или --listen"),
+    };
+    let scan = ScanParams {
+        max_workers: args.jobs,
+    };
+
+    let started = Instant::now();
+    let images = scan::decode_all(&signal, &args.decoder.to_params(), &scan)?;
+    if images.is_empty() {
+        anyhow::bail!(messages::cli_no_transmissions());
+    }
+
+    let mut failed = 0;
+    for (n, scanned) in images.iter().enumerate() {
+        let at = scanned.transmission.start_secs();
+        let outcome = match &scanned.outcome {
+            Ok(outcome) => outcome,
+            Err(e) => {
+                failed += 1;
+                eprintln!(
+                    "{}",
+                    messages::cli_transmission_failed(n + 1, at, &format!("{:#}", e))
+                );
+                continue;
+            }
+        };
+        let output = numbered_path(&args.output, n + 1);
+        outcome
+            .image
+            .save_with_format(&output, ImageFormat::Png)
+            .with_context(|| messages::result_save_failed(&output))?;
+        let mut details = vec![output];
+        if let Some(mean) = outcome.mean_confidence() {
+            details.push(messages::cli_confidence(mean * 100.0));
+        }
+        if let Some(vis) = &outcome.vis_detected {
+            details.push(format!("VIS {}", vis.code));
+        }
+        println!(
+            "{}",
+            messages::cli_transmission(n + 1, at, &details.join(", "))
+        );
+        if args.telemetry {
+            print_telemetry(&outcome.image);
+        }
+    }
+
+    println!(
+        "{}",
+        messages::cli_decoded_transmissions(
+            images.len() - failed,
+            images.len(),
+            started.elapsed().as_secs_f64()
         )
+    );
+    if failed > 0 {
+        anyhow::bail!(messages::cli_transmissions_failed(failed));
+    }
+    Ok(())
+}
+
+/// Путь `output` с номером перед расширением: output.png → output_3.png
+fn numbered_path(output: &str, n: usize) -> String {
+    let path = Path::new(output);
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let name = match path.extension() {
+        Some(ext) => format!("{}_{}.{}", stem, n, ext.to_string_lossy()),
+        None => format!("{}_{}", stem, n),
+    };
+    path.with_file_name(name).to_string_lossy().into_owned()
+}
+
+fn run_watermark(args: &WatermarkArgs) -> Result<()> {
+    let main_image = read_image(&args.input, messages::cli_source_image())?;
+    let params = args.channel.to_params()?;
+    print_param_warnings(&params);
+    let mut processor = args.channel.processor(params)?;
+    let report = watermark::measure(
+        &mut processor,
+        &main_image,
+        &WatermarkParams {
+            seed: args.seed,
+            bits: args.bits,
+            cell: args.cell,
+            strength: args.strength,
+        },
+    )?;
+    println!("{}", report.describe());
+    Ok(())
+}
+
+fn run_snapshot(args: &SnapshotArgs) -> Result<()> {
+    let main_image = read_image(&args.input, messages::cli_source_image())?;
+    let retarder_image = args.channel.read_retarder()?;
+    let params = args.channel.to_params()?;
+    print_param_warnings(&params);
+    let mut processor = args.channel.processor(params)?;
+    let report = processor.process(&main_image, retarder_image.as_ref())?;
+    print_report(&report, processor.params().decoder.conceal);
+
+    let sent = processor.params().overlay_params().apply(&main_image)?;
+    Snapshot::new(&sent, &report, processor.params().metric_space)
+        .render()
+        .save(&args.out)
+        .with_context(|| messages::cli_snapshot_save_failed(&args.out))?;
+    println!("{}", messages::cli_done(&args.out));
+    Ok(())
+}
+
+fn run_serve(args: &ServeArgs) -> Result<()> {
+    let params = args.channel.to_params()?;
+    print_param_warnings(&params);
+    let processors = (0..args.max_jobs)
+        .map(|_| args.channel.processor(params.clone()))
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut server = Server::with_limits(
+        processors,
+        ServeLimits {
+            max_queue: args.max_queue,
+            max_pixels: args.max_pixels,
+            max_request_bytes: args.max_request_bytes,
+            max_sample_rate: args.max_sample_rate,
+            max_signal_samples: args.max_signal_samples,
+            max_connections: args.max_connections,
+        },
+    )?;
+    if let Some(root) = &args.root {
+        server = server.with_root(root)?;
+    }
+    // stdout занят ответами, поэтому сообщения — в stderr
+    match &args.listen {
+        Some(addr) => {
+            eprintln!("{}", messages::cli_serve_listening(addr));
+            server.serve_tcp(addr)?;
+        }
+        None => {
+            eprintln!("{}", messages::cli_serve_stdin());
+            server.serve(std::io::stdin().lock(), std::io::stdout().lock())?;
+        }
+    }
+    eprintln!("{}", messages::cli_serve_done(server.jobs()));
+    Ok(())
+}
+
+fn run_pack(args: &PackArgs) -> Result<()> {
+    let out = args.out.clone().unwrap_or_else(|| {
+        let name = args
+            .dir
+            .canonicalize()
+            .ok()
+            .and_then(|dir| dir.file_name().map(|name| name.to_os_string()))
+            .unwrap_or_else(|| "run".into());
+        PathBuf::from(name).with_extension("zip")
+    });
+    let files = archive::pack(&args.dir, &out)?;
+    println!(
+        "{}",
+        messages::cli_packed(files, &out.display().to_string())
+    );
+    Ok(())
+}
+
+fn run_import(args: &ImportArgs) -> Result<()> {
+    let import = StationImport::from_file(&args.file)?;
+    let mut params = match &args.base {
+        Some(base) => ProcessingParams::from_file(base)?,
+        None => ProcessingParams::default(),
+    };
+    let notes = import.apply(&mut params);
+    params.to_file(&args.out)?;
+    println!(
+        "{}",
+        messages::cli_imported(&import.describe(), &args.out.display().to_string())
+    );
+    for note in notes {
+        println!("  {}", note);
+    }
+    Ok(())
+}
+
+fn run_unpack(args: &UnpackArgs) -> Result<()> {
+    let dir = archive::unpack(&args.archive, &args.out)?;
+    let manifest: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(dir.join(RUN_MANIFEST))?)?;
+    println!(
+        "{}",
+        messages::cli_unpacked(
+            &dir.display().to_string(),
+            manifest["command"].as_str().unwrap_or_default()
+        )
+    );
+    Ok(())
+}
+
+fn run_vectors(args: &VectorsArgs) -> Result<()> {
+    let mut suite = VectorSuite::new(&args.out);
+    suite.format = args.manifest;
+    suite.seed = args.seed;
+
+    let pool = threads::pool(args.jobs)?;
+    let total = conformance::MODES.len() * vectors::CASES.len();
+    let done = AtomicUsize::new(0);
+    println!(
+        "{}",
+        messages::cli_vectors_start(total, &args.out.display().to_string())
+    );
+    pool.install(|| {
+        suite.write(|entry| {
+            let k = done.fetch_add(1, Ordering::Relaxed) + 1;
+            println!("[{}/{}] {} ({})", k, total, entry.file, entry.crc32);
+        })
+    })?;
+    println!(
+        "{}",
+        messages::cli_vectors_done(&suite.manifest_path().display().to_string())
+    );
+    Ok(())
+}
+
+/// Слушает, пока идёт сигнал или не вышло `--secs`: каждое принятое изображение
+/// сохраняется в `--out` с метаданными и отметкой приёма и уходит в веб-панель
+fn run_monitor(args: &MonitorArgs) -> Result<()> {
+    let source = match &args.pcm {
+        Some(path) => MonitorSource::Pcm {
+            path: path.clone(),
+            sample_rate: args.pcm_rate,
+            format: args.pcm_format,
+        },
+        None => MonitorSource::Device(args.input_device.clone()),
+    };
+    let location = match (&args.gpsd, args.location) {
+        (Some(addr), _) => Some(LocationSource::Gpsd(addr.clone())),
+        (None, Some(location)) => Some(LocationSource::Static(location)),
+        (None, None) => None,
+    };
+    std::fs::create_dir_all(&args.out)
+        .with_context(|| messages::create_dir_failed(&args.out.display().to_string()))?;
+    #[cfg(feature = "webhook")]
+    let notifier = match &args.webhook {
+        Some(url) => Some(sstv_processor::Notifier::start(
+            sstv_processor::WebhookParams {
+                url: url.clone(),
+                kind: args.webhook_kind,
+                chat_id: args.webhook_chat.clone(),
+            },
+        )?),
+        None => None,
+    };
+
+    let monitor = Arc::new(Monitor::start(
+        MonitorParams {
+            source,
+            decoder: args.decoder.to_params(),
+            location,
+            squelch: SquelchParams {
+                enabled: args.squelch,
+                open_db: args.squelch_open_db,
+                hysteresis_db: args.squelch_hysteresis,
+                min_level_db: args.squelch_min_level,
+                hold_secs: args.squelch_hold,
+                require_vis: !args.squelch_any_vis,
+            },
+            ..Default::default()
+        },
+        || {},
+    ));
+    let dashboard = match &args.http {
+        Some(addr) => {
+            let dashboard = Dashboard::start(addr, Arc::clone(&monitor))?;
+            println!("{}", messages::cli_monitor_dashboard(addr));
+            Some(dashboard)
+        }
+        None => None,
+    };
+    let save = |dashboard: Option<&Dashboard>| -> Result<()> {
+        for received in monitor.take_received() {
+            let stem = received.stamp.utc().replace([':', '-'], "");
+            let mut path = args.out.join(format!("{}.png", stem));
+            for n in 2.. {
+                if !path.exists() {
+                    break;
+                }
+                path = args.out.join(format!("{}_{}.png", stem, n));
+            }
+            let image = DynamicImage::ImageRgb8(received.image.clone());
+            std::fs::write(
+                &path,
+                metadata::png_with_text(&image, &received.text_fields())?,
+            )
+            .with_context(|| messages::save_failed(&path.display().to_string()))?;
+            println!(
+                "{}",
+                messages::cli_monitor_saved(
+                    &path.display().to_string(),
+                    &received.stamp.describe()
+                )
+            );
+            if let Some(dashboard) = dashboard {
+                dashboard.push(&received)?;
+            }
+            #[cfg(feature = "webhook")]
+            if let Some(notifier) = &notifier {
+                notifier.notify(&received)?;
+            }
+        }
+        Ok(())
+    };
+
+    let deadline = args
+        .secs
+        .map(|secs| Instant::now() + Duration::from_secs(secs));
+    while monitor.is_running() && deadline.is_none_or(|deadline| Instant::now() < deadline) {
+        std::thread::sleep(Duration::from_millis(250));
+        save(dashboard.as_ref())?;
+    }
+    monitor.stop();
+    // Изображение, ждавшее метаданных, уходит в принятые, когда поток приёма выходит
+    let stopping = Instant::now();
+    while monitor.is_running() && stopping.elapsed() < Duration::from_secs(1) {
+        std::thread::sleep(Duration::from_millis(50));
+    }
+    save(dashboard.as_ref())?;
+    // Отправка на вебхук дописывает очередь
+    #[cfg(feature = "webhook")]
+    if let Some(notifier) = notifier {
+        notifier.finish();
+    }
+    if let Some(error) = monitor.state().error.clone() {
+        anyhow::bail!("{error}");
+    }
+    Ok(())
+}
+
+fn run_channel(args: &ChannelArgs) -> Result<()> {
+    let retarder_image = args.channel.read_retarder()?;
+    let params = args.channel.to_params()?;
+    print_param_warnings(&params);
+    let mut processor = args.channel.processor(params)?;
+    let live_params = LiveParams {
+        input_device: args.input_device.clone(),
+        output_device: args.output_device.clone(),
+        block_ms: args.block_ms,
+        duration: Duration::from_secs(args.secs),
+        input_rms: args.input_rms,
+        checkpoint: args.checkpoint.clone(),
+        checkpoint_every: args
+            .checkpoint_every
+            .map(|secs| Duration::from_secs_f32(secs.max(0.0))),
+        resume: args.resume.clone(),
+    };
+    let effects: Vec<String> = processor
+        .effects()
+        .iter()
+        .filter(|e| e.is_enabled())
+        .map(|e| e.describe())
+        .collect();
+    let effects = if effects.is_empty() {
+        messages::cli_channel_passthrough().to_string()
     } else {
-        None
+        effects.join("; ")
     };
+    println!("{}", messages::cli_channel_running(args.secs, &effects));
+
+    let report = live::run(
+        &mut processor,
+        retarder_image.as_ref(),
+        &live_params,
+        &AtomicBool::new(false),
+    )?;
+    for event in &report.sanitized {
+        println!("{}", messages::cli_sanitized(&event.describe()));
+    }
+    println!("{}", report.describe());
+    Ok(())
+}
+
+fn run_null(args: &NullArgs) -> Result<()> {
+    let reference = wav::read_wav(&args.reference)?;
+    let candidate = wav::read_wav(&args.candidate)?;
+    if reference.rate != candidate.rate {
+        anyhow::bail!(messages::cli_rates_differ(reference.rate, candidate.rate));
+    }
 
-    // Настраиваем параметры
-    let noise_params = NoiseParams {
-        level: args.noise,
-        env: args.noise_env,
-        repeat: args.noise_repeat,
+    let max_offset = reference.ms_to_frames(args.max_offset_ms.max(0.0) as f64);
+    let report = nulling::null_test(&reference, &candidate, max_offset);
+    println!("{}", messages::cli_residual(&report.describe()));
+    if !report.is_transparent(args.min_depth_db) {
+        anyhow::bail!(messages::cli_signals_differ(
+            report.depth_db(),
+            args.min_depth_db
+        ));
+    }
+    println!("{}", messages::cli_signals_match());
+    Ok(())
+}
+
+fn run_conformance(args: &ConformanceArgs) -> Result<()> {
+    let params = ConformanceParams {
+        freq_tolerance_hz: args.freq_tolerance_hz,
+        edge_tolerance_us: args.edge_tolerance_us,
+        period_tolerance_ppm: args.period_tolerance_ppm,
     };
 
-    let retarder_params = RetarderParams {
-        level: args.level,
-        env: args.ret_env,
-        repeat: args.ret_repeat,
-        delay_ms: args.delay_ms,
+    if let Some(input) = &args.input {
+        let signal = wav::read_wav(input)?;
+        let spec = conformance::detect_mode(&signal, signal.rate)?;
+        return report_conformance(
+            input,
+            &conformance::check(&signal, signal.rate, spec, &params)?,
+        );
+    }
+
+    let mut failed = 0;
+    for &spec in conformance::MODES {
+        let samples = SampleBuffer::mono(
+            conformance::reference_signal(spec, args.encoder),
+            SAMPLE_RATE as u32,
+        );
+        let report = conformance::check(&samples, samples.rate, spec, &params)?;
+        let conforms =
+            report_conformance(&messages::cli_encoder_target(args.encoder.name()), &report).is_ok();
+        if !conforms {
+            failed += 1;
+        }
+
+        // Эталон, не соответствующий спецификации, закрепил бы ошибку кодера
+        if let Some(dir) = &args.write_golden
+            && !conforms
+        {
+            println!("{}", messages::cli_golden_skipped(dir));
+        } else if let Some(dir) = &args.write_golden {
+            std::fs::create_dir_all(dir).with_context(|| messages::create_dir_failed(dir))?;
+            let path = Path::new(dir).join(conformance::golden_file_name(spec));
+            wav::write_wav(&path.to_string_lossy(), &samples)?;
+            println!(
+                "{}",
+                messages::cli_golden_written(&path.display().to_string())
+            );
+        }
+
+        if let Some(dir) = &args.golden {
+            let path = Path::new(dir).join(conformance::golden_file_name(spec));
+            let path = path.to_string_lossy();
+            let golden = wav::read_wav(&path)?;
+            let report = conformance::check(&golden, golden.rate, spec, &params)?;
+            if report_conformance(&path, &report).is_err() {
+                failed += 1;
+            }
+            // Эталон записан в 16 бит, поэтому совпадение — до подавления TRANSPARENT_DEPTH_DB
+            let null = nulling::null_at(&golden, &samples, 0);
+            println!("{}", messages::cli_golden_null(&null.describe()));
+            if golden.rate != samples.rate || !null.is_transparent(nulling::TRANSPARENT_DEPTH_DB) {
+                println!("{}", messages::cli_golden_mismatch(&path));
+                failed += 1;
+            }
+        }
+    }
+
+    if failed > 0 {
+        anyhow::bail!(messages::cli_checks_failed(failed));
+    }
+    Ok(())
+}
+
+/// Печатает итог проверки соответствия; ошибка, если какая-то проверка не пройдена
+fn report_conformance(what: &str, report: &ConformanceReport) -> Result<()> {
+    println!("{} — {}:", report.mode, what);
+    for check in &report.checks {
+        println!("  {}", check.describe());
+    }
+    let failures = report.failures().count();
+    if failures > 0 {
+        println!("{}", messages::cli_nonconforming(failures));
+        anyhow::bail!(messages::cli_not_conforming(what, report.mode));
+    }
+    println!("{}", messages::cli_conforms());
+    Ok(())
+}
+
+fn run_fuzz(args: &FuzzArgs) -> Result<()> {
+    let params = FuzzParams {
+        seed: args.seed,
+        effect_cases: args.effect_cases,
+        decode_cases: args.decode_cases,
+        process_cases: args.process_cases,
+        differential_cases: args.differential_cases,
+        min_agreement_db: args.min_agreement_db,
+        max_len: args.max_len,
     };
+    let total = params.effect_cases
+        + params.decode_cases
+        + params.process_cases
+        + params.differential_cases;
 
-    let params = ProcessingParams {
-        noise: noise_params,
-        retarder: retarder_params,
+    // Паники ловятся и входят в отчёт; стандартный обработчик засорял бы вывод
+    let hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(|_| {}));
+    let mut done = 0;
+    let report = fuzz::run(&params, |target, seed| {
+        done += 1;
+        if done % 50 == 0 || done == total {
+            println!(
+                "{}",
+                messages::cli_fuzz_progress(done, total, target.name(), seed)
+            );
+        }
+    });
+    std::panic::set_hook(hook);
+
+    for failure in &report.failures {
+        println!("✗ {}", failure.describe());
+    }
+    if let Some(difference) = report.worst_difference() {
+        println!(
+            "{}",
+            messages::cli_fuzz_worst_difference(&difference.describe())
+        );
+    }
+    println!(
+        "{}",
+        messages::cli_fuzz_summary(report.cases, report.rejected, report.failures.len())
+    );
+    if !report.passed() {
+        anyhow::bail!(messages::cli_fuzz_failed(report.failures.len()));
+    }
+    Ok(())
+}
+
+fn run_batch(args: &BatchArgs) -> Result<()> {
+    let inputs = collect_inputs(&args.input)?;
+    if inputs.is_empty() {
+        anyhow::bail!(messages::cli_no_images(&args.input));
+    }
+
+    let output_dir = Path::new(&args.output_dir);
+    std::fs::create_dir_all(output_dir)
+        .with_context(|| messages::create_dir_failed(&output_dir.display().to_string()))?;
+
+    // Общие картинки читаются один раз на весь пакет
+    let retarder_image = args.channel.read_retarder()?;
+    let params = args.channel.to_params()?;
+
+    let pool = threads::pool(args.jobs)?;
+
+    let total = inputs.len();
+    let done = AtomicUsize::new(0);
+    let start = Instant::now();
+    println!(
+        "{}",
+        messages::cli_batch_start(total, pool.current_num_threads())
+    );
+
+    let failures: Vec<(PathBuf, anyhow::Error)> = pool.install(|| {
+        inputs
+            .par_iter()
+            .filter_map(|input| {
+                let result = process_batch_file(
+                    args,
+                    input,
+                    output_dir,
+                    params.clone(),
+                    retarder_image.as_ref(),
+                );
+                let k = done.fetch_add(1, Ordering::Relaxed) + 1;
+                match result {
+                    Ok(()) => {
+                        println!("[{}/{}] {}", k, total, input.display());
+                        None
+                    }
+                    Err(e) => {
+                        eprintln!("[{}/{}] {}: {:#}", k, total, input.display(), e);
+                        Some((input.clone(), e))
+                    }
+                }
+            })
+            .collect()
+    });
+
+    println!(
+        "{}",
+        messages::cli_batch_done(
+            total - failures.len(),
+            total,
+            start.elapsed().as_secs_f32(),
+            failures.len()
+        )
+    );
+    if !failures.is_empty() {
+        for (path, e) in &failures {
+            eprintln!("  {}: {:#}", path.display(), e);
+        }
+        anyhow::bail!(messages::cli_files_failed(failures.len()));
+    }
+    Ok(())
+}
+
+fn run_sweep(args: &SweepArgs) -> Result<()> {
+    let main_image = read_image(&args.input, messages::cli_source_image())?;
+    let retarder_image = args.channel.read_retarder()?;
+
+    let axes = SweepAxes {
+        noise: args.noise_range,
+        retarder_level: args.level_range,
+        delay_ms: args.delay_range,
+    };
+    let mut runner = SweepRunner::new(args.channel.to_params()?, axes, &args.output_dir);
+    runner.format = args.manifest;
+    runner.save_wav = args.wav;
+    runner.coarse = args.coarse;
+
+    let paths = CommandPaths {
+        input: args.input.clone(),
+        output: String::new(),
+        retarder: args.channel.retarder.clone(),
+        qrm: args.channel.qrm.clone(),
+        noise_env_file: args.channel.noise_env_file.clone(),
+        ret_env_file: args.channel.ret_env_file.clone(),
+        timeline: args.channel.timeline.clone(),
     };
 
-    // Обрабатываем
-    let mut processor = SSTVProcessor::new_with_params(params);
+    let pool = threads::pool(args.jobs)?;
 
-    let result = processor.process(&main_image, retarder_image.as_ref())?;
+    let total: usize = runner.passes().iter().map(Vec::len).sum();
+    let done = AtomicUsize::new(0);
+    let start = Instant::now();
+    println!(
+        "{}",
+        messages::cli_sweep_start(total, pool.current_num_threads())
+    );
 
-    // Сохраняем
-    processor.save_result(&result, &args.output)?;
+    let entries = pool.install(|| {
+        runner.run(&main_image, retarder_image.as_ref(), &paths, |entry| {
+            let k = done.fetch_add(1, Ordering::Relaxed) + 1;
+            println!(
+                "[{}/{}] {} ({})",
+                k,
+                total,
+                entry.file,
+                entry.quality.name()
+            );
+        })
+    })?;
+
+    println!(
+        "{}",
+        messages::cli_sweep_done(
+            start.elapsed().as_secs_f32(),
+            &runner.manifest_path().display().to_string()
+        )
+    );
+
+    #[cfg(feature = "plot")]
+    let chart = args.curve_chart;
+    #[cfg(not(feature = "plot"))]
+    let chart = false;
+    if args.curve || chart {
+        // Кривая строится по основному проходу, грубый в неё не входит
+        let main: Vec<_> = entries
+            .into_iter()
+            .filter(|entry| entry.quality == runner.base.quality)
+            .collect();
+        let curves = DegradationCurve::from_entries(&main)?;
+        for curve in &curves {
+            println!("{}", curve.describe());
+        }
+        let csv = Path::new(&args.output_dir).join("curve.csv");
+        std::fs::write(&csv, curve::curves_csv(&curves))
+            .with_context(|| messages::write_failed(&csv.display().to_string()))?;
+        println!(
+            "{}",
+            messages::cli_curve_written(&csv.display().to_string())
+        );
+
+        #[cfg(feature = "plot")]
+        if chart {
+            let png = Path::new(&args.output_dir).join("curve.png");
+            curve::render_chart(&curves)?
+                .save(&png)
+                .with_context(|| messages::save_failed(&png.display().to_string()))?;
+            println!("{}", messages::cli_done(&png.display().to_string()));
+        }
+    }
+    Ok(())
+}
+
+fn run_sequence(args: &SequenceArgs) -> Result<()> {
+    let frames = sequence::read_frames(Path::new(&args.input), args.frame_ms)?;
+    if frames.is_empty() {
+        anyhow::bail!(messages::cli_no_frames(&args.input));
+    }
+    let retarder_image = args.channel.read_retarder()?;
+    let ramps = SequenceRamps {
+        noise: args.noise_ramp,
+        retarder_level: args.level_ramp,
+        delay_ms: args.delay_ramp,
+    };
+    let runner = SequenceRunner::new(args.channel.to_params()?, ramps);
+
+    let pool = threads::pool(args.jobs)?;
+
+    let total = frames.len();
+    let done = AtomicUsize::new(0);
+    let start = Instant::now();
+    println!(
+        "{}",
+        messages::cli_sequence_start(total, pool.current_num_threads())
+    );
+
+    let degraded = pool.install(|| {
+        runner.run(&frames, retarder_image.as_ref(), |index| {
+            let k = done.fetch_add(1, Ordering::Relaxed) + 1;
+            let params = runner.params_at(index, total);
+            let frame = if retarder_image.is_some() {
+                messages::cli_sequence_frame_retarder(
+                    index + 1,
+                    params.noise.level,
+                    params.retarder.level,
+                    params.retarder.delay_ms,
+                )
+            } else {
+                messages::cli_sequence_frame(index + 1, params.noise.level)
+            };
+            println!("[{}/{}] {}", k, total, frame);
+        })
+    })?;
+    sequence::write_frames(Path::new(&args.output), &degraded)?;
+
+    println!(
+        "{}",
+        messages::cli_sequence_done(start.elapsed().as_secs_f32(), &args.output)
+    );
+    Ok(())
+}
+
+/// Обрабатывает один файл пакета; результат сохраняется под тем же именем в PNG
+fn process_batch_file(
+    args: &BatchArgs,
+    input: &Path,
+    output_dir: &Path,
+    params: ProcessingParams,
+    retarder_image: Option<&DynamicImage>,
+) -> Result<()> {
+    let main_image = read_image(&input.to_string_lossy(), messages::cli_source_image())?;
+    let mut processor = args.channel.processor(params)?;
+
+    let samples = processor.render_samples(&main_image, retarder_image)?;
+    let result = processor.decode_samples(&samples)?.image;
+
+    let stem = input.file_stem().unwrap_or_default();
+    let output = output_dir.join(stem).with_extension("png");
+    processor.save_result(&result, &output.to_string_lossy())?;
 
-    println!("Готово: {}", args.output);
+    if args.wav {
+        let wav_path = output.with_extension("wav");
+        processor.write_wav(&wav_path.to_string_lossy(), &samples)?;
+    }
+    if args.masks {
+        let masks = labels::masks(
+            processor.params(),
+            retarder_image.is_some(),
+            samples.len(),
+            samples.rate,
+        );
+        labels::save(&masks, &output.with_extension("mask"))?;
+    }
     Ok(())
 }
+
+/// Расширения файлов, которые берутся из каталога
+const IMAGE_EXTENSIONS: &[&str] = &[
+    "png", "jpg", "jpeg", "bmp", "gif", "tif", "tiff", "webp", "ppm", "pgm", "pnm",
+];
+
+/// Список входных файлов: все изображения каталога либо файлы по шаблону
+/// с `*` и `?` в имени файла
+fn collect_inputs(input: &str) -> Result<Vec<PathBuf>> {
+    let path = Path::new(input);
+    let (dir, pattern) = if path.is_dir() {
+        (path, None)
+    } else {
+        let name = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .with_context(|| messages::cli_bad_pattern(input))?;
+        let dir = path.parent().filter(|p| !p.as_os_str().is_empty());
+        (dir.unwrap_or(Path::new(".")), Some(name))
+    };
+
+    let mut files = Vec::new();
+    for entry in std::fs::read_dir(dir)
+        .with_context(|| messages::read_dir_failed(&dir.display().to_string()))?
+    {
+        let path = entry?.path();
+        if !path.is_file() {
+            continue;
+        }
+        let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+        let matches = match pattern {
+            Some(pattern) => wildcard_match(pattern, name),
+            None => path
+                .extension()
+                .and_then(|e| e.to_str())
+                .is_some_and(|e| IMAGE_EXTENSIONS.contains(&e.to_ascii_lowercase().as_str())),
+        };
+        if matches {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Сопоставление имени с шаблоном: `*` — любая подстрока, `?` — любой символ
+fn wildcard_match(pattern: &str, name: &str) -> bool {
+    let p: Vec<char> = pattern.chars().collect();
+    let n: Vec<char> = name.chars().collect();
+    let (mut pi, mut ni) = (0, 0);
+    // Позиция последней звёздочки и место в имени, с которого она сейчас сопоставлена
+    let mut star: Option<(usize, usize)> = None;
+
+    while ni < n.len() {
+        if pi < p.len() && (p[pi] == '?' || p[pi] == n[ni]) {
+            pi += 1;
+            ni += 1;
+        } else if pi < p.len() && p[pi] == '*' {
+            star = Some((pi, ni));
+            pi += 1;
+        } else if let Some((sp, sn)) = star {
+            pi = sp + 1;
+            ni = sn + 1;
+            star = Some((sp, sn + 1));
+        } else {
+            return false;
+        }
+    }
+    p[pi..].iter().all(|&c| c == '*')
+}
+
+/// Печатает телеметрию из полосы декодированного изображения
+fn print_telemetry(image: &DynamicImage) {
+    let telemetry = TelemetryStrip::new()
+        .extract(image)
+        .and_then(|bytes| Telemetry::from_bytes(&bytes));
+    match telemetry {
+        Ok(t) => println!("{}", messages::cli_telemetry(&t.to_string())),
+        Err(e) => println!("{}", messages::cli_telemetry_failed(&e.to_string())),
+    }
+}
+
+/// Печатает замечания к параметрам: молча поправленные или бессмысленные значения
+fn print_param_warnings(params: &ProcessingParams) {
+    for warning in params.validate() {
+        eprintln!("⚠ {}", warning);
+    }
+}
+
+/// Печатает советы пробного прогона
+fn print_suggestions(suggestions: &Suggestions) {
+    for suggestion in suggestions.iter() {
+        eprintln!("{}", suggestion);
+    }
+}
+
+/// Печатает, насколько хорошо принято изображение: строки, синхронизацию, наклон, С/Ш,
+/// сводку канала, VIS, поправку АПЧ и замаскированные строки (если маскировка включена)
+/// Печатает, почему декодер сдался и куда сохранены остатки приёма
+fn print_salvage(salvage: &Salvage, paths: &[PathBuf]) {
+    let files: Vec<String> = paths.iter().map(|p| p.display().to_string()).collect();
+    println!(
+        "{}",
+        messages::cli_salvaged(&salvage.reason, &files.join(", "))
+    );
+}
+
+fn print_report(report: &DecodeReport, conceal: bool) {
+    println!("{}", report.describe());
+    if let Some(channel) = &report.channel {
+        println!("{}", channel.describe());
+    }
+    for event in &report.sanitized {
+        eprintln!("{}", messages::cli_sanitized(&event.describe()));
+    }
+    match &report.vis_detected {
+        Some(vis) => println!("{}", vis.describe()),
+        None => println!("{}", messages::cli_vis_missing()),
+    }
+    if let Some(trace) = report.afc_trace.as_deref()
+        && let (Some(first), Some(last)) = (trace.first(), trace.last())
+    {
+        let (min, max) = trace
+            .iter()
+            .fold((f32::INFINITY, f32::NEG_INFINITY), |(lo, hi), &v| {
+                (lo.min(v), hi.max(v))
+            });
+        println!("{}", messages::cli_afc(*first, *last, min, max));
+    }
+    if conceal {
+        println!("{}", messages::cli_concealed(report.concealed_lines.len()));
+    }
+}
@@ -1,12 +1,745 @@
 use egui::{ColorImage, TextureHandle, Vec2};
+use egui_dock::{DockArea, DockState, NodeIndex, Style};
 use image::{DynamicImage, GenericImageView};
-use std::sync::{Arc, Mutex};
-use std::time::Instant;
+use rand::Rng;
+use std::collections::{HashSet, VecDeque};
+use std::ops::Range;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{Receiver, Sender, channel};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tracing::Level;
+use tracing::field::{Field, Visit};
+use tracing_subscriber::filter::Targets;
+use tracing_subscriber::layer::{self, Layer};
+use tracing_subscriber::prelude::*;
 
 // Импортируем из локального крейта
-use sstv_processor::{EnvelopeKind, SSTVProcessor};
+use sstv_processor::filters::MAX_FILTER_STAGES;
+use sstv_processor::messages::gui;
+use sstv_processor::noise::level_snr_db;
+use sstv_processor::overlay::MAX_OVERLAY_SIZE;
+use sstv_processor::soak::resident_memory;
+use sstv_processor::{
+    AmplitudeDistribution, AppParams, AutomationRecording, BlankerFill, Carrier, ChannelSummary,
+    CommandPaths, ConvolutionBackend, CustomEnvelope, DecodeReport, DecoderBackend, DropoutKind,
+    DropoutWindow, EncoderBackend, EncoderParams, EnvelopeAnchor, EnvelopeKind, FadingModel,
+    FilterKind, IdMode, ImageSink, Language, LocationSource, LoudnessMeasure, MARTIN_M1, MetaField,
+    MetricSpace, MixTarget, ModeTiming, Monitor, MonitorParams, MonitorSource, MultipathTap,
+    NativeEncoder, NoiseColor, NoiseKind, Palette, ParamWarning, PcmFormat, Phosphor,
+    ProcessingParams, QualityReport, QualityTier, ReceivedImage, Registration, RetarderSource,
+    RunArtifacts, RunExporter, SAMPLE_RATE, SSTVProcessor, SampleBuffer, SanitizeMode,
+    ScanConverterParams, Segment, SegmentPurpose, Severity, Sideband, Spectrogram, SquelchParams,
+    SstvError, Stage, Suggestions, Timeline, TransceiverOutput, VisReport, advice, audio, command,
+    diff_image, encoder, location, messages, metadata, metrics, wav,
+};
+
+/// Панель предпросмотра с изображением
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Panel {
+    Main,
+    Retarder,
+    Result,
+    Waterfall,
+}
+
+/// Вкладка панели результата
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum ResultTab {
+    Image,
+    /// Исходное и результат под шторкой, справа — результат или карта разницы
+    Compare,
+}
+
+/// Вкладка стыкуемого интерфейса
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+enum Tab {
+    Parameters,
+    Images,
+    Waterfall,
+    Metrics,
+    Log,
+    Receive,
+}
+
+impl Tab {
+    fn title(&self) -> &'static str {
+        match self {
+            Tab::Parameters => gui::tab_parameters(),
+            Tab::Images => gui::tab_images(),
+            Tab::Waterfall => gui::tab_waterfall(),
+            Tab::Metrics => gui::tab_metrics(),
+            Tab::Log => gui::tab_log(),
+            Tab::Receive => gui::tab_receive(),
+        }
+    }
+
+    /// Начальная раскладка: параметры слева, изображения и приём справа, под ними
+    /// водопад, метрики и журнал
+    fn default_layout() -> DockState<Tab> {
+        let mut dock = DockState::new(vec![Tab::Images, Tab::Receive]);
+        let surface = dock.main_surface_mut();
+        let [images, _] = surface.split_left(NodeIndex::root(), 0.3, vec![Tab::Parameters]);
+        surface.split_below(images, 0.65, vec![Tab::Waterfall, Tab::Metrics, Tab::Log]);
+        dock
+    }
+}
+
+/// Что вкладки запросили за кадр; выполняется после отрисовки, когда приложение
+/// снова доступно целиком
+#[derive(Default)]
+struct FrameActions {
+    panel_action: Option<PanelAction>,
+    history_click: Option<u64>,
+    hovered_line: Option<u32>,
+    /// Отрезок сигнала под щелчком по осциллограмме или спектрограмме, с
+    inspect: Option<Range<f64>>,
+    /// Вынести вкладку в отдельное окно
+    detach: Option<Tab>,
+    /// Вернуть вкладку из отдельного окна в раскладку
+    attach: Option<Tab>,
+}
+
+/// Отрисовка вкладок раскладки
+struct TabView<'a> {
+    app: &'a mut SSTVApp,
+    actions: &'a mut FrameActions,
+}
+
+impl egui_dock::TabViewer for TabView<'_> {
+    type Tab = Tab;
+
+    fn title(&mut self, tab: &mut Tab) -> egui::WidgetText {
+        tab.title().into()
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui, tab: &mut Tab) {
+        self.app.tab_ui(ui, *tab, self.actions);
+    }
+
+    /// Вкладки не закрываются: пропавшую панель негде было бы вернуть
+    fn closeable(&mut self, _tab: &mut Tab) -> bool {
+        false
+    }
+}
+
+/// Параметр, движения ползунка которого записываются в огибающую
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum AutomationTarget {
+    Noise,
+    Retarder,
+}
+
+impl AutomationTarget {
+    const ALL: &'static [AutomationTarget] = &[AutomationTarget::Noise, AutomationTarget::Retarder];
+
+    fn name(&self) -> &'static str {
+        match self {
+            AutomationTarget::Noise => messages::noise(),
+            AutomationTarget::Retarder => messages::retarder(),
+        }
+    }
+
+    /// Файл записанной огибающей рядом с выходным файлом
+    fn file_name(&self) -> &'static str {
+        match self {
+            AutomationTarget::Noise => "automation_noise.txt",
+            AutomationTarget::Retarder => "automation_retarder.txt",
+        }
+    }
+
+    /// Множитель амплитуды эффекта для значения ползунка
+    fn amplitude(&self) -> fn(f32) -> f32 {
+        match self {
+            AutomationTarget::Noise => |v| 10f32.powf(-level_snr_db(v.round() as u8) / 20.0),
+            AutomationTarget::Retarder => |v| v,
+        }
+    }
+}
+
+/// Идущая запись автоматизации: воспроизведение началось в `started`
+struct AutomationSession {
+    target: AutomationTarget,
+    started: Instant,
+    /// Длительность воспроизводимого сигнала, с
+    duration: f32,
+    recording: AutomationRecording,
+}
+
+/// Действие из контекстного меню панели
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum PanelAction {
+    SaveAs(Panel),
+    CopyImage(Panel),
+    OpenExternal(Panel),
+    CopyCli,
+}
+
+/// Сколько последних результатов хранится в истории по умолчанию
+const HISTORY_SIZE: usize = 12;
+/// Наибольший размер истории: каждая запись держит текстуру и сигналы превью
+const MAX_HISTORY_SIZE: usize = 64;
+/// История в режиме витрины: при работе сутками хватает нескольких последних результатов
+const SOAK_HISTORY_SIZE: usize = 3;
+/// Записей в журнале
+const LOG_SIZE: usize = 500;
+/// Расширения изображений, которые витрина берёт из папки
+const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "bmp", "gif", "tiff", "webp"];
+
+/// Размер спектрограммы: окон по времени и полос частот
+const SPECTROGRAM_COLUMNS: usize = 2048;
+const SPECTROGRAM_BINS: usize = 128;
+/// Полоса спектрограммы: VIS, синхроимпульс и яркость с запасом на расстройку
+const SPECTROGRAM_FREQ: Range<f32> = 900.0..2600.0;
+/// Динамический диапазон спектрограммы, дБ
+const SPECTROGRAM_RANGE_DB: f32 = 60.0;
+/// Полоса водопада: сигнал SSTV вместе с шумом и помехами вокруг него
+const WATERFALL_FREQ: Range<f32> = 0.0..4000.0;
+/// Столбцов осциллограммы по всему сигналу
+const WAVEFORM_COLUMNS: usize = 1200;
+/// Наибольшее число строк инспектора тонов
+const INSPECTOR_MAX_LINES: usize = 8;
+/// Частоты дискретизации выходного сигнала на выбор
+const SAMPLE_RATES: &[u32] = &[8000, 11025, 22050, 44100, 48000, 96000];
+/// Цвета огибающих на осциллограмме
+const NOISE_ENVELOPE_COLOR: egui::Color32 = egui::Color32::from_rgb(255, 165, 0);
+const RETARDER_ENVELOPE_COLOR: egui::Color32 = egui::Color32::from_rgb(0, 200, 255);
+
+/// Спектрограмма и водопад сигнала результата и положение тела изображения в нём
+#[derive(Clone)]
+struct SpectrogramView {
+    texture: TextureHandle,
+    /// Водопад всей полосы: время сверху вниз, частота слева направо
+    waterfall: DynamicImage,
+    waterfall_texture: TextureHandle,
+    /// Длина сигнала, сэмплов
+    len: usize,
+    /// Частота дискретизации сигнала, Гц
+    sample_rate: u32,
+    /// Разметка строк изображения в сигнале
+    timing: ModeTiming,
+}
+
+impl SpectrogramView {
+    fn new(ctx: &egui::Context, samples: &[f32], sample_rate: u32, timing: ModeTiming) -> Self {
+        // Та же картинка, повёрнутая так, чтобы время шло слева направо, а высокие частоты были сверху
+        let spectrogram = Spectrogram {
+            lines: SPECTROGRAM_COLUMNS,
+            bins: SPECTROGRAM_BINS,
+            freq: SPECTROGRAM_FREQ,
+            range_db: SPECTROGRAM_RANGE_DB,
+        }
+        .render(samples, sample_rate)
+        .rotate270();
+        let waterfall = Spectrogram {
+            freq: WATERFALL_FREQ,
+            ..Spectrogram::new()
+        }
+        .render(samples, sample_rate);
+
+        let texture = |name: &str, image: &DynamicImage| {
+            let color_image = SSTVApp::dynamic_image_to_color_image(image);
+            ctx.load_texture(name, color_image, egui::TextureOptions::LINEAR)
+        };
+        Self {
+            texture: texture("spectrogram", &spectrogram),
+            waterfall_texture: texture("waterfall", &waterfall),
+            waterfall,
+            len: samples.len(),
+            sample_rate,
+            timing,
+        }
+    }
+}
+
+/// Осциллограмма сигнала после эффектов с наложенными огибающими шума и призрака
+#[derive(Clone)]
+struct WaveformView {
+    /// Минимум и максимум сэмплов в каждом столбце
+    columns: Vec<(f32, f32)>,
+    /// Огибающая шума по столбцам (0–1), если шум включён
+    noise_envelope: Option<Vec<f32>>,
+    /// Огибающая призрака по столбцам (0–1), если призрак задан
+    retarder_envelope: Option<Vec<f32>>,
+    /// Длина сигнала, сэмплов
+    len: usize,
+    /// Частота дискретизации сигнала, Гц
+    sample_rate: u32,
+    /// Параметры кодера и переданное изображение в разрешении режима — для инспектора
+    /// тонов
+    encoder: EncoderParams,
+    sent: Arc<DynamicImage>,
+}
+
+impl WaveformView {
+    /// `sent` — изображение, как оно ушло в кодер: с надписью
+    fn new(
+        samples: &[f32],
+        params: &ProcessingParams,
+        has_retarder: bool,
+        sent: &DynamicImage,
+    ) -> Self {
+        let len = samples.len();
+        let at = |column: usize| column * len / WAVEFORM_COLUMNS;
+        let columns = (0..WAVEFORM_COLUMNS)
+            .map(|c| {
+                samples[at(c)..at(c + 1).max(at(c))]
+                    .iter()
+                    .fold((0.0_f32, 0.0_f32), |(lo, hi), &v| (lo.min(v), hi.max(v)))
+            })
+            .collect();
+
+        // Огибающие считаются так же, как в эффектах: по позиции сэмпла с учётом привязки
+        let rate = params.signal_rate();
+        let span = encoder::image_span(&params.encoder, rate, len);
+        let curve = |kind: &EnvelopeKind, repeat: f32, anchor: EnvelopeAnchor| -> Vec<f32> {
+            (0..WAVEFORM_COLUMNS)
+                .map(|c| {
+                    let (pos, env_len) = anchor.locate(at(c), len, &span);
+                    kind.factor(pos, env_len, repeat, rate)
+                })
+                .collect()
+        };
+        let noise = &params.noise;
+        let retarder = &params.retarder;
+        Self {
+            columns,
+            noise_envelope: (noise.level > 0)
+                .then(|| curve(&noise.env, noise.repeat, noise.anchor)),
+            retarder_envelope: (has_retarder && retarder.level > 0.0)
+                .then(|| curve(&retarder.env, retarder.repeat, retarder.anchor)),
+            len,
+            sample_rate: rate,
+            encoder: params.encoder.clone(),
+            sent: Arc::new(encoder::fit_to_mode(sent, &MARTIN_M1)),
+        }
+    }
+
+    /// Длительность сигнала, с
+    fn seconds(&self) -> f64 {
+        self.len as f64 / self.sample_rate as f64
+    }
+
+    /// Тоны встроенного кодера на отрезке `range`, с. Пиксели одного канала строки
+    /// подряд сводятся в одну строку
+    fn inspect(&self, range: Range<f64>) -> ToneInspection {
+        if self.encoder.backend != EncoderBackend::Native {
+            return ToneInspection {
+                range,
+                lines: vec![gui::inspector_rsstv().to_string()],
+            };
+        }
+        let (from_us, to_us) = (range.start * 1e6, range.end * 1e6);
+        let encoder = NativeEncoder::new_with_params(self.encoder.clone());
+        let segments: Vec<Segment> = encoder
+            .segments(&self.sent)
+            .skip_while(|s| s.end_us() <= from_us)
+            .take_while(|s| s.start_us < to_us)
+            .collect();
+
+        let pixel_run = |a: &Segment, b: &Segment| match (a.purpose, b.purpose) {
+            (
+                SegmentPurpose::Pixel { line, channel, .. },
+                SegmentPurpose::Pixel {
+                    line: next_line,
+                    channel: next_channel,
+                    ..
+                },
+            ) => line == next_line && channel == next_channel,
+            _ => false,
+        };
+        let mut lines: Vec<String> = segments
+            .chunk_by(pixel_run)
+            .map(|run| match run {
+                [single] => gui::inspector_tone(
+                    &single.purpose.describe(),
+                    single.tone.freq_hz,
+                    single.tone.duration_us / 1000.0,
+                ),
+                [first, .., last] => {
+                    let SegmentPurpose::Pixel { x, .. } = last.purpose else {
+                        unreachable!("в серии только пиксели");
+                    };
+                    let (low, high) = run.iter().fold((f32::MAX, f32::MIN), |(lo, hi), s| {
+                        (lo.min(s.tone.freq_hz), hi.max(s.tone.freq_hz))
+                    });
+                    gui::inspector_pixels(&first.purpose.describe(), x, low, high)
+                }
+                [] => unreachable!("chunk_by не выдаёт пустых серий"),
+            })
+            .collect();
+        if lines.is_empty() {
+            lines.push(gui::inspector_outside().to_string());
+        } else if lines.len() > INSPECTOR_MAX_LINES {
+            let more = lines.len() - INSPECTOR_MAX_LINES + 1;
+            lines.truncate(INSPECTOR_MAX_LINES - 1);
+            lines.push(gui::inspector_more(more));
+        }
+        ToneInspection { range, lines }
+    }
+}
+
+/// Что кодирует отрезок сигнала под щелчком по осциллограмме или спектрограмме
+struct ToneInspection {
+    /// Отрезок сигнала, с
+    range: Range<f64>,
+    /// Описания тонов отрезка
+    lines: Vec<String>,
+}
+
+/// Запрос к фоновому потоку обработки
+struct JobRequest {
+    id: u64,
+    params: ProcessingParams,
+    main_image: DynamicImage,
+    retarder_image: Option<DynamicImage>,
+    cancel: Arc<AtomicBool>,
+}
+
+/// Сообщение фонового потока обработки о запросе `id`
+enum JobMessage {
+    Progress(u64, Stage, f32),
+    /// Советы пробного прогона, приходят до результата
+    Advice(u64, Suggestions),
+    /// Осциллограмма сигнала после эффектов, приходит до декодирования
+    Rendered(u64, WaveformView),
+    /// Декодер начал изображение с такими шириной и высотой
+    DecodeStarted(u64, u32, u32),
+    /// Строка, только что принятая декодером, RGB по три байта
+    DecodedRow(u64, u32, Vec<u8>),
+    Done(u64, Result<Box<JobResult>, String>),
+    Cancelled(u64),
+}
+
+/// Передаёт строки декодера фонового потока интерфейсу, чтобы изображение появлялось
+/// сверху вниз, как при приёме
+struct RowSender {
+    id: u64,
+    results: Sender<JobMessage>,
+    ctx: egui::Context,
+}
+
+impl ImageSink for RowSender {
+    fn begin(&mut self, width: u32, height: u32) -> sstv_processor::error::Result<()> {
+        let _ = self
+            .results
+            .send(JobMessage::DecodeStarted(self.id, width, height));
+        Ok(())
+    }
+
+    fn write_row(&mut self, y: u32, row: &[u8]) -> sstv_processor::error::Result<()> {
+        let _ = self
+            .results
+            .send(JobMessage::DecodedRow(self.id, y, row.to_vec()));
+        self.ctx.request_repaint();
+        Ok(())
+    }
+}
+
+/// Результат фоновой обработки
+struct JobResult {
+    report: DecodeReport,
+    /// Сигнал после эффектов
+    signal: SampleBuffer,
+    /// Включена ли маскировка строк
+    conceal: bool,
+    /// Сигнал кодера взят из кэша процессора
+    encode_cached: bool,
+}
+
+/// Последний отправленный запрос, результат которого ждёт интерфейс
+struct ProcessingJob {
+    id: u64,
+    cancel: Arc<AtomicBool>,
+    /// `None`, пока запрос стоит в очереди
+    progress: Option<(Stage, f32)>,
+    /// Принятые декодером строки поверх прежнего результата и их текстура
+    decoded: Option<(image::RgbImage, TextureHandle)>,
+    started: Instant,
+    // Снимок на момент запуска: интерфейс может измениться, пока идёт обработка
+    main_image: DynamicImage,
+    params: AppParams,
+    encoder: EncoderParams,
+    command: String,
+}
+
+/// Фоновый поток обработки. Из накопившихся запросов выполняется только последний.
+/// Процессор один на все прогоны: его кэш кодера избавляет от повторного кодирования
+/// той же картинки, когда меняются только эффекты
+fn run_worker(requests: Receiver<JobRequest>, results: Sender<JobMessage>, ctx: egui::Context) {
+    let mut processor = SSTVProcessor::new();
+    while let Ok(mut request) = requests.recv() {
+        while let Ok(newer) = requests.try_recv() {
+            let _ = results.send(JobMessage::Cancelled(request.id));
+            request = newer;
+        }
+
+        let id = request.id;
+        // Неверные параметры пробы покажет сама обработка
+        if let Ok(probe) = advice::probe(
+            &request.params,
+            &request.main_image,
+            request.retarder_image.as_ref(),
+        ) {
+            let suggestions = advice::recommend(&request.params, &probe);
+            let _ = results.send(JobMessage::Advice(id, suggestions));
+            ctx.request_repaint();
+        }
+
+        let conceal = request.params.decoder.conceal;
+        if let Err(e) = processor.try_set_params(request.params) {
+            let _ = results.send(JobMessage::Done(id, Err(format!("{:#}", e))));
+            ctx.request_repaint();
+            continue;
+        }
+        let progress = |stage, fraction| {
+            let _ = results.send(JobMessage::Progress(id, stage, fraction));
+            ctx.request_repaint();
+        };
+        let result = processor
+            .render_with_progress(
+                &request.main_image,
+                request.retarder_image.as_ref(),
+                progress,
+                &request.cancel,
+            )
+            .and_then(|samples| {
+                // Сигнал показывается сразу, пока идёт медленное декодирование
+                let sent = processor
+                    .params()
+                    .overlay_params()
+                    .apply(&request.main_image)?;
+                let waveform = WaveformView::new(
+                    &samples,
+                    processor.params(),
+                    request.retarder_image.is_some()
+                        || processor.params().retarder.source != RetarderSource::Image,
+                    &sent,
+                );
+                let _ = results.send(JobMessage::Rendered(id, waveform));
+                ctx.request_repaint();
+                processor.set_image_sink(RowSender {
+                    id,
+                    results: results.clone(),
+                    ctx: ctx.clone(),
+                });
+                processor.decode_rendered(samples, progress, &request.cancel)
+            });
+        let message = match result {
+            Ok(report) => JobMessage::Done(
+                id,
+                Ok(Box::new(JobResult {
+                    report,
+                    signal: processor.last_buffer().clone(),
+                    conceal,
+                    encode_cached: processor.encode_cache_hit(),
+                })),
+            ),
+            Err(SstvError::Cancelled) => JobMessage::Cancelled(id),
+            Err(e) => JobMessage::Done(id, Err(format!("{:#}", e))),
+        };
+        if results.send(message).is_err() {
+            break;
+        }
+        ctx.request_repaint();
+    }
+}
+
+/// Режим витрины: изображения очереди обрабатываются по кругу без участия пользователя,
+/// каждый результат показывается `dwell_secs` секунд. Рассчитан на работу сутками, поэтому
+/// история урезана, а при превышении предела памяти кэши результатов сбрасываются
+struct SoakMode {
+    queue: Vec<String>,
+    next: usize,
+    running: bool,
+    dwell_secs: f32,
+    /// Новые случайные параметры для каждого изображения (закреплённые 🔒 не меняются)
+    randomize: bool,
+    /// Предел занятой процессом памяти, МБ
+    memory_limit_mb: u64,
+    /// Когда показан последний результат; `None`, пока идёт обработка
+    shown_at: Option<Instant>,
+    started: Instant,
+    cycles: u64,
+    errors: u64,
+    /// Сколько раз кэши сбрасывались из-за памяти
+    cleanups: u64,
+    peak_memory: u64,
+}
+
+impl Default for SoakMode {
+    fn default() -> Self {
+        Self {
+            queue: Vec::new(),
+            next: 0,
+            running: false,
+            dwell_secs: 10.0,
+            randomize: true,
+            memory_limit_mb: 2048,
+            shown_at: None,
+            started: Instant::now(),
+            cycles: 0,
+            errors: 0,
+            cleanups: 0,
+            peak_memory: 0,
+        }
+    }
+}
+
+/// Изображения папки по имени
+fn list_images(folder: &std::path::Path) -> std::io::Result<Vec<String>> {
+    let mut images: Vec<String> = std::fs::read_dir(folder)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            path.extension()
+                .and_then(|e| e.to_str())
+                .is_some_and(|e| IMAGE_EXTENSIONS.contains(&e.to_lowercase().as_str()))
+        })
+        .map(|path| path.to_string_lossy().to_string())
+        .collect();
+    images.sort();
+    Ok(images)
+}
+
+/// Текстура из изображения RGB без промежуточного RGBA
+fn rgb_to_color_image(image: &image::RgbImage) -> ColorImage {
+    ColorImage::from_rgb(
+        [image.width() as usize, image.height() as usize],
+        image.as_raw(),
+    )
+}
+
+/// Результат из истории вместе с параметрами, которыми он получен
+struct HistoryEntry {
+    id: u64,
+    params: AppParams,
+    /// Команда CLI, повторяющая обработку
+    command: String,
+    image: DynamicImage,
+    texture: TextureHandle,
+    quality: Option<QualityReport>,
+    registered: Option<(QualityReport, Registration)>,
+    concealed: Option<usize>,
+    vis: Option<VisReport>,
+    decode_summary: Option<String>,
+    spectrogram: Option<SpectrogramView>,
+    waveform: Option<WaveformView>,
+}
+
+/// Запись журнала сессии
+struct LogEntry {
+    /// Секунд от запуска
+    seconds: f32,
+    level: tracing::Level,
+    message: String,
+}
+
+impl LogEntry {
+    fn line(&self) -> String {
+        gui::log_line(self.seconds, self.level.as_str(), &self.message)
+    }
+}
+
+/// Журнал сессии: его пополняет подписчик tracing из любого потока — загрузка файлов,
+/// обработка, звук, предупреждения библиотеки, — а читает вкладка «Журнал». Раньше
+/// ошибки уходили только в stdout, которого пользователь окна не видит
+#[derive(Clone)]
+struct SessionLog {
+    entries: Arc<Mutex<VecDeque<LogEntry>>>,
+    launched: Instant,
+    /// Контекст окна, чтобы запись из фонового потока сразу появилась на экране
+    repaint: Arc<OnceLock<egui::Context>>,
+}
+
+impl SessionLog {
+    fn new() -> Self {
+        Self {
+            entries: Arc::new(Mutex::new(VecDeque::new())),
+            launched: Instant::now(),
+            repaint: Arc::new(OnceLock::new()),
+        }
+    }
+
+    /// Добавляет запись со временем от запуска, вытесняя самые старые
+    fn push(&self, level: tracing::Level, message: String) {
+        let seconds = self.launched.elapsed().as_secs_f32();
+        let mut entries = self.entries.lock().unwrap();
+        entries.push_back(LogEntry {
+            seconds,
+            level,
+            message,
+        });
+        while entries.len() > LOG_SIZE {
+            entries.pop_front();
+        }
+        drop(entries);
+        if let Some(ctx) = self.repaint.get() {
+            ctx.request_repaint();
+        }
+    }
+
+    /// Устанавливает подписчика: события крейтов приложения идут в журнал и, как прежде,
+    /// в stderr; из чужих крейтов (eframe, wgpu) в stderr попадают только предупреждения
+    fn install(&self) {
+        let own = Targets::new()
+            .with_target("gui", Level::DEBUG)
+            .with_target("sstv_processor", Level::DEBUG);
+        let console = own.clone().with_default(Level::WARN);
+        tracing_subscriber::registry()
+            .with(self.clone().with_filter(own))
+            .with(
+                tracing_subscriber::fmt::layer()
+                    .with_writer(std::io::stderr)
+                    .with_filter(console),
+            )
+            .init();
+    }
+}
+
+impl<S: tracing::Subscriber> Layer<S> for SessionLog {
+    fn on_event(&self, event: &tracing::Event<'_>, _ctx: layer::Context<'_, S>) {
+        let mut message = LogMessage(String::new());
+        event.record(&mut message);
+        self.push(*event.metadata().level(), message.0);
+    }
+}
+
+/// Текст события: сообщение, за ним остальные поля как `имя=значение`
+struct LogMessage(String);
+
+impl Visit for LogMessage {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.record_debug(field, &format_args!("{}", value));
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        use std::fmt::Write;
+        let separator = if self.0.is_empty() { "" } else { " " };
+        let _ = if field.name() == "message" {
+            write!(self.0, "{}{:?}", separator, value)
+        } else {
+            write!(self.0, "{}{}={:?}", separator, field.name(), value)
+        };
+    }
+}
+
+/// Название уровня журнала на текущем языке
+type LevelName = fn() -> &'static str;
+
+/// Наименьшая важность записей, которые показывает вкладка «Журнал»
+const LOG_LEVELS: &[(Level, LevelName)] = &[
+    (Level::ERROR, gui::log_errors),
+    (Level::WARN, gui::log_warnings),
+    (Level::INFO, gui::log_info),
+    (Level::DEBUG, gui::log_debug),
+];
 
 fn main() -> Result<(), eframe::Error> {
+    // Окно открывается на русском; язык переключается в параметрах вместе с отчётами
+    messages::set_language(Language::Ru);
+    let log = SessionLog::new();
+    log.install();
     let options = eframe::NativeOptions {
         viewport: egui::ViewportBuilder::default()
             .with_inner_size([1200.0, 800.0])
@@ -17,7 +750,7 @@ fn main() -> Result<(), eframe::Error> {
     eframe::run_native(
         "SSTV Processor",
         options,
-        Box::new(|cc| Box::new(SSTVApp::new(cc))),
+        Box::new(|cc| Box::new(SSTVApp::new(cc, log))),
     )
 }
 
@@ -30,10 +763,64 @@ struct SSTVApp {
     main_texture: Option<TextureHandle>,
     retarder_texture: Option<TextureHandle>,
     result_texture: Option<TextureHandle>,
+    /// Качество результата относительно исходного изображения
+    quality: Option<QualityReport>,
+    /// Качество после совмещения с исходным и само совмещение
+    registered: Option<(QualityReport, Registration)>,
+    /// Число замаскированных строк, если маскировка включена
+    concealed: Option<usize>,
+    /// Код VIS, принятый декодером при последней обработке
+    vis: Option<VisReport>,
+    /// Как прошло последнее декодирование: строки, синхронизация, наклон, С/Ш
+    decode_summary: Option<String>,
+    /// Советы пробного прогона последней обработки
+    suggestions: Suggestions,
+    /// Последний прогон взял сигнал кодера из кэша
+    encode_cached: bool,
+    /// Замечания к параметрам последнего запуска
+    param_warnings: Vec<ParamWarning>,
+    /// Что эффекты сделали бы с сигналом при последнем запуске, без обработки
+    channel_summary: Option<ChannelSummary>,
+    /// Ошибка последней обработки
+    last_error: Option<String>,
+    spectrogram: Option<SpectrogramView>,
+    /// Осциллограмма последнего сигнала; появляется до окончания декодирования
+    waveform: Option<WaveformView>,
+    /// Тоны под последним щелчком по сигналу
+    inspection: Option<ToneInspection>,
+    result_tab: ResultTab,
+    /// Раскладка вкладок главного окна
+    dock: DockState<Tab>,
+    /// Вкладки в отдельных окнах
+    detached: Vec<Tab>,
+    /// Журнал сессии, новые записи в конце
+    log: SessionLog,
+    /// Наименьшая важность записей на вкладке «Журнал»
+    log_level: Level,
+    /// Области панелей исходного и ретардера в прошлом кадре: туда бросают файлы
+    main_drop_rect: Option<egui::Rect>,
+    retarder_drop_rect: Option<egui::Rect>,
+    /// Положение шторки сравнения 0–1: левее — исходное, правее — результат
+    compare_wipe: f32,
+    /// Правее шторки показывать карту разницы вместо результата
+    compare_diff: bool,
+    /// Карта разницы и текстуры исходного и результата, по которым она построена
+    diff_texture: Option<(egui::TextureId, egui::TextureId, TextureHandle)>,
+    /// Строка изображения под курсором на результате или спектрограмме
+    hovered_line: Option<u32>,
 
-    processing: Arc<Mutex<bool>>,
+    job: Option<ProcessingJob>,
+    next_job_id: u64,
+    worker_tx: Sender<JobRequest>,
+    worker_rx: Receiver<JobMessage>,
+    /// Сигнал последней обработки после эффектов
+    last_signal: SampleBuffer,
+    /// Параметры и отчёт последней обработки — для папки прогона
+    last_run: Option<(AppParams, DecodeReport)>,
     last_process_time: Option<Instant>,
     auto_process: bool,
+    /// Автообработка идёт на уровне качества Preview, ручная — на выбранном
+    fast_preview: bool,
     manual_processing_requested: bool,
 
     // Параметры из интерфейса
@@ -41,18 +828,61 @@ struct SSTVApp {
     retarder_image_path: String,
     output_path: String,
 
-    // Локальные копии параметров для GUI
-    noise_level: u8,
-    noise_env: EnvelopeKind,
-    noise_repeat: f32,
-    retarder_level: f32,
-    retarder_env: EnvelopeKind,
-    retarder_repeat: f32,
-    delay_ms: u32,
+    // Параметры обработки, общие с CLI
+    params: AppParams,
+
+    // Связь огибающих и закреплённые параметры
+    link_envelopes: bool,
+    locked: HashSet<&'static str>,
+
+    // История результатов, новые в начале
+    history: VecDeque<HistoryEntry>,
+    next_history_id: u64,
+    selected_history: Option<u64>,
+    history_size: usize,
+
+    // Звуковые устройства
+    output_devices: Vec<String>,
+    input_devices: Vec<String>,
+    output_device: Option<String>,
+    input_device: Option<String>,
+    listen_secs: u64,
+
+    soak: SoakMode,
+    automation_target: AutomationTarget,
+    /// Запись автоматизации во время воспроизведения
+    automation: Option<AutomationSession>,
+    audio_busy: Arc<Mutex<bool>>,
+    listen_rx: Option<Receiver<Result<DecodeReport, String>>>,
+
+    // Непрерывный приём
+    monitor: Option<Monitor>,
+    /// Источник — сырой PCM, иначе устройство ввода
+    receive_pcm: bool,
+    receive_pcm_path: String,
+    receive_pcm_rate: u32,
+    receive_pcm_format: PcmFormat,
+    /// Место приёмника: QTH-локатор или «широта,долгота»; пусто — без места
+    receive_location: String,
+    /// Место берётся у gpsd по адресу `receive_gpsd_addr`, а не из `receive_location`
+    receive_gpsd: bool,
+    receive_gpsd_addr: String,
+    receive_squelch: SquelchParams,
+    receive_texture: Option<TextureHandle>,
+    /// Версия изображения приёмника, показанная в `receive_texture`
+    receive_revision: u64,
+    /// Принятые изображения, новые в начале
+    received: Vec<(ReceivedImage, TextureHandle)>,
 }
 
 impl SSTVApp {
-    fn new(_cc: &eframe::CreationContext<'_>) -> Self {
+    fn new(cc: &eframe::CreationContext<'_>, log: SessionLog) -> Self {
+        let _ = log.repaint.set(cc.egui_ctx.clone());
+        let (worker_tx, requests) = channel();
+        let (results, worker_rx) = channel();
+        let ctx = cc.egui_ctx.clone();
+        std::thread::spawn(move || run_worker(requests, results, ctx));
+
         Self {
             processor: SSTVProcessor::new(),
             main_image: None,
@@ -61,56 +891,239 @@ impl SSTVApp {
             main_texture: None,
             retarder_texture: None,
             result_texture: None,
-            processing: Arc::new(Mutex::new(false)),
+            quality: None,
+            registered: None,
+            concealed: None,
+            vis: None,
+            decode_summary: None,
+            suggestions: Suggestions::default(),
+            encode_cached: false,
+            param_warnings: Vec::new(),
+            channel_summary: None,
+            last_error: None,
+            spectrogram: None,
+            waveform: None,
+            inspection: None,
+            result_tab: ResultTab::Image,
+            dock: Tab::default_layout(),
+            detached: Vec::new(),
+            log,
+            log_level: Level::INFO,
+            main_drop_rect: None,
+            retarder_drop_rect: None,
+            compare_wipe: 0.5,
+            compare_diff: false,
+            diff_texture: None,
+            hovered_line: None,
+            job: None,
+            next_job_id: 0,
+            worker_tx,
+            worker_rx,
+            last_signal: SampleBuffer::mono(Vec::new(), SAMPLE_RATE as u32),
+            last_run: None,
             last_process_time: None,
             auto_process: false, // Отключаем по умолчанию
+            fast_preview: true,
             manual_processing_requested: false,
             main_image_path: String::new(),
             retarder_image_path: String::new(),
             output_path: "output.png".to_string(),
 
-            // Инициализируем локальные параметры значениями по умолчанию
-            noise_level: 0,
-            noise_env: EnvelopeKind::Const,
-            noise_repeat: 1.0,
-            retarder_level: 0.3,
-            retarder_env: EnvelopeKind::Const,
-            retarder_repeat: 1.0,
-            delay_ms: 0,
+            params: AppParams::default(),
+
+            link_envelopes: false,
+            locked: HashSet::new(),
+
+            history: VecDeque::new(),
+            next_history_id: 0,
+            selected_history: None,
+            history_size: HISTORY_SIZE,
+
+            output_devices: audio::output_devices().unwrap_or_default(),
+            input_devices: audio::input_devices().unwrap_or_default(),
+            output_device: None,
+            input_device: None,
+            listen_secs: 120,
+            soak: SoakMode::default(),
+            automation_target: AutomationTarget::Noise,
+            automation: None,
+            audio_busy: Arc::new(Mutex::new(false)),
+            listen_rx: None,
+            monitor: None,
+            receive_pcm: false,
+            receive_pcm_path: "-".to_string(),
+            receive_pcm_rate: 48000,
+            receive_pcm_format: PcmFormat::default(),
+            receive_location: String::new(),
+            receive_gpsd: false,
+            receive_gpsd_addr: location::GPSD_DEFAULT.to_string(),
+            receive_squelch: SquelchParams::default(),
+            receive_texture: None,
+            receive_revision: 0,
+            received: Vec::new(),
         }
     }
 
     fn load_image(&mut self, path: &str, is_main: bool) {
-        println!("Загружаем изображение: {}", path);
+        tracing::info!("{}", gui::log_loading_image(path));
         match image::open(path) {
-            Ok(img) => {
-                let (w, h) = img.dimensions();
-                println!("Загружено: {}×{} пикселей", w, h);
-
-                if is_main {
-                    self.main_image = Some(img);
-                } else {
-                    self.retarder_image = Some(img);
-                }
-                self.schedule_processing();
-            }
+            Ok(img) => self.set_image(img, is_main),
             Err(e) => {
-                eprintln!("Не удалось загрузить изображение {}: {}", path, e);
+                tracing::error!("{}", gui::log_image_failed(path, &e.to_string()));
             }
         }
     }
 
+    /// Ставит изображение основным или ретардером и планирует обработку
+    fn set_image(&mut self, img: DynamicImage, is_main: bool) {
+        let (w, h) = img.dimensions();
+        tracing::info!("{}", gui::log_image_loaded(w, h));
+
+        if is_main {
+            self.main_image = Some(img);
+        } else {
+            self.retarder_image = Some(img);
+        }
+        self.schedule_processing();
+    }
+
     fn load_image_and_update_texture(&mut self, ctx: &egui::Context, path: &str, is_main: bool) {
         self.load_image(path, is_main);
+        self.update_image_texture(ctx, is_main);
+    }
+
+    /// Загружает изображение из буфера обмена; если там текст — это путь к файлу
+    /// (так его кладут файловые менеджеры)
+    fn paste_image(&mut self, ctx: &egui::Context, is_main: bool) {
+        let mut clipboard = match arboard::Clipboard::new() {
+            Ok(clipboard) => clipboard,
+            Err(e) => {
+                tracing::error!("{}", gui::log_clipboard_unavailable(&e.to_string()));
+                return;
+            }
+        };
+        if let Ok(data) = clipboard.get_image() {
+            let Some(rgba) = image::RgbaImage::from_raw(
+                data.width as u32,
+                data.height as u32,
+                data.bytes.into_owned(),
+            ) else {
+                tracing::error!("{}", gui::log_clipboard_corrupted());
+                return;
+            };
+            // У вставленного изображения нет файла: команда CLI его не повторит
+            if is_main {
+                self.main_image_path.clear();
+            } else {
+                self.retarder_image_path.clear();
+            }
+            self.set_image(DynamicImage::ImageRgba8(rgba), is_main);
+            self.update_image_texture(ctx, is_main);
+            self.request_manual_processing();
+            return;
+        }
+        match clipboard.get_text() {
+            Ok(text) => self.load_pasted_path(ctx, &text, is_main),
+            Err(_) => tracing::warn!("{}", gui::log_clipboard_empty()),
+        }
+    }
+
+    /// Загружает изображение по пути из вставленного текста (`file://` допускается)
+    fn load_pasted_path(&mut self, ctx: &egui::Context, text: &str, is_main: bool) {
+        let text = text.lines().next().unwrap_or_default().trim();
+        let path = text.strip_prefix("file://").unwrap_or(text);
+        if !std::path::Path::new(path).is_file() {
+            tracing::warn!("{}", gui::log_clipboard_no_path(text));
+            return;
+        }
+        self.load_dropped_path(ctx, path, is_main);
+    }
+
+    /// Загружает изображение из перетащенного или вставленного файла, как кнопка «Выбрать»
+    fn load_dropped_path(&mut self, ctx: &egui::Context, path: &str, is_main: bool) {
+        if is_main {
+            self.main_image_path = path.to_string();
+        } else {
+            self.retarder_image_path = path.to_string();
+        }
+        self.load_image_and_update_texture(ctx, path, is_main);
+        self.request_manual_processing();
+    }
+
+    /// Файлы, брошенные на окно, и путь, вставленный Ctrl+V вне полей ввода. Цель —
+    /// панель ретардера, если курсор над ней, иначе основное изображение
+    fn handle_dropped_files(&mut self, ctx: &egui::Context) {
+        let (dropped, pasted, pointer) = ctx.input(|i| {
+            let dropped = i.raw.dropped_files.iter().find_map(|f| f.path.clone());
+            let pasted = i.events.iter().find_map(|e| match e {
+                egui::Event::Paste(text) => Some(text.clone()),
+                _ => None,
+            });
+            (dropped, pasted, i.pointer.hover_pos())
+        });
+        let is_main = !pointer.is_some_and(|pos| {
+            self.retarder_drop_rect
+                .is_some_and(|rect| rect.contains(pos))
+        });
+        if let Some(path) = dropped {
+            self.load_dropped_path(ctx, &path.to_string_lossy(), is_main);
+        } else if let Some(text) = pasted
+            && ctx.memory(|m| m.focused().is_none())
+        {
+            self.load_pasted_path(ctx, &text, is_main);
+        }
+    }
+
+    /// Подсвечивает панели, на которые можно бросить файл, пока его тянут над окном
+    fn paint_drop_targets(&self, ctx: &egui::Context) {
+        if ctx.input(|i| i.raw.hovered_files.is_empty()) {
+            return;
+        }
+        let painter = ctx.layer_painter(egui::LayerId::new(
+            egui::Order::Foreground,
+            egui::Id::new("drop_targets"),
+        ));
+        let targets = [
+            (self.main_drop_rect, gui::main_image()),
+            (self.retarder_drop_rect, messages::retarder()),
+        ];
+        for (rect, label) in targets {
+            let Some(rect) = rect else {
+                continue;
+            };
+            painter.rect_filled(rect, 4.0, egui::Color32::from_black_alpha(160));
+            painter.rect_stroke(
+                rect,
+                4.0,
+                egui::Stroke::new(2.0, egui::Color32::from_rgb(0, 255, 255)),
+            );
+            painter.text(
+                rect.center(),
+                egui::Align2::CENTER_CENTER,
+                gui::drop_here(label),
+                egui::FontId::proportional(16.0),
+                egui::Color32::WHITE,
+            );
+        }
+    }
 
+    /// Обновляет текстуру основного изображения или ретардера после загрузки
+    fn update_image_texture(&mut self, ctx: &egui::Context, is_main: bool) {
         // Создаем текстуры после загрузки изображений
-        if is_main && self.main_image.is_some() {
-            let img = self.main_image.as_ref().unwrap();
-            let color_image = Self::dynamic_image_to_color_image(img);
+        if is_main && let Some(img) = &self.main_image {
+            // Основное изображение показывается с надписью, как оно уйдёт в эфир
+            let overlay = self.params.overlay.to_params();
+            let metadata = self.params.metadata();
+            let color_image = match metadata.overlay_on(&overlay).apply(img) {
+                Ok(overlaid) => Self::dynamic_image_to_color_image(&overlaid),
+                Err(e) => {
+                    tracing::warn!("{}", gui::log_overlay_failed(&format!("{:#}", e)));
+                    Self::dynamic_image_to_color_image(img)
+                }
+            };
             let handle = ctx.load_texture("main", color_image, egui::TextureOptions::LINEAR);
             self.main_texture = Some(handle);
-        } else if !is_main && self.retarder_image.is_some() {
-            let img = self.retarder_image.as_ref().unwrap();
+        } else if !is_main && let Some(img) = &self.retarder_image {
             let color_image = Self::dynamic_image_to_color_image(img);
             let handle = ctx.load_texture("retarder", color_image, egui::TextureOptions::LINEAR);
             self.retarder_texture = Some(handle);
@@ -125,10 +1138,7 @@ impl SSTVApp {
         const SSTV_HEIGHT: u32 = 256;
 
         if w != SSTV_WIDTH || h != SSTV_HEIGHT {
-            println!(
-                "Изменяем размер с {}×{} на {}×{} для SSTV",
-                w, h, SSTV_WIDTH, SSTV_HEIGHT
-            );
+            tracing::info!("{}", gui::log_resizing(w, h, SSTV_WIDTH, SSTV_HEIGHT));
             img.resize_exact(
                 SSTV_WIDTH,
                 SSTV_HEIGHT,
@@ -159,45 +1169,126 @@ impl SSTVApp {
         self.manual_processing_requested = true;
     }
 
-    fn sync_params_to_processor(&mut self) {
-        // Обновляем параметры без пересоздания процессоров
-        self.processor.params.noise.level = self.noise_level;
-        self.processor.params.noise.env = self.noise_env;
-        self.processor.params.noise.repeat = self.noise_repeat;
+    /// Переносит огибающую шума на ретардер, если огибающие связаны
+    fn link_from_noise(&mut self) {
+        if self.link_envelopes {
+            self.params.ret_env = self.params.noise_env.clone();
+            self.params.ret_env_file = self.params.noise_env_file.clone();
+            self.params.ret_repeat = self.params.noise_repeat;
+        }
+    }
+
+    /// Переносит огибающую ретардера на шум, если огибающие связаны
+    fn link_from_retarder(&mut self) {
+        if self.link_envelopes {
+            self.params.noise_env = self.params.ret_env.clone();
+            self.params.noise_env_file = self.params.ret_env_file.clone();
+            self.params.noise_repeat = self.params.ret_repeat;
+        }
+    }
+
+    /// Кнопка-замок: закреплённые параметры не меняются при случайном подборе
+    fn lock_button(ui: &mut egui::Ui, locked: &mut HashSet<&'static str>, key: &'static str) {
+        let is_locked = locked.contains(key);
+        let icon = if is_locked { "🔒" } else { "🔓" };
+        if ui
+            .small_button(icon)
+            .on_hover_text(gui::lock_hover())
+            .clicked()
+        {
+            if is_locked {
+                locked.remove(key);
+            } else {
+                locked.insert(key);
+            }
+        }
+    }
+
+    /// Случайно подбирает незакреплённые параметры в пределах ползунков
+    fn randomize_params(&mut self) {
+        let mut rng = rand::rng();
+        let free = |key: &str| !self.locked.contains(key);
+
+        if free("noise_level") {
+            self.params.noise = rng.random_range(0..=100);
+        }
+        if free("impulse_rate") {
+            self.params.impulse_rate = rng.random_range(0.1..=50.0);
+        }
+        if free("impulse_ms") {
+            self.params.impulse_ms = rng.random_range(0.1..=100.0);
+        }
+        if free("noise_repeat") {
+            self.params.noise_repeat = rng.random_range(0.1..=10.0);
+        }
+        if free("retarder_level") {
+            self.params.level = rng.random_range(0.0..=1.0);
+        }
+        if free("retarder_repeat") {
+            self.params.ret_repeat = rng.random_range(0.1..=10.0);
+        }
+        if free("delay_ms") {
+            self.params.delay_ms = rng.random_range(0..=1000);
+        }
+        if free("fade_depth") {
+            self.params.fade_depth = rng.random_range(0.0..=1.0);
+        }
+        if free("fade_rate") {
+            self.params.fade_rate = rng.random_range(0.01..=5.0);
+        }
+        if free("offset_hz") {
+            self.params.offset_hz = rng.random_range(-300.0..=300.0);
+        }
+        if free("drift_hz_per_s") {
+            self.params.drift_hz_per_s = rng.random_range(-5.0..=5.0);
+        }
+        if free("wobble_hz") {
+            self.params.wobble_hz = rng.random_range(0.0..=50.0);
+        }
+
+        self.link_from_noise();
+        self.schedule_processing();
+    }
 
-        self.processor.params.retarder.level = self.retarder_level;
-        self.processor.params.retarder.env = self.retarder_env;
-        self.processor.params.retarder.repeat = self.retarder_repeat;
-        self.processor.params.retarder.delay_ms = self.delay_ms;
+    fn sync_params_to_processor(&mut self) {
+        // Параметры строятся так же, как в CLI
+        let applied = self.params.to_params().and_then(|params| {
+            self.param_warnings = params.validate();
+            self.processor.try_set_params(params)
+        });
+        if let Err(e) = applied {
+            tracing::error!("{}", gui::log_params_error(&format!("{:#}", e)));
+        }
     }
 
-    fn process_if_needed(&mut self, ctx: &egui::Context) {
-        let should_process = if self.manual_processing_requested {
+    fn process_if_needed(&mut self) {
+        // Во время записи автоматизации ползунок движется непрерывно: обработка ждёт её конца
+        if self.automation.is_some() {
+            return;
+        }
+        let preview = if self.manual_processing_requested {
             self.manual_processing_requested = false;
-            true
+            Some(false)
         } else if let Some(last_time) = self.last_process_time {
             if last_time.elapsed().as_millis() > 2000 {
                 // Увеличиваем debounce до 2 секунд
                 self.last_process_time = None;
-                true
+                Some(self.fast_preview)
             } else {
-                false
+                None
             }
         } else {
-            false
+            None
         };
 
-        if should_process {
-            self.process_images(ctx);
+        if let Some(preview) = preview {
+            self.process_images(preview);
         }
     }
 
-    fn process_images(&mut self, ctx: &egui::Context) {
-        // Проверяем, что не происходит обработка и есть главное изображение
-        if self.processing.try_lock().map_or(true, |p| *p) {
-            return;
-        }
-
+    /// Запускает обработку; при `preview` — на уровне качества Preview независимо от выбранного
+    fn process_images(&mut self, preview: bool) {
+        // Проверяем, что есть главное изображение
         let (original_main_image, _original_dimensions) = match &self.main_image {
             Some(img) => {
                 let dims = img.dimensions();
@@ -211,370 +1302,3656 @@ impl SSTVApp {
         let retarder_image = self
             .retarder_image
             .as_ref()
-            .map(|img| Self::prepare_image_for_sstv(img));
-
-        // Устанавливаем флаг обработки
-        if let Ok(mut is_processing) = self.processing.try_lock() {
-            *is_processing = true;
-        }
+            .map(Self::prepare_image_for_sstv);
 
         // Синхронизируем параметры ТОЛЬКО ОДИН РАЗ
         self.sync_params_to_processor();
+        // Сводка канала считается без прогона эффектов и видна сразу
+        self.channel_summary = self
+            .processor
+            .analyze(&main_image, retarder_image.as_ref())
+            .ok();
 
-        println!("Начинаем SSTV обработку (320×256)...");
-        let start_time = std::time::Instant::now();
+        tracing::info!("{}", gui::log_processing_started());
+        for warning in &self.param_warnings {
+            tracing::warn!("{}", warning);
+        }
 
-        // Выполняем обработку на уменьшенных изображениях
-        match self.processor.process(&main_image, retarder_image.as_ref()) {
-            Ok(sstv_result) => {
-                let duration = start_time.elapsed();
-                println!("Обработка завершена за {:.2}с", duration.as_secs_f32());
-                let final_result = sstv_result;
+        // Новый запрос вытесняет прежний: его результат уже устарел
+        if let Some(previous) = &self.job {
+            previous.cancel.store(true, Ordering::Relaxed);
+        }
 
-                let color_image = Self::dynamic_image_to_color_image(&final_result);
-                let handle = ctx.load_texture("result", color_image, egui::TextureOptions::LINEAR);
-                self.result_image = Some(final_result);
+        let id = self.next_job_id;
+        self.next_job_id += 1;
+        let cancel = Arc::new(AtomicBool::new(false));
+        let mut params = self.processor.params().clone();
+        if preview {
+            params.quality = QualityTier::Preview;
+        }
+        let request = JobRequest {
+            id,
+            params,
+            main_image: main_image.clone(),
+            retarder_image,
+            cancel: Arc::clone(&cancel),
+        };
+        if self.worker_tx.send(request).is_err() {
+            tracing::error!("{}", gui::log_worker_unavailable());
+            return;
+        }
+
+        // Качество считается относительно переданного, то есть с надписью
+        let main_image = match self.processor.params().overlay_params().apply(&main_image) {
+            Ok(overlaid) => overlaid.into_owned(),
+            Err(_) => main_image,
+        };
+        self.job = Some(ProcessingJob {
+            id,
+            cancel,
+            progress: None,
+            decoded: None,
+            started: Instant::now(),
+            main_image,
+            params: self.params.clone(),
+            encoder: self.processor.params().encoder.clone(),
+            command: self.cli_command(),
+        });
+    }
+
+    /// Забирает сообщения фоновой обработки; сообщения вытесненных запросов отбрасываются
+    fn poll_job(&mut self, ctx: &egui::Context) {
+        let mut finished = None;
+        let mut rows_changed = false;
+        while let Ok(message) = self.worker_rx.try_recv() {
+            let Some(job) = &mut self.job else {
+                continue;
+            };
+            match message {
+                JobMessage::DecodeStarted(id, width, height) if id == job.id => {
+                    // Новые строки ложатся поверх прежнего результата, как при приёме
+                    let image = match &self.result_image {
+                        Some(previous) if previous.dimensions() == (width, height) => {
+                            previous.to_rgb8()
+                        }
+                        _ => image::RgbImage::new(width, height),
+                    };
+                    let size = [width as usize, height as usize];
+                    let color_image = ColorImage::from_rgb(size, image.as_raw());
+                    let handle =
+                        ctx.load_texture("decoding", color_image, egui::TextureOptions::LINEAR);
+                    job.decoded = Some((image, handle));
+                }
+                JobMessage::DecodedRow(id, y, row) if id == job.id => {
+                    if let Some((image, _)) = &mut job.decoded {
+                        let stride = image.width() as usize * 3;
+                        let start = y as usize * stride;
+                        if let Some(dst) = image.get_mut(start..start + stride) {
+                            dst.copy_from_slice(&row[..stride]);
+                            rows_changed = true;
+                        }
+                    }
+                }
+                JobMessage::Progress(id, stage, fraction) if id == job.id => {
+                    job.progress = Some((stage, fraction));
+                }
+                JobMessage::Advice(id, suggestions) if id == job.id => {
+                    self.suggestions = suggestions;
+                }
+                JobMessage::Rendered(id, waveform) if id == job.id => {
+                    self.waveform = Some(waveform);
+                    self.inspection = None;
+                }
+                JobMessage::Done(id, _) | JobMessage::Cancelled(id) if id == job.id => {
+                    finished = Some(message);
+                    break;
+                }
+                _ => {}
+            }
+        }
+        // Текстура обновляется раз за кадр, сколько бы строк ни пришло
+        if rows_changed
+            && let Some(job) = &mut self.job
+            && let Some((image, handle)) = &mut job.decoded
+        {
+            let size = [image.width() as usize, image.height() as usize];
+            handle.set(
+                ColorImage::from_rgb(size, image.as_raw()),
+                egui::TextureOptions::LINEAR,
+            );
+        }
+        let Some(message) = finished else {
+            return;
+        };
+        let Some(job) = self.job.take() else {
+            return;
+        };
+        let elapsed = job.started.elapsed().as_secs_f32();
+
+        match message {
+            JobMessage::Done(_, Ok(result)) => {
+                let JobResult {
+                    report,
+                    signal,
+                    conceal,
+                    encode_cached,
+                } = *result;
+                let note = if encode_cached {
+                    gui::log_encoder_cached()
+                } else {
+                    ""
+                };
+                tracing::info!("{}", gui::log_processing_done(elapsed, note));
+                self.encode_cached = encode_cached;
+                self.last_error = None;
+                self.last_run = Some((job.params.clone(), report.clone()));
+                let image = self.take_report(report, conceal);
+                let color_image = Self::dynamic_image_to_color_image(&image);
+                let handle = ctx.load_texture("result", color_image, egui::TextureOptions::LINEAR);
+                let space = job.params.metric_space;
+                self.quality = Some(metrics::compare_in(&job.main_image, &image, space));
+                self.registered = Some(metrics::compare_registered(&job.main_image, &image, space));
+                let timing = encoder::timing(&job.encoder, signal.rate, signal.len());
+                self.spectrogram = Some(SpectrogramView::new(ctx, &signal, signal.rate, timing));
+                self.last_signal = signal;
+                self.push_history(job.params, job.command, image.clone(), handle.clone());
+                self.result_image = Some(image);
                 self.result_texture = Some(handle);
+                if self.soak.running {
+                    self.soak.cycles += 1;
+                    self.soak.shown_at = Some(Instant::now());
+                }
             }
-            Err(e) => {
-                let duration = start_time.elapsed();
-                eprintln!("Ошибка обработки за {:.2}с: {}", duration.as_secs_f32(), e);
+            JobMessage::Done(_, Err(e)) => {
+                tracing::error!("{}", gui::log_processing_failed(elapsed, &e.to_string()));
+                self.last_error = Some(e);
+                if self.soak.running {
+                    self.soak.errors += 1;
+                    self.soak.shown_at = Some(Instant::now());
+                }
+            }
+            JobMessage::Cancelled(_) => {
+                tracing::info!("{}", gui::log_processing_cancelled(elapsed));
+            }
+            JobMessage::Progress(..)
+            | JobMessage::Advice(..)
+            | JobMessage::Rendered(..)
+            | JobMessage::DecodeStarted(..)
+            | JobMessage::DecodedRow(..) => {}
+        }
+    }
+
+    /// Запоминает, как прошло декодирование, и возвращает изображение отчёта
+    fn take_report(&mut self, report: DecodeReport, conceal: bool) -> DynamicImage {
+        self.decode_summary = Some(report.describe());
+        let DecodeReport {
+            image,
+            vis_detected,
+            concealed_lines,
+            channel,
+            ..
+        } = report;
+        // Сводка прогона точнее предварительной: посчитана на частоте эффектов этого прогона
+        if channel.is_some() {
+            self.channel_summary = channel;
+        }
+        self.concealed = conceal.then_some(concealed_lines.len());
+        self.vis = vis_detected;
+        image
+    }
+
+    /// Добавляет результат в историю, вытесняя самый старый
+    fn push_history(
+        &mut self,
+        params: AppParams,
+        command: String,
+        image: DynamicImage,
+        texture: TextureHandle,
+    ) {
+        let id = self.next_history_id;
+        self.next_history_id += 1;
+        self.history.push_front(HistoryEntry {
+            id,
+            params,
+            command,
+            image,
+            texture,
+            quality: self.quality,
+            registered: self.registered,
+            concealed: self.concealed,
+            vis: self.vis.clone(),
+            decode_summary: self.decode_summary.clone(),
+            spectrogram: self.spectrogram.clone(),
+            waveform: self.waveform.clone(),
+        });
+        self.history.truncate(if self.soak.running {
+            SOAK_HISTORY_SIZE
+        } else {
+            self.history_size
+        });
+        self.selected_history = Some(id);
+    }
+
+    /// Витрина: когда результат показан положенное время, берёт следующее изображение очереди.
+    /// Между изображениями проверяется память и при превышении предела сбрасываются кэши
+    fn poll_soak(&mut self, ctx: &egui::Context) {
+        if !self.soak.running || self.job.is_some() || self.listen_rx.is_some() {
+            return;
+        }
+        if let Some(shown) = self.soak.shown_at
+            && shown.elapsed().as_secs_f32() < self.soak.dwell_secs
+        {
+            return;
+        }
+        if self.soak.queue.is_empty() {
+            self.soak.running = false;
+            return;
+        }
+
+        if let Some(memory) = resident_memory() {
+            self.soak.peak_memory = self.soak.peak_memory.max(memory);
+            if memory > self.soak.memory_limit_mb * 1024 * 1024 {
+                tracing::info!("{}", gui::log_soak_cleanup(memory / (1024 * 1024)));
+                self.release_caches();
+                self.soak.cleanups += 1;
+            }
+        }
+
+        let path = self.soak.queue[self.soak.next % self.soak.queue.len()].clone();
+        self.soak.next = (self.soak.next + 1) % self.soak.queue.len();
+        self.main_image_path = path.clone();
+        self.load_image_and_update_texture(ctx, &path, true);
+        if self.soak.randomize {
+            self.randomize_params();
+        }
+        self.soak.shown_at = None;
+        // Обработка запускается сразу, отложенная по автообработке не нужна
+        self.last_process_time = None;
+        self.process_images(false);
+    }
+
+    /// Освобождает историю с её текстурами и данные сигнала последнего результата;
+    /// текущее изображение результата остаётся на экране
+    fn release_caches(&mut self) {
+        self.history.clear();
+        self.selected_history = None;
+        self.spectrogram = None;
+        self.waveform = None;
+        self.inspection = None;
+        self.diff_texture = None;
+        self.last_signal.data.clear();
+        self.last_run = None;
+    }
+
+    /// Настройки и состояние витрины
+    fn soak_panel(&mut self, ui: &mut egui::Ui, ctx: &egui::Context) {
+        let soak = &mut self.soak;
+        ui.add_enabled_ui(!soak.running, |ui| {
+            ui.horizontal(|ui| {
+                if ui.button(gui::soak_folder()).clicked()
+                    && let Some(folder) = rfd::FileDialog::new()
+                        .set_title(gui::soak_folder_title())
+                        .pick_folder()
+                {
+                    match list_images(&folder) {
+                        Ok(images) => {
+                            soak.queue = images;
+                            soak.next = 0;
+                        }
+                        Err(e) => {
+                            tracing::error!(
+                                "{}",
+                                gui::log_folder_failed(
+                                    &folder.display().to_string(),
+                                    &e.to_string()
+                                )
+                            )
+                        }
+                    }
+                }
+                ui.label(gui::soak_queued(soak.queue.len()));
+            });
+            ui.add(
+                egui::Slider::new(&mut soak.dwell_secs, 1.0..=600.0)
+                    .logarithmic(true)
+                    .text(gui::soak_dwell()),
+            );
+            ui.checkbox(&mut soak.randomize, gui::soak_randomize());
+            ui.horizontal(|ui| {
+                ui.label(gui::soak_memory_limit());
+                ui.add(egui::DragValue::new(&mut soak.memory_limit_mb).clamp_range(256..=65536));
+            });
+        });
+
+        if soak.running {
+            if ui.button(gui::soak_stop()).clicked() {
+                soak.running = false;
+            }
+        } else if ui
+            .add_enabled(!soak.queue.is_empty(), egui::Button::new(gui::soak_start()))
+            .clicked()
+        {
+            *soak = SoakMode {
+                queue: std::mem::take(&mut soak.queue),
+                running: true,
+                dwell_secs: soak.dwell_secs,
+                randomize: soak.randomize,
+                memory_limit_mb: soak.memory_limit_mb,
+                ..SoakMode::default()
+            };
+            // Лишние записи обычной истории сразу отбрасываются
+            self.history.truncate(SOAK_HISTORY_SIZE);
+            return;
+        }
+
+        if soak.running || soak.cycles > 0 {
+            let uptime = soak.started.elapsed().as_secs();
+            let (textures, texture_bytes) = {
+                let manager = ctx.tex_manager();
+                let manager = manager.read();
+                let bytes: usize = manager.allocated().map(|(_, meta)| meta.bytes_used()).sum();
+                (manager.num_allocated(), bytes)
+            };
+            let mb = |bytes: u64| bytes as f64 / (1024.0 * 1024.0);
+            ui.label(gui::soak_uptime(
+                uptime / 3600,
+                uptime / 60 % 60,
+                uptime % 60,
+                soak.cycles,
+                soak.errors,
+            ));
+            if let Some(memory) = resident_memory() {
+                ui.label(gui::soak_memory(
+                    mb(memory),
+                    mb(soak.peak_memory.max(memory)),
+                    soak.cleanups,
+                ));
+            }
+            ui.label(gui::soak_textures(
+                textures,
+                mb(texture_bytes as u64),
+                self.history.len(),
+            ));
+        }
+    }
+
+    /// Возвращает параметры и результат из истории без повторной обработки
+    fn restore_history(&mut self, id: u64) {
+        let Some(entry) = self.history.iter().find(|e| e.id == id) else {
+            return;
+        };
+        self.params = entry.params.clone();
+        self.result_image = Some(entry.image.clone());
+        self.result_texture = Some(entry.texture.clone());
+        self.quality = entry.quality;
+        self.registered = entry.registered;
+        self.concealed = entry.concealed;
+        self.vis = entry.vis.clone();
+        self.decode_summary = entry.decode_summary.clone();
+        self.spectrogram = entry.spectrogram.clone();
+        self.waveform = entry.waveform.clone();
+        self.inspection = None;
+        self.selected_history = Some(id);
+
+        // Отменяем отложенную обработку, иначе она перезапишет восстановленный результат
+        self.last_process_time = None;
+        self.sync_params_to_processor();
+    }
+
+    /// Соседняя с показанной запись истории: более старая (`older`, отмена) или более
+    /// новая (возврат)
+    fn history_neighbour(&self, older: bool) -> Option<u64> {
+        let index = self
+            .selected_history
+            .and_then(|id| self.history.iter().position(|e| e.id == id));
+        let next = match (index, older) {
+            (Some(index), true) => index + 1,
+            (Some(index), false) => index.checked_sub(1)?,
+            // Показанная запись вытеснена: отмена возвращает последний результат
+            (None, true) => 0,
+            (None, false) => return None,
+        };
+        self.history.get(next).map(|e| e.id)
+    }
+
+    /// Ctrl+Z / Ctrl+Shift+Z (Ctrl+Y) вне полей ввода шагают по истории
+    fn handle_history_keys(&mut self, ctx: &egui::Context) {
+        if ctx.memory(|m| m.focused().is_some()) {
+            return;
+        }
+        let (undo, redo) = ctx.input_mut(|i| {
+            // Ctrl+Z совпадает и с Ctrl+Shift+Z, поэтому возврат проверяется первым
+            let redo = i.consume_shortcut(&egui::KeyboardShortcut::new(
+                egui::Modifiers::COMMAND | egui::Modifiers::SHIFT,
+                egui::Key::Z,
+            )) || i.consume_shortcut(&egui::KeyboardShortcut::new(
+                egui::Modifiers::COMMAND,
+                egui::Key::Y,
+            ));
+            let undo = i.consume_shortcut(&egui::KeyboardShortcut::new(
+                egui::Modifiers::COMMAND,
+                egui::Key::Z,
+            ));
+            (undo, redo)
+        });
+        if (undo || redo)
+            && let Some(id) = self.history_neighbour(undo)
+        {
+            self.restore_history(id);
+        }
+    }
+
+    /// Лента миниатюр истории с кнопками отмены и возврата; возвращает выбранную запись
+    fn history_strip(&mut self, ui: &mut egui::Ui) -> Option<u64> {
+        let mut clicked = None;
+        ui.horizontal(|ui| {
+            ui.label(gui::history_label());
+            let older = self.history_neighbour(true);
+            if ui
+                .add_enabled(older.is_some(), egui::Button::new("↶"))
+                .on_hover_text(gui::history_previous())
+                .clicked()
+            {
+                clicked = older;
+            }
+            let newer = self.history_neighbour(false);
+            if ui
+                .add_enabled(newer.is_some(), egui::Button::new("↷"))
+                .on_hover_text(gui::history_next())
+                .clicked()
+            {
+                clicked = newer;
+            }
+            if ui
+                .add(
+                    egui::DragValue::new(&mut self.history_size)
+                        .clamp_range(1..=MAX_HISTORY_SIZE)
+                        .prefix(gui::history_keep()),
+                )
+                .on_hover_text(gui::history_size_hover())
+                .changed()
+                && !self.soak.running
+            {
+                self.history.truncate(self.history_size);
+            }
+        });
+        egui::ScrollArea::horizontal()
+            .id_source("history")
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    for (age, entry) in self.history.iter().enumerate() {
+                        let size = entry.texture.size_vec2();
+                        let thumb = size * (64.0 / size.y.max(1.0));
+                        let image =
+                            egui::Image::from_texture(&entry.texture).fit_to_exact_size(thumb);
+                        let selected = self.selected_history == Some(entry.id);
+                        let hover = match age {
+                            0 => gui::history_latest(&entry.command),
+                            _ => gui::history_ago(age, &entry.command),
+                        };
+                        if ui
+                            .add(egui::ImageButton::new(image).selected(selected))
+                            .on_hover_text(hover)
+                            .clicked()
+                        {
+                            clicked = Some(entry.id);
+                        }
+                    }
+                });
+            });
+        clicked
+    }
+
+    fn play_result(&mut self) {
+        let signal = self.last_signal.clone();
+        if signal.is_empty() {
+            tracing::warn!("{}", gui::log_no_signal());
+            return;
+        }
+
+        let device = self.output_device.clone();
+        let busy = Arc::clone(&self.audio_busy);
+        *busy.lock().unwrap() = true;
+        std::thread::spawn(move || {
+            if let Err(e) = audio::play(&signal, device.as_deref()) {
+                tracing::error!("{}", gui::log_playback_failed(&e.to_string()));
+            }
+            *busy.lock().unwrap() = false;
+        });
+    }
+
+    /// Воспроизводит сигнал и записывает движения ползунка выбранного параметра
+    fn start_automation(&mut self) {
+        if self.last_signal.is_empty() {
+            tracing::warn!("{}", gui::log_no_signal());
+            return;
+        }
+        self.automation = Some(AutomationSession {
+            target: self.automation_target,
+            started: Instant::now(),
+            duration: self.last_signal.duration_secs() as f32,
+            recording: AutomationRecording::new(),
+        });
+        self.play_result();
+    }
+
+    /// Дописывает значение ползунка; по окончании воспроизведения сохраняет огибающую
+    fn poll_automation(&mut self, ctx: &egui::Context) {
+        let Some(session) = &mut self.automation else {
+            return;
+        };
+        let t = session.started.elapsed().as_secs_f32();
+        let value = match session.target {
+            AutomationTarget::Noise => f32::from(self.params.noise),
+            AutomationTarget::Retarder => self.params.level,
+        };
+        session.recording.record(t.min(session.duration), value);
+        if t < session.duration {
+            ctx.request_repaint_after(Duration::from_millis(20));
+            return;
+        }
+        if let Some(session) = self.automation.take() {
+            self.finish_automation(session);
+        }
+    }
+
+    /// Сохраняет запись как огибающую по точкам и ставит её параметру: уровень
+    /// становится наибольшим записанным, огибающая растягивается на весь звук
+    fn finish_automation(&mut self, session: AutomationSession) {
+        let target = session.target;
+        let Some((curve, base)) = session
+            .recording
+            .to_envelope(session.duration, target.amplitude())
+        else {
+            return;
+        };
+        let path = Path::new(&self.output_path).with_file_name(target.file_name());
+        if let Err(e) = curve.save(&path) {
+            self.last_error = Some(gui::automation_save_failed(&e.to_string()));
+            return;
+        }
+        let file = Some(path.to_string_lossy().to_string());
+        let env = EnvelopeKind::Custom(curve);
+        match target {
+            AutomationTarget::Noise => {
+                self.params.noise = base.round() as u8;
+                self.params.noise_env = env;
+                self.params.noise_env_file = file;
+                self.params.noise_repeat = 1.0;
+                self.params.noise_anchor = EnvelopeAnchor::Audio;
+                self.link_from_noise();
+            }
+            AutomationTarget::Retarder => {
+                self.params.level = base;
+                self.params.ret_env = env;
+                self.params.ret_env_file = file;
+                self.params.ret_repeat = 1.0;
+                self.params.ret_anchor = EnvelopeAnchor::Audio;
+                self.link_from_retarder();
+            }
+        }
+        tracing::info!("{}", gui::log_automation_saved(&path.display().to_string()));
+        self.request_manual_processing();
+    }
+
+    fn start_listening(&mut self) {
+        let device = self.input_device.clone();
+        let duration = Duration::from_secs(self.listen_secs);
+        let params = self.processor.params().clone();
+        let busy = Arc::clone(&self.audio_busy);
+        let (tx, rx) = channel();
+
+        *busy.lock().unwrap() = true;
+        std::thread::spawn(move || {
+            let result = audio::capture(duration, device.as_deref())
+                .and_then(|signal| SSTVProcessor::new_with_params(params).decode_samples(&signal));
+            let _ = tx.send(result.map_err(|e| e.to_string()));
+            *busy.lock().unwrap() = false;
+        });
+        self.listen_rx = Some(rx);
+    }
+
+    fn poll_listening(&mut self, ctx: &egui::Context) {
+        let Some(rx) = &self.listen_rx else {
+            return;
+        };
+        match rx.try_recv() {
+            Ok(Ok(report)) => {
+                let conceal = self.processor.params().decoder.conceal;
+                let img = self.take_report(report, conceal);
+                let color_image = Self::dynamic_image_to_color_image(&img);
+                let handle = ctx.load_texture("result", color_image, egui::TextureOptions::LINEAR);
+                self.result_image = Some(img);
+                self.result_texture = Some(handle);
+                self.quality = None;
+                self.registered = None;
+                self.spectrogram = None;
+                self.waveform = None;
+                self.inspection = None;
+                self.listen_rx = None;
+                tracing::info!("{}", gui::log_capture_decoded());
+            }
+            Ok(Err(e)) => {
+                tracing::error!("{}", gui::log_capture_failed(&e));
+                self.listen_rx = None;
+            }
+            Err(std::sync::mpsc::TryRecvError::Empty) => {}
+            Err(std::sync::mpsc::TryRecvError::Disconnected) => self.listen_rx = None,
+        }
+    }
+
+    fn start_monitor(&mut self, ctx: &egui::Context) {
+        let source = if self.receive_pcm {
+            MonitorSource::Pcm {
+                path: self.receive_pcm_path.clone().into(),
+                sample_rate: self.receive_pcm_rate,
+                format: self.receive_pcm_format,
+            }
+        } else {
+            MonitorSource::Device(self.input_device.clone())
+        };
+        let location = if self.receive_gpsd {
+            Some(LocationSource::Gpsd(
+                self.receive_gpsd_addr.trim().to_string(),
+            ))
+        } else if self.receive_location.trim().is_empty() {
+            None
+        } else {
+            match self.receive_location.parse() {
+                Ok(location) => Some(LocationSource::Static(location)),
+                Err(e) => {
+                    tracing::error!("{}", gui::log_error(&e.to_string()));
+                    return;
+                }
+            }
+        };
+        let params = MonitorParams {
+            source,
+            decoder: self.processor.params().decoder.clone(),
+            location,
+            squelch: self.receive_squelch,
+            ..Default::default()
+        };
+        let ctx = ctx.clone();
+        self.receive_texture = None;
+        self.monitor = Some(Monitor::start(params, move || ctx.request_repaint()));
+    }
+
+    /// Забирает у приёмника законченные изображения и обновляет текстуру текущего,
+    /// когда в нём появились строки
+    fn poll_monitor(&mut self, ctx: &egui::Context) {
+        let Some(monitor) = &self.monitor else {
+            return;
+        };
+        for received in monitor.take_received() {
+            let texture = ctx.load_texture(
+                "received",
+                rgb_to_color_image(&received.image),
+                egui::TextureOptions::LINEAR,
+            );
+            self.received.insert(0, (received, texture));
+        }
+        let state = monitor.state();
+        if state.revision != self.receive_revision {
+            self.receive_revision = state.revision;
+            self.receive_texture = state.image.as_ref().map(|image| {
+                ctx.load_texture(
+                    "receive",
+                    rgb_to_color_image(image),
+                    egui::TextureOptions::LINEAR,
+                )
+            });
+        }
+    }
+
+    /// Показывает принятое изображение в панели результата
+    fn show_received(&mut self, ctx: &egui::Context, index: usize) {
+        let Some((received, _)) = self.received.get(index) else {
+            return;
+        };
+        let img = DynamicImage::ImageRgb8(received.image.clone());
+        let handle = ctx.load_texture(
+            "result",
+            Self::dynamic_image_to_color_image(&img),
+            egui::TextureOptions::LINEAR,
+        );
+        self.vis = received.vis.clone();
+        self.result_image = Some(img);
+        self.result_texture = Some(handle);
+        self.quality = None;
+        self.registered = None;
+        self.spectrogram = None;
+        self.waveform = None;
+        self.inspection = None;
+    }
+
+    fn save_received(&self, index: usize) -> anyhow::Result<()> {
+        let Some((received, _)) = self.received.get(index) else {
+            return Ok(());
+        };
+        if let Some(path) = rfd::FileDialog::new()
+            .add_filter("PNG", &["png"])
+            .set_file_name("received.png")
+            .save_file()
+        {
+            let image = DynamicImage::ImageRgb8(received.image.clone());
+            std::fs::write(
+                &path,
+                metadata::png_with_text(&image, &received.text_fields())?,
+            )?;
+            tracing::info!("{}", gui::log_saved(&path.display().to_string()));
+        }
+        Ok(())
+    }
+
+    /// Пишет в WAV последние секунды сигнала, которые хранит приёмник
+    fn save_received_audio(&self) -> anyhow::Result<()> {
+        let Some(monitor) = &self.monitor else {
+            return Ok(());
+        };
+        let recent: Vec<f32> = monitor.state().recent.iter().copied().collect();
+        if let Some(path) = rfd::FileDialog::new()
+            .add_filter("WAV", &["wav"])
+            .set_file_name("received.wav")
+            .save_file()
+        {
+            let path = path.display().to_string();
+            wav::write_wav(&path, &SampleBuffer::mono(recent, SAMPLE_RATE as u32))?;
+            tracing::info!("{}", gui::log_saved(&path));
+        }
+        Ok(())
+    }
+
+    fn device_combo(
+        ui: &mut egui::Ui,
+        label: &str,
+        devices: &[String],
+        selected: &mut Option<String>,
+    ) {
+        egui::ComboBox::from_label(label)
+            .selected_text(selected.as_deref().unwrap_or(gui::default_device()))
+            .show_ui(ui, |ui| {
+                ui.selectable_value(selected, None, gui::default_device());
+                for name in devices {
+                    ui.selectable_value(selected, Some(name.clone()), name);
+                }
+            });
+    }
+
+    fn panel_image(&self, panel: Panel) -> Option<&DynamicImage> {
+        match panel {
+            Panel::Main => self.main_image.as_ref(),
+            Panel::Retarder => self.retarder_image.as_ref(),
+            Panel::Result => self.result_image.as_ref(),
+            Panel::Waterfall => self.spectrogram.as_ref().map(|view| &view.waterfall),
+        }
+    }
+
+    /// Показывает изображение панели с контекстным меню экспорта
+    fn image_panel(
+        ui: &mut egui::Ui,
+        texture: &TextureHandle,
+        panel: Panel,
+        action: &mut Option<PanelAction>,
+    ) -> egui::Response {
+        let size = texture.size_vec2();
+        let scale = (300.0 / size.x.max(size.y)).min(1.0);
+        let scaled_size = size * scale;
+        let response = ui
+            .add(
+                egui::Image::from_texture(texture)
+                    .fit_to_exact_size(scaled_size)
+                    .sense(egui::Sense::click()),
+            )
+            .on_hover_text(gui::export_hover());
+        response.context_menu(|ui| {
+            let mut item = |ui: &mut egui::Ui, text: &str, a: PanelAction| {
+                if ui.button(text).clicked() {
+                    *action = Some(a);
+                    ui.close_menu();
+                }
+            };
+            item(ui, gui::save_as(), PanelAction::SaveAs(panel));
+            item(ui, gui::copy_image(), PanelAction::CopyImage(panel));
+            item(ui, gui::open_external(), PanelAction::OpenExternal(panel));
+            ui.separator();
+            item(ui, gui::copy_cli(), PanelAction::CopyCli);
+        });
+        ui.label(format!("{}×{}", size.x as u32, size.y as u32));
+        response
+    }
+
+    /// Карта разницы исходного и результата; строится заново, когда меняется любое из них
+    fn diff_texture(&mut self, ctx: &egui::Context) -> Option<TextureHandle> {
+        let (main, result) = (self.main_texture.as_ref()?, self.result_texture.as_ref()?);
+        let key = (main.id(), result.id());
+        if let Some((main_id, result_id, texture)) = &self.diff_texture
+            && (*main_id, *result_id) == key
+        {
+            return Some(texture.clone());
+        }
+        let main = self
+            .params
+            .overlay
+            .to_params()
+            .apply(self.main_image.as_ref()?)
+            .ok()?;
+        let diff = diff_image(&main, self.result_image.as_ref()?);
+        let color_image = Self::dynamic_image_to_color_image(&diff);
+        let texture = ctx.load_texture("diff", color_image, egui::TextureOptions::LINEAR);
+        self.diff_texture = Some((key.0, key.1, texture.clone()));
+        Some(texture)
+    }
+
+    /// Сравнение со шторкой: левее неё исходное, правее результат или карта разницы.
+    /// Шторку можно тянуть мышью по изображению
+    fn compare_panel(
+        ui: &mut egui::Ui,
+        original: &TextureHandle,
+        right: &TextureHandle,
+        wipe: &mut f32,
+    ) {
+        let size = right.size_vec2();
+        let scale = (300.0 / size.x.max(size.y)).min(1.0);
+        let (rect, response) = ui.allocate_exact_size(size * scale, egui::Sense::click_and_drag());
+        if let Some(pos) = response.interact_pointer_pos() {
+            *wipe = ((pos.x - rect.left()) / rect.width()).clamp(0.0, 1.0);
+        }
+
+        let split = rect.left() + rect.width() * *wipe;
+        let painter = ui.painter_at(rect);
+        let uv = |from: f32, to: f32| {
+            egui::Rect::from_min_max(egui::pos2(from, 0.0), egui::pos2(to, 1.0))
+        };
+        painter.image(
+            original.id(),
+            egui::Rect::from_min_max(rect.min, egui::pos2(split, rect.bottom())),
+            uv(0.0, *wipe),
+            egui::Color32::WHITE,
+        );
+        painter.image(
+            right.id(),
+            egui::Rect::from_min_max(egui::pos2(split, rect.top()), rect.max),
+            uv(*wipe, 1.0),
+            egui::Color32::WHITE,
+        );
+        painter.vline(
+            split,
+            rect.y_range(),
+            egui::Stroke::new(1.5, egui::Color32::from_rgb(0, 255, 255)),
+        );
+        response.on_hover_text(gui::wipe_hover());
+
+        ui.add(
+            egui::Slider::new(wipe, 0.0..=1.0)
+                .text(gui::wipe())
+                .show_value(false),
+        );
+    }
+
+    /// Строка изображения под курсором на панели результата; подсвечивает `highlight`
+    fn result_line_hover(
+        ui: &egui::Ui,
+        response: &egui::Response,
+        height: u32,
+        highlight: Option<u32>,
+    ) -> Option<u32> {
+        let rect = response.rect;
+        let line_y = |line: u32| rect.top() + (line as f32 + 0.5) / height as f32 * rect.height();
+        if let Some(line) = highlight {
+            let y = line_y(line);
+            ui.painter().hline(
+                rect.x_range(),
+                y,
+                egui::Stroke::new(1.0, egui::Color32::from_rgb(0, 255, 255)),
+            );
+        }
+
+        let pos = response.hover_pos()?;
+        let line = ((pos.y - rect.top()) / rect.height() * height as f32) as u32;
+        Some(line.min(height - 1))
+    }
+
+    /// Водопад сигнала результата; подсказка показывает частоту и время под курсором
+    fn waterfall_panel(
+        ui: &mut egui::Ui,
+        view: &SpectrogramView,
+        action: &mut Option<PanelAction>,
+    ) {
+        let response = Self::image_panel(ui, &view.waterfall_texture, Panel::Waterfall, action);
+        let rect = response.rect;
+        if let Some(pos) = response.hover_pos() {
+            let fx = ((pos.x - rect.left()) / rect.width()).clamp(0.0, 1.0);
+            let fy = ((pos.y - rect.top()) / rect.height()).clamp(0.0, 1.0);
+            let hz = WATERFALL_FREQ.start + fx * (WATERFALL_FREQ.end - WATERFALL_FREQ.start);
+            let seconds = fy * view.len as f32 / view.sample_rate as f32;
+            response.on_hover_text(gui::waterfall_hover(hz, seconds));
+        }
+        ui.label(gui::waterfall_range(
+            WATERFALL_FREQ.start,
+            WATERFALL_FREQ.end,
+        ));
+    }
+
+    /// Осциллограмма: размах сигнала по столбцам и огибающие эффектов поверх него. Щелчок
+    /// задаёт `inspect` — отрезок под курсором; `inspected` отмечается полосой
+    fn waveform_panel(
+        ui: &mut egui::Ui,
+        view: &WaveformView,
+        inspected: Option<&Range<f64>>,
+        inspect: &mut Option<Range<f64>>,
+    ) {
+        let width = ui.available_width().max(300.0);
+        let (rect, response) =
+            ui.allocate_exact_size(Vec2::new(width, 120.0), egui::Sense::click());
+        let painter = ui.painter_at(rect);
+        painter.rect_filled(rect, 0.0, egui::Color32::from_gray(20));
+
+        let peak = view
+            .columns
+            .iter()
+            .fold(f32::EPSILON, |m, &(lo, hi)| m.max(-lo).max(hi));
+        let column_width = rect.width() / view.columns.len().max(1) as f32;
+        let x_at = |c: usize| rect.left() + (c as f32 + 0.5) * column_width;
+        let y_at = |v: f32| rect.center().y - v / peak * rect.height() / 2.0;
+        for (c, &(lo, hi)) in view.columns.iter().enumerate() {
+            painter.line_segment(
+                [egui::pos2(x_at(c), y_at(hi)), egui::pos2(x_at(c), y_at(lo))],
+                egui::Stroke::new(column_width.max(1.0), egui::Color32::from_gray(170)),
+            );
+        }
+
+        // Огибающая 0–1 занимает всю высоту: 0 внизу, 1 вверху
+        let envelopes = [
+            (&view.noise_envelope, NOISE_ENVELOPE_COLOR),
+            (&view.retarder_envelope, RETARDER_ENVELOPE_COLOR),
+        ];
+        for (envelope, color) in envelopes {
+            if let Some(envelope) = envelope {
+                let points = envelope
+                    .iter()
+                    .enumerate()
+                    .map(|(c, &f)| egui::pos2(x_at(c), rect.bottom() - f * rect.height()))
+                    .collect();
+                painter.add(egui::Shape::line(points, egui::Stroke::new(1.5, color)));
+            }
+        }
+
+        Self::inspected_band(ui, rect, view.seconds(), inspected);
+        if let Some(span) = Self::clicked_span(&response, view.seconds()) {
+            *inspect = Some(span);
+        }
+        if let Some(pos) = response.hover_pos() {
+            let seconds =
+                (pos.x - rect.left()) / rect.width() * view.len as f32 / view.sample_rate as f32;
+            response.on_hover_text(gui::seconds_hover(seconds));
+        }
+        ui.horizontal(|ui| {
+            ui.label(gui::waveform_peak(
+                peak,
+                view.len as f32 / view.sample_rate as f32,
+            ));
+            if view.noise_envelope.is_some() {
+                ui.colored_label(NOISE_ENVELOPE_COLOR, gui::noise_envelope_legend());
+            }
+            if view.retarder_envelope.is_some() {
+                ui.colored_label(RETARDER_ENVELOPE_COLOR, gui::ghost_envelope_legend());
+            }
+        });
+    }
+
+    /// Спектрограмма результата с отметкой строки `highlight`; возвращает строку под
+    /// курсором. Щелчок и отметка отрезка — как у [`Self::waveform_panel`]
+    fn spectrogram_panel(
+        ui: &mut egui::Ui,
+        view: &SpectrogramView,
+        highlight: Option<u32>,
+        inspected: Option<&Range<f64>>,
+        inspect: &mut Option<Range<f64>>,
+    ) -> Option<u32> {
+        let width = ui.available_width().max(300.0);
+        let response = ui.add(
+            egui::Image::from_texture(&view.texture)
+                .fit_to_exact_size(Vec2::new(width, 160.0))
+                .sense(egui::Sense::click()),
+        );
+        let rect = response.rect;
+        let x_at =
+            |sample: usize| rect.left() + sample as f32 / view.len.max(1) as f32 * rect.width();
+
+        if let Some(line) = highlight {
+            let samples = view.timing.line_to_sample_range(line);
+            let band = egui::Rect::from_x_y_ranges(
+                x_at(samples.start)..=x_at(samples.end).max(x_at(samples.start) + 1.0),
+                rect.y_range(),
+            );
+            ui.painter().rect_filled(
+                band,
+                0.0,
+                egui::Color32::from_rgba_unmultiplied(0, 255, 255, 60),
+            );
+        }
+
+        let seconds = view.len as f64 / view.sample_rate as f64;
+        Self::inspected_band(ui, rect, seconds, inspected);
+        if let Some(span) = Self::clicked_span(&response, seconds) {
+            *inspect = Some(span);
+        }
+
+        ui.label(gui::spectrogram_range(
+            SPECTROGRAM_FREQ.start,
+            SPECTROGRAM_FREQ.end,
+            view.len as f32 / view.sample_rate as f32,
+        ));
+
+        let pos = response.hover_pos()?;
+        let sample = ((pos.x - rect.left()) / rect.width() * view.len as f32) as usize;
+        let line = view.timing.sample_to_line(sample);
+        response.on_hover_text(match line {
+            Some(line) => gui::spectrogram_line(line),
+            None => gui::spectrogram_outside().to_string(),
+        });
+        line
+    }
+
+    /// Отрезок сигнала длительностью `seconds`, с, под щелчком по панели `response`,
+    /// которую он занимает по ширине: один экранный пиксель
+    fn clicked_span(response: &egui::Response, seconds: f64) -> Option<Range<f64>> {
+        if !response.clicked() {
+            return None;
+        }
+        let rect = response.rect;
+        let pos = response.interact_pointer_pos()?;
+        let per_px = seconds / rect.width() as f64;
+        let at = (pos.x - rect.left()) as f64 * per_px;
+        Some((at - per_px / 2.0).max(0.0)..at + per_px / 2.0)
+    }
+
+    /// Полоса отрезка `inspected` на панели `rect` сигнала длительностью `seconds`, с
+    fn inspected_band(
+        ui: &egui::Ui,
+        rect: egui::Rect,
+        seconds: f64,
+        inspected: Option<&Range<f64>>,
+    ) {
+        let Some(range) = inspected else {
+            return;
+        };
+        let x_at = |t: f64| rect.left() + (t / seconds.max(f64::EPSILON)) as f32 * rect.width();
+        let (from, to) = (x_at(range.start), x_at(range.end));
+        ui.painter().rect_filled(
+            egui::Rect::from_x_y_ranges(from..=to.max(from + 2.0), rect.y_range()),
+            0.0,
+            egui::Color32::from_rgba_unmultiplied(255, 255, 0, 90),
+        );
+    }
+
+    /// Тоны под последним щелчком по сигналу или подсказка, как их получить
+    fn inspector_panel(ui: &mut egui::Ui, inspection: Option<&ToneInspection>) {
+        ui.label(gui::inspector());
+        let Some(inspection) = inspection else {
+            ui.colored_label(egui::Color32::GRAY, gui::inspector_hint());
+            return;
+        };
+        ui.label(gui::inspector_span(
+            inspection.range.start,
+            inspection.range.end,
+        ));
+        for line in &inspection.lines {
+            ui.monospace(line);
+        }
+    }
+
+    fn handle_panel_action(&mut self, ctx: &egui::Context, action: PanelAction) {
+        let result = match action {
+            PanelAction::SaveAs(panel) => self.save_panel_as(panel),
+            PanelAction::CopyImage(panel) => self.copy_panel_image(panel),
+            PanelAction::OpenExternal(panel) => self.open_panel_external(panel),
+            PanelAction::CopyCli => {
+                self.sync_params_to_processor();
+                let command = self.cli_command();
+                ctx.output_mut(|o| o.copied_text = command.clone());
+                tracing::info!("{}", gui::log_command_copied(&command));
+                Ok(())
+            }
+        };
+
+        if let Err(e) = result {
+            tracing::error!("{}", gui::log_error(&e.to_string()));
+        }
+    }
+
+    fn save_panel_as(&self, panel: Panel) -> anyhow::Result<()> {
+        let Some(image) = self.panel_image(panel) else {
+            return Ok(());
+        };
+        if let Some(path) = rfd::FileDialog::new()
+            .add_filter("PNG", &["png"])
+            .set_file_name("sstv.png")
+            .save_file()
+        {
+            image.save(&path)?;
+            tracing::info!("{}", gui::log_saved(&path.display().to_string()));
+        }
+        Ok(())
+    }
+
+    fn copy_panel_image(&self, panel: Panel) -> anyhow::Result<()> {
+        let Some(image) = self.panel_image(panel) else {
+            return Ok(());
+        };
+        let rgba = image.to_rgba8();
+        let data = arboard::ImageData {
+            width: rgba.width() as usize,
+            height: rgba.height() as usize,
+            bytes: rgba.into_raw().into(),
+        };
+        arboard::Clipboard::new()?.set_image(data)?;
+        Ok(())
+    }
+
+    /// Сохраняет изображение во временный файл и открывает системным просмотрщиком
+    fn open_panel_external(&self, panel: Panel) -> anyhow::Result<()> {
+        let Some(image) = self.panel_image(panel) else {
+            return Ok(());
+        };
+        let path = std::env::temp_dir().join(format!("sstv_{:?}.png", panel).to_lowercase());
+        image.save(&path)?;
+
+        #[cfg(target_os = "windows")]
+        let mut command = {
+            let mut c = std::process::Command::new("cmd");
+            c.args(["/C", "start", ""]);
+            c
+        };
+        #[cfg(target_os = "macos")]
+        let mut command = std::process::Command::new("open");
+        #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+        let mut command = std::process::Command::new("xdg-open");
+
+        command.arg(&path).spawn()?;
+        Ok(())
+    }
+
+    /// Команда CLI, воспроизводящая текущую обработку
+    fn cli_command(&self) -> String {
+        command::cli_command(self.processor.params(), &self.command_paths(&self.params))
+    }
+
+    /// Пути файлов для команды CLI с параметрами `params`
+    fn command_paths(&self, params: &AppParams) -> CommandPaths {
+        let optional = |path: &str| (!path.is_empty()).then(|| path.to_string());
+        CommandPaths {
+            input: self.main_image_path.clone(),
+            output: self.output_path.clone(),
+            retarder: optional(&self.retarder_image_path),
+            qrm: params.qrm.clone(),
+            noise_env_file: params.noise_env_file.clone(),
+            ret_env_file: params.ret_env_file.clone(),
+            timeline: params.timeline.clone(),
+        }
+    }
+
+    /// Пишет папку последнего прогона: исходное изображение, результат, искажённый
+    /// и чистый WAV и опись со всеми параметрами и метриками
+    fn export_run(&self) {
+        let (Some((params, report)), Some(original)) = (&self.last_run, &self.main_image) else {
+            tracing::warn!("{}", gui::log_no_run());
+            return;
+        };
+        let Some(root) = rfd::FileDialog::new()
+            .set_title(gui::runs_folder_title())
+            .pick_folder()
+        else {
+            return;
+        };
+        let exported = params
+            .to_params()
+            .and_then(|p| params.processor(p))
+            .and_then(|processor| {
+                RunExporter::new(root).export(&RunArtifacts {
+                    processor: &processor,
+                    paths: &self.command_paths(params),
+                    original,
+                    retarder: self.retarder_image.as_ref(),
+                    report,
+                    degraded: &self.last_signal,
+                })
+            });
+        match exported {
+            Ok(dir) => tracing::info!("{}", gui::log_run_saved(&dir.display().to_string())),
+            Err(e) => tracing::error!("{}", gui::log_run_failed(&format!("{:#}", e))),
+        }
+    }
+
+    /// Настройки формы огибающих ADSR и пачек; вернёт `true`, если форма изменилась
+    fn envelope_shape_ui(ui: &mut egui::Ui, env: &mut EnvelopeKind) -> bool {
+        let mut changed = false;
+        match env {
+            EnvelopeKind::Adsr(adsr) => {
+                ui.horizontal(|ui| {
+                    for (value, label) in [
+                        (&mut adsr.attack, gui::adsr_attack()),
+                        (&mut adsr.decay, gui::adsr_decay()),
+                        (&mut adsr.sustain, gui::adsr_sustain()),
+                        (&mut adsr.release, gui::adsr_release()),
+                    ] {
+                        changed |= ui
+                            .add(
+                                egui::DragValue::new(value)
+                                    .speed(0.01)
+                                    .clamp_range(0.0..=1.0)
+                                    .prefix(format!("{}: ", label)),
+                            )
+                            .changed();
+                    }
+                });
+            }
+            EnvelopeKind::Burst(burst) => {
+                ui.horizontal(|ui| {
+                    for (value, label) in [
+                        (&mut burst.on_ms, gui::burst_on()),
+                        (&mut burst.period_ms, gui::burst_period()),
+                        (&mut burst.jitter_ms, gui::burst_jitter()),
+                        (&mut burst.ramp_ms, gui::burst_ramp()),
+                    ] {
+                        changed |= ui
+                            .add(
+                                egui::DragValue::new(value)
+                                    .speed(5.0)
+                                    .clamp_range(0.0..=60000.0)
+                                    .prefix(format!("{}: ", label))
+                                    .suffix(gui::suffix_ms()),
+                            )
+                            .changed();
+                    }
+                });
+            }
+            _ => {}
+        }
+        changed
+    }
+
+    /// Выбирает и читает файл пользовательской огибающей; возвращает путь и огибающую
+    fn pick_envelope_file() -> Option<(String, EnvelopeKind)> {
+        let path = rfd::FileDialog::new()
+            .add_filter(gui::filter_envelope(), &["txt", "csv", "toml", "json"])
+            .set_title(gui::load_envelope_title())
+            .pick_file()?;
+        match CustomEnvelope::from_file(&path) {
+            Ok(curve) => Some((
+                path.to_string_lossy().to_string(),
+                EnvelopeKind::Custom(curve),
+            )),
+            Err(e) => {
+                tracing::error!("{}", gui::log_envelope_failed(&format!("{:#}", e)));
+                None
+            }
+        }
+    }
+
+    fn pick_timeline_file() -> Option<(String, Timeline)> {
+        let path = rfd::FileDialog::new()
+            .add_filter(gui::filter_timeline(), &["toml", "json"])
+            .set_title(gui::load_timeline_title())
+            .pick_file()?;
+        match Timeline::from_file(&path) {
+            Ok(timeline) => Some((path.to_string_lossy().to_string(), timeline)),
+            Err(e) => {
+                tracing::error!("{}", gui::log_timeline_failed(&format!("{:#}", e)));
+                None
+            }
+        }
+    }
+
+    /// Сохраняет текущие параметры в пресет TOML/JSON
+    fn save_preset(&mut self) {
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter(gui::filter_preset(), &["toml", "json"])
+            .set_file_name("preset.toml")
+            .set_title(gui::save_preset_title())
+            .save_file()
+        else {
+            return;
+        };
+        // Ползунки могли измениться после последней обработки
+        self.sync_params_to_processor();
+        match self.processor.params().to_file(&path) {
+            Ok(()) => tracing::info!("{}", gui::log_preset_saved(&path.display().to_string())),
+            Err(e) => tracing::error!("{}", gui::log_preset_save_failed(&format!("{:#}", e))),
+        }
+    }
+
+    /// Загружает параметры из пресета; картинки остаются прежними
+    fn load_preset(&mut self) {
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter(gui::filter_preset(), &["toml", "json"])
+            .set_title(gui::load_preset_title())
+            .pick_file()
+        else {
+            return;
+        };
+        match ProcessingParams::from_file(&path) {
+            Ok(params) => {
+                self.params.set_params(&params);
+                self.sync_params_to_processor();
+                self.request_manual_processing();
+                tracing::info!("{}", gui::log_preset_loaded(&path.display().to_string()));
+            }
+            Err(e) => {
+                tracing::error!("{}", gui::log_preset_load_failed(&format!("{:#}", e)));
+            }
+        }
+    }
+
+    fn save_result(&mut self) {
+        if let Some(result) = &self.result_image {
+            match self.processor.save_result(result, &self.output_path) {
+                Ok(_) => {
+                    tracing::info!("{}", gui::log_saved(&self.output_path));
+
+                    // Показываем системное уведомление об успешном сохранении
+                    #[cfg(not(target_arch = "wasm32"))]
+                    {
+                        if let Err(e) = std::process::Command::new("notify-send")
+                            .args(["SSTV Processor", &gui::file_saved(&self.output_path)])
+                            .output()
+                        {
+                            // Если notify-send не работает, просто игнорируем
+                            let _ = e;
+                        }
+                    }
+                }
+                Err(e) => {
+                    tracing::error!("{}", gui::log_save_failed(&e.to_string()));
+                }
+            }
+        } else {
+            tracing::warn!("{}", gui::log_no_result());
+        }
+    }
+}
+
+impl SSTVApp {
+    /// Вкладка «Параметры»: файлы, эффекты, обработка, звук и сохранение
+    fn parameters_tab(&mut self, ui: &mut egui::Ui) {
+        let ctx = &ui.ctx().clone();
+        ui.heading(gui::heading());
+
+        ui.separator();
+        ui.label(gui::files());
+
+        ui.horizontal(|ui| {
+            ui.label(gui::main_image_label())
+                .on_hover_text(gui::main_image_hover());
+
+            if ui.button(gui::choose()).clicked()
+                && let Some(path) = rfd::FileDialog::new()
+                    .add_filter(
+                        gui::filter_images(),
+                        &["png", "jpg", "jpeg", "bmp", "gif", "tiff", "webp"],
+                    )
+                    .set_title(gui::choose_main_title())
+                    .pick_file()
+            {
+                self.main_image_path = path.to_string_lossy().to_string();
+                let path_clone = self.main_image_path.clone();
+                self.load_image_and_update_texture(ctx, &path_clone, true);
+            }
+            if ui
+                .button(gui::paste())
+                .on_hover_text(gui::paste_main_hover())
+                .clicked()
+            {
+                self.paste_image(ctx, true);
+            }
+        });
+        ui.text_edit_singleline(&mut self.main_image_path);
+        if ui.button(gui::load_path()).clicked() && !self.main_image_path.is_empty() {
+            let path = self.main_image_path.clone();
+            self.load_image_and_update_texture(ctx, &path, true);
+            self.request_manual_processing(); // Обрабатываем сразу при загрузке
+            self.request_manual_processing(); // Обрабатываем сразу при загрузке
+        }
+
+        ui.horizontal(|ui| {
+            ui.label(gui::retarder_label());
+            if ui.button(gui::choose()).clicked()
+                && let Some(path) = rfd::FileDialog::new()
+                    .add_filter(
+                        gui::filter_images(),
+                        &["png", "jpg", "jpeg", "bmp", "gif", "tiff", "webp"],
+                    )
+                    .set_title(gui::choose_retarder_title())
+                    .pick_file()
+            {
+                self.retarder_image_path = path.to_string_lossy().to_string();
+                let path_clone = self.retarder_image_path.clone();
+                self.load_image_and_update_texture(ctx, &path_clone, false);
+                self.request_manual_processing(); // Обрабатываем сразу при загрузке
+            }
+            if ui
+                .button(gui::paste())
+                .on_hover_text(gui::paste_retarder_hover())
+                .clicked()
+            {
+                self.paste_image(ctx, false);
+            }
+            if ui.button(gui::clear()).clicked() {
+                self.retarder_image = None;
+                self.retarder_texture = None;
+                self.retarder_image_path.clear();
+                self.schedule_processing();
+            }
+        });
+        ui.text_edit_singleline(&mut self.retarder_image_path);
+        if ui.button(gui::load_path()).clicked() && !self.retarder_image_path.is_empty() {
+            let path = self.retarder_image_path.clone();
+            self.load_image_and_update_texture(ctx, &path, false);
+            self.request_manual_processing(); // Обрабатываем сразу при загрузке
+        }
+
+        ui.separator();
+        if ui
+            .checkbox(&mut self.link_envelopes, gui::link_envelopes())
+            .on_hover_text(gui::link_envelopes_hover())
+            .changed()
+            && self.link_envelopes
+        {
+            self.link_from_noise();
+            self.schedule_processing();
+        }
+
+        ui.separator();
+        ui.label(gui::noise_label());
+
+        ui.horizontal(|ui| {
+            Self::lock_button(ui, &mut self.locked, "noise_level");
+            if ui
+                .add(egui::Slider::new(&mut self.params.noise, 0..=100).text(gui::level()))
+                .changed()
+            {
+                self.schedule_processing();
+            }
+        });
+
+        egui::ComboBox::from_label(gui::noise_kind())
+            .selected_text(self.params.noise_kind.name())
+            .show_ui(ui, |ui| {
+                for &kind in NoiseKind::ALL {
+                    if ui
+                        .selectable_value(&mut self.params.noise_kind, kind, kind.name())
+                        .changed()
+                    {
+                        self.schedule_processing();
+                    }
+                }
+            });
+
+        egui::ComboBox::from_label(gui::noise_color())
+            .selected_text(self.params.noise_color.name())
+            .show_ui(ui, |ui| {
+                for color in NoiseColor::ALL {
+                    let selected = self.params.noise_color.same_kind(color);
+                    if ui.selectable_label(selected, color.name()).clicked() && !selected {
+                        self.params.noise_color = *color;
+                        self.schedule_processing();
+                    }
+                }
+            });
+        let mut band_changed = false;
+        if let NoiseColor::BandLimited { low_hz, high_hz } = &mut self.params.noise_color {
+            let low = ui.add(
+                egui::Slider::new(low_hz, 10.0..=10000.0)
+                    .logarithmic(true)
+                    .text(gui::band_low()),
+            );
+            let high = ui.add(
+                egui::Slider::new(high_hz, 10.0..=20000.0)
+                    .logarithmic(true)
+                    .text(gui::band_high()),
+            );
+            if low.changed() || high.changed() {
+                *high_hz = high_hz.max(*low_hz + 10.0);
+                band_changed = true;
+            }
+        }
+        if band_changed {
+            self.schedule_processing();
+        }
+
+        if self.params.noise_kind != NoiseKind::Gaussian {
+            ui.horizontal(|ui| {
+                Self::lock_button(ui, &mut self.locked, "impulse_rate");
+                if ui
+                    .add(
+                        egui::Slider::new(&mut self.params.impulse_rate, 0.1..=50.0)
+                            .logarithmic(true)
+                            .text(gui::impulse_rate()),
+                    )
+                    .changed()
+                {
+                    self.schedule_processing();
+                }
+            });
+
+            if self.params.noise_kind == NoiseKind::Impulse {
+                ui.horizontal(|ui| {
+                    Self::lock_button(ui, &mut self.locked, "impulse_ms");
+                    if ui
+                        .add(
+                            egui::Slider::new(&mut self.params.impulse_ms, 0.1..=100.0)
+                                .logarithmic(true)
+                                .text(gui::impulse_ms()),
+                        )
+                        .changed()
+                    {
+                        self.schedule_processing();
+                    }
+                });
+            }
+
+            egui::ComboBox::from_label(gui::impulse_dist())
+                .selected_text(self.params.impulse_dist.name())
+                .show_ui(ui, |ui| {
+                    for &dist in AmplitudeDistribution::ALL {
+                        if ui
+                            .selectable_value(&mut self.params.impulse_dist, dist, dist.name())
+                            .changed()
+                        {
+                            self.schedule_processing();
+                        }
+                    }
+                });
+        }
+
+        ui.horizontal(|ui| {
+            egui::ComboBox::from_label(gui::noise_env())
+                .selected_text(self.params.noise_env.name())
+                .show_ui(ui, |ui| {
+                    for env in EnvelopeKind::ALL {
+                        let selected = self.params.noise_env.same_kind(env);
+                        if ui.selectable_label(selected, env.name()).clicked() && !selected {
+                            self.params.noise_env = env.clone();
+                            self.params.noise_env_file = None;
+                            self.link_from_noise();
+                            self.schedule_processing();
+                        }
+                    }
+                });
+            if ui
+                .button(gui::curve())
+                .on_hover_text(gui::curve_hover())
+                .clicked()
+                && let Some((path, env)) = Self::pick_envelope_file()
+            {
+                self.params.noise_env = env;
+                self.params.noise_env_file = Some(path);
+                self.link_from_noise();
+                self.schedule_processing();
+            }
+        });
+        if Self::envelope_shape_ui(ui, &mut self.params.noise_env) {
+            self.link_from_noise();
+            self.schedule_processing();
+        }
+
+        ui.horizontal(|ui| {
+            Self::lock_button(ui, &mut self.locked, "noise_repeat");
+            if ui
+                .add(
+                    egui::Slider::new(&mut self.params.noise_repeat, 0.1..=10.0)
+                        .text(gui::repeat()),
+                )
+                .changed()
+            {
+                self.link_from_noise();
+                self.schedule_processing();
+            }
+        });
+
+        egui::ComboBox::from_label(gui::noise_anchor())
+            .selected_text(self.params.noise_anchor.name())
+            .show_ui(ui, |ui| {
+                for &anchor in EnvelopeAnchor::ALL {
+                    if ui
+                        .selectable_value(&mut self.params.noise_anchor, anchor, anchor.name())
+                        .changed()
+                    {
+                        self.schedule_processing();
+                    }
+                }
+            });
+
+        ui.separator();
+        ui.label(gui::retarder_label());
+
+        ui.horizontal(|ui| {
+            Self::lock_button(ui, &mut self.locked, "retarder_level");
+            if ui
+                .add(egui::Slider::new(&mut self.params.level, 0.0..=1.0).text(gui::level()))
+                .changed()
+            {
+                self.schedule_processing();
+            }
+        });
+
+        ui.horizontal(|ui| {
+            egui::ComboBox::from_label(gui::ghost_source())
+                .selected_text(self.params.ret_source.name())
+                .show_ui(ui, |ui| {
+                    for source in RetarderSource::SIMPLE {
+                        let selected = self.params.ret_source == *source;
+                        if ui.selectable_label(selected, source.name()).clicked() && !selected {
+                            self.params.ret_source = source.clone();
+                            self.schedule_processing();
+                        }
+                    }
+                });
+            if ui
+                .button("📂 WAV…")
+                .on_hover_text(gui::ghost_wav_hover())
+                .clicked()
+                && let Some(path) = rfd::FileDialog::new()
+                    .add_filter("WAV", &["wav"])
+                    .set_title(gui::ghost_wav_title())
+                    .pick_file()
+            {
+                self.params.ret_source =
+                    RetarderSource::WavFile(path.to_string_lossy().to_string());
+                self.schedule_processing();
+            }
+        });
+        if let RetarderSource::WavFile(path) = &self.params.ret_source {
+            ui.label(format!("WAV: {}", path));
+        }
+
+        ui.horizontal(|ui| {
+            egui::ComboBox::from_label(gui::retarder_env())
+                .selected_text(self.params.ret_env.name())
+                .show_ui(ui, |ui| {
+                    for env in EnvelopeKind::ALL {
+                        let selected = self.params.ret_env.same_kind(env);
+                        if ui.selectable_label(selected, env.name()).clicked() && !selected {
+                            self.params.ret_env = env.clone();
+                            self.params.ret_env_file = None;
+                            self.link_from_retarder();
+                            self.schedule_processing();
+                        }
+                    }
+                });
+            if ui
+                .button(gui::curve())
+                .on_hover_text(gui::curve_hover())
+                .clicked()
+                && let Some((path, env)) = Self::pick_envelope_file()
+            {
+                self.params.ret_env = env;
+                self.params.ret_env_file = Some(path);
+                self.link_from_retarder();
+                self.schedule_processing();
+            }
+        });
+        if Self::envelope_shape_ui(ui, &mut self.params.ret_env) {
+            self.link_from_retarder();
+            self.schedule_processing();
+        }
+
+        ui.horizontal(|ui| {
+            Self::lock_button(ui, &mut self.locked, "retarder_repeat");
+            if ui
+                .add(egui::Slider::new(&mut self.params.ret_repeat, 0.1..=10.0).text(gui::repeat()))
+                .changed()
+            {
+                self.link_from_retarder();
+                self.schedule_processing();
+            }
+        });
+
+        egui::ComboBox::from_label(gui::retarder_anchor())
+            .selected_text(self.params.ret_anchor.name())
+            .show_ui(ui, |ui| {
+                for &anchor in EnvelopeAnchor::ALL {
+                    if ui
+                        .selectable_value(&mut self.params.ret_anchor, anchor, anchor.name())
+                        .changed()
+                    {
+                        self.schedule_processing();
+                    }
+                }
+            });
+
+        ui.horizontal(|ui| {
+            Self::lock_button(ui, &mut self.locked, "delay_ms");
+            if ui
+                .add(egui::Slider::new(&mut self.params.delay_ms, 0..=1000).text(gui::delay_ms()))
+                .changed()
+            {
+                self.schedule_processing();
+            }
+        });
+
+        ui.separator();
+        ui.label(gui::multipath());
+
+        let mut removed = None;
+        let mut taps_changed = false;
+        for (i, tap) in self.params.taps.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                ui.label(gui::path_number(i + 1));
+                if ui.button("✖").clicked() {
+                    removed = Some(i);
+                }
+            });
+            if ui
+                .add(egui::Slider::new(&mut tap.delay_ms, 0.0..=20.0).text(gui::delay_ms()))
+                .changed()
+            {
+                taps_changed = true;
+            }
+            if ui
+                .add(egui::Slider::new(&mut tap.gain, -1.0..=1.0).text(gui::gain()))
+                .changed()
+            {
+                taps_changed = true;
+            }
+            if ui
+                .add(egui::Slider::new(&mut tap.phase_deg, -180.0..=180.0).text(gui::phase_deg()))
+                .changed()
+            {
+                taps_changed = true;
+            }
+        }
+        if let Some(i) = removed {
+            self.params.taps.remove(i);
+            taps_changed = true;
+        }
+        if taps_changed {
+            self.schedule_processing();
+        }
+        if ui.button(gui::add_path()).clicked() {
+            self.params.taps.push(MultipathTap {
+                delay_ms: 2.0,
+                gain: 0.5,
+                phase_deg: 0.0,
+            });
+            self.schedule_processing();
+        }
+
+        ui.separator();
+        ui.label(gui::fading());
+
+        ui.horizontal(|ui| {
+            Self::lock_button(ui, &mut self.locked, "fade_depth");
+            if ui
+                .add(egui::Slider::new(&mut self.params.fade_depth, 0.0..=1.0).text(gui::depth()))
+                .changed()
+            {
+                self.schedule_processing();
+            }
+        });
+
+        ui.horizontal(|ui| {
+            Self::lock_button(ui, &mut self.locked, "fade_rate");
+            if ui
+                .add(
+                    egui::Slider::new(&mut self.params.fade_rate, 0.01..=5.0)
+                        .logarithmic(true)
+                        .text(gui::rate_hz()),
+                )
+                .changed()
+            {
+                self.schedule_processing();
+            }
+        });
+
+        egui::ComboBox::from_label(gui::fade_model())
+            .selected_text(self.params.fade_model.name())
+            .show_ui(ui, |ui| {
+                for &model in FadingModel::ALL {
+                    if ui
+                        .selectable_value(&mut self.params.fade_model, model, model.name())
+                        .changed()
+                    {
+                        self.schedule_processing();
+                    }
+                }
+            });
+
+        ui.separator();
+        ui.label(gui::frequency_drift());
+
+        ui.horizontal(|ui| {
+            Self::lock_button(ui, &mut self.locked, "offset_hz");
+            if ui
+                .add(
+                    egui::Slider::new(&mut self.params.offset_hz, -300.0..=300.0)
+                        .text(gui::offset_hz()),
+                )
+                .changed()
+            {
+                self.schedule_processing();
+            }
+        });
+
+        ui.horizontal(|ui| {
+            Self::lock_button(ui, &mut self.locked, "drift_hz_per_s");
+            if ui
+                .add(
+                    egui::Slider::new(&mut self.params.drift_hz_per_s, -5.0..=5.0)
+                        .text(gui::drift_hz_per_s()),
+                )
+                .changed()
+            {
+                self.schedule_processing();
+            }
+        });
+
+        ui.horizontal(|ui| {
+            Self::lock_button(ui, &mut self.locked, "wobble_hz");
+            if ui
+                .add(
+                    egui::Slider::new(&mut self.params.wobble_hz, 0.0..=50.0)
+                        .text(gui::wobble_hz()),
+                )
+                .changed()
+            {
+                self.schedule_processing();
+            }
+        });
+
+        ui.separator();
+        ui.label(gui::interference());
+
+        let mut removed = None;
+        let mut carriers_changed = false;
+        for (i, carrier) in self.params.carriers.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                ui.label(gui::carrier_number(i + 1));
+                if ui.button("✖").clicked() {
+                    removed = Some(i);
+                }
+            });
+            if ui
+                .add(
+                    egui::Slider::new(&mut carrier.freq_hz, 100.0..=3000.0)
+                        .text(gui::frequency_hz()),
+                )
+                .changed()
+            {
+                carriers_changed = true;
+            }
+            if ui
+                .add(egui::Slider::new(&mut carrier.level, 0.0..=2.0).text(gui::level()))
+                .changed()
+            {
+                carriers_changed = true;
+            }
+            if ui
+                .add(egui::Slider::new(&mut carrier.duty, 0.0..=1.0).text(gui::duty()))
+                .changed()
+            {
+                carriers_changed = true;
+            }
+            if ui
+                .add_enabled(
+                    carrier.duty < 1.0,
+                    egui::Slider::new(&mut carrier.period_ms, 20.0..=2000.0)
+                        .logarithmic(true)
+                        .text(gui::period_ms()),
+                )
+                .changed()
+            {
+                carriers_changed = true;
+            }
+        }
+        if let Some(i) = removed {
+            self.params.carriers.remove(i);
+            carriers_changed = true;
+        }
+        if carriers_changed {
+            self.schedule_processing();
+        }
+        if ui.button(gui::add_carrier()).clicked() {
+            self.params.carriers.push(Carrier::new(1500.0, 0.3));
+            self.schedule_processing();
+        }
+
+        ui.horizontal(|ui| {
+            ui.label(gui::qrm_label());
+            if ui.button(gui::choose()).clicked()
+                && let Some(path) = rfd::FileDialog::new()
+                    .add_filter(
+                        gui::filter_images(),
+                        &["png", "jpg", "jpeg", "bmp", "gif", "tiff", "webp"],
+                    )
+                    .set_title(gui::qrm_title())
+                    .pick_file()
+            {
+                // Картинка читается при каждой обработке, как в CLI
+                self.params.qrm = Some(path.to_string_lossy().to_string());
+                self.schedule_processing();
+            }
+            if ui.button(gui::clear()).clicked() {
+                self.params.qrm = None;
+                self.schedule_processing();
+            }
+        });
+        if let Some(path) = &self.params.qrm {
+            ui.label(path.as_str());
+            let mut changed = false;
+            changed |= ui
+                .add(
+                    egui::Slider::new(&mut self.params.qrm_level, 0.0..=2.0).text(gui::qrm_level()),
+                )
+                .changed();
+            changed |= ui
+                .add(
+                    egui::Slider::new(&mut self.params.qrm_offset_hz, -1000.0..=1000.0)
+                        .text(gui::qrm_offset()),
+                )
+                .changed();
+            changed |= ui
+                .add(
+                    egui::Slider::new(&mut self.params.qrm_delay_ms, 0.0..=60000.0)
+                        .text(gui::qrm_delay()),
+                )
+                .changed();
+            if changed {
+                self.schedule_processing();
+            }
+        }
+
+        ui.separator();
+        if ui
+            .checkbox(&mut self.params.ssb, gui::ssb())
+            .on_hover_text(gui::ssb_hover())
+            .changed()
+        {
+            self.schedule_processing();
+        }
+        ui.add_enabled_ui(self.params.ssb, |ui| {
+            let mut changed = false;
+            changed |= ui
+                .add(
+                    egui::Slider::new(&mut self.params.ssb_low_hz, 0.0..=1000.0)
+                        .text(gui::band_low()),
+                )
+                .changed();
+            changed |= ui
+                .add(
+                    egui::Slider::new(&mut self.params.ssb_high_hz, 1500.0..=4000.0)
+                        .text(gui::band_high()),
+                )
+                .changed();
+            changed |= ui
+                .add(
+                    egui::Slider::new(&mut self.params.ssb_drive_db, -6.0..=30.0)
+                        .text(gui::ssb_drive()),
+                )
+                .changed();
+            changed |= ui
+                .add(
+                    egui::Slider::new(&mut self.params.ssb_alc_ms, 0.0..=1000.0)
+                        .text(gui::ssb_alc()),
+                )
+                .changed();
+            egui::ComboBox::from_label(gui::ssb_output())
+                .selected_text(self.params.ssb_output.name())
+                .show_ui(ui, |ui| {
+                    for &output in TransceiverOutput::ALL {
+                        changed |= ui
+                            .selectable_value(&mut self.params.ssb_output, output, output.name())
+                            .changed();
+                    }
+                })
+                .response
+                .on_hover_text(gui::ssb_output_hover());
+            ui.add_enabled_ui(self.params.ssb_output == TransceiverOutput::Iq, |ui| {
+                egui::ComboBox::from_label(gui::sideband())
+                    .selected_text(self.params.ssb_sideband.name())
+                    .show_ui(ui, |ui| {
+                        for &sideband in Sideband::ALL {
+                            changed |= ui
+                                .selectable_value(
+                                    &mut self.params.ssb_sideband,
+                                    sideband,
+                                    sideband.name(),
+                                )
+                                .changed();
+                        }
+                    });
+                changed |= ui
+                    .add(
+                        egui::Slider::new(&mut self.params.ssb_carrier, 0.0..=0.5)
+                            .text(gui::carrier_leak()),
+                    )
+                    .changed();
+                changed |= ui
+                    .add(
+                        egui::Slider::new(&mut self.params.ssb_iq_gain_db, -3.0..=3.0)
+                            .text(gui::iq_gain()),
+                    )
+                    .changed();
+                changed |= ui
+                    .add(
+                        egui::Slider::new(&mut self.params.ssb_iq_phase_deg, -20.0..=20.0)
+                            .text(gui::iq_phase()),
+                    )
+                    .changed();
+            });
+            if changed {
+                self.schedule_processing();
+            }
+        });
+
+        ui.separator();
+        if ui
+            .checkbox(&mut self.params.blanker, gui::blanker())
+            .on_hover_text(gui::blanker_hover())
+            .changed()
+        {
+            self.schedule_processing();
+        }
+        ui.add_enabled_ui(self.params.blanker, |ui| {
+            let mut changed = false;
+            changed |= ui
+                .add(
+                    egui::Slider::new(&mut self.params.blanker_threshold, 1.0..=10.0)
+                        .text(gui::blanker_threshold()),
+                )
+                .on_hover_text(gui::blanker_threshold_hover())
+                .changed();
+            changed |= ui
+                .add(
+                    egui::Slider::new(&mut self.params.blanker_hold_ms, 0.0..=10.0)
+                        .text(gui::hold_ms()),
+                )
+                .changed();
+            changed |= ui
+                .add(
+                    egui::Slider::new(&mut self.params.blanker_average_ms, 1.0..=200.0)
+                        .text(gui::blanker_average()),
+                )
+                .changed();
+            egui::ComboBox::from_label(gui::blanker_fill())
+                .selected_text(self.params.blanker_fill.name())
+                .show_ui(ui, |ui| {
+                    for &fill in BlankerFill::ALL {
+                        changed |= ui
+                            .selectable_value(&mut self.params.blanker_fill, fill, fill.name())
+                            .changed();
+                    }
+                });
+            if changed {
+                self.schedule_processing();
+            }
+        });
+
+        ui.separator();
+        if ui
+            .checkbox(&mut self.params.filter, gui::filter())
+            .on_hover_text(gui::filter_hover())
+            .changed()
+        {
+            self.schedule_processing();
+        }
+        ui.add_enabled_ui(self.params.filter, |ui| {
+            let mut changed = false;
+            egui::ComboBox::from_label(gui::filter_kind())
+                .selected_text(self.params.filter_kind.name())
+                .show_ui(ui, |ui| {
+                    for &kind in FilterKind::ALL {
+                        changed |= ui
+                            .selectable_value(&mut self.params.filter_kind, kind, kind.name())
+                            .changed();
+                    }
+                });
+            changed |= ui
+                .add(
+                    egui::Slider::new(&mut self.params.filter_hz, 100.0..=4000.0)
+                        .text(gui::frequency_hz()),
+                )
+                .changed();
+            changed |= ui
+                .add(
+                    egui::Slider::new(&mut self.params.filter_q, 0.3..=20.0)
+                        .logarithmic(true)
+                        .text(gui::q_factor()),
+                )
+                .changed();
+            changed |= ui
+                .add(
+                    egui::Slider::new(&mut self.params.filter_stages, 1..=MAX_FILTER_STAGES)
+                        .text(gui::filter_stages()),
+                )
+                .changed();
+            if changed {
+                self.schedule_processing();
+            }
+        });
+
+        if ui
+            .checkbox(&mut self.params.notch, gui::notch())
+            .on_hover_text(gui::notch_hover())
+            .changed()
+        {
+            self.schedule_processing();
+        }
+        ui.add_enabled_ui(self.params.notch, |ui| {
+            let mut changed = false;
+            changed |= ui
+                .add(
+                    egui::Slider::new(&mut self.params.notch_hz, 300.0..=3000.0)
+                        .text(gui::notch_hz()),
+                )
+                .changed();
+            changed |= ui
+                .add(
+                    egui::Slider::new(&mut self.params.notch_q, 1.0..=50.0)
+                        .logarithmic(true)
+                        .text(gui::notch_q()),
+                )
+                .changed();
+            if changed {
+                self.schedule_processing();
+            }
+        });
+
+        if ui
+            .checkbox(&mut self.params.agc, gui::agc())
+            .on_hover_text(gui::agc_hover())
+            .changed()
+        {
+            self.schedule_processing();
+        }
+        ui.add_enabled_ui(self.params.agc, |ui| {
+            let mut changed = false;
+            changed |= ui
+                .add(egui::Slider::new(&mut self.params.agc_target, 0.05..=1.0).text(gui::level()))
+                .changed();
+            changed |= ui
+                .add(
+                    egui::Slider::new(&mut self.params.agc_attack_ms, 0.1..=100.0)
+                        .logarithmic(true)
+                        .text(gui::attack_ms()),
+                )
+                .changed();
+            changed |= ui
+                .add(
+                    egui::Slider::new(&mut self.params.agc_release_ms, 10.0..=5000.0)
+                        .logarithmic(true)
+                        .text(gui::release_ms()),
+                )
+                .changed();
+            changed |= ui
+                .add(
+                    egui::Slider::new(&mut self.params.agc_max_gain_db, 0.0..=60.0)
+                        .text(gui::max_gain()),
+                )
+                .changed();
+            if changed {
+                self.schedule_processing();
+            }
+        });
+
+        ui.separator();
+        ui.label(gui::dropouts());
+
+        let mut changed = false;
+        changed |= ui
+            .add(
+                egui::Slider::new(&mut self.params.dropout_rate, 0.0..=20.0)
+                    .text(gui::dropout_rate()),
+            )
+            .changed();
+        changed |= ui
+            .add_enabled(
+                self.params.dropout_rate > 0.0,
+                egui::Slider::new(&mut self.params.dropout_ms, 10.0..=10000.0)
+                    .logarithmic(true)
+                    .text(gui::impulse_ms()),
+            )
+            .changed();
+        let mut removed = None;
+        for (i, window) in self.params.dropouts.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                ui.label(gui::dropout_number(i + 1));
+                if ui.button("✖").clicked() {
+                    removed = Some(i);
+                }
+            });
+            changed |= ui
+                .add(
+                    egui::Slider::new(&mut window.start_ms, -2000.0..=115000.0)
+                        .text(gui::start_ms()),
+                )
+                .on_hover_text(gui::dropout_start_hover())
+                .changed();
+            changed |= ui
+                .add(
+                    egui::Slider::new(&mut window.duration_ms, 10.0..=120000.0)
+                        .logarithmic(true)
+                        .text(gui::impulse_ms()),
+                )
+                .changed();
+        }
+        if let Some(i) = removed {
+            self.params.dropouts.remove(i);
+            changed = true;
+        }
+        if ui.button(gui::add_dropout()).clicked() {
+            self.params
+                .dropouts
+                .push(DropoutWindow::new(30000.0, 2000.0));
+            changed = true;
+        }
+        egui::ComboBox::from_label(gui::dropout_kind())
+            .selected_text(self.params.dropout_kind.name())
+            .show_ui(ui, |ui| {
+                for &kind in DropoutKind::ALL {
+                    changed |= ui
+                        .selectable_value(&mut self.params.dropout_kind, kind, kind.name())
+                        .changed();
+                }
+            })
+            .response
+            .on_hover_text(gui::dropout_kind_hover());
+        changed |= ui
+            .add_enabled(
+                self.params.dropout_kind == DropoutKind::Mute,
+                egui::Slider::new(&mut self.params.dropout_db, 0.0..=80.0)
+                    .text(gui::attenuation_db()),
+            )
+            .changed();
+        if changed {
+            self.schedule_processing();
+        }
+
+        ui.separator();
+        ui.label(gui::timeline());
+        ui.horizontal(|ui| {
+            if ui
+                .button(gui::timeline_button())
+                .on_hover_text(gui::timeline_hover())
+                .clicked()
+                && let Some((path, timeline)) = Self::pick_timeline_file()
+            {
+                self.params.events = timeline.events;
+                self.params.timeline = Some(path);
+                self.schedule_processing();
+            }
+            if ui
+                .add_enabled(
+                    !self.params.events.is_empty(),
+                    egui::Button::new(gui::reset()),
+                )
+                .clicked()
+            {
+                self.params.events.clear();
+                self.params.timeline = None;
+                self.schedule_processing();
+            }
+        });
+        if self.params.events.is_empty() {
+            ui.label(gui::no_events());
+        } else {
+            for event in &self.params.events {
+                ui.label(event.describe());
+            }
+        }
+
+        ui.separator();
+        ui.label(gui::mix_and_output());
+        let mut changed = false;
+        egui::Grid::new("effect_mix").num_columns(3).show(ui, |ui| {
+            for &target in MixTarget::ALL {
+                ui.label(target.name());
+                let mix = self.params.mix_mut(target);
+                changed |= ui
+                    .add(egui::Slider::new(&mut mix.wet, 0.0..=1.0).text("wet"))
+                    .on_hover_text(gui::wet_hover())
+                    .changed();
+                changed |= ui
+                    .add(egui::Slider::new(&mut mix.dry, 0.0..=1.0).text("dry"))
+                    .on_hover_text(gui::dry_hover())
+                    .changed();
+                ui.end_row();
+            }
+        });
+        // Нетронутые эффекты не засоряют команду CLI и пресеты лишними --mix
+        self.params.mix.retain(|m| !m.mix.is_identity());
+        changed |= ui
+            .add(
+                egui::Slider::new(&mut self.params.output_gain_db, -24.0..=12.0)
+                    .text(gui::output_gain()),
+            )
+            .on_hover_text(gui::output_gain_hover())
+            .changed();
+        egui::ComboBox::from_label(gui::normalize())
+            .selected_text(self.params.normalize.name())
+            .show_ui(ui, |ui| {
+                for &measure in LoudnessMeasure::ALL {
+                    changed |= ui
+                        .selectable_value(&mut self.params.normalize, measure, measure.name())
+                        .changed();
+                }
+            })
+            .response
+            .on_hover_text(gui::normalize_hover());
+        let normalize = self.params.normalize != LoudnessMeasure::Off;
+        changed |= ui
+            .add_enabled(
+                normalize,
+                egui::Slider::new(&mut self.params.normalize_target, -40.0..=0.0)
+                    .text(gui::normalize_target()),
+            )
+            .changed();
+        changed |= ui
+            .add_enabled(
+                normalize,
+                egui::Slider::new(&mut self.params.true_peak_db, -12.0..=0.0)
+                    .text(gui::true_peak_db()),
+            )
+            .changed();
+        if changed {
+            self.schedule_processing();
+        }
+
+        ui.separator();
+        ui.label(gui::encoder());
+
+        egui::ComboBox::from_label(gui::encoder_backend())
+            .selected_text(self.params.encoder.encoder.name())
+            .show_ui(ui, |ui| {
+                for &backend in EncoderBackend::ALL {
+                    if ui
+                        .selectable_value(&mut self.params.encoder.encoder, backend, backend.name())
+                        .changed()
+                    {
+                        self.schedule_processing();
+                    }
+                }
+            });
+
+        ui.add_enabled_ui(
+            self.params.encoder.encoder == EncoderBackend::Native,
+            |ui| {
+                let mut header = !self.params.encoder.no_header;
+                if ui.checkbox(&mut header, gui::header_and_vis()).changed() {
+                    self.params.encoder.no_header = !header;
+                    self.schedule_processing();
+                }
+
+                if ui
+                    .add(
+                        egui::Slider::new(&mut self.params.encoder.leader_ms, 0.0..=600.0)
+                            .text(gui::leader_ms()),
+                    )
+                    .changed()
+                {
+                    self.schedule_processing();
+                }
+
+                if ui
+                    .add(
+                        egui::Slider::new(&mut self.params.encoder.vis_code, 0..=127)
+                            .text(gui::vis_code()),
+                    )
+                    .changed()
+                {
+                    self.schedule_processing();
+                }
+
+                if ui
+                    .add(
+                        egui::Slider::new(&mut self.params.encoder.vis_attenuation_db, 0.0..=40.0)
+                            .text(gui::vis_attenuation()),
+                    )
+                    .changed()
+                {
+                    self.schedule_processing();
+                }
+
+                if ui
+                    .add(
+                        egui::Slider::new(&mut self.params.encoder.vis_flip_bits, 0..=8)
+                            .text(gui::vis_flip_bits()),
+                    )
+                    .changed()
+                {
+                    self.schedule_processing();
+                }
+
+                if ui
+                    .add(
+                        egui::Slider::new(&mut self.params.encoder.timing_scale, 0.98..=1.02)
+                            .text(gui::timing_scale()),
+                    )
+                    .changed()
+                {
+                    self.schedule_processing();
+                }
+            },
+        );
+
+        ui.horizontal(|ui| {
+            ui.label(gui::callsign());
+            let mut callsign = self.params.encoder.id.clone().unwrap_or_default();
+            if ui
+                .text_edit_singleline(&mut callsign)
+                .on_hover_text(gui::callsign_hover())
+                .changed()
+            {
+                self.params.encoder.id = (!callsign.trim().is_empty()).then_some(callsign);
+                self.schedule_processing();
+            }
+        });
+        ui.add_enabled_ui(self.params.encoder.id.is_some(), |ui| {
+            let mut changed = false;
+            egui::ComboBox::from_label(gui::id_mode())
+                .selected_text(self.params.encoder.id_mode.name())
+                .show_ui(ui, |ui| {
+                    for &mode in IdMode::ALL {
+                        changed |= ui
+                            .selectable_value(&mut self.params.encoder.id_mode, mode, mode.name())
+                            .changed();
+                    }
+                });
+            ui.add_enabled_ui(self.params.encoder.id_mode == IdMode::Cw, |ui| {
+                changed |= ui
+                    .add(
+                        egui::Slider::new(&mut self.params.encoder.id_wpm, 5.0..=40.0)
+                            .text(gui::id_wpm()),
+                    )
+                    .changed();
+                changed |= ui
+                    .add(
+                        egui::Slider::new(&mut self.params.encoder.id_tone_hz, 300.0..=2500.0)
+                            .text(gui::id_tone()),
+                    )
+                    .changed();
+            });
+            if changed {
+                self.schedule_processing();
+            }
+        });
+
+        ui.separator();
+        ui.label(gui::overlay());
+        let mut changed = false;
+        let mut text = self.params.overlay.overlay.join("\n");
+        if ui
+            .add(egui::TextEdit::multiline(&mut text).desired_rows(2))
+            .on_hover_text(gui::overlay_hover())
+            .changed()
+        {
+            self.params.overlay.overlay = text
+                .lines()
+                .filter(|line| !line.trim().is_empty())
+                .map(str::to_string)
+                .collect();
+            changed = true;
+        }
+        ui.add_enabled_ui(!self.params.overlay.overlay.is_empty(), |ui| {
+            changed |= ui
+                .add(egui::Slider::new(&mut self.params.overlay.overlay_x, -320..=320).text("X"))
+                .on_hover_text(gui::overlay_x_hover())
+                .changed();
+            changed |= ui
+                .add(egui::Slider::new(&mut self.params.overlay.overlay_y, -256..=256).text("Y"))
+                .on_hover_text(gui::overlay_y_hover())
+                .changed();
+            changed |= ui
+                .add(
+                    egui::Slider::new(
+                        &mut self.params.overlay.overlay_size,
+                        1.0..=MAX_OVERLAY_SIZE,
+                    )
+                    .text(gui::overlay_size()),
+                )
+                .changed();
+            ui.horizontal(|ui| {
+                ui.label(gui::color());
+                changed |= ui
+                    .color_edit_button_srgb(&mut self.params.overlay.overlay_color.0)
+                    .changed();
+                changed |= ui
+                    .checkbox(&mut self.params.overlay.overlay_shadow, gui::shadow())
+                    .changed();
+            });
+            ui.horizontal(|ui| {
+                let font = self
+                    .params
+                    .overlay
+                    .overlay_font
+                    .as_deref()
+                    .map(|path| {
+                        Path::new(path)
+                            .file_name()
+                            .map_or(path.to_string(), |name| name.to_string_lossy().to_string())
+                    })
+                    .unwrap_or_else(|| gui::builtin_font().to_string());
+                ui.label(gui::font_label(&font));
+                if ui
+                    .button(gui::font_button())
+                    .on_hover_text(gui::font_hover())
+                    .clicked()
+                    && let Some(path) = rfd::FileDialog::new()
+                        .add_filter(gui::filter_font(), &["ttf", "otf"])
+                        .set_title(gui::font_title())
+                        .pick_file()
+                {
+                    self.params.overlay.overlay_font = Some(path.to_string_lossy().to_string());
+                    changed = true;
+                }
+                if ui
+                    .add_enabled(
+                        self.params.overlay.overlay_font.is_some(),
+                        egui::Button::new(gui::reset()),
+                    )
+                    .clicked()
+                {
+                    self.params.overlay.overlay_font = None;
+                    changed = true;
+                }
+            });
+        });
+        if changed {
+            self.update_image_texture(&ui.ctx().clone(), true);
+            self.schedule_processing();
+        }
+
+        ui.separator();
+        ui.label(gui::metadata());
+        let mut changed = false;
+        let mut removed = None;
+        for (i, field) in self.params.meta.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                changed |= ui
+                    .add(
+                        egui::TextEdit::singleline(&mut field.key)
+                            .desired_width(90.0)
+                            .hint_text(gui::metadata_key()),
+                    )
+                    .changed();
+                changed |= ui
+                    .add(
+                        egui::TextEdit::singleline(&mut field.value)
+                            .desired_width(150.0)
+                            .hint_text(gui::metadata_value()),
+                    )
+                    .changed();
+                if ui.button("✖").clicked() {
+                    removed = Some(i);
+                }
+            });
+        }
+        if let Some(i) = removed {
+            self.params.meta.remove(i);
+            changed = true;
+        }
+        if ui.button(gui::add_metadata()).clicked() {
+            self.params.meta.push(MetaField {
+                key: String::new(),
+                value: String::new(),
+            });
+        }
+        ui.add_enabled_ui(!self.params.meta.is_empty(), |ui| {
+            changed |= ui
+                .checkbox(&mut self.params.meta_overlay, gui::metadata_overlay())
+                .changed();
+            changed |= ui
+                .checkbox(&mut self.params.meta_fsk, gui::metadata_fsk())
+                .on_hover_text(gui::metadata_fsk_hover())
+                .changed();
+        });
+        if changed {
+            self.update_image_texture(&ui.ctx().clone(), true);
+            self.schedule_processing();
+        }
+
+        ui.separator();
+        ui.label(gui::decoder());
+
+        egui::ComboBox::from_label(gui::decoder_backend())
+            .selected_text(self.params.decoder.decoder.name())
+            .show_ui(ui, |ui| {
+                for &backend in DecoderBackend::ALL {
+                    if ui
+                        .selectable_value(&mut self.params.decoder.decoder, backend, backend.name())
+                        .changed()
+                    {
+                        self.schedule_processing();
+                    }
+                }
+            });
+
+        if ui
+            .add(
+                egui::Slider::new(&mut self.params.decoder.sync_threshold, 0.05..=1.0)
+                    .text(gui::sync_threshold()),
+            )
+            .changed()
+        {
+            self.schedule_processing();
+        }
+
+        if ui
+            .add(
+                egui::Slider::new(&mut self.params.decoder.search_window_ms, 0..=10000)
+                    .text(gui::search_window()),
+            )
+            .changed()
+        {
+            self.schedule_processing();
+        }
+
+        if ui
+            .add(
+                egui::Slider::new(&mut self.params.decoder.max_deviation_hz, 25.0..=500.0)
+                    .text(gui::max_deviation()),
+            )
+            .changed()
+        {
+            self.schedule_processing();
+        }
+
+        if ui
+            .checkbox(&mut self.params.decoder.afc, gui::afc())
+            .on_hover_text(gui::afc_hover())
+            .changed()
+        {
+            self.schedule_processing();
+        }
+
+        if ui
+            .checkbox(&mut self.params.decoder.conceal, gui::conceal())
+            .on_hover_text(gui::conceal_hover())
+            .changed()
+        {
+            self.schedule_processing();
+        }
+
+        ui.add_enabled_ui(
+            self.params.decoder.decoder == DecoderBackend::Native,
+            |ui| {
+                if ui
+                    .checkbox(&mut self.params.decoder.deskew, gui::deskew())
+                    .on_hover_text(gui::deskew_hover())
+                    .changed()
+                {
+                    self.schedule_processing();
+                }
+
+                ui.horizontal(|ui| {
+                    let mut manual = self.params.decoder.slant.is_some();
+                    if ui
+                        .checkbox(&mut manual, gui::manual_slant())
+                        .on_hover_text(gui::manual_slant_hover())
+                        .changed()
+                    {
+                        self.params.decoder.slant = manual.then_some(0.0);
+                        self.schedule_processing();
+                    }
+                    if let Some(slant) = &mut self.params.decoder.slant
+                        && ui
+                            .add(
+                                egui::DragValue::new(slant)
+                                    .speed(10.0)
+                                    .clamp_range(-50_000.0..=50_000.0),
+                            )
+                            .changed()
+                    {
+                        self.schedule_processing();
+                    }
+                });
+            },
+        );
+
+        egui::ComboBox::from_label(gui::palette())
+            .selected_text(self.params.decoder.palette.name())
+            .show_ui(ui, |ui| {
+                for &palette in Palette::ALL {
+                    if ui
+                        .selectable_value(&mut self.params.decoder.palette, palette, palette.name())
+                        .changed()
+                    {
+                        self.schedule_processing();
+                    }
+                }
+            })
+            .response
+            .on_hover_text(gui::palette_hover());
+
+        ui.separator();
+        ui.label(gui::scan_converter());
+
+        if ui
+            .add(egui::Slider::new(&mut self.params.decoder.blur, 0.0..=1.0).text(gui::blur()))
+            .on_hover_text(gui::blur_hover())
+            .changed()
+        {
+            self.schedule_processing();
+        }
+
+        if ui
+            .add(
+                egui::Slider::new(&mut self.params.decoder.scanlines, 0.0..=1.0)
+                    .text(gui::scanlines()),
+            )
+            .on_hover_text(gui::scanlines_hover())
+            .changed()
+        {
+            self.schedule_processing();
+        }
+
+        ui.horizontal(|ui| {
+            egui::ComboBox::from_label(gui::phosphor())
+                .selected_text(self.params.decoder.phosphor.name())
+                .show_ui(ui, |ui| {
+                    for &phosphor in Phosphor::ALL {
+                        if ui
+                            .selectable_value(
+                                &mut self.params.decoder.phosphor,
+                                phosphor,
+                                phosphor.name(),
+                            )
+                            .changed()
+                        {
+                            self.schedule_processing();
+                        }
+                    }
+                });
+            if ui
+                .add(
+                    egui::Slider::new(&mut self.params.decoder.phosphor_tint, 0.0..=1.0)
+                        .text(gui::tint()),
+                )
+                .changed()
+            {
+                self.schedule_processing();
             }
-        }
+        });
 
-        // Сбрасываем флаг обработки
-        if let Ok(mut is_processing) = self.processing.try_lock() {
-            *is_processing = false;
+        if ui
+            .button(gui::robot_1200c())
+            .on_hover_text(gui::robot_1200c_hover())
+            .clicked()
+        {
+            let converter = ScanConverterParams::robot_1200c();
+            let decoder = &mut self.params.decoder;
+            decoder.palette = ScanConverterParams::ROBOT_1200C_PALETTE;
+            decoder.blur = converter.blur;
+            decoder.scanlines = converter.scanlines;
+            decoder.phosphor = converter.phosphor;
+            decoder.phosphor_tint = converter.tint;
+            self.schedule_processing();
         }
-    }
 
-    fn save_result(&self) {
-        if let Some(result) = &self.result_image {
-            match self.processor.save_result(result, &self.output_path) {
-                Ok(_) => {
-                    println!("Сохранено в: {}", self.output_path);
+        ui.separator();
 
-                    // Показываем системное уведомление об успешном сохранении
-                    #[cfg(not(target_arch = "wasm32"))]
+        ui.horizontal(|ui| {
+            let mut fixed = self.params.seed.is_some();
+            if ui
+                .checkbox(&mut fixed, gui::fixed_seed())
+                .on_hover_text(gui::fixed_seed_hover())
+                .changed()
+            {
+                self.params.seed = fixed.then_some(0);
+                self.schedule_processing();
+            }
+            if let Some(seed) = &mut self.params.seed
+                && ui.add(egui::DragValue::new(seed)).changed()
+            {
+                self.schedule_processing();
+            }
+        });
+
+        egui::ComboBox::from_label(gui::convolution())
+            .selected_text(self.params.convolution.name())
+            .show_ui(ui, |ui| {
+                for &backend in ConvolutionBackend::ALL {
+                    if ui
+                        .selectable_value(&mut self.params.convolution, backend, backend.name())
+                        .changed()
                     {
-                        if let Err(e) = std::process::Command::new("notify-send")
-                            .args(&[
-                                "SSTV Processor",
-                                &format!("Файл сохранен: {}", self.output_path),
-                            ])
-                            .output()
-                        {
-                            // Если notify-send не работает, просто игнорируем
-                            let _ = e;
-                        }
+                        self.schedule_processing();
                     }
                 }
-                Err(e) => {
-                    eprintln!("Ошибка сохранения: {}", e);
-                }
-            }
-        } else {
-            eprintln!("Нет результата для сохранения");
-        }
-    }
-}
+            })
+            .response
+            .on_hover_text(gui::convolution_hover());
 
-impl eframe::App for SSTVApp {
-    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
-        // Проверяем, нужно ли обработать изображения
-        self.process_if_needed(ctx);
+        ui.checkbox(&mut self.params.parallel, gui::parallel())
+            .on_hover_text(gui::parallel_hover());
 
-        // Левая панель - параметры
-        egui::SidePanel::left("parameters").show(ctx, |ui| {
-            ui.heading("Параметры SSTV");
+        egui::ComboBox::from_label(gui::sample_rate())
+            .selected_text(gui::hz(self.params.sample_rate))
+            .show_ui(ui, |ui| {
+                for &rate in SAMPLE_RATES {
+                    if ui
+                        .selectable_value(&mut self.params.sample_rate, rate, gui::hz(rate))
+                        .changed()
+                    {
+                        self.schedule_processing();
+                    }
+                }
+            })
+            .response
+            .on_hover_text(gui::sample_rate_hover());
 
-            ui.separator();
-            ui.label("Файлы:");
+        egui::ComboBox::from_label(gui::quality())
+            .selected_text(self.params.quality.name())
+            .show_ui(ui, |ui| {
+                for &tier in QualityTier::ALL {
+                    if ui
+                        .selectable_value(&mut self.params.quality, tier, tier.name())
+                        .changed()
+                    {
+                        self.schedule_processing();
+                    }
+                }
+            })
+            .response
+            .on_hover_text(gui::quality_hover());
 
-            ui.horizontal(|ui| {
-                ui
-                    .label("Основное изображение:")
-                    .on_hover_text("Изображение для обработки");
-
-                if ui.button("Выбрать").clicked() {
-                    if let Some(path) = rfd::FileDialog::new()
-                        .add_filter("Изображения", &["png", "jpg", "jpeg", "bmp", "gif", "tiff", "webp"])
-                        .set_title("Выберите основное изображение")
-                        .pick_file()
+        egui::ComboBox::from_label(gui::sanitize())
+            .selected_text(self.params.sanitize.name())
+            .show_ui(ui, |ui| {
+                for &mode in SanitizeMode::ALL {
+                    if ui
+                        .selectable_value(&mut self.params.sanitize, mode, mode.name())
+                        .changed()
                     {
-                        self.main_image_path = path.to_string_lossy().to_string();
-                        let path_clone = self.main_image_path.clone();
-                        self.load_image_and_update_texture(ctx, &path_clone, true);
+                        self.schedule_processing();
                     }
                 }
-            });
-            ui.text_edit_singleline(&mut self.main_image_path);
-            if ui.button("Загрузить по пути").clicked() && !self.main_image_path.is_empty() {
-                let path = self.main_image_path.clone();
-                self.load_image_and_update_texture(ctx, &path, true);
-                self.request_manual_processing();  // Обрабатываем сразу при загрузке
-                self.request_manual_processing();  // Обрабатываем сразу при загрузке
-            }
+            })
+            .response
+            .on_hover_text(gui::sanitize_hover());
 
-            ui.horizontal(|ui| {
-                ui.label("Ретардер:");
-                if ui.button("Выбрать").clicked() {
-                    if let Some(path) = rfd::FileDialog::new()
-                        .add_filter("Изображения", &["png", "jpg", "jpeg", "bmp", "gif", "tiff", "webp"])
-                        .set_title("Выберите изображение ретардера")
-                        .pick_file()
+        egui::ComboBox::from_label(gui::metric_space())
+            .selected_text(self.params.metric_space.name())
+            .show_ui(ui, |ui| {
+                for &space in MetricSpace::ALL {
+                    if ui
+                        .selectable_value(&mut self.params.metric_space, space, space.name())
+                        .changed()
                     {
-                        self.retarder_image_path = path.to_string_lossy().to_string();
-                        let path_clone = self.retarder_image_path.clone();
-                        self.load_image_and_update_texture(ctx, &path_clone, false);
-                        self.request_manual_processing();  // Обрабатываем сразу при загрузке
+                        self.schedule_processing();
                     }
                 }
-                if ui.button("Очистить").clicked() {
-                    self.retarder_image = None;
-                    self.retarder_texture = None;
-                    self.retarder_image_path.clear();
-                    self.schedule_processing();
+            })
+            .response
+            .on_hover_text(gui::metric_space_hover());
+
+        let mut language = messages::language();
+        egui::ComboBox::from_label(gui::language_label())
+            .selected_text(language.name())
+            .show_ui(ui, |ui| {
+                for &option in Language::ALL {
+                    if ui
+                        .selectable_value(&mut language, option, option.name())
+                        .changed()
+                    {
+                        messages::set_language(language);
+                    }
                 }
-            });
-            ui.text_edit_singleline(&mut self.retarder_image_path);
-            if ui.button("Загрузить по пути").clicked() && !self.retarder_image_path.is_empty() {
-                let path = self.retarder_image_path.clone();
-                self.load_image_and_update_texture(ctx, &path, false);
-                self.request_manual_processing();  // Обрабатываем сразу при загрузке
+            })
+            .response
+            .on_hover_text(gui::language_hover());
+
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.auto_process, gui::auto_process());
+            ui.checkbox(&mut self.fast_preview, gui::fast_preview())
+                .on_hover_text(gui::fast_preview_hover());
+            if ui.button(gui::process_now()).clicked() {
+                self.request_manual_processing();
             }
+        });
 
-            ui.separator();
-            ui.label("Шум:");
+        if ui
+            .button(gui::randomize())
+            .on_hover_text(gui::randomize_hover())
+            .clicked()
+        {
+            self.randomize_params();
+        }
 
-            if ui.add(egui::Slider::new(&mut self.noise_level, 0..=100)
-                .text("Уровень")).changed() {
-                self.schedule_processing();
+        ui.horizontal(|ui| {
+            if ui
+                .button(gui::save_preset())
+                .on_hover_text(gui::save_preset_hover())
+                .clicked()
+            {
+                self.save_preset();
             }
+            if ui.button(gui::load_preset()).clicked() {
+                self.load_preset();
+            }
+        });
 
-            egui::ComboBox::from_label("Огибающая шума")
-                .selected_text(self.noise_env.name())
-                .show_ui(ui, |ui| {
-                    for &env in EnvelopeKind::ALL {
-                        if ui.selectable_value(&mut self.noise_env, env, env.name()).changed() {
-                            self.schedule_processing();
-                        }
-                    }
-                });
+        ui.collapsing(gui::soak(), |ui| self.soak_panel(ui, ctx));
 
-            if ui.add(egui::Slider::new(&mut self.noise_repeat, 0.1..=10.0)
-                .text("Повторение")).changed() {
-                self.schedule_processing();
+        if !self.auto_process {
+            ui.colored_label(
+                egui::Color32::from_rgb(255, 165, 0),
+                gui::auto_process_off(),
+            );
+        }
+
+        // Показываем статус обработки
+        if let Some(job) = &self.job {
+            let (overall, text) = match job.progress {
+                Some((stage, fraction)) => {
+                    let index = Stage::ALL.iter().position(|&s| s == stage).unwrap_or(0);
+                    (
+                        (index as f32 + fraction) / Stage::ALL.len() as f32,
+                        format!("⏳ {} {:.0}%", stage.name(), fraction * 100.0),
+                    )
+                }
+                None => (0.0, gui::queued().to_string()),
+            };
+            ui.horizontal(|ui| {
+                ui.add(
+                    egui::ProgressBar::new(overall)
+                        .desired_width(200.0)
+                        .text(text),
+                );
+                if ui.button(gui::cancel()).clicked() {
+                    job.cancel.store(true, Ordering::Relaxed);
+                }
+            });
+        }
+        if self.last_process_time.is_some() {
+            let remaining = self
+                .last_process_time
+                .map_or(0, |t| 2000_u128.saturating_sub(t.elapsed().as_millis()));
+            if remaining > 0 {
+                ui.colored_label(
+                    egui::Color32::from_rgb(100, 150, 255),
+                    gui::auto_process_in(remaining as f32 / 1000.0),
+                );
             }
+        }
 
-            ui.separator();
-            ui.label("Ретардер:");
+        if self.encode_cached && self.last_error.is_none() {
+            ui.colored_label(
+                egui::Color32::from_rgb(100, 200, 100),
+                gui::encoder_cached(),
+            )
+            .on_hover_text(gui::encoder_cached_hover());
+        }
 
-            if ui.add(egui::Slider::new(&mut self.retarder_level, 0.0..=1.0)
-                .text("Уровень")).changed() {
-                self.schedule_processing();
-            }
+        // Ошибка и советы пробного прогона вместо одной строки в консоли
+        if let Some(error) = &self.last_error {
+            ui.colored_label(
+                egui::Color32::from_rgb(255, 80, 80),
+                format!("❌ {}", error),
+            );
+        }
+        for warning in &self.param_warnings {
+            ui.colored_label(
+                egui::Color32::from_rgb(255, 165, 0),
+                format!("⚠ {}", warning),
+            );
+        }
+        for suggestion in self.suggestions.iter() {
+            let color = match suggestion.severity {
+                Severity::Fatal => egui::Color32::from_rgb(255, 80, 80),
+                Severity::Warning => egui::Color32::from_rgb(255, 165, 0),
+                Severity::Info => egui::Color32::from_rgb(100, 150, 255),
+            };
+            ui.colored_label(color, format!("⚠ {}", suggestion));
+        }
 
-            egui::ComboBox::from_label("Огибающая ретардера")
-                .selected_text(self.retarder_env.name())
+        ui.separator();
+        ui.label(gui::sound());
+
+        Self::device_combo(
+            ui,
+            gui::output_device(),
+            &self.output_devices,
+            &mut self.output_device,
+        );
+        Self::device_combo(
+            ui,
+            gui::input_device(),
+            &self.input_devices,
+            &mut self.input_device,
+        );
+        if ui.button(gui::refresh_devices()).clicked() {
+            self.output_devices = audio::output_devices().unwrap_or_default();
+            self.input_devices = audio::input_devices().unwrap_or_default();
+        }
+        ui.add(egui::Slider::new(&mut self.listen_secs, 10..=300).text(gui::listen_secs()));
+
+        let audio_busy = self.audio_busy.try_lock().is_ok_and(|b| *b);
+        ui.horizontal(|ui| {
+            ui.add_enabled_ui(!audio_busy, |ui| {
+                if ui.button(gui::play()).clicked() {
+                    self.play_result();
+                }
+                if ui.button(gui::listen()).clicked() {
+                    self.sync_params_to_processor();
+                    self.start_listening();
+                }
+            });
+        });
+        ui.horizontal(|ui| {
+            egui::ComboBox::from_label(gui::automation())
+                .selected_text(self.automation_target.name())
                 .show_ui(ui, |ui| {
-                    for &env in EnvelopeKind::ALL {
-                        if ui.selectable_value(&mut self.retarder_env, env, env.name()).changed() {
-                            self.schedule_processing();
-                        }
+                    for &target in AutomationTarget::ALL {
+                        ui.selectable_value(&mut self.automation_target, target, target.name());
                     }
                 });
+            ui.add_enabled_ui(!audio_busy, |ui| {
+                if ui
+                    .button(gui::record())
+                    .on_hover_text(gui::record_hover())
+                    .clicked()
+                {
+                    self.start_automation();
+                }
+            });
+        });
+        if let Some(session) = &self.automation {
+            ui.colored_label(
+                egui::Color32::from_rgb(255, 80, 80),
+                gui::recording(
+                    session.target.name(),
+                    session
+                        .started
+                        .elapsed()
+                        .as_secs_f32()
+                        .min(session.duration),
+                    session.duration,
+                ),
+            );
+        }
+        if audio_busy {
+            ui.colored_label(egui::Color32::YELLOW, gui::audio_busy());
+        }
 
-            if ui.add(egui::Slider::new(&mut self.retarder_repeat, 0.1..=10.0)
-                .text("Повторение")).changed() {
-                self.schedule_processing();
+        ui.separator();
+        ui.horizontal(|ui| {
+            ui.label(gui::output_file());
+            ui.text_edit_singleline(&mut self.output_path);
+            if ui.button(gui::choose_folder()).clicked()
+                && let Some(folder) = rfd::FileDialog::new()
+                    .set_title(gui::choose_folder_title())
+                    .pick_folder()
+            {
+                let file_name = std::path::Path::new(&self.output_path)
+                    .file_name()
+                    .unwrap_or(std::ffi::OsStr::new("output.png"))
+                    .to_string_lossy()
+                    .to_string();
+                self.output_path = folder.join(&file_name).to_string_lossy().to_string();
             }
+        });
 
-            if ui.add(egui::Slider::new(&mut self.delay_ms, 0..=1000)
-                .text("Задержка (мс)")).changed() {
-                self.schedule_processing();
+        if ui.button(gui::save_result()).clicked() {
+            if self.result_image.is_some() {
+                self.save_result();
+            } else {
+                tracing::warn!("{}", gui::log_no_result_yet());
             }
+        }
+        if ui
+            .button(gui::save_run())
+            .on_hover_text(gui::save_run_hover())
+            .clicked()
+        {
+            self.export_run();
+        }
+    }
+
+    /// Вкладка «Изображения»: исходное, ретардер, результат, осциллограмма и история
+    fn images_tab(&mut self, ui: &mut egui::Ui, actions: &mut FrameActions) {
+        ui.horizontal(|ui| {
+            // Исходное изображение
+            let main = ui.vertical(|ui| {
+                ui.label(gui::original());
+                if let Some(texture) = &self.main_texture {
+                    Self::image_panel(ui, texture, Panel::Main, &mut actions.panel_action);
+                } else {
+                    ui.colored_label(egui::Color32::GRAY, gui::no_image());
+                    ui.allocate_space(Vec2::new(300.0, 200.0));
+                }
+            });
+            self.main_drop_rect = Some(main.response.rect);
 
             ui.separator();
 
-            ui.horizontal(|ui| {
-                ui.checkbox(&mut self.auto_process, "Автообработка (медленно!)");
-                if ui.button("🔄 Обработать сейчас").clicked() {
-                    self.request_manual_processing();
+            // Ретардер
+            let retarder = ui.vertical(|ui| {
+                ui.label(messages::retarder());
+                if let Some(texture) = &self.retarder_texture {
+                    Self::image_panel(ui, texture, Panel::Retarder, &mut actions.panel_action);
+                } else {
+                    ui.colored_label(egui::Color32::GRAY, gui::no_retarder());
+                    ui.allocate_space(Vec2::new(300.0, 200.0));
                 }
             });
+            self.retarder_drop_rect = Some(retarder.response.rect);
 
-            if !self.auto_process {
-                ui.colored_label(
-                    egui::Color32::from_rgb(255, 165, 0),
-                    "💡 Автообработка отключена. Нажмите '🔄 Обработать сейчас' для получения результата."
-                );
-            }
+            ui.separator();
 
-            // Показываем статус обработки
-            if self.processing.try_lock().map_or(false, |p| *p) {
-                ui.colored_label(egui::Color32::YELLOW, "⏳ Обработка...");
-            } else if self.last_process_time.is_some() {
-                let remaining = self.last_process_time.map_or(0, |t| {
-                    2000_u128.saturating_sub(t.elapsed().as_millis())
+            // Результат и водопад сигнала
+            ui.vertical(|ui| {
+                ui.horizontal(|ui| {
+                    ui.selectable_value(&mut self.result_tab, ResultTab::Image, gui::result());
+                    ui.selectable_value(&mut self.result_tab, ResultTab::Compare, gui::compare());
                 });
-                if remaining > 0 {
-                    ui.colored_label(
-                        egui::Color32::from_rgb(100, 150, 255),
-                        format!("⏱️ Автообработка через {:.1}с", remaining as f32 / 1000.0)
-                    );
+                let diff = (self.result_tab == ResultTab::Compare && self.compare_diff)
+                    .then(|| self.diff_texture(ui.ctx()))
+                    .flatten();
+                if self.result_tab == ResultTab::Compare
+                    && let (Some(original), Some(result)) =
+                        (&self.main_texture, &self.result_texture)
+                {
+                    let right = diff.as_ref().unwrap_or(result);
+                    Self::compare_panel(ui, original, right, &mut self.compare_wipe);
+                    ui.checkbox(&mut self.compare_diff, gui::compare_diff())
+                        .on_hover_text(gui::compare_diff_hover());
+                } else if let Some((_, texture)) =
+                    self.job.as_ref().and_then(|job| job.decoded.as_ref())
+                {
+                    Self::image_panel(ui, texture, Panel::Result, &mut actions.panel_action);
+                } else if let Some(texture) = &self.result_texture {
+                    let response =
+                        Self::image_panel(ui, texture, Panel::Result, &mut actions.panel_action);
+                    if self.spectrogram.is_some() {
+                        actions.hovered_line = actions.hovered_line.or(Self::result_line_hover(
+                            ui,
+                            &response,
+                            texture.size()[1] as u32,
+                            self.hovered_line,
+                        ));
+                    }
+                } else if self.job.is_some() {
+                    ui.colored_label(egui::Color32::YELLOW, gui::processing());
+                    ui.allocate_space(Vec2::new(300.0, 200.0));
+                } else {
+                    ui.colored_label(egui::Color32::GRAY, gui::no_result());
+                    ui.allocate_space(Vec2::new(300.0, 200.0));
                 }
-            }
+            });
+        });
+
+        if let Some(view) = &self.waveform {
+            ui.label(gui::waveform());
+            let inspected = self.inspection.as_ref().map(|i| &i.range);
+            Self::waveform_panel(ui, view, inspected, &mut actions.inspect);
+            Self::inspector_panel(ui, self.inspection.as_ref());
+            ui.separator();
+        }
 
+        if !self.history.is_empty() {
+            actions.history_click = self.history_strip(ui);
             ui.separator();
+        }
+    }
+
+    /// Вкладка «Водопад»: спектрограмма, связанная с результатом, и водопад 0–4000 Гц
+    fn waterfall_tab(&mut self, ui: &mut egui::Ui, actions: &mut FrameActions) {
+        let Some(view) = &self.spectrogram else {
+            ui.colored_label(egui::Color32::GRAY, gui::no_signal());
+            return;
+        };
+        ui.label(gui::spectrogram());
+        let inspected = self.inspection.as_ref().map(|i| &i.range);
+        let hovered =
+            Self::spectrogram_panel(ui, view, self.hovered_line, inspected, &mut actions.inspect);
+        actions.hovered_line = actions.hovered_line.or(hovered);
+        if self.waveform.is_some() {
+            Self::inspector_panel(ui, self.inspection.as_ref());
+        }
+        ui.separator();
+        Self::waterfall_panel(ui, view, &mut actions.panel_action);
+    }
 
-            // Информация о текущих настройках
-            ui.collapsing("Информация", |ui| {
-                ui.label(format!("Шум: {}%", self.noise_level));
-                if self.noise_level > 0 {
-                    let snr = 30.0 * (1.0 - self.noise_level as f32 / 100.0) + 0.1;
-                    ui.label(format!("SNR: {:.1} дБ", snr));
+    /// Вкладка «Метрики»: что эффекты делают с каналом, качество результата, отчёт декодера
+    fn metrics_tab(&mut self, ui: &mut egui::Ui) {
+        ui.label(gui::channel());
+        match &self.channel_summary {
+            Some(summary) => {
+                for line in summary.describe().lines() {
+                    ui.label(line);
                 }
-                ui.label(format!("Ретардер: {:.1}%", self.retarder_level * 100.0));
-                if self.delay_ms > 0 {
-                    ui.label(format!("Задержка: {} мс", self.delay_ms));
+            }
+            None => {
+                ui.label(gui::metrics_noise(self.params.noise));
+                ui.label(gui::metrics_retarder(self.params.level * 100.0));
+                if self.params.delay_ms > 0 {
+                    ui.label(gui::metrics_delay(self.params.delay_ms));
                 }
-            });
+            }
+        }
+
+        ui.separator();
+        if let Some(main_img) = &self.main_image {
+            let (w, h) = main_img.dimensions();
+            ui.label(gui::source_resolution(w, h));
+
+            if w != 320 || h != 256 {
+                ui.colored_label(egui::Color32::from_rgb(255, 165, 0), gui::will_resize());
+            } else {
+                ui.colored_label(egui::Color32::from_rgb(0, 255, 0), gui::ideal_size());
+            }
+        }
+
+        ui.label(gui::sstv_mode());
+        ui.label(gui::transmission_time());
+
+        if self.params.noise > 0 {
+            let snr = 30.0 * (1.0 - self.params.noise as f32 / 100.0) + 0.1;
+            ui.label(gui::snr(snr));
+        }
 
+        if let Some(quality) = &self.quality {
             ui.separator();
+            ui.label(gui::quality_heading());
+            if quality.psnr.is_finite() {
+                ui.label(gui::psnr(quality.psnr));
+            } else {
+                ui.label(gui::psnr_infinite());
+            }
+            ui.label(format!("SSIM: {:.4}", quality.ssim))
+                .on_hover_text(gui::ssim_hover());
+            ui.label(format!("MSE: {:.2}", quality.mse));
+            if let Some((registered, registration)) = &self.registered
+                && !registration.is_identity()
+            {
+                ui.label(gui::registered_quality(registered.psnr, registered.ssim))
+                    .on_hover_text(registration.describe());
+            }
+        }
+
+        if let Some(summary) = &self.decode_summary {
+            ui.label(summary).on_hover_text(gui::decode_summary_hover());
+        }
+        if let Some(concealed) = self.concealed {
+            ui.label(gui::concealed_lines(concealed));
+        }
+        if let Some(vis) = &self.vis {
+            ui.label(vis.describe()).on_hover_text(gui::vis_hover());
+        }
+    }
+
+    /// Вкладка «Журнал»: ошибки загрузки, время обработки и предупреждения, новые внизу
+    fn receive_tab(&mut self, ui: &mut egui::Ui) {
+        let running = self.monitor.as_ref().is_some_and(Monitor::is_running);
+        ui.add_enabled_ui(!running, |ui| {
             ui.horizontal(|ui| {
-                ui.label("Выходной файл:");
-                ui.text_edit_singleline(&mut self.output_path);
-                if ui.button("Выбрать папку").clicked() {
-                    if let Some(folder) = rfd::FileDialog::new()
-                        .set_title("Выберите папку для сохранения")
-                        .pick_folder()
-                    {
-                        let file_name = std::path::Path::new(&self.output_path)
-                            .file_name()
-                            .unwrap_or(std::ffi::OsStr::new("output.png"))
-                            .to_string_lossy()
-                            .to_string();
-                        self.output_path = folder.join(&file_name).to_string_lossy().to_string();
-                    }
+                ui.radio_value(&mut self.receive_pcm, false, gui::receive_device());
+                ui.radio_value(&mut self.receive_pcm, true, gui::receive_pcm())
+                    .on_hover_text(gui::receive_pcm_hover());
+            });
+            if self.receive_pcm {
+                ui.horizontal(|ui| {
+                    ui.label(gui::receive_pcm_path());
+                    ui.text_edit_singleline(&mut self.receive_pcm_path);
+                });
+                ui.horizontal(|ui| {
+                    ui.add(
+                        egui::DragValue::new(&mut self.receive_pcm_rate)
+                            .clamp_range(8000..=192_000),
+                    );
+                    ui.label(gui::receive_pcm_rate());
+                    egui::ComboBox::from_label(gui::receive_pcm_format())
+                        .selected_text(self.receive_pcm_format.name())
+                        .show_ui(ui, |ui| {
+                            for &format in PcmFormat::ALL {
+                                ui.selectable_value(
+                                    &mut self.receive_pcm_format,
+                                    format,
+                                    format.name(),
+                                );
+                            }
+                        });
+                });
+            } else {
+                Self::device_combo(
+                    ui,
+                    gui::input_device(),
+                    &self.input_devices,
+                    &mut self.input_device,
+                );
+            }
+            ui.horizontal(|ui| {
+                ui.label(gui::receive_location());
+                ui.add_enabled(
+                    !self.receive_gpsd,
+                    egui::TextEdit::singleline(&mut self.receive_location).desired_width(140.0),
+                )
+                .on_hover_text(gui::receive_location_hover());
+                ui.checkbox(&mut self.receive_gpsd, "gpsd")
+                    .on_hover_text(gui::receive_gpsd_hover());
+                if self.receive_gpsd {
+                    ui.text_edit_singleline(&mut self.receive_gpsd_addr);
                 }
             });
+            let squelch = &mut self.receive_squelch;
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut squelch.enabled, gui::receive_squelch())
+                    .on_hover_text(gui::receive_squelch_hover());
+                ui.add_enabled_ui(squelch.enabled, |ui| {
+                    ui.add(
+                        egui::DragValue::new(&mut squelch.open_db)
+                            .clamp_range(-30.0..=0.0)
+                            .speed(0.5)
+                            .suffix(" dB"),
+                    )
+                    .on_hover_text(gui::receive_squelch_open_hover());
+                    ui.add(
+                        egui::DragValue::new(&mut squelch.hysteresis_db)
+                            .clamp_range(0.0..=20.0)
+                            .speed(0.5)
+                            .prefix("± ")
+                            .suffix(" dB"),
+                    )
+                    .on_hover_text(gui::receive_squelch_hysteresis_hover());
+                    ui.add(
+                        egui::DragValue::new(&mut squelch.min_level_db)
+                            .clamp_range(-90.0..=0.0)
+                            .speed(0.5)
+                            .suffix(" dBFS"),
+                    )
+                    .on_hover_text(gui::receive_squelch_level_hover());
+                    ui.checkbox(&mut squelch.require_vis, "VIS")
+                        .on_hover_text(gui::receive_squelch_vis_hover());
+                });
+            });
+        });
 
-            if ui.button("💾 Сохранить результат").clicked() {
-                if self.result_image.is_some() {
-                    self.save_result();
-                } else {
-                    eprintln!("Нет результата для сохранения. Сначала обработайте изображение.");
+        ui.horizontal(|ui| {
+            match &self.monitor {
+                Some(monitor) if running => {
+                    if ui.button(gui::receive_stop()).clicked() {
+                        monitor.stop();
+                    }
+                    if ui
+                        .button(gui::receive_reset())
+                        .on_hover_text(gui::receive_reset_hover())
+                        .clicked()
+                    {
+                        monitor.reset();
+                    }
+                }
+                _ => {
+                    if ui.button(gui::receive_start()).clicked() {
+                        self.sync_params_to_processor();
+                        self.start_monitor(ui.ctx());
+                    }
                 }
             }
+            ui.add_enabled_ui(self.monitor.is_some(), |ui| {
+                if ui
+                    .button(gui::receive_save_audio())
+                    .on_hover_text(gui::receive_save_audio_hover())
+                    .clicked()
+                    && let Err(e) = self.save_received_audio()
+                {
+                    tracing::error!("{}", gui::log_error(&e.to_string()));
+                }
+            });
         });
 
-        // Центральная область - изображения
-        egui::CentralPanel::default().show(ctx, |ui| {
-            ui.heading("Предварительный просмотр");
+        if let Some(monitor) = &self.monitor {
+            let state = monitor.state();
+            if let Some(error) = &state.error {
+                ui.colored_label(
+                    egui::Color32::from_rgb(255, 80, 80),
+                    format!("❌ {}", error),
+                );
+            }
+            match &state.image {
+                _ if !state.running => ui.label(gui::receive_idle()),
+                Some(image) => ui.label(gui::receive_lines(state.lines, image.height())),
+                None => ui.label(gui::receive_waiting()),
+            };
+            if let Some(vis) = &state.vis {
+                ui.label(vis.describe());
+            }
+            if let Some(location) = &state.location {
+                ui.label(location.describe());
+            }
+            ui.label(gui::receive_level(state.level_db, state.elapsed_secs));
+            if self.receive_squelch.enabled {
+                let squelch = if state.squelch_open {
+                    gui::receive_squelch_open()
+                } else {
+                    gui::receive_squelch_closed()
+                };
+                ui.label(gui::receive_squelch_state(squelch, state.leader_db));
+            }
+        }
+        if let Some(texture) = &self.receive_texture {
+            let size = texture.size_vec2();
+            let scale = (ui.available_width() / size.x).min(2.0);
+            ui.add(egui::Image::from_texture(texture).fit_to_exact_size(size * scale));
+        }
 
-            egui::ScrollArea::vertical().show(ui, |ui| {
-                ui.horizontal(|ui| {
-                    // Исходное изображение
+        ui.separator();
+        ui.label(gui::receive_history());
+        if self.received.is_empty() {
+            ui.colored_label(egui::Color32::GRAY, gui::receive_none());
+        }
+        let mut show = None;
+        let mut save = None;
+        let count = self.received.len();
+        egui::ScrollArea::horizontal().show(ui, |ui| {
+            ui.horizontal(|ui| {
+                for (index, (received, texture)) in self.received.iter().enumerate() {
                     ui.vertical(|ui| {
-                        ui.label("Исходное");
-                        if let Some(texture) = &self.main_texture {
-                            let size = texture.size_vec2();
-                            let scale = (300.0 / size.x.max(size.y)).min(1.0);
-                            let scaled_size = size * scale;
-                            ui.add(
-                                egui::Image::from_texture(texture).fit_to_exact_size(scaled_size),
-                            );
-                            ui.label(format!("{}×{}", size.x as u32, size.y as u32));
-                        } else {
-                            ui.colored_label(egui::Color32::GRAY, "Изображение не загружено");
-                            ui.allocate_space(Vec2::new(300.0, 200.0));
+                        let size = texture.size_vec2();
+                        let scale = (160.0 / size.x.max(size.y)).min(1.0);
+                        ui.add(egui::Image::from_texture(texture).fit_to_exact_size(size * scale));
+                        ui.label(gui::receive_entry(count - index, received.lines));
+                        if let Some(vis) = &received.vis {
+                            ui.label(vis.describe());
                         }
-                    });
-
-                    ui.separator();
-
-                    // Ретардер
-                    ui.vertical(|ui| {
-                        ui.label("Ретардер");
-                        if let Some(texture) = &self.retarder_texture {
-                            let size = texture.size_vec2();
-                            let scale = (300.0 / size.x.max(size.y)).min(1.0);
-                            let scaled_size = size * scale;
-                            ui.add(
-                                egui::Image::from_texture(texture).fit_to_exact_size(scaled_size),
-                            );
-                            ui.label(format!("{}×{}", size.x as u32, size.y as u32));
-                        } else {
-                            ui.colored_label(egui::Color32::GRAY, "Ретардер не загружен");
-                            ui.allocate_space(Vec2::new(300.0, 200.0));
+                        if !received.metadata.is_empty() {
+                            ui.label(metadata::describe(&received.metadata));
                         }
+                        ui.label(received.stamp.describe());
+                        ui.horizontal(|ui| {
+                            if ui.small_button(gui::receive_show()).clicked() {
+                                show = Some(index);
+                            }
+                            if ui.small_button(gui::receive_save()).clicked() {
+                                save = Some(index);
+                            }
+                        });
                     });
+                }
+            });
+        });
+        if let Some(index) = show {
+            self.show_received(ui.ctx(), index);
+        }
+        if let Some(index) = save
+            && let Err(e) = self.save_received(index)
+        {
+            tracing::error!("{}", gui::log_error(&e.to_string()));
+        }
+    }
 
-                    ui.separator();
-
-                    // Результат
-                    ui.vertical(|ui| {
-                        ui.label("Результат");
-                        if let Some(texture) = &self.result_texture {
-                            let size = texture.size_vec2();
-                            let scale = (300.0 / size.x.max(size.y)).min(1.0);
-                            let scaled_size = size * scale;
-                            ui.add(
-                                egui::Image::from_texture(texture).fit_to_exact_size(scaled_size),
-                            );
-                            ui.label(format!("{}×{}", size.x as u32, size.y as u32));
-                        } else if self.processing.try_lock().map_or(false, |p| *p) {
-                            ui.colored_label(egui::Color32::YELLOW, "Обработка...");
-                            ui.allocate_space(Vec2::new(300.0, 200.0));
-                        } else {
-                            ui.colored_label(egui::Color32::GRAY, "Результат не готов");
-                            ui.allocate_space(Vec2::new(300.0, 200.0));
-                        }
-                    });
+    fn log_tab(&mut self, ui: &mut egui::Ui) {
+        // Копия под замком: запись из фонового потока не ждёт отрисовки
+        let visible: Vec<(Level, String)> = self
+            .log
+            .entries
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|entry| entry.level <= self.log_level)
+            .map(|entry| (entry.level, entry.line()))
+            .collect();
+        ui.horizontal(|ui| {
+            egui::ComboBox::from_id_source("log_level")
+                .selected_text(
+                    LOG_LEVELS
+                        .iter()
+                        .find(|(level, _)| *level == self.log_level)
+                        .map_or("", |(_, name)| name()),
+                )
+                .show_ui(ui, |ui| {
+                    for &(level, name) in LOG_LEVELS {
+                        ui.selectable_value(&mut self.log_level, level, name());
+                    }
                 });
+            ui.label(gui::log_entries(visible.len()));
+            if ui
+                .button(gui::copy())
+                .on_hover_text(gui::copy_log_hover())
+                .clicked()
+            {
+                let text: Vec<&str> = visible.iter().map(|(_, line)| line.as_str()).collect();
+                ui.ctx().output_mut(|o| o.copied_text = text.join("\n"));
+            }
+            if ui.button(gui::clear_log()).clicked() {
+                self.log.entries.lock().unwrap().clear();
+            }
+        });
+        ui.separator();
+        for (level, line) in visible {
+            let color = match level {
+                Level::ERROR => ui.visuals().error_fg_color,
+                Level::WARN => ui.visuals().warn_fg_color,
+                _ => ui.visuals().text_color(),
+            };
+            ui.colored_label(color, egui::RichText::new(line).monospace());
+        }
+    }
 
-                ui.separator();
+    /// Содержимое вкладки с кнопкой переноса в отдельное окно или обратно в раскладку
+    fn tab_ui(&mut self, ui: &mut egui::Ui, tab: Tab, actions: &mut FrameActions) {
+        ui.horizontal(|ui| {
+            if self.detached.contains(&tab) {
+                if ui
+                    .small_button(gui::attach())
+                    .on_hover_text(gui::attach_hover())
+                    .clicked()
+                {
+                    actions.attach = Some(tab);
+                }
+            } else if ui
+                .small_button(gui::detach())
+                .on_hover_text(gui::detach_hover())
+                .clicked()
+            {
+                actions.detach = Some(tab);
+            }
+        });
+        match tab {
+            Tab::Parameters => self.parameters_tab(ui),
+            Tab::Images => self.images_tab(ui, actions),
+            Tab::Waterfall => self.waterfall_tab(ui, actions),
+            Tab::Metrics => self.metrics_tab(ui),
+            Tab::Log => self.log_tab(ui),
+            Tab::Receive => self.receive_tab(ui),
+        }
+    }
 
-                // Дополнительная информация
-                if self.main_image.is_some() || self.result_image.is_some() {
-                    ui.collapsing("📊 Детали обработки", |ui| {
-                        if let Some(main_img) = &self.main_image {
-                            let (w, h) = main_img.dimensions();
-                            ui.label(format!("Исходное разрешение: {}×{}", w, h));
+    /// Вкладки, вынесенные в отдельные окна системы: их можно перетащить на другой монитор.
+    /// Закрытие окна возвращает вкладку в раскладку
+    fn show_detached(&mut self, ctx: &egui::Context, actions: &mut FrameActions) {
+        for tab in self.detached.clone() {
+            ctx.show_viewport_immediate(
+                egui::ViewportId::from_hash_of(("detached", tab)),
+                egui::ViewportBuilder::default()
+                    .with_title(tab.title())
+                    .with_inner_size([900.0, 600.0]),
+                |ctx, _class| {
+                    egui::CentralPanel::default().show(ctx, |ui| {
+                        egui::ScrollArea::both().show(ui, |ui| self.tab_ui(ui, tab, actions));
+                    });
+                    if ctx.input(|i| i.viewport().close_requested()) {
+                        actions.attach = Some(tab);
+                    }
+                },
+            );
+        }
+    }
+}
 
-                            if w != 320 || h != 256 {
-                                ui.colored_label(
-                                    egui::Color32::from_rgb(255, 165, 0),
-                                    "⚠️ Изображение будет сжато до 320×256 для SSTV обработки",
-                                );
-                            } else {
-                                ui.colored_label(
-                                    egui::Color32::from_rgb(0, 255, 0),
-                                    "✅ Идеальный размер для SSTV (320×256)",
-                                );
-                            }
-                        }
+impl eframe::App for SSTVApp {
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        // Проверяем, нужно ли обработать изображения
+        self.poll_job(ctx);
+        self.poll_soak(ctx);
+        self.process_if_needed();
+        self.poll_listening(ctx);
+        self.poll_monitor(ctx);
+        self.poll_automation(ctx);
+        self.handle_dropped_files(ctx);
+        self.handle_history_keys(ctx);
 
-                        ui.label("SSTV режим: Martin M1 (320×256)");
-                        ui.label("Длительность передачи: ~114.5 секунд");
+        // Вкладки можно перетаскивать, делить окно между ними и выносить в отдельные окна
+        let mut actions = FrameActions::default();
+        // Области бросания файлов задаёт вкладка изображений, только если она видна
+        self.main_drop_rect = None;
+        self.retarder_drop_rect = None;
+        let mut dock = std::mem::replace(&mut self.dock, DockState::new(Vec::new()));
+        DockArea::new(&mut dock)
+            .style(Style::from_egui(ctx.style().as_ref()))
+            .show(
+                ctx,
+                &mut TabView {
+                    app: self,
+                    actions: &mut actions,
+                },
+            );
+        self.dock = dock;
+        self.show_detached(ctx, &mut actions);
+        if let Some(tab) = actions.detach
+            && let Some(index) = self.dock.find_tab(&tab)
+        {
+            self.dock.remove_tab(index);
+            self.detached.push(tab);
+        }
+        if let Some(tab) = actions.attach {
+            self.detached.retain(|&t| t != tab);
+            self.dock.push_to_focused_leaf(tab);
+        }
 
-                        if self.noise_level > 0 {
-                            let snr = 30.0 * (1.0 - self.noise_level as f32 / 100.0) + 0.1;
-                            ui.label(format!("Отношение сигнал/шум: {:.1} дБ", snr));
-                        }
+        let FrameActions {
+            panel_action,
+            history_click,
+            hovered_line,
+            inspect,
+            ..
+        } = actions;
+        if let Some(action) = panel_action {
+            self.handle_panel_action(ctx, action);
+        }
+        if let Some(id) = history_click {
+            self.restore_history(id);
+        }
+        if hovered_line != self.hovered_line {
+            self.hovered_line = hovered_line;
+            ctx.request_repaint();
+        }
+        if let Some(range) = inspect {
+            self.inspection = self.waveform.as_ref().map(|view| view.inspect(range));
+        }
+        self.paint_drop_targets(ctx);
 
-                        if self.delay_ms > 0 {
-                            let delay_samples = (self.delay_ms as f32 / 1000.0 * 11_025.0) as usize;
-                            ui.label(format!("Задержка в сэмплах: {}", delay_samples));
-                        }
-                    });
-                }
-            });
-        });
+        // Витрина должна переключать изображения и без действий пользователя
+        if self.soak.running {
+            ctx.request_repaint_after(Duration::from_millis(500));
+        }
 
         // Запрашиваем перерисовку для анимации
-        if self.processing.try_lock().map_or(false, |p| *p) || self.last_process_time.is_some() {
+        if self.job.is_some()
+            || self.last_process_time.is_some()
+            || self.audio_busy.try_lock().is_ok_and(|b| *b)
+        {
             ctx.request_repaint();
         }
     }
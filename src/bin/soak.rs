@@ -0,0 +1,157 @@
+use anyhow::{Context, Result};
+use clap::{CommandFactory, FromArgMatches, Parser};
+use sstv_processor::messages::{self, help};
+use sstv_processor::{Language, MARTIN_M1, SoakParams, conformance, soak};
+use std::fs::File;
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+/// Параметры долгого прогона
+#[derive(Parser)]
+#[command(version, about = help::soak_about())]
+struct Args {
+    #[arg(short = 'i', long, help = help::soak_image())]
+    image: Option<PathBuf>,
+
+    #[arg(long, default_value_t = 1.0, help = help::soak_hours())]
+    hours: f64,
+
+    #[arg(long, help = help::soak_cycles())]
+    cycles: Option<usize>,
+
+    #[arg(long, default_value_t = SoakParams::default().seed, help = help::soak_seed())]
+    seed: u64,
+
+    #[arg(long, default_value_t = SoakParams::default().min_psnr_db, help = help::soak_min_psnr_db())]
+    min_psnr_db: f64,
+
+    #[arg(long, default_value_t = SoakParams::default().warmup_cycles, help = help::soak_warmup())]
+    warmup: usize,
+
+    #[arg(
+        long,
+        default_value_t = SoakParams::default().max_memory_growth_mb,
+        help = help::soak_max_growth_mb()
+    )]
+    max_growth_mb: f64,
+
+    #[arg(long, default_value_t = SoakParams::default().min_success_ratio, help = help::soak_min_success())]
+    min_success: f32,
+
+    #[arg(long, value_name = "SECS", default_value_t = 60, help = help::soak_report_every())]
+    report_every: u64,
+
+    #[arg(long, value_name = "FILE", help = help::soak_log())]
+    log: Option<PathBuf>,
+
+    #[arg(long, default_value = "en", help = help::lang())]
+    lang: Language,
+}
+
+/// Язык из `--lang` до разбора остальных аргументов, как в `cli`
+fn requested_language() -> Language {
+    let args: Vec<String> = std::env::args().collect();
+    args.iter()
+        .enumerate()
+        .find_map(|(i, arg)| match arg.strip_prefix("--lang") {
+            Some("") => args.get(i + 1).map(String::as_str),
+            Some(value) => value.strip_prefix('='),
+            None => None,
+        })
+        .and_then(|value| <Language as clap::ValueEnum>::from_str(value, true).ok())
+        .unwrap_or_default()
+}
+
+fn main() -> Result<()> {
+    messages::set_language(requested_language());
+    let matches = Args::command().get_matches();
+    let args = Args::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    messages::set_language(args.lang);
+
+    let image = match &args.image {
+        Some(path) => image::open(path)
+            .with_context(|| messages::soak_open_failed(&path.display().to_string()))?,
+        None => conformance::test_pattern(&MARTIN_M1),
+    };
+    let params = SoakParams {
+        seed: args.seed,
+        min_psnr_db: args.min_psnr_db,
+        warmup_cycles: args.warmup,
+        max_memory_growth_mb: args.max_growth_mb,
+        min_success_ratio: args.min_success,
+    };
+    let mut log = match &args.log {
+        Some(path) => {
+            let mut file = File::create(path)
+                .with_context(|| messages::soak_create_failed(&path.display().to_string()))?;
+            writeln!(
+                file,
+                "seed,elapsed_s,lines,psnr_db,success,resident_mb,error"
+            )?;
+            Some(file)
+        }
+        None => None,
+    };
+
+    // Паники циклов ловятся и входят в статистику; стандартный обработчик засорял бы вывод
+    let hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(|_| {}));
+    println!("{}", messages::soak_started(args.hours, args.seed));
+    let started = Instant::now();
+    let duration = Duration::from_secs_f64(args.hours.max(0.0) * 3600.0);
+    let every = Duration::from_secs(args.report_every.max(1));
+    let mut reported = Instant::now();
+    let mut log_error = None;
+    let stats = soak::run(&params, &image, |cycle, stats| {
+        if let Some(file) = log.as_mut() {
+            let row = writeln!(
+                file,
+                "{},{:.3},{},{},{},{},\"{}\"",
+                cycle.seed,
+                cycle.elapsed.as_secs_f64(),
+                cycle.lines,
+                cycle.psnr_db.map_or(String::new(), |p| format!("{:.2}", p)),
+                cycle.success,
+                cycle
+                    .resident
+                    .map_or(String::new(), |r| format!("{:.1}", r as f64 / 1048576.0)),
+                cycle.error.as_deref().unwrap_or_default().replace('"', "'"),
+            );
+            if let Err(e) = row {
+                log_error = Some(e);
+                return false;
+            }
+        }
+        if cycle.error.is_some() {
+            println!("✗ {}", cycle.describe());
+        }
+        if reported.elapsed() >= every {
+            reported = Instant::now();
+            let minutes = started.elapsed().as_secs() / 60;
+            println!(
+                "{}",
+                messages::soak_progress(minutes / 60, minutes % 60, &stats.describe())
+            );
+        }
+        started.elapsed() < duration && args.cycles.is_none_or(|max| stats.cycles < max)
+    });
+    std::panic::set_hook(hook);
+    if let Some(e) = log_error {
+        return Err(e).context(messages::soak_log_failed());
+    }
+
+    println!("{}", stats.describe());
+    for (seed, error) in &stats.first_errors {
+        println!("✗ {}", messages::soak_cycle_error(*seed, error));
+    }
+    let problems = stats.problems(&params);
+    for problem in &problems {
+        println!("✗ {}", problem);
+    }
+    if !problems.is_empty() {
+        anyhow::bail!(messages::soak_failed(problems.len()));
+    }
+    println!("{}", messages::soak_passed());
+    Ok(())
+}
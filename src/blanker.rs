@@ -0,0 +1,287 @@
+use crate::buffer::SampleBuffer;
+use crate::effect::{ChannelEffect, EffectContext, EffectMix};
+use crate::error::Result;
+use crate::messages;
+use crate::processor::ProcessingParams;
+use serde::{Deserialize, Serialize};
+
+/// Участок перед дырой, по которому ищется период тона, мс
+const PERIOD_WINDOW_MS: f32 = 2.0;
+/// Пределы частоты тона при поиске периода, Гц: тоны SSTV с запасом
+const PERIOD_MIN_HZ: f32 = 1000.0;
+const PERIOD_MAX_HZ: f32 = 2500.0;
+/// Наименьшая корреляция, при которой участок считается тоном; иначе — как `Hold`
+const PERIOD_MIN_CORRELATION: f32 = 0.5;
+
+/// Чем подавитель заполняет вырезанный участок
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BlankerFill {
+    /// Тишина: классический ключ, закрывающий тракт на время импульса
+    Zero,
+    /// Последний целый сэмпл перед участком (выборка-хранение)
+    Hold,
+    /// Линейная интерполяция между целыми сэмплами по краям участка
+    Interpolate,
+    /// Повтор последнего периода тона перед участком: фаза не рвётся, и частотный
+    /// детектор видит продолжение предыдущего тона
+    #[default]
+    Repeat,
+}
+
+impl BlankerFill {
+    pub const ALL: &'static [BlankerFill] = &[
+        BlankerFill::Zero,
+        BlankerFill::Hold,
+        BlankerFill::Interpolate,
+        BlankerFill::Repeat,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            BlankerFill::Zero => messages::blanker_fill_zero(),
+            BlankerFill::Hold => messages::blanker_fill_hold(),
+            BlankerFill::Interpolate => messages::blanker_fill_interpolate(),
+            BlankerFill::Repeat => messages::blanker_fill_repeat(),
+        }
+    }
+}
+
+impl clap::ValueEnum for BlankerFill {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[Self::Zero, Self::Hold, Self::Interpolate, Self::Repeat]
+    }
+
+    fn to_possible_value<'a>(&self) -> Option<clap::builder::PossibleValue> {
+        Some(match self {
+            Self::Zero => clap::builder::PossibleValue::new("zero"),
+            Self::Hold => clap::builder::PossibleValue::new("hold"),
+            Self::Interpolate => clap::builder::PossibleValue::new("interp"),
+            Self::Repeat => clap::builder::PossibleValue::new("repeat"),
+        })
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BlankerParams {
+    /// Включить подавитель импульсных помех приёмника
+    pub enabled: bool,
+    /// Порог срабатывания: во сколько раз выброс в канале обнаружения превышает его СКЗ.
+    /// Пики тонов SSTV — около 1.4–1.7 СКЗ, поэтому ниже 2 вырезается и сам сигнал
+    pub threshold: f32,
+    /// Сколько ещё вырезается до и после превышения порога, мс
+    pub hold_ms: f32,
+    /// Постоянная времени оценки СКЗ, мс
+    pub average_ms: f32,
+    pub fill: BlankerFill,
+    /// Доли смешивания выхода эффекта со входом
+    pub mix: EffectMix,
+}
+
+impl Default for BlankerParams {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            threshold: 4.0,
+            hold_ms: 0.2,
+            average_ms: 20.0,
+            fill: BlankerFill::Repeat,
+            mix: EffectMix::default(),
+        }
+    }
+}
+
+/// Подавитель импульсных помех (noise blanker) приёмника: вырезает участки, где выброс в
+/// канале обнаружения резко превышает средний уровень, и заполняет дыры. Как у настоящих
+/// подавителей, канал обнаружения широкополосный: первая разность сигнала, в которой
+/// широкополосный импульс заметен даже после ограничения, а тоны SSTV ослаблены. Стоит
+/// перед фильтром приёмника, пока импульс ещё не растянут фильтром. Против треска и
+/// грозовых разрядов помогает, но при низком пороге режет пики самого сигнала: сильнее
+/// всего высокие тона (белый), у которых первая разность больше
+pub struct BlankerProcessor {
+    #[deprecated(since = "0.1.0", note = "используйте `params()` и `set_params()`")]
+    pub params: BlankerParams,
+}
+
+impl Default for BlankerProcessor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[allow(deprecated)]
+impl BlankerProcessor {
+    pub fn new() -> Self {
+        Self {
+            params: BlankerParams::default(),
+        }
+    }
+
+    pub fn new_with_params(params: BlankerParams) -> Self {
+        Self { params }
+    }
+
+    pub fn params(&self) -> &BlankerParams {
+        &self.params
+    }
+
+    pub fn set_params(&mut self, params: BlankerParams) {
+        self.params = params;
+    }
+
+    /// Вырезает импульсы из буфера сэмплов; возвращает число вырезанных сэмплов
+    pub fn apply_blanker(&self, buffer: &mut SampleBuffer) -> usize {
+        let sample_rate = buffer.rate;
+        let samples = &mut buffer.data;
+        if !self.is_enabled() || samples.is_empty() {
+            return 0;
+        }
+        let mask = self.blanking_mask(samples, sample_rate);
+        fill_holes(samples, &mask, self.params.fill, sample_rate);
+        mask.iter().filter(|&&blanked| blanked).count()
+    }
+
+    /// Сэмплы, которые подавитель вырезает. Уровень — экспоненциальное среднее квадрата
+    /// первой разности, ограниченного порогом, так что сами импульсы его не завышают
+    fn blanking_mask(&self, samples: &[f32], sample_rate: u32) -> Vec<bool> {
+        let fs = sample_rate as f32;
+        let window = ((self.params.average_ms.max(0.0) / 1000.0 * fs) as usize).max(1);
+        let alpha = 1.0 / window as f32;
+        let threshold = self.params.threshold.max(0.0);
+
+        let detector: Vec<f32> = std::iter::once(0.0)
+            .chain(samples.windows(2).map(|w| w[1] - w[0]))
+            .collect();
+
+        // Начальный уровень — по первому окну, иначе срабатывание на фронте сигнала
+        let head = &detector[..window.min(detector.len())];
+        let mut mean_square = head.iter().map(|x| x * x).sum::<f32>() / head.len() as f32;
+
+        let mut triggered = vec![false; samples.len()];
+        for (flag, &x) in triggered.iter_mut().zip(&detector) {
+            let limit = threshold * threshold * mean_square;
+            let square = x * x;
+            *flag = mean_square > 0.0 && square > limit;
+            mean_square += alpha * (square.min(limit) - mean_square);
+        }
+
+        // Расширяем вырезку на время удержания в обе стороны
+        let hold = (self.params.hold_ms.max(0.0) / 1000.0 * fs).round() as usize;
+        let mut mask = vec![false; samples.len()];
+        let mut last_trigger = None;
+        for i in 0..samples.len() {
+            if triggered[i] {
+                let from = i.saturating_sub(hold);
+                mask[from..i].fill(true);
+                last_trigger = Some(i);
+            }
+            mask[i] |= last_trigger.is_some_and(|t| i - t <= hold);
+        }
+        mask
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.params.enabled
+    }
+
+    /// Возвращает описание текущих настроек подавителя
+    pub fn describe(&self) -> String {
+        if !self.is_enabled() {
+            return messages::blanker_off().to_string();
+        }
+        let p = &self.params;
+        messages::blanker_describe(p.threshold, p.hold_ms, p.average_ms, p.fill.name())
+    }
+}
+
+/// Заполняет вырезанные участки `mask` способом `fill`
+fn fill_holes(samples: &mut [f32], mask: &[bool], fill: BlankerFill, sample_rate: u32) {
+    let mut i = 0;
+    while i < samples.len() {
+        if !mask[i] {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        while i < samples.len() && mask[i] {
+            i += 1;
+        }
+        // Целые сэмплы по краям участка; у краёв сигнала их может не быть
+        let before = start.checked_sub(1).map(|k| samples[k]);
+        let after = samples.get(i).copied();
+        if fill == BlankerFill::Repeat
+            && let Some(period) = tone_period(&samples[..start], sample_rate)
+        {
+            for k in start..i {
+                samples[k] = samples[k - period];
+            }
+            continue;
+        }
+        let hole = &mut samples[start..i];
+        match (fill, before, after) {
+            (BlankerFill::Zero, ..) => hole.fill(0.0),
+            (BlankerFill::Interpolate, Some(a), Some(b)) => {
+                let steps = (hole.len() + 1) as f32;
+                for (k, x) in hole.iter_mut().enumerate() {
+                    *x = a + (b - a) * (k + 1) as f32 / steps;
+                }
+            }
+            (_, Some(a), _) => hole.fill(a),
+            (_, None, Some(b)) => hole.fill(b),
+            (_, None, None) => hole.fill(0.0),
+        }
+    }
+}
+
+/// Период тона в конце `history`, сэмплов: сдвиг в пределах периодов тонов SSTV с
+/// наибольшей нормированной автокорреляцией последних [`PERIOD_WINDOW_MS`]. `None`, если
+/// истории мало или она не похожа на тон
+fn tone_period(history: &[f32], sample_rate: u32) -> Option<usize> {
+    let fs = sample_rate as f32;
+    let shortest = (fs / PERIOD_MAX_HZ).floor().max(1.0) as usize;
+    let longest = (fs / PERIOD_MIN_HZ).ceil() as usize;
+    let window = (PERIOD_WINDOW_MS / 1000.0 * fs) as usize;
+    if history.len() < window + longest {
+        return None;
+    }
+    let tail = &history[history.len() - window..];
+    let energy: f32 = tail.iter().map(|x| x * x).sum();
+    let (period, correlation) = (shortest..=longest)
+        .map(|lag| {
+            let earlier = &history[history.len() - window - lag..history.len() - lag];
+            let dot: f32 = tail.iter().zip(earlier).map(|(a, b)| a * b).sum();
+            let norm = (energy * earlier.iter().map(|x| x * x).sum::<f32>()).sqrt();
+            (lag, if norm > 0.0 { dot / norm } else { 0.0 })
+        })
+        .max_by(|a, b| a.1.total_cmp(&b.1))?;
+    (correlation >= PERIOD_MIN_CORRELATION).then_some(period)
+}
+
+#[allow(deprecated)]
+impl ChannelEffect for BlankerProcessor {
+    fn name(&self) -> &'static str {
+        messages::blanker()
+    }
+
+    fn mix(&self) -> EffectMix {
+        self.params.mix
+    }
+
+    fn apply(&self, buffer: &mut SampleBuffer, _ctx: &EffectContext) -> Result<()> {
+        self.apply_blanker(buffer);
+        Ok(())
+    }
+
+    fn is_enabled(&self) -> bool {
+        BlankerProcessor::is_enabled(self)
+    }
+
+    fn describe(&self) -> String {
+        BlankerProcessor::describe(self)
+    }
+
+    fn sync_params(&mut self, params: &ProcessingParams) {
+        self.params = params.blanker.clone();
+    }
+}
@@ -0,0 +1,116 @@
+use crate::error::{Result, SstvError};
+use crate::messages;
+use crate::quality::QualityTier;
+
+/// Сэмплы вместе с частотой дискретизации и числом каналов. Эффекты и ввод-вывод
+/// получают частоту из самого буфера, поэтому задержки, фильтры и огибающие не могут
+/// незаметно посчитаться по чужой частоте. Многоканальные данные перемежаются
+/// по кадрам; эффекты канала работают с моно
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SampleBuffer {
+    /// Сэмплы в диапазоне −1…1; эффекты меняют их на месте, не меняя длину. Между
+    /// эффектами сумма может выходить за диапазон, его восстанавливает выходной каскад
+    pub data: Vec<f32>,
+    /// Частота дискретизации, Гц
+    pub rate: u32,
+    /// Число перемежающихся каналов
+    pub channels: u16,
+}
+
+impl SampleBuffer {
+    /// Моно-буфер
+    pub fn mono(data: Vec<f32>, rate: u32) -> Self {
+        Self::new(data, rate, 1)
+    }
+
+    pub fn new(data: Vec<f32>, rate: u32, channels: u16) -> Self {
+        Self {
+            data,
+            rate,
+            channels: channels.max(1),
+        }
+    }
+
+    /// Число сэмплов всех каналов
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Число кадров — сэмплов одного канала
+    pub fn frames(&self) -> usize {
+        self.data.len() / self.channels.max(1) as usize
+    }
+
+    /// Средняя мощность: средний квадрат сэмплов всех каналов, 0 для пустого буфера
+    pub fn mean_power(&self) -> f32 {
+        self.data.iter().map(|x| x * x).sum::<f32>() / self.data.len().max(1) as f32
+    }
+
+    /// Длительность, секунд
+    pub fn duration_secs(&self) -> f64 {
+        self.frames() as f64 / self.rate.max(1) as f64
+    }
+
+    /// Сколько кадров длится `ms` миллисекунд при частоте буфера
+    pub fn ms_to_frames(&self, ms: f64) -> usize {
+        (ms / 1000.0 * self.rate as f64).round().max(0.0) as usize
+    }
+
+    /// Буфер, сведённый в моно усреднением каналов
+    pub fn to_mono(&self) -> Self {
+        if self.channels <= 1 {
+            return self.clone();
+        }
+        let channels = self.channels as usize;
+        let data = self
+            .data
+            .chunks_exact(channels)
+            .map(|frame| frame.iter().sum::<f32>() / channels as f32)
+            .collect();
+        Self::mono(data, self.rate)
+    }
+
+    /// Моно-буфер с частотой `rate`; при той же частоте возвращается копия
+    pub fn resampled(&self, rate: u32) -> Self {
+        self.resampled_with(rate, QualityTier::Normal)
+    }
+
+    /// Как [`resampled`](Self::resampled), ядром передискретизации уровня `quality`
+    pub fn resampled_with(&self, rate: u32, quality: QualityTier) -> Self {
+        let mono = self.to_mono();
+        if rate == mono.rate {
+            return mono;
+        }
+        Self::mono(quality.resample(&mono.data, mono.rate, rate), rate)
+    }
+
+    /// Ошибка, если буфер не моно: эффекты канала обрабатывают один канал
+    pub fn check_mono(&self) -> Result<()> {
+        if self.channels == 1 {
+            Ok(())
+        } else {
+            Err(SstvError::invalid(
+                "channels",
+                messages::buffer_not_mono(self.channels),
+            ))
+        }
+    }
+}
+
+impl std::ops::Deref for SampleBuffer {
+    type Target = [f32];
+
+    fn deref(&self) -> &[f32] {
+        &self.data
+    }
+}
+
+impl std::ops::DerefMut for SampleBuffer {
+    fn deref_mut(&mut self) -> &mut [f32] {
+        &mut self.data
+    }
+}
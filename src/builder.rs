@@ -0,0 +1,203 @@
+use crate::blanker::{BlankerParams, BlankerProcessor};
+use crate::deadline::TimeLimits;
+use crate::decoder::DecoderParams;
+use crate::drift::{DriftParams, DriftProcessor};
+use crate::dropout::{DropoutParams, DropoutProcessor};
+use crate::effect::ChannelEffect;
+use crate::encoder::{EncoderParams, LineHook};
+use crate::error::{Result, SstvError};
+use crate::fading::{FadingParams, FadingProcessor};
+use crate::filters::{
+    AgcParams, AgcProcessor, FilterParams, FilterProcessor, NotchParams, NotchProcessor,
+};
+use crate::interference::{InterferenceParams, InterferenceProcessor};
+use crate::messages;
+use crate::modes::{MARTIN_M1, ModeSpec};
+use crate::multipath::{MultipathParams, MultipathProcessor};
+use crate::noise::{NoiseParams, NoiseProcessor};
+use crate::processor::{ProcessingParams, SSTVProcessor, check_params};
+use crate::quality::QualityTier;
+use crate::retarder::{RetarderParams, RetarderProcessor};
+use crate::sink::ImageSink;
+use crate::transceiver::{TransceiverParams, TransceiverProcessor};
+use image::Rgb;
+
+/// Параметры встроенного эффекта канала, которые построитель ставит ступенью конвейера.
+/// Встроенные эффекты перед каждым прогоном берут параметры из [`ProcessingParams`],
+/// поэтому параметры кладутся и туда: два эффекта одного вида работают с последними
+pub trait EffectParams {
+    /// Записывает параметры в `params` и возвращает ступень конвейера
+    fn into_effect(self, params: &mut ProcessingParams) -> Box<dyn ChannelEffect>;
+}
+
+macro_rules! effect_params {
+    ($($params:ty => $field:ident, $processor:ty;)*) => {
+        $(
+            impl EffectParams for $params {
+                fn into_effect(self, params: &mut ProcessingParams) -> Box<dyn ChannelEffect> {
+                    params.$field = self.clone();
+                    Box::new(<$processor>::new_with_params(self))
+                }
+            }
+        )*
+    };
+}
+
+effect_params! {
+    NoiseParams => noise, NoiseProcessor;
+    RetarderParams => retarder, RetarderProcessor;
+    MultipathParams => multipath, MultipathProcessor;
+    FadingParams => fading, FadingProcessor;
+    DriftParams => drift, DriftProcessor;
+    InterferenceParams => interference, InterferenceProcessor;
+    BlankerParams => blanker, BlankerProcessor;
+    FilterParams => filter, FilterProcessor;
+    NotchParams => notch, NotchProcessor;
+    AgcParams => agc, AgcProcessor;
+    DropoutParams => dropout, DropoutProcessor;
+}
+
+/// Построитель [`SSTVProcessor`]: параметры и конвейер задаются цепочкой вызовов и
+/// проверяются один раз в [`build`](Self::build). Без вызовов [`effect`](Self::effect)
+/// конвейер стандартный, см. [`SSTVProcessor::default_effects`]; с ними — ровно из
+/// перечисленных эффектов в порядке вызовов
+#[must_use]
+pub struct SSTVProcessorBuilder {
+    params: ProcessingParams,
+    mode: &'static ModeSpec,
+    effects: Option<Vec<Box<dyn ChannelEffect>>>,
+    /// Тракт трансивера: передатчик встаёт перед явным конвейером, приёмник — после
+    transceiver: bool,
+    line_hook: Option<Box<LineHook>>,
+    image_sink: Option<Box<dyn ImageSink + Send>>,
+    limits: TimeLimits,
+}
+
+impl Default for SSTVProcessorBuilder {
+    fn default() -> Self {
+        Self {
+            params: ProcessingParams::default(),
+            mode: &MARTIN_M1,
+            effects: None,
+            transceiver: false,
+            line_hook: None,
+            image_sink: None,
+            limits: TimeLimits::default(),
+        }
+    }
+}
+
+impl SSTVProcessorBuilder {
+    /// Начинает с готовых параметров, например из пресета
+    pub fn params(mut self, params: ProcessingParams) -> Self {
+        self.params = params;
+        self
+    }
+
+    /// Режим SSTV; поддерживается только [`MARTIN_M1`]
+    pub fn mode(mut self, mode: &'static ModeSpec) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Частота дискретизации выходного сигнала, Гц
+    pub fn sample_rate(mut self, sample_rate: u32) -> Self {
+        self.params.sample_rate = sample_rate;
+        self
+    }
+
+    /// Зерно генератора случайных чисел для воспроизводимых прогонов
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.params.seed = Some(seed);
+        self
+    }
+
+    pub fn quality(mut self, quality: QualityTier) -> Self {
+        self.params.quality = quality;
+        self
+    }
+
+    pub fn encoder(mut self, encoder: EncoderParams) -> Self {
+        self.params.encoder = encoder;
+        self
+    }
+
+    pub fn decoder(mut self, decoder: DecoderParams) -> Self {
+        self.params.decoder = decoder;
+        self
+    }
+
+    /// Добавляет встроенный эффект в конец явного конвейера
+    pub fn effect(mut self, effect: impl EffectParams) -> Self {
+        let stage = effect.into_effect(&mut self.params);
+        self.effects.get_or_insert_with(Vec::new).push(stage);
+        self
+    }
+
+    /// Добавляет сторонний эффект в конец явного конвейера
+    pub fn custom_effect(mut self, effect: impl ChannelEffect + 'static) -> Self {
+        self.effects
+            .get_or_insert_with(Vec::new)
+            .push(Box::new(effect));
+        self
+    }
+
+    /// Тракт SSB-трансивера вокруг явного конвейера; стандартный конвейер уже включает его
+    pub fn transceiver(mut self, transceiver: TransceiverParams) -> Self {
+        self.params.transceiver = transceiver;
+        self.transceiver = true;
+        self
+    }
+
+    /// Обработчик строк основного изображения, см. [`SSTVProcessor::set_line_hook`]
+    pub fn line_hook(mut self, hook: impl FnMut(&mut [Rgb<u8>], usize) + Send + 'static) -> Self {
+        self.line_hook = Some(Box::new(hook));
+        self
+    }
+
+    /// Получатель строк декодированного изображения, см. [`SSTVProcessor::set_image_sink`]
+    pub fn image_sink(mut self, sink: impl ImageSink + Send + 'static) -> Self {
+        self.image_sink = Some(Box::new(sink));
+        self
+    }
+
+    /// Пределы времени прогона, см. [`SSTVProcessor::set_time_limits`]
+    pub fn time_limits(mut self, limits: TimeLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Проверяет режим, частоту дискретизации и временные параметры кодера и строит процессор
+    pub fn build(self) -> Result<SSTVProcessor> {
+        if self.mode.vis_code != MARTIN_M1.vis_code {
+            return Err(SstvError::invalid(
+                "mode",
+                messages::mode_unsupported(self.mode.name, MARTIN_M1.name),
+            ));
+        }
+        check_params(&self.params)?;
+
+        let mut processor = SSTVProcessor::new_with_params(self.params);
+        if let Some(mut effects) = self.effects {
+            if self.transceiver {
+                let transceiver = &processor.params().transceiver;
+                effects.insert(
+                    0,
+                    Box::new(TransceiverProcessor::transmitter(transceiver.clone())),
+                );
+                effects.push(Box::new(TransceiverProcessor::receiver(
+                    transceiver.clone(),
+                )));
+            }
+            *processor.effects_mut() = effects;
+        }
+        if let Some(hook) = self.line_hook {
+            processor.set_line_hook(hook);
+        }
+        if let Some(sink) = self.image_sink {
+            processor.set_image_sink(sink);
+        }
+        processor.set_time_limits(self.limits);
+        Ok(processor)
+    }
+}
@@ -0,0 +1,368 @@
+use crate::effect::{MixSetting, MixTarget};
+use crate::envelope::EnvelopeKind;
+use crate::processor::ProcessingParams;
+
+/// Имя исполняемого файла CLI
+const CLI_NAME: &str = "cli";
+
+/// Пути к файлам, которые не входят в параметры обработки
+#[derive(Clone, Debug, Default)]
+pub struct CommandPaths {
+    pub input: String,
+    pub output: String,
+    pub retarder: Option<String>,
+    /// Изображение мешающей SSTV-передачи
+    pub qrm: Option<String>,
+    /// Файлы пользовательских огибающих шума и ретардера
+    pub noise_env_file: Option<String>,
+    pub ret_env_file: Option<String>,
+    /// Файл сценария событий, из которого прочитаны события
+    pub timeline: Option<String>,
+}
+
+/// Аргументы CLI (без имени программы), воспроизводящие обработку с параметрами `params`.
+/// Флаги, совпадающие со значениями CLI по умолчанию, опускаются. Параметры кодера без
+/// флагов CLI (разрыв лидер-тонов, длительность бита VIS, амплитуда) не передаются
+pub fn cli_args(params: &ProcessingParams, paths: &CommandPaths) -> Vec<String> {
+    let defaults = ProcessingParams::default();
+    let mut args = Args::default();
+
+    args.value("-i", &paths.input);
+    args.value("-o", &paths.output);
+
+    // ── Шум ──
+    let (noise, d) = (&params.noise, &defaults.noise);
+    args.changed("-n", noise.level, d.level);
+    args.changed_enum("--noise-kind", &noise.kind, &d.kind);
+    args.changed("--noise-color", noise.color, d.color);
+    args.changed("--impulse-rate", noise.impulse.rate_hz, d.impulse.rate_hz);
+    args.changed(
+        "--impulse-ms",
+        noise.impulse.duration_ms,
+        d.impulse.duration_ms,
+    );
+    args.changed_enum(
+        "--impulse-dist",
+        &noise.impulse.amplitude,
+        &d.impulse.amplitude,
+    );
+    envelope(
+        &mut args,
+        "noise",
+        &noise.env,
+        &d.env,
+        &paths.noise_env_file,
+    );
+    args.changed("--noise-repeat", noise.repeat, d.repeat);
+    args.changed_enum("--noise-anchor", &noise.anchor, &d.anchor);
+
+    // ── Ретардер ──
+    let (retarder, d) = (&params.retarder, &defaults.retarder);
+    if let Some(path) = &paths.retarder {
+        args.value("-r", path);
+    }
+    args.changed("--level", retarder.level, d.level);
+    envelope(&mut args, "ret", &retarder.env, &d.env, &paths.ret_env_file);
+    args.changed("--ret-repeat", retarder.repeat, d.repeat);
+    args.changed_enum("--ret-anchor", &retarder.anchor, &d.anchor);
+    args.changed("--delay-ms", retarder.delay_ms, d.delay_ms);
+    args.changed("--ret-source", &retarder.source, &d.source);
+
+    // ── Многолучёвость ──
+    for tap in &params.multipath.taps {
+        args.value(
+            "--tap",
+            format!("{}:{}:{}", tap.delay_ms, tap.gain, tap.phase_deg),
+        );
+    }
+
+    // ── Замирания ──
+    let (fading, d) = (&params.fading, &defaults.fading);
+    args.changed("--fade-depth", fading.depth, d.depth);
+    args.changed("--fade-rate", fading.rate_hz, d.rate_hz);
+    args.changed_enum("--fade-model", &fading.model, &d.model);
+
+    // ── Уход частоты ──
+    let (drift, d) = (&params.drift, &defaults.drift);
+    args.changed("--offset-hz", drift.offset_hz, d.offset_hz);
+    args.changed("--drift-hz-per-s", drift.drift_hz_per_s, d.drift_hz_per_s);
+    args.changed("--wobble-hz", drift.wobble_hz, d.wobble_hz);
+    args.changed("--wobble-rate", drift.wobble_rate_hz, d.wobble_rate_hz);
+
+    // ── Помехи ──
+    for c in &params.interference.carriers {
+        args.value(
+            "--carrier",
+            format!("{}:{}:{}:{}", c.freq_hz, c.level, c.duty, c.period_ms),
+        );
+    }
+    if let (Some(qrm), Some(path)) = (&params.interference.qrm, &paths.qrm) {
+        args.value("--qrm", path);
+        args.value("--qrm-level", qrm.level);
+        args.changed("--qrm-offset-hz", qrm.offset_hz, 0.0);
+        args.changed("--qrm-delay-ms", qrm.delay_ms, 0.0);
+    }
+
+    // ── Трансивер SSB ──
+    let (ssb, d) = (&params.transceiver, &defaults.transceiver);
+    if ssb.enabled {
+        args.flag("--ssb");
+    }
+    args.changed("--ssb-low-hz", ssb.low_hz, d.low_hz);
+    args.changed("--ssb-high-hz", ssb.high_hz, d.high_hz);
+    args.changed("--ssb-drive-db", ssb.drive_db, d.drive_db);
+    args.changed("--ssb-alc-ms", ssb.alc_release_ms, d.alc_release_ms);
+    args.changed("--ssb-carrier", ssb.carrier_leak, d.carrier_leak);
+    args.changed("--ssb-iq-gain-db", ssb.iq_gain_db, d.iq_gain_db);
+    args.changed("--ssb-iq-phase-deg", ssb.iq_phase_deg, d.iq_phase_deg);
+    args.changed_enum("--ssb-sideband", &ssb.sideband, &d.sideband);
+    args.changed_enum("--ssb-output", &ssb.output, &d.output);
+
+    // ── Подавитель помех ──
+    let (blanker, d) = (&params.blanker, &defaults.blanker);
+    if blanker.enabled {
+        args.flag("--blanker");
+    }
+    args.changed("--blanker-threshold", blanker.threshold, d.threshold);
+    args.changed("--blanker-hold-ms", blanker.hold_ms, d.hold_ms);
+    args.changed("--blanker-average-ms", blanker.average_ms, d.average_ms);
+    args.changed_enum("--blanker-fill", &blanker.fill, &d.fill);
+
+    // ── Фильтры и АРУ приёмника ──
+    let (filter, d) = (&params.filter, &defaults.filter);
+    if filter.enabled {
+        args.flag("--filter");
+    }
+    args.changed_enum("--filter-kind", &filter.kind, &d.kind);
+    args.changed("--filter-hz", filter.freq_hz, d.freq_hz);
+    args.changed("--filter-q", filter.q, d.q);
+    args.changed("--filter-stages", filter.stages, d.stages);
+    let (notch, d) = (&params.notch, &defaults.notch);
+    if notch.enabled {
+        args.flag("--notch");
+    }
+    args.changed("--notch-hz", notch.freq_hz, d.freq_hz);
+    args.changed("--notch-q", notch.q, d.q);
+    let (agc, d) = (&params.agc, &defaults.agc);
+    if agc.enabled {
+        args.flag("--agc");
+    }
+    args.changed("--agc-target", agc.target, d.target);
+    args.changed("--agc-attack-ms", agc.attack_ms, d.attack_ms);
+    args.changed("--agc-release-ms", agc.release_ms, d.release_ms);
+    args.changed("--agc-max-gain-db", agc.max_gain_db, d.max_gain_db);
+
+    // ── Выпадения ──
+    let (dropout, d) = (&params.dropout, &defaults.dropout);
+    for w in &dropout.windows {
+        args.value("--dropout", format!("{}:{}", w.start_ms, w.duration_ms));
+    }
+    args.changed("--dropout-rate", dropout.rate_per_min, d.rate_per_min);
+    args.changed("--dropout-ms", dropout.duration_ms, d.duration_ms);
+    args.changed_enum("--dropout-kind", &dropout.kind, &d.kind);
+    args.changed("--dropout-db", dropout.attenuation_db, d.attenuation_db);
+
+    // ── Кодер ──
+    let (encoder, d) = (&params.encoder, &defaults.encoder);
+    args.changed_enum("--encoder", &encoder.backend, &d.backend);
+    if !encoder.header {
+        args.flag("--no-header");
+    }
+    args.changed("--leader-ms", encoder.leader_ms, d.leader_ms);
+    args.changed("--vis-code", encoder.vis_code, d.vis_code);
+    args.changed(
+        "--vis-attenuation-db",
+        encoder.vis_attenuation_db,
+        d.vis_attenuation_db,
+    );
+    args.changed("--vis-flip-bits", encoder.vis_flip_bits, d.vis_flip_bits);
+    args.changed("--timing-scale", encoder.timing_scale, d.timing_scale);
+    if encoder.id.is_enabled() {
+        args.value("--id", &encoder.id.callsign);
+    }
+    args.changed_enum("--id-mode", &encoder.id.mode, &d.id.mode);
+    args.changed("--id-wpm", encoder.id.wpm, d.id.wpm);
+    args.changed("--id-tone-hz", encoder.id.tone_hz, d.id.tone_hz);
+
+    // ── Надпись ──
+    let (overlay, d) = (&params.overlay, &defaults.overlay);
+    for line in &overlay.lines {
+        args.value("--overlay", line);
+    }
+    args.changed("--overlay-x", overlay.x, d.x);
+    args.changed("--overlay-y", overlay.y, d.y);
+    args.changed("--overlay-size", overlay.size, d.size);
+    args.changed("--overlay-color", overlay.color, d.color);
+    if let Some(font) = &overlay.font {
+        args.value("--overlay-font", font);
+    }
+    if overlay.shadow {
+        args.flag("--overlay-shadow");
+    }
+
+    // ── Метаданные ──
+    for (key, value) in &params.metadata.fields {
+        args.value("--meta", format!("{}={}", key, value));
+    }
+    if params.metadata.overlay {
+        args.flag("--meta-overlay");
+    }
+    if !params.encoder.id.text.trim().is_empty() {
+        args.flag("--meta-fsk");
+    }
+
+    // ── Декодер ──
+    let (decoder, d) = (&params.decoder, &defaults.decoder);
+    args.changed_enum("--decoder", &decoder.backend, &d.backend);
+    args.changed("--sync-threshold", decoder.sync_threshold, d.sync_threshold);
+    args.changed(
+        "--search-window-ms",
+        decoder.search_window_ms,
+        d.search_window_ms,
+    );
+    args.changed(
+        "--max-deviation-hz",
+        decoder.max_freq_deviation_hz,
+        d.max_freq_deviation_hz,
+    );
+    if decoder.afc {
+        args.flag("--afc");
+    }
+    if decoder.conceal {
+        args.flag("--conceal");
+    }
+    if decoder.deskew {
+        args.flag("--deskew");
+    }
+    if let Some(ppm) = decoder.slant_ppm {
+        args.value("--slant", ppm);
+    }
+    args.changed_enum("--palette", &decoder.palette, &d.palette);
+    args.changed("--blur", decoder.converter.blur, d.converter.blur);
+    args.changed(
+        "--scanlines",
+        decoder.converter.scanlines,
+        d.converter.scanlines,
+    );
+    args.changed_enum(
+        "--phosphor",
+        &decoder.converter.phosphor,
+        &d.converter.phosphor,
+    );
+    args.changed("--phosphor-tint", decoder.converter.tint, d.converter.tint);
+
+    // События, как и пользовательскую огибающую, выражает только файл сценария
+    if !params.events.is_empty()
+        && let Some(path) = &paths.timeline
+    {
+        args.value("--timeline", path);
+    }
+
+    args.changed_enum("--convolution", &params.convolution, &defaults.convolution);
+    if params.parallel {
+        args.flag("--parallel");
+    }
+    args.changed_enum("--quality", &params.quality, &defaults.quality);
+    args.changed_enum("--sanitize", &params.sanitize, &defaults.sanitize);
+    args.changed_enum(
+        "--metric-space",
+        &params.metric_space,
+        &defaults.metric_space,
+    );
+    args.changed("--sample-rate", params.sample_rate, defaults.sample_rate);
+    args.changed(
+        "--output-gain-db",
+        params.output_gain_db,
+        defaults.output_gain_db,
+    );
+    let (loudness, d) = (&params.loudness, &defaults.loudness);
+    args.changed_enum("--normalize", &loudness.measure, &d.measure);
+    args.changed("--normalize-target", loudness.target_db, d.target_db);
+    args.changed("--true-peak-db", loudness.true_peak_db, d.true_peak_db);
+    for &target in MixTarget::ALL {
+        let mix = params.mix(target);
+        if !mix.is_identity() {
+            args.value("--mix", MixSetting { target, mix });
+        }
+    }
+
+    if let Some(seed) = params.seed {
+        args.value("--seed", seed);
+    }
+
+    args.0
+}
+
+/// Флаги огибающей `--<prefix>-env` и её формы. ADSR и пачки передаются формой целиком,
+/// пользовательская огибающая — файлом, из которого она прочитана; без файла кривую в
+/// командной строке не выразить, и флаг опускается
+fn envelope(
+    args: &mut Args,
+    prefix: &str,
+    env: &EnvelopeKind,
+    default: &EnvelopeKind,
+    file: &Option<String>,
+) {
+    match env {
+        EnvelopeKind::Adsr(adsr) => args.value(&format!("--{}-adsr", prefix), adsr),
+        EnvelopeKind::Burst(burst) => args.value(&format!("--{}-burst", prefix), burst),
+        EnvelopeKind::Custom(_) => {
+            if let Some(path) = file {
+                args.value(&format!("--{}-env-file", prefix), path);
+            }
+        }
+        _ => args.changed_enum(&format!("--{}-env", prefix), env, default),
+    }
+}
+
+/// Строка вызова CLI для POSIX-оболочки, см. [`cli_args`]
+pub fn cli_command(params: &ProcessingParams, paths: &CommandPaths) -> String {
+    std::iter::once(CLI_NAME.to_string())
+        .chain(cli_args(params, paths).iter().map(|a| shell_quote(a)))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Экранирует аргумент для POSIX-оболочки
+pub fn shell_quote(arg: &str) -> String {
+    let safe = |c: char| c.is_ascii_alphanumeric() || "-_./:=,+@".contains(c);
+    if !arg.is_empty() && arg.chars().all(safe) {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', "'\\''"))
+    }
+}
+
+#[derive(Default)]
+struct Args(Vec<String>);
+
+impl Args {
+    fn flag(&mut self, flag: &str) {
+        self.0.push(flag.to_string());
+    }
+
+    /// Отрицательные значения передаются через '=', иначе clap примет их за флаги
+    fn value(&mut self, flag: &str, value: impl ToString) {
+        let value = value.to_string();
+        if value.starts_with('-') && flag.starts_with("--") {
+            self.0.push(format!("{}={}", flag, value));
+        } else {
+            self.0.push(flag.to_string());
+            self.0.push(value);
+        }
+    }
+
+    fn changed<T: PartialEq + ToString>(&mut self, flag: &str, value: T, default: T) {
+        if value != default {
+            self.value(flag, value);
+        }
+    }
+
+    fn changed_enum<T: PartialEq + clap::ValueEnum>(&mut self, flag: &str, value: &T, default: &T) {
+        if value != default
+            && let Some(v) = value.to_possible_value()
+        {
+            self.value(flag, v.get_name());
+        }
+    }
+}
@@ -0,0 +1,92 @@
+use image::{Rgb, RgbImage};
+
+/// Уверенность синхронизации, ниже которой строка считается пропущенной
+const SYNC_MISS_CONFIDENCE: f32 = 0.1;
+/// Во сколько раз отличие строки от соседей должно превышать медианное по изображению
+const OUTLIER_RATIO: f32 = 4.0;
+/// Отличие от соседей, ниже которого строка не считается испорченной, уровни 0–255
+const OUTLIER_MIN_LEVEL: f32 = 24.0;
+
+/// Маскировка испорченных строк после декодирования: строки с пропущенным синхроимпульсом
+/// или с неправдоподобным отличием от обеих соседних заменяются интерполяцией между
+/// ближайшими целыми строками. `confidence` — уверенность синхронизации по строкам, если
+/// декодер её даёт. Возвращает номера замаскированных строк
+pub fn conceal_lines(image: &mut RgbImage, confidence: Option<&[f32]>) -> Vec<u32> {
+    let height = image.height();
+    if height < 3 {
+        return Vec::new();
+    }
+
+    let outlier = neighbour_difference(image);
+    let mut sorted = outlier.clone();
+    sorted.sort_by(f32::total_cmp);
+    let threshold = (sorted[sorted.len() / 2] * OUTLIER_RATIO).max(OUTLIER_MIN_LEVEL);
+
+    let bad: Vec<bool> = (0..height as usize)
+        .map(|y| {
+            let sync_miss = confidence
+                .and_then(|c| c.get(y))
+                .is_some_and(|&c| c < SYNC_MISS_CONFIDENCE);
+            sync_miss || outlier[y] > threshold
+        })
+        .collect();
+
+    // Если испорчено всё, интерполировать не из чего
+    let concealed: Vec<u32> = (0..height).filter(|&y| bad[y as usize]).collect();
+    if concealed.len() == height as usize {
+        return Vec::new();
+    }
+
+    let source = image.clone();
+    for &y in &concealed {
+        let above = (0..y).rev().find(|&a| !bad[a as usize]);
+        let below = (y + 1..height).find(|&b| !bad[b as usize]);
+        for x in 0..image.width() {
+            let pixel = match (above, below) {
+                (Some(a), Some(b)) => {
+                    let t = (y - a) as f32 / (b - a) as f32;
+                    let (pa, pb) = (source.get_pixel(x, a), source.get_pixel(x, b));
+                    Rgb(std::array::from_fn(|c| {
+                        (pa[c] as f32 + (pb[c] as f32 - pa[c] as f32) * t).round() as u8
+                    }))
+                }
+                (Some(n), None) | (None, Some(n)) => *source.get_pixel(x, n),
+                (None, None) => unreachable!("есть хотя бы одна целая строка"),
+            };
+            image.put_pixel(x, y, pixel);
+        }
+    }
+
+    concealed
+}
+
+/// Для каждой строки — среднее по пикселям меньшее из отличий от строки выше и ниже.
+/// Граница в изображении совпадает с одной из соседних строк и даёт малое значение,
+/// строка, испорченная помехой, отличается от обеих
+fn neighbour_difference(image: &RgbImage) -> Vec<f32> {
+    let (width, height) = image.dimensions();
+    let diff = |p: &Rgb<u8>, q: &Rgb<u8>| -> f32 {
+        (0..3)
+            .map(|c| (p[c] as f32 - q[c] as f32).abs())
+            .sum::<f32>()
+            / 3.0
+    };
+
+    (0..height)
+        .map(|y| {
+            let total: f32 = (0..width)
+                .map(|x| {
+                    let p = image.get_pixel(x, y);
+                    let up = (y > 0).then(|| diff(p, image.get_pixel(x, y - 1)));
+                    let down = (y + 1 < height).then(|| diff(p, image.get_pixel(x, y + 1)));
+                    match (up, down) {
+                        (Some(u), Some(d)) => u.min(d),
+                        (Some(v), None) | (None, Some(v)) => v,
+                        (None, None) => 0.0,
+                    }
+                })
+                .sum();
+            total / width.max(1) as f32
+        })
+        .collect()
+}
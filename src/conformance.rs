@@ -0,0 +1,584 @@
+use crate::dsp::{self, ConvolutionBackend};
+use crate::encoder::{self, EncoderBackend, EncoderParams};
+use crate::error::{Result, SstvError};
+use crate::messages;
+use crate::modes::{
+    BLACK_HZ, BREAK_MS, LEADER_HZ, LEADER_MS, MARTIN_M1, ModeSpec, SYNC_HZ, VIS_BIT_MS, VIS_ONE_HZ,
+    VIS_ZERO_HZ, WHITE_HZ,
+};
+use image::{DynamicImage, Rgb, RgbImage};
+use std::f64::consts::PI;
+
+/// Режимы, которые умеет кодер; для каждого в корпусе есть эталонный сигнал
+pub const MODES: &[&ModeSpec] = &[&MARTIN_M1];
+
+/// Вертикальных полос тестовой таблицы
+const BARS: u32 = 8;
+/// Окно сглаживания мгновенной частоты, мкс
+const SMOOTH_US: f64 = 1000.0;
+/// Отступ от границ тона при измерении его частоты, мкс: сглаживание и фильтр Гильберта
+/// размазывают переход примерно на столько. Более короткие тоны (площадка, разделители)
+/// проверяются только по границам
+const GUARD_US: f64 = 2000.0;
+/// Наименьший скачок частоты на границе тонов, по которому она измеряется, Гц
+const MIN_STEP_HZ: f32 = 100.0;
+/// Окно поиска границы тона вокруг ожидаемого положения, мкс
+const EDGE_SEARCH_US: f64 = 3000.0;
+/// Окно поиска конца синхроимпульса строки вокруг предсказанного, доля строки
+const LINE_SEARCH: f64 = 0.02;
+/// Окно поиска конца синхроимпульса первой строки после заголовка, мс: два бита VIS
+const HEADER_SEARCH_MS: u32 = 2 * VIS_BIT_MS;
+/// Лидер-тон, после которого ищется разрыв, должен длиться хотя бы столько, мкс
+const MIN_LEADER_US: f64 = 100_000.0;
+/// Отклонение частоты, при котором сэмпл ещё считается лидер-тоном, Гц
+const LEADER_BAND_HZ: f32 = 50.0;
+/// Порог огибающей, ниже которого сигнал считается тишиной, доля пика
+const SILENCE_RATIO: f32 = 0.1;
+
+/// Допуски проверки соответствия
+#[derive(Clone, Debug)]
+pub struct ConformanceParams {
+    /// Отклонение частоты тона от спецификации, Гц
+    pub freq_tolerance_hz: f64,
+    /// Смещение границы тона от положения по спецификации, мкс
+    pub edge_tolerance_us: f64,
+    /// Отклонение периода строки, ppm
+    pub period_tolerance_ppm: f64,
+}
+
+impl Default for ConformanceParams {
+    fn default() -> Self {
+        Self {
+            freq_tolerance_hz: 10.0,
+            edge_tolerance_us: 50.0,
+            period_tolerance_ppm: 50.0,
+        }
+    }
+}
+
+/// Одна проверка: все измерения одной величины и их отклонения от спецификации
+#[derive(Clone, Debug)]
+pub struct Check {
+    pub name: &'static str,
+    pub unit: &'static str,
+    /// Допустимое отклонение (по модулю)
+    pub tolerance: f64,
+    /// Число измерений
+    pub count: usize,
+    /// Среднее отклонение
+    pub mean_deviation: f64,
+    /// Наибольшее по модулю отклонение, со знаком
+    pub worst_deviation: f64,
+}
+
+impl Check {
+    fn new(name: &'static str, unit: &'static str, tolerance: f64) -> Self {
+        Self {
+            name,
+            unit,
+            tolerance,
+            count: 0,
+            mean_deviation: 0.0,
+            worst_deviation: 0.0,
+        }
+    }
+
+    fn add(&mut self, deviation: f64) {
+        self.count += 1;
+        self.mean_deviation += (deviation - self.mean_deviation) / self.count as f64;
+        if deviation.abs() > self.worst_deviation.abs() {
+            self.worst_deviation = deviation;
+        }
+    }
+
+    /// Проверка пройдена: измерения есть, и все в пределах допуска
+    pub fn passed(&self) -> bool {
+        self.count > 0 && self.worst_deviation.abs() <= self.tolerance
+    }
+
+    /// Возвращает описание проверки
+    pub fn describe(&self) -> String {
+        let mark = if self.passed() { "✓" } else { "✗" };
+        if self.count == 0 {
+            return messages::check_unmeasured(mark, self.name);
+        }
+        let with_unit = |value: String| match self.unit {
+            "" => value,
+            unit => format!("{} {}", value, unit),
+        };
+        messages::check_describe(
+            mark,
+            self.name,
+            self.count,
+            &with_unit(format!("{:+.2}", self.mean_deviation)),
+            &with_unit(format!("{:+.2}", self.worst_deviation)),
+            &with_unit(self.tolerance.to_string()),
+        )
+    }
+}
+
+/// Итог проверки сигнала тестовой таблицы на соответствие спецификации режима
+#[derive(Clone, Debug)]
+pub struct ConformanceReport {
+    pub mode: &'static str,
+    pub checks: Vec<Check>,
+}
+
+impl ConformanceReport {
+    /// Все проверки пройдены
+    pub fn passed(&self) -> bool {
+        self.checks.iter().all(Check::passed)
+    }
+
+    /// Непройденные проверки
+    pub fn failures(&self) -> impl Iterator<Item = &Check> {
+        self.checks.iter().filter(|c| !c.passed())
+    }
+}
+
+/// Тестовая таблица режима: [`BARS`] вертикальных полос, в каждом канале свой рисунок —
+/// красный нарастает, зелёный спадает, синий чередует чёрный и белый. Соседние
+/// внутренние полосы различаются во всех каналах, поэтому граница каждой видна по
+/// частоте, а порядок каналов — по уровням. Крайние полосы чёрные: короткие площадка
+/// и разделители оказываются между длинными тонами 1500 Гц, и соседние яркие пиксели
+/// не сдвигают измеренные границы синхроимпульса
+pub fn test_pattern(spec: &ModeSpec) -> DynamicImage {
+    let image = RgbImage::from_fn(spec.width, spec.height, |x, _| {
+        let bar = bar_at(x, spec.width);
+        Rgb([0, 1, 2].map(|c| bar_level(c, bar)))
+    });
+    DynamicImage::ImageRgb8(image)
+}
+
+/// Эталонный сигнал режима: тестовая таблица, закодированная `backend` со стандартным
+/// заголовком
+pub fn reference_signal(spec: &ModeSpec, backend: EncoderBackend) -> Vec<f32> {
+    let params = EncoderParams {
+        backend,
+        vis_code: spec.vis_code,
+        ..Default::default()
+    };
+    encoder::encode(&test_pattern(spec), &params)
+}
+
+/// Имя файла эталонного сигнала режима в каталоге корпуса
+pub fn golden_file_name(spec: &ModeSpec) -> String {
+    format!("{}.wav", spec.name.to_lowercase().replace(' ', "_"))
+}
+
+/// Режим по коду VIS в заголовке сигнала
+pub fn detect_mode(samples: &[f32], sample_rate: u32) -> Result<&'static ModeSpec> {
+    let track = Track::new(samples, sample_rate);
+    let Some(break_start) = track.find_break() else {
+        return Err(SstvError::DecodeNotFound(
+            messages::check_no_header().into(),
+        ));
+    };
+    let (code, _) = track.read_vis(break_start);
+    match MODES.iter().find(|m| m.vis_code == code) {
+        Some(spec) => Ok(spec),
+        None => Err(SstvError::DecodeNotFound(messages::check_unknown_vis(code))),
+    }
+}
+
+/// Проверяет сигнал тестовой таблицы [`test_pattern`] режима `spec`: частоты лидер-тона,
+/// синхроимпульсов, бит VIS и яркости пикселей, код VIS, длительности тонов заголовка
+/// и строк по их границам и период строки. Границы строки меряются от конца её
+/// синхроимпульса, поэтому ошибка периода не накапливается в них и видна отдельно
+pub fn check(
+    samples: &[f32],
+    sample_rate: u32,
+    spec: &ModeSpec,
+    params: &ConformanceParams,
+) -> Result<ConformanceReport> {
+    let track = Track::new(samples, sample_rate);
+    let Some(break_start) = track.find_break() else {
+        return Err(SstvError::DecodeNotFound(
+            messages::check_no_header().into(),
+        ));
+    };
+
+    let freq = |name| Check::new(name, messages::unit_hz(), params.freq_tolerance_hz);
+    let edge = |name| Check::new(name, messages::unit_us(), params.edge_tolerance_us);
+    let mut leader_freq = freq(messages::check_leader_freq());
+    let mut header_sync_freq = freq(messages::check_header_sync_freq());
+    let mut vis_freq = freq(messages::check_vis_freq());
+    let mut vis_code = Check::new(messages::check_vis_code(), "", 0.0);
+    let mut leader_len = edge(messages::check_leader_len());
+    let mut header_edges = edge(messages::check_header_edges());
+    let mut sync_freq = freq(messages::check_sync_freq());
+    let mut sync_len = edge(messages::check_sync_len());
+    let mut pixel_freq = freq(messages::check_pixel_freq());
+    let mut line_edges = edge(messages::check_line_edges());
+    let mut period = Check::new(messages::check_period(), "ppm", params.period_tolerance_ppm);
+    let mut lines = Check::new(messages::check_lines(), "", 0.0);
+
+    // Заголовок от начала разрыва: разрыв, второй лидер-тон, стартовый бит, биты VIS
+    // и стоповый бит, слитый с синхроимпульсом первой строки
+    let (code, parity_ok) = track.read_vis(break_start);
+    vis_code.add(code as f64 - spec.vis_code as f64);
+    vis_code.add(if parity_ok { 0.0 } else { 1.0 });
+    let ms = |ms: u32| ms as f64 * 1000.0;
+    let mut header = vec![
+        (SYNC_HZ, ms(BREAK_MS)),
+        (LEADER_HZ, ms(LEADER_MS)),
+        (SYNC_HZ, ms(VIS_BIT_MS)),
+    ];
+    header.extend(vis_bits(spec.vis_code).map(|hz| (hz, ms(VIS_BIT_MS))));
+    header.push((SYNC_HZ, ms(VIS_BIT_MS) + spec.sync_us));
+
+    let leader_end = break_start;
+    let leader_start = track.leader_start(leader_end);
+    leader_len.add(track.us(leader_end - leader_start) - ms(LEADER_MS));
+    leader_freq.add(track.mean_hz(leader_start, leader_end) - LEADER_HZ as f64);
+
+    // Начало разрыва — точка отсчёта, поэтому его граница не меряется
+    let mut last = SYNC_HZ;
+    let mut at_us = 0.0;
+    for &(hz, duration) in &header {
+        let expected = break_start + track.samples(at_us);
+        let from = track
+            .edge(last, hz, expected)
+            .inspect(|&found| header_edges.add(track.us(found - expected)))
+            .unwrap_or(expected);
+        let deviation = track.mean_hz(from, from + track.samples(duration)) - hz as f64;
+        match hz {
+            LEADER_HZ => leader_freq.add(deviation),
+            SYNC_HZ => header_sync_freq.add(deviation),
+            _ => vis_freq.add(deviation),
+        }
+        last = hz;
+        at_us += duration;
+    }
+
+    // Строки: конец синхроимпульса первой — последняя граница заголовка
+    let line_samples = track.samples(spec.line_us());
+    let body = line_body(spec);
+    let mut sync_ends: Vec<f64> = Vec::new();
+    let mut predicted = break_start + track.samples(at_us);
+    for _ in 0..spec.height {
+        // Первую строку ищем шире: заголовок с лишним или пропущенным битом VIS не
+        // должен мешать проверить строки
+        let tolerance = if sync_ends.is_empty() {
+            track.samples(HEADER_SEARCH_MS as f64 * 1000.0)
+        } else {
+            line_samples * LINE_SEARCH
+        };
+        let Some(sync_end) =
+            track.crossing(predicted, tolerance, midpoint(SYNC_HZ, BLACK_HZ), true)
+        else {
+            break;
+        };
+        if let Some(&previous) = sync_ends.last() {
+            // Начало синхроимпульса: переход с разделителя прошлой строки
+            let expected = sync_end - track.samples(spec.sync_us);
+            if let Some(start) = track.edge(BLACK_HZ, SYNC_HZ, expected) {
+                sync_len.add(track.us(sync_end - start) - spec.sync_us);
+                sync_freq.add(track.mean_hz(start, sync_end) - SYNC_HZ as f64);
+            }
+            predicted = sync_end + (sync_end - previous);
+        } else {
+            // Конец заголовка: переход со стопового бита и синхроимпульса на площадку
+            header_edges.add(track.us(sync_end - predicted));
+            predicted = sync_end + line_samples;
+        }
+        sync_ends.push(sync_end);
+
+        let mut last = SYNC_HZ;
+        let mut at_us = 0.0;
+        for &(hz, duration, is_pixel) in &body {
+            let expected = sync_end + track.samples(at_us);
+            let from = track
+                .edge(last, hz, expected)
+                .inspect(|&found| line_edges.add(track.us(found - expected)))
+                .unwrap_or(expected);
+            if is_pixel {
+                let to = from + track.samples(duration);
+                pixel_freq.add(track.mean_hz(from, to) - hz as f64);
+            }
+            last = hz;
+            at_us += duration;
+        }
+    }
+
+    lines.add(sync_ends.len() as f64 - spec.height as f64);
+    if sync_ends.len() >= 2 {
+        // Наклон прямой по концам синхроимпульсов, МНК
+        let n = sync_ends.len() as f64;
+        let mean_y = (n - 1.0) / 2.0;
+        let mean_t = sync_ends.iter().sum::<f64>() / n;
+        let (mut cov, mut var) = (0.0, 0.0);
+        for (y, &t) in sync_ends.iter().enumerate() {
+            cov += (y as f64 - mean_y) * (t - mean_t);
+            var += (y as f64 - mean_y).powi(2);
+        }
+        period.add((track.us(cov / var) / spec.line_us() - 1.0) * 1e6);
+    }
+
+    Ok(ConformanceReport {
+        mode: spec.name,
+        checks: vec![
+            leader_freq,
+            header_sync_freq,
+            vis_freq,
+            vis_code,
+            leader_len,
+            header_edges,
+            sync_freq,
+            sync_len,
+            pixel_freq,
+            line_edges,
+            period,
+            lines,
+        ],
+    })
+}
+
+/// Частоты восьми бит VIS (семь бит кода младшим первым и чётность)
+fn vis_bits(code: u8) -> impl Iterator<Item = f32> {
+    let code = code & 0x7F;
+    let bits = code | ((code.count_ones() % 2) as u8) << 7;
+    (0..8).map(move |bit| {
+        if (bits >> bit) & 1 == 1 {
+            VIS_ONE_HZ
+        } else {
+            VIS_ZERO_HZ
+        }
+    })
+}
+
+/// Тоны строки тестовой таблицы после синхроимпульса: частота, длительность в мкс и
+/// признак пикселя
+fn line_body(spec: &ModeSpec) -> Vec<(f32, f64, bool)> {
+    let mut tones = vec![(BLACK_HZ, spec.porch_us, false)];
+    for &channel in &spec.channel_order {
+        for bar in 0..BARS {
+            let pixels = (0..spec.width)
+                .filter(|&x| bar_at(x, spec.width) == bar)
+                .count();
+            let level = bar_level(channel, bar) as f32 / 255.0;
+            tones.push((
+                BLACK_HZ + level * (WHITE_HZ - BLACK_HZ),
+                pixels as f64 * spec.pixel_us,
+                true,
+            ));
+        }
+        tones.push((BLACK_HZ, spec.separator_us, false));
+    }
+    tones
+}
+
+/// Полоса тестовой таблицы, в которую попадает столбец `x`
+fn bar_at(x: u32, width: u32) -> u32 {
+    x * BARS / width
+}
+
+/// Уровень канала `channel` (R=0, G=1, B=2) в полосе `bar` тестовой таблицы
+fn bar_level(channel: usize, bar: u32) -> u8 {
+    if bar == 0 || bar == BARS - 1 {
+        return 0;
+    }
+    let ramp = (bar * 255 / (BARS - 1)) as u8;
+    match channel {
+        0 => ramp,
+        1 => 255 - ramp,
+        _ => {
+            if bar % 2 == 1 {
+                255
+            } else {
+                0
+            }
+        }
+    }
+}
+
+fn midpoint(a: f32, b: f32) -> f32 {
+    (a + b) / 2.0
+}
+
+/// Сглаженная мгновенная частота и огибающая сигнала
+struct Track {
+    sample_rate: u32,
+    /// Префиксные суммы мгновенной частоты для средних по отрезкам
+    sum: Vec<f64>,
+    /// Мгновенная частота, сглаженная окном [`SMOOTH_US`]
+    smooth: Vec<f32>,
+    envelope: Vec<f32>,
+    /// Порог тишины по огибающей
+    silence: f32,
+}
+
+impl Track {
+    fn new(samples: &[f32], sample_rate: u32) -> Self {
+        let analytic = dsp::analytic_signal(samples, ConvolutionBackend::Auto);
+        let envelope: Vec<f32> = analytic.iter().map(|&(x, h)| x.hypot(h)).collect();
+        let to_hz = sample_rate as f64 / (2.0 * PI);
+        let mut freq = vec![0.0f64; analytic.len()];
+        for n in 1..analytic.len() {
+            let ((re, im), (pre, pim)) = (analytic[n], analytic[n - 1]);
+            let dot = (re * pre + im * pim) as f64;
+            let cross = (im * pre - re * pim) as f64;
+            freq[n] = cross.atan2(dot) * to_hz;
+        }
+        if freq.len() > 1 {
+            freq[0] = freq[1];
+        }
+
+        let mut acc = 0.0;
+        let sum: Vec<f64> = std::iter::once(0.0)
+            .chain(freq.iter().map(|&v| {
+                acc += v;
+                acc
+            }))
+            .collect();
+        let half = (SMOOTH_US * 1e-6 * sample_rate as f64 / 2.0).round() as usize;
+        let smooth = (0..freq.len())
+            .map(|n| {
+                let (from, to) = (n.saturating_sub(half), (n + half + 1).min(freq.len()));
+                ((sum[to] - sum[from]) / (to - from) as f64) as f32
+            })
+            .collect();
+        let peak = envelope.iter().fold(0.0f32, |m, &v| m.max(v));
+
+        Self {
+            sample_rate,
+            sum,
+            smooth,
+            envelope,
+            silence: peak * SILENCE_RATIO,
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.smooth.len()
+    }
+
+    /// Длительность `samples` сэмплов, мкс
+    fn us(&self, samples: f64) -> f64 {
+        samples / self.sample_rate as f64 * 1e6
+    }
+
+    /// Сэмплов в `us` микросекундах
+    fn samples(&self, us: f64) -> f64 {
+        us * 1e-6 * self.sample_rate as f64
+    }
+
+    /// Средняя мгновенная частота тона на [from, to) без отступов [`GUARD_US`] от краёв, Гц;
+    /// у коротких тонов — в середине
+    fn mean_hz(&self, from: f64, to: f64) -> f64 {
+        let guard = self.samples(GUARD_US);
+        let (from, to) = if to - from > 2.0 * guard {
+            (from + guard, to - guard)
+        } else {
+            let middle = (from + to) / 2.0;
+            (middle, middle + 1.0)
+        };
+        let to = (to.round() as usize).min(self.len());
+        let from = (from.round() as usize).min(to.saturating_sub(1));
+        if to <= from {
+            return f64::NAN;
+        }
+        (self.sum[to] - self.sum[from]) / (to - from) as f64
+    }
+
+    /// Граница между тонами `from_hz` и `to_hz`, ближайшая к `expected`; `None`, если
+    /// скачок частоты слишком мал, чтобы его мерить, или граница не найдена
+    fn edge(&self, from_hz: f32, to_hz: f32, expected: f64) -> Option<f64> {
+        if (to_hz - from_hz).abs() < MIN_STEP_HZ {
+            return None;
+        }
+        self.crossing(
+            expected,
+            self.samples(EDGE_SEARCH_US),
+            midpoint(from_hz, to_hz),
+            to_hz > from_hz,
+        )
+    }
+
+    /// Пересечение сглаженной частотой уровня `level` в нужную сторону, ближайшее
+    /// к `around` в пределах ±`window` сэмплов, с точностью до доли сэмпла
+    fn crossing(&self, around: f64, window: f64, level: f32, rising: bool) -> Option<f64> {
+        let from = ((around - window).max(1.0) as usize).min(self.len());
+        let to = ((around + window).max(0.0) as usize + 1).min(self.len());
+        (from..to)
+            .filter_map(|n| {
+                let (a, b) = (self.smooth[n - 1], self.smooth[n]);
+                let crosses = if rising {
+                    a < level && b >= level
+                } else {
+                    a >= level && b < level
+                };
+                crosses.then(|| (n - 1) as f64 + ((level - a) / (b - a)) as f64)
+            })
+            .min_by(|x, y| (x - around).abs().total_cmp(&(y - around).abs()))
+    }
+
+    /// Начало разрыва после первого лидер-тона заголовка
+    fn find_break(&self) -> Option<f64> {
+        let need = self.samples(MIN_LEADER_US) as usize;
+        let window = self.samples(EDGE_SEARCH_US) as usize;
+        let level = midpoint(LEADER_HZ, SYNC_HZ);
+        let mut run = 0;
+        let mut armed_until = None;
+        for n in 1..self.len() {
+            if (self.smooth[n] - LEADER_HZ).abs() < LEADER_BAND_HZ
+                && self.envelope[n] > self.silence
+            {
+                run += 1;
+                continue;
+            }
+            if run >= need {
+                armed_until = Some(n + window);
+            }
+            run = 0;
+            match armed_until {
+                Some(limit) if n <= limit => {
+                    let (a, b) = (self.smooth[n - 1], self.smooth[n]);
+                    if a >= level && b < level {
+                        return Some((n - 1) as f64 + ((level - a) / (b - a)) as f64);
+                    }
+                }
+                _ => armed_until = None,
+            }
+        }
+        None
+    }
+
+    /// Начало первого лидер-тона, кончающегося в `end`: по огибающей, если перед ним
+    /// тишина, иначе по границе с предыдущим тоном
+    fn leader_start(&self, end: f64) -> f64 {
+        // Граница с разрывом размыта сглаживанием: начинаем с середины тона
+        let mut n = ((end - self.samples(GUARD_US)).max(0.0) as usize).min(self.len());
+        while n > 0
+            && self.envelope[n - 1] > self.silence
+            && (self.smooth[n - 1] - LEADER_HZ).abs() < 2.0 * MIN_STEP_HZ
+        {
+            n -= 1;
+        }
+        if n == 0 || self.envelope[n - 1] <= self.silence {
+            return n as f64;
+        }
+        // Перед лидер-тоном другой тон: граница — посередине между их частотами
+        let before = self.samples(GUARD_US) as usize;
+        let from = n.saturating_sub(2 * before);
+        let previous = (self.sum[n - before.min(n)] - self.sum[from])
+            / (n - before.min(n) - from).max(1) as f64;
+        self.edge(previous as f32, LEADER_HZ, n as f64)
+            .unwrap_or(n as f64)
+    }
+
+    /// Код VIS и верность чётности по средним частотам бит после разрыва `break_start`
+    fn read_vis(&self, break_start: f64) -> (u8, bool) {
+        let ms = |ms: u32| self.samples(ms as f64 * 1000.0);
+        let first_bit = break_start + ms(BREAK_MS + LEADER_MS + VIS_BIT_MS);
+        let mut bits = 0u8;
+        for bit in 0..8 {
+            let from = first_bit + bit as f64 * ms(VIS_BIT_MS);
+            let hz = self.mean_hz(from, from + ms(VIS_BIT_MS));
+            if hz < SYNC_HZ as f64 {
+                bits |= 1 << bit;
+            }
+        }
+        (bits & 0x7F, bits.count_ones().is_multiple_of(2))
+    }
+}
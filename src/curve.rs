@@ -0,0 +1,454 @@
+use crate::error::{Result, SstvError};
+use crate::messages;
+use crate::modes::MARTIN_M1;
+use crate::noise::level_snr_db;
+use crate::sweep::SweepEntry;
+
+/// Шаг перебора середины логистической кривой, дБ
+const FIT_MID_STEP_DB: f64 = 0.1;
+/// Ширина перехода логистической кривой: от и до, с шагом, дБ
+const FIT_WIDTH_DB: (f64, f64, f64) = (0.25, 10.0, 0.25);
+/// Меньше точек с разным С/Ш кривая не подбирается
+const MIN_FIT_POINTS: usize = 3;
+
+/// Точка кривой: среднее качество всех результатов с одним С/Ш
+#[derive(Clone, Debug, PartialEq)]
+pub struct CurvePoint {
+    pub snr_db: f64,
+    /// Средний PSNR, дБ
+    pub psnr: f64,
+    /// Средний SSIM
+    pub ssim: f64,
+}
+
+/// Логистическая кривая SSIM(С/Ш) = low + (high − low) / (1 + e^−(С/Ш − mid)/width):
+/// «водопадная» кривая модемов, перевёрнутая в качество
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct LogisticFit {
+    /// SSIM при очень плохом сигнале
+    pub low: f64,
+    /// SSIM при очень хорошем сигнале
+    pub high: f64,
+    /// Порог: С/Ш, при котором качество посередине между `low` и `high`, дБ
+    pub mid_db: f64,
+    /// Ширина перехода, дБ: от 27% до 73% пути между `low` и `high` — две ширины
+    pub width_db: f64,
+    /// Среднеквадратичное отклонение точек от кривой
+    pub rmse: f64,
+}
+
+impl LogisticFit {
+    /// SSIM кривой при С/Ш `snr_db`
+    pub fn eval(&self, snr_db: f64) -> f64 {
+        self.low + (self.high - self.low) * logistic((snr_db - self.mid_db) / self.width_db)
+    }
+
+    /// `порог 7.3 дБ, ширина 1.2 дБ, SSIM 0.05–0.97`
+    pub fn describe(&self) -> String {
+        messages::curve_fit(self.mid_db, self.width_db, self.low, self.high)
+    }
+}
+
+/// Кривая качества от С/Ш для одного режима и одного сочетания остальных осей перебора
+#[derive(Clone, Debug)]
+pub struct DegradationCurve {
+    pub mode: &'static str,
+    pub retarder_level: f32,
+    pub delay_ms: u32,
+    /// Точки по возрастанию С/Ш; результаты без шума в кривую не входят
+    pub points: Vec<CurvePoint>,
+    /// `None`, если различных С/Ш меньше трёх
+    pub fit: Option<LogisticFit>,
+}
+
+impl DegradationCurve {
+    /// Кривые по результатам перебора с осью шума: по одной на каждое сочетание уровня и
+    /// задержки ретардера. С/Ш точки — номинальный для её уровня шума, см. [`level_snr_db`]
+    pub fn from_entries(entries: &[SweepEntry]) -> Result<Vec<Self>> {
+        let mut curves: Vec<Self> = Vec::new();
+        for entry in entries.iter().filter(|e| e.noise > 0) {
+            let snr_db = level_snr_db(entry.noise) as f64;
+            let index = match curves.iter().position(|c| {
+                c.retarder_level == entry.retarder_level && c.delay_ms == entry.delay_ms
+            }) {
+                Some(index) => index,
+                None => {
+                    curves.push(Self {
+                        // Кодер передаёт только этот режим
+                        mode: MARTIN_M1.name,
+                        retarder_level: entry.retarder_level,
+                        delay_ms: entry.delay_ms,
+                        points: Vec::new(),
+                        fit: None,
+                    });
+                    curves.len() - 1
+                }
+            };
+            curves[index].points.push(CurvePoint {
+                snr_db,
+                psnr: entry.psnr,
+                ssim: entry.ssim,
+            });
+        }
+        if curves.is_empty() {
+            return Err(SstvError::invalid(
+                "noise_range",
+                messages::curve_no_noise(),
+            ));
+        }
+        for curve in &mut curves {
+            curve.points = average(std::mem::take(&mut curve.points));
+            curve.fit = fit_logistic(&curve.points);
+        }
+        Ok(curves)
+    }
+
+    /// `Martin M1, ретардер 0.00 / 0 мс: порог 7.3 дБ, …`
+    pub fn describe(&self) -> String {
+        let fit = self.fit.as_ref().map_or_else(
+            || messages::curve_no_fit().to_string(),
+            LogisticFit::describe,
+        );
+        messages::curve_describe(self.mode, self.retarder_level, self.delay_ms, &fit)
+    }
+}
+
+/// Кривые в CSV: строка на точку с подобранным SSIM и параметрами кривой
+pub fn curves_csv(curves: &[DegradationCurve]) -> String {
+    let mut csv = String::from(
+        "mode,retarder_level,delay_ms,snr_db,psnr,ssim,ssim_fit,threshold_db,width_db\n",
+    );
+    for curve in curves {
+        let fit = |f: &dyn Fn(&LogisticFit) -> f64| {
+            curve
+                .fit
+                .as_ref()
+                .map_or_else(String::new, |fit| format!("{:.4}", f(fit)))
+        };
+        for point in &curve.points {
+            csv.push_str(&format!(
+                "{},{},{},{:.2},{:.2},{:.4},{},{},{}\n",
+                curve.mode,
+                curve.retarder_level,
+                curve.delay_ms,
+                point.snr_db,
+                point.psnr,
+                point.ssim,
+                fit(&|fit| fit.eval(point.snr_db)),
+                fit(&|fit| fit.mid_db),
+                fit(&|fit| fit.width_db)
+            ));
+        }
+    }
+    csv
+}
+
+/// Средние PSNR и SSIM по точкам с одинаковым С/Ш, по возрастанию С/Ш. Бесконечный PSNR
+/// (точное совпадение) в среднее не входит
+fn average(mut points: Vec<CurvePoint>) -> Vec<CurvePoint> {
+    points.sort_by(|a, b| a.snr_db.total_cmp(&b.snr_db));
+    let mut averaged = Vec::new();
+    for group in points.chunk_by(|a, b| a.snr_db == b.snr_db) {
+        let finite: Vec<f64> = group
+            .iter()
+            .map(|p| p.psnr)
+            .filter(|p| p.is_finite())
+            .collect();
+        averaged.push(CurvePoint {
+            snr_db: group[0].snr_db,
+            psnr: if finite.is_empty() {
+                f64::INFINITY
+            } else {
+                finite.iter().sum::<f64>() / finite.len() as f64
+            },
+            ssim: group.iter().map(|p| p.ssim).sum::<f64>() / group.len() as f64,
+        });
+    }
+    averaged
+}
+
+fn logistic(x: f64) -> f64 {
+    1.0 / (1.0 + (-x).exp())
+}
+
+/// Подбирает логистическую кривую по наименьшим квадратам: середина и ширина
+/// перебираются по сетке, `low` и `high` для каждой пары решаются точно. Качество
+/// должно расти с С/Ш
+fn fit_logistic(points: &[CurvePoint]) -> Option<LogisticFit> {
+    if points.len() < MIN_FIT_POINTS {
+        return None;
+    }
+    let (first, last) = (points[0].snr_db, points[points.len() - 1].snr_db);
+    let n = points.len() as f64;
+    let mean_q = points.iter().map(|p| p.ssim).sum::<f64>() / n;
+
+    let mut best: Option<LogisticFit> = None;
+    let mids = ((last - first) / FIT_MID_STEP_DB).round() as usize;
+    let (width_from, width_to, width_step) = FIT_WIDTH_DB;
+    let widths = ((width_to - width_from) / width_step).round() as usize;
+    for m in 0..=mids {
+        let mid_db = first + m as f64 * FIT_MID_STEP_DB;
+        for w in 0..=widths {
+            let width_db = width_from + w as f64 * width_step;
+            let s: Vec<f64> = points
+                .iter()
+                .map(|p| logistic((p.snr_db - mid_db) / width_db))
+                .collect();
+            let mean_s = s.iter().sum::<f64>() / n;
+            let var: f64 = s.iter().map(|v| (v - mean_s).powi(2)).sum();
+            if var < 1e-12 {
+                continue;
+            }
+            let cov: f64 = s
+                .iter()
+                .zip(points)
+                .map(|(v, p)| (v - mean_s) * (p.ssim - mean_q))
+                .sum();
+            let span = cov / var;
+            if span <= 0.0 {
+                continue;
+            }
+            let low = mean_q - span * mean_s;
+            let rmse = (s
+                .iter()
+                .zip(points)
+                .map(|(v, p)| (low + span * v - p.ssim).powi(2))
+                .sum::<f64>()
+                / n)
+                .sqrt();
+            if best.is_none_or(|b| rmse < b.rmse) {
+                best = Some(LogisticFit {
+                    low,
+                    high: low + span,
+                    mid_db,
+                    width_db,
+                    rmse,
+                });
+            }
+        }
+    }
+    best
+}
+
+#[cfg(feature = "plot")]
+mod chart {
+    use super::DegradationCurve;
+    use crate::error::{Result, SstvError};
+    use crate::messages;
+    use crate::overlay;
+    use image::{Rgb, RgbImage};
+    use plotters::prelude::*;
+
+    const WIDTH: u32 = 800;
+    const HEIGHT: u32 = 500;
+    /// Поля вокруг области графика: слева и снизу — под подписи осей
+    const LEFT: u32 = 64;
+    const RIGHT: u32 = 24;
+    const TOP: u32 = 40;
+    const BOTTOM: u32 = 48;
+    const TEXT_SCALE: u32 = 2;
+    /// Высота строки легенды, пикселей
+    const LEGEND_LINE: i32 = 20;
+
+    const GRID: RGBColor = RGBColor(225, 225, 225);
+    const AXIS: RGBColor = RGBColor(60, 60, 60);
+    const TEXT: Rgb<u8> = Rgb([40, 40, 40]);
+    /// Цвета кривых по порядку
+    const COLORS: &[RGBColor] = &[
+        RGBColor(31, 119, 180),
+        RGBColor(214, 39, 40),
+        RGBColor(44, 160, 44),
+        RGBColor(255, 127, 14),
+        RGBColor(148, 103, 189),
+        RGBColor(140, 86, 75),
+    ];
+
+    /// Рисует кривые в PNG: точки — средний SSIM каждого С/Ш, линии — подобранные кривые,
+    /// крестик — порог. Подписи — встроенным шрифтом 5×7, как в снимке сравнения
+    pub fn render_chart(curves: &[DegradationCurve]) -> Result<RgbImage> {
+        let Some(first) = curves.first() else {
+            return Err(SstvError::invalid("curves", messages::curve_none()));
+        };
+        let snrs = curves
+            .iter()
+            .flat_map(|c| c.points.iter().map(|p| p.snr_db));
+        let (lo, hi) = snrs.fold((f64::MAX, f64::MIN), |(lo, hi), s| (lo.min(s), hi.max(s)));
+        let (x0, x1) = ((lo - 1.0).floor(), (hi + 1.0).ceil());
+        let y0 = curves
+            .iter()
+            .flat_map(|c| c.points.iter().map(|p| p.ssim))
+            .fold(0.0f64, f64::min)
+            .floor();
+        let step = tick_step(x1 - x0);
+        let x_ticks: Vec<f64> = (0..)
+            .map(|k| (x0 / step).ceil() * step + k as f64 * step)
+            .take_while(|&x| x <= x1)
+            .collect();
+        let y_ticks: Vec<f64> = (0..)
+            .map(|k| y0 + k as f64 * 0.2)
+            .take_while(|&y| y <= 1.0 + 1e-9)
+            .collect();
+
+        let context = messages::curve_chart_failed();
+        let fail = |e: &dyn std::fmt::Display| SstvError::io(context, e.to_string());
+        let mut buffer = vec![255u8; (WIDTH * HEIGHT * 3) as usize];
+        let mut labels: Vec<(i32, i32, String, Rgb<u8>)> = Vec::new();
+        {
+            let root = BitMapBackend::with_buffer(&mut buffer, (WIDTH, HEIGHT)).into_drawing_area();
+            let mut chart = ChartBuilder::on(&root)
+                .margin_left(LEFT)
+                .margin_right(RIGHT)
+                .margin_top(TOP)
+                .margin_bottom(BOTTOM)
+                .build_cartesian_2d(x0..x1, y0..1.0)
+                .map_err(|e| fail(&e))?;
+
+            let grid = x_ticks
+                .iter()
+                .map(|&x| vec![(x, y0), (x, 1.0)])
+                .chain(y_ticks.iter().map(|&y| vec![(x0, y), (x1, y)]));
+            chart
+                .draw_series(grid.map(|path| PathElement::new(path, GRID)))
+                .map_err(|e| fail(&e))?;
+            chart
+                .draw_series([
+                    PathElement::new(vec![(x0, y0), (x1, y0)], AXIS),
+                    PathElement::new(vec![(x0, y0), (x0, 1.0)], AXIS),
+                ])
+                .map_err(|e| fail(&e))?;
+
+            for (k, curve) in curves.iter().enumerate() {
+                let color = COLORS[k % COLORS.len()];
+                chart
+                    .draw_series(
+                        curve
+                            .points
+                            .iter()
+                            .map(|p| Circle::new((p.snr_db, p.ssim), 4, color.filled())),
+                    )
+                    .map_err(|e| fail(&e))?;
+                if let Some(fit) = &curve.fit {
+                    let samples = 200;
+                    let line = (0..=samples).map(|i| {
+                        let x = x0 + (x1 - x0) * i as f64 / samples as f64;
+                        (x, fit.eval(x))
+                    });
+                    chart
+                        .draw_series(LineSeries::new(line, color.stroke_width(2)))
+                        .map_err(|e| fail(&e))?;
+                    let y = fit.eval(fit.mid_db);
+                    chart
+                        .draw_series([Cross::new((fit.mid_db, y), 7, color.stroke_width(2))])
+                        .map_err(|e| fail(&e))?;
+                }
+                // Шрифт 5×7 знает только ASCII, поэтому легенда всегда по-английски
+                let legend = match &curve.fit {
+                    Some(fit) => format!(
+                        "{} retarder {:.2} / {} ms: threshold {:.1} dB, width {:.1} dB",
+                        curve_label(k),
+                        curve.retarder_level,
+                        curve.delay_ms,
+                        fit.mid_db,
+                        fit.width_db
+                    ),
+                    None => format!(
+                        "{} retarder {:.2} / {} ms: no fit",
+                        curve_label(k),
+                        curve.retarder_level,
+                        curve.delay_ms
+                    ),
+                };
+                let [r, g, b] = [color.0, color.1, color.2];
+                let row = TOP as i32 + 8 + k as i32 * LEGEND_LINE;
+                labels.push((LEFT as i32 + 12, row, legend, Rgb([r, g, b])));
+            }
+
+            for &x in &x_ticks {
+                let (px, py) = chart.backend_coord(&(x, y0));
+                let text = format!("{}", x);
+                labels.push((px - text_width(&text) / 2, py + 8, text, TEXT));
+            }
+            for &y in &y_ticks {
+                let (px, py) = chart.backend_coord(&(x0, y));
+                let text = format!("{:.1}", y);
+                labels.push((px - text_width(&text) - 8, py - 7, text, TEXT));
+            }
+            root.present().map_err(|e| fail(&e))?;
+        }
+
+        let mut image = RgbImage::from_raw(WIDTH, HEIGHT, buffer)
+            .ok_or_else(|| SstvError::io(context, messages::curve_bad_buffer()))?;
+        let title = format!("SSIM vs SNR, {}", first.mode);
+        labels.push((LEFT as i32, 12, title, TEXT));
+        let axis = "SNR, dB";
+        labels.push((
+            (WIDTH - RIGHT) as i32 - text_width(axis),
+            (HEIGHT - 20) as i32,
+            axis.to_string(),
+            TEXT,
+        ));
+        for (x, y, text, color) in labels {
+            overlay::draw_pixel_text(&mut image, x, y, &text, TEXT_SCALE, color);
+        }
+        Ok(image)
+    }
+
+    /// Шаг делений оси: 1, 2 или 5 с множителем 10, чтобы делений было около восьми
+    fn tick_step(range: f64) -> f64 {
+        let raw = (range / 8.0).max(1e-3);
+        let magnitude = 10f64.powf(raw.log10().floor());
+        [1.0, 2.0, 5.0, 10.0]
+            .into_iter()
+            .map(|m| m * magnitude)
+            .find(|&step| step >= raw)
+            .unwrap_or(10.0 * magnitude)
+    }
+
+    fn text_width(text: &str) -> i32 {
+        (text.chars().count() as u32 * 6 * TEXT_SCALE) as i32
+    }
+
+    /// Метка кривой в легенде: `A`, `B`, …
+    fn curve_label(k: usize) -> String {
+        format!("{}:", (b'A' + (k % 26) as u8) as char)
+    }
+}
+
+#[cfg(feature = "plot")]
+pub use chart::render_chart;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn curve_fit_finds_threshold() {
+        use crate::quality::QualityTier;
+        let entries: Vec<SweepEntry> = (0..=100)
+            .step_by(5)
+            .map(|noise| {
+                let snr = level_snr_db(noise as u8) as f64;
+                SweepEntry {
+                    index: noise,
+                    quality: QualityTier::Normal,
+                    file: String::new(),
+                    noise: noise as u8,
+                    retarder_level: 0.0,
+                    delay_ms: 0,
+                    psnr: snr,
+                    ssim: 0.1 + 0.8 / (1.0 + (-(snr - 12.0) / 2.0).exp()),
+                    command: String::new(),
+                }
+            })
+            .collect();
+        let curves = DegradationCurve::from_entries(&entries).unwrap();
+        assert_eq!(curves.len(), 1);
+        // Без шума точка в кривую не входит
+        assert_eq!(curves[0].points.len(), 20);
+        let fit = curves[0].fit.unwrap();
+        assert!((fit.mid_db - 12.0).abs() < 0.15, "{fit:?}");
+        assert!((fit.width_db - 2.0).abs() < 0.3, "{fit:?}");
+        assert!(fit.rmse < 0.01, "{fit:?}");
+        assert!(DegradationCurve::from_entries(&entries[..1]).is_err());
+    }
+}
@@ -0,0 +1,290 @@
+use crate::decoder::VisReport;
+use crate::error::{Result, SstvError};
+use crate::messages;
+use crate::metadata;
+use crate::monitor::{Monitor, ReceivedImage};
+use crate::spectrogram::Spectrogram;
+use image::{DynamicImage, ImageFormat};
+use rsstv::SAMPLE_RATE;
+use serde_json::json;
+use std::collections::VecDeque;
+use std::io::{Cursor, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Сколько последних изображений показывает панель
+pub const DASHBOARD_IMAGES: usize = 12;
+/// Сколько последних строк журнала приёма хранится
+const DASHBOARD_LOG: usize = 200;
+/// Самый длинный заголовок запроса, байт
+const MAX_REQUEST: usize = 8192;
+/// Сколько ждать запрос от подключившегося браузера, с
+const REQUEST_TIMEOUT_SECS: u64 = 5;
+/// Как часто сервер проверяет, не пора ли остановиться, мс
+const ACCEPT_POLL_MS: u64 = 100;
+/// Сколько запросов обслуживается одновременно; остальным сразу отвечает 503
+const MAX_CONNECTIONS: usize = 16;
+
+const INDEX_HTML: &str = include_str!("../assets/dashboard/index.html");
+const DASHBOARD_JS: &str = include_str!("../assets/dashboard/dashboard.js");
+const DASHBOARD_CSS: &str = include_str!("../assets/dashboard/dashboard.css");
+
+/// Веб-панель приёмника для безголового поста: страница со статусом, последними
+/// изображениями, водопадом последних секунд сигнала и журналом приёма. Простой HTTP/1.1
+/// без сторонних зависимостей, каждый запрос — в своём потоке, не больше
+/// [`MAX_CONNECTIONS`] сразу, соединение закрывается после ответа. Изображения в панель
+/// передаёт владелец [`Monitor`] через [`Dashboard::push`]: он же забирает их у приёмника
+pub struct Dashboard {
+    board: Arc<Mutex<Board>>,
+    stop: Arc<AtomicBool>,
+}
+
+/// Что панель помнит между запросами
+#[derive(Default)]
+struct Board {
+    /// Последние изображения, новые в конце
+    images: VecDeque<Entry>,
+    /// Журнал приёма, новые строки в конце
+    log: VecDeque<String>,
+    next_id: u64,
+}
+
+/// Принятое изображение, готовое к отдаче браузеру
+struct Entry {
+    id: u64,
+    png: Vec<u8>,
+    description: String,
+}
+
+impl Dashboard {
+    /// Открывает порт `addr` и отвечает на запросы в фоновом потоке до [`Dashboard::stop`]
+    pub fn start(addr: &str, monitor: Arc<Monitor>) -> Result<Self> {
+        let listener = TcpListener::bind(addr)
+            .and_then(|listener| listener.set_nonblocking(true).map(|()| listener))
+            .map_err(|e| SstvError::io(messages::serve_bind_failed(addr), e))?;
+        let board = Arc::new(Mutex::new(Board::default()));
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let (shared, stopped) = (Arc::clone(&board), Arc::clone(&stop));
+        let active = Arc::new(AtomicUsize::new(0));
+        std::thread::spawn(move || {
+            while !stopped.load(Ordering::Relaxed) {
+                match listener.accept() {
+                    Ok((mut stream, _)) => {
+                        if active.fetch_add(1, Ordering::AcqRel) >= MAX_CONNECTIONS {
+                            active.fetch_sub(1, Ordering::AcqRel);
+                            // Ответ короткий и уходит в буфер сокета, не задерживая приём
+                            let _ = stream.set_nonblocking(true).and_then(|()| {
+                                stream.write_all(&response(
+                                    "503 Service Unavailable",
+                                    "text/plain",
+                                    b"busy",
+                                ))
+                            });
+                            continue;
+                        }
+                        let (board, monitor) = (Arc::clone(&shared), Arc::clone(&monitor));
+                        let slot = Slot(Arc::clone(&active));
+                        std::thread::spawn(move || {
+                            if let Err(e) = serve(stream, &board, &monitor) {
+                                tracing::debug!("{}", e);
+                            }
+                            drop(slot);
+                        });
+                    }
+                    Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                        std::thread::sleep(Duration::from_millis(ACCEPT_POLL_MS));
+                    }
+                    Err(e) => tracing::warn!("{}", messages::serve_accept_failed(&e.to_string())),
+                }
+            }
+        });
+        Ok(Self { board, stop })
+    }
+
+    /// Добавляет принятое изображение в панель и строку о нём в журнал
+    pub fn push(&self, received: &ReceivedImage) -> Result<()> {
+        let image = DynamicImage::ImageRgb8(received.image.clone());
+        let png = metadata::png_with_text(&image, &received.text_fields())
+            .map_err(|e| SstvError::io(messages::dashboard_png_failed(), e))?;
+        let description = received.describe();
+
+        let mut board = self.board.lock().unwrap();
+        board.note(description.clone());
+        let id = board.next_id;
+        board.next_id += 1;
+        board.images.push_back(Entry {
+            id,
+            png,
+            description,
+        });
+        let excess = board.images.len().saturating_sub(DASHBOARD_IMAGES);
+        board.images.drain(..excess);
+        Ok(())
+    }
+
+    /// Добавляет строку в журнал приёма
+    pub fn note(&self, line: impl Into<String>) {
+        self.board.lock().unwrap().note(line.into());
+    }
+
+    /// Перестаёт принимать подключения; начатые ответы дописываются
+    pub fn stop(&self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+impl Drop for Dashboard {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// Занятое место среди [`MAX_CONNECTIONS`]; освобождается, даже если поток упал
+struct Slot(Arc<AtomicUsize>);
+
+impl Drop for Slot {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+impl Board {
+    fn note(&mut self, line: String) {
+        self.log.push_back(line);
+        let excess = self.log.len().saturating_sub(DASHBOARD_LOG);
+        self.log.drain(..excess);
+    }
+}
+
+/// Читает один запрос GET и отвечает на него
+fn serve(mut stream: TcpStream, board: &Mutex<Board>, monitor: &Monitor) -> Result<()> {
+    let context = messages::dashboard_reply_failed();
+    stream
+        .set_nonblocking(false)
+        .and_then(|()| stream.set_read_timeout(Some(Duration::from_secs(REQUEST_TIMEOUT_SECS))))
+        .map_err(|e| SstvError::io(context, e))?;
+    let mut request = Vec::new();
+    let mut chunk = [0; 1024];
+    while !request.windows(4).any(|w| w == b"\r\n\r\n") && request.len() < MAX_REQUEST {
+        let read = stream
+            .read(&mut chunk)
+            .map_err(|e| SstvError::io(context, e))?;
+        if read == 0 {
+            break;
+        }
+        request.extend_from_slice(&chunk[..read]);
+    }
+    let request = String::from_utf8_lossy(&request);
+    let mut parts = request
+        .lines()
+        .next()
+        .unwrap_or_default()
+        .split_whitespace();
+    let (method, target) = (
+        parts.next().unwrap_or_default(),
+        parts.next().unwrap_or("/"),
+    );
+    // Запрос вида ?t=… только сбивает кэш браузера
+    let path = target.split('?').next().unwrap_or_default();
+
+    let (status, kind, body) = if method != "GET" {
+        ("405 Method Not Allowed", "text/plain", b"GET only".to_vec())
+    } else {
+        route(path, board, monitor).unwrap_or((
+            "404 Not Found",
+            "text/plain",
+            b"not found".to_vec(),
+        ))
+    };
+    stream
+        .write_all(&response(status, kind, &body))
+        .map_err(|e| SstvError::io(context, e))
+}
+
+/// Ответ HTTP/1.1 целиком: заголовок и тело `body` типа `kind`
+fn response(status: &str, kind: &str, body: &[u8]) -> Vec<u8> {
+    let mut response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nCache-Control: no-store\r\nConnection: close\r\n\r\n",
+        status,
+        kind,
+        body.len()
+    )
+    .into_bytes();
+    response.extend_from_slice(body);
+    response
+}
+
+/// Ответ на путь `path`: статус, тип содержимого и тело; `None` — такого пути нет
+fn route(
+    path: &str,
+    board: &Mutex<Board>,
+    monitor: &Monitor,
+) -> Option<(&'static str, &'static str, Vec<u8>)> {
+    const OK: &str = "200 OK";
+    let png = |image: Option<DynamicImage>| {
+        let mut bytes = Vec::new();
+        image?
+            .write_to(&mut Cursor::new(&mut bytes), ImageFormat::Png)
+            .ok()?;
+        Some((OK, "image/png", bytes))
+    };
+    match path {
+        "/" | "/index.html" => Some((OK, "text/html; charset=utf-8", INDEX_HTML.into())),
+        "/dashboard.js" => Some((OK, "text/javascript; charset=utf-8", DASHBOARD_JS.into())),
+        "/dashboard.css" => Some((OK, "text/css; charset=utf-8", DASHBOARD_CSS.into())),
+        "/api/status" => Some((
+            OK,
+            "application/json",
+            status(board, monitor).to_string().into_bytes(),
+        )),
+        "/current.png" => png(monitor.state().image.clone().map(DynamicImage::ImageRgb8)),
+        "/waterfall.png" => {
+            let recent: Vec<f32> = monitor.state().recent.iter().copied().collect();
+            png((!recent.is_empty()).then(|| {
+                Spectrogram {
+                    lines: 256,
+                    ..Default::default()
+                }
+                .render(&recent, SAMPLE_RATE as u32)
+            }))
+        }
+        _ => {
+            let id: u64 = path
+                .strip_prefix("/image/")?
+                .strip_suffix(".png")?
+                .parse()
+                .ok()?;
+            let board = board.lock().unwrap();
+            let entry = board.images.iter().find(|entry| entry.id == id)?;
+            Some((OK, "image/png", entry.png.clone()))
+        }
+    }
+}
+
+/// Состояние приёмника, список изображений и журнал для страницы
+fn status(board: &Mutex<Board>, monitor: &Monitor) -> serde_json::Value {
+    let state = monitor.state();
+    let board = board.lock().unwrap();
+    json!({
+        "running": state.running,
+        "error": state.error,
+        "receiving": state.image.as_ref().map(|image| json!({
+            "lines": state.lines,
+            "height": image.height(),
+        })),
+        "vis": state.vis.as_ref().map(VisReport::describe),
+        "revision": state.revision,
+        "level_db": state.level_db,
+        "squelch_open": state.squelch_open,
+        "elapsed_secs": state.elapsed_secs,
+        "location": state.location.as_ref().map(|location| location.describe()),
+        "images": board.images.iter().rev().map(|entry| json!({
+            "id": entry.id,
+            "description": entry.description,
+        })).collect::<Vec<_>>(),
+        "log": board.log,
+    })
+}
@@ -0,0 +1,189 @@
+use crate::error::{Result, SstvError};
+use crate::messages;
+use crate::processor::Stage;
+use std::str::FromStr;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+/// Как часто сторож проверяет часы и флаг отмены вызывающего
+const POLL: Duration = Duration::from_millis(10);
+
+/// Пределы времени прогона по настенным часам; `None` — без предела. Прогон
+/// останавливается в ближайшей точке отмены и возвращает [`SstvError::Timeout`], поэтому
+/// один непрерываемый шаг (кодирование изображения) может превысить предел
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct TimeLimits {
+    /// Весь прогон одного вызова процессора
+    pub total: Option<Duration>,
+    pub encode: Option<Duration>,
+    pub effects: Option<Duration>,
+    pub decode: Option<Duration>,
+}
+
+impl TimeLimits {
+    /// Без пределов сторож не запускается
+    pub fn is_unlimited(&self) -> bool {
+        self.total.is_none() && Stage::ALL.iter().all(|&s| self.stage(s).is_none())
+    }
+
+    /// Предел этапа `stage`
+    pub fn stage(&self, stage: Stage) -> Option<Duration> {
+        match stage {
+            Stage::Encode => self.encode,
+            Stage::Effects => self.effects,
+            Stage::Decode => self.decode,
+        }
+    }
+
+    /// Те же пределы с пределом этапа из `limit`
+    pub fn with_stage(mut self, limit: StageLimit) -> Self {
+        let slot = match limit.stage {
+            Stage::Encode => &mut self.encode,
+            Stage::Effects => &mut self.effects,
+            Stage::Decode => &mut self.decode,
+        };
+        *slot = Some(limit.limit);
+        self
+    }
+}
+
+/// Предел одного этапа, как он задаётся в командной строке: `этап:секунды`
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct StageLimit {
+    pub stage: Stage,
+    pub limit: Duration,
+}
+
+impl FromStr for StageLimit {
+    type Err = SstvError;
+
+    /// Разбирает `encode|effects|decode:секунды`, например `decode:30`
+    fn from_str(s: &str) -> Result<Self> {
+        let (stage, secs) = s.split_once(':').ok_or_else(|| {
+            SstvError::invalid("stage_timeout", messages::timeout_expected_stage(s))
+        })?;
+        let stage = Stage::ALL
+            .iter()
+            .copied()
+            .find(|st| st.key() == stage.trim())
+            .ok_or_else(|| {
+                let known: Vec<_> = Stage::ALL.iter().map(Stage::key).collect();
+                SstvError::invalid(
+                    "stage_timeout",
+                    messages::timeout_unknown_stage(stage, &known.join(", ")),
+                )
+            })?;
+        Ok(Self {
+            stage,
+            limit: parse_secs(secs.trim(), "stage_timeout")?,
+        })
+    }
+}
+
+/// Положительное число секунд
+pub(crate) fn parse_secs(s: &str, field: &'static str) -> Result<Duration> {
+    s.parse::<f64>()
+        .ok()
+        .filter(|secs| secs.is_finite() && *secs > 0.0)
+        .map(Duration::from_secs_f64)
+        .ok_or_else(|| SstvError::invalid(field, messages::timeout_expected_seconds(s)))
+}
+
+/// Сторож прогона: пока идёт `f`, отдельный поток следит за часами и флагом отмены
+/// вызывающего `cancel`. Истёк предел или поднят `cancel` — поднимается флаг, который
+/// получает `f`, и прогон останавливается в ближайшей точке отмены. Этап отслеживается по
+/// отчёту о ходе: `f` должна сообщать его через переданный ей `progress`. Отмена по
+/// пределу возвращается как [`SstvError::Timeout`]
+pub(crate) fn guarded<T>(
+    limits: &TimeLimits,
+    cancel: &AtomicBool,
+    progress: &dyn Fn(Stage, f32),
+    f: impl FnOnce(&AtomicBool, &dyn Fn(Stage, f32)) -> Result<T>,
+) -> Result<T> {
+    if limits.is_unlimited() {
+        return f(cancel, progress);
+    }
+    let watchdog = Watchdog::new(*limits);
+    let done = AtomicBool::new(false);
+    std::thread::scope(|scope| {
+        scope.spawn(|| watchdog.watch(cancel, &done));
+        let tracked = |stage, fraction| {
+            watchdog.enter(stage);
+            progress(stage, fraction);
+        };
+        let result = f(&watchdog.flag, &tracked);
+        done.store(true, Ordering::Relaxed);
+        match (result, watchdog.expired()) {
+            (Err(SstvError::Cancelled), Some(timeout)) => Err(timeout),
+            (result, _) => result,
+        }
+    })
+}
+
+struct Watchdog {
+    limits: TimeLimits,
+    started: Instant,
+    /// Текущий этап и когда он начался
+    stage: Mutex<Option<(Stage, Instant)>>,
+    /// Какой предел истёк
+    expired: Mutex<Option<SstvError>>,
+    flag: AtomicBool,
+}
+
+impl Watchdog {
+    fn new(limits: TimeLimits) -> Self {
+        Self {
+            limits,
+            started: Instant::now(),
+            stage: Mutex::new(None),
+            expired: Mutex::new(None),
+            flag: AtomicBool::new(false),
+        }
+    }
+
+    /// Отмечает этап `stage`; часы этапа идут с первой отметки
+    fn enter(&self, stage: Stage) {
+        let mut current = self.stage.lock().unwrap_or_else(|e| e.into_inner());
+        if current.is_none_or(|(s, _)| s != stage) {
+            *current = Some((stage, Instant::now()));
+        }
+    }
+
+    /// Истёкший предел, если он есть
+    fn check(&self) -> Option<SstvError> {
+        let stage = *self.stage.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(limit) = self.limits.total
+            && self.started.elapsed() > limit
+        {
+            return Some(SstvError::Timeout { stage: None, limit });
+        }
+        let (stage, since) = stage?;
+        let limit = self.limits.stage(stage)?;
+        (since.elapsed() > limit).then_some(SstvError::Timeout {
+            stage: Some(stage),
+            limit,
+        })
+    }
+
+    fn watch(&self, cancel: &AtomicBool, done: &AtomicBool) {
+        while !done.load(Ordering::Relaxed) {
+            std::thread::sleep(POLL);
+            if cancel.load(Ordering::Relaxed) {
+                self.flag.store(true, Ordering::Relaxed);
+            }
+            if let Some(timeout) = self.check() {
+                *self.expired.lock().unwrap_or_else(|e| e.into_inner()) = Some(timeout);
+                self.flag.store(true, Ordering::Relaxed);
+                return;
+            }
+        }
+    }
+
+    fn expired(&self) -> Option<SstvError> {
+        self.expired
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .take()
+    }
+}
@@ -0,0 +1,489 @@
+use crate::dsp::{self, ConvolutionBackend};
+use crate::effect::ImageEffect;
+use crate::error::{Result, SstvError};
+use crate::messages;
+use crate::modes::{
+    BREAK_MS, HEADER_MS, LEADER_HZ, LEADER_MS, MARTIN_M1, SYNC_HZ, VIS_BIT_MS, VIS_ONE_HZ,
+    VIS_ZERO_HZ,
+};
+use crate::native_decoder::NativeDecoder;
+use crate::palette::Palette;
+use crate::scan_converter::ScanConverterParams;
+use crate::sink::{self, ImageSink};
+use image::DynamicImage;
+use rsstv::SAMPLE_RATE;
+use rsstv::{
+    common::{DecodeResult, SSTVMode},
+    martinm1::MartinM1,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::AtomicBool;
+
+/// Длина блока анализа при поиске заголовка, мс
+const BLOCK_MS: u32 = 20;
+/// Сколько строк может не хватать сигналу rsstv, чтобы он ещё считался целым: строки
+/// кодера rsstv короче номинала почти на 1%, и к концу набегает пара строк
+const PARTIAL_MARGIN_LINES: f64 = 4.0;
+/// Шаг перебора частоты внутри допустимого отклонения, Гц
+const FREQ_STEP_HZ: f32 = 25.0;
+/// Шаг поиска стартового бита VIS, мс
+const VIS_SEARCH_STEP_MS: f64 = 1.0;
+/// Отступ от краёв бита VIS при оценке его тона, мс: переходы между тонами
+const VIS_EDGE_MS: f64 = 5.0;
+/// Наименьшая сумма долей лидер-тона и стартового бита (из 2), при которой VIS читается
+const VIS_MIN_SCORE: f32 = 0.5;
+
+/// Реализация декодера, используемая при обработке
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DecoderBackend {
+    Rsstv,
+    Native,
+}
+
+impl DecoderBackend {
+    pub const ALL: &'static [DecoderBackend] = &[DecoderBackend::Rsstv, DecoderBackend::Native];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            DecoderBackend::Rsstv => "rsstv",
+            DecoderBackend::Native => "Native",
+        }
+    }
+}
+
+impl clap::ValueEnum for DecoderBackend {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[Self::Rsstv, Self::Native]
+    }
+
+    fn to_possible_value<'a>(&self) -> Option<clap::builder::PossibleValue> {
+        Some(match self {
+            Self::Rsstv => clap::builder::PossibleValue::new("rsstv"),
+            Self::Native => clap::builder::PossibleValue::new("native"),
+        })
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DecoderParams {
+    pub backend: DecoderBackend,
+    /// Порог корреляции синхросигнала 0–1
+    pub sync_threshold: f32,
+    /// Окно поиска заголовка от начала сигнала, мс (0 — весь сигнал)
+    pub search_window_ms: u32,
+    /// Максимальное отклонение частоты тона от номинала, Гц
+    pub max_freq_deviation_hz: f32,
+    /// Автоподстройка частоты (АПЧ): расстройка приёмника оценивается и вычитается при
+    /// декодировании. Встроенный декодер ведёт её по синхроимпульсам каждой строки,
+    /// для rsstv сигнал один раз сдвигается на расстройку лидер-тона
+    pub afc: bool,
+    /// Маскировать после декодирования строки с пропущенным синхроимпульсом или
+    /// неправдоподобным отличием от соседних, см. [`conceal_lines`](crate::conceal::conceal_lines)
+    pub conceal: bool,
+    /// Автокоррекция наклона: период строки ищется перебором по синхроимпульсам всего
+    /// изображения, а не по первым строкам, поэтому находится и большой наклон
+    /// (встроенный декодер)
+    pub deskew: bool,
+    /// Наклон, заданный вручную, ppm: период строки фиксируется, синхроимпульсы уточняют
+    /// только фазу строк. Перекрывает `deskew` (встроенный декодер)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub slant_ppm: Option<f64>,
+    /// Палитра, к которой приводится декодированное изображение
+    pub palette: Palette,
+    /// Эффекты монитора старинного преобразователя развёртки после палитры
+    pub converter: ScanConverterParams,
+}
+
+impl Default for DecoderParams {
+    fn default() -> Self {
+        Self {
+            backend: DecoderBackend::Rsstv,
+            sync_threshold: 0.5,
+            search_window_ms: 0,
+            max_freq_deviation_hz: 250.0,
+            afc: false,
+            conceal: false,
+            deskew: false,
+            slant_ppm: None,
+            palette: Palette::Full,
+            converter: ScanConverterParams::default(),
+        }
+    }
+}
+
+impl DecoderParams {
+    /// Эффекты изображения после декодирования: палитра памяти преобразователя, затем
+    /// монитор
+    pub fn image_effects(&self) -> Vec<Box<dyn ImageEffect>> {
+        let mut effects: Vec<Box<dyn ImageEffect>> = vec![Box::new(self.palette)];
+        effects.extend(self.converter.effects());
+        effects
+    }
+}
+
+/// Общий интерфейс декодеров SSTV. `Send`: процессор с декодером уходит в рабочий поток
+/// сервера заданий
+pub trait Decoder: Send {
+    /// Декодирует изображение из сэмплов
+    fn decode(&mut self, samples: &[f32]) -> Result<DynamicImage>;
+
+    /// Декодирует, сообщая долю выполнения (0–1) и проверяя отмену, если реализация
+    /// это поддерживает; по умолчанию — обычное декодирование целиком
+    fn decode_with_progress(
+        &mut self,
+        samples: &[f32],
+        progress: &dyn Fn(f32),
+        cancel: &AtomicBool,
+    ) -> Result<DynamicImage> {
+        let _ = (progress, cancel);
+        self.decode(samples)
+    }
+
+    /// Декодирует, передавая строки в `sink` по мере приёма, см. [`ImageSink`]. По
+    /// умолчанию декодирует целиком и отдаёт строки готового изображения
+    fn decode_into(
+        &mut self,
+        samples: &[f32],
+        sink: &mut dyn ImageSink,
+        progress: &dyn Fn(f32),
+        cancel: &AtomicBool,
+    ) -> Result<()> {
+        let image = self.decode_with_progress(samples, progress, cancel)?;
+        sink::write_image(sink, &image.to_rgb8())
+    }
+
+    /// Название реализации
+    fn name(&self) -> &'static str;
+
+    /// Уверенность синхронизации по строкам последнего декодирования, если доступна
+    fn line_confidence(&self) -> Option<&[f32]> {
+        None
+    }
+
+    /// Поправка частоты АПЧ по строкам последнего декодирования, Гц, если декодер её ведёт
+    fn afc_trace(&self) -> Option<&[f32]> {
+        None
+    }
+
+    /// Код VIS, прочитанный при последнем декодировании, если декодер его читает
+    fn vis(&self) -> Option<&VisReport> {
+        None
+    }
+
+    /// Отклонение измеренного периода строки от номинального при последнем
+    /// декодировании, ppm, если декодер следит за часами: наклон картинки
+    fn slant_ppm(&self) -> Option<f64> {
+        None
+    }
+
+    /// Сигнал при последнем декодировании кончился раньше изображения: нижние строки
+    /// не приняты
+    fn partial(&self) -> bool {
+        false
+    }
+}
+
+/// Сигнал без цифровой тишины в конце. Точные нули не даёт ни эфир, ни приёмник — это
+/// оборванная запись, поэтому декодеры считают, что передача на них кончилась
+pub fn trim_silence(samples: &[f32]) -> &[f32] {
+    let end = samples.iter().rposition(|&x| x != 0.0).map_or(0, |i| i + 1);
+    &samples[..end]
+}
+
+/// Создаёт декодер выбранной реализации
+pub fn create_decoder(params: &DecoderParams) -> Box<dyn Decoder> {
+    match params.backend {
+        DecoderBackend::Rsstv => Box::new(RsstvDecoder::new_with_params(params.clone())),
+        DecoderBackend::Native => Box::new(NativeDecoder::new_with_params(params.clone())),
+    }
+}
+
+/// Найденный калибровочный заголовок
+#[derive(Clone, Debug)]
+pub struct HeaderMatch {
+    /// Индекс сэмпла начала лидер-тона
+    pub start: usize,
+    /// Средняя корреляция на протяжении лидер-тона
+    pub correlation: f32,
+    /// Измеренное отклонение частоты лидер-тона, Гц
+    pub freq_offset_hz: f32,
+}
+
+/// Принятый код VIS. Декодеры его только сообщают: изображение декодируется как Martin M1
+/// при любом коде
+#[derive(Clone, Debug, PartialEq)]
+pub struct VisReport {
+    /// Семь бит кода
+    pub code: u8,
+    /// Принятый бит чётности
+    pub parity: bool,
+    /// Уверенность по каждому биту 0–1 в порядке передачи: семь бит данных и чётность
+    pub bit_confidence: [f32; 8],
+    /// Индекс сэмпла начала стартового бита
+    pub start: usize,
+}
+
+impl VisReport {
+    /// Совпадает ли бит чётного паритета с битами данных
+    pub fn parity_ok(&self) -> bool {
+        (self.code.count_ones() % 2 == 1) == self.parity
+    }
+
+    /// Уверенность самого сомнительного бита
+    pub fn min_confidence(&self) -> f32 {
+        self.bit_confidence.iter().fold(1.0f32, |m, &c| m.min(c))
+    }
+
+    /// Режим, которому соответствует код, если он известен
+    pub fn mode_name(&self) -> Option<&'static str> {
+        (self.code == MARTIN_M1.vis_code).then_some(MARTIN_M1.name)
+    }
+
+    /// Возвращает описание принятого кода
+    pub fn describe(&self) -> String {
+        messages::vis_describe(
+            self.code,
+            self.mode_name().unwrap_or(messages::vis_unknown_mode()),
+            if self.parity_ok() {
+                messages::vis_parity_ok()
+            } else {
+                messages::vis_parity_bad()
+            },
+            self.min_confidence() * 100.0,
+        )
+    }
+}
+
+pub struct RsstvDecoder {
+    #[deprecated(since = "0.1.0", note = "используйте `params()`")]
+    pub params: DecoderParams,
+    vis: Option<VisReport>,
+    partial: bool,
+}
+
+impl Default for RsstvDecoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[allow(deprecated)]
+impl RsstvDecoder {
+    pub fn new() -> Self {
+        Self::new_with_params(DecoderParams::default())
+    }
+
+    pub fn new_with_params(params: DecoderParams) -> Self {
+        Self {
+            params,
+            vis: None,
+            partial: false,
+        }
+    }
+
+    /// Параметры, с которыми создан; для других нужен новый экземпляр
+    pub fn params(&self) -> &DecoderParams {
+        &self.params
+    }
+}
+
+#[allow(deprecated)]
+impl Decoder for RsstvDecoder {
+    /// Ищет заголовок и декодирует изображение начиная с него
+    fn decode(&mut self, samples: &[f32]) -> Result<DynamicImage> {
+        self.vis = None;
+        self.partial = false;
+        let samples = trim_silence(samples);
+        let Some(header) = locate_header(samples, &self.params) else {
+            return Err(SstvError::DecodeNotFound(
+                messages::decode_not_found().into(),
+            ));
+        };
+        self.vis = read_vis(samples, &header);
+
+        // Отдаём rsstv сигнал с небольшим запасом перед лидер-тоном
+        let margin = ms_to_samples(BLOCK_MS);
+        let start = header.start.saturating_sub(margin);
+
+        // rsstv не знает о расстройке, поэтому АПЧ сдвигает сигнал целиком
+        let mut signal = samples[start..].to_vec();
+        if self.params.afc && header.freq_offset_hz != 0.0 {
+            let step =
+                -2.0 * std::f64::consts::PI * header.freq_offset_hz as f64 / SAMPLE_RATE as f64;
+            dsp::shift_by_phase(&mut signal, ConvolutionBackend::Auto, |i| step * i as f64);
+        }
+
+        // rsstv отдаёт `Partial` и на целом сигнале, если ему не хватило сэмплов на хвост
+        // последней строки, поэтому неполноту определяем по длине сигнала
+        let image_len = (samples.len() - header.start).saturating_sub(ms_to_samples(HEADER_MS));
+        let lines = image_len as f64 / (MARTIN_M1.line_us() * 1e-6 * SAMPLE_RATE as f64);
+        self.partial = lines + PARTIAL_MARGIN_LINES < MARTIN_M1.height as f64;
+
+        let mut dec = MartinM1::new();
+        match dec.decode(&signal) {
+            DecodeResult::Finished(img) | DecodeResult::Partial(img) => Ok(img),
+            DecodeResult::NoneFound => Err(SstvError::DecodeNotFound(
+                messages::decode_not_found().into(),
+            )),
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        DecoderBackend::Rsstv.name()
+    }
+
+    fn vis(&self) -> Option<&VisReport> {
+        self.vis.as_ref()
+    }
+
+    fn partial(&self) -> bool {
+        self.partial
+    }
+}
+
+/// Шаг, с которым [`locate_header`] перебирает блоки анализа, сэмплов. Если отбросить
+/// начало сигнала кратно шагу, оставшиеся блоки не сдвинутся
+pub(crate) fn header_search_hop() -> usize {
+    ms_to_samples(BLOCK_MS) / 2
+}
+
+/// Ищет лидер-тон заголовка в пределах окна поиска
+pub fn locate_header(samples: &[f32], params: &DecoderParams) -> Option<HeaderMatch> {
+    let block = ms_to_samples(BLOCK_MS);
+    let hop = header_search_hop();
+    let window = match params.search_window_ms {
+        0 => samples.len(),
+        ms => ms_to_samples(ms).min(samples.len()),
+    };
+    // Лидер считается найденным, если тон держится хотя бы 2/3 своей длительности
+    let needed = (ms_to_samples(LEADER_MS * 2 / 3) / hop).max(1);
+
+    let mut run_start = 0;
+    let mut run_len = 0;
+    let mut corr_sum = 0.0;
+    let mut freq_sum = 0.0;
+
+    let mut pos = 0;
+    while pos + block <= samples.len() {
+        let (corr, freq) = best_leader_match(&samples[pos..pos + block], params);
+
+        if corr >= params.sync_threshold {
+            if run_len == 0 {
+                run_start = pos;
+                corr_sum = 0.0;
+                freq_sum = 0.0;
+            }
+            run_len += 1;
+            corr_sum += corr;
+            freq_sum += freq;
+
+            if run_len >= needed {
+                return Some(HeaderMatch {
+                    start: run_start,
+                    correlation: corr_sum / run_len as f32,
+                    freq_offset_hz: freq_sum / run_len as f32 - LEADER_HZ,
+                });
+            }
+        } else {
+            // Новый лидер может начинаться только внутри окна поиска
+            if pos >= window {
+                break;
+            }
+            run_len = 0;
+        }
+
+        pos += hop;
+    }
+
+    None
+}
+
+/// Читает VIS после найденного лидер-тона. Стартовый бит ищется как переход от лидер-тона
+/// к 1200 Гц длиной в бит: заголовок мог быть найден и по первому, и по второму лидер-тону.
+/// Бит — тон 1100 или 1300 Гц с поправкой на расстройку лидер-тона, чей Гёрцель больше.
+/// Длительность бита — стандартная
+pub fn read_vis(samples: &[f32], header: &HeaderMatch) -> Option<VisReport> {
+    let fs = SAMPLE_RATE as f64;
+    let to_samples = |ms: f64| (ms / 1000.0 * fs).round() as usize;
+    let bit = to_samples(VIS_BIT_MS as f64);
+    let edge = to_samples(VIS_EDGE_MS);
+    let step = to_samples(VIS_SEARCH_STEP_MS).max(1);
+    let offset = header.freq_offset_hz;
+    let fraction = |block: &[f32], freq: f32| {
+        let energy: f32 = block.iter().map(|x| x * x).sum();
+        if energy <= f32::EPSILON {
+            return 0.0;
+        }
+        let power = dsp::goertzel_power(block, freq, SAMPLE_RATE as u32);
+        (power / (energy * block.len() as f32 / 2.0)).min(1.0)
+    };
+
+    // Стартовый бит — не раньше 2/3 лидер-тона и не позже конца второго лидер-тона
+    let from = header.start + to_samples(LEADER_MS as f64 * 2.0 / 3.0);
+    let to = header.start + to_samples((2 * LEADER_MS + BREAK_MS) as f64) + bit;
+    let (score, start) = (from..=to)
+        .step_by(step)
+        .take_while(|&p| p + 10 * bit <= samples.len())
+        .map(|p| {
+            let leader = fraction(&samples[p - bit..p], LEADER_HZ + offset);
+            let start_bit = fraction(&samples[p..p + bit], SYNC_HZ + offset);
+            (leader + start_bit, p)
+        })
+        .fold((0.0f32, 0), |best, c| if c.0 > best.0 { c } else { best });
+    if score < VIS_MIN_SCORE {
+        return None;
+    }
+
+    let mut bits = 0u8;
+    let mut bit_confidence = [0.0f32; 8];
+    for (k, confidence) in bit_confidence.iter_mut().enumerate() {
+        let from = start + (k + 1) * bit + edge;
+        let block = &samples[from..from + bit - 2 * edge];
+        let one = dsp::goertzel_power(block, VIS_ONE_HZ + offset, SAMPLE_RATE as u32);
+        let zero = dsp::goertzel_power(block, VIS_ZERO_HZ + offset, SAMPLE_RATE as u32);
+        if one > zero {
+            bits |= 1 << k;
+        }
+        *confidence = if one + zero > 0.0 {
+            (one - zero).abs() / (one + zero)
+        } else {
+            0.0
+        };
+    }
+
+    Some(VisReport {
+        code: bits & 0x7F,
+        parity: bits & 0x80 != 0,
+        bit_confidence,
+        start,
+    })
+}
+
+/// Возвращает лучшую корреляцию с лидер-тоном и частоту, на которой она достигнута
+fn best_leader_match(block: &[f32], params: &DecoderParams) -> (f32, f32) {
+    let energy: f32 = block.iter().map(|x| x * x).sum();
+    if energy <= f32::EPSILON {
+        return (0.0, LEADER_HZ);
+    }
+
+    let deviation = params.max_freq_deviation_hz.max(0.0);
+    let steps = (deviation / FREQ_STEP_HZ).floor() as i32;
+
+    let mut best = (0.0, LEADER_HZ);
+    for k in -steps..=steps {
+        let freq = LEADER_HZ + k as f32 * FREQ_STEP_HZ;
+        let power = dsp::goertzel_power(block, freq, SAMPLE_RATE as u32);
+        // Нормируем так, чтобы чистый тон давал корреляцию ≈ 1
+        let corr = (power / (energy * block.len() as f32 / 2.0)).min(1.0);
+        if corr > best.0 {
+            best = (corr, freq);
+        }
+    }
+    best
+}
+
+fn ms_to_samples(ms: u32) -> usize {
+    (ms as usize * SAMPLE_RATE) / 1000
+}
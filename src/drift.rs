@@ -0,0 +1,232 @@
+use crate::buffer::SampleBuffer;
+use crate::dsp::{self, ConvolutionBackend, HilbertStream};
+use crate::effect::{
+    BlockProcessor, Bypass, ChannelEffect, EffectContext, EffectEstimate, EffectMix, StreamContext,
+    from_block_state, to_block_state,
+};
+use crate::error::Result;
+use crate::messages;
+use crate::processor::ProcessingParams;
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DriftParams {
+    /// Постоянная расстройка, Гц
+    pub offset_hz: f32,
+    /// Линейный уход частоты, Гц/с
+    pub drift_hz_per_s: f32,
+    /// Размах синусоидального «плавания» частоты, Гц
+    pub wobble_hz: f32,
+    /// Частота «плавания», Гц
+    pub wobble_rate_hz: f32,
+    /// Доли смешивания выхода эффекта со входом
+    pub mix: EffectMix,
+}
+
+impl Default for DriftParams {
+    fn default() -> Self {
+        Self {
+            offset_hz: 0.0,
+            drift_hz_per_s: 0.0,
+            wobble_hz: 0.0,
+            wobble_rate_hz: 0.5,
+            mix: EffectMix::default(),
+        }
+    }
+}
+
+/// Расстройка и уход частоты: сдвигает спектр сигнала через аналитический сигнал,
+/// имитируя неточную настройку приёмника и нестабильный гетеродин
+pub struct DriftProcessor {
+    #[deprecated(since = "0.1.0", note = "используйте `params()` и `set_params()`")]
+    pub params: DriftParams,
+}
+
+impl Default for DriftProcessor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[allow(deprecated)]
+impl DriftProcessor {
+    pub fn new() -> Self {
+        Self {
+            params: DriftParams::default(),
+        }
+    }
+
+    pub fn new_with_params(params: DriftParams) -> Self {
+        Self { params }
+    }
+
+    pub fn params(&self) -> &DriftParams {
+        &self.params
+    }
+
+    pub fn set_params(&mut self, params: DriftParams) {
+        self.params = params;
+    }
+
+    /// Применяет сдвиг частоты к буферу сэмплов
+    pub fn apply_drift(
+        &self,
+        buffer: &mut SampleBuffer,
+        convolution: ConvolutionBackend,
+    ) -> Result<()> {
+        let sample_rate = buffer.rate;
+        let samples = &mut buffer.data;
+        if !self.is_enabled() {
+            return Ok(());
+        }
+
+        dsp::shift_by_phase(samples, convolution, self.phase(sample_rate));
+        Ok(())
+    }
+
+    /// Накопленная фаза сдвига для сэмпла `i` на частоте `sample_rate` — интеграл
+    /// мгновенной частоты f(t) = offset + drift·t + wobble·sin(2π·rate·t)
+    fn phase(&self, sample_rate: u32) -> impl Fn(usize) -> f64 + Send + 'static {
+        let fs = sample_rate as f64;
+        let offset = self.params.offset_hz as f64;
+        let drift = self.params.drift_hz_per_s as f64;
+        let wobble = self.params.wobble_hz as f64;
+        let rate = self.params.wobble_rate_hz as f64;
+        move |i| {
+            let t = i as f64 / fs;
+            let wobble_phase = if rate > 0.0 {
+                wobble / rate * (1.0 - (2.0 * PI * rate * t).cos())
+            } else {
+                0.0
+            };
+            2.0 * PI * (offset * t + drift * t * t / 2.0) + wobble_phase
+        }
+    }
+
+    /// Устанавливает постоянную расстройку
+    pub fn set_offset_hz(&mut self, offset_hz: f32) {
+        self.params.offset_hz = offset_hz;
+    }
+
+    /// Устанавливает скорость ухода частоты
+    pub fn set_drift_hz_per_s(&mut self, drift_hz_per_s: f32) {
+        self.params.drift_hz_per_s = drift_hz_per_s;
+    }
+
+    /// Устанавливает размах «плавания» частоты
+    pub fn set_wobble_hz(&mut self, wobble_hz: f32) {
+        self.params.wobble_hz = wobble_hz.max(0.0);
+    }
+
+    /// Проверяет, задан ли какой-либо сдвиг частоты
+    pub fn is_enabled(&self) -> bool {
+        self.params.offset_hz != 0.0
+            || self.params.drift_hz_per_s != 0.0
+            || self.params.wobble_hz != 0.0
+    }
+
+    /// Возвращает описание текущих настроек ухода частоты
+    pub fn describe(&self) -> String {
+        if !self.is_enabled() {
+            return messages::drift_off().to_string();
+        }
+
+        messages::drift_describe(
+            self.params.offset_hz,
+            self.params.drift_hz_per_s,
+            self.params.wobble_hz,
+            self.params.wobble_rate_hz,
+        )
+    }
+}
+
+#[allow(deprecated)]
+impl ChannelEffect for DriftProcessor {
+    fn name(&self) -> &'static str {
+        messages::drift()
+    }
+
+    fn mix(&self) -> EffectMix {
+        self.params.mix
+    }
+
+    fn apply(&self, buffer: &mut SampleBuffer, ctx: &EffectContext) -> Result<()> {
+        self.apply_drift(buffer, ctx.convolution)
+    }
+
+    fn is_enabled(&self) -> bool {
+        DriftProcessor::is_enabled(self)
+    }
+
+    fn describe(&self) -> String {
+        DriftProcessor::describe(self)
+    }
+
+    fn sync_params(&mut self, params: &ProcessingParams) {
+        self.params = params.drift.clone();
+    }
+
+    /// Расстройка в начале и в конце сигнала, расширенная на размах «плавания»
+    fn analyze(&self, buffer: &SampleBuffer, _ctx: &EffectContext) -> EffectEstimate {
+        let p = &self.params;
+        let start = p.offset_hz;
+        let end = p.offset_hz + p.drift_hz_per_s * buffer.duration_secs() as f32;
+        let wobble = p.wobble_hz.abs();
+        EffectEstimate {
+            offset_hz: Some((start.min(end) - wobble, start.max(end) + wobble)),
+            ..EffectEstimate::new(self.name(), self.describe())
+        }
+    }
+
+    /// Фильтру Гильберта нужны будущие сэмплы, поэтому блоками сдвиг выходит с
+    /// задержкой [`HilbertStream::delay`]; фаза считается по номеру задержанного сэмпла
+    fn block_processor(&self, ctx: &StreamContext) -> Result<Box<dyn BlockProcessor>> {
+        if !self.is_enabled() {
+            return Ok(Box::new(Bypass));
+        }
+        Ok(Box::new(DriftBlocks {
+            hilbert: HilbertStream::new(),
+            phase: Box::new(self.phase(ctx.sample_rate)),
+        }))
+    }
+}
+
+/// Сдвиг частоты блоками: фильтр Гильберта со своей историей и фаза сдвига
+struct DriftBlocks {
+    hilbert: HilbertStream,
+    phase: Box<dyn Fn(usize) -> f64 + Send>,
+}
+
+impl BlockProcessor for DriftBlocks {
+    fn process_block(&mut self, block: &mut [f32], start: usize) -> Result<()> {
+        let delay = HilbertStream::delay();
+        for (k, sample) in block.iter_mut().enumerate() {
+            let (i, q) = self.hilbert.push(*sample);
+            // Re{(x + jH{x}) · e^{jφ}} для сэмпла, вышедшего из фильтра
+            *sample = match (start + k).checked_sub(delay) {
+                Some(n) => {
+                    let (sin, cos) = (self.phase)(n).sin_cos();
+                    i * cos as f32 - q * sin as f32
+                }
+                None => 0.0,
+            };
+        }
+        Ok(())
+    }
+
+    fn latency(&self) -> usize {
+        HilbertStream::delay()
+    }
+
+    fn save_state(&mut self) -> Result<serde_json::Value> {
+        to_block_state(&self.hilbert.history())
+    }
+
+    fn restore_state(&mut self, state: &serde_json::Value) -> Result<()> {
+        let history: Vec<f32> = from_block_state(state, messages::drift())?;
+        self.hilbert.set_history(&history);
+        Ok(())
+    }
+}
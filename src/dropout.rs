@@ -0,0 +1,362 @@
+use crate::buffer::SampleBuffer;
+use crate::effect::{
+    self, BlockProcessor, ChannelEffect, EffectContext, EffectEstimate, EffectMix, StreamContext,
+};
+use crate::error::{Result, SstvError};
+use crate::messages;
+use crate::processor::ProcessingParams;
+use rand::{Rng, rng};
+use rand_distr::Exp1;
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+use std::str::FromStr;
+
+/// Как пропадает сигнал в окне выпадения
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DropoutKind {
+    /// Цифровая тишина: звук не записан или не дошёл. Тишина до конца сигнала
+    /// обрывает передачу, и декодер отдаёт неполное изображение
+    #[default]
+    Zero,
+    /// Ослабление на `attenuation_db`: глубокий провал, сигнал ещё слышен
+    Mute,
+}
+
+impl DropoutKind {
+    pub const ALL: &'static [DropoutKind] = &[DropoutKind::Zero, DropoutKind::Mute];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            DropoutKind::Zero => messages::dropout_kind_zero(),
+            DropoutKind::Mute => messages::dropout_kind_mute(),
+        }
+    }
+}
+
+impl clap::ValueEnum for DropoutKind {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[Self::Zero, Self::Mute]
+    }
+
+    fn to_possible_value<'a>(&self) -> Option<clap::builder::PossibleValue> {
+        Some(match self {
+            Self::Zero => clap::builder::PossibleValue::new("zero"),
+            Self::Mute => clap::builder::PossibleValue::new("mute"),
+        })
+    }
+}
+
+/// Окно выпадения, заданное явно
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DropoutWindow {
+    /// Начало от начала тела изображения, мс; отрицательное — в заголовке
+    pub start_ms: f32,
+    /// Длительность, мс
+    pub duration_ms: f32,
+}
+
+impl DropoutWindow {
+    pub fn new(start_ms: f32, duration_ms: f32) -> Self {
+        Self {
+            start_ms,
+            duration_ms,
+        }
+    }
+}
+
+impl FromStr for DropoutWindow {
+    type Err = SstvError;
+
+    /// Разбирает окно в формате `начало_мс:длительность_мс`
+    fn from_str(s: &str) -> Result<Self> {
+        let Some((start, duration)) = s.split_once(':') else {
+            return Err(SstvError::invalid(
+                "windows",
+                messages::expected_format(messages::format_window(), s),
+            ));
+        };
+        let parse = |v: &str, what: &str| -> Result<f32> {
+            v.trim()
+                .parse()
+                .map_err(|_| SstvError::invalid("windows", messages::invalid_value(what, v)))
+        };
+        let window = Self::new(
+            parse(start, messages::value_start())?,
+            parse(duration, messages::value_duration())?,
+        );
+        if window.duration_ms <= 0.0 {
+            return Err(SstvError::invalid(
+                "windows",
+                messages::dropout_duration(window.duration_ms),
+            ));
+        }
+        Ok(window)
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DropoutParams {
+    pub kind: DropoutKind,
+    /// Ослабление сигнала в окне для [`DropoutKind::Mute`], дБ
+    pub attenuation_db: f32,
+    /// Явно заданные окна
+    pub windows: Vec<DropoutWindow>,
+    /// Средняя частота случайных выпадений в теле изображения, в минуту (0 — нет)
+    pub rate_per_min: f32,
+    /// Средняя длительность случайного выпадения, мс; каждое — от половины до полутора
+    pub duration_ms: f32,
+    /// Доли смешивания выхода эффекта со входом
+    pub mix: EffectMix,
+}
+
+impl Default for DropoutParams {
+    fn default() -> Self {
+        Self {
+            kind: DropoutKind::Zero,
+            attenuation_db: 40.0,
+            windows: Vec::new(),
+            rate_per_min: 0.0,
+            duration_ms: 1000.0,
+            mix: EffectMix::default(),
+        }
+    }
+}
+
+/// Выпадения сигнала: в заданных или случайных окнах звук пропадает или резко ослабевает,
+/// и в изображении не хватает строк. Стоит последним в конвейере — это сбой записи или
+/// звукового тракта после приёмника, поэтому тишина остаётся точной тишиной
+pub struct DropoutProcessor {
+    #[deprecated(since = "0.1.0", note = "используйте `params()` и `set_params()`")]
+    pub params: DropoutParams,
+}
+
+impl Default for DropoutProcessor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[allow(deprecated)]
+impl DropoutProcessor {
+    pub fn new() -> Self {
+        Self {
+            params: DropoutParams::default(),
+        }
+    }
+
+    pub fn new_with_params(params: DropoutParams) -> Self {
+        Self { params }
+    }
+
+    pub fn params(&self) -> &DropoutParams {
+        &self.params
+    }
+
+    pub fn set_params(&mut self, params: DropoutParams) {
+        self.params = params;
+    }
+
+    /// Применяет выпадения; `image_span` — тело изображения, от начала которого
+    /// отсчитываются окна. Возвращает участки сигнала, попавшие в выпадения
+    pub fn apply_dropout(
+        &self,
+        buffer: &mut SampleBuffer,
+        image_span: &Range<usize>,
+    ) -> Vec<Range<usize>> {
+        self.apply_dropout_with_rng(buffer, image_span, &mut rng())
+    }
+
+    /// Как [`apply_dropout`](Self::apply_dropout), беря случайные окна из генератора `rng`
+    pub fn apply_dropout_with_rng(
+        &self,
+        buffer: &mut SampleBuffer,
+        image_span: &Range<usize>,
+        rng: &mut impl Rng,
+    ) -> Vec<Range<usize>> {
+        let sample_rate = buffer.rate;
+        let samples = &mut buffer.data;
+        if !self.is_enabled() || samples.is_empty() {
+            return Vec::new();
+        }
+        let gain = self.window_gain();
+        let ranges = self.windows(samples.len(), image_span, sample_rate, rng);
+        for range in &ranges {
+            samples[range.clone()].iter_mut().for_each(|x| *x *= gain);
+        }
+        ranges
+    }
+
+    /// Усиление сигнала внутри окна выпадения
+    fn window_gain(&self) -> f32 {
+        match self.params.kind {
+            DropoutKind::Zero => 0.0,
+            DropoutKind::Mute => 10f32.powf(-self.params.attenuation_db.max(0.0) / 20.0),
+        }
+    }
+
+    /// Участки выпадений в сэмплах: явные окна и случайные в теле изображения
+    fn windows(
+        &self,
+        len: usize,
+        image_span: &Range<usize>,
+        sample_rate: u32,
+        rng: &mut impl Rng,
+    ) -> Vec<Range<usize>> {
+        let fs = sample_rate as f32;
+        let to_range = |start: f32, duration: f32| {
+            let end = (start + duration.max(0.0)).max(0.0) as usize;
+            (start.max(0.0) as usize).min(len)..end.min(len)
+        };
+
+        let origin = image_span.start as f32;
+        let mut ranges: Vec<Range<usize>> = self
+            .params
+            .windows
+            .iter()
+            .map(|w| {
+                to_range(
+                    origin + w.start_ms / 1000.0 * fs,
+                    w.duration_ms / 1000.0 * fs,
+                )
+            })
+            .collect();
+
+        if self.params.rate_per_min > 0.0 {
+            let mean_gap = fs * 60.0 / self.params.rate_per_min;
+            let mean_len = self.params.duration_ms / 1000.0 * fs;
+            let mut t = origin;
+            loop {
+                t += rng.sample::<f32, _>(Exp1) * mean_gap;
+                if t >= image_span.end as f32 {
+                    break;
+                }
+                let duration = mean_len * rng.random_range(0.5..1.5);
+                ranges.push(to_range(t, duration));
+            }
+        }
+
+        ranges.retain(|r| !r.is_empty());
+        ranges
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.params.windows.is_empty() || self.params.rate_per_min > 0.0
+    }
+
+    /// Возвращает описание текущих настроек выпадений
+    pub fn describe(&self) -> String {
+        if !self.is_enabled() {
+            return messages::dropout_off().to_string();
+        }
+        let p = &self.params;
+        let mut parts = Vec::new();
+        if !p.windows.is_empty() {
+            parts.push(messages::dropout_windows(p.windows.len()));
+        }
+        if p.rate_per_min > 0.0 {
+            parts.push(messages::dropout_rate(p.rate_per_min, p.duration_ms));
+        }
+        let kind = match p.kind {
+            DropoutKind::Zero => p.kind.name().to_string(),
+            DropoutKind::Mute => messages::dropout_mute(p.kind.name(), p.attenuation_db),
+        };
+        messages::dropout_describe(&parts.join(", "), &kind)
+    }
+}
+
+#[allow(deprecated)]
+impl ChannelEffect for DropoutProcessor {
+    fn name(&self) -> &'static str {
+        messages::dropout()
+    }
+
+    fn mix(&self) -> EffectMix {
+        self.params.mix
+    }
+
+    fn apply(&self, buffer: &mut SampleBuffer, ctx: &EffectContext) -> Result<()> {
+        self.apply_dropout_with_rng(buffer, &ctx.image_span, &mut *ctx.rng.borrow_mut());
+        Ok(())
+    }
+
+    fn is_enabled(&self) -> bool {
+        DropoutProcessor::is_enabled(self)
+    }
+
+    fn describe(&self) -> String {
+        DropoutProcessor::describe(self)
+    }
+
+    fn sync_params(&mut self, params: &ProcessingParams) {
+        self.params = params.dropout.clone();
+    }
+
+    /// Окна строятся так же, как при обработке, из копии генератора контекста
+    fn analyze(&self, buffer: &SampleBuffer, ctx: &EffectContext) -> EffectEstimate {
+        let ranges = self.windows(
+            buffer.len(),
+            &ctx.image_span,
+            buffer.rate,
+            &mut ctx.rng.borrow().clone(),
+        );
+        let mut covered = vec![false; buffer.len()];
+        for range in ranges {
+            covered[range].fill(true);
+        }
+        let affected = covered.iter().filter(|&&c| c).count() as f32 / buffer.len().max(1) as f32;
+        let residual = match self.params.kind {
+            DropoutKind::Zero => 0.0,
+            DropoutKind::Mute => 10f32.powf(-self.params.attenuation_db.max(0.0) / 10.0),
+        };
+        EffectEstimate {
+            gain_db: Some(effect::power_db(1.0 - affected + affected * residual)),
+            affected: Some(affected),
+            ..EffectEstimate::new(self.name(), self.describe())
+        }
+    }
+
+    /// Окна выпадений строятся заранее по длине и телу изображения из контекста
+    fn block_processor(&self, ctx: &StreamContext) -> Result<Box<dyn BlockProcessor>> {
+        Ok(Box::new(DropoutBlocks {
+            gain: self.window_gain(),
+            ranges: self.windows(
+                ctx.len,
+                &ctx.image_span,
+                ctx.sample_rate,
+                &mut ctx.fork_rng(),
+            ),
+        }))
+    }
+}
+
+/// Выпадения блоками: заранее построенные окна и усиление в них
+struct DropoutBlocks {
+    gain: f32,
+    ranges: Vec<Range<usize>>,
+}
+
+impl BlockProcessor for DropoutBlocks {
+    fn process_block(&mut self, block: &mut [f32], start: usize) -> Result<()> {
+        let end = start + block.len();
+        for range in &self.ranges {
+            let from = range.start.max(start);
+            let to = range.end.min(end);
+            if from < to {
+                block[from - start..to - start]
+                    .iter_mut()
+                    .for_each(|x| *x *= self.gain);
+            }
+        }
+        Ok(())
+    }
+    fn save_state(&mut self) -> Result<serde_json::Value> {
+        effect::to_block_state(&self.ranges)
+    }
+
+    fn restore_state(&mut self, state: &serde_json::Value) -> Result<()> {
+        self.ranges = effect::from_block_state(state, messages::dropout())?;
+        Ok(())
+    }
+}
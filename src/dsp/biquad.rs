@@ -0,0 +1,259 @@
+use std::f64::consts::PI;
+
+/// Звено второго порядка (БИХ) по формулам RBJ Audio EQ Cookbook, транспонированная
+/// прямая форма II. Коэффициенты нормированы на a0
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Biquad {
+    b0: f64,
+    b1: f64,
+    b2: f64,
+    a1: f64,
+    a2: f64,
+    z1: f64,
+    z2: f64,
+}
+
+impl Biquad {
+    /// ФНЧ с частотой среза `freq` (−3 дБ при `q` = 1/√2)
+    pub fn lowpass(sample_rate: u32, freq: f32, q: f32) -> Self {
+        let (cos, alpha) = Self::angle(sample_rate, freq, q);
+        let b1 = 1.0 - cos;
+        Self::normalized(b1 / 2.0, b1, b1 / 2.0, 1.0 + alpha, -2.0 * cos, 1.0 - alpha)
+    }
+
+    /// ФВЧ с частотой среза `freq`
+    pub fn highpass(sample_rate: u32, freq: f32, q: f32) -> Self {
+        let (cos, alpha) = Self::angle(sample_rate, freq, q);
+        let b1 = -(1.0 + cos);
+        Self::normalized(
+            -b1 / 2.0,
+            b1,
+            -b1 / 2.0,
+            1.0 + alpha,
+            -2.0 * cos,
+            1.0 - alpha,
+        )
+    }
+
+    /// Полосовой фильтр с единичным усилением на `freq`, ширина полосы — `freq / q`
+    pub fn bandpass(sample_rate: u32, freq: f32, q: f32) -> Self {
+        let (cos, alpha) = Self::angle(sample_rate, freq, q);
+        Self::normalized(alpha, 0.0, -alpha, 1.0 + alpha, -2.0 * cos, 1.0 - alpha)
+    }
+
+    /// Режекторный фильтр: ноль на `freq`
+    pub fn notch(sample_rate: u32, freq: f32, q: f32) -> Self {
+        let (cos, alpha) = Self::angle(sample_rate, freq, q);
+        Self::normalized(1.0, -2.0 * cos, 1.0, 1.0 + alpha, -2.0 * cos, 1.0 - alpha)
+    }
+
+    /// Колоколообразный эквалайзер: усиление `gain_db` на `freq`, единичное вдали от неё
+    pub fn peaking(sample_rate: u32, freq: f32, q: f32, gain_db: f32) -> Self {
+        let (cos, alpha) = Self::angle(sample_rate, freq, q);
+        let a = 10f64.powf(gain_db as f64 / 40.0);
+        Self::normalized(
+            1.0 + alpha * a,
+            -2.0 * cos,
+            1.0 - alpha * a,
+            1.0 + alpha / a,
+            -2.0 * cos,
+            1.0 - alpha / a,
+        )
+    }
+
+    /// Звено с готовыми коэффициентами передаточной функции
+    /// (b0 + b1·z⁻¹ + b2·z⁻²) / (a0 + a1·z⁻¹ + a2·z⁻²)
+    pub fn from_coefficients([b0, b1, b2]: [f64; 3], [a0, a1, a2]: [f64; 3]) -> Self {
+        Self::normalized(b0, b1, b2, a0, a1, a2)
+    }
+
+    /// ФНЧ первого порядка с частотой среза `freq`: −6 дБ на октаву выше неё
+    pub fn lowpass1(sample_rate: u32, freq: f32) -> Self {
+        let k = Self::warp(sample_rate, freq);
+        Self::normalized(1.0, 1.0, 0.0, 1.0 + k, 1.0 - k, 0.0)
+    }
+
+    /// Звено первого порядка с полюсом на `pole` и нулём на `zero`, Гц: при `zero` выше
+    /// `pole` усиление спадает на 6 дБ на октаву между ними и равно `pole / zero` выше нуля
+    pub fn pole_zero(sample_rate: u32, pole: f32, zero: f32) -> Self {
+        let kp = Self::warp(sample_rate, pole);
+        let kz = Self::warp(sample_rate, zero);
+        Self::normalized(1.0 + kz, 1.0 - kz, 0.0, 1.0 + kp, 1.0 - kp, 0.0)
+    }
+
+    /// 2·fs / ω' для билинейного преобразования с предыскажением частоты `freq`
+    fn warp(sample_rate: u32, freq: f32) -> f64 {
+        1.0 / (PI * freq as f64 / sample_rate as f64).tan()
+    }
+
+    /// cos ω₀ и α = sin ω₀ / 2Q
+    fn angle(sample_rate: u32, freq: f32, q: f32) -> (f64, f64) {
+        let w0 = 2.0 * PI * freq as f64 / sample_rate as f64;
+        (w0.cos(), w0.sin() / (2.0 * q.max(1e-3) as f64))
+    }
+
+    fn normalized(b0: f64, b1: f64, b2: f64, a0: f64, a1: f64, a2: f64) -> Self {
+        Self {
+            b0: b0 / a0,
+            b1: b1 / a0,
+            b2: b2 / a0,
+            a1: a1 / a0,
+            a2: a2 / a0,
+            z1: 0.0,
+            z2: 0.0,
+        }
+    }
+
+    /// Обрабатывает один сэмпл
+    pub fn process(&mut self, x: f32) -> f32 {
+        let x = x as f64;
+        let y = self.b0 * x + self.z1;
+        self.z1 = self.b1 * x - self.a1 * y + self.z2;
+        self.z2 = self.b2 * x - self.a2 * y;
+        y as f32
+    }
+
+    /// Обрабатывает сэмплы на месте, продолжая с текущего состояния
+    pub fn process_slice(&mut self, samples: &mut [f32]) {
+        for sample in samples {
+            *sample = self.process(*sample);
+        }
+    }
+
+    /// Сбрасывает состояние фильтра
+    pub fn reset(&mut self) {
+        self.z1 = 0.0;
+        self.z2 = 0.0;
+    }
+
+    /// Состояние фильтра (z1, z2) — для контрольных точек потоковой обработки
+    pub fn state(&self) -> (f64, f64) {
+        (self.z1, self.z2)
+    }
+
+    /// Восстанавливает состояние, полученное из [`state`](Self::state)
+    pub fn set_state(&mut self, (z1, z2): (f64, f64)) {
+        self.z1 = z1;
+        self.z2 = z2;
+    }
+
+    /// Модуль частотной характеристики на частоте `freq`
+    pub fn magnitude(&self, sample_rate: u32, freq: f32) -> f64 {
+        let w = 2.0 * PI * freq as f64 / sample_rate as f64;
+        // H(z) при z = e^{jω}: отношение многочленов от z⁻¹
+        let poly = |c0: f64, c1: f64, c2: f64| {
+            let re = c0 + c1 * w.cos() + c2 * (2.0 * w).cos();
+            let im = -c1 * w.sin() - c2 * (2.0 * w).sin();
+            re.hypot(im)
+        };
+        poly(self.b0, self.b1, self.b2) / poly(1.0, self.a1, self.a2)
+    }
+
+    /// Групповая задержка на частоте `freq`, сэмплов: −dφ/dω. В нуле числителя на
+    /// единичной окружности (центр режекции) фаза скачет на π, и задержка берётся
+    /// у соседней частоты
+    pub fn group_delay(&self, sample_rate: u32, freq: f32) -> f64 {
+        let w = 2.0 * PI * freq as f64 / sample_rate as f64;
+        // Для многочлена P(ω) = Σ c_k e^{−jωk} задержка τ = Re(Σ k c_k e^{−jωk} / P(ω))
+        let delay = |c: [f64; 3], w: f64| -> Option<f64> {
+            let (mut re, mut im, mut k_re, mut k_im) = (0.0, 0.0, 0.0, 0.0);
+            for (k, &c) in c.iter().enumerate() {
+                let (sin, cos) = (k as f64 * w).sin_cos();
+                re += c * cos;
+                im -= c * sin;
+                k_re += k as f64 * c * cos;
+                k_im -= k as f64 * c * sin;
+            }
+            let norm = re * re + im * im;
+            (norm > 1e-18).then(|| (k_re * re + k_im * im) / norm)
+        };
+        let numerator = [self.b0, self.b1, self.b2];
+        let tau_b = delay(numerator, w)
+            .or_else(|| delay(numerator, w + 1e-6))
+            .unwrap_or(0.0);
+        tau_b - delay([1.0, self.a1, self.a2], w).unwrap_or(0.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::FRAC_1_SQRT_2;
+
+    const FS: u32 = 44100;
+
+    #[test]
+    fn lowpass_and_highpass_are_3db_down_at_cutoff() {
+        let lowpass = Biquad::lowpass(FS, 1000.0, FRAC_1_SQRT_2);
+        assert!((lowpass.magnitude(FS, 0.0) - 1.0).abs() < 1e-9);
+        assert!((lowpass.magnitude(FS, 1000.0) - FRAC_1_SQRT_2 as f64).abs() < 1e-6);
+        assert!(lowpass.magnitude(FS, 10000.0) < 0.02);
+
+        let highpass = Biquad::highpass(FS, 1000.0, FRAC_1_SQRT_2);
+        assert!(highpass.magnitude(FS, 0.0) < 1e-9);
+        assert!((highpass.magnitude(FS, 1000.0) - FRAC_1_SQRT_2 as f64).abs() < 1e-6);
+    }
+
+    #[test]
+    fn bandpass_notch_and_peaking_at_center() {
+        assert!((Biquad::bandpass(FS, 1500.0, 2.0).magnitude(FS, 1500.0) - 1.0).abs() < 1e-9);
+        assert!(Biquad::notch(FS, 1500.0, 2.0).magnitude(FS, 1500.0) < 1e-9);
+        let peaking = Biquad::peaking(FS, 1500.0, 1.0, 6.0);
+        assert!((peaking.magnitude(FS, 1500.0) - 10f64.powf(6.0 / 20.0)).abs() < 1e-6);
+        assert!((peaking.magnitude(FS, 0.0) - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn first_order_sections() {
+        let lowpass = Biquad::lowpass1(FS, 100.0);
+        assert!((lowpass.magnitude(FS, 0.0) - 1.0).abs() < 1e-9);
+        assert!((lowpass.magnitude(FS, 100.0) - FRAC_1_SQRT_2 as f64).abs() < 1e-6);
+        let shelf = Biquad::pole_zero(FS, 100.0, 400.0);
+        assert!((shelf.magnitude(FS, 0.0) - 1.0).abs() < 1e-9);
+        assert!((shelf.magnitude(FS, 10000.0) - 0.25).abs() < 1e-2);
+    }
+
+    #[test]
+    fn group_delay_matches_phase_slope() {
+        assert!(
+            (Biquad::normalized(0.0, 1.0, 0.0, 1.0, 0.0, 0.0).group_delay(FS, 1000.0) - 1.0).abs()
+                < 1e-12
+        );
+        // В центре режекции фаза скачет, но задержка остаётся конечной
+        let notch = Biquad::notch(FS, 1500.0, 2.0);
+        assert!(notch.group_delay(FS, 1500.0).is_finite());
+
+        let filter = Biquad::bandpass(FS, 1900.0, 3.0);
+        let phase = |freq: f64| {
+            let w = 2.0 * PI * freq / FS as f64;
+            let z = |c0: f64, c1: f64, c2: f64| {
+                let re = c0 + c1 * w.cos() + c2 * (2.0 * w).cos();
+                let im = -c1 * w.sin() - c2 * (2.0 * w).sin();
+                im.atan2(re)
+            };
+            z(filter.b0, filter.b1, filter.b2) - z(1.0, filter.a1, filter.a2)
+        };
+        let (freq, step) = (1700.0, 0.01);
+        let slope =
+            -(phase(freq + step) - phase(freq - step)) / (2.0 * PI * 2.0 * step / FS as f64);
+        assert!((filter.group_delay(FS, freq as f32) - slope).abs() < 1e-3);
+    }
+
+    #[test]
+    fn filtered_sine_matches_frequency_response() {
+        let mut filter = Biquad::lowpass(FS, 1000.0, FRAC_1_SQRT_2);
+        let freq = 2000.0;
+        let mut samples: Vec<f32> = (0..FS as usize / 2)
+            .map(|i| (2.0 * PI * freq as f64 * i as f64 / FS as f64).sin() as f32)
+            .collect();
+        filter.process_slice(&mut samples);
+        // После установления амплитуда равна модулю характеристики
+        let peak = samples[samples.len() / 2..]
+            .iter()
+            .fold(0.0f32, |m, &v| m.max(v.abs()));
+        assert!((peak as f64 - filter.magnitude(FS, freq)).abs() < 1e-3);
+
+        filter.reset();
+        assert_eq!(filter.process(0.0), 0.0);
+    }
+}
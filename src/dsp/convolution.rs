@@ -0,0 +1,216 @@
+use super::simd;
+use rustfft::FftPlanner;
+use rustfft::num_complex::Complex;
+use serde::{Deserialize, Serialize};
+
+/// Отношение стоимости отсчёта БПФ (на log₂ длины) к умножению-сложению прямой свёртки;
+/// подобрано по `cargo bench --bench convolution`
+const FFT_COST: f64 = 2.0;
+/// Наименьшая длина БПФ блочной свёртки
+const MIN_FFT_LEN: usize = 1024;
+
+/// Способ вычисления свёртки
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConvolutionBackend {
+    /// Выбор по длине сигнала и числу ненулевых отсчётов ядра, см. [`choose_backend`]
+    #[default]
+    Auto,
+    /// Прямое суммирование по ненулевым отсчётам ядра
+    Direct,
+    /// Блочная свёртка через БПФ (перекрытие со сложением)
+    Fft,
+}
+
+impl ConvolutionBackend {
+    pub const ALL: &'static [ConvolutionBackend] = &[
+        ConvolutionBackend::Auto,
+        ConvolutionBackend::Direct,
+        ConvolutionBackend::Fft,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ConvolutionBackend::Auto => "Auto",
+            ConvolutionBackend::Direct => "Direct",
+            ConvolutionBackend::Fft => "FFT",
+        }
+    }
+}
+
+impl clap::ValueEnum for ConvolutionBackend {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[Self::Auto, Self::Direct, Self::Fft]
+    }
+
+    fn to_possible_value<'a>(&self) -> Option<clap::builder::PossibleValue> {
+        Some(match self {
+            Self::Auto => clap::builder::PossibleValue::new("auto"),
+            Self::Direct => clap::builder::PossibleValue::new("direct"),
+            Self::Fft => clap::builder::PossibleValue::new("fft"),
+        })
+    }
+}
+
+/// Длина БПФ блочной свёртки для ядра длиной `kernel_len`
+fn fft_len(kernel_len: usize) -> usize {
+    (4 * kernel_len).next_power_of_two().max(MIN_FFT_LEN)
+}
+
+/// Разрешает [`ConvolutionBackend::Auto`]: прямая свёртка стоит `signal_len · taps`
+/// умножений, блочная — по два БПФ длины n на каждые n − kernel_len + 1 сэмплов.
+/// `taps` — число ненулевых отсчётов ядра: у разреженных ядер (многолучёвость, фильтр
+/// Гильберта) прямая свёртка выгодна и при большой длине
+pub fn choose_backend(
+    backend: ConvolutionBackend,
+    signal_len: usize,
+    kernel_len: usize,
+    taps: usize,
+) -> ConvolutionBackend {
+    if backend != ConvolutionBackend::Auto {
+        return backend;
+    }
+    if signal_len == 0 || kernel_len == 0 {
+        return ConvolutionBackend::Direct;
+    }
+    let n = fft_len(kernel_len);
+    let blocks = signal_len.div_ceil(n - kernel_len + 1) as f64;
+    let direct = signal_len as f64 * taps as f64;
+    let fft = blocks * n as f64 * (2.0 * FFT_COST * (n as f64).log2() + 1.0);
+    if fft < direct {
+        ConvolutionBackend::Fft
+    } else {
+        ConvolutionBackend::Direct
+    }
+}
+
+/// Свёртка сигнала с ядром той же длины, что и сигнал: `out[i] = Σₖ kernel[k]·x[i + center − k]`,
+/// где отсчёты вне сигнала равны нулю. `center` — индекс ядра, соответствующий нулевой
+/// задержке (0 для причинного фильтра, середина для симметричного)
+pub fn convolve(
+    samples: &[f32],
+    kernel: &[f32],
+    center: usize,
+    backend: ConvolutionBackend,
+) -> Vec<f32> {
+    let taps = kernel.iter().filter(|&&h| h != 0.0).count();
+    match choose_backend(backend, samples.len(), kernel.len(), taps) {
+        ConvolutionBackend::Fft => convolve_fft(samples, kernel, center),
+        _ => convolve_direct(samples, kernel, center),
+    }
+}
+
+/// Прямая свёртка, см. [`convolve`]; нулевые отсчёты ядра пропускаются
+pub fn convolve_direct(samples: &[f32], kernel: &[f32], center: usize) -> Vec<f32> {
+    let len = samples.len();
+    let mut out = vec![0.0; len];
+    for (k, &h) in kernel.iter().enumerate().filter(|&(_, &h)| h != 0.0) {
+        // out[i] += h·x[i + center − k] для i, где индекс попадает в сигнал
+        let shift = center as isize - k as isize;
+        let from = (-shift).max(0) as usize;
+        let to = (len as isize - shift).clamp(0, len as isize) as usize;
+        if from >= to {
+            continue;
+        }
+        let source = &samples[(from as isize + shift) as usize..(to as isize + shift) as usize];
+        simd::axpy(&mut out[from..to], source, h);
+    }
+    out
+}
+
+/// Блочная свёртка через БПФ с перекрытием и сложением, см. [`convolve`]
+pub fn convolve_fft(samples: &[f32], kernel: &[f32], center: usize) -> Vec<f32> {
+    let len = samples.len();
+    if len == 0 || kernel.is_empty() {
+        return vec![0.0; len];
+    }
+    let n = fft_len(kernel.len());
+    let block = n - kernel.len() + 1;
+
+    let mut planner = FftPlanner::<f32>::new();
+    let forward = planner.plan_fft_forward(n);
+    let inverse = planner.plan_fft_inverse(n);
+    let mut response = vec![Complex::default(); n];
+    for (slot, &h) in response.iter_mut().zip(kernel) {
+        slot.re = h / n as f32;
+    }
+    forward.process(&mut response);
+
+    // Полная свёртка длиной len + kernel_len − 1, из неё берётся окно с началом в center
+    let mut full = vec![0.0; len + kernel.len() - 1];
+    let mut buffer = vec![Complex::default(); n];
+    for start in (0..len).step_by(block) {
+        let chunk = &samples[start..(start + block).min(len)];
+        buffer.fill(Complex::default());
+        for (slot, &x) in buffer.iter_mut().zip(chunk) {
+            slot.re = x;
+        }
+        forward.process(&mut buffer);
+        for (b, r) in buffer.iter_mut().zip(&response) {
+            *b *= r;
+        }
+        inverse.process(&mut buffer);
+        for (o, b) in full[start..].iter_mut().zip(&buffer) {
+            *o += b.re;
+        }
+    }
+
+    let mut out = vec![0.0; len];
+    for (o, &v) in out.iter_mut().zip(full.iter().skip(center)) {
+        *o = v;
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    fn random(len: usize, seed: u64) -> Vec<f32> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        (0..len).map(|_| rng.random_range(-1.0..1.0)).collect()
+    }
+
+    #[test]
+    fn direct_and_fft_agree() {
+        let samples = random(10_000, 1);
+        let kernel = random(301, 2);
+        let direct = convolve_direct(&samples, &kernel, 150);
+        let fft = convolve_fft(&samples, &kernel, 150);
+        assert_eq!(direct.len(), samples.len());
+        assert_eq!(fft.len(), samples.len());
+        for (a, b) in direct.iter().zip(&fft) {
+            assert!((a - b).abs() < 1e-3, "{a} != {b}");
+        }
+    }
+
+    #[test]
+    fn impulse_kernel_delays_signal() {
+        let samples = random(1000, 3);
+        let mut kernel = vec![0.0; 11];
+        kernel[7] = 1.0;
+        // Центр 5, единица на 7 — задержка на два сэмпла
+        for backend in [ConvolutionBackend::Direct, ConvolutionBackend::Fft] {
+            let out = convolve(&samples, &kernel, 5, backend);
+            assert!(out[..2].iter().all(|v| v.abs() < 1e-5));
+            for (a, b) in out[2..].iter().zip(&samples) {
+                assert!((a - b).abs() < 1e-5);
+            }
+        }
+    }
+
+    #[test]
+    fn auto_prefers_fft_for_long_dense_kernels() {
+        let pick =
+            |kernel_len, taps| choose_backend(ConvolutionBackend::Auto, 1 << 20, kernel_len, taps);
+        assert_eq!(pick(4001, 4001), ConvolutionBackend::Fft);
+        assert_eq!(pick(4001, 3), ConvolutionBackend::Direct);
+        assert_eq!(pick(5, 5), ConvolutionBackend::Direct);
+        assert_eq!(
+            choose_backend(ConvolutionBackend::Fft, 10, 3, 3),
+            ConvolutionBackend::Fft
+        );
+    }
+}
@@ -0,0 +1,123 @@
+use rustfft::FftPlanner;
+use rustfft::num_complex::Complex;
+
+/// Наибольшее окно эталона для поиска сдвига, сэмплов (≈ 24 с при 44.1 кГц)
+const ALIGN_WINDOW: usize = 1 << 20;
+
+/// Сдвиг `candidate` относительно `reference` в пределах ±`max_lag` сэмплов, при котором
+/// их взаимная корреляция наибольшая: `candidate[i + lag]` соответствует `reference[i]`.
+/// Корреляция считается БПФ по окну из середины эталона — начало сигнала SSTV занято
+/// ровными тонами заголовка, по которым сдвиг неоднозначен. Из равных пиков выбирается
+/// ближайший к нулю
+pub fn best_lag(reference: &[f32], candidate: &[f32], max_lag: usize) -> isize {
+    let window = reference.len().min(ALIGN_WINDOW);
+    let start = (reference.len() - window) / 2;
+    let a = &reference[start..start + window];
+    let b_start = start.saturating_sub(max_lag);
+    let b_end = (start + window + max_lag).min(candidate.len());
+    if window == 0 || b_start >= b_end {
+        return 0;
+    }
+    let b = &candidate[b_start..b_end];
+
+    // Линейная корреляция через круговую: длина с запасом на оба сигнала
+    let n = (a.len() + b.len()).next_power_of_two();
+    let mut planner = FftPlanner::<f32>::new();
+    let forward = planner.plan_fft_forward(n);
+    let spectrum = |x: &[f32]| {
+        let mut buffer = vec![Complex::default(); n];
+        for (slot, &v) in buffer.iter_mut().zip(x) {
+            slot.re = v;
+        }
+        forward.process(&mut buffer);
+        buffer
+    };
+    let (fa, fb) = (spectrum(a), spectrum(b));
+    let mut product: Vec<Complex<f32>> = fa.iter().zip(&fb).map(|(x, y)| x.conj() * y).collect();
+    planner.plan_fft_inverse(n).process(&mut product);
+
+    // product[j] = Σ a[i]·b[i + j]; сдвиг lag соответствует j = lag + (start − b_start)
+    let base = (start - b_start) as isize;
+    let correlation = |lag: isize| product[(lag + base).rem_euclid(n as isize) as usize].re;
+    let max_lag = max_lag as isize;
+    (1..=max_lag).flat_map(|k| [k, -k]).fold(0, |best, lag| {
+        if correlation(lag) > correlation(best) {
+            lag
+        } else {
+            best
+        }
+    })
+}
+
+/// Выравнивает `candidate` по `reference` с точностью до сэмпла: сдвиг `offset` — пик
+/// взаимной корреляции по всей длине сигналов (см. [`best_lag`]), масштаб `scale` —
+/// по наименьшим квадратам, так что `scale · candidate[i + offset]` ближе всего к
+/// `reference[i]`. Так сравнивают запись с эфира с кодированием известного изображения:
+/// задержка и усиление тракта на сравнение не влияют. Без общих сэмплов или для
+/// тишины масштаб равен 0
+pub fn align(reference: &[f32], candidate: &[f32]) -> (isize, f32) {
+    let max_lag = reference.len().max(candidate.len());
+    let offset = best_lag(reference, candidate, max_lag);
+    let (cross, energy) = overlapping(reference, candidate, offset)
+        .fold((0.0f64, 0.0f64), |(cross, energy), (a, b)| {
+            (cross + a as f64 * b as f64, energy + b as f64 * b as f64)
+        });
+    let scale = if energy > 0.0 { cross / energy } else { 0.0 };
+    (offset, scale as f32)
+}
+
+/// Пары сэмплов `(reference[i], candidate[i + offset])`, которые есть в обоих сигналах
+pub fn overlapping<'a>(
+    reference: &'a [f32],
+    candidate: &'a [f32],
+    offset: isize,
+) -> impl Iterator<Item = (f32, f32)> + 'a {
+    let start = (-offset).max(0) as usize;
+    let end = (reference.len() as isize).min(candidate.len() as isize - offset);
+    (start..end.max(start as isize) as usize)
+        .map(move |i| (reference[i], candidate[(i as isize + offset) as usize]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn finds_known_lag_in_both_directions() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let reference: Vec<f32> = (0..20_000).map(|_| rng.random_range(-1.0..1.0)).collect();
+        for lag in [-37isize, 0, 123] {
+            // candidate[i + lag] = reference[i]
+            let candidate: Vec<f32> = (0..reference.len() as isize)
+                .map(|i| {
+                    reference
+                        .get((i - lag) as usize)
+                        .copied()
+                        .filter(|_| i >= lag)
+                        .unwrap_or(0.0)
+                })
+                .collect();
+            assert_eq!(best_lag(&reference, &candidate, 200), lag);
+        }
+    }
+
+    #[test]
+    fn align_recovers_offset_and_scale() {
+        let mut rng = StdRng::seed_from_u64(11);
+        let reference: Vec<f32> = (0..30_000).map(|_| rng.random_range(-1.0..1.0)).collect();
+        // Запись начата на 4000 сэмплов раньше передачи и тише вдвое
+        let mut candidate = vec![0.0; 4000];
+        candidate.extend(reference.iter().map(|&v| 0.5 * v));
+        let (offset, scale) = align(&reference, &candidate);
+        assert_eq!(offset, 4000);
+        assert!((scale - 2.0).abs() < 1e-4, "{scale}");
+
+        let (offset, scale) = align(&candidate, &reference);
+        assert_eq!(offset, -4000);
+        assert!((scale - 0.5).abs() < 1e-4, "{scale}");
+        assert_eq!(align(&reference, &[]).1, 0.0);
+        assert_eq!(overlapping(&reference, &candidate, 4000).count(), 30_000);
+    }
+}
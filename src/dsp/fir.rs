@@ -0,0 +1,126 @@
+use std::f64::consts::PI;
+
+/// Окно КИХ-фильтра
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Window {
+    /// Боковые лепестки ≈ −43 дБ, узкий переход
+    Hamming,
+    /// Боковые лепестки ≈ −58 дБ, переход в полтора раза шире, чем у Хэмминга
+    Blackman,
+    /// Боковые лепестки ≈ −31 дБ, быстрый спад дальних лепестков
+    Hann,
+}
+
+impl Window {
+    /// Значение симметричного окна длиной `len` в отсчёте `n` (0…len − 1)
+    pub fn at(&self, n: usize, len: usize) -> f64 {
+        if len <= 1 {
+            return 1.0;
+        }
+        let x = 2.0 * PI * n as f64 / (len - 1) as f64;
+        match self {
+            Window::Hamming => 0.54 - 0.46 * x.cos(),
+            Window::Blackman => 0.42 - 0.5 * x.cos() + 0.08 * (2.0 * x).cos(),
+            Window::Hann => 0.5 - 0.5 * x.cos(),
+        }
+    }
+}
+
+/// ФНЧ длиной `len` отсчётов: sinc с окном, частота среза `cutoff` — доля частоты
+/// дискретизации. При нечётной длине задержка равна целым (len − 1) / 2 сэмплам
+pub fn lowpass(cutoff: f64, len: usize, window: Window) -> Vec<f64> {
+    let middle = len.saturating_sub(1) as f64 / 2.0;
+    (0..len)
+        .map(|n| ideal_lowpass(cutoff, n as f64 - middle) * window.at(n, len))
+        .collect()
+}
+
+/// Полосовой фильтр `low`–`high` (доли частоты дискретизации): разность двух ФНЧ
+pub fn bandpass(low: f64, high: f64, len: usize, window: Window) -> Vec<f64> {
+    lowpass(high, len, window)
+        .into_iter()
+        .zip(lowpass(low, len, window))
+        .map(|(h, l)| h - l)
+        .collect()
+}
+
+/// Нормирует коэффициенты так, чтобы усиление на нулевой частоте было ровно 1
+pub fn normalize_dc(taps: &mut [f64]) {
+    let sum: f64 = taps.iter().sum();
+    if sum != 0.0 {
+        taps.iter_mut().for_each(|t| *t /= sum);
+    }
+}
+
+/// Модуль частотной характеристики фильтра на частоте `freq` (доля частоты дискретизации)
+pub fn magnitude(taps: &[f64], freq: f64) -> f64 {
+    let (re, im) = taps
+        .iter()
+        .enumerate()
+        .fold((0.0, 0.0), |(re, im), (n, &h)| {
+            let w = 2.0 * PI * freq * n as f64;
+            (re + h * w.cos(), im - h * w.sin())
+        });
+    re.hypot(im)
+}
+
+/// Импульсная характеристика идеального ФНЧ в точке `x` сэмплов от центра
+fn ideal_lowpass(cutoff: f64, x: f64) -> f64 {
+    if x == 0.0 {
+        2.0 * cutoff
+    } else {
+        (2.0 * PI * cutoff * x).sin() / (PI * x)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn windows_are_symmetric_with_unit_peak() {
+        for window in [Window::Hamming, Window::Blackman, Window::Hann] {
+            let len = 65;
+            assert!((window.at(len / 2, len) - 1.0).abs() < 1e-12);
+            for n in 0..len {
+                assert!((window.at(n, len) - window.at(len - 1 - n, len)).abs() < 1e-12);
+            }
+        }
+        assert!((Window::Hamming.at(0, 65) - 0.08).abs() < 1e-12);
+        assert!(Window::Blackman.at(0, 65).abs() < 1e-12);
+    }
+
+    #[test]
+    fn lowpass_passes_dc_and_stops_above_cutoff() {
+        let taps = lowpass(0.1, 101, Window::Hamming);
+        assert!((magnitude(&taps, 0.0) - 1.0).abs() < 1e-2);
+        assert!(
+            (magnitude(&taps, 0.1) - 0.5).abs() < 2e-2,
+            "−6 дБ на частоте среза"
+        );
+        assert!(
+            magnitude(&taps, 0.2) < 3e-3,
+            "подавление в полосе задерживания"
+        );
+    }
+
+    #[test]
+    fn bandpass_keeps_band_and_rejects_edges() {
+        let fs = 44100.0;
+        let taps = bandpass(300.0 / fs, 2700.0 / fs, 883, Window::Blackman);
+        assert!((magnitude(&taps, 1500.0 / fs) - 1.0).abs() < 1e-2);
+        assert!(magnitude(&taps, 0.0) < 1e-3);
+        assert!(magnitude(&taps, 4000.0 / fs) < 1e-3);
+        // Симметричные коэффициенты — линейная ФЧХ
+        for (a, b) in taps.iter().zip(taps.iter().rev()) {
+            assert!((a - b).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn normalize_dc_gives_unit_gain() {
+        let mut taps = lowpass(0.01, 31, Window::Hann);
+        normalize_dc(&mut taps);
+        assert!((magnitude(&taps, 0.0) - 1.0).abs() < 1e-12);
+    }
+}
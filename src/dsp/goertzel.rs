@@ -0,0 +1,44 @@
+use std::f32::consts::PI;
+
+/// Мощность сигнала на частоте `freq` по алгоритму Гёрцеля: |X(f)|² одного отсчёта
+/// ДПФ блока. Тон амплитуды A на целом числе периодов даёт (A·N/2)²
+pub fn goertzel_power(block: &[f32], freq: f32, sample_rate: u32) -> f32 {
+    let coeff = 2.0 * (2.0 * PI * freq / sample_rate as f32).cos();
+    let (mut s1, mut s2) = (0.0f32, 0.0f32);
+    for &x in block {
+        let s0 = x + coeff * s1 - s2;
+        s2 = s1;
+        s1 = s0;
+    }
+    s1 * s1 + s2 * s2 - coeff * s1 * s2
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FS: u32 = 44100;
+
+    fn tone(freq: f32, amplitude: f32, len: usize) -> Vec<f32> {
+        (0..len)
+            .map(|i| amplitude * (2.0 * PI * freq * i as f32 / FS as f32).sin())
+            .collect()
+    }
+
+    #[test]
+    fn tone_power_matches_dft_bin() {
+        // 441 сэмпл — ровно 30 периодов тона 3000 Гц
+        let block = tone(3000.0, 0.5, 441);
+        let expected = (0.5 * 441.0 / 2.0f32).powi(2);
+        let power = goertzel_power(&block, 3000.0, FS);
+        assert!((power - expected).abs() / expected < 1e-3);
+    }
+
+    #[test]
+    fn other_frequencies_are_rejected() {
+        let block = tone(1200.0, 1.0, 441 * 4);
+        let on = goertzel_power(&block, 1200.0, FS);
+        let off = goertzel_power(&block, 1500.0, FS);
+        assert!(off < on * 1e-4);
+    }
+}
@@ -0,0 +1,152 @@
+use super::convolution::{ConvolutionBackend, convolve};
+use super::fir::Window;
+use super::simd;
+use std::f32::consts::PI;
+
+/// Половина длины КИХ-фильтра Гильберта, сэмплов
+const HILBERT_HALF_LEN: usize = 64;
+
+/// Ядро КИХ-фильтра Гильберта длиной `2·HILBERT_HALF_LEN + 1` с окном Хэмминга
+fn kernel() -> Vec<f32> {
+    let m = HILBERT_HALF_LEN as isize;
+    let len = 2 * HILBERT_HALF_LEN + 1;
+    // Ненулевые только нечётные отсчёты: h[n] = 2 / (π n)
+    (-m..=m)
+        .map(|n| {
+            if n % 2 == 0 {
+                return 0.0;
+            }
+            let window = Window::Hamming.at((n + m) as usize, len) as f32;
+            2.0 / (PI * n as f32) * window
+        })
+        .collect()
+}
+
+/// Преобразование Гильберта КИХ-фильтром с окном Хэмминга, задержка скомпенсирована.
+/// Полоса SSTV (1–2.5 кГц) при 44.1 кГц обрабатывается с пренебрежимой ошибкой
+pub fn hilbert(samples: &[f32], backend: ConvolutionBackend) -> Vec<f32> {
+    convolve(samples, &kernel(), HILBERT_HALF_LEN, backend)
+}
+
+/// Тот же фильтр Гильберта, что у [`hilbert`], по одному сэмплу: для потоковой обработки,
+/// где будущих сэмплов ещё нет. Задержку компенсировать нечем, поэтому пары выходят
+/// на [`delay`](Self::delay) сэмплов позже входа. История хранится дважды подряд, чтобы
+/// последние сэмплы всегда лежали сплошным окном для [`simd::dot`]
+pub struct HilbertStream {
+    /// Ядро задом наперёд: `reversed[j]` умножается на `j`-й сэмпл окна от старых к новым
+    reversed: Vec<f32>,
+    history: Vec<f32>,
+    pos: usize,
+}
+
+impl Default for HilbertStream {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HilbertStream {
+    pub fn new() -> Self {
+        let mut reversed = kernel();
+        reversed.reverse();
+        Self {
+            history: vec![0.0; 2 * reversed.len()],
+            reversed,
+            pos: 0,
+        }
+    }
+
+    /// Задержка выхода относительно входа, сэмплов
+    pub const fn delay() -> usize {
+        HILBERT_HALF_LEN
+    }
+
+    /// Принимает сэмпл `x[n]` и возвращает пару (x, H{x}) для сэмпла `n − delay`;
+    /// до начала сигнала вход считается нулевым
+    pub fn push(&mut self, x: f32) -> (f32, f32) {
+        let len = self.reversed.len();
+        self.history[self.pos] = x;
+        self.history[self.pos + len] = x;
+        self.pos = (self.pos + 1) % len;
+        // out[n − half] = Σₖ kernel[k]·x[n − k]; окно — x[n − len + 1]…x[n]
+        let window = &self.history[self.pos..self.pos + len];
+        (
+            window[len - 1 - HILBERT_HALF_LEN],
+            simd::dot(&self.reversed, window),
+        )
+    }
+
+    /// Последние принятые сэмплы, от старых к новым, — для контрольных точек
+    pub fn history(&self) -> Vec<f32> {
+        self.history[self.pos..self.pos + self.reversed.len()].to_vec()
+    }
+
+    /// Восстанавливает историю из [`history`](Self::history). Лишние старые сэмплы
+    /// отбрасываются, недостающие считаются нулями
+    pub fn set_history(&mut self, history: &[f32]) {
+        let len = self.reversed.len();
+        let take = history.len().min(len);
+        let (first, second) = self.history.split_at_mut(len);
+        first.fill(0.0);
+        first[len - take..].copy_from_slice(&history[history.len() - take..]);
+        second.copy_from_slice(first);
+        self.pos = 0;
+    }
+}
+
+/// Аналитический сигнал: пары (x, H{x})
+pub fn analytic_signal(samples: &[f32], backend: ConvolutionBackend) -> Vec<(f32, f32)> {
+    samples
+        .iter()
+        .copied()
+        .zip(hilbert(samples, backend))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FS: u32 = 44100;
+
+    fn tone(freq: f32, len: usize, phase: f32) -> Vec<f32> {
+        (0..len)
+            .map(|i| (2.0 * PI * freq * i as f32 / FS as f32 + phase).cos())
+            .collect()
+    }
+
+    #[test]
+    fn hilbert_turns_cosine_into_sine() {
+        let len = 8820;
+        let q = hilbert(&tone(1500.0, len, 0.0), ConvolutionBackend::Auto);
+        let sine = tone(1500.0, len, -PI / 2.0);
+        // Края искажены неполным окном фильтра
+        for (a, b) in q[200..len - 200].iter().zip(&sine[200..len - 200]) {
+            assert!((a - b).abs() < 1e-2, "{a} != {b}");
+        }
+    }
+
+    #[test]
+    fn analytic_signal_has_unit_envelope() {
+        let len = 4410;
+        let analytic = analytic_signal(&tone(2000.0, len, 0.3), ConvolutionBackend::Direct);
+        for &(i, q) in &analytic[200..len - 200] {
+            assert!((i.hypot(q) - 1.0).abs() < 1e-2);
+        }
+    }
+
+    #[test]
+    fn stream_matches_whole_buffer_with_delay() {
+        let samples = tone(1700.0, 2000, 0.7);
+        let whole = analytic_signal(&samples, ConvolutionBackend::Direct);
+        let mut stream = HilbertStream::new();
+        let streamed: Vec<(f32, f32)> = samples.iter().map(|&x| stream.push(x)).collect();
+        let delay = HilbertStream::delay();
+        for (&(i, q), &(si, sq)) in whole.iter().zip(&streamed[delay..]) {
+            assert!(
+                (i - si).abs() < 1e-5 && (q - sq).abs() < 1e-4,
+                "{q} != {sq}"
+            );
+        }
+    }
+}
@@ -0,0 +1,111 @@
+use super::simd;
+use rayon::prelude::*;
+
+/// Длина куска, на которые эффекты делят длинный сигнал: кусок помещается в кэш,
+/// а внутренний цикл считается векторным ядром, см. [`simd`]
+pub const CHUNK_LEN: usize = 16 * 1024;
+
+/// Вызывает `f(offset, chunk)` для кусков `data` длиной до [`CHUNK_LEN`], где `offset` —
+/// индекс первого сэмпла куска. При `parallel` куски обрабатываются в пуле rayon, поэтому
+/// `f` не должна зависеть от порядка кусков; результат от `parallel` не зависит
+pub fn for_each_chunk(data: &mut [f32], parallel: bool, f: impl Fn(usize, &mut [f32]) + Sync) {
+    if parallel {
+        data.par_chunks_mut(CHUNK_LEN)
+            .enumerate()
+            .for_each(|(k, chunk)| f(k * CHUNK_LEN, chunk));
+    } else {
+        data.chunks_mut(CHUNK_LEN)
+            .enumerate()
+            .for_each(|(k, chunk)| f(k * CHUNK_LEN, chunk));
+    }
+}
+
+/// Прибавляет к `samples` сигнал `addend`, умноженный на `gain` и на огибающую
+/// `envelope` (`None` — постоянная 1), и ограничивает результат диапазоном −1…1.
+/// `addend` и `envelope` не короче `samples`
+pub fn mix_clamped(
+    samples: &mut [f32],
+    addend: &[f32],
+    envelope: Option<&[f32]>,
+    gain: f32,
+    parallel: bool,
+) {
+    mix_limited(samples, addend, envelope, gain, 1.0, parallel);
+}
+
+/// Как [`mix_clamped`], но без ограничения: перегрузку суммы убирает выходной каскад
+/// после всех эффектов
+pub fn mix(
+    samples: &mut [f32],
+    addend: &[f32],
+    envelope: Option<&[f32]>,
+    gain: f32,
+    parallel: bool,
+) {
+    mix_limited(samples, addend, envelope, gain, f32::INFINITY, parallel);
+}
+
+fn mix_limited(
+    samples: &mut [f32],
+    addend: &[f32],
+    envelope: Option<&[f32]>,
+    gain: f32,
+    limit: f32,
+    parallel: bool,
+) {
+    for_each_chunk(samples, parallel, |offset, chunk| {
+        let addend = &addend[offset..offset + chunk.len()];
+        let envelope = envelope.map(|e| &e[offset..offset + chunk.len()]);
+        simd::mix_scaled(chunk, addend, envelope, gain, limit);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn signal(len: usize, step: f32) -> Vec<f32> {
+        (0..len).map(|i| (i as f32 * step).sin()).collect()
+    }
+
+    #[test]
+    fn chunks_cover_signal_in_order() {
+        let mut data = vec![0.0; 3 * CHUNK_LEN + 17];
+        for_each_chunk(&mut data, true, |offset, chunk| {
+            for (k, x) in chunk.iter_mut().enumerate() {
+                *x = (offset + k) as f32;
+            }
+        });
+        assert!(data.iter().enumerate().all(|(i, &x)| x == i as f32));
+    }
+
+    #[test]
+    fn mix_matches_per_sample_loop() {
+        let len = 2 * CHUNK_LEN + 5;
+        let (main, addend, envelope) = (signal(len, 0.01), signal(len, 0.3), signal(len, 0.001));
+        let mut expected = main.clone();
+        for (i, s) in expected.iter_mut().enumerate() {
+            *s = (*s + addend[i] * 0.7 * envelope[i]).clamp(-1.0, 1.0);
+        }
+
+        for parallel in [false, true] {
+            let mut mixed = main.clone();
+            mix_clamped(&mut mixed, &addend, Some(&envelope), 0.7, parallel);
+            assert_eq!(mixed, expected);
+        }
+    }
+
+    #[test]
+    fn mix_clamps_to_full_scale() {
+        let mut samples = vec![0.9, -0.9, 0.0];
+        mix_clamped(&mut samples, &[0.5, -0.5, 0.25], None, 1.0, false);
+        assert_eq!(samples, vec![1.0, -1.0, 0.25]);
+    }
+
+    #[test]
+    fn unclamped_mix_keeps_overload() {
+        let mut samples = vec![0.75, -0.75, 0.0];
+        mix(&mut samples, &[0.5, -0.5, 0.25], None, 1.0, false);
+        assert_eq!(samples, vec![1.25, -1.25, 0.25]);
+    }
+}
@@ -0,0 +1,25 @@
+pub mod biquad;
+pub mod convolution;
+pub mod correlation;
+pub mod fir;
+pub mod goertzel;
+pub mod hilbert;
+pub mod mix;
+pub mod resample;
+pub mod simd;
+pub mod spectrum;
+pub mod ssb;
+
+pub use biquad::Biquad;
+pub use convolution::{
+    ConvolutionBackend, choose_backend, convolve, convolve_direct, convolve_fft,
+};
+pub use correlation::{align, best_lag, overlapping};
+pub use fir::Window;
+pub use goertzel::goertzel_power;
+pub use hilbert::{HilbertStream, analytic_signal, hilbert};
+pub use mix::{for_each_chunk, mix, mix_clamped};
+pub use resample::{ResampleStream, Resampler, resample};
+pub use simd::{SimdLevel, set_simd_level, simd_level};
+pub use spectrum::{power_frames, spectrogram};
+pub use ssb::{Sideband, shift_by_phase};
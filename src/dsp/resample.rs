@@ -0,0 +1,300 @@
+use rayon::prelude::*;
+use std::f64::consts::PI;
+
+/// Переходов sinc через ноль с каждой стороны ядра по умолчанию
+pub const ZERO_CROSSINGS: usize = 16;
+/// Отсчётов таблицы ядра на один переход через ноль; между ними значение интерполируется
+const TABLE_STEPS: usize = 512;
+/// Параметр окна Кайзера: боковые лепестки ниже ≈ −90 дБ
+const KAISER_BETA: f64 = 9.0;
+/// Граница полосы пропускания относительно частоты Найквиста меньшей из частот
+const PASSBAND: f64 = 0.92;
+
+/// Передискретизатор с ограниченным окном Кайзера sinc-ядром. При понижении частоты
+/// ядро растягивается и срезает всё выше новой частоты Найквиста, поэтому
+/// наложения спектра нет. Ядро табулируется один раз и годится для любого сигнала
+#[derive(Clone, Debug)]
+pub struct Resampler {
+    from_rate: u32,
+    to_rate: u32,
+    /// Переходов через ноль с каждой стороны ядра: длиннее ядро — круче срез и медленнее
+    zero_crossings: usize,
+    /// Ядро sinc·окно в точках `k / TABLE_STEPS`, k = 0…zero_crossings·TABLE_STEPS
+    table: Vec<f32>,
+}
+
+impl Resampler {
+    pub fn new(from_rate: u32, to_rate: u32) -> Self {
+        Self::with_zero_crossings(from_rate, to_rate, ZERO_CROSSINGS)
+    }
+
+    /// Передискретизатор с ядром в `zero_crossings` переходов через ноль с каждой стороны
+    pub fn with_zero_crossings(from_rate: u32, to_rate: u32, zero_crossings: usize) -> Self {
+        let zero_crossings = zero_crossings.max(1);
+        let norm = bessel_i0(KAISER_BETA);
+        let table = (0..=zero_crossings * TABLE_STEPS + 1)
+            .map(|k| {
+                let u = k as f64 / TABLE_STEPS as f64;
+                let r = (u / zero_crossings as f64).min(1.0);
+                let window = bessel_i0(KAISER_BETA * (1.0 - r * r).sqrt()) / norm;
+                let sinc = if k == 0 {
+                    1.0
+                } else {
+                    (PI * u).sin() / (PI * u)
+                };
+                (sinc * window) as f32
+            })
+            .collect();
+        Self {
+            from_rate,
+            to_rate,
+            zero_crossings,
+            table,
+        }
+    }
+
+    pub fn from_rate(&self) -> u32 {
+        self.from_rate
+    }
+
+    pub fn to_rate(&self) -> u32 {
+        self.to_rate
+    }
+
+    /// Длина результата для сигнала из `len` сэмплов
+    pub fn output_len(&self, len: usize) -> usize {
+        (len as u64 * self.to_rate as u64 / self.from_rate.max(1) as u64) as usize
+    }
+
+    /// Передискретизирует сигнал; при равных частотах возвращает копию
+    pub fn process(&self, samples: &[f32]) -> Vec<f32> {
+        if self.from_rate == self.to_rate || samples.is_empty() {
+            return samples.to_vec();
+        }
+
+        let last = samples.len() - 1;
+        (0..self.output_len(samples.len()))
+            .into_par_iter()
+            .map(|i| self.sample_at(i, samples, 0, last))
+            .collect()
+    }
+
+    /// Срез относительно частоты Найквиста входа, охват ядра во входных сэмплах и шаг
+    /// по таблице ядра на один входной сэмпл
+    fn geometry(&self) -> (f64, f64, f64) {
+        let scale = (self.to_rate as f64 / self.from_rate as f64).min(1.0) * PASSBAND;
+        (
+            scale,
+            self.zero_crossings as f64 / scale,
+            scale * TABLE_STEPS as f64,
+        )
+    }
+
+    /// Момент выходного сэмпла `i` во входных сэмплах
+    fn input_time(&self, i: usize) -> f64 {
+        i as f64 * self.from_rate as f64 / self.to_rate as f64
+    }
+
+    /// Выходной сэмпл `i`; `input[k]` — входной сэмпл `origin + k`, `last` — индекс
+    /// последнего входного сэмпла сигнала
+    fn sample_at(&self, i: usize, input: &[f32], origin: usize, last: usize) -> f32 {
+        let (scale, reach, step) = self.geometry();
+        let t = self.input_time(i);
+        let lo = (t - reach).ceil().max(0.0) as usize;
+        let hi = ((t + reach).floor() as usize).min(last);
+        let mut pos = (t - lo as f64) * step;
+        let mut acc = 0.0f32;
+        for &x in &input[lo - origin..=hi - origin] {
+            acc += x * self.kernel(pos.abs());
+            pos -= step;
+        }
+        acc * scale as f32
+    }
+
+    /// Значение ядра в точке `pos` таблицы (`TABLE_STEPS` точек на переход через ноль)
+    fn kernel(&self, pos: f64) -> f32 {
+        let k = pos as usize;
+        if k >= self.zero_crossings * TABLE_STEPS {
+            return 0.0;
+        }
+        let frac = (pos - k as f64) as f32;
+        self.table[k] + (self.table[k + 1] - self.table[k]) * frac
+    }
+}
+
+/// Потоковый передискретизатор: принимает сигнал блоками и выдаёт ровно те же
+/// сэмплы, что [`Resampler::process`] для всего сигнала целиком. Выходной сэмпл
+/// выдаётся, как только пришли все входные сэмплы под его ядром, поэтому задержка
+/// равна охвату ядра — `zero_crossings` переходов через ноль
+#[derive(Clone, Debug)]
+pub struct ResampleStream {
+    resampler: Resampler,
+    /// Входные сэмплы, ещё нужные ядру; `pending[k]` — сэмпл `origin + k`
+    pending: Vec<f32>,
+    origin: usize,
+    /// Принято входных сэмплов с начала сигнала
+    received: usize,
+    /// Выдано выходных сэмплов с начала сигнала
+    produced: usize,
+}
+
+impl ResampleStream {
+    pub fn new(resampler: Resampler) -> Self {
+        Self {
+            resampler,
+            pending: Vec::new(),
+            origin: 0,
+            received: 0,
+            produced: 0,
+        }
+    }
+
+    pub fn resampler(&self) -> &Resampler {
+        &self.resampler
+    }
+
+    /// Принимает блок и дописывает в `out` готовые выходные сэмплы
+    pub fn push(&mut self, block: &[f32], out: &mut Vec<f32>) {
+        if self.resampler.from_rate == self.resampler.to_rate {
+            out.extend_from_slice(block);
+            return;
+        }
+        self.pending.extend_from_slice(block);
+        self.received += block.len();
+        if self.received == 0 {
+            return;
+        }
+
+        let (_, reach, _) = self.resampler.geometry();
+        let last = self.received - 1;
+        while (self.resampler.input_time(self.produced) + reach).floor() as usize <= last {
+            out.push(
+                self.resampler
+                    .sample_at(self.produced, &self.pending, self.origin, last),
+            );
+            self.produced += 1;
+        }
+
+        // Сэмплы левее ядра следующего выходного сэмпла больше не понадобятся
+        let keep = (self.resampler.input_time(self.produced) - reach)
+            .ceil()
+            .max(0.0) as usize;
+        let drop = keep.saturating_sub(self.origin).min(self.pending.len());
+        self.pending.drain(..drop);
+        self.origin += drop;
+    }
+
+    /// Дописывает хвост сигнала — сэмплы, ядру которых не хватило входа справа, — и
+    /// готовит поток к новому сигналу
+    pub fn finish(&mut self, out: &mut Vec<f32>) {
+        if self.received > 0 {
+            let total = self.resampler.output_len(self.received);
+            let last = self.received - 1;
+            while self.produced < total {
+                out.push(
+                    self.resampler
+                        .sample_at(self.produced, &self.pending, self.origin, last),
+                );
+                self.produced += 1;
+            }
+        }
+        self.reset();
+    }
+
+    /// Забывает принятый сигнал
+    pub fn reset(&mut self) {
+        self.pending.clear();
+        self.origin = 0;
+        self.received = 0;
+        self.produced = 0;
+    }
+}
+
+/// Передискретизирует сигнал с частоты `from_rate` на `to_rate`, см. [`Resampler`]
+pub fn resample(samples: &[f32], from_rate: u32, to_rate: u32) -> Vec<f32> {
+    if from_rate == to_rate {
+        return samples.to_vec();
+    }
+    Resampler::new(from_rate, to_rate).process(samples)
+}
+
+/// Модифицированная функция Бесселя первого рода нулевого порядка (ряд до сходимости)
+fn bessel_i0(x: f64) -> f64 {
+    let half = x / 2.0;
+    let mut term = 1.0;
+    let mut sum = 1.0;
+    for k in 1..64 {
+        term *= (half / k as f64).powi(2);
+        sum += term;
+        if term < sum * 1e-12 {
+            break;
+        }
+    }
+    sum
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dsp::goertzel_power;
+
+    fn tone(freq: f64, rate: u32, len: usize) -> Vec<f32> {
+        (0..len)
+            .map(|i| (2.0 * PI * freq * i as f64 / rate as f64).sin() as f32)
+            .collect()
+    }
+
+    #[test]
+    fn output_len_scales_with_rate() {
+        assert_eq!(Resampler::new(44100, 48000).output_len(44100), 48000);
+        assert_eq!(Resampler::new(48000, 8000).output_len(4800), 800);
+        assert_eq!(resample(&[1.0, 2.0], 8000, 8000), vec![1.0, 2.0]);
+    }
+
+    #[test]
+    fn round_trip_preserves_tone() {
+        let original = tone(1500.0, 44100, 22050);
+        let there = resample(&original, 44100, 48000);
+        let back = resample(&there, 48000, 44100);
+        assert_eq!(back.len(), original.len());
+        // Вдали от краёв ошибка ниже −60 дБ
+        let middle = 2000..original.len() - 2000;
+        let error = original[middle.clone()]
+            .iter()
+            .zip(&back[middle])
+            .map(|(a, b)| (a - b).abs())
+            .fold(0.0f32, f32::max);
+        assert!(error < 1e-3, "ошибка {error}");
+    }
+
+    #[test]
+    fn stream_matches_whole_signal() {
+        let signal = tone(1200.0, 48000, 9000);
+        for (from, to) in [(48000, 44100), (44100, 48000), (48000, 8000)] {
+            let resampler = Resampler::new(from, to);
+            let whole = resampler.process(&signal);
+            let mut stream = ResampleStream::new(resampler);
+            let mut streamed = Vec::new();
+            for block in signal.chunks(777) {
+                stream.push(block, &mut streamed);
+            }
+            stream.finish(&mut streamed);
+            assert_eq!(streamed, whole, "{from} → {to}");
+        }
+    }
+
+    #[test]
+    fn downsampling_rejects_tones_above_nyquist() {
+        // 6 кГц выше частоты Найквиста 8 кГц — без фильтра появился бы тон 2 кГц
+        let mixed: Vec<f32> = tone(1000.0, 44100, 44100)
+            .iter()
+            .zip(tone(6000.0, 44100, 44100))
+            .map(|(a, b)| a + b)
+            .collect();
+        let out = resample(&mixed, 44100, 8000);
+        let block = &out[400..out.len() - 400];
+        let wanted = goertzel_power(block, 1000.0, 8000);
+        let alias = goertzel_power(block, 2000.0, 8000);
+        assert!(alias < wanted * 1e-6, "наложение {alias} при {wanted}");
+    }
+}
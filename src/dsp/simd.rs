@@ -0,0 +1,486 @@
+use crate::error::{Result, SstvError};
+use crate::messages;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// Набор векторных инструкций горячих циклов DSP: прямой свёртки, смешивания и
+/// потокового фильтра Гильберта. Выбирается при первом вызове по возможностям процессора,
+/// поэтому готовая сборка без `target-cpu=native` работает на полной скорости. Умножение и
+/// сложение раздельные, без FMA: свёртка и смешивание дают одни и те же биты на любом
+/// наборе, а скалярное произведение складывает по дорожкам и может разойтись в младших
+/// разрядах
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SimdLevel {
+    /// Лучший набор, который есть у процессора, см. [`detect`]
+    #[default]
+    Auto,
+    /// Без явных векторных инструкций: только то, что векторизует компилятор
+    Scalar,
+    /// SSE2 (x86): по 4 сэмпла
+    Sse2,
+    /// AVX2 (x86): по 8 сэмплов
+    Avx2,
+    /// NEON (ARM64): по 4 сэмпла
+    Neon,
+}
+
+impl SimdLevel {
+    pub const ALL: &'static [SimdLevel] = &[
+        SimdLevel::Auto,
+        SimdLevel::Scalar,
+        SimdLevel::Sse2,
+        SimdLevel::Avx2,
+        SimdLevel::Neon,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            SimdLevel::Auto => "Auto",
+            SimdLevel::Scalar => "Scalar",
+            SimdLevel::Sse2 => "SSE2",
+            SimdLevel::Avx2 => "AVX2",
+            SimdLevel::Neon => "NEON",
+        }
+    }
+
+    /// Есть ли набор у этого процессора; `Auto` и `Scalar` есть всегда
+    pub fn is_supported(&self) -> bool {
+        match self {
+            SimdLevel::Auto | SimdLevel::Scalar => true,
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            SimdLevel::Sse2 => is_x86_feature_detected!("sse2"),
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            SimdLevel::Avx2 => is_x86_feature_detected!("avx2"),
+            #[cfg(target_arch = "aarch64")]
+            SimdLevel::Neon => std::arch::is_aarch64_feature_detected!("neon"),
+            _ => false,
+        }
+    }
+}
+
+impl clap::ValueEnum for SimdLevel {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[Self::Auto, Self::Scalar, Self::Sse2, Self::Avx2, Self::Neon]
+    }
+
+    fn to_possible_value<'a>(&self) -> Option<clap::builder::PossibleValue> {
+        Some(match self {
+            Self::Auto => clap::builder::PossibleValue::new("auto"),
+            Self::Scalar => clap::builder::PossibleValue::new("scalar"),
+            Self::Sse2 => clap::builder::PossibleValue::new("sse2"),
+            Self::Avx2 => clap::builder::PossibleValue::new("avx2"),
+            Self::Neon => clap::builder::PossibleValue::new("neon"),
+        })
+    }
+}
+
+/// Выбранный набор, индекс в [`SimdLevel::ALL`]; 0 (`Auto`) — ещё не выбран
+static LEVEL: AtomicU8 = AtomicU8::new(0);
+
+/// Лучший набор, который есть у процессора
+pub fn detect() -> SimdLevel {
+    [SimdLevel::Avx2, SimdLevel::Neon, SimdLevel::Sse2]
+        .into_iter()
+        .find(SimdLevel::is_supported)
+        .unwrap_or(SimdLevel::Scalar)
+}
+
+/// Выбирает набор для всего процесса, включая рабочие потоки, и возвращает выбранный:
+/// для `Auto` — найденный [`detect`]. Ошибка, если процессор набор не поддерживает
+pub fn set_simd_level(level: SimdLevel) -> Result<SimdLevel> {
+    if !level.is_supported() {
+        return Err(SstvError::invalid(
+            "simd",
+            messages::simd_unsupported(level.name()),
+        ));
+    }
+    let level = match level {
+        SimdLevel::Auto => detect(),
+        level => level,
+    };
+    let index = SimdLevel::ALL.iter().position(|&l| l == level).unwrap_or(0);
+    LEVEL.store(index as u8, Ordering::Relaxed);
+    Ok(level)
+}
+
+/// Набор, которым считаются ядра; при первом вызове без [`set_simd_level`] — [`detect`]
+pub fn simd_level() -> SimdLevel {
+    match SimdLevel::ALL[LEVEL.load(Ordering::Relaxed) as usize] {
+        SimdLevel::Auto => {
+            let level = detect();
+            let index = SimdLevel::ALL.iter().position(|&l| l == level).unwrap_or(0);
+            LEVEL.store(index as u8, Ordering::Relaxed);
+            level
+        }
+        level => level,
+    }
+}
+
+/// `out[i] += a · x[i]`; `x` не короче `out`
+pub fn axpy(out: &mut [f32], x: &[f32], a: f32) {
+    axpy_with(simd_level(), out, &x[..out.len()], a);
+}
+
+/// `out[i] = clamp(out[i] + addend[i] · gain · envelope[i], −limit, limit)`, где
+/// `envelope` `None` — постоянная 1; `addend` и `envelope` не короче `out`. NaN проходит
+/// насквозь, как у [`f32::clamp`]
+pub fn mix_scaled(
+    out: &mut [f32],
+    addend: &[f32],
+    envelope: Option<&[f32]>,
+    gain: f32,
+    limit: f32,
+) {
+    let len = out.len();
+    mix_with(
+        simd_level(),
+        out,
+        &addend[..len],
+        envelope.map(|e| &e[..len]),
+        gain,
+        limit,
+    );
+}
+
+/// Скалярное произведение `Σ a[i]·b[i]` по общей длине
+pub fn dot(a: &[f32], b: &[f32]) -> f32 {
+    let len = a.len().min(b.len());
+    dot_with(simd_level(), &a[..len], &b[..len])
+}
+
+// Ядра с явным набором: `level` должен поддерживаться процессором, длины уже выровнены
+
+fn axpy_with(level: SimdLevel, out: &mut [f32], x: &[f32], a: f32) {
+    match level {
+        // SAFETY: набор выбран только если процессор его поддерживает
+        #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+        SimdLevel::Avx2 => unsafe { x86::axpy_avx2(out, x, a) },
+        #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+        SimdLevel::Sse2 => unsafe { x86::axpy_sse2(out, x, a) },
+        #[cfg(target_arch = "aarch64")]
+        SimdLevel::Neon => unsafe { neon::axpy(out, x, a) },
+        _ => scalar::axpy(out, x, a),
+    }
+}
+
+fn mix_with(
+    level: SimdLevel,
+    out: &mut [f32],
+    addend: &[f32],
+    envelope: Option<&[f32]>,
+    gain: f32,
+    limit: f32,
+) {
+    match level {
+        // SAFETY: см. axpy_with
+        #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+        SimdLevel::Avx2 => unsafe { x86::mix_avx2(out, addend, envelope, gain, limit) },
+        #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+        SimdLevel::Sse2 => unsafe { x86::mix_sse2(out, addend, envelope, gain, limit) },
+        #[cfg(target_arch = "aarch64")]
+        SimdLevel::Neon => unsafe { neon::mix(out, addend, envelope, gain, limit) },
+        _ => scalar::mix(out, addend, envelope, gain, limit),
+    }
+}
+
+fn dot_with(level: SimdLevel, a: &[f32], b: &[f32]) -> f32 {
+    match level {
+        // SAFETY: см. axpy_with
+        #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+        SimdLevel::Avx2 => unsafe { x86::dot_avx2(a, b) },
+        #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+        SimdLevel::Sse2 => unsafe { x86::dot_sse2(a, b) },
+        #[cfg(target_arch = "aarch64")]
+        SimdLevel::Neon => unsafe { neon::dot(a, b) },
+        _ => scalar::dot(a, b),
+    }
+}
+
+/// Обычные циклы; ими же векторные ядра досчитывают хвост короче вектора
+mod scalar {
+    pub fn axpy(out: &mut [f32], x: &[f32], a: f32) {
+        for (o, &x) in out.iter_mut().zip(x) {
+            *o += a * x;
+        }
+    }
+
+    pub fn mix(out: &mut [f32], addend: &[f32], envelope: Option<&[f32]>, gain: f32, limit: f32) {
+        match envelope {
+            Some(envelope) => {
+                for ((s, &a), &e) in out.iter_mut().zip(addend).zip(envelope) {
+                    *s = (*s + a * gain * e).clamp(-limit, limit);
+                }
+            }
+            None => {
+                for (s, &a) in out.iter_mut().zip(addend) {
+                    *s = (*s + a * gain).clamp(-limit, limit);
+                }
+            }
+        }
+    }
+
+    pub fn dot(a: &[f32], b: &[f32]) -> f32 {
+        a.iter().zip(b).map(|(&x, &y)| x * y).sum()
+    }
+}
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+mod x86 {
+    use super::scalar;
+    #[cfg(target_arch = "x86")]
+    use std::arch::x86::*;
+    #[cfg(target_arch = "x86_64")]
+    use std::arch::x86_64::*;
+
+    // max(lo, s) и min(hi, s) возвращают второй аргумент, если один из них NaN: NaN
+    // проходит насквозь, как у f32::clamp
+
+    #[target_feature(enable = "avx2")]
+    pub unsafe fn axpy_avx2(out: &mut [f32], x: &[f32], a: f32) {
+        let head = out.len() / 8 * 8;
+        unsafe {
+            let va = _mm256_set1_ps(a);
+            for i in (0..head).step_by(8) {
+                let o = out.as_mut_ptr().add(i);
+                let ax = _mm256_mul_ps(va, _mm256_loadu_ps(x.as_ptr().add(i)));
+                _mm256_storeu_ps(o, _mm256_add_ps(_mm256_loadu_ps(o), ax));
+            }
+        }
+        scalar::axpy(&mut out[head..], &x[head..], a);
+    }
+
+    #[target_feature(enable = "avx2")]
+    pub unsafe fn mix_avx2(
+        out: &mut [f32],
+        addend: &[f32],
+        envelope: Option<&[f32]>,
+        gain: f32,
+        limit: f32,
+    ) {
+        let head = out.len() / 8 * 8;
+        unsafe {
+            let vg = _mm256_set1_ps(gain);
+            let (lo, hi) = (_mm256_set1_ps(-limit), _mm256_set1_ps(limit));
+            for i in (0..head).step_by(8) {
+                let mut v = _mm256_mul_ps(_mm256_loadu_ps(addend.as_ptr().add(i)), vg);
+                if let Some(e) = envelope {
+                    v = _mm256_mul_ps(v, _mm256_loadu_ps(e.as_ptr().add(i)));
+                }
+                let o = out.as_mut_ptr().add(i);
+                let s = _mm256_add_ps(_mm256_loadu_ps(o), v);
+                _mm256_storeu_ps(o, _mm256_min_ps(hi, _mm256_max_ps(lo, s)));
+            }
+        }
+        let envelope = envelope.map(|e| &e[head..]);
+        scalar::mix(&mut out[head..], &addend[head..], envelope, gain, limit);
+    }
+
+    #[target_feature(enable = "avx2")]
+    pub unsafe fn dot_avx2(a: &[f32], b: &[f32]) -> f32 {
+        let head = a.len() / 8 * 8;
+        let mut lanes = [0.0f32; 8];
+        unsafe {
+            let mut acc = _mm256_setzero_ps();
+            for i in (0..head).step_by(8) {
+                let ab = _mm256_mul_ps(
+                    _mm256_loadu_ps(a.as_ptr().add(i)),
+                    _mm256_loadu_ps(b.as_ptr().add(i)),
+                );
+                acc = _mm256_add_ps(acc, ab);
+            }
+            _mm256_storeu_ps(lanes.as_mut_ptr(), acc);
+        }
+        lanes.iter().sum::<f32>() + scalar::dot(&a[head..], &b[head..])
+    }
+
+    #[target_feature(enable = "sse2")]
+    pub unsafe fn axpy_sse2(out: &mut [f32], x: &[f32], a: f32) {
+        let head = out.len() / 4 * 4;
+        unsafe {
+            let va = _mm_set1_ps(a);
+            for i in (0..head).step_by(4) {
+                let o = out.as_mut_ptr().add(i);
+                let ax = _mm_mul_ps(va, _mm_loadu_ps(x.as_ptr().add(i)));
+                _mm_storeu_ps(o, _mm_add_ps(_mm_loadu_ps(o), ax));
+            }
+        }
+        scalar::axpy(&mut out[head..], &x[head..], a);
+    }
+
+    #[target_feature(enable = "sse2")]
+    pub unsafe fn mix_sse2(
+        out: &mut [f32],
+        addend: &[f32],
+        envelope: Option<&[f32]>,
+        gain: f32,
+        limit: f32,
+    ) {
+        let head = out.len() / 4 * 4;
+        unsafe {
+            let vg = _mm_set1_ps(gain);
+            let (lo, hi) = (_mm_set1_ps(-limit), _mm_set1_ps(limit));
+            for i in (0..head).step_by(4) {
+                let mut v = _mm_mul_ps(_mm_loadu_ps(addend.as_ptr().add(i)), vg);
+                if let Some(e) = envelope {
+                    v = _mm_mul_ps(v, _mm_loadu_ps(e.as_ptr().add(i)));
+                }
+                let o = out.as_mut_ptr().add(i);
+                let s = _mm_add_ps(_mm_loadu_ps(o), v);
+                _mm_storeu_ps(o, _mm_min_ps(hi, _mm_max_ps(lo, s)));
+            }
+        }
+        let envelope = envelope.map(|e| &e[head..]);
+        scalar::mix(&mut out[head..], &addend[head..], envelope, gain, limit);
+    }
+
+    #[target_feature(enable = "sse2")]
+    pub unsafe fn dot_sse2(a: &[f32], b: &[f32]) -> f32 {
+        let head = a.len() / 4 * 4;
+        let mut lanes = [0.0f32; 4];
+        unsafe {
+            let mut acc = _mm_setzero_ps();
+            for i in (0..head).step_by(4) {
+                let ab = _mm_mul_ps(
+                    _mm_loadu_ps(a.as_ptr().add(i)),
+                    _mm_loadu_ps(b.as_ptr().add(i)),
+                );
+                acc = _mm_add_ps(acc, ab);
+            }
+            _mm_storeu_ps(lanes.as_mut_ptr(), acc);
+        }
+        lanes.iter().sum::<f32>() + scalar::dot(&a[head..], &b[head..])
+    }
+}
+
+#[cfg(target_arch = "aarch64")]
+mod neon {
+    use super::scalar;
+    use std::arch::aarch64::*;
+
+    // FMAX и FMIN возвращают NaN, если он есть среди аргументов, — как f32::clamp
+
+    #[target_feature(enable = "neon")]
+    pub unsafe fn axpy(out: &mut [f32], x: &[f32], a: f32) {
+        let head = out.len() / 4 * 4;
+        unsafe {
+            let va = vdupq_n_f32(a);
+            for i in (0..head).step_by(4) {
+                let o = out.as_mut_ptr().add(i);
+                let ax = vmulq_f32(va, vld1q_f32(x.as_ptr().add(i)));
+                vst1q_f32(o, vaddq_f32(vld1q_f32(o), ax));
+            }
+        }
+        scalar::axpy(&mut out[head..], &x[head..], a);
+    }
+
+    #[target_feature(enable = "neon")]
+    pub unsafe fn mix(
+        out: &mut [f32],
+        addend: &[f32],
+        envelope: Option<&[f32]>,
+        gain: f32,
+        limit: f32,
+    ) {
+        let head = out.len() / 4 * 4;
+        unsafe {
+            let vg = vdupq_n_f32(gain);
+            let (lo, hi) = (vdupq_n_f32(-limit), vdupq_n_f32(limit));
+            for i in (0..head).step_by(4) {
+                let mut v = vmulq_f32(vld1q_f32(addend.as_ptr().add(i)), vg);
+                if let Some(e) = envelope {
+                    v = vmulq_f32(v, vld1q_f32(e.as_ptr().add(i)));
+                }
+                let o = out.as_mut_ptr().add(i);
+                let s = vaddq_f32(vld1q_f32(o), v);
+                vst1q_f32(o, vminq_f32(hi, vmaxq_f32(lo, s)));
+            }
+        }
+        let envelope = envelope.map(|e| &e[head..]);
+        scalar::mix(&mut out[head..], &addend[head..], envelope, gain, limit);
+    }
+
+    #[target_feature(enable = "neon")]
+    pub unsafe fn dot(a: &[f32], b: &[f32]) -> f32 {
+        let head = a.len() / 4 * 4;
+        let mut lanes = [0.0f32; 4];
+        unsafe {
+            let mut acc = vdupq_n_f32(0.0);
+            for i in (0..head).step_by(4) {
+                let ab = vmulq_f32(vld1q_f32(a.as_ptr().add(i)), vld1q_f32(b.as_ptr().add(i)));
+                acc = vaddq_f32(acc, ab);
+            }
+            vst1q_f32(lanes.as_mut_ptr(), acc);
+        }
+        lanes.iter().sum::<f32>() + scalar::dot(&a[head..], &b[head..])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn signal(len: usize, step: f32) -> Vec<f32> {
+        (0..len).map(|i| (i as f32 * step).sin()).collect()
+    }
+
+    /// Наборы этого процессора, кроме скалярного, с которым они сравниваются
+    fn vector_levels() -> impl Iterator<Item = SimdLevel> {
+        [SimdLevel::Sse2, SimdLevel::Avx2, SimdLevel::Neon]
+            .into_iter()
+            .filter(SimdLevel::is_supported)
+    }
+
+    #[test]
+    fn vector_kernels_match_scalar_bits() {
+        // Длины с хвостами короче вектора
+        for len in [0, 3, 8, 37, 1029] {
+            let (main, x, envelope) = (signal(len, 0.01), signal(len, 0.3), signal(len, 0.002));
+            let mut expected_axpy = main.clone();
+            axpy_with(SimdLevel::Scalar, &mut expected_axpy, &x, 0.7);
+            let mut expected_mix = main.clone();
+            mix_with(
+                SimdLevel::Scalar,
+                &mut expected_mix,
+                &x,
+                Some(&envelope),
+                1.9,
+                1.0,
+            );
+            for level in vector_levels() {
+                let mut out = main.clone();
+                axpy_with(level, &mut out, &x, 0.7);
+                assert_eq!(out, expected_axpy, "{}", level.name());
+                let mut out = main.clone();
+                mix_with(level, &mut out, &x, Some(&envelope), 1.9, 1.0);
+                assert_eq!(out, expected_mix, "{}", level.name());
+            }
+        }
+    }
+
+    #[test]
+    fn vector_dot_matches_scalar() {
+        let (a, b) = (signal(1031, 0.07), signal(1031, 0.011));
+        let expected = dot_with(SimdLevel::Scalar, &a, &b);
+        for level in vector_levels() {
+            let value = dot_with(level, &a, &b);
+            assert!(
+                (value - expected).abs() < 1e-4,
+                "{}: {value} != {expected}",
+                level.name()
+            );
+        }
+    }
+
+    #[test]
+    fn mix_passes_nan_through() {
+        for level in vector_levels().chain([SimdLevel::Scalar]) {
+            let mut out = vec![0.5; 9];
+            let mut addend = vec![0.25; 9];
+            addend[2] = f32::NAN;
+            mix_with(level, &mut out, &addend, None, 4.0, 1.0);
+            assert!(out[2].is_nan(), "{}", level.name());
+            assert_eq!(out[0], 1.0);
+        }
+    }
+}
@@ -0,0 +1,138 @@
+use rustfft::FftPlanner;
+use rustfft::num_complex::Complex;
+use std::f32::consts::PI;
+use std::ops::Range;
+
+/// Длина окна спектрограммы, сэмплов (≈ 12 мс при 44.1 кГц)
+const SPECTROGRAM_WINDOW: usize = 512;
+/// Длина БПФ спектрограммы: окно дополняется нулями для более частой сетки полос
+const SPECTROGRAM_FFT_LEN: usize = 2048;
+
+/// Спектрограмма сигнала: `columns` окон Ханна, равномерно расставленных по всему сигналу,
+/// и `bins` полос частот в диапазоне `freq`. Спектр окна считается БПФ, мощность полосы
+/// интерполируется между соседними отсчётами БПФ. Мощность в дБ хранится построчно
+/// (`[bin * columns + column]`), нижние частоты первыми
+pub fn spectrogram(
+    samples: &[f32],
+    sample_rate: u32,
+    columns: usize,
+    bins: usize,
+    freq: Range<f32>,
+) -> Vec<f32> {
+    let window = SPECTROGRAM_WINDOW.min(samples.len());
+    let mut power = vec![0.0; columns * bins];
+    if window == 0 {
+        return power;
+    }
+
+    let fft = FftPlanner::<f32>::new().plan_fft_forward(SPECTROGRAM_FFT_LEN);
+    let hann: Vec<f32> = (0..window)
+        .map(|n| 0.5 - 0.5 * (2.0 * PI * n as f32 / window as f32).cos())
+        .collect();
+    // Дробный номер отсчёта БПФ для центра каждой полосы
+    let positions: Vec<f32> = (0..bins)
+        .map(|b| {
+            let f = freq.start + (freq.end - freq.start) * (b as f32 + 0.5) / bins as f32;
+            (f * SPECTROGRAM_FFT_LEN as f32 / sample_rate as f32)
+                .clamp(0.0, (SPECTROGRAM_FFT_LEN / 2) as f32)
+        })
+        .collect();
+
+    let hop = (samples.len() - window) as f64 / columns.saturating_sub(1).max(1) as f64;
+    let mut buffer = vec![Complex::default(); SPECTROGRAM_FFT_LEN];
+    let mut spectrum = vec![0.0f32; SPECTROGRAM_FFT_LEN / 2 + 1];
+    for column in 0..columns {
+        let start = (column as f64 * hop).round() as usize;
+        let frame = &samples[start..start + window];
+        buffer.fill(Complex::default());
+        for ((slot, x), w) in buffer.iter_mut().zip(frame).zip(&hann) {
+            slot.re = x * w;
+        }
+        fft.process(&mut buffer);
+        for (p, c) in spectrum.iter_mut().zip(&buffer) {
+            *p = c.norm_sqr();
+        }
+
+        for (b, &pos) in positions.iter().enumerate() {
+            let k = (pos as usize).min(spectrum.len() - 2);
+            let t = pos - k as f32;
+            let value = spectrum[k] * (1.0 - t) + spectrum[k + 1] * t;
+            power[b * columns + column] = 10.0 * (value + 1e-12).log10();
+        }
+    }
+    power
+}
+
+/// Спектры мощности окон Ханна длиной `fft_len` с перекрытием наполовину, как в методе
+/// Уэлча, но без усреднения: `[окно][отсчёт БПФ]`, от 0 до частоты Найквиста включительно,
+/// шаг `sample_rate / fft_len` Гц. Мощность линейная и нормирована на энергию окна, так что
+/// белый шум с дисперсией σ² даёт в среднем σ² в каждом отсчёте
+pub fn power_frames(samples: &[f32], fft_len: usize) -> Vec<Vec<f32>> {
+    if fft_len == 0 || samples.len() < fft_len {
+        return Vec::new();
+    }
+    let fft = FftPlanner::<f32>::new().plan_fft_forward(fft_len);
+    let hann: Vec<f32> = (0..fft_len)
+        .map(|n| 0.5 - 0.5 * (2.0 * PI * n as f32 / fft_len as f32).cos())
+        .collect();
+    let energy: f32 = hann.iter().map(|w| w * w).sum();
+    let mut buffer = vec![Complex::default(); fft_len];
+    samples
+        .windows(fft_len)
+        .step_by(fft_len / 2)
+        .map(|frame| {
+            for ((slot, x), w) in buffer.iter_mut().zip(frame).zip(&hann) {
+                *slot = Complex::new(x * w, 0.0);
+            }
+            fft.process(&mut buffer);
+            buffer[..=fft_len / 2]
+                .iter()
+                .map(|c| c.norm_sqr() / energy)
+                .collect()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tone_peaks_in_its_bin() {
+        let fs = 44100;
+        let samples: Vec<f32> = (0..fs as usize / 4)
+            .map(|i| (2.0 * PI * 1950.0 * i as f32 / fs as f32).sin())
+            .collect();
+        // 20 полос по 100 Гц от 1000 Гц: тон 1950 Гц — центр девятой
+        let (columns, bins) = (4, 20);
+        let power = spectrogram(&samples, fs, columns, bins, 1000.0..3000.0);
+        for column in 0..columns {
+            let peak = (0..bins)
+                .max_by(|&a, &b| {
+                    power[a * columns + column].total_cmp(&power[b * columns + column])
+                })
+                .unwrap();
+            assert_eq!(peak, 9);
+        }
+    }
+
+    #[test]
+    fn white_noise_power_matches_variance() {
+        use rand::rngs::StdRng;
+        use rand::{Rng, SeedableRng};
+        let mut rng = StdRng::seed_from_u64(3);
+        // Равномерный шум на [−1, 1): дисперсия 1/3
+        let samples: Vec<f32> = (0..65_536).map(|_| rng.random_range(-1.0..1.0)).collect();
+        let frames = power_frames(&samples, 1024);
+        assert_eq!(frames.len(), 127);
+        let bins = frames.iter().flat_map(|f| &f[1..512]);
+        let mean = bins.clone().sum::<f32>() / bins.count() as f32;
+        assert!((mean - 1.0 / 3.0).abs() < 0.01, "{mean}");
+    }
+
+    #[test]
+    fn empty_signal_gives_zero_power() {
+        assert_eq!(spectrogram(&[], 44100, 3, 2, 0.0..1000.0), vec![0.0; 6]);
+        assert!(power_frames(&[0.0; 100], 1024).is_empty());
+    }
+}
@@ -0,0 +1,205 @@
+use super::convolution::ConvolutionBackend;
+use super::hilbert::hilbert;
+use serde::{Deserialize, Serialize};
+
+/// Боковая полоса однополосной модуляции
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Sideband {
+    /// Верхняя: тон f звука оказывается на несущей + f
+    #[default]
+    Upper,
+    /// Нижняя: тон f звука оказывается на несущей − f, спектр инвертирован
+    Lower,
+}
+
+impl Sideband {
+    pub const ALL: &'static [Sideband] = &[Sideband::Upper, Sideband::Lower];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Sideband::Upper => "USB",
+            Sideband::Lower => "LSB",
+        }
+    }
+}
+
+impl clap::ValueEnum for Sideband {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[Self::Upper, Self::Lower]
+    }
+
+    fn to_possible_value<'a>(&self) -> Option<clap::builder::PossibleValue> {
+        Some(match self {
+            Self::Upper => clap::builder::PossibleValue::new("usb"),
+            Self::Lower => clap::builder::PossibleValue::new("lsb"),
+        })
+    }
+}
+
+/// Комплексная огибающая звука `samples` на выходе SSB-модулятора относительно
+/// подавленной несущей: x + jH{x} для верхней полосы, x − jH{x} для нижней. Пары (I, Q)
+pub fn to_iq(samples: &[f32], sideband: Sideband, backend: ConvolutionBackend) -> Vec<(f32, f32)> {
+    let sign = match sideband {
+        Sideband::Upper => 1.0,
+        Sideband::Lower => -1.0,
+    };
+    samples
+        .iter()
+        .zip(hilbert(samples, backend))
+        .map(|(&i, q)| (i, sign * q))
+        .collect()
+}
+
+/// Перенос комплексной огибающей на несущую: Re{(I + jQ) · e^{jφ}}, где `phase(i)` —
+/// накопленная фаза несущей в радианах для сэмпла `i`
+pub fn from_iq(iq: &[(f32, f32)], phase: impl Fn(usize) -> f64) -> Vec<f32> {
+    iq.iter()
+        .enumerate()
+        .map(|(n, &(i, q))| {
+            let (sin, cos) = phase(n).sin_cos();
+            i * cos as f32 - q * sin as f32
+        })
+        .collect()
+}
+
+/// SSB-модулятор фазовым методом: звук переносится на несущую с фазой `phase` без
+/// зеркальной полосы, которую дало бы простое умножение на косинус
+pub fn modulate(
+    samples: &[f32],
+    sideband: Sideband,
+    backend: ConvolutionBackend,
+    phase: impl Fn(usize) -> f64,
+) -> Vec<f32> {
+    from_iq(&to_iq(samples, sideband, backend), phase)
+}
+
+/// SSB-демодулятор фазовым методом: сигнал переносится с несущей с фазой `phase` в
+/// комплексную огибающую z = I + jQ, звук выбранной полосы — (I ∓ H{Q}) / 2.
+/// Противоположная полоса, как и у настоящего приёмника, подавляется
+pub fn demodulate(
+    samples: &[f32],
+    sideband: Sideband,
+    backend: ConvolutionBackend,
+    phase: impl Fn(usize) -> f64,
+) -> Vec<f32> {
+    let baseband: Vec<(f32, f32)> = to_iq(samples, Sideband::Upper, backend)
+        .into_iter()
+        .enumerate()
+        .map(|(n, (i, q))| {
+            // z · e^{−jφ}
+            let (sin, cos) = phase(n).sin_cos();
+            let (sin, cos) = (sin as f32, cos as f32);
+            (i * cos + q * sin, q * cos - i * sin)
+        })
+        .collect();
+    let quadrature: Vec<f32> = baseband.iter().map(|&(_, q)| q).collect();
+    let sign = match sideband {
+        Sideband::Upper => -1.0,
+        Sideband::Lower => 1.0,
+    };
+    baseband
+        .iter()
+        .zip(hilbert(&quadrature, backend))
+        .map(|(&(i, _), hq)| 0.5 * (i + sign * hq))
+        .collect()
+}
+
+/// Сдвигает спектр сигнала на переменную частоту: `phase[i]` — накопленная фаза сдвига
+/// в радианах для сэмпла `i`. Это модуляция верхней боковой полосы с несущей `phase`:
+/// Re{(x + jH{x}) · e^{jφ}}
+pub fn shift_by_phase(
+    samples: &mut [f32],
+    backend: ConvolutionBackend,
+    phase: impl Fn(usize) -> f64,
+) {
+    let shifted = modulate(samples, Sideband::Upper, backend, phase);
+    samples.copy_from_slice(&shifted);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dsp::goertzel_power;
+    use std::f64::consts::PI;
+
+    const FS: u32 = 44100;
+    const LEN: usize = 8820;
+
+    fn tone(freq: f64) -> Vec<f32> {
+        (0..LEN)
+            .map(|i| (2.0 * PI * freq * i as f64 / FS as f64).cos() as f32)
+            .collect()
+    }
+
+    fn carrier(freq: f64) -> impl Fn(usize) -> f64 {
+        move |i| 2.0 * PI * freq * i as f64 / FS as f64
+    }
+
+    /// Мощность на частоте `freq` вдали от краёв, где фильтр Гильберта неполон
+    fn power(samples: &[f32], freq: f32) -> f32 {
+        goertzel_power(&samples[441..LEN - 441], freq, FS)
+    }
+
+    #[test]
+    fn modulator_places_tone_on_selected_sideband() {
+        let audio = tone(1000.0);
+        let usb = modulate(
+            &audio,
+            Sideband::Upper,
+            ConvolutionBackend::Auto,
+            carrier(5000.0),
+        );
+        assert!(power(&usb, 4000.0) < power(&usb, 6000.0) * 1e-3);
+        let lsb = modulate(
+            &audio,
+            Sideband::Lower,
+            ConvolutionBackend::Auto,
+            carrier(5000.0),
+        );
+        assert!(power(&lsb, 6000.0) < power(&lsb, 4000.0) * 1e-3);
+    }
+
+    #[test]
+    fn demodulator_recovers_audio() {
+        let audio = tone(1500.0);
+        for sideband in [Sideband::Upper, Sideband::Lower] {
+            let rf = modulate(&audio, sideband, ConvolutionBackend::Auto, carrier(8000.0));
+            let back = demodulate(&rf, sideband, ConvolutionBackend::Auto, carrier(8000.0));
+            for (a, b) in audio[441..LEN - 441].iter().zip(&back[441..LEN - 441]) {
+                assert!((a - b).abs() < 2e-2, "{} {a} != {b}", sideband.name());
+            }
+        }
+    }
+
+    #[test]
+    fn demodulator_rejects_opposite_sideband() {
+        // Тон на несущей + 1200 Гц — верхняя полоса; приём нижней его не слышит
+        let rf = tone(9200.0);
+        let usb = demodulate(
+            &rf,
+            Sideband::Upper,
+            ConvolutionBackend::Auto,
+            carrier(8000.0),
+        );
+        let lsb = demodulate(
+            &rf,
+            Sideband::Lower,
+            ConvolutionBackend::Auto,
+            carrier(8000.0),
+        );
+        let wanted = power(&usb, 1200.0);
+        assert!(wanted > 0.0);
+        assert!(power(&lsb, 1200.0) < wanted * 1e-3);
+    }
+
+    #[test]
+    fn lower_sideband_iq_is_conjugate() {
+        let audio = tone(700.0);
+        let usb = to_iq(&audio, Sideband::Upper, ConvolutionBackend::Direct);
+        let lsb = to_iq(&audio, Sideband::Lower, ConvolutionBackend::Direct);
+        for (u, l) in usb.iter().zip(&lsb) {
+            assert_eq!((u.0, -u.1), *l);
+        }
+    }
+}
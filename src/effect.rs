@@ -0,0 +1,906 @@
+use crate::buffer::SampleBuffer;
+use crate::dsp::ConvolutionBackend;
+use crate::encoder::EncoderParams;
+use crate::error::{Result, SstvError};
+use crate::messages;
+use crate::modes::{MARTIN_M1, ModeTiming, ScanOffset};
+use crate::processor::ProcessingParams;
+use image::{DynamicImage, RgbImage};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::ops::Range;
+use std::str::FromStr;
+use std::sync::atomic::AtomicBool;
+
+/// Что конвейер делает с NaN и бесконечностями, появившимися в сигнале после эффекта
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SanitizeMode {
+    /// Не проверять: испорченные сэмплы уходят дальше по конвейеру, в WAV и декодер
+    #[default]
+    Off,
+    /// Заменять NaN тишиной, бесконечности — полной шкалой того же знака, и сообщать,
+    /// после какого эффекта они появились
+    Repair,
+    /// Прерывать обработку ошибкой, называющей эффект, — для отладки эффектов
+    Strict,
+}
+
+impl SanitizeMode {
+    pub const ALL: &'static [SanitizeMode] = &[
+        SanitizeMode::Off,
+        SanitizeMode::Repair,
+        SanitizeMode::Strict,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            SanitizeMode::Off => messages::sanitize_off(),
+            SanitizeMode::Repair => messages::sanitize_repair(),
+            SanitizeMode::Strict => messages::sanitize_strict(),
+        }
+    }
+}
+
+impl clap::ValueEnum for SanitizeMode {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[Self::Off, Self::Repair, Self::Strict]
+    }
+
+    fn to_possible_value<'a>(&self) -> Option<clap::builder::PossibleValue> {
+        Some(match self {
+            Self::Off => clap::builder::PossibleValue::new("off"),
+            Self::Repair => clap::builder::PossibleValue::new("repair"),
+            Self::Strict => clap::builder::PossibleValue::new("strict"),
+        })
+    }
+}
+
+/// Эффект, после которого в сигнале нашлись NaN или бесконечности
+#[derive(Clone, Debug, PartialEq)]
+pub struct SanitizeEvent {
+    /// Название эффекта, см. [`ChannelEffect::name`]
+    pub effect: &'static str,
+    /// Сэмплов NaN
+    pub nan: usize,
+    /// Сэмплов ±∞
+    pub infinite: usize,
+    /// Первый испорченный сэмпл
+    pub first: usize,
+}
+
+impl SanitizeEvent {
+    pub fn describe(&self) -> String {
+        messages::sanitize_describe(self.effect, self.nan, self.infinite, self.first)
+    }
+}
+
+/// Ищет в `samples` NaN и бесконечности, появившиеся после эффекта `effect`, и при
+/// `repair` заменяет их: NaN — нулём, ±∞ — ±1. `None`, если сигнал цел
+pub fn sanitize(samples: &mut [f32], effect: &'static str, repair: bool) -> Option<SanitizeEvent> {
+    let first = samples.iter().position(|x| !x.is_finite())?;
+    let mut event = SanitizeEvent {
+        effect,
+        nan: 0,
+        infinite: 0,
+        first,
+    };
+    for sample in &mut samples[first..] {
+        if sample.is_nan() {
+            event.nan += 1;
+        } else if sample.is_infinite() {
+            event.infinite += 1;
+        } else {
+            continue;
+        }
+        if repair {
+            *sample = if sample.is_nan() {
+                0.0
+            } else {
+                sample.signum()
+            };
+        }
+    }
+    Some(event)
+}
+
+/// Что эффект сделал бы с сигналом, оценённое без изменения буфера, см.
+/// [`ChannelEffect::analyze`]. Мощности — относительно мощности входного сигнала
+#[derive(Clone, Debug, PartialEq)]
+pub struct EffectEstimate {
+    /// Название эффекта, см. [`ChannelEffect::name`]
+    pub effect: &'static str,
+    /// Описание настроек, см. [`ChannelEffect::describe`]
+    pub settings: String,
+    /// Мощность, которую эффект добавит к сигналу (шум, помехи, призрак, эхо), дБ
+    pub added_power_db: Option<f32>,
+    /// Изменение мощности самого сигнала (замирания, выпадения), дБ
+    pub gain_db: Option<f32>,
+    /// Наибольшая задержка добавленной копии сигнала, мс
+    pub delay_ms: Option<f32>,
+    /// Та же задержка в сэмплах сигнала. Если эффект её не задал, её считает
+    /// [`analyze_effects`] из `delay_ms`
+    pub latency_samples: Option<usize>,
+    /// Групповая задержка самого сигнала, сэмплов, см. [`ChannelEffect::group_delay`]
+    pub group_delay_samples: Option<f32>,
+    /// Сдвиг копии на изображении режима при задержке `delay_ms`, см.
+    /// [`ModeSpec::scan_offset`](crate::modes::ModeSpec::scan_offset)
+    pub ghost_offset: Option<ScanOffset>,
+    /// На сколько эффект удлиняет сигнал, сэмплов, см. [`ChannelEffect::output_len`]
+    pub added_samples: Option<usize>,
+    /// Полоса пропускания фильтра, Гц
+    pub passband_hz: Option<(f32, f32)>,
+    /// Наименьший и наибольший сдвиг частоты за время сигнала, Гц
+    pub offset_hz: Option<(f32, f32)>,
+    /// Доля сигнала, которую затронет эффект, 0–1
+    pub affected: Option<f32>,
+}
+
+impl EffectEstimate {
+    /// Оценка без чисел: только название и настройки
+    pub fn new(effect: &'static str, settings: String) -> Self {
+        Self {
+            effect,
+            settings,
+            added_power_db: None,
+            gain_db: None,
+            delay_ms: None,
+            latency_samples: None,
+            group_delay_samples: None,
+            ghost_offset: None,
+            added_samples: None,
+            passband_hz: None,
+            offset_hz: None,
+            affected: None,
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some(db) = self.added_power_db {
+            parts.push(messages::estimate_added_power(db));
+        }
+        if let Some(db) = self.gain_db {
+            parts.push(messages::estimate_gain(db));
+        }
+        if let Some(ms) = self.delay_ms {
+            parts.push(messages::estimate_delay(ms));
+        }
+        if let Some(samples) = self.latency_samples {
+            parts.push(messages::estimate_latency(samples));
+        }
+        if let Some(samples) = self.group_delay_samples {
+            parts.push(messages::estimate_group_delay(samples));
+        }
+        if let Some(offset) = &self.ghost_offset {
+            parts.push(offset.describe());
+        }
+        if let Some(samples) = self.added_samples {
+            parts.push(messages::estimate_added(samples));
+        }
+        if let Some((low, high)) = self.passband_hz {
+            parts.push(messages::estimate_passband(low, high));
+        }
+        if let Some((min, max)) = self.offset_hz {
+            parts.push(messages::estimate_offset(min, max));
+        }
+        if let Some(fraction) = self.affected {
+            parts.push(messages::estimate_affected(fraction * 100.0));
+        }
+        if parts.is_empty() {
+            return self.settings.clone();
+        }
+        format!("{} — {}", self.settings, parts.join(", "))
+    }
+}
+
+/// Отношение мощностей в дБ; нулевое отношение — очень большое ослабление, а не −∞
+pub fn power_db(ratio: f32) -> f32 {
+    10.0 * ratio.max(1e-12).log10()
+}
+
+/// Сводка канала: оценки всех включённых эффектов по порядку конвейера
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ChannelSummary {
+    pub effects: Vec<EffectEstimate>,
+    /// Длина сигнала на входе конвейера, сэмплов
+    pub input_len: usize,
+    /// Частота дискретизации сигнала эффектов, Гц
+    pub sample_rate: u32,
+}
+
+impl ChannelSummary {
+    /// Сколько сэмплов добавляют все эффекты вместе
+    pub fn added_samples(&self) -> usize {
+        self.effects.iter().filter_map(|e| e.added_samples).sum()
+    }
+
+    /// Длина сигнала на выходе конвейера, сэмплов
+    pub fn output_len(&self) -> usize {
+        self.input_len + self.added_samples()
+    }
+
+    /// Наибольшее запаздывание копии сигнала среди эффектов, сэмплов
+    pub fn latency_samples(&self) -> Option<usize> {
+        self.effects.iter().filter_map(|e| e.latency_samples).max()
+    }
+
+    /// Групповая задержка сигнала всеми эффектами вместе, сэмплов: на столько выход
+    /// конвейера отстаёт от входа
+    pub fn group_delay_samples(&self) -> f32 {
+        self.effects
+            .iter()
+            .filter_map(|e| e.group_delay_samples)
+            .sum()
+    }
+
+    /// Та же задержка в миллисекундах
+    pub fn group_delay_ms(&self) -> f32 {
+        self.group_delay_samples() * 1000.0 / self.sample_rate.max(1) as f32
+    }
+
+    /// Переводит число сэмплов сигнала в миллисекунды
+    fn samples_to_ms(&self, samples: usize) -> f32 {
+        samples as f32 * 1000.0 / self.sample_rate.max(1) as f32
+    }
+
+    /// Отношение сигнал/(шум + помехи) на выходе, дБ: сумма добавленных мощностей
+    /// против мощности сигнала после всех изменений усиления. Порядок эффектов не
+    /// учитывается: шум, добавленный до замираний, считается не замирающим. `None`,
+    /// если ни один эффект ничего не добавляет
+    pub fn snr_db(&self) -> Option<f32> {
+        let added: f32 = self
+            .effects
+            .iter()
+            .filter_map(|e| e.added_power_db)
+            .map(|db| 10f32.powf(db / 10.0))
+            .sum();
+        if added <= 0.0 {
+            return None;
+        }
+        let gain_db: f32 = self.effects.iter().filter_map(|e| e.gain_db).sum();
+        Some(gain_db - 10.0 * added.log10())
+    }
+
+    /// Построчное описание: эффект за эффектом, затем итоговое С/Ш
+    pub fn describe(&self) -> String {
+        if self.effects.is_empty() {
+            return messages::estimate_clean().to_string();
+        }
+        let mut lines: Vec<String> = self.effects.iter().map(EffectEstimate::describe).collect();
+        if let Some(snr) = self.snr_db() {
+            lines.push(messages::estimate_snr(snr));
+        }
+        if self.sample_rate > 0 {
+            lines.push(messages::estimate_length(
+                self.samples_to_ms(self.output_len()) / 1000.0,
+                self.sample_rate,
+                self.samples_to_ms(self.added_samples()),
+            ));
+            let delay = self.group_delay_samples();
+            if delay > 0.0 {
+                lines.push(messages::estimate_total_group_delay(
+                    delay,
+                    self.group_delay_ms(),
+                ));
+            }
+        }
+        lines.join("\n")
+    }
+}
+
+/// Смешивание выхода эффекта со входом: `wet · эффект(x) + dry · x`. По умолчанию
+/// эффект целиком заменяет сигнал (wet 1, dry 0)
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EffectMix {
+    /// Доля выхода эффекта, 0–1
+    pub wet: f32,
+    /// Доля входного сигнала, 0–1
+    pub dry: f32,
+}
+
+impl Default for EffectMix {
+    fn default() -> Self {
+        Self { wet: 1.0, dry: 0.0 }
+    }
+}
+
+impl EffectMix {
+    /// Смешивание не меняет выход эффекта
+    pub fn is_identity(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Доли, обрезанные до 0–1
+    pub fn clamped(&self) -> Self {
+        Self {
+            wet: self.wet.clamp(0.0, 1.0),
+            dry: self.dry.clamp(0.0, 1.0),
+        }
+    }
+
+    /// Смешивает выход эффекта `wet` на месте со входом `dry`. Если эффект изменил
+    /// длину сигнала, за концом входа он считается тишиной
+    pub fn blend(&self, wet: &mut [f32], dry: &[f32]) {
+        let Self { wet: w, dry: d } = self.clamped();
+        for (i, sample) in wet.iter_mut().enumerate() {
+            *sample = w * *sample + d * dry.get(i).copied().unwrap_or(0.0);
+        }
+    }
+
+    /// Поправляет оценку эффекта на смешивание: добавленная мощность ослабляется
+    /// долей wet, изменение усиления сигнала считается по сумме долей
+    pub fn adjust(&self, estimate: &mut EffectEstimate) {
+        let Self { wet, dry } = self.clamped();
+        if let Some(db) = &mut estimate.added_power_db {
+            *db += power_db(wet * wet);
+        }
+        let gain = estimate.gain_db.map_or(1.0, |db| 10f32.powf(db / 20.0));
+        let mixed = wet * gain + dry;
+        if estimate.gain_db.is_some() || mixed != 1.0 {
+            estimate.gain_db = Some(power_db(mixed * mixed));
+        }
+        estimate.settings = format!("{} ({})", estimate.settings, self);
+    }
+}
+
+impl std::fmt::Display for EffectMix {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&messages::effect_mix(self.wet, self.dry))
+    }
+}
+
+/// Встроенный эффект, у которого есть свои доли смешивания [`EffectMix`]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MixTarget {
+    Retarder,
+    Multipath,
+    Fading,
+    Drift,
+    Interference,
+    Noise,
+    Blanker,
+    Filter,
+    Notch,
+    Agc,
+    Dropout,
+}
+
+impl MixTarget {
+    pub const ALL: &'static [MixTarget] = &[
+        MixTarget::Retarder,
+        MixTarget::Multipath,
+        MixTarget::Fading,
+        MixTarget::Drift,
+        MixTarget::Interference,
+        MixTarget::Noise,
+        MixTarget::Blanker,
+        MixTarget::Filter,
+        MixTarget::Notch,
+        MixTarget::Agc,
+        MixTarget::Dropout,
+    ];
+
+    /// Название в `--mix` и раздел пресета
+    pub fn key(&self) -> &'static str {
+        match self {
+            MixTarget::Retarder => "retarder",
+            MixTarget::Multipath => "multipath",
+            MixTarget::Fading => "fading",
+            MixTarget::Drift => "drift",
+            MixTarget::Interference => "interference",
+            MixTarget::Noise => "noise",
+            MixTarget::Blanker => "blanker",
+            MixTarget::Filter => "filter",
+            MixTarget::Notch => "notch",
+            MixTarget::Agc => "agc",
+            MixTarget::Dropout => "dropout",
+        }
+    }
+
+    /// Поля долей в пресете, для замечаний [`ProcessingParams::validate`]
+    pub fn fields(&self) -> (&'static str, &'static str) {
+        match self {
+            MixTarget::Retarder => ("retarder.mix.wet", "retarder.mix.dry"),
+            MixTarget::Multipath => ("multipath.mix.wet", "multipath.mix.dry"),
+            MixTarget::Fading => ("fading.mix.wet", "fading.mix.dry"),
+            MixTarget::Drift => ("drift.mix.wet", "drift.mix.dry"),
+            MixTarget::Interference => ("interference.mix.wet", "interference.mix.dry"),
+            MixTarget::Noise => ("noise.mix.wet", "noise.mix.dry"),
+            MixTarget::Blanker => ("blanker.mix.wet", "blanker.mix.dry"),
+            MixTarget::Filter => ("filter.mix.wet", "filter.mix.dry"),
+            MixTarget::Notch => ("notch.mix.wet", "notch.mix.dry"),
+            MixTarget::Agc => ("agc.mix.wet", "agc.mix.dry"),
+            MixTarget::Dropout => ("dropout.mix.wet", "dropout.mix.dry"),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            MixTarget::Retarder => messages::retarder(),
+            MixTarget::Multipath => messages::multipath(),
+            MixTarget::Fading => messages::fading(),
+            MixTarget::Drift => messages::drift(),
+            MixTarget::Interference => messages::interference(),
+            MixTarget::Noise => messages::noise(),
+            MixTarget::Blanker => messages::blanker(),
+            MixTarget::Filter => messages::filter(),
+            MixTarget::Notch => messages::notch(),
+            MixTarget::Agc => messages::agc(),
+            MixTarget::Dropout => messages::dropout(),
+        }
+    }
+}
+
+/// Доли смешивания одного эффекта из `--mix`: `эффект:wet[:dry]`
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct MixSetting {
+    pub target: MixTarget,
+    pub mix: EffectMix,
+}
+
+impl FromStr for MixSetting {
+    type Err = SstvError;
+
+    /// Разбирает `эффект:wet[:dry]`; без dry вход не подмешивается
+    fn from_str(s: &str) -> Result<Self> {
+        let parts: Vec<&str> = s.split(':').collect();
+        if !(2..=3).contains(&parts.len()) {
+            return Err(SstvError::invalid(
+                "mix",
+                messages::expected_format(messages::format_mix(), s),
+            ));
+        }
+        let target = MixTarget::ALL
+            .iter()
+            .copied()
+            .find(|t| t.key() == parts[0].trim())
+            .ok_or_else(|| {
+                let known: Vec<_> = MixTarget::ALL.iter().map(MixTarget::key).collect();
+                SstvError::invalid(
+                    "mix",
+                    messages::mix_unknown_effect(parts[0], &known.join(", ")),
+                )
+            })?;
+        let parse = |v: &str, what: &str| -> Result<f32> {
+            v.trim()
+                .parse()
+                .ok()
+                .filter(|x: &f32| (0.0..=1.0).contains(x))
+                .ok_or_else(|| SstvError::invalid("mix", messages::mix_share(what, v)))
+        };
+        Ok(Self {
+            target,
+            mix: EffectMix {
+                wet: parse(parts[1], "wet")?,
+                dry: match parts.get(2) {
+                    Some(d) => parse(d, "dry")?,
+                    None => 0.0,
+                },
+            },
+        })
+    }
+}
+
+impl std::fmt::Display for MixSetting {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.target.key(), self.mix.wet)?;
+        if self.mix.dry != 0.0 {
+            write!(f, ":{}", self.mix.dry)?;
+        }
+        Ok(())
+    }
+}
+
+/// Данные, доступные эффектам канала при обработке сигнала. Частоты здесь нет:
+/// эффект берёт её из обрабатываемого [`SampleBuffer`]
+pub struct EffectContext<'a> {
+    /// Закодированный сигнал картинки-призрака со своей частотой, если она задана
+    pub retarder: Option<&'a SampleBuffer>,
+    /// Сэмплы тела изображения (без заголовка и добавленных участков)
+    pub image_span: Range<usize>,
+    /// Общий генератор случайных чисел прогона; при заданном зерне результат воспроизводим
+    pub rng: &'a RefCell<StdRng>,
+    /// Способ вычисления свёрток (фильтр Гильберта, многолучёвость)
+    pub convolution: ConvolutionBackend,
+    /// Обрабатывать куски длинного сигнала параллельно, см. [`crate::dsp::for_each_chunk`]
+    pub parallel: bool,
+    /// Проверка сигнала на NaN и бесконечности после каждого эффекта
+    pub sanitize: SanitizeMode,
+}
+
+impl EffectContext<'_> {
+    /// Разметка строк и пикселей сигнала по телу изображения
+    pub fn timing(&self) -> ModeTiming {
+        MARTIN_M1.timing_from_span(self.image_span.clone())
+    }
+}
+
+/// Данные, доступные эффектам при обработке сигнала блоками, см.
+/// [`ChannelEffect::block_processor`]. Сигнал целиком нигде не хранится, поэтому его
+/// длину, тело изображения и уровень заранее сообщает кодер
+pub struct StreamContext<'a> {
+    /// Частота дискретизации блоков, Гц
+    pub sample_rate: u32,
+    /// Длина всего сигнала, сэмплов
+    pub len: usize,
+    /// Сэмплы тела изображения (без заголовка и опознавателя)
+    pub image_span: Range<usize>,
+    /// СКЗ чистого сигнала кодера: по нему шум выбирает свой уровень
+    pub signal_rms: f32,
+    /// Параметры кодера; призрак кодируется ими же
+    pub encoder: &'a EncoderParams,
+    /// Картинка призрака, уже выбранная по источнику ретардера: для `SelfEcho` — само
+    /// основное изображение, для `Flipped` и `Inverted` — построенная из него
+    pub retarder_image: Option<&'a DynamicImage>,
+    /// Общий генератор прогона; обработчики берут из него зёрна своих генераторов
+    pub rng: &'a RefCell<StdRng>,
+}
+
+impl StreamContext<'_> {
+    /// Собственный генератор обработчика блоков, засеянный из общего генератора прогона
+    pub fn fork_rng(&self) -> StdRng {
+        StdRng::seed_from_u64(self.rng.borrow_mut().random())
+    }
+}
+
+/// Эффект, обрабатывающий сигнал блоками по порядку, см. [`ChannelEffect::block_processor`].
+/// Состояние между блоками — линии задержки, генератор, призрак — он хранит сам
+pub trait BlockProcessor: Send {
+    /// Обрабатывает на месте блок `block`, первый сэмпл которого — сэмпл `start` сигнала.
+    /// Блоки приходят подряд, без пропусков
+    fn process_block(&mut self, block: &mut [f32], start: usize) -> Result<()>;
+
+    /// На сколько сэмплов выход отстаёт от входа: фильтрам, которым нужны будущие
+    /// сэмплы, их взять неоткуда, кроме задержки. Цепочка задерживает на столько же
+    /// необработанную долю смешивания и складывает задержки в общую
+    fn latency(&self) -> usize {
+        0
+    }
+
+    /// Состояние между блоками для контрольной точки потока: истории фильтров, линии
+    /// задержки, огибающие, зёрна генераторов. Генератор сохраняется через
+    /// [`checkpoint_rng`], поэтому после точки прогон идёт уже от нового зерна — и
+    /// продолжение из точки совпадает с ним сэмпл в сэмпл. `Null` — хранить нечего
+    fn save_state(&mut self) -> Result<serde_json::Value> {
+        Ok(serde_json::Value::Null)
+    }
+
+    /// Возвращает состояние из [`save_state`](Self::save_state) обработчику, заново
+    /// построенному тем же эффектом с теми же настройками и контекстом
+    fn restore_state(&mut self, _state: &serde_json::Value) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Зерно генератора для контрольной точки: берётся из самого генератора, и генератор
+/// тут же засевается им заново, так что продолжение после
+/// [`BlockProcessor::restore_state`] совпадает с продолжением после точки
+pub fn checkpoint_rng(rng: &mut StdRng) -> u64 {
+    let seed = rng.random();
+    *rng = StdRng::seed_from_u64(seed);
+    seed
+}
+
+/// Состояние обработчика блоков для [`BlockProcessor::save_state`]
+pub fn to_block_state<T: Serialize>(state: &T) -> Result<serde_json::Value> {
+    serde_json::to_value(state).map_err(|e| SstvError::invalid("state", e.to_string()))
+}
+
+/// Разбирает состояние из [`BlockProcessor::restore_state`]; `effect` называет эффект
+/// в ошибке, если состояние сохранено другим эффектом или повреждено
+pub fn from_block_state<T: serde::de::DeserializeOwned>(
+    state: &serde_json::Value,
+    effect: &'static str,
+) -> Result<T> {
+    T::deserialize(state)
+        .map_err(|e| SstvError::invalid("state", messages::state_mismatch(effect, &e.to_string())))
+}
+
+/// Обработчик блоков, оставляющий сигнал как есть: эффекту нечего подмешивать
+pub struct Bypass;
+
+impl BlockProcessor for Bypass {
+    fn process_block(&mut self, _block: &mut [f32], _start: usize) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Эффект канала связи, применяемый к закодированному сигналу
+///
+/// Эффекты выполняются по порядку своего положения в конвейере процессора;
+/// один и тот же тип эффекта может встречаться в конвейере несколько раз.
+pub trait ChannelEffect: Send {
+    /// Название эффекта
+    fn name(&self) -> &'static str;
+
+    /// Применяет эффект к моно-буферу на месте; задержки, фильтры и огибающие
+    /// считаются по частоте буфера
+    fn apply(&self, buffer: &mut SampleBuffer, ctx: &EffectContext) -> Result<()>;
+
+    /// Отключённые эффекты пропускаются конвейером
+    fn is_enabled(&self) -> bool {
+        true
+    }
+
+    /// Описание текущих настроек эффекта
+    fn describe(&self) -> String {
+        self.name().to_string()
+    }
+
+    /// Обновляет настройки из общих параметров обработки перед прогоном конвейера.
+    /// Встроенные эффекты берут отсюда свои параметры, сторонние могут игнорировать
+    fn sync_params(&mut self, _params: &ProcessingParams) {}
+
+    /// Доли смешивания выхода эффекта со входом; конвейер смешивает их сам, после
+    /// [`apply`](Self::apply). По умолчанию выход заменяет вход
+    fn mix(&self) -> EffectMix {
+        EffectMix::default()
+    }
+
+    /// Оценивает, что эффект сделал бы с буфером, не меняя его. Случайные значения
+    /// берутся из копии генератора контекста, поэтому оценка не сдвигает прогон.
+    /// По умолчанию — только название и описание настроек
+    fn analyze(&self, _buffer: &SampleBuffer, _ctx: &EffectContext) -> EffectEstimate {
+        EffectEstimate::new(self.name(), self.describe())
+    }
+
+    /// Длина выхода эффекта для входа длиной `input_len` с частотой `sample_rate`.
+    /// Встроенные эффекты длину не меняют; по умолчанию она та же
+    fn output_len(&self, input_len: usize, _sample_rate: u32) -> usize {
+        input_len
+    }
+
+    /// Групповая задержка самого сигнала на средней частоте изображения
+    /// ([`LEADER_HZ`](crate::modes::LEADER_HZ)), сэмплов частоты `sample_rate`: на столько
+    /// выход эффекта отстаёт от входа. Её дают фильтры БИХ; КИХ-фильтры с центрированным
+    /// ядром и эффекты без фильтров сигнал не сдвигают, по умолчанию 0. Задержку потоковой
+    /// обработки сверх этого сообщает [`BlockProcessor::latency`]
+    fn group_delay(&self, _sample_rate: u32) -> f32 {
+        0.0
+    }
+
+    /// Обработчик блоков для потоковой обработки, см. [`BlockProcessor`]. Эффекты, которым
+    /// нужен весь сигнал сразу, и настройки, которые блоками не посчитать, дают ошибку
+    /// с объяснением; по умолчанию потоковая обработка не поддерживается
+    fn block_processor(&self, _ctx: &StreamContext) -> Result<Box<dyn BlockProcessor>> {
+        Err(SstvError::invalid(
+            "streaming",
+            messages::streaming_unsupported(self.name()),
+        ))
+    }
+}
+
+/// Оценивает включённые эффекты по порядку, не меняя буфер: каждый видит один и тот же
+/// входной сигнал, а длина выхода считается по цепочке. Задержку в сэмплах и сдвиг копии
+/// на изображении достраивает из `delay_ms`, если эффект их не задал. Буфер должен быть
+/// моно
+pub fn analyze_effects(
+    effects: &[Box<dyn ChannelEffect>],
+    buffer: &SampleBuffer,
+    ctx: &EffectContext,
+) -> Result<ChannelSummary> {
+    buffer.check_mono()?;
+    let rate = buffer.rate;
+    let mut len = buffer.len();
+    let mut estimates = Vec::new();
+    for effect in effects.iter().filter(|e| e.is_enabled()) {
+        let mut estimate = effect.analyze(buffer, ctx);
+        let mix = effect.mix();
+        if !mix.is_identity() {
+            mix.adjust(&mut estimate);
+        }
+        let delay = effect.group_delay(rate);
+        if delay > 0.0 {
+            estimate.group_delay_samples.get_or_insert(delay);
+        }
+        if let Some(ms) = estimate.delay_ms.filter(|&ms| ms > 0.0) {
+            estimate
+                .latency_samples
+                .get_or_insert_with(|| (ms as f64 / 1000.0 * rate as f64).round() as usize);
+            estimate
+                .ghost_offset
+                .get_or_insert_with(|| MARTIN_M1.scan_offset(ms));
+        }
+        let output = effect.output_len(len, rate);
+        if output > len {
+            estimate.added_samples = Some(output - len);
+        }
+        len = output;
+        estimates.push(estimate);
+    }
+    Ok(ChannelSummary {
+        effects: estimates,
+        input_len: buffer.len(),
+        sample_rate: rate,
+    })
+}
+
+/// Групповая задержка сигнала включёнными эффектами вместе, сэмплов частоты
+/// `sample_rate`, см. [`ChannelEffect::group_delay`]. По ней выравнивают выход конвейера
+/// с чистым сигналом
+pub fn group_delay(effects: &[Box<dyn ChannelEffect>], sample_rate: u32) -> f32 {
+    effects
+        .iter()
+        .filter(|e| e.is_enabled())
+        .map(|e| e.group_delay(sample_rate))
+        .sum()
+}
+
+/// Применяет включённые эффекты по порядку. Возвращает эффекты, после которых
+/// проверка [`EffectContext::sanitize`] нашла и исправила испорченные сэмплы
+pub fn apply_effects(
+    effects: &[Box<dyn ChannelEffect>],
+    buffer: &mut SampleBuffer,
+    ctx: &EffectContext,
+) -> Result<Vec<SanitizeEvent>> {
+    apply_effects_with_progress(effects, buffer, ctx, &|_| {}, &AtomicBool::new(false))
+}
+
+/// Применяет включённые эффекты по порядку, сообщая долю выполненных эффектов.
+/// Выход эффекта смешивается со входом по его [`ChannelEffect::mix`]. Отмена
+/// проверяется перед каждым эффектом, NaN и бесконечности — после каждого,
+/// если это включено в [`EffectContext::sanitize`]. Буфер должен быть моно
+pub fn apply_effects_with_progress(
+    effects: &[Box<dyn ChannelEffect>],
+    buffer: &mut SampleBuffer,
+    ctx: &EffectContext,
+    progress: &dyn Fn(f32),
+    cancel: &AtomicBool,
+) -> Result<Vec<SanitizeEvent>> {
+    buffer.check_mono()?;
+    let enabled: Vec<_> = effects.iter().filter(|e| e.is_enabled()).collect();
+    let mut events = Vec::new();
+    for (k, effect) in enabled.iter().enumerate() {
+        SstvError::check_cancelled(cancel)?;
+        progress(k as f32 / enabled.len() as f32);
+        let mix = effect.mix();
+        let dry = (!mix.is_identity()).then(|| buffer.data.clone());
+        tracing::debug!("{}", effect.describe());
+        effect.apply(buffer, ctx)?;
+        if let Some(dry) = dry {
+            mix.blend(&mut buffer.data, &dry);
+        }
+        if ctx.sanitize == SanitizeMode::Off {
+            continue;
+        }
+        let repair = ctx.sanitize == SanitizeMode::Repair;
+        if let Some(event) = sanitize(buffer, effect.name(), repair) {
+            if !repair {
+                return Err(SstvError::invalid(
+                    event.effect,
+                    messages::effect_corrupted(&event.describe()),
+                ));
+            }
+            tracing::warn!("{}", event.describe());
+            events.push(event);
+        }
+    }
+    progress(1.0);
+    Ok(events)
+}
+
+/// Эффект, применяемый к декодированному изображению: то, что делали с картинкой
+/// преобразователь развёртки и монитор после демодуляции
+pub trait ImageEffect: Send {
+    /// Название эффекта
+    fn name(&self) -> &'static str;
+
+    /// Применяет эффект к изображению на месте
+    fn apply(&self, image: &mut RgbImage);
+
+    /// Отключённые эффекты пропускаются
+    fn is_enabled(&self) -> bool {
+        true
+    }
+
+    /// Описание текущих настроек эффекта
+    fn describe(&self) -> String {
+        self.name().to_string()
+    }
+}
+
+/// Применяет включённые эффекты изображения по порядку; без них изображение
+/// возвращается как есть
+pub fn apply_image_effects(effects: &[Box<dyn ImageEffect>], image: DynamicImage) -> DynamicImage {
+    let enabled: Vec<_> = effects.iter().filter(|e| e.is_enabled()).collect();
+    if enabled.is_empty() {
+        return image;
+    }
+    let mut rgb = image.to_rgb8();
+    for effect in enabled {
+        effect.apply(&mut rgb);
+    }
+    DynamicImage::ImageRgb8(rgb)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fading::{FadingParams, FadingProcessor};
+    use crate::filters::{
+        AgcParams, AgcProcessor, FilterKind, FilterParams, FilterProcessor, NotchParams,
+        NotchProcessor,
+    };
+    use crate::testing::{STREAM_RATE, with_stream_context};
+
+    /// Обработчик блоков `effect`, прогнанный блоками случайной длины
+    fn process_in_blocks(effect: &dyn ChannelEffect, samples: &[f32], seed: u64) -> Vec<f32> {
+        let mut processor =
+            with_stream_context(samples.len(), seed, |ctx| effect.block_processor(ctx)).unwrap();
+        let mut out = samples.to_vec();
+        let mut rng = StdRng::seed_from_u64(seed + 1);
+        let mut start = 0;
+        while start < out.len() {
+            let end = (start + rng.random_range(1..700)).min(out.len());
+            processor
+                .process_block(&mut out[start..end], start)
+                .unwrap();
+            start = end;
+        }
+        out
+    }
+
+    #[test]
+    fn blocks_match_whole_buffer() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let samples: Vec<f32> = (0..5000)
+            .map(|i| 0.4 * (i as f32 * 0.13).sin() + rng.random_range(-0.1..0.1))
+            .collect();
+        let whole = |apply: &dyn Fn(&mut SampleBuffer)| {
+            let mut buffer = SampleBuffer::mono(samples.clone(), STREAM_RATE);
+            apply(&mut buffer);
+            buffer.data
+        };
+        let assert_close = |a: &[f32], b: &[f32]| {
+            for (x, y) in a.iter().zip(b) {
+                assert!((x - y).abs() < 1e-5, "{x} != {y}");
+            }
+        };
+
+        let filter = FilterProcessor::new_with_params(FilterParams {
+            enabled: true,
+            kind: FilterKind::Bandpass,
+            ..FilterParams::default()
+        });
+        assert_close(
+            &whole(&|b| filter.apply_filter(b)),
+            &process_in_blocks(&filter, &samples, 1),
+        );
+        let notch = NotchProcessor::new_with_params(NotchParams {
+            enabled: true,
+            ..NotchParams::default()
+        });
+        assert_close(
+            &whole(&|b| notch.apply_notch(b)),
+            &process_in_blocks(&notch, &samples, 2),
+        );
+        let agc = AgcProcessor::new_with_params(AgcParams {
+            enabled: true,
+            ..AgcParams::default()
+        });
+        assert_close(
+            &whole(&|b| agc.apply_agc(b)),
+            &process_in_blocks(&agc, &samples, 3),
+        );
+
+        // Обработчик блоков засевает свой генератор первым числом общего
+        let fading = FadingProcessor::new_with_params(FadingParams {
+            depth: 0.8,
+            rate_hz: 20.0,
+            ..FadingParams::default()
+        });
+        let fork = StdRng::seed_from_u64(4).random::<u64>();
+        assert_close(
+            &whole(&|b| {
+                fading
+                    .apply_fading_with_rng(b, &mut StdRng::seed_from_u64(fork))
+                    .unwrap()
+            }),
+            &process_in_blocks(&fading, &samples, 4),
+        );
+    }
+}
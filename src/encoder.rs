@@ -0,0 +1,671 @@
+use crate::error::{Result, SstvError};
+use crate::messages;
+use crate::modes::{
+    BLACK_HZ, BREAK_MS, LEADER_HZ, LEADER_MS, MARTIN_M1, ModeSpec, ModeTiming, SYNC_HZ, VIS_BIT_MS,
+    VIS_ONE_HZ, VIS_ZERO_HZ, WHITE_HZ,
+};
+use crate::station_id::IdParams;
+use image::{DynamicImage, GenericImageView, Rgb, RgbImage, imageops::FilterType};
+use rsstv::SAMPLE_RATE;
+use rsstv::{common::SSTVMode, martinm1::MartinM1};
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+use std::ops::Range;
+
+/// Реализация кодера, используемая при обработке
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EncoderBackend {
+    Rsstv,
+    Native,
+}
+
+impl EncoderBackend {
+    pub const ALL: &'static [EncoderBackend] = &[EncoderBackend::Rsstv, EncoderBackend::Native];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            EncoderBackend::Rsstv => "rsstv",
+            EncoderBackend::Native => "Native",
+        }
+    }
+}
+
+impl clap::ValueEnum for EncoderBackend {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[Self::Rsstv, Self::Native]
+    }
+
+    fn to_possible_value<'a>(&self) -> Option<clap::builder::PossibleValue> {
+        Some(match self {
+            Self::Rsstv => clap::builder::PossibleValue::new("rsstv"),
+            Self::Native => clap::builder::PossibleValue::new("native"),
+        })
+    }
+}
+
+/// Параметры кодера. Временные настройки действуют только для встроенного кодера
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EncoderParams {
+    pub backend: EncoderBackend,
+    /// Передавать калибровочный заголовок и VIS
+    pub header: bool,
+    /// Длительность каждого из двух лидер-тонов, мс
+    pub leader_ms: f32,
+    /// Разрыв между лидер-тонами, мс
+    pub break_ms: f32,
+    /// Код VIS (7 бит, старший игнорируется)
+    pub vis_code: u8,
+    /// Длительность одного бита VIS, мс
+    pub vis_bit_ms: f32,
+    /// Ослабление VIS (от стартового до стопового бита) относительно остального сигнала, дБ
+    pub vis_attenuation_db: f32,
+    /// Число искажённых бит VIS 0–8: инвертируются первые по порядку передачи из семи бит
+    /// данных и бита чётности, чётность считается до искажения
+    pub vis_flip_bits: u8,
+    /// Масштаб временной сетки строки (1.0 — по стандарту)
+    pub timing_scale: f64,
+    /// Амплитуда тона
+    pub amplitude: f32,
+    /// Опознаватель станции после изображения; передаётся любым кодером
+    pub id: IdParams,
+}
+
+impl Default for EncoderParams {
+    fn default() -> Self {
+        Self {
+            backend: EncoderBackend::Rsstv,
+            header: true,
+            leader_ms: LEADER_MS as f32,
+            break_ms: BREAK_MS as f32,
+            vis_code: MARTIN_M1.vis_code,
+            vis_bit_ms: VIS_BIT_MS as f32,
+            vis_attenuation_db: 0.0,
+            vis_flip_bits: 0,
+            timing_scale: 1.0,
+            amplitude: 1.0,
+            id: IdParams::default(),
+        }
+    }
+}
+
+impl EncoderParams {
+    /// Проверяет, что длительности заголовка неотрицательны и вместе с масштабом сетки
+    /// не раздувают сигнал до часов
+    pub fn validate(&self) -> Result<()> {
+        let durations = [
+            ("leader_ms", messages::value_leader(), self.leader_ms),
+            ("break_ms", messages::value_break(), self.break_ms),
+            ("vis_bit_ms", messages::value_vis_bit(), self.vis_bit_ms),
+        ];
+        for (field, what, ms) in durations {
+            if !(0.0..=MAX_HEADER_TONE_MS).contains(&ms) {
+                return Err(SstvError::invalid(
+                    field,
+                    messages::header_duration(what, ms, MAX_HEADER_TONE_MS),
+                ));
+            }
+        }
+        if !(self.timing_scale > 0.0 && self.timing_scale <= MAX_TIMING_SCALE) {
+            return Err(SstvError::invalid(
+                "timing_scale",
+                messages::timing_scale_range(self.timing_scale, MAX_TIMING_SCALE),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Длительность передачи до опознавателя станции, мс: заголовок, все строки и, у rsstv,
+    /// его тишина по краям
+    pub fn transmission_ms(&self) -> f64 {
+        let lines_us = MARTIN_M1.height as f64 * MARTIN_M1.line_us();
+        let us = match self.backend {
+            EncoderBackend::Rsstv => {
+                RSSTV_PREFIX_US.iter().sum::<f64>() + lines_us + RSSTV_SUFFIX_US
+            }
+            EncoderBackend::Native => {
+                let header_us: f64 = if self.header {
+                    let encoder = NativeEncoder::new_with_params(self.clone());
+                    encoder.header_tones().iter().map(|t| t.duration_us).sum()
+                } else {
+                    0.0
+                };
+                header_us + lines_us * self.timing_scale
+            }
+        };
+        us / 1000.0
+    }
+}
+
+/// Наибольшая длительность тона заголовка, мс
+pub const MAX_HEADER_TONE_MS: f32 = 10_000.0;
+/// Наибольший масштаб временной сетки: изображение Martin M1 идёт тогда почти 20 минут
+pub const MAX_TIMING_SCALE: f64 = 10.0;
+
+/// Один тон сигнала: частота (0 — тишина) и длительность
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Tone {
+    pub freq_hz: f32,
+    pub duration_us: f64,
+}
+
+/// Назначение тона в сигнале кодера
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SegmentPurpose {
+    /// Лидер-тон заголовка
+    Leader,
+    /// Разрыв между лидер-тонами
+    Break,
+    /// Стартовый бит VIS
+    VisStart,
+    /// Бит данных VIS, 0 — младший
+    VisBit(u8),
+    /// Бит чётности VIS
+    VisParity,
+    /// Стоповый бит VIS
+    VisStop,
+    /// Синхроимпульс строки
+    Sync { line: u32 },
+    /// Площадка после синхроимпульса
+    Porch { line: u32 },
+    /// Пиксель `x` канала `channel` (0 — R, 1 — G, 2 — B)
+    Pixel { line: u32, channel: usize, x: u32 },
+    /// Разделитель после канала `channel`
+    Separator { line: u32, channel: usize },
+    /// Опознаватель с позывным после изображения, вместе с паузой перед ним
+    StationId,
+    /// Посылка FSK ID с текстом, вместе с паузой перед ней
+    IdText,
+}
+
+impl SegmentPurpose {
+    pub fn describe(&self) -> String {
+        let channel_name = |channel: usize| ["R", "G", "B"].get(channel).copied().unwrap_or("?");
+        match *self {
+            SegmentPurpose::Leader => messages::segment_leader().to_string(),
+            SegmentPurpose::Break => messages::segment_break().to_string(),
+            SegmentPurpose::VisStart => messages::segment_vis_start().to_string(),
+            SegmentPurpose::VisBit(bit) => messages::segment_vis_bit(bit),
+            SegmentPurpose::VisParity => messages::segment_vis_parity().to_string(),
+            SegmentPurpose::VisStop => messages::segment_vis_stop().to_string(),
+            SegmentPurpose::Sync { line } => messages::segment_sync(line),
+            SegmentPurpose::Porch { line } => messages::segment_porch(line),
+            SegmentPurpose::Pixel { line, channel, x } => {
+                messages::segment_pixel(line, channel_name(channel), x)
+            }
+            SegmentPurpose::Separator { line, channel } => {
+                messages::segment_separator(line, channel_name(channel))
+            }
+            SegmentPurpose::StationId => messages::segment_station_id().to_string(),
+            SegmentPurpose::IdText => messages::segment_id_text().to_string(),
+        }
+    }
+}
+
+/// Тон сигнала кодера с его назначением и положением
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Segment {
+    pub tone: Tone,
+    pub purpose: SegmentPurpose,
+    /// Начало от начала сигнала, мкс
+    pub start_us: f64,
+}
+
+impl Segment {
+    /// Конец от начала сигнала, мкс
+    pub fn end_us(&self) -> f64 {
+        self.start_us + self.tone.duration_us
+    }
+
+    /// Сэмплы тона в сигнале с частотой `sample_rate`; границы округляются так же, как
+    /// в [`synthesize`]
+    pub fn sample_range(&self, sample_rate: u32) -> Range<usize> {
+        let sample = |us: f64| (us * 1e-6 * sample_rate as f64).round() as usize;
+        sample(self.start_us)..sample(self.end_us())
+    }
+}
+
+/// Длительности тонов перед изображением у кодера rsstv, мкс: преамбула 1000 Гц,
+/// два лидер-тона с разрывом и 9 бит VIS
+const RSSTV_PREFIX_US: [f64; 13] = [
+    200_000.0, 300_000.0, 10_000.0, 300_000.0, 30_000.0, 30_000.0, 30_000.0, 30_000.0, 30_000.0,
+    30_000.0, 30_000.0, 30_000.0, 30_000.0,
+];
+/// Тишина после изображения у кодера rsstv, мкс
+const RSSTV_SUFFIX_US: f64 = 100_000.0;
+
+/// Обработчик, вызываемый перед кодированием каждой строки: получает пиксели строки
+/// в разрешении режима и номер строки, может изменить их на месте
+pub type LineHook = dyn FnMut(&mut [Rgb<u8>], usize) + Send;
+
+/// Кодирует изображение в сэмплы выбранной реализацией кодера
+pub fn encode(image: &DynamicImage, params: &EncoderParams) -> Vec<f32> {
+    encode_with_hook(image, params, None)
+}
+
+/// Кодирует изображение, вызывая обработчик для каждой строки. Опознаватель, если он
+/// задан, добавляется в конец с пиковой амплитудой изображения
+pub fn encode_with_hook(
+    image: &DynamicImage,
+    params: &EncoderParams,
+    hook: Option<&mut LineHook>,
+) -> Vec<f32> {
+    let mut samples = match params.backend {
+        EncoderBackend::Rsstv => {
+            // rsstv кодирует картинку целиком, поэтому строки правятся заранее
+            let image = match hook {
+                Some(hook) => apply_line_hook(image, &MARTIN_M1, hook),
+                None => image.clone(),
+            };
+            MartinM1::new().encode(image).to_samples()
+        }
+        EncoderBackend::Native => {
+            NativeEncoder::new_with_params(params.clone()).encode_with_hook(image, hook)
+        }
+    };
+    if params.id.is_transmitted() {
+        let peak = samples.iter().fold(0.0f32, |m, &v| m.max(v.abs()));
+        samples.extend(params.id.synthesize(peak));
+    }
+    samples
+}
+
+/// Диапазон сэмплов тела изображения в сигнале кодера длиной `len`, передискретизированном
+/// на частоту `sample_rate`
+pub fn image_span(params: &EncoderParams, sample_rate: u32, len: usize) -> Range<usize> {
+    // rsstv округляет каждый тон вниз до целого сэмпла
+    let samples = |us: f64| (us * 1e-6 * SAMPLE_RATE as f64) as usize;
+    let (start, tail) = match params.backend {
+        EncoderBackend::Rsstv => (
+            RSSTV_PREFIX_US.iter().map(|&us| samples(us)).sum(),
+            samples(RSSTV_SUFFIX_US),
+        ),
+        EncoderBackend::Native => {
+            let encoder = NativeEncoder::new_with_params(params.clone());
+            let header_us: f64 = if params.header {
+                encoder.header_tones().iter().map(|t| t.duration_us).sum()
+            } else {
+                0.0
+            };
+            ((header_us * 1e-6 * SAMPLE_RATE as f64).round() as usize, 0)
+        }
+    };
+    let tail = tail + params.id.sample_count();
+    // Как в `Signal::resampled`: на частоте кодера отступы не меняются
+    let scale = |i: usize| (i as f64 * sample_rate as f64 / SAMPLE_RATE as f64) as usize;
+    let (start, tail) = (scale(start), scale(tail));
+    start.min(len)..len.saturating_sub(tail).max(start.min(len))
+}
+
+/// Разметка строк и пикселей в сигнале кодера длиной `len` с частотой `sample_rate`
+pub fn timing(params: &EncoderParams, sample_rate: u32, len: usize) -> ModeTiming {
+    MARTIN_M1.timing_from_span(image_span(params, sample_rate, len))
+}
+
+/// Приводит изображение к разрешению режима и прогоняет каждую строку через обработчик
+pub fn apply_line_hook(image: &DynamicImage, spec: &ModeSpec, hook: &mut LineHook) -> DynamicImage {
+    let mut rgb = fit_to_mode(image, spec).to_rgb8();
+    for y in 0..spec.height {
+        let mut line = image_line(&rgb, y);
+        hook(&mut line, y as usize);
+        for (x, pixel) in line.into_iter().enumerate() {
+            rgb.put_pixel(x as u32, y, pixel);
+        }
+    }
+    DynamicImage::ImageRgb8(rgb)
+}
+
+/// Встроенный кодер Martin M1 с точным по сэмплам управлением временной сеткой
+pub struct NativeEncoder {
+    #[deprecated(since = "0.1.0", note = "используйте `params()`")]
+    pub params: EncoderParams,
+}
+
+impl Default for NativeEncoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[allow(deprecated)]
+impl NativeEncoder {
+    pub fn new() -> Self {
+        Self::new_with_params(EncoderParams {
+            backend: EncoderBackend::Native,
+            ..Default::default()
+        })
+    }
+
+    pub fn new_with_params(params: EncoderParams) -> Self {
+        Self { params }
+    }
+
+    /// Параметры, с которыми создан; для других нужен новый экземпляр
+    pub fn params(&self) -> &EncoderParams {
+        &self.params
+    }
+
+    /// Кодирует изображение в сэмплы
+    pub fn encode(&self, image: &DynamicImage) -> Vec<f32> {
+        self.encode_with_hook(image, None)
+    }
+
+    /// Кодирует изображение, вызывая обработчик для каждой строки
+    pub fn encode_with_hook(&self, image: &DynamicImage, hook: Option<&mut LineHook>) -> Vec<f32> {
+        let mut samples = synthesize(&self.tones_with_hook(image, hook), self.params.amplitude);
+        if self.params.vis_attenuation_db != 0.0 {
+            let gain = 10f32.powf(-self.params.vis_attenuation_db / 20.0);
+            let span = self.vis_span();
+            samples[span].iter_mut().for_each(|s| *s *= gain);
+        }
+        samples
+    }
+
+    /// Сэмплы VIS от стартового до стопового бита включительно; пусто без заголовка
+    pub fn vis_span(&self) -> Range<usize> {
+        if !self.params.header {
+            return 0..0;
+        }
+        // Границы — как в `synthesize`: по накопленному времени тонов
+        let tones = self.header_tones();
+        let sample = |tones: &[Tone]| {
+            let us: f64 = tones.iter().map(|t| t.duration_us).sum();
+            (us * 1e-6 * SAMPLE_RATE as f64).round() as usize
+        };
+        // Два лидер-тона и разрыв перед стартовым битом
+        sample(&tones[..3])..sample(&tones)
+    }
+
+    /// Строит последовательность тонов для изображения
+    pub fn tones(&self, image: &DynamicImage) -> Vec<Tone> {
+        self.tones_with_hook(image, None)
+    }
+
+    /// Строит последовательность тонов, отдавая каждую строку обработчику перед кодированием
+    pub fn tones_with_hook(
+        &self,
+        image: &DynamicImage,
+        mut hook: Option<&mut LineHook>,
+    ) -> Vec<Tone> {
+        let spec = &MARTIN_M1;
+        let mut tones = Vec::new();
+
+        if self.params.header {
+            tones.extend(self.header_tones());
+        }
+
+        let image = fit_to_mode(image, spec);
+        let rgb = image.to_rgb8();
+
+        for y in 0..spec.height {
+            let mut line = image_line(&rgb, y);
+            if let Some(hook) = hook.as_deref_mut() {
+                hook(&mut line, y as usize);
+            }
+            tones.extend(self.line_tones(&line));
+        }
+
+        tones
+    }
+
+    /// Тоны одной строки: синхроимпульс, площадка и три канала пикселей `line`
+    /// в разрешении режима
+    pub fn line_tones(&self, line: &[Rgb<u8>]) -> Vec<Tone> {
+        self.line_parts(line, 0)
+            .into_iter()
+            .map(|(t, _)| t)
+            .collect()
+    }
+
+    /// Калибровочный заголовок: два лидер-тона с разрывом и VIS с битом чётности
+    pub fn header_tones(&self) -> Vec<Tone> {
+        self.header_parts().into_iter().map(|(t, _)| t).collect()
+    }
+
+    /// Тоны сигнала [`encoder::encode`](encode) со встроенным кодером по порядку, с
+    /// назначением и началом каждого: заголовок, строки изображения и опознаватель, если
+    /// он передаётся. Строки строятся по мере обхода
+    pub fn segments<'a>(&'a self, image: &DynamicImage) -> impl Iterator<Item = Segment> + use<'a> {
+        let rgb = fit_to_mode(image, &MARTIN_M1).to_rgb8();
+        let header = if self.params.header {
+            self.header_parts()
+        } else {
+            Vec::new()
+        };
+        let lines =
+            (0..MARTIN_M1.height).flat_map(move |y| self.line_parts(&image_line(&rgb, y), y));
+        let id = &self.params.id;
+        let callsign = id
+            .tones()
+            .into_iter()
+            .map(|t| (t, SegmentPurpose::StationId));
+        let text = id
+            .text_tones()
+            .into_iter()
+            .map(|t| (t, SegmentPurpose::IdText));
+
+        header
+            .into_iter()
+            .chain(lines)
+            .chain(callsign)
+            .chain(text)
+            .scan(0.0, |start_us, (tone, purpose)| {
+                let segment = Segment {
+                    tone,
+                    purpose,
+                    start_us: *start_us,
+                };
+                *start_us += tone.duration_us;
+                Some(segment)
+            })
+    }
+
+    /// Тоны строки `y` с назначением, см. [`line_tones`](Self::line_tones)
+    fn line_parts(&self, line: &[Rgb<u8>], y: u32) -> Vec<(Tone, SegmentPurpose)> {
+        let spec = &MARTIN_M1;
+        let scale = self.params.timing_scale;
+        let range = WHITE_HZ - BLACK_HZ;
+        let mut tones = Vec::with_capacity(2 + spec.channel_order.len() * (line.len() + 1));
+
+        tones.push((
+            tone(SYNC_HZ, spec.sync_us * scale),
+            SegmentPurpose::Sync { line: y },
+        ));
+        tones.push((
+            tone(BLACK_HZ, spec.porch_us * scale),
+            SegmentPurpose::Porch { line: y },
+        ));
+
+        for &channel in &spec.channel_order {
+            for (x, pixel) in line.iter().enumerate() {
+                let value = pixel.0[channel] as f32 / 255.0;
+                let purpose = SegmentPurpose::Pixel {
+                    line: y,
+                    channel,
+                    x: x as u32,
+                };
+                tones.push((
+                    tone(BLACK_HZ + value * range, spec.pixel_us * scale),
+                    purpose,
+                ));
+            }
+            tones.push((
+                tone(BLACK_HZ, spec.separator_us * scale),
+                SegmentPurpose::Separator { line: y, channel },
+            ));
+        }
+
+        tones
+    }
+
+    /// Тоны заголовка с назначением, см. [`header_tones`](Self::header_tones)
+    fn header_parts(&self) -> Vec<(Tone, SegmentPurpose)> {
+        let p = &self.params;
+        let ms = |ms: f32| ms as f64 * 1000.0;
+        let mut tones = vec![
+            (tone(LEADER_HZ, ms(p.leader_ms)), SegmentPurpose::Leader),
+            (tone(SYNC_HZ, ms(p.break_ms)), SegmentPurpose::Break),
+            (tone(LEADER_HZ, ms(p.leader_ms)), SegmentPurpose::Leader),
+            (tone(SYNC_HZ, ms(p.vis_bit_ms)), SegmentPurpose::VisStart),
+        ];
+
+        // 7 бит данных младшим первым и бит чётного паритета; искажение — после
+        // подсчёта чётности, как ошибка в канале
+        let code = p.vis_code & 0x7F;
+        let parity = (code.count_ones() % 2) as u8;
+        let bits = code | parity << 7;
+        let flipped = bits ^ (((1u16 << p.vis_flip_bits.min(8)) - 1) as u8);
+        for bit in 0..8 {
+            let freq = if (flipped >> bit) & 1 == 1 {
+                VIS_ONE_HZ
+            } else {
+                VIS_ZERO_HZ
+            };
+            let purpose = if bit < 7 {
+                SegmentPurpose::VisBit(bit)
+            } else {
+                SegmentPurpose::VisParity
+            };
+            tones.push((tone(freq, ms(p.vis_bit_ms)), purpose));
+        }
+
+        tones.push((tone(SYNC_HZ, ms(p.vis_bit_ms)), SegmentPurpose::VisStop));
+
+        tones
+    }
+}
+
+/// Синтезирует сигнал с непрерывной фазой; границы тонов считаются по накопленному времени,
+/// поэтому округление до сэмпла не накапливает ошибку
+pub fn synthesize(tones: &[Tone], amplitude: f32) -> Vec<f32> {
+    let total_us: f64 = tones.iter().map(|t| t.duration_us).sum();
+    let mut samples = Vec::with_capacity((total_us * 1e-6 * SAMPLE_RATE as f64) as usize + 1);
+    let mut synth = Synth::new(amplitude);
+    for t in tones {
+        synth.push(t, &mut samples);
+    }
+    samples
+}
+
+/// Состояние [`synthesize`] между тонами: фаза и накопленное время. Сигнал можно
+/// синтезировать по частям — выход тот же, что и у синтеза целиком
+pub(crate) struct Synth {
+    amplitude: f32,
+    phase: f64,
+    end_us: f64,
+    /// Сэмплов выдано с начала сигнала
+    pos: usize,
+}
+
+impl Synth {
+    pub(crate) fn new(amplitude: f32) -> Self {
+        Self {
+            amplitude,
+            phase: 0.0,
+            end_us: 0.0,
+            pos: 0,
+        }
+    }
+
+    /// Дописывает сэмплы тона `t` в `out`
+    pub(crate) fn push(&mut self, t: &Tone, out: &mut Vec<f32>) {
+        let fs = SAMPLE_RATE as f64;
+        self.end_us += t.duration_us;
+        let end_sample = (self.end_us * 1e-6 * fs).round() as usize;
+        let step = 2.0 * PI * t.freq_hz as f64 / fs;
+        let gain = if t.freq_hz > 0.0 { self.amplitude } else { 0.0 };
+
+        while self.pos < end_sample {
+            out.push(self.phase.sin() as f32 * gain);
+            self.phase = (self.phase + step) % (2.0 * PI);
+            self.pos += 1;
+        }
+    }
+}
+
+/// Приводит изображение к разрешению режима
+pub fn fit_to_mode(image: &DynamicImage, spec: &ModeSpec) -> DynamicImage {
+    if image.dimensions() == (spec.width, spec.height) {
+        image.clone()
+    } else {
+        image.resize_exact(spec.width, spec.height, FilterType::Lanczos3)
+    }
+}
+
+pub(crate) fn image_line(rgb: &RgbImage, y: u32) -> Vec<Rgb<u8>> {
+    (0..rgb.width()).map(|x| *rgb.get_pixel(x, y)).collect()
+}
+
+fn tone(freq_hz: f32, duration_us: f64) -> Tone {
+    Tone {
+        freq_hz,
+        duration_us,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn segments_follow_mode_spec() {
+        let params = EncoderParams {
+            backend: EncoderBackend::Native,
+            id: IdParams {
+                callsign: "R1ABC".into(),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let encoder = NativeEncoder::new_with_params(params.clone());
+        let image = DynamicImage::ImageRgb8(RgbImage::from_fn(320, 256, |x, y| {
+            Rgb([x as u8, y as u8, (x ^ y) as u8])
+        }));
+        let segments: Vec<_> = encoder.segments(&image).collect();
+
+        let tones: Vec<_> = segments.iter().map(|s| s.tone).collect();
+        let image_tones = encoder.tones(&image);
+        assert_eq!(tones[..image_tones.len()], image_tones[..]);
+        for pair in segments.windows(2) {
+            assert_eq!(pair[0].end_us(), pair[1].start_us);
+        }
+        let last = segments.last().unwrap();
+        assert_eq!(last.purpose, SegmentPurpose::StationId);
+        // Опознаватель синтезируется отдельно и округляется сам по себе
+        let len = encode(&image, &params).len();
+        assert!(last.sample_range(SAMPLE_RATE as u32).end.abs_diff(len) <= 1);
+
+        let vis: Vec<_> = segments
+            .iter()
+            .filter_map(|s| match s.purpose {
+                SegmentPurpose::VisBit(bit) => Some(bit),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(vis, (0..7).collect::<Vec<_>>());
+
+        // Синхроимпульсы идут с периодом строки, пиксели — по спецификации режима
+        let syncs: Vec<_> = segments
+            .iter()
+            .filter(|s| matches!(s.purpose, SegmentPurpose::Sync { .. }))
+            .collect();
+        assert_eq!(syncs.len(), MARTIN_M1.height as usize);
+        assert!(syncs.iter().all(|s| s.tone.freq_hz == SYNC_HZ));
+        for pair in syncs.windows(2) {
+            assert!((pair[1].start_us - pair[0].start_us - MARTIN_M1.line_us()).abs() < 1e-3);
+        }
+        let pixel = segments
+            .iter()
+            .find(|s| {
+                s.purpose
+                    == SegmentPurpose::Pixel {
+                        line: 10,
+                        channel: 0,
+                        x: 20,
+                    }
+            })
+            .unwrap();
+        assert_eq!(pixel.tone.duration_us, MARTIN_M1.pixel_us);
+    }
+}
@@ -0,0 +1,188 @@
+use crate::error::{Result, SstvError};
+use crate::messages;
+use crate::metrics::{self, QualityReport};
+use crate::processor::{ProcessingParams, SSTVProcessor};
+use crate::spectrogram;
+use image::{DynamicImage, RgbImage};
+
+/// Ансамбль прогонов одного канала с разными зёрнами шума: среднее декодированных
+/// изображений и разброс каждого пикселя. Шум в прогонах независим, поэтому среднее
+/// чище любого из них — это выигрыш разнесённого приёма
+#[derive(Clone, Debug)]
+pub struct Ensemble {
+    /// Зёрна прогонов по порядку
+    pub seeds: Vec<u64>,
+    /// Среднее декодированных изображений
+    pub mean: DynamicImage,
+    /// Дисперсия каждого пикселя по прогонам, среднее по трём каналам, уровней²; по
+    /// строкам
+    pub variance: Vec<f32>,
+    /// Качество каждого прогона относительно переданного изображения
+    pub runs: Vec<QualityReport>,
+    /// Качество среднего
+    pub combined: QualityReport,
+}
+
+impl Ensemble {
+    /// Средний PSNR отдельных прогонов, дБ; бесконечные не учитываются
+    pub fn mean_run_psnr(&self) -> f64 {
+        let finite: Vec<f64> = self
+            .runs
+            .iter()
+            .map(|r| r.psnr)
+            .filter(|p| p.is_finite())
+            .collect();
+        finite.iter().sum::<f64>() / finite.len().max(1) as f64
+    }
+
+    /// Выигрыш усреднения: PSNR среднего минус средний PSNR прогонов, дБ
+    pub fn gain_db(&self) -> f64 {
+        self.combined.psnr - self.mean_run_psnr()
+    }
+
+    /// Средняя по пикселям дисперсия, уровней²
+    pub fn mean_variance(&self) -> f64 {
+        self.variance.iter().map(|&v| v as f64).sum::<f64>() / self.variance.len().max(1) as f64
+    }
+
+    /// Карта разброса: среднеквадратичное отклонение пикселя по прогонам в той же шкале,
+    /// что и карта ошибок [`metrics::diff_image`]
+    pub fn variance_image(&self) -> DynamicImage {
+        let width = self.mean.width();
+        let map = RgbImage::from_fn(width, self.mean.height(), |x, y| {
+            let variance = self.variance[(y * width + x) as usize];
+            spectrogram::heat((variance.sqrt() / 255.0).sqrt().min(1.0))
+        });
+        DynamicImage::ImageRgb8(map)
+    }
+
+    pub fn describe(&self) -> String {
+        messages::ensemble_describe(
+            self.seeds.len(),
+            self.mean_run_psnr(),
+            self.combined.psnr,
+            self.gain_db(),
+            self.mean_variance().sqrt(),
+        )
+    }
+}
+
+/// Прогоняет `image` через канал `processor` `runs` раз с зёрнами шума подряд от
+/// [`ProcessingParams::seed`](crate::processor::ProcessingParams::seed) (без него — от
+/// нуля) и усредняет декодированные изображения. Параметры процессора после прогонов
+/// прежние
+pub fn run(
+    processor: &mut SSTVProcessor,
+    image: &DynamicImage,
+    retarder: Option<&DynamicImage>,
+    runs: usize,
+) -> Result<Ensemble> {
+    if runs == 0 {
+        return Err(SstvError::invalid("ensemble", messages::ensemble_no_runs()));
+    }
+    let params = processor.params().clone();
+    let first = params.seed.unwrap_or(0);
+    let seeds: Vec<u64> = (0..runs as u64).map(|k| first.wrapping_add(k)).collect();
+    let sent = params.overlay_params().apply(image)?;
+
+    let mut sums: Vec<f64> = Vec::new();
+    let mut squares: Vec<f64> = Vec::new();
+    let mut size = None;
+    let mut reports = Vec::with_capacity(runs);
+    let result = seeds.iter().try_for_each(|&seed| {
+        processor.try_set_params(ProcessingParams {
+            seed: Some(seed),
+            ..params.clone()
+        })?;
+        let report = processor.process(image, retarder)?;
+        // Прогоны с потерянными строками приводятся к размеру первого
+        let decoded = match size {
+            None => {
+                let (width, height) = (report.image.width(), report.image.height());
+                size = Some((width, height));
+                sums = vec![0.0; (width * height * 3) as usize];
+                squares = sums.clone();
+                report.image.to_rgb8()
+            }
+            Some((width, height)) => metrics::fit(&report.image, width, height),
+        };
+        for ((sum, square), &value) in sums.iter_mut().zip(&mut squares).zip(decoded.as_raw()) {
+            *sum += value as f64;
+            *square += value as f64 * value as f64;
+        }
+        reports.push(metrics::compare_in(
+            &sent,
+            &report.image,
+            params.metric_space,
+        ));
+        Ok::<_, SstvError>(())
+    });
+    processor.try_set_params(params.clone())?;
+    result?;
+
+    let n = runs as f64;
+    let (width, height) = size.unwrap_or_default();
+    let mean_raw: Vec<u8> = sums
+        .iter()
+        .map(|s| (s / n).round().clamp(0.0, 255.0) as u8)
+        .collect();
+    let variance = sums
+        .chunks_exact(3)
+        .zip(squares.chunks_exact(3))
+        .map(|(s, q)| {
+            (0..3)
+                .map(|c| (q[c] / n - (s[c] / n).powi(2)).max(0.0))
+                .sum::<f64>() as f32
+                / 3.0
+        })
+        .collect();
+    let mean = DynamicImage::ImageRgb8(
+        RgbImage::from_raw(width, height, mean_raw).expect("буфер по размеру изображения"),
+    );
+    let combined = metrics::compare_in(&sent, &mean, params.metric_space);
+    Ok(Ensemble {
+        seeds,
+        mean,
+        variance,
+        runs: reports,
+        combined,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metrics::MetricSpace;
+
+    #[test]
+    fn ensemble_reports_gain_and_spread() {
+        let report = |psnr| QualityReport {
+            psnr,
+            ssim: 1.0,
+            mse: 0.0,
+            space: MetricSpace::Srgb,
+        };
+        let ensemble = Ensemble {
+            seeds: vec![0, 1, 2, 3],
+            mean: DynamicImage::ImageRgb8(RgbImage::new(2, 1)),
+            variance: vec![0.0, 65025.0],
+            runs: vec![
+                report(20.0),
+                report(22.0),
+                report(f64::INFINITY),
+                report(24.0),
+            ],
+            combined: report(28.0),
+        };
+        assert_eq!(ensemble.mean_run_psnr(), 22.0);
+        assert_eq!(ensemble.gain_db(), 6.0);
+        assert_eq!(ensemble.mean_variance(), 32512.5);
+        let map = ensemble.variance_image().to_rgb8();
+        assert_eq!(map.dimensions(), (2, 1));
+        assert_ne!(map.get_pixel(0, 0), map.get_pixel(1, 0));
+
+        let mut processor = SSTVProcessor::builder().build().unwrap();
+        let image = DynamicImage::ImageRgb8(RgbImage::new(8, 8));
+        assert!(run(&mut processor, &image, None, 0).is_err());
+    }
+}
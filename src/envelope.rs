@@ -1,8 +1,17 @@
-use rand::random;
+use crate::dsp;
+use crate::error::{Result, SstvError};
+use crate::messages;
+use rand::{Rng, rng};
+use serde::{Deserialize, Serialize};
 use std::f32::consts::PI;
+use std::fmt;
+use std::ops::Range;
+use std::path::Path;
+use std::str::FromStr;
 
 /// Огибающие для уровня во времени
-#[derive(Copy, Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum EnvelopeKind {
     Const,
     Sin,
@@ -10,15 +19,119 @@ pub enum EnvelopeKind {
     Saw,
     Square,
     Rand,
+    /// Атака — спад — удержание — затухание за каждый период
+    Adsr(AdsrEnvelope),
+    /// Пачки с плавными фронтами, включающиеся через равные промежутки со случайным сдвигом
+    Burst(BurstEnvelope),
+    /// Кривая по точкам пользователя, повторяется так же, как остальные огибающие
+    Custom(CustomEnvelope),
 }
 
 impl EnvelopeKind {
-    /// Вернёт коэффициент (0…1) для сэмпла `idx` при длине `len` и коэффициенте повторения `rep`
-    pub fn factor(self, idx: usize, len: usize, rep: f32) -> f32 {
-        let t = idx as f32 / (len as f32 - 1.0); // 0‥1
+    /// Средний квадрат огибающей длиной `len`: во сколько раз она в среднем ослабляет
+    /// мощность подмешиваемого сигнала. 1 для `Const`
+    pub fn mean_power(
+        &self,
+        len: usize,
+        rep: f32,
+        sample_rate: u32,
+        anchor: EnvelopeAnchor,
+        image: &Range<usize>,
+        rng: &mut impl Rng,
+    ) -> f32 {
+        match self.factors_with_rng(len, rep, sample_rate, anchor, image, false, rng) {
+            Some(factors) => factors.iter().map(|f| f * f).sum::<f32>() / len.max(1) as f32,
+            None => 1.0,
+        }
+    }
+
+    /// Вернёт коэффициент (0…1) для сэмпла `idx` при длине `len` и коэффициенте повторения `rep`.
+    /// По частоте сигнала `sample_rate` огибающие с длительностями в мс переводят сэмплы во время
+    pub fn factor(&self, idx: usize, len: usize, rep: f32, sample_rate: u32) -> f32 {
+        self.factor_with_rng(idx, len, rep, sample_rate, &mut rng())
+    }
+
+    /// То же, что [`factor`](Self::factor), но `Rand` берёт значения из генератора `rng`
+    pub fn factor_with_rng(
+        &self,
+        idx: usize,
+        len: usize,
+        rep: f32,
+        sample_rate: u32,
+        rng: &mut impl Rng,
+    ) -> f32 {
+        match self {
+            Self::Rand => rng.random::<f32>(),
+            _ => self.shape(idx, len, rep, sample_rate),
+        }
+    }
+
+    /// Коэффициенты огибающей для всех `len` сэмплов сигнала, растянутой по привязке
+    /// `anchor` (`image` — тело изображения). Детерминированные огибающие считаются кусками,
+    /// при `parallel` — в пуле rayon; `Rand` берёт значения из `rng` по порядку сэмплов,
+    /// поэтому при заданном зерне результат от `parallel` не зависит. `None` для `Const`:
+    /// умножать на 1 незачем
+    #[allow(clippy::too_many_arguments)]
+    pub fn factors_with_rng(
+        &self,
+        len: usize,
+        rep: f32,
+        sample_rate: u32,
+        anchor: EnvelopeAnchor,
+        image: &Range<usize>,
+        parallel: bool,
+        rng: &mut impl Rng,
+    ) -> Option<Vec<f32>> {
+        let mut factors = vec![0.0; len];
+        match self {
+            Self::Const => return None,
+            Self::Rand => factors.iter_mut().for_each(|f| *f = rng.random()),
+            _ => dsp::for_each_chunk(&mut factors, parallel, |offset, chunk| {
+                for (k, f) in chunk.iter_mut().enumerate() {
+                    let (pos, env_len) = anchor.locate(offset + k, len, image);
+                    *f = self.shape(pos, env_len, rep, sample_rate);
+                }
+            }),
+        }
+        Some(factors)
+    }
+
+    /// Коэффициент сэмпла `idx` той же огибающей, что у
+    /// [`factors_with_rng`](Self::factors_with_rng), — для обработки сигнала блоками,
+    /// когда весь вектор коэффициентов не нужен. `Rand` берёт значение из `rng`
+    #[allow(clippy::too_many_arguments)]
+    pub fn factor_anchored(
+        &self,
+        idx: usize,
+        len: usize,
+        rep: f32,
+        sample_rate: u32,
+        anchor: EnvelopeAnchor,
+        image: &Range<usize>,
+        rng: &mut impl Rng,
+    ) -> f32 {
+        match self {
+            Self::Rand => rng.random(),
+            _ => {
+                let (pos, env_len) = anchor.locate(idx, len, image);
+                self.shape(pos, env_len, rep, sample_rate)
+            }
+        }
+    }
+
+    /// Коэффициент детерминированной огибающей; случайную `Rand` считает
+    /// [`factor_with_rng`](Self::factor_with_rng)
+    #[inline]
+    fn shape(&self, idx: usize, len: usize, rep: f32, sample_rate: u32) -> f32 {
+        // 0‥1; огибающая из одного сэмпла стоит в начале периода
+        let t = if len > 1 {
+            idx as f32 / (len as f32 - 1.0)
+        } else {
+            0.0
+        };
         let x = t * rep;
         match self {
-            Self::Const => 1.0,
+            Self::Const | Self::Rand => 1.0,
             Self::Sin => 0.5 * (1.0 + (2.0 * PI * x).sin()),
             Self::Tri => 1.0 - (2.0 * (x.fract()) - 1.0).abs(),
             Self::Saw => x.fract(),
@@ -29,7 +142,9 @@ impl EnvelopeKind {
                     0.0
                 }
             }
-            Self::Rand => random::<f32>(),
+            Self::Adsr(adsr) => adsr.level(period_phase(x)),
+            Self::Burst(burst) => burst.level(idx as f32 * 1000.0 / sample_rate as f32),
+            Self::Custom(curve) => curve.level(period_phase(x)),
         }
     }
 
@@ -40,6 +155,8 @@ impl EnvelopeKind {
         EnvelopeKind::Saw,
         EnvelopeKind::Square,
         EnvelopeKind::Rand,
+        EnvelopeKind::Adsr(AdsrEnvelope::DEFAULT),
+        EnvelopeKind::Burst(BurstEnvelope::DEFAULT),
     ];
 
     pub fn name(&self) -> &'static str {
@@ -50,8 +167,26 @@ impl EnvelopeKind {
             EnvelopeKind::Saw => "Saw",
             EnvelopeKind::Square => "Square",
             EnvelopeKind::Rand => "Rand",
+            EnvelopeKind::Adsr(_) => "ADSR",
+            EnvelopeKind::Burst(_) => "Burst",
+            EnvelopeKind::Custom(_) => "Custom",
         }
     }
+
+    /// Тот же вид огибающей, параметры формы не сравниваются
+    pub fn same_kind(&self, other: &Self) -> bool {
+        std::mem::discriminant(self) == std::mem::discriminant(other)
+    }
+}
+
+/// Доля периода 0–1 для позиции `x` в периодах; конец периода относится к нему же,
+/// а не к началу следующего
+fn period_phase(x: f32) -> f32 {
+    if x > 0.0 && x.fract() == 0.0 {
+        1.0
+    } else {
+        x.fract()
+    }
 }
 
 impl clap::ValueEnum for EnvelopeKind {
@@ -63,6 +198,8 @@ impl clap::ValueEnum for EnvelopeKind {
             Self::Saw,
             Self::Square,
             Self::Rand,
+            Self::Adsr(AdsrEnvelope::DEFAULT),
+            Self::Burst(BurstEnvelope::DEFAULT),
         ]
     }
 
@@ -74,6 +211,553 @@ impl clap::ValueEnum for EnvelopeKind {
             Self::Saw => clap::builder::PossibleValue::new("saw"),
             Self::Square => clap::builder::PossibleValue::new("square"),
             Self::Rand => clap::builder::PossibleValue::new("rand"),
+            // Форма по умолчанию; свою задают --noise-adsr, --noise-burst и т. п.
+            Self::Adsr(_) => clap::builder::PossibleValue::new("adsr"),
+            Self::Burst(_) => clap::builder::PossibleValue::new("burst"),
+            // Кривая задаётся файлом, см. `CustomEnvelope::from_file`
+            Self::Custom(_) => return None,
+        })
+    }
+}
+
+/// Огибающая ADSR. Атака, спад и затухание — доли периода огибающей (если в сумме
+/// больше 1, они сжимаются пропорционально), удержание — уровень 0–1 между спадом и
+/// затуханием. Подходит для помех, которые нарастают и стихают, а не включаются рывком
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AdsrEnvelope {
+    pub attack: f32,
+    pub decay: f32,
+    pub sustain: f32,
+    pub release: f32,
+}
+
+impl AdsrEnvelope {
+    pub const DEFAULT: Self = Self {
+        attack: 0.1,
+        decay: 0.1,
+        sustain: 0.7,
+        release: 0.2,
+    };
+
+    /// Уровень в момент `t` (доля периода 0–1)
+    pub fn level(&self, t: f32) -> f32 {
+        let sustain = self.sustain.clamp(0.0, 1.0);
+        let (mut attack, mut decay, mut release) = (
+            self.attack.max(0.0),
+            self.decay.max(0.0),
+            self.release.max(0.0),
+        );
+        let total = attack + decay + release;
+        if total > 1.0 {
+            attack /= total;
+            decay /= total;
+            release /= total;
+        }
+
+        let t = t.clamp(0.0, 1.0);
+        if t < attack {
+            t / attack
+        } else if t < attack + decay {
+            1.0 - (1.0 - sustain) * (t - attack) / decay
+        } else if t < 1.0 - release {
+            sustain
+        } else if release > 0.0 {
+            sustain * (1.0 - t) / release
+        } else {
+            sustain
+        }
+    }
+}
+
+impl Default for AdsrEnvelope {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+impl FromStr for AdsrEnvelope {
+    type Err = SstvError;
+
+    /// Разбирает форму в формате `атака:спад:удержание:затухание`
+    fn from_str(s: &str) -> Result<Self> {
+        let values = parse_fields(s, 4..=4, messages::format_adsr(), "adsr")?;
+        if values.iter().any(|&v| v < 0.0) {
+            return Err(SstvError::invalid("adsr", messages::adsr_negative(s)));
+        }
+        if values[2] > 1.0 {
+            return Err(SstvError::invalid(
+                "adsr",
+                messages::adsr_sustain(values[2]),
+            ));
+        }
+        Ok(Self {
+            attack: values[0],
+            decay: values[1],
+            sustain: values[2],
+            release: values[3],
+        })
+    }
+}
+
+impl fmt::Display for AdsrEnvelope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}:{}:{}",
+            self.attack, self.decay, self.sustain, self.release
+        )
+    }
+}
+
+/// Пачки: включение на `on_ms` каждые `period_ms`, начало каждой пачки сдвинуто на
+/// случайную величину до ±`jitter_ms`, фронты нарастают и спадают за `ramp_ms`. Время
+/// отсчитывается от начала отрезка привязки, коэффициент повторения не учитывается.
+/// Сдвиги определяются номером пачки, поэтому одинаковы при каждом запуске
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BurstEnvelope {
+    pub on_ms: f32,
+    pub period_ms: f32,
+    pub jitter_ms: f32,
+    pub ramp_ms: f32,
+}
+
+impl BurstEnvelope {
+    pub const DEFAULT: Self = Self {
+        on_ms: 200.0,
+        period_ms: 1000.0,
+        jitter_ms: 100.0,
+        ramp_ms: 20.0,
+    };
+
+    /// Уровень в момент `ms` от начала отрезка
+    pub fn level(&self, ms: f32) -> f32 {
+        let period = self.period_ms.max(1.0);
+        let on = self.on_ms.clamp(0.0, period);
+        let jitter = self.jitter_ms.clamp(0.0, period / 2.0);
+        let ramp = self.ramp_ms.clamp(0.0, on / 2.0);
+        if on <= 0.0 {
+            return 0.0;
+        }
+
+        // Со сдвигом не больше полупериода пачка длиной не больше периода
+        // задевает лишь свой период и соседние
+        let k = (ms / period).floor() as i64;
+        (k - 1..=k + 1)
+            .map(|n| {
+                let start = n as f32 * period + jitter * burst_shift(n);
+                let d = ms - start;
+                if d < 0.0 || d > on {
+                    0.0
+                } else if ramp > 0.0 {
+                    (d / ramp).min((on - d) / ramp).min(1.0)
+                } else {
+                    1.0
+                }
+            })
+            .fold(0.0, f32::max)
+    }
+}
+
+impl Default for BurstEnvelope {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// Сдвиг пачки номер `n` в долях наибольшего, −1…1 (хеш SplitMix64)
+fn burst_shift(n: i64) -> f32 {
+    let mut z = (n as u64).wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^= z >> 31;
+    (z >> 40) as f32 / (1u64 << 24) as f32 * 2.0 - 1.0
+}
+
+impl FromStr for BurstEnvelope {
+    type Err = SstvError;
+
+    /// Разбирает пачки в формате `вкл_мс:период_мс[:разброс_мс[:фронт_мс]]`
+    fn from_str(s: &str) -> Result<Self> {
+        let values = parse_fields(s, 2..=4, messages::format_bursts(), "bursts")?;
+        if values.iter().any(|&v| v < 0.0) {
+            return Err(SstvError::invalid("bursts", messages::bursts_negative(s)));
+        }
+        let (on_ms, period_ms) = (values[0], values[1]);
+        if period_ms <= 0.0 || on_ms > period_ms {
+            return Err(SstvError::invalid(
+                "bursts",
+                messages::burst_period(on_ms, period_ms),
+            ));
+        }
+        Ok(Self {
+            on_ms,
+            period_ms,
+            jitter_ms: values.get(2).copied().unwrap_or(0.0),
+            ramp_ms: values.get(3).copied().unwrap_or(0.0),
+        })
+    }
+}
+
+impl fmt::Display for BurstEnvelope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}:{}:{}",
+            self.on_ms, self.period_ms, self.jitter_ms, self.ramp_ms
+        )
+    }
+}
+
+/// Числа через двоеточие; `count` — допустимое их количество, `format` — для сообщения,
+/// `field` — параметр, к которому относится ошибка
+fn parse_fields(
+    s: &str,
+    count: std::ops::RangeInclusive<usize>,
+    format: &str,
+    field: &'static str,
+) -> Result<Vec<f32>> {
+    let parts: Vec<&str> = s.split(':').collect();
+    if !count.contains(&parts.len()) {
+        return Err(SstvError::invalid(
+            field,
+            messages::expected_format(format, s),
+        ));
+    }
+    parts
+        .iter()
+        .map(|v| {
+            v.trim()
+                .parse()
+                .map_err(|_| SstvError::invalid(field, messages::invalid_number_in(v, s)))
+        })
+        .collect()
+}
+
+/// Интерполяция между точками пользовательской огибающей
+#[derive(Copy, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Interpolation {
+    #[default]
+    Linear,
+    /// Кубический сплайн Эрмита с касательными Катмулла — Рома: плавные подъёмы и спады
+    Spline,
+}
+
+impl Interpolation {
+    pub const ALL: &'static [Interpolation] = &[Interpolation::Linear, Interpolation::Spline];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Interpolation::Linear => "Linear",
+            Interpolation::Spline => "Spline",
+        }
+    }
+}
+
+/// Огибающая по точкам (время, уровень). Время — доля периода 0–1, уровень 0–1;
+/// до первой и после последней точки держится крайний уровень
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CustomEnvelope {
+    pub points: Vec<(f32, f32)>,
+    pub interpolation: Interpolation,
+}
+
+impl CustomEnvelope {
+    /// Точки сортируются по времени, уровни ограничиваются диапазоном 0–1
+    pub fn new(points: Vec<(f32, f32)>, interpolation: Interpolation) -> Self {
+        let mut points: Vec<(f32, f32)> = points
+            .into_iter()
+            .map(|(t, level)| (t, level.clamp(0.0, 1.0)))
+            .collect();
+        points.sort_by(|a, b| a.0.total_cmp(&b.0));
+        Self {
+            points,
+            interpolation,
+        }
+    }
+
+    /// Уровень в момент `t` (доля периода 0–1); без точек огибающая постоянна и равна 1
+    pub fn level(&self, t: f32) -> f32 {
+        let points = &self.points;
+        let (Some(first), Some(last)) = (points.first(), points.last()) else {
+            return 1.0;
+        };
+        if t <= first.0 {
+            return first.1;
+        }
+        if t >= last.0 {
+            return last.1;
+        }
+
+        // Отрезок [i, i + 1], внутри которого лежит t
+        let i = points.partition_point(|p| p.0 <= t) - 1;
+        let ((t0, y0), (t1, y1)) = (points[i], points[i + 1]);
+        let dt = t1 - t0;
+        if dt <= 0.0 {
+            return y1;
+        }
+        let u = (t - t0) / dt;
+
+        match self.interpolation {
+            Interpolation::Linear => y0 + (y1 - y0) * u,
+            Interpolation::Spline => {
+                // Наклон в точке по соседям; на краях — по единственному отрезку
+                let slope = |k: usize| {
+                    let (a, b) = (
+                        points[k.saturating_sub(1)],
+                        points[(k + 1).min(points.len() - 1)],
+                    );
+                    if b.0 > a.0 {
+                        (b.1 - a.1) / (b.0 - a.0)
+                    } else {
+                        0.0
+                    }
+                };
+                let (m0, m1) = (slope(i) * dt, slope(i + 1) * dt);
+                let (u2, u3) = (u * u, u * u * u);
+                let y = (2.0 * u3 - 3.0 * u2 + 1.0) * y0
+                    + (u3 - 2.0 * u2 + u) * m0
+                    + (-2.0 * u3 + 3.0 * u2) * y1
+                    + (u3 - u2) * m1;
+                y.clamp(0.0, 1.0)
+            }
+        }
+    }
+
+    /// Читает огибающую из файла. `.toml` и `.json` хранят структуру целиком, остальные
+    /// файлы — текст, см. [`parse`](Self::parse)
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path).map_err(|e| {
+            SstvError::io(
+                messages::envelope_read_failed(&path.display().to_string()),
+                e,
+            )
+        })?;
+        let extension = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(str::to_lowercase);
+        let curve = match extension.as_deref() {
+            Some("toml") => toml::from_str(&text)
+                .map(|c: Self| Self::new(c.points, c.interpolation))
+                .map_err(|e| SstvError::invalid("envelope", e.to_string())),
+            Some("json") => serde_json::from_str(&text)
+                .map(|c: Self| Self::new(c.points, c.interpolation))
+                .map_err(|e| SstvError::invalid("envelope", e.to_string())),
+            _ => Self::parse(&text),
+        };
+        curve.map_err(|e| {
+            SstvError::invalid(
+                "envelope",
+                messages::envelope_invalid(&path.display().to_string(), &e.to_string()),
+            )
+        })
+    }
+
+    /// Разбирает текстовое описание: по строке на точку «время уровень» (разделитель —
+    /// пробел, запятая или точка с запятой), строка `linear` или `spline` выбирает
+    /// интерполяцию, `#` начинает комментарий. Время переводится в долю периода от первой
+    /// точки до последней, поэтому его можно записывать в любых единицах, например в секундах
+    /// записи помехи
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut points = Vec::new();
+        let mut interpolation = Interpolation::Linear;
+
+        for (number, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            if let Some(&kind) = Interpolation::ALL
+                .iter()
+                .find(|k| k.name().eq_ignore_ascii_case(line))
+            {
+                interpolation = kind;
+                continue;
+            }
+
+            let fields: Vec<&str> = line
+                .split(|c: char| c == ',' || c == ';' || c.is_whitespace())
+                .filter(|f| !f.is_empty())
+                .collect();
+            let parsed: Option<Vec<f32>> = fields.iter().map(|f| f.parse().ok()).collect();
+            match parsed.as_deref() {
+                Some(&[t, level]) => points.push((t, level)),
+                _ => {
+                    return Err(SstvError::invalid(
+                        "envelope",
+                        messages::envelope_line(number + 1, line),
+                    ));
+                }
+            }
+        }
+
+        if points.is_empty() {
+            return Err(SstvError::invalid("envelope", messages::envelope_empty()));
+        }
+        let curve = Self::new(points, interpolation);
+        let (start, end) = (curve.points[0].0, curve.points[curve.points.len() - 1].0);
+        let span = end - start;
+        let points = curve
+            .points
+            .iter()
+            .map(|&(t, level)| {
+                let t = if span > 0.0 { (t - start) / span } else { 0.0 };
+                (t, level)
+            })
+            .collect();
+        Ok(Self {
+            points,
+            interpolation,
+        })
+    }
+
+    /// Текстовое описание в формате [`parse`](Self::parse): интерполяция и точки по строке
+    pub fn to_text(&self) -> String {
+        let mut text = format!("{}\n", self.interpolation.name().to_lowercase());
+        for (t, level) in &self.points {
+            text.push_str(&format!("{t:.4} {level:.4}\n"));
+        }
+        text
+    }
+
+    /// Записывает огибающую в файл, который прочитает [`from_file`](Self::from_file):
+    /// `.toml` и `.json` — структуру целиком, остальные — текст, см. [`to_text`](Self::to_text)
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let extension = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(str::to_lowercase);
+        let text = match extension.as_deref() {
+            Some("toml") => {
+                toml::to_string(self).map_err(|e| SstvError::invalid("envelope", e.to_string()))?
+            }
+            Some("json") => serde_json::to_string_pretty(self)
+                .map_err(|e| SstvError::invalid("envelope", e.to_string()))?,
+            _ => self.to_text(),
+        };
+        std::fs::write(path, text).map_err(|e| {
+            SstvError::io(
+                messages::envelope_write_failed(&path.display().to_string()),
+                e,
+            )
+        })
+    }
+}
+
+/// Запись движений ползунка во времени: значения параметра с отметками секунд от начала
+/// воспроизведения. Превращается в огибающую по точкам, см. [`to_envelope`](Self::to_envelope)
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AutomationRecording {
+    points: Vec<(f32, f32)>,
+}
+
+impl AutomationRecording {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Добавляет значение `value` в момент `t` секунд. Точка, повторяющая две предыдущие,
+    /// только сдвигает последнюю: на неподвижном ползунке запись не растёт
+    pub fn record(&mut self, t: f32, value: f32) {
+        if let [.., (_, a), (last_t, b)] = self.points.as_mut_slice()
+            && *a == value
+            && *b == value
+        {
+            *last_t = t;
+            return;
+        }
+        self.points.push((t, value));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    /// Огибающая на `duration` секунд и базовое значение параметра. `amplitude` переводит
+    /// значение ползунка в множитель амплитуды эффекта; базой становится значение
+    /// с наибольшей амплитудой, а уровень огибающей — амплитуда относительно неё.
+    /// Первое и последнее значения держатся до краёв записи. `None` без точек
+    pub fn to_envelope(
+        &self,
+        duration: f32,
+        amplitude: impl Fn(f32) -> f32,
+    ) -> Option<(CustomEnvelope, f32)> {
+        let (&(_, first), &(_, last)) = (self.points.first()?, self.points.last()?);
+        let base = self
+            .points
+            .iter()
+            .map(|&(_, value)| value)
+            .max_by(|a, b| amplitude(*a).total_cmp(&amplitude(*b)))?;
+        let peak = amplitude(base);
+        let level = |value: f32| {
+            if peak > 0.0 {
+                amplitude(value) / peak
+            } else {
+                0.0
+            }
+        };
+
+        let duration = duration.max(f32::MIN_POSITIVE);
+        let mut points = vec![(0.0, level(first))];
+        points.extend(
+            self.points
+                .iter()
+                .map(|&(t, value)| ((t / duration).clamp(0.0, 1.0), level(value))),
+        );
+        points.push((1.0, level(last)));
+        Some((CustomEnvelope::new(points, Interpolation::Linear), base))
+    }
+}
+
+/// Отрезок сигнала, на который растягивается огибающая
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EnvelopeAnchor {
+    /// Весь звук, включая заголовок и добавленные эффектами участки
+    Audio,
+    /// Только тело изображения; до и после него огибающая держит крайние значения
+    Image,
+}
+
+impl EnvelopeAnchor {
+    pub const ALL: &'static [EnvelopeAnchor] = &[EnvelopeAnchor::Audio, EnvelopeAnchor::Image];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            EnvelopeAnchor::Audio => "Audio",
+            EnvelopeAnchor::Image => "Image",
+        }
+    }
+
+    /// Переводит индекс сэмпла в пару (позиция, длина) для [`EnvelopeKind::factor`]
+    #[inline]
+    pub fn locate(self, idx: usize, len: usize, image: &Range<usize>) -> (usize, usize) {
+        let end = image.end.min(len);
+        match self {
+            Self::Image if image.start < end => (
+                idx.clamp(image.start, end - 1) - image.start,
+                end - image.start,
+            ),
+            _ => (idx, len),
+        }
+    }
+}
+
+impl clap::ValueEnum for EnvelopeAnchor {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[Self::Audio, Self::Image]
+    }
+
+    fn to_possible_value<'a>(&self) -> Option<clap::builder::PossibleValue> {
+        Some(match self {
+            Self::Audio => clap::builder::PossibleValue::new("audio"),
+            Self::Image => clap::builder::PossibleValue::new("image"),
         })
     }
 }
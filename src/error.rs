@@ -0,0 +1,102 @@
+use std::error::Error;
+use std::fmt;
+
+use crate::messages;
+use crate::processor::Stage;
+use std::time::Duration;
+
+/// Результат операций библиотеки
+pub type Result<T, E = SstvError> = std::result::Result<T, E>;
+
+/// Ошибка библиотеки; по варианту вызывающий отличает, например, ненайденное
+/// изображение от сбоя записи файла
+#[derive(Debug)]
+pub enum SstvError {
+    /// Кодер не смог построить сигнал из изображения
+    EncodeFailed(String),
+    /// Декодер не нашёл в сигнале изображение, заголовок или синхроимпульс
+    DecodeNotFound(String),
+    /// Частота дискретизации ниже той, при которой тоны SSTV помещаются в полосу
+    UnsupportedSampleRate { rate: u32, min: u32 },
+    /// Сбой чтения или записи файла: `context` говорит, что делали, `source` — почему не вышло
+    IoError {
+        context: String,
+        source: Box<dyn Error + Send + Sync>,
+    },
+    /// Неверное значение параметра `field`
+    InvalidParams { field: &'static str, reason: String },
+    /// Звуковое устройство недоступно или не поддерживает нужный формат
+    AudioDevice(String),
+    /// Обработка прервана флагом отмены
+    Cancelled,
+    /// Прогон не уложился в предел времени `limit` этапа `stage` или, при `None`, всего
+    /// прогона, см. [`TimeLimits`](crate::deadline::TimeLimits)
+    Timeout {
+        stage: Option<Stage>,
+        limit: Duration,
+    },
+}
+
+impl SstvError {
+    pub(crate) fn invalid(field: &'static str, reason: impl Into<String>) -> Self {
+        SstvError::InvalidParams {
+            field,
+            reason: reason.into(),
+        }
+    }
+
+    pub(crate) fn io(
+        context: impl Into<String>,
+        source: impl Into<Box<dyn Error + Send + Sync>>,
+    ) -> Self {
+        SstvError::IoError {
+            context: context.into(),
+            source: source.into(),
+        }
+    }
+
+    /// Возвращает [`SstvError::Cancelled`], если запрошена отмена
+    pub fn check_cancelled(cancel: &std::sync::atomic::AtomicBool) -> Result<()> {
+        if cancel.load(std::sync::atomic::Ordering::Relaxed) {
+            Err(SstvError::Cancelled)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl fmt::Display for SstvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SstvError::EncodeFailed(reason)
+            | SstvError::DecodeNotFound(reason)
+            | SstvError::InvalidParams { reason, .. }
+            | SstvError::AudioDevice(reason) => write!(f, "{}", reason),
+            SstvError::UnsupportedSampleRate { rate, min } => {
+                write!(f, "{}", messages::unsupported_sample_rate(*rate, *min))
+            }
+            // Как у anyhow: `{:#}` дописывает причину после пояснения
+            SstvError::IoError { context, source } if f.alternate() => {
+                write!(f, "{}: {}", context, source)
+            }
+            SstvError::IoError { context, .. } => write!(f, "{}", context),
+            SstvError::Cancelled => write!(f, "{}", messages::cancelled()),
+            SstvError::Timeout { stage, limit } => {
+                let limit = limit.as_secs_f64();
+                match stage {
+                    Some(stage) => write!(f, "{}", messages::timeout_stage(stage.name(), limit)),
+                    None => write!(f, "{}", messages::timeout_total(limit)),
+                }
+            }
+        }
+    }
+}
+
+impl Error for SstvError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SstvError::IoError { source, .. } => Some(source.as_ref()),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,307 @@
+use crate::buffer::SampleBuffer;
+use crate::command::{self, CommandPaths};
+use crate::effect::ChannelSummary;
+use crate::error::{Result, SstvError};
+use crate::labels;
+use crate::messages;
+use crate::metrics::{self, MetricSpace, Registration, SignalQuality};
+use crate::occupancy::Occupancy;
+use crate::overlay;
+use crate::processor::{ProcessingParams, SSTVProcessor};
+use crate::report::DecodeReport;
+use crate::signal;
+#[cfg(feature = "wav")]
+use crate::wav;
+use image::{DynamicImage, ImageFormat};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Имя описи прогона в его папке
+pub const RUN_MANIFEST: &str = "params.json";
+
+/// Всё, что осталось от одного прогона обработки
+pub struct RunArtifacts<'a> {
+    /// Процессор прогона: его параметры и запись WAV (дизеринг, выход I/Q)
+    pub processor: &'a SSTVProcessor,
+    /// Пути исходных файлов; основное изображение и призрак в команде заменяются
+    /// копиями в папке прогона
+    pub paths: &'a CommandPaths,
+    /// Исходное изображение до приведения к режиму
+    pub original: &'a DynamicImage,
+    pub retarder: Option<&'a DynamicImage>,
+    pub report: &'a DecodeReport,
+    /// Сигнал после эффектов
+    pub degraded: &'a SampleBuffer,
+}
+
+/// Файлы папки прогона, относительно неё
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct RunFiles {
+    pub original: String,
+    pub retarder: Option<String>,
+    pub result: String,
+    /// Сигнал после эффектов (только со сборкой `wav`)
+    pub degraded_wav: Option<String>,
+    /// Чистый сигнал кодера на частоте искажённого, для сравнения и вычитания, сдвинутый
+    /// на запаздывание искажённого (только со сборкой `wav`)
+    pub clean_wav: Option<String>,
+    /// Маски искажений для обучающей выборки, см. [`labels::save`]: объединение
+    /// `mask.png` и по маске на эффект
+    pub masks: Vec<String>,
+}
+
+/// Метрики результата и отчёт декодера
+#[derive(Clone, Debug, Serialize)]
+pub struct RunMetrics {
+    /// PSNR относительно переданного изображения, дБ; `null`, если они совпадают
+    pub psnr: f64,
+    pub ssim: f64,
+    pub mse: f64,
+    /// Цветовое пространство оценок изображения
+    pub metric_space: MetricSpace,
+    /// PSNR после совмещения с переданным изображением, дБ, см.
+    /// [`metrics::compare_registered`]
+    pub registered_psnr: f64,
+    pub registered_ssim: f64,
+    /// Сдвиг и наклон декодированного изображения относительно переданного
+    pub registration: Registration,
+    /// С/Ш искажённого сигнала относительно чистого после выравнивания по сдвигу и
+    /// масштабу, дБ, см. [`metrics::compare_signals`]; `null`, если они совпадают
+    pub signal_snr_db: f64,
+    /// Запаздывание искажённого сигнала относительно чистого, найденное выравниванием, мс
+    pub signal_offset_ms: f64,
+    pub lines_decoded: u32,
+    pub partial: bool,
+    pub sync_lock_ratio: Option<f32>,
+    pub snr_estimate: Option<f32>,
+    pub slant_ppm: Option<f64>,
+    /// Занимаемая полоса переданного сигнала (99% мощности), Гц
+    pub occupied_bandwidth_hz: Option<f32>,
+    /// Запас спектра переданного сигнала до маски канала, дБ; отрицательный — маска нарушена
+    pub mask_margin_db: Option<f32>,
+    /// Описание принятого VIS, если декодер его прочитал
+    pub vis: Option<String>,
+    /// Отчёт декодера одной строкой, как в CLI
+    pub report: String,
+    /// Сводка канала, если сигнал прошёл через эффекты процессора
+    pub channel: Option<String>,
+    /// Групповая задержка сигнала эффектами, мс, см. [`ChannelSummary::group_delay_ms`]
+    pub group_delay_ms: Option<f32>,
+}
+
+/// Опись `params.json`: когда, чем и с какими настройками получены файлы папки
+#[derive(Clone, Debug, Serialize)]
+pub struct RunManifest {
+    /// Время прогона по UTC, ISO 8601
+    pub created: String,
+    /// Версия библиотеки
+    pub version: String,
+    /// Команда CLI, повторяющая прогон по копиям изображений в папке
+    pub command: String,
+    pub files: RunFiles,
+    /// Все параметры обработки; при пустом зерне случайные эффекты не повторятся точно
+    pub params: ProcessingParams,
+    pub metrics: RunMetrics,
+}
+
+/// Пишет каждый прогон в отдельную папку с отметкой времени: исходное изображение,
+/// результат, искажённый и чистый WAV и опись [`RUN_MANIFEST`] со всеми параметрами
+/// и метриками. По папке можно понять, какие настройки дали результат, и повторить его
+#[derive(Clone, Debug)]
+pub struct RunExporter {
+    /// Каталог, в котором создаются папки прогонов
+    pub root: PathBuf,
+}
+
+impl RunExporter {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// Пишет папку прогона `run_ГГГГ-ММ-ДД_ЧЧ-ММ-СС` (с номером, если такая уже есть)
+    /// и возвращает её путь
+    pub fn export(&self, run: &RunArtifacts) -> Result<PathBuf> {
+        let (year, month, day, secs) = overlay::civil_utc(SystemTime::now());
+        let dir = self.create_dir(&format!(
+            "run_{:04}-{:02}-{:02}_{:02}-{:02}-{:02}",
+            year,
+            month,
+            day,
+            secs / 3600,
+            secs % 3600 / 60,
+            secs % 60
+        ))?;
+        let params = run.processor.params();
+
+        let mut files = RunFiles {
+            original: "original.png".into(),
+            result: "result.png".into(),
+            ..RunFiles::default()
+        };
+        save_png(run.original, &dir.join(&files.original))?;
+        // Результат — с метаданными прогона в текстовых блоках PNG
+        run.processor.save_result(
+            &run.report.image,
+            &dir.join(&files.result).to_string_lossy(),
+        )?;
+        let masks = labels::masks(
+            params,
+            run.retarder.is_some(),
+            run.degraded.len(),
+            run.degraded.rate,
+        );
+        files.masks = labels::save(&masks, &dir.join("mask"))?
+            .iter()
+            .filter_map(|path| path.file_name())
+            .map(|name| name.to_string_lossy().into_owned())
+            .collect();
+        if let Some(retarder) = run.retarder {
+            let file = "retarder.png".to_string();
+            save_png(retarder, &dir.join(&file))?;
+            files.retarder = Some(file);
+        }
+        // Чистый сигнал — того же изображения с надписью, как оно ушло в канал, на частоте
+        // искажённого. Искажённый выравнивается по нему, чтобы задержка эффектов не
+        // портила сравнение
+        let sent = params.overlay_params().apply(run.original)?;
+        let clean = signal::encode(&sent, &params.encoder)
+            .resampled_with(run.degraded.rate, params.quality)
+            .into_buffer();
+        let alignment = metrics::compare_signals(&clean.data, &run.degraded.data);
+
+        #[cfg(feature = "wav")]
+        {
+            let degraded = "degraded.wav".to_string();
+            run.processor
+                .write_wav(&dir.join(&degraded).to_string_lossy(), run.degraded)?;
+            files.degraded_wav = Some(degraded);
+
+            // Чистый сдвигается на найденное запаздывание, чтобы лечь под искажённый
+            let mut clean = clean;
+            match usize::try_from(alignment.offset) {
+                Ok(delay) => {
+                    clean.data.splice(0..0, std::iter::repeat_n(0.0, delay));
+                }
+                Err(_) => {
+                    let lead = alignment.offset.unsigned_abs().min(clean.data.len());
+                    clean.data.drain(..lead);
+                }
+            }
+            let file = "clean.wav".to_string();
+            wav::write_wav(&dir.join(&file).to_string_lossy(), &clean)?;
+            files.clean_wav = Some(file);
+        }
+
+        let paths = CommandPaths {
+            input: files.original.clone(),
+            output: files.result.clone(),
+            retarder: files
+                .retarder
+                .clone()
+                .or_else(|| run.paths.retarder.clone()),
+            ..run.paths.clone()
+        };
+        let manifest = RunManifest {
+            created: format!(
+                "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+                year,
+                month,
+                day,
+                secs / 3600,
+                secs % 3600 / 60,
+                secs % 60
+            ),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            command: command::cli_command(params, &paths),
+            files,
+            params: params.clone(),
+            metrics: run_metrics(
+                &sent,
+                run.report,
+                &alignment,
+                run.degraded.rate,
+                params.metric_space,
+            ),
+        };
+        let text = serde_json::to_string_pretty(&manifest)
+            .map_err(|e| SstvError::io(messages::run_manifest_serialize_failed(), e))?;
+        let path = dir.join(RUN_MANIFEST);
+        std::fs::write(&path, text).map_err(|e| {
+            SstvError::io(
+                messages::manifest_write_failed(&path.display().to_string()),
+                e,
+            )
+        })?;
+        Ok(dir)
+    }
+
+    /// Создаёт папку `name` в корне; если она уже есть, — `name_2`, `name_3` и так далее
+    fn create_dir(&self, name: &str) -> Result<PathBuf> {
+        std::fs::create_dir_all(&self.root).map_err(|e| {
+            SstvError::io(
+                messages::create_dir_failed(&self.root.display().to_string()),
+                e,
+            )
+        })?;
+        let mut attempt = 1;
+        loop {
+            let dir = match attempt {
+                1 => self.root.join(name),
+                n => self.root.join(format!("{}_{}", name, n)),
+            };
+            match std::fs::create_dir(&dir) {
+                Ok(()) => return Ok(dir),
+                Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => attempt += 1,
+                Err(e) => {
+                    return Err(SstvError::io(
+                        messages::create_dir_failed(&dir.display().to_string()),
+                        e,
+                    ));
+                }
+            }
+        }
+    }
+}
+
+/// Метрики относительно переданного изображения `sent` (с надписью) и чистого сигнала
+/// частоты `sample_rate`, с которым искажённый сравнён в `alignment`; качество
+/// изображения — в пространстве `space`
+fn run_metrics(
+    sent: &DynamicImage,
+    report: &DecodeReport,
+    alignment: &SignalQuality,
+    sample_rate: u32,
+    space: MetricSpace,
+) -> RunMetrics {
+    let quality = metrics::compare_in(sent, &report.image, space);
+    let (registered, registration) = metrics::compare_registered(sent, &report.image, space);
+    RunMetrics {
+        psnr: quality.psnr,
+        ssim: quality.ssim,
+        mse: quality.mse,
+        metric_space: space,
+        registered_psnr: registered.psnr,
+        registered_ssim: registered.ssim,
+        registration,
+        signal_snr_db: alignment.snr_db,
+        signal_offset_ms: alignment.offset_ms(sample_rate),
+        lines_decoded: report.lines_decoded,
+        partial: report.partial,
+        sync_lock_ratio: report.sync_lock_ratio,
+        snr_estimate: report.snr_estimate,
+        slant_ppm: report.slant_estimate,
+        occupied_bandwidth_hz: report.occupancy.as_ref().map(Occupancy::bandwidth_hz),
+        mask_margin_db: report.occupancy.map(|occupancy| occupancy.mask_margin_db),
+        vis: report.vis_detected.as_ref().map(|vis| vis.describe()),
+        report: report.describe(),
+        channel: report.channel.as_ref().map(|channel| channel.describe()),
+        group_delay_ms: report.channel.as_ref().map(ChannelSummary::group_delay_ms),
+    }
+}
+
+fn save_png(image: &DynamicImage, path: &Path) -> Result<()> {
+    image
+        .save_with_format(path, ImageFormat::Png)
+        .map_err(|e| SstvError::io(messages::save_failed(&path.display().to_string()), e))
+}
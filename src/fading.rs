@@ -0,0 +1,342 @@
+use crate::buffer::SampleBuffer;
+use crate::effect::{
+    self, BlockProcessor, Bypass, ChannelEffect, EffectContext, EffectEstimate, EffectMix,
+    StreamContext,
+};
+use crate::error::{Result, SstvError};
+use crate::messages;
+use crate::processor::ProcessingParams;
+use rand::{Rng, rng};
+use rand_distr::StandardNormal;
+use serde::{Deserialize, Serialize};
+use std::f32::consts::PI;
+
+/// Точек огибающей на один период скорости замираний
+const CONTROL_POINTS_PER_CYCLE: f32 = 32.0;
+/// Число синусоид в модели Джейкса
+const JAKES_OSCILLATORS: usize = 16;
+
+/// Статистическая модель замираний
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FadingModel {
+    /// Периодические замирания по синусоиде
+    Sine,
+    /// Рэлеевские замирания с классическим доплеровским спектром (модель Джейкса)
+    Rayleigh,
+    /// Рэлеевские замирания с гауссовым доплеровским спектром, как в модели Уоттерсона
+    Watterson,
+}
+
+impl FadingModel {
+    pub const ALL: &'static [FadingModel] = &[
+        FadingModel::Sine,
+        FadingModel::Rayleigh,
+        FadingModel::Watterson,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            FadingModel::Sine => "Sine",
+            FadingModel::Rayleigh => "Rayleigh",
+            FadingModel::Watterson => "Watterson",
+        }
+    }
+}
+
+impl clap::ValueEnum for FadingModel {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[Self::Sine, Self::Rayleigh, Self::Watterson]
+    }
+
+    fn to_possible_value<'a>(&self) -> Option<clap::builder::PossibleValue> {
+        Some(match self {
+            Self::Sine => clap::builder::PossibleValue::new("sine"),
+            Self::Rayleigh => clap::builder::PossibleValue::new("rayleigh"),
+            Self::Watterson => clap::builder::PossibleValue::new("watterson"),
+        })
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FadingParams {
+    pub model: FadingModel,
+    /// Глубина замираний 0–1 (0 — отключено)
+    pub depth: f32,
+    /// Скорость замираний, Гц: частота синусоиды или доплеровское рассеяние
+    pub rate_hz: f32,
+    /// Доли смешивания выхода эффекта со входом
+    pub mix: EffectMix,
+}
+
+impl Default for FadingParams {
+    fn default() -> Self {
+        Self {
+            model: FadingModel::Rayleigh,
+            depth: 0.0,
+            rate_hz: 0.2,
+            mix: EffectMix::default(),
+        }
+    }
+}
+
+/// Селективные замирания (QSB): модулирует амплитуду сигнала во времени
+pub struct FadingProcessor {
+    #[deprecated(since = "0.1.0", note = "используйте `params()` и `set_params()`")]
+    pub params: FadingParams,
+}
+
+impl Default for FadingProcessor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[allow(deprecated)]
+impl FadingProcessor {
+    pub fn new() -> Self {
+        Self {
+            params: FadingParams::default(),
+        }
+    }
+
+    pub fn new_with_params(params: FadingParams) -> Self {
+        Self { params }
+    }
+
+    pub fn params(&self) -> &FadingParams {
+        &self.params
+    }
+
+    pub fn set_params(&mut self, params: FadingParams) {
+        self.params = params;
+    }
+
+    /// Применяет замирания к буферу сэмплов
+    pub fn apply_fading(&self, buffer: &mut SampleBuffer) -> Result<()> {
+        self.apply_fading_with_rng(buffer, &mut rng())
+    }
+
+    /// Применяет замирания, беря случайные фазы и шум из генератора `rng`
+    pub fn apply_fading_with_rng(
+        &self,
+        buffer: &mut SampleBuffer,
+        rng: &mut impl Rng,
+    ) -> Result<()> {
+        if !self.is_enabled() || buffer.data.is_empty() {
+            return Ok(());
+        }
+        self.curve(buffer.data.len(), buffer.rate, rng)
+            .process_block(&mut buffer.data, 0)
+    }
+
+    /// Огибающая на сигнал длиной `len` сэмплов. Она считается на редкой сетке сразу
+    /// на весь сигнал и интерполируется до частоты сигнала по номеру сэмпла, поэтому
+    /// блоки дают тот же выход, что и весь буфер
+    fn curve(&self, len: usize, sample_rate: u32, rng: &mut impl Rng) -> FadingCurve {
+        let control_rate = self.params.rate_hz * CONTROL_POINTS_PER_CYCLE;
+        let duration = len as f32 / sample_rate as f32;
+        let points = (duration * control_rate).ceil() as usize + 2;
+        FadingCurve {
+            envelope: self.envelope(points, control_rate, rng),
+            step: control_rate / sample_rate as f32,
+            depth: self.params.depth.clamp(0.0, 1.0),
+        }
+    }
+
+    /// Огибающая замираний с единичным среднеквадратичным уровнем
+    fn envelope(&self, points: usize, control_rate: f32, rng: &mut impl Rng) -> Vec<f32> {
+        let rate = self.params.rate_hz;
+
+        match self.params.model {
+            FadingModel::Sine => {
+                let phase = rng.random::<f32>() * 2.0 * PI;
+                (0..points)
+                    .map(|k| {
+                        let t = k as f32 / control_rate;
+                        0.5 * (1.0 + (2.0 * PI * rate * t + phase).cos())
+                    })
+                    .collect()
+            }
+            FadingModel::Rayleigh => {
+                let oscillators: Vec<(f32, f32, f32)> = (0..JAKES_OSCILLATORS)
+                    .map(|_| {
+                        let angle = rng.random::<f32>() * 2.0 * PI;
+                        let doppler = 2.0 * PI * rate * angle.cos();
+                        (
+                            doppler,
+                            rng.random::<f32>() * 2.0 * PI,
+                            rng.random::<f32>() * 2.0 * PI,
+                        )
+                    })
+                    .collect();
+                let norm = (JAKES_OSCILLATORS as f32).sqrt();
+
+                (0..points)
+                    .map(|k| {
+                        let t = k as f32 / control_rate;
+                        let (i, q) = oscillators.iter().fold((0.0, 0.0), |(i, q), &(w, pi, pq)| {
+                            (i + (w * t + pi).cos(), q + (w * t + pq).cos())
+                        });
+                        (i * i + q * q).sqrt() / norm
+                    })
+                    .collect()
+            }
+            FadingModel::Watterson => {
+                // Комплексный гауссов шум через гауссов фильтр с шириной спектра rate_hz (2σ)
+                let sigma = control_rate / (2.0 * PI * rate / 2.0);
+                let half = (3.0 * sigma).ceil() as usize;
+                let kernel: Vec<f32> = (0..=2 * half)
+                    .map(|j| {
+                        let x = j as f32 - half as f32;
+                        (-x * x / (2.0 * sigma * sigma)).exp()
+                    })
+                    .collect();
+
+                let noise: Vec<(f32, f32)> = (0..points + 2 * half)
+                    .map(|_| (rng.sample(StandardNormal), rng.sample(StandardNormal)))
+                    .collect();
+                let envelope: Vec<f32> = (0..points)
+                    .map(|k| {
+                        let (i, q) = kernel
+                            .iter()
+                            .zip(&noise[k..])
+                            .fold((0.0, 0.0), |(i, q), (&g, &(ni, nq))| {
+                                (i + g * ni, q + g * nq)
+                            });
+                        (i * i + q * q).sqrt()
+                    })
+                    .collect();
+
+                let rms = (envelope.iter().map(|e| e * e).sum::<f32>() / points as f32).sqrt();
+                envelope.iter().map(|e| e / rms.max(f32::EPSILON)).collect()
+            }
+        }
+    }
+
+    /// Устанавливает модель замираний
+    pub fn set_model(&mut self, model: FadingModel) {
+        self.params.model = model;
+    }
+
+    /// Устанавливает глубину замираний
+    pub fn set_depth(&mut self, depth: f32) {
+        self.params.depth = depth.clamp(0.0, 1.0);
+    }
+
+    /// Устанавливает скорость замираний
+    pub fn set_rate_hz(&mut self, rate_hz: f32) {
+        self.params.rate_hz = rate_hz.max(0.01);
+    }
+
+    /// Проверяет, включены ли замирания
+    pub fn is_enabled(&self) -> bool {
+        self.params.depth > 0.0 && self.params.rate_hz > 0.0
+    }
+
+    /// Возвращает описание текущих настроек замираний
+    pub fn describe(&self) -> String {
+        if !self.is_enabled() {
+            return messages::fading_off().to_string();
+        }
+
+        messages::fading_describe(
+            self.params.model.name(),
+            self.params.depth * 100.0,
+            self.params.rate_hz,
+        )
+    }
+}
+
+/// Огибающая замираний на редкой сетке: `step` — шаг сетки на сэмпл сигнала
+struct FadingCurve {
+    envelope: Vec<f32>,
+    step: f32,
+    depth: f32,
+}
+
+impl BlockProcessor for FadingCurve {
+    fn process_block(&mut self, block: &mut [f32], start: usize) -> Result<()> {
+        let last = self.envelope.len() - 1;
+        for (k, sample) in block.iter_mut().enumerate() {
+            let pos = (start + k) as f32 * self.step;
+            let i = (pos as usize).min(last - 1);
+            let frac = (pos - i as f32).min(1.0);
+            let e = self.envelope[i] * (1.0 - frac) + self.envelope[i + 1] * frac;
+            *sample *= 1.0 - self.depth + self.depth * e;
+        }
+        Ok(())
+    }
+    fn save_state(&mut self) -> Result<serde_json::Value> {
+        effect::to_block_state(&self.envelope)
+    }
+
+    fn restore_state(&mut self, state: &serde_json::Value) -> Result<()> {
+        let envelope: Vec<f32> = effect::from_block_state(state, messages::fading())?;
+        if envelope.len() != self.envelope.len() {
+            return Err(SstvError::invalid(
+                "state",
+                messages::fading_state_points(envelope.len(), self.envelope.len()),
+            ));
+        }
+        self.envelope = envelope;
+        Ok(())
+    }
+}
+
+#[allow(deprecated)]
+impl ChannelEffect for FadingProcessor {
+    fn name(&self) -> &'static str {
+        messages::fading()
+    }
+
+    fn mix(&self) -> EffectMix {
+        self.params.mix
+    }
+
+    fn apply(&self, buffer: &mut SampleBuffer, ctx: &EffectContext) -> Result<()> {
+        self.apply_fading_with_rng(buffer, &mut *ctx.rng.borrow_mut())
+    }
+
+    fn is_enabled(&self) -> bool {
+        FadingProcessor::is_enabled(self)
+    }
+
+    fn describe(&self) -> String {
+        FadingProcessor::describe(self)
+    }
+
+    fn sync_params(&mut self, params: &ProcessingParams) {
+        self.params = params.fading.clone();
+    }
+
+    /// Средняя мощность сигнала под огибающей `1 − d + d·e` по моментам огибающей
+    /// модели: синусоида 0.5·(1 + cos) или рэлеевская с единичным СКЗ
+    fn analyze(&self, _buffer: &SampleBuffer, _ctx: &EffectContext) -> EffectEstimate {
+        let depth = self.params.depth.clamp(0.0, 1.0);
+        let (mean, mean_square) = match self.params.model {
+            FadingModel::Sine => (0.5, 0.375),
+            FadingModel::Rayleigh | FadingModel::Watterson => (PI.sqrt() / 2.0, 1.0),
+        };
+        let power = (1.0 - depth).powi(2)
+            + 2.0 * depth * (1.0 - depth) * mean
+            + depth * depth * mean_square;
+        EffectEstimate {
+            gain_db: Some(effect::power_db(power)),
+            ..EffectEstimate::new(self.name(), self.describe())
+        }
+    }
+
+    /// Огибающая строится заранее на всю длину сигнала из контекста
+    fn block_processor(&self, ctx: &StreamContext) -> Result<Box<dyn BlockProcessor>> {
+        if !self.is_enabled() || ctx.len == 0 {
+            return Ok(Box::new(Bypass));
+        }
+        Ok(Box::new(self.curve(
+            ctx.len,
+            ctx.sample_rate,
+            &mut ctx.fork_rng(),
+        )))
+    }
+}
@@ -0,0 +1,364 @@
+use crate::error::{Result, SstvError};
+use crate::messages;
+use crate::processor::{ProcessingParams, SSTVProcessor};
+use crate::signal::{self, Signal};
+use image::{DynamicImage, ImageFormat};
+use std::cell::RefCell;
+use std::ffi::{CStr, CString, c_char, c_int};
+use std::io::Cursor;
+use std::panic::{self, AssertUnwindSafe};
+
+/// Байты, выделенные библиотекой: PNG результата
+#[repr(C)]
+pub struct SstvBytes {
+    pub data: *mut u8,
+    pub len: usize,
+}
+
+/// Моно-сигнал: сэмплы −1…1, частота дискретизации и тело изображения
+/// `image_start..image_end` в сэмплах (его используют огибающие с привязкой к изображению)
+#[repr(C)]
+pub struct SstvSamples {
+    pub data: *mut f32,
+    pub len: usize,
+    pub rate: u32,
+    pub image_start: usize,
+    pub image_end: usize,
+}
+
+/// Код ошибки вызова: обработка не удалась, текст в [`sstv_last_error`]
+pub const SSTV_ERROR: c_int = -1;
+
+/// Код неверного аргумента: `NULL` вместо входа, тело изображения за пределами сэмплов
+/// или неверный JSON параметров
+pub const SSTV_INVALID_ARGUMENT: c_int = -2;
+
+/// Поля [`SstvError::InvalidParams`], ошибки в которых — неверные аргументы вызова
+const ARGUMENTS: &[&str] = &["input", "params_json"];
+
+thread_local! {
+    static LAST_ERROR: RefCell<CString> = RefCell::new(CString::default());
+}
+
+/// Текст ошибки последнего вызова в этом потоке (UTF-8); пустая строка, если вызов
+/// удался. Остальные функции возвращают 0 при успехе, [`SSTV_INVALID_ARGUMENT`] при
+/// неверных аргументах и [`SSTV_ERROR`] при прочих ошибках. Указатель действителен
+/// до следующего вызова библиотеки в этом потоке
+#[unsafe(no_mangle)]
+pub extern "C" fn sstv_last_error() -> *const c_char {
+    LAST_ERROR.with(|e| e.borrow().as_ptr())
+}
+
+/// Кодирует изображение (PNG, JPEG, GIF, BMP... в памяти), искажает сигнал и декодирует
+/// его, как `process`. Результат — PNG в `out`, освобождается
+/// [`sstv_bytes_free`]. `params_json` — JSON [`ProcessingParams`]: пропущенные поля
+/// берутся по умолчанию, `NULL` — все параметры по умолчанию
+///
+/// # Safety
+/// `image` указывает на `image_len` байт, `params_json` — `NULL` или строка
+/// с нулём в конце, `out` — доступная для записи [`SstvBytes`]
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn sstv_process(
+    image: *const u8,
+    image_len: usize,
+    params_json: *const c_char,
+    out: *mut SstvBytes,
+) -> c_int {
+    guard(|| {
+        // SAFETY: условия вызова
+        let (image, params) = unsafe { (slice(image, image_len), params(params_json)?) };
+        let image = load_image(image)?;
+        let mut processor = SSTVProcessor::new_with_params(params);
+        let samples = processor.render_samples(&image, None)?;
+        let report = processor.decode_samples(&samples)?;
+        let png = encode_png(&report.image)?;
+        // SAFETY: условия вызова
+        unsafe { out.write(into_bytes(png)) };
+        Ok(())
+    })
+}
+
+/// Кодирует изображение в чистый сигнал SSTV без эффектов
+///
+/// # Safety
+/// Как у [`sstv_process`]; `out` — доступная для записи [`SstvSamples`]
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn sstv_encode(
+    image: *const u8,
+    image_len: usize,
+    params_json: *const c_char,
+    out: *mut SstvSamples,
+) -> c_int {
+    guard(|| {
+        // SAFETY: условия вызова
+        let (image, params) = unsafe { (slice(image, image_len), params(params_json)?) };
+        let signal = signal::encode(&load_image(image)?, &params.encoder);
+        // SAFETY: условия вызова
+        unsafe { out.write(into_samples(signal)) };
+        Ok(())
+    })
+}
+
+/// Применяет стандартный конвейер эффектов к сигналу `input`, см. `Signal::degrade`.
+/// Вход не меняется и не освобождается: это может быть и память вызывающего. Длина и
+/// частота результата могут отличаться от входа, если задан `sample_rate`
+///
+/// # Safety
+/// `input` — `NULL` (ошибка [`SSTV_INVALID_ARGUMENT`]) или [`SstvSamples`] с `len`
+/// сэмплами по `data`; `params_json` — как
+/// у [`sstv_process`]; `out` — доступная для записи [`SstvSamples`]
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn sstv_apply_effects(
+    input: *const SstvSamples,
+    params_json: *const c_char,
+    out: *mut SstvSamples,
+) -> c_int {
+    guard(|| {
+        // SAFETY: условия вызова
+        let (mut signal, params) = unsafe { (read_signal(input)?, params(params_json)?) };
+        signal.degrade(&params, None)?;
+        // SAFETY: условия вызова
+        unsafe { out.write(into_samples(signal)) };
+        Ok(())
+    })
+}
+
+/// Декодирует сигнал `input` с любой частотой дискретизации; результат — PNG в `out`
+///
+/// # Safety
+/// Как у [`sstv_apply_effects`]; `out` — доступная для записи [`SstvBytes`]
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn sstv_decode(
+    input: *const SstvSamples,
+    params_json: *const c_char,
+    out: *mut SstvBytes,
+) -> c_int {
+    guard(|| {
+        // SAFETY: условия вызова
+        let (signal, params) = unsafe { (read_signal(input)?, params(params_json)?) };
+        let report = signal::decode(&signal, &params.decoder)?;
+        let png = encode_png(&report.image)?;
+        // SAFETY: условия вызова
+        unsafe { out.write(into_bytes(png)) };
+        Ok(())
+    })
+}
+
+/// Освобождает байты, выданные библиотекой, и обнуляет `bytes`. `NULL` допустим
+///
+/// # Safety
+/// `bytes` — `NULL` или [`SstvBytes`], заполненная этой библиотекой и ещё не освобождённая
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn sstv_bytes_free(bytes: *mut SstvBytes) {
+    // SAFETY: условия вызова
+    let Some(bytes) = (unsafe { bytes.as_mut() }) else {
+        return;
+    };
+    if !bytes.data.is_null() {
+        // SAFETY: указатель и длина получены из `Box<[u8]>` в `into_bytes`
+        drop(unsafe { Box::from_raw(std::ptr::slice_from_raw_parts_mut(bytes.data, bytes.len)) });
+    }
+    bytes.data = std::ptr::null_mut();
+    bytes.len = 0;
+}
+
+/// Освобождает сэмплы, выданные библиотекой, и обнуляет `samples`. `NULL` допустим
+///
+/// # Safety
+/// `samples` — `NULL` или [`SstvSamples`], заполненная этой библиотекой и ещё
+/// не освобождённая
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn sstv_samples_free(samples: *mut SstvSamples) {
+    // SAFETY: условия вызова
+    let Some(samples) = (unsafe { samples.as_mut() }) else {
+        return;
+    };
+    if !samples.data.is_null() {
+        // SAFETY: указатель и длина получены из `Box<[f32]>` в `into_samples`
+        drop(unsafe {
+            Box::from_raw(std::ptr::slice_from_raw_parts_mut(
+                samples.data,
+                samples.len,
+            ))
+        });
+    }
+    samples.data = std::ptr::null_mut();
+    samples.len = 0;
+}
+
+/// Выполняет `f`, переводя ошибку и панику в код ошибки и текст [`sstv_last_error`]
+fn guard(f: impl FnOnce() -> Result<()>) -> c_int {
+    let (code, message) = match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(())) => (0, String::new()),
+        Ok(Err(e)) => {
+            let code = match &e {
+                SstvError::InvalidParams { field, .. } if ARGUMENTS.contains(field) => {
+                    SSTV_INVALID_ARGUMENT
+                }
+                _ => SSTV_ERROR,
+            };
+            (code, e.to_string())
+        }
+        Err(payload) => (
+            SSTV_ERROR,
+            payload
+                .downcast_ref::<&str>()
+                .map(|s| s.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| messages::ffi_panic().into()),
+        ),
+    };
+    // Внутренний ноль обрезал бы текст: заменяется, чтобы CString принял строку
+    let message = CString::new(message.replace('\0', " ")).unwrap_or_default();
+    LAST_ERROR.with(|e| *e.borrow_mut() = message);
+    code
+}
+
+/// # Safety
+/// `data` — `NULL` при `len == 0` или указатель на `len` элементов
+unsafe fn slice<'a, T>(data: *const T, len: usize) -> &'a [T] {
+    if data.is_null() || len == 0 {
+        &[]
+    } else {
+        // SAFETY: условия вызова
+        unsafe { std::slice::from_raw_parts(data, len) }
+    }
+}
+
+/// # Safety
+/// `json` — `NULL` или строка с нулём в конце
+unsafe fn params(json: *const c_char) -> Result<ProcessingParams> {
+    if json.is_null() {
+        return Ok(ProcessingParams::default());
+    }
+    // SAFETY: условия вызова
+    let json = unsafe { CStr::from_ptr(json) }
+        .to_str()
+        .map_err(|e| SstvError::invalid("params_json", e.to_string()))?;
+    serde_json::from_str(json).map_err(|e| SstvError::invalid("params_json", e.to_string()))
+}
+
+/// Копирует сигнал вызывающего; `NULL`, `data` без сэмплов при ненулевой `len` и тело
+/// изображения за пределами сэмплов — ошибки аргумента `input`
+///
+/// # Safety
+/// `input` — `NULL` или указатель на [`SstvSamples`] с `len` сэмплами по `data`
+unsafe fn read_signal(input: *const SstvSamples) -> Result<Signal> {
+    // SAFETY: условия вызова
+    let Some(input) = (unsafe { input.as_ref() }) else {
+        return Err(SstvError::invalid("input", messages::ffi_null_input()));
+    };
+    if input.data.is_null() && input.len > 0 {
+        return Err(SstvError::invalid(
+            "input",
+            messages::ffi_null_samples(input.len),
+        ));
+    }
+    if input.image_start > input.image_end || input.image_end > input.len {
+        return Err(SstvError::invalid(
+            "input",
+            messages::ffi_image_span(input.image_start, input.image_end, input.len),
+        ));
+    }
+    // SAFETY: условия вызова
+    let samples = unsafe { slice(input.data, input.len) }.to_vec();
+    Ok(Signal::with_image_span(
+        samples,
+        input.rate,
+        input.image_start..input.image_end,
+    ))
+}
+
+fn load_image(bytes: &[u8]) -> Result<DynamicImage> {
+    image::load_from_memory(bytes).map_err(|e| SstvError::io(messages::ffi_image_failed(), e))
+}
+
+fn encode_png(image: &DynamicImage) -> Result<Vec<u8>> {
+    let mut png = Vec::new();
+    image
+        .write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+        .map_err(|e| SstvError::io(messages::ffi_png_failed(), e))?;
+    Ok(png)
+}
+
+fn into_bytes(bytes: Vec<u8>) -> SstvBytes {
+    let len = bytes.len();
+    SstvBytes {
+        data: Box::into_raw(bytes.into_boxed_slice()).cast(),
+        len,
+    }
+}
+
+fn into_samples(signal: Signal) -> SstvSamples {
+    let span = signal.image_span();
+    let rate = signal.sample_rate();
+    let samples = signal.into_samples().into_boxed_slice();
+    SstvSamples {
+        len: samples.len(),
+        data: Box::into_raw(samples).cast(),
+        rate,
+        image_start: span.start,
+        image_end: span.end,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ptr;
+
+    fn last_error() -> String {
+        // SAFETY: строка из `LAST_ERROR` этого потока
+        unsafe { CStr::from_ptr(sstv_last_error()) }
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    #[test]
+    fn bad_input_is_an_invalid_argument() {
+        let mut out = SstvBytes {
+            data: ptr::null_mut(),
+            len: 0,
+        };
+        let mut samples = vec![0.0f32; 100];
+        let signal = |data: *mut f32, image_start, image_end| SstvSamples {
+            data,
+            len: 100,
+            rate: 8000,
+            image_start,
+            image_end,
+        };
+        let cases = [
+            (ptr::null(), messages::ffi_null_input().to_string()),
+            (
+                &signal(ptr::null_mut(), 0, 0) as *const _,
+                messages::ffi_null_samples(100),
+            ),
+            (
+                &signal(samples.as_mut_ptr(), 50, 200),
+                messages::ffi_image_span(50, 200, 100),
+            ),
+            (
+                &signal(samples.as_mut_ptr(), 60, 40),
+                messages::ffi_image_span(60, 40, 100),
+            ),
+        ];
+        for (input, message) in cases {
+            // SAFETY: `input` — `NULL` или живая `SstvSamples`, `out` доступна для записи
+            let code = unsafe { sstv_decode(input, ptr::null(), &mut out) };
+            assert_eq!(code, SSTV_INVALID_ARGUMENT, "{message}");
+            assert_eq!(last_error(), message);
+        }
+
+        let input = signal(samples.as_mut_ptr(), 0, 100);
+        let json = c"{\"noise\": ";
+        // SAFETY: как выше, `json` — строка с нулём в конце
+        let code = unsafe { sstv_decode(&input, json.as_ptr(), &mut out) };
+        assert_eq!(code, SSTV_INVALID_ARGUMENT);
+        // SAFETY: как выше
+        let code = unsafe { sstv_decode(&input, ptr::null(), &mut out) };
+        assert_eq!(code, SSTV_ERROR, "в тишине нечего декодировать");
+        assert!(!last_error().is_empty());
+        assert!(out.data.is_null());
+    }
+}
@@ -0,0 +1,594 @@
+use crate::buffer::SampleBuffer;
+use crate::dsp::Biquad;
+use crate::effect::{
+    BlockProcessor, ChannelEffect, EffectContext, EffectEstimate, EffectMix, StreamContext,
+    from_block_state, to_block_state,
+};
+use crate::error::{Result, SstvError};
+use crate::messages;
+use crate::modes::LEADER_HZ;
+use crate::processor::ProcessingParams;
+use serde::{Deserialize, Serialize};
+
+/// Наибольшее число звеньев фильтра: дальше крутизна уже не растёт заметно, а
+/// ошибки округления в каскаде — растут
+pub const MAX_FILTER_STAGES: u32 = 8;
+
+/// Предел наибольшего усиления АРУ, дБ: выше усиление тишины переполняется до
+/// бесконечности, а ноль на бесконечность — NaN
+pub const MAX_AGC_GAIN_DB: f32 = 120.0;
+
+/// Вид звукового фильтра приёмника
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FilterKind {
+    /// ФНЧ: срезает всё выше частоты среза
+    Lowpass,
+    /// ФВЧ: срезает всё ниже частоты среза
+    Highpass,
+    /// Полосовой фильтр вокруг центральной частоты; ширина полосы — частота / добротность
+    #[default]
+    Bandpass,
+}
+
+impl FilterKind {
+    pub const ALL: &'static [FilterKind] = &[
+        FilterKind::Lowpass,
+        FilterKind::Highpass,
+        FilterKind::Bandpass,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            FilterKind::Lowpass => messages::filter_kind_lowpass(),
+            FilterKind::Highpass => messages::filter_kind_highpass(),
+            FilterKind::Bandpass => messages::filter_kind_bandpass(),
+        }
+    }
+
+    /// Одно звено фильтра этого вида
+    fn biquad(&self, sample_rate: u32, freq: f32, q: f32) -> Biquad {
+        match self {
+            FilterKind::Lowpass => Biquad::lowpass(sample_rate, freq, q),
+            FilterKind::Highpass => Biquad::highpass(sample_rate, freq, q),
+            FilterKind::Bandpass => Biquad::bandpass(sample_rate, freq, q),
+        }
+    }
+}
+
+impl clap::ValueEnum for FilterKind {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[Self::Lowpass, Self::Highpass, Self::Bandpass]
+    }
+
+    fn to_possible_value<'a>(&self) -> Option<clap::builder::PossibleValue> {
+        Some(match self {
+            Self::Lowpass => clap::builder::PossibleValue::new("lowpass"),
+            Self::Highpass => clap::builder::PossibleValue::new("highpass"),
+            Self::Bandpass => clap::builder::PossibleValue::new("bandpass"),
+        })
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FilterParams {
+    /// Включить звуковой фильтр приёмника
+    pub enabled: bool,
+    pub kind: FilterKind,
+    /// Частота среза ФНЧ и ФВЧ или центральная частота полосового фильтра, Гц
+    pub freq_hz: f32,
+    /// Добротность звена: 1/√2 — срез без подъёма у ФНЧ и ФВЧ; у полосового —
+    /// отношение центральной частоты к ширине полосы
+    pub q: f32,
+    /// Число одинаковых звеньев подряд, 1–[`MAX_FILTER_STAGES`]: каждое добавляет
+    /// 12 дБ/октаву крутизны
+    pub stages: u32,
+    /// Доли смешивания выхода эффекта со входом
+    pub mix: EffectMix,
+}
+
+impl Default for FilterParams {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            kind: FilterKind::Bandpass,
+            freq_hz: 1900.0,
+            q: 2.0,
+            stages: 2,
+            mix: EffectMix::default(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NotchParams {
+    /// Включить режекторный фильтр приёмника
+    pub enabled: bool,
+    /// Частота вырезки, Гц. Внутри полосы SSTV (1100–2300 Гц) вырезает часть яркостей,
+    /// как ручная или автоматическая режекция, поставленная против несущей
+    pub freq_hz: f32,
+    /// Добротность: частота / ширина вырезки по уровню −3 дБ
+    pub q: f32,
+    /// Доли смешивания выхода эффекта со входом
+    pub mix: EffectMix,
+}
+
+impl Default for NotchParams {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            freq_hz: 1900.0,
+            q: 10.0,
+            mix: EffectMix::default(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AgcParams {
+    /// Включить АРУ приёмника
+    pub enabled: bool,
+    /// Уровень огибающей, к которому АРУ приводит сигнал, 0–1 полной шкалы
+    pub target: f32,
+    /// Постоянная времени атаки: как быстро усиление падает при росте уровня, мс
+    pub attack_ms: f32,
+    /// Постоянная времени восстановления: как быстро усиление растёт после спада, мс
+    pub release_ms: f32,
+    /// Наибольшее усиление, 0–[`MAX_AGC_GAIN_DB`] дБ: в паузах АРУ не поднимает шум выше
+    pub max_gain_db: f32,
+    /// Доли смешивания выхода эффекта со входом
+    pub mix: EffectMix,
+}
+
+impl Default for AgcParams {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            target: 0.5,
+            attack_ms: 5.0,
+            release_ms: 300.0,
+            max_gain_db: 30.0,
+            mix: EffectMix::default(),
+        }
+    }
+}
+
+/// Звуковой фильтр приёмника из звеньев второго порядка: узкий полосовой фильтр режет
+/// края полосы SSTV (чёрный и белый, синхроимпульсы), ФНЧ и ФВЧ — одну из сторон
+pub struct FilterProcessor {
+    #[deprecated(since = "0.1.0", note = "используйте `params()` и `set_params()`")]
+    pub params: FilterParams,
+}
+
+impl Default for FilterProcessor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[allow(deprecated)]
+impl FilterProcessor {
+    pub fn new() -> Self {
+        Self {
+            params: FilterParams::default(),
+        }
+    }
+
+    pub fn new_with_params(params: FilterParams) -> Self {
+        Self { params }
+    }
+
+    pub fn params(&self) -> &FilterParams {
+        &self.params
+    }
+
+    pub fn set_params(&mut self, params: FilterParams) {
+        self.params = params;
+    }
+
+    /// Каскад звеньев для частоты `sample_rate`; частота среза не выше Найквиста
+    fn cascade(&self, sample_rate: u32) -> Vec<Biquad> {
+        let p = &self.params;
+        let freq = p.freq_hz.clamp(1.0, sample_rate as f32 * 0.49);
+        let q = p.q.max(0.1);
+        let stage = p.kind.biquad(sample_rate, freq, q);
+        vec![stage; p.stages.clamp(1, MAX_FILTER_STAGES) as usize]
+    }
+
+    /// Пропускает сэмплы через каскад звеньев
+    pub fn apply_filter(&self, buffer: &mut SampleBuffer) {
+        if !self.is_enabled() {
+            return;
+        }
+        BiquadBlocks(self.cascade(buffer.rate)).process(&mut buffer.data);
+    }
+
+    /// Границы полосы по уровню −3 дБ всего каскада, Гц, найденные по его АЧХ
+    fn passband(&self, sample_rate: u32) -> (f32, f32) {
+        let cascade = self.cascade(sample_rate);
+        let gain = |freq: f32| -> f64 {
+            cascade
+                .iter()
+                .map(|s| s.magnitude(sample_rate, freq))
+                .product()
+        };
+        let nyquist = sample_rate as f32 / 2.0;
+        let peak = match self.params.kind {
+            FilterKind::Lowpass => gain(0.0),
+            FilterKind::Highpass => gain(nyquist),
+            FilterKind::Bandpass => gain(self.params.freq_hz.clamp(1.0, nyquist * 0.98)),
+        };
+        let level = peak / std::f64::consts::SQRT_2;
+        let passes = |freq: f32| gain(freq) >= level;
+        let step = 10.0;
+        let low = (0..)
+            .map(|k| k as f32 * step)
+            .take_while(|&f| f <= nyquist)
+            .find(|&f| passes(f))
+            .unwrap_or(0.0);
+        let high = (0..)
+            .map(|k| nyquist - k as f32 * step)
+            .take_while(|&f| f >= low)
+            .find(|&f| passes(f))
+            .unwrap_or(nyquist);
+        (low, high)
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.params.enabled && self.params.freq_hz > 0.0
+    }
+
+    /// Возвращает описание текущих настроек фильтра
+    pub fn describe(&self) -> String {
+        if !self.is_enabled() {
+            return messages::filter_off().to_string();
+        }
+        let p = &self.params;
+        messages::filter_describe(
+            p.kind.name(),
+            p.freq_hz,
+            p.q,
+            p.stages.clamp(1, MAX_FILTER_STAGES),
+        )
+    }
+}
+
+#[allow(deprecated)]
+impl ChannelEffect for FilterProcessor {
+    fn name(&self) -> &'static str {
+        messages::filter()
+    }
+
+    fn mix(&self) -> EffectMix {
+        self.params.mix
+    }
+
+    fn apply(&self, buffer: &mut SampleBuffer, _ctx: &EffectContext) -> Result<()> {
+        self.apply_filter(buffer);
+        Ok(())
+    }
+
+    fn is_enabled(&self) -> bool {
+        FilterProcessor::is_enabled(self)
+    }
+
+    fn describe(&self) -> String {
+        FilterProcessor::describe(self)
+    }
+
+    fn sync_params(&mut self, params: &ProcessingParams) {
+        self.params = params.filter.clone();
+    }
+
+    fn analyze(&self, buffer: &SampleBuffer, _ctx: &EffectContext) -> EffectEstimate {
+        EffectEstimate {
+            passband_hz: Some(self.passband(buffer.rate)),
+            ..EffectEstimate::new(self.name(), self.describe())
+        }
+    }
+
+    fn group_delay(&self, sample_rate: u32) -> f32 {
+        BiquadBlocks(self.cascade(sample_rate)).group_delay(sample_rate)
+    }
+
+    /// Звенья БИХ хранят своё состояние сами, поэтому блоки дают тот же выход
+    fn block_processor(&self, ctx: &StreamContext) -> Result<Box<dyn BlockProcessor>> {
+        Ok(Box::new(BiquadBlocks(self.cascade(ctx.sample_rate))))
+    }
+}
+
+/// Режекторный фильтр приёмника: одно звено, вырезающее узкую полосу вокруг частоты.
+/// Поставленный внутрь полосы SSTV, он выедает одну яркость — на картинке это полосы
+/// искажённого тона там, где частота строки проходит через вырезку
+pub struct NotchProcessor {
+    #[deprecated(since = "0.1.0", note = "используйте `params()` и `set_params()`")]
+    pub params: NotchParams,
+}
+
+impl Default for NotchProcessor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[allow(deprecated)]
+impl NotchProcessor {
+    pub fn new() -> Self {
+        Self {
+            params: NotchParams::default(),
+        }
+    }
+
+    pub fn new_with_params(params: NotchParams) -> Self {
+        Self { params }
+    }
+
+    pub fn params(&self) -> &NotchParams {
+        &self.params
+    }
+
+    pub fn set_params(&mut self, params: NotchParams) {
+        self.params = params;
+    }
+
+    fn biquad(&self, sample_rate: u32) -> Biquad {
+        let freq = self.params.freq_hz.clamp(1.0, sample_rate as f32 * 0.49);
+        Biquad::notch(sample_rate, freq, self.params.q.max(0.1))
+    }
+
+    /// Вырезает полосу из сэмплов
+    pub fn apply_notch(&self, buffer: &mut SampleBuffer) {
+        if self.is_enabled() {
+            BiquadBlocks(vec![self.biquad(buffer.rate)]).process(&mut buffer.data);
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.params.enabled && self.params.freq_hz > 0.0
+    }
+
+    /// Возвращает описание текущих настроек режекции
+    pub fn describe(&self) -> String {
+        if !self.is_enabled() {
+            return messages::notch_off().to_string();
+        }
+        messages::notch_describe(self.params.freq_hz, self.params.q)
+    }
+}
+
+#[allow(deprecated)]
+impl ChannelEffect for NotchProcessor {
+    fn name(&self) -> &'static str {
+        messages::notch()
+    }
+
+    fn mix(&self) -> EffectMix {
+        self.params.mix
+    }
+
+    fn apply(&self, buffer: &mut SampleBuffer, _ctx: &EffectContext) -> Result<()> {
+        self.apply_notch(buffer);
+        Ok(())
+    }
+
+    fn is_enabled(&self) -> bool {
+        NotchProcessor::is_enabled(self)
+    }
+
+    fn describe(&self) -> String {
+        NotchProcessor::describe(self)
+    }
+
+    fn sync_params(&mut self, params: &ProcessingParams) {
+        self.params = params.notch.clone();
+    }
+
+    fn group_delay(&self, sample_rate: u32) -> f32 {
+        BiquadBlocks(vec![self.biquad(sample_rate)]).group_delay(sample_rate)
+    }
+
+    fn block_processor(&self, ctx: &StreamContext) -> Result<Box<dyn BlockProcessor>> {
+        Ok(Box::new(BiquadBlocks(vec![self.biquad(ctx.sample_rate)])))
+    }
+}
+
+/// Звенья второго порядка блоками: состояние звеньев переходит из блока в блок. Весь
+/// буфер — тот же обработчик с одним блоком
+struct BiquadBlocks(Vec<Biquad>);
+
+impl BiquadBlocks {
+    fn process(&mut self, samples: &mut [f32]) {
+        for stage in &mut self.0 {
+            stage.process_slice(samples);
+        }
+    }
+
+    /// Групповая задержка каскада на средней частоте изображения, сэмплов
+    fn group_delay(&self, sample_rate: u32) -> f32 {
+        self.0
+            .iter()
+            .map(|stage| stage.group_delay(sample_rate, LEADER_HZ))
+            .sum::<f64>() as f32
+    }
+}
+
+impl BlockProcessor for BiquadBlocks {
+    fn process_block(&mut self, block: &mut [f32], _start: usize) -> Result<()> {
+        self.process(block);
+        Ok(())
+    }
+    fn save_state(&mut self) -> Result<serde_json::Value> {
+        let states: Vec<(f64, f64)> = self.0.iter().map(Biquad::state).collect();
+        to_block_state(&states)
+    }
+
+    fn restore_state(&mut self, state: &serde_json::Value) -> Result<()> {
+        let states: Vec<(f64, f64)> = from_block_state(state, messages::filter())?;
+        if states.len() != self.0.len() {
+            return Err(SstvError::invalid(
+                "state",
+                messages::filter_state_stages(states.len(), self.0.len()),
+            ));
+        }
+        for (stage, state) in self.0.iter_mut().zip(states) {
+            stage.set_state(state);
+        }
+        Ok(())
+    }
+}
+
+/// АРУ приёмника: детектор огибающей по модулю сигнала с разными постоянными атаки и
+/// восстановления и усиление, приводящее огибающую к целевому уровню. При замираниях
+/// подтягивает ослабленный сигнал вместе с шумом, после импульсной помехи на время
+/// восстановления глушит всё, что за ней
+pub struct AgcProcessor {
+    #[deprecated(since = "0.1.0", note = "используйте `params()` и `set_params()`")]
+    pub params: AgcParams,
+}
+
+impl Default for AgcProcessor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[allow(deprecated)]
+impl AgcProcessor {
+    pub fn new() -> Self {
+        Self {
+            params: AgcParams::default(),
+        }
+    }
+
+    pub fn new_with_params(params: AgcParams) -> Self {
+        Self { params }
+    }
+
+    pub fn params(&self) -> &AgcParams {
+        &self.params
+    }
+
+    pub fn set_params(&mut self, params: AgcParams) {
+        self.params = params;
+    }
+
+    /// Регулирует уровень сэмплов
+    pub fn apply_agc(&self, buffer: &mut SampleBuffer) {
+        if self.is_enabled() {
+            AgcState::new(&self.params, buffer.rate).process(&mut buffer.data);
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.params.enabled && self.params.target > 0.0
+    }
+
+    /// Возвращает описание текущих настроек АРУ
+    pub fn describe(&self) -> String {
+        if !self.is_enabled() {
+            return messages::agc_off().to_string();
+        }
+        let p = &self.params;
+        messages::agc_describe(p.target, p.attack_ms, p.release_ms, p.max_gain_db)
+    }
+}
+
+/// Постоянная одного полюса для постоянной времени `ms` на частоте `sample_rate`;
+/// нулевая постоянная — мгновенный отклик
+fn smoothing(ms: f32, sample_rate: u32) -> f32 {
+    if ms <= 0.0 {
+        return 0.0;
+    }
+    (-1000.0 / (ms * sample_rate as f32)).exp()
+}
+
+/// Состояние АРУ: огибающая переходит из блока в блок
+struct AgcState {
+    target: f32,
+    attack: f32,
+    release: f32,
+    max_gain: f32,
+    envelope: f32,
+}
+
+impl AgcState {
+    fn new(params: &AgcParams, sample_rate: u32) -> Self {
+        let max_gain = 10f32.powf(params.max_gain_db.clamp(0.0, MAX_AGC_GAIN_DB) / 20.0);
+        Self {
+            target: params.target,
+            attack: smoothing(params.attack_ms, sample_rate),
+            release: smoothing(params.release_ms, sample_rate),
+            max_gain,
+            // Начинаем с уровня, при котором усиление — целевое: без выброса в начале
+            envelope: params.target,
+        }
+    }
+
+    fn process(&mut self, samples: &mut [f32]) {
+        for sample in samples.iter_mut() {
+            let level = sample.abs();
+            let coef = if level > self.envelope {
+                self.attack
+            } else {
+                self.release
+            };
+            self.envelope = level + coef * (self.envelope - level);
+            let gain = if self.envelope > 0.0 {
+                (self.target / self.envelope).min(self.max_gain)
+            } else {
+                self.max_gain
+            };
+            *sample *= gain;
+        }
+    }
+}
+
+impl BlockProcessor for AgcState {
+    fn process_block(&mut self, block: &mut [f32], _start: usize) -> Result<()> {
+        self.process(block);
+        Ok(())
+    }
+    fn save_state(&mut self) -> Result<serde_json::Value> {
+        to_block_state(&self.envelope)
+    }
+
+    fn restore_state(&mut self, state: &serde_json::Value) -> Result<()> {
+        self.envelope = from_block_state(state, messages::agc())?;
+        Ok(())
+    }
+}
+
+#[allow(deprecated)]
+impl ChannelEffect for AgcProcessor {
+    fn name(&self) -> &'static str {
+        messages::agc()
+    }
+
+    fn mix(&self) -> EffectMix {
+        self.params.mix
+    }
+
+    fn apply(&self, buffer: &mut SampleBuffer, _ctx: &EffectContext) -> Result<()> {
+        self.apply_agc(buffer);
+        Ok(())
+    }
+
+    fn is_enabled(&self) -> bool {
+        AgcProcessor::is_enabled(self)
+    }
+
+    fn describe(&self) -> String {
+        AgcProcessor::describe(self)
+    }
+
+    fn sync_params(&mut self, params: &ProcessingParams) {
+        self.params = params.agc.clone();
+    }
+
+    fn block_processor(&self, ctx: &StreamContext) -> Result<Box<dyn BlockProcessor>> {
+        Ok(Box::new(AgcState::new(&self.params, ctx.sample_rate)))
+    }
+}
@@ -0,0 +1,851 @@
+use crate::blanker::{BlankerFill, BlankerParams};
+use crate::buffer::SampleBuffer;
+use crate::decoder::{DecoderBackend, DecoderParams};
+use crate::drift::DriftParams;
+use crate::dropout::{DropoutKind, DropoutParams, DropoutWindow};
+use crate::dsp::{ConvolutionBackend, Sideband};
+use crate::effect::{EffectMix, SanitizeMode};
+use crate::encoder::{EncoderBackend, EncoderParams};
+use crate::envelope::{
+    AdsrEnvelope, BurstEnvelope, CustomEnvelope, EnvelopeAnchor, EnvelopeKind, Interpolation,
+};
+use crate::fading::{FadingModel, FadingParams};
+use crate::filters::{AgcParams, FilterKind, FilterParams, NotchParams};
+use crate::interference::{Carrier, InterferenceParams, QrmTransmission};
+use crate::loudness::{LoudnessMeasure, LoudnessParams};
+use crate::messages;
+use crate::metadata::Metadata;
+use crate::metrics::{self, MetricSpace, QualityReport, Registration};
+use crate::multipath::{MultipathParams, MultipathTap};
+use crate::noise::{AmplitudeDistribution, ImpulseParams, NoiseColor, NoiseKind, NoiseParams};
+use crate::overlay::{MAX_OVERLAY_SIZE, OverlayColor, OverlayParams};
+use crate::palette::Palette;
+use crate::processor::{ProcessingParams, SSTVProcessor};
+use crate::quality::QualityTier;
+use crate::retarder::{RetarderParams, RetarderSource};
+use crate::scan_converter::{Phosphor, ScanConverterParams};
+use crate::signal::Signal;
+use crate::timeline::{EffectEvent, EventEffect, ScanChannel};
+use crate::transceiver::{TransceiverOutput, TransceiverParams};
+use image::{DynamicImage, GenericImageView, Rgb, RgbImage};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rsstv::SAMPLE_RATE;
+use std::ops::Range;
+use std::panic::{self, AssertUnwindSafe};
+
+/// Обычные частоты дискретизации
+const SAMPLE_RATES: &[u32] = &[8000, 11025, 22050, 44100, 48000, 96000];
+/// Заведомо неверные частоты: обработка должна отвергать их ошибкой
+const BAD_SAMPLE_RATES: &[u32] = &[0, 1, 4000];
+/// Наименьшая яркость канала в изображении сравнения декодеров. rsstv принимает за
+/// синхроимпульс любой тон ниже 1450 Гц, а выброс его ФНЧ 400 Гц на переходе к почти
+/// чёрному (1500 Гц) заходит туда: строка сбивается и на чистом сигнале
+const MIN_BLOCK_LEVEL: u8 = 48;
+
+/// Что проверяет случай
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum FuzzTarget {
+    /// Конвейер эффектов канала на произвольном буфере, см. [`Signal::degrade`]
+    Effects,
+    /// Декодер на произвольном буфере
+    Decode,
+    /// Полный прогон [`SSTVProcessor::process`] по случайному изображению
+    Process,
+    /// Одна и та же искажённая передача декодируется встроенным декодером и rsstv,
+    /// см. [`compare_decoders`]
+    Differential,
+}
+
+impl FuzzTarget {
+    pub const ALL: &'static [FuzzTarget] = &[
+        FuzzTarget::Effects,
+        FuzzTarget::Decode,
+        FuzzTarget::Process,
+        FuzzTarget::Differential,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            FuzzTarget::Effects => messages::fuzz_effects(),
+            FuzzTarget::Decode => messages::fuzz_decode(),
+            FuzzTarget::Process => messages::fuzz_process(),
+            FuzzTarget::Differential => messages::fuzz_differential(),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct FuzzParams {
+    /// Зерно первого случая; случай `k` строится из зерна `seed + k`
+    pub seed: u64,
+    /// Случаев конвейера эффектов
+    pub effect_cases: usize,
+    /// Случаев декодера
+    pub decode_cases: usize,
+    /// Полных прогонов: они медленные, секунды каждый
+    pub process_cases: usize,
+    /// Сравнений декодеров на одной передаче
+    pub differential_cases: usize,
+    /// Наименьший PSNR между совмещёнными изображениями двух декодеров, дБ: ниже —
+    /// расхождение. rsstv оценивает пиксель средним без отсчётов между соседями, и края
+    /// блоков у него размыты иначе, чем у встроенного: на передачах [`compare_decoders`]
+    /// выходит 24–26 дБ, порог оставляет запас на шум
+    pub min_agreement_db: f64,
+    /// Наибольшая длина случайного буфера, сэмплов
+    pub max_len: usize,
+}
+
+impl Default for FuzzParams {
+    fn default() -> Self {
+        Self {
+            seed: 0,
+            effect_cases: 200,
+            decode_cases: 20,
+            process_cases: 2,
+            differential_cases: 4,
+            min_agreement_db: 20.0,
+            max_len: 20000,
+        }
+    }
+}
+
+/// Нарушение инварианта в одном случае
+#[derive(Clone, Debug)]
+pub struct FuzzFailure {
+    pub target: FuzzTarget,
+    /// Зерно случая: тот же случай повторяется [`run_case`] с этим зерном
+    pub seed: u64,
+    pub message: String,
+}
+
+impl FuzzFailure {
+    pub fn describe(&self) -> String {
+        messages::fuzz_failure(self.target.name(), self.seed, &self.message)
+    }
+}
+
+/// Расхождение встроенного декодера и rsstv на одной передаче
+#[derive(Clone, Debug)]
+pub struct DecoderDifference {
+    /// Зерно случая, см. [`compare_decoders`]
+    pub seed: u64,
+    /// Изображение rsstv относительно изображения встроенного декодера после совмещения
+    pub quality: QualityReport,
+    /// Сдвиг изображения rsstv относительно изображения встроенного декодера
+    pub registration: Registration,
+    pub rsstv_lines: u32,
+    pub native_lines: u32,
+}
+
+impl DecoderDifference {
+    pub fn describe(&self) -> String {
+        messages::fuzz_difference(
+            self.seed,
+            &self.quality.describe(),
+            &self.registration.describe(),
+            self.rsstv_lines,
+            self.native_lines,
+        )
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct FuzzReport {
+    /// Проверено случаев
+    pub cases: usize,
+    /// Случаев, закончившихся ошибкой (допустимо: неверные параметры отвергаются)
+    pub rejected: usize,
+    pub failures: Vec<FuzzFailure>,
+    /// Сравнения декодеров, в которых оба декодировали передачу
+    pub differences: Vec<DecoderDifference>,
+}
+
+impl FuzzReport {
+    pub fn passed(&self) -> bool {
+        self.failures.is_empty()
+    }
+
+    /// Сравнение с наибольшим расхождением декодеров
+    pub fn worst_difference(&self) -> Option<&DecoderDifference> {
+        self.differences
+            .iter()
+            .min_by(|a, b| a.quality.psnr.total_cmp(&b.quality.psnr))
+    }
+}
+
+/// Прогоняет все случаи по порядку: эффекты, декодер, полные прогоны, сравнения
+/// декодеров. `progress` получает каждый проверенный случай
+pub fn run(params: &FuzzParams, mut progress: impl FnMut(FuzzTarget, u64)) -> FuzzReport {
+    let mut report = FuzzReport::default();
+    let counts = [
+        (FuzzTarget::Effects, params.effect_cases),
+        (FuzzTarget::Decode, params.decode_cases),
+        (FuzzTarget::Process, params.process_cases),
+        (FuzzTarget::Differential, params.differential_cases),
+    ];
+    let mut seed = params.seed;
+    for (target, count) in counts {
+        for _ in 0..count {
+            let outcome = if target == FuzzTarget::Differential {
+                compare_decoders(seed).and_then(|difference| {
+                    let Some(difference) = difference else {
+                        return Ok(false);
+                    };
+                    let agreement = check_agreement(&difference, params.min_agreement_db);
+                    report.differences.push(difference);
+                    agreement
+                })
+            } else {
+                run_case(target, seed, params.max_len)
+            };
+            match outcome {
+                Ok(true) => {}
+                Ok(false) => report.rejected += 1,
+                Err(failure) => report.failures.push(failure),
+            }
+            report.cases += 1;
+            progress(target, seed);
+            seed = seed.wrapping_add(1);
+        }
+    }
+    report
+}
+
+/// Строит случай из зерна `seed` и проверяет его. `Ok(false)` — обработка вернула ошибку,
+/// что допустимо; паника, NaN или бесконечность в выходе и неверная длина — нарушение.
+/// Сравнение декодеров проверяется с порогом [`FuzzParams::default`]
+pub fn run_case(target: FuzzTarget, seed: u64, max_len: usize) -> Result<bool, FuzzFailure> {
+    if target == FuzzTarget::Differential {
+        return match compare_decoders(seed)? {
+            Some(difference) => {
+                check_agreement(&difference, FuzzParams::default().min_agreement_db)
+            }
+            None => Ok(false),
+        };
+    }
+    let mut rng = StdRng::seed_from_u64(seed);
+    let fail = |message: String| FuzzFailure {
+        target,
+        seed,
+        message,
+    };
+    let outcome = panic::catch_unwind(AssertUnwindSafe(|| match target {
+        FuzzTarget::Effects => check_effects(&mut rng, seed, max_len),
+        FuzzTarget::Decode => check_decode(&mut rng, seed, max_len),
+        FuzzTarget::Process => check_process(&mut rng, seed),
+        FuzzTarget::Differential => unreachable!(),
+    }));
+    match outcome {
+        Ok(result) => result.map_err(fail),
+        Err(payload) => Err(fail(messages::fuzz_panic(&panic_message(&*payload)))),
+    }
+}
+
+fn check_effects(rng: &mut StdRng, seed: u64, max_len: usize) -> Result<bool, String> {
+    let mut params = random_params(rng);
+    params.seed = Some(seed);
+    // Исправления называют эффект, выдавший NaN, — это точнее итоговой проверки
+    params.sanitize = SanitizeMode::Repair;
+    let rate = random_rate(rng);
+    let samples = random_samples(rng, max_len);
+    let span = random_span(rng, samples.len());
+    let retarder = rng
+        .random_bool(0.5)
+        .then(|| Signal::new(random_samples(rng, max_len), rate));
+
+    let len = samples.len();
+    let mut signal = Signal::with_image_span(samples, rate, span);
+    let Ok(sanitized) = signal.degrade(&params, retarder.as_ref()) else {
+        return Ok(false);
+    };
+    if let Some(event) = sanitized.first() {
+        return Err(messages::fuzz_sanitized(&event.describe()));
+    }
+
+    let target_rate = params.signal_rate();
+    let expected = if rate == target_rate {
+        len
+    } else {
+        (len as u64 * target_rate as u64 / rate.max(1) as u64) as usize
+    };
+    if signal.len() != expected {
+        return Err(messages::fuzz_length(
+            signal.len(),
+            expected,
+            rate,
+            target_rate,
+        ));
+    }
+    if signal.sample_rate() != target_rate {
+        return Err(messages::fuzz_rate(signal.sample_rate(), target_rate));
+    }
+    check_finite(signal.samples())?;
+    let span = signal.image_span();
+    if span.start > span.end || span.end > signal.len() {
+        return Err(messages::fuzz_span(&format!("{:?}", span), signal.len()));
+    }
+    Ok(true)
+}
+
+fn check_decode(rng: &mut StdRng, seed: u64, max_len: usize) -> Result<bool, String> {
+    let mut params = random_params(rng);
+    params.seed = Some(seed);
+    let (samples, rate) = if rng.random_bool(0.5) || params.encoder.validate().is_err() {
+        (random_samples(rng, max_len), random_rate(rng))
+    } else {
+        // Со случайным порогом и окном поиска передача редко находится: половина
+        // случаев ищет её как обычно, чтобы дойти до разбора строк
+        if rng.random_bool(0.5) {
+            let defaults = DecoderParams::default();
+            params.decoder.sync_threshold = defaults.sync_threshold;
+            params.decoder.search_window_ms = defaults.search_window_ms;
+            params.decoder.max_freq_deviation_hz = defaults.max_freq_deviation_hz;
+        }
+        cut_transmission(rng, &params)
+    };
+    let mut processor = SSTVProcessor::new_with_params(params);
+    match processor.decode_samples(&SampleBuffer::mono(samples, rate)) {
+        Ok(report) => check_image(&report.image),
+        Err(_) => Ok(false),
+    }
+}
+
+/// Настоящая передача случайного изображения, оборванная в случайном месте: декодер
+/// находит заголовок и доходит до конца сигнала посреди изображения
+fn cut_transmission(rng: &mut StdRng, params: &ProcessingParams) -> (Vec<f32>, u32) {
+    let image = random_image(rng);
+    let mut samples = crate::signal::encode(&image, &params.encoder).into_samples();
+    let end = rng.random_range(0..=samples.len());
+    samples.truncate(end);
+    (samples, SAMPLE_RATE as u32)
+}
+
+fn check_process(rng: &mut StdRng, seed: u64) -> Result<bool, String> {
+    let mut params = random_params(rng);
+    params.seed = Some(seed);
+    let main = random_image(rng);
+    let retarder = rng.random_bool(0.5).then(|| random_image(rng));
+    if rng.random_bool(0.3) {
+        let mut qrm = QrmTransmission::new(random_image(rng));
+        qrm.level = value(rng, 0.0, 2.0);
+        qrm.offset_hz = value(rng, -500.0, 500.0);
+        qrm.delay_ms = value(rng, 0.0, 5000.0);
+        params.interference.qrm = Some(qrm);
+    }
+
+    let mut processor = SSTVProcessor::new_with_params(params);
+    let report = match processor.process(&main, retarder.as_ref()) {
+        Ok(report) => report,
+        Err(_) => return Ok(false),
+    };
+    check_finite(processor.last_samples())?;
+    check_image(&report.image)
+}
+
+/// Кодирует изображение из крупных цветных блоков, искажает сигнал умеренным шумом,
+/// расстройкой и замираниями и декодирует один и тот же буфер встроенным декодером и
+/// rsstv. `Ok(None)` — оба декодера отвергли сигнал; если отверг только один, это
+/// нарушение. Изображения сравниваются после совмещения: rsstv теряет первую строку —
+/// биты VIS (1100 и 1300 Гц) попадают в его допуск синхроимпульса ±250 Гц и сливаются с
+/// синхроимпульсом строки 0
+pub fn compare_decoders(seed: u64) -> Result<Option<DecoderDifference>, FuzzFailure> {
+    let fail = |message: String| FuzzFailure {
+        target: FuzzTarget::Differential,
+        seed,
+        message,
+    };
+    let outcome = panic::catch_unwind(AssertUnwindSafe(|| {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let params = impaired_params(&mut rng, seed);
+        let image = block_image(&mut rng);
+        let samples = SSTVProcessor::new_with_params(params.clone())
+            .render_samples(&image, None)
+            .map_err(|e| e.to_string())?;
+
+        let decode = |backend: DecoderBackend| {
+            let mut params = params.clone();
+            params.decoder.backend = backend;
+            SSTVProcessor::new_with_params(params).decode_samples(&samples)
+        };
+        let rsstv = decode(DecoderBackend::Rsstv);
+        let native = decode(DecoderBackend::Native);
+        match (rsstv, native) {
+            (Err(_), Err(_)) => Ok(None),
+            (Ok(_), Err(e)) => Err(messages::fuzz_only_decoded(
+                DecoderBackend::Rsstv.name(),
+                DecoderBackend::Native.name(),
+                &e.to_string(),
+            )),
+            (Err(e), Ok(_)) => Err(messages::fuzz_only_decoded(
+                DecoderBackend::Native.name(),
+                DecoderBackend::Rsstv.name(),
+                &e.to_string(),
+            )),
+            (Ok(rsstv), Ok(native)) => {
+                check_image(&rsstv.image)?;
+                check_image(&native.image)?;
+                let (quality, registration) =
+                    metrics::compare_registered(&native.image, &rsstv.image, MetricSpace::Srgb);
+                Ok(Some(DecoderDifference {
+                    seed,
+                    quality,
+                    registration,
+                    rsstv_lines: rsstv.lines_decoded,
+                    native_lines: native.lines_decoded,
+                }))
+            }
+        }
+    }));
+    match outcome {
+        Ok(result) => result.map_err(fail),
+        Err(payload) => Err(fail(messages::fuzz_panic(&panic_message(&*payload)))),
+    }
+}
+
+fn check_agreement(difference: &DecoderDifference, min_db: f64) -> Result<bool, FuzzFailure> {
+    if difference.quality.psnr < min_db {
+        return Err(FuzzFailure {
+            target: FuzzTarget::Differential,
+            seed: difference.seed,
+            message: messages::fuzz_disagreement(&difference.quality.describe(), min_db),
+        });
+    }
+    Ok(true)
+}
+
+/// Параметры по умолчанию с искажениями, которые оба декодера обязаны переносить:
+/// С/Ш не ниже 24 дБ, расстройка до ±30 Гц, неглубокие замирания
+fn impaired_params(rng: &mut impl Rng, seed: u64) -> ProcessingParams {
+    let mut params = ProcessingParams {
+        seed: Some(seed),
+        ..ProcessingParams::default()
+    };
+    params.noise.level = rng.random_range(0..=20);
+    params.drift.offset_hz = rng.random_range(-30.0..=30.0);
+    if rng.random_bool(0.5) {
+        params.fading.model = FadingModel::Rayleigh;
+        params.fading.depth = rng.random_range(0.0..=0.3);
+        params.fading.rate_hz = rng.random_range(0.1..=1.0);
+    }
+    params.decoder.afc = params.drift.offset_hz.abs() > 10.0;
+    params
+}
+
+/// Изображение режима из блоков 40×32 случайного цвета: сдвиг на пиксель между
+/// декодерами меняет только края блоков, а не каждый пиксель, как у шума. Каналы не
+/// темнее [`MIN_BLOCK_LEVEL`]
+fn block_image(rng: &mut impl Rng) -> DynamicImage {
+    let colors: Vec<Rgb<u8>> = (0..64)
+        .map(|_| {
+            Rgb(std::array::from_fn(|_| {
+                rng.random_range(MIN_BLOCK_LEVEL..=u8::MAX)
+            }))
+        })
+        .collect();
+    let image = RgbImage::from_fn(320, 256, |x, y| colors[(y / 32 * 8 + x / 40) as usize]);
+    DynamicImage::ImageRgb8(image)
+}
+
+fn check_finite(samples: &[f32]) -> Result<(), String> {
+    match samples.iter().position(|x| !x.is_finite()) {
+        Some(i) => Err(messages::fuzz_not_finite(i, samples[i])),
+        None => Ok(()),
+    }
+}
+
+fn check_image(image: &DynamicImage) -> Result<bool, String> {
+    let (w, h) = image.dimensions();
+    if w == 0 || h == 0 {
+        return Err(messages::fuzz_empty_image(w, h));
+    }
+    Ok(true)
+}
+
+pub(crate) fn panic_message(payload: &(dyn std::any::Any + Send)) -> String {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        messages::fuzz_no_message().to_string()
+    }
+}
+
+/// Частота дискретизации; изредка неверная
+fn random_rate(rng: &mut impl Rng) -> u32 {
+    if rng.random_bool(0.05) {
+        *pick(rng, BAD_SAMPLE_RATES)
+    } else {
+        *pick(rng, SAMPLE_RATES)
+    }
+}
+
+fn pick<'a, T>(rng: &mut impl Rng, items: &'a [T]) -> &'a T {
+    &items[rng.random_range(0..items.len())]
+}
+
+/// Значение из `lo..=hi` или одно из крайних: ноль, почти ноль, границы, отрицательное,
+/// далеко за верхней границей
+fn value(rng: &mut impl Rng, lo: f32, hi: f32) -> f32 {
+    match rng.random_range(0..10) {
+        0 => 0.0,
+        1 => 1e-6,
+        2 => lo,
+        3 => hi,
+        4 => -hi,
+        5 => hi * 1000.0,
+        _ => rng.random_range(lo..=hi),
+    }
+}
+
+/// Буфер длиной от нуля до `max_len`: тишина, шум, тон или перегруженный сигнал
+fn random_samples(rng: &mut impl Rng, max_len: usize) -> Vec<f32> {
+    let len = match rng.random_range(0..4) {
+        0 => rng.random_range(0..4),
+        1 => rng.random_range(0..256),
+        _ => rng.random_range(0..=max_len),
+    };
+    let amplitude = value(rng, 0.0, 1.0);
+    let freq = rng.random_range(0.0..0.5f32);
+    match rng.random_range(0..4) {
+        0 => vec![0.0; len],
+        1 => (0..len)
+            .map(|_| amplitude * rng.random_range(-1.0..=1.0))
+            .collect(),
+        2 => (0..len)
+            .map(|i| amplitude * (std::f32::consts::TAU * freq * i as f32).sin())
+            .collect(),
+        _ => (0..len)
+            .map(|i| if i % 2 == 0 { amplitude } else { -amplitude } * 4.0)
+            .collect(),
+    }
+}
+
+/// Тело изображения: целиком, пустое, внутри сигнала или за его концом
+fn random_span(rng: &mut impl Rng, len: usize) -> Range<usize> {
+    match rng.random_range(0..4) {
+        0 => 0..len,
+        1 => len..len,
+        2 => {
+            let start = rng.random_range(0..=len);
+            start..rng.random_range(start..=len)
+        }
+        _ => len / 2..len * 2 + 10,
+    }
+}
+
+fn random_image(rng: &mut impl Rng) -> DynamicImage {
+    let w = rng.random_range(1..=400);
+    let h = rng.random_range(1..=300);
+    let image = RgbImage::from_fn(w, h, |_, _| Rgb(rng.random()));
+    DynamicImage::ImageRgb8(image)
+}
+
+fn random_envelope(rng: &mut impl Rng) -> EnvelopeKind {
+    match rng.random_range(0..9) {
+        0 => EnvelopeKind::Const,
+        1 => EnvelopeKind::Sin,
+        2 => EnvelopeKind::Tri,
+        3 => EnvelopeKind::Saw,
+        4 => EnvelopeKind::Square,
+        5 => EnvelopeKind::Rand,
+        6 => EnvelopeKind::Adsr(AdsrEnvelope {
+            attack: value(rng, 0.0, 1.0),
+            decay: value(rng, 0.0, 1.0),
+            sustain: value(rng, 0.0, 1.0),
+            release: value(rng, 0.0, 1.0),
+        }),
+        7 => EnvelopeKind::Burst(BurstEnvelope {
+            on_ms: value(rng, 0.0, 1000.0),
+            period_ms: value(rng, 0.0, 2000.0),
+            jitter_ms: value(rng, 0.0, 500.0),
+            ramp_ms: value(rng, 0.0, 100.0),
+        }),
+        _ => {
+            let count = rng.random_range(0..6);
+            EnvelopeKind::Custom(CustomEnvelope {
+                points: (0..count)
+                    .map(|_| (value(rng, 0.0, 1.0), value(rng, 0.0, 1.0)))
+                    .collect(),
+                interpolation: *pick(rng, Interpolation::ALL),
+            })
+        }
+    }
+}
+
+/// Окраска шума; полоса — любая, в том числе перевёрнутая и выше Найквиста
+fn random_noise_color(rng: &mut impl Rng) -> NoiseColor {
+    match rng.random_range(0..4) {
+        0 => NoiseColor::White,
+        1 => NoiseColor::Pink,
+        2 => NoiseColor::Brown,
+        _ => NoiseColor::BandLimited {
+            low_hz: value(rng, 0.0, 30000.0),
+            high_hz: value(rng, 0.0, 30000.0),
+        },
+    }
+}
+
+/// Доли смешивания эффекта: чаще всего без смешивания, иначе любые
+fn random_mix(rng: &mut impl Rng) -> EffectMix {
+    if rng.random_bool(0.5) {
+        return EffectMix::default();
+    }
+    EffectMix {
+        wet: value(rng, 0.0, 1.0),
+        dry: value(rng, 0.0, 1.0),
+    }
+}
+
+/// Случайное сочетание всех параметров обработки, включая крайние и неверные значения
+pub fn random_params(rng: &mut impl Rng) -> ProcessingParams {
+    let mut params = ProcessingParams {
+        noise: NoiseParams {
+            level: rng.random_range(0..=u8::MAX),
+            kind: *pick(rng, NoiseKind::ALL),
+            color: random_noise_color(rng),
+            impulse: ImpulseParams {
+                rate_hz: value(rng, 0.0, 50.0),
+                duration_ms: value(rng, 0.0, 100.0),
+                amplitude: *pick(rng, AmplitudeDistribution::ALL),
+            },
+            env: random_envelope(rng),
+            repeat: value(rng, 0.0, 10.0),
+            anchor: *pick(rng, EnvelopeAnchor::ALL),
+            mix: random_mix(rng),
+        },
+        retarder: RetarderParams {
+            level: value(rng, 0.0, 1.0),
+            env: random_envelope(rng),
+            repeat: value(rng, 0.0, 10.0),
+            delay_ms: rng.random_range(0..=5000),
+            anchor: *pick(rng, EnvelopeAnchor::ALL),
+            source: pick(rng, RetarderSource::SIMPLE).clone(),
+            mix: random_mix(rng),
+        },
+        multipath: MultipathParams {
+            taps: (0..rng.random_range(0..4))
+                .map(|_| MultipathTap {
+                    delay_ms: value(rng, 0.0, 20.0),
+                    gain: value(rng, -1.0, 1.0),
+                    phase_deg: value(rng, -180.0, 180.0),
+                })
+                .collect(),
+            mix: random_mix(rng),
+        },
+        fading: FadingParams {
+            model: *pick(rng, FadingModel::ALL),
+            depth: value(rng, 0.0, 1.0),
+            rate_hz: value(rng, 0.0, 5.0),
+            mix: random_mix(rng),
+        },
+        drift: DriftParams {
+            offset_hz: value(rng, -300.0, 300.0),
+            drift_hz_per_s: value(rng, -5.0, 5.0),
+            wobble_hz: value(rng, 0.0, 50.0),
+            wobble_rate_hz: value(rng, 0.0, 10.0),
+            mix: random_mix(rng),
+        },
+        interference: InterferenceParams {
+            carriers: (0..rng.random_range(0..3))
+                .map(|_| Carrier {
+                    freq_hz: value(rng, 0.0, 4000.0),
+                    level: value(rng, 0.0, 2.0),
+                    duty: value(rng, 0.0, 1.0),
+                    period_ms: value(rng, 0.0, 1000.0),
+                })
+                .collect(),
+            qrm: None,
+            mix: random_mix(rng),
+        },
+        blanker: BlankerParams {
+            enabled: rng.random_bool(0.5),
+            threshold: value(rng, 0.0, 20.0),
+            hold_ms: value(rng, 0.0, 10.0),
+            average_ms: value(rng, 0.0, 100.0),
+            fill: *pick(rng, BlankerFill::ALL),
+            mix: random_mix(rng),
+        },
+        transceiver: TransceiverParams {
+            enabled: rng.random_bool(0.5),
+            low_hz: value(rng, 0.0, 1000.0),
+            high_hz: value(rng, 1000.0, 5000.0),
+            drive_db: value(rng, -20.0, 40.0),
+            alc_release_ms: value(rng, 0.0, 1000.0),
+            carrier_leak: value(rng, 0.0, 1.0),
+            iq_gain_db: value(rng, -6.0, 6.0),
+            iq_phase_deg: value(rng, -30.0, 30.0),
+            sideband: *pick(rng, Sideband::ALL),
+            output: *pick(rng, TransceiverOutput::ALL),
+        },
+        filter: FilterParams {
+            enabled: rng.random_bool(0.5),
+            kind: *pick(rng, FilterKind::ALL),
+            freq_hz: value(rng, 0.0, 30000.0),
+            q: value(rng, 0.0, 50.0),
+            stages: rng.random_range(0..=20),
+            mix: random_mix(rng),
+        },
+        notch: NotchParams {
+            enabled: rng.random_bool(0.5),
+            freq_hz: value(rng, 0.0, 30000.0),
+            q: value(rng, 0.0, 100.0),
+            mix: random_mix(rng),
+        },
+        agc: AgcParams {
+            enabled: rng.random_bool(0.5),
+            target: value(rng, 0.0, 2.0),
+            attack_ms: value(rng, 0.0, 100.0),
+            release_ms: value(rng, 0.0, 5000.0),
+            max_gain_db: value(rng, -10.0, 80.0),
+            mix: random_mix(rng),
+        },
+        dropout: DropoutParams {
+            kind: *pick(rng, DropoutKind::ALL),
+            attenuation_db: value(rng, 0.0, 100.0),
+            windows: (0..rng.random_range(0..3))
+                .map(|_| DropoutWindow::new(value(rng, -1000.0, 5000.0), value(rng, 0.0, 2000.0)))
+                .collect(),
+            rate_per_min: value(rng, 0.0, 60.0),
+            duration_ms: value(rng, 0.0, 2000.0),
+            mix: random_mix(rng),
+        },
+        decoder: DecoderParams {
+            backend: *pick(rng, DecoderBackend::ALL),
+            sync_threshold: value(rng, 0.0, 1.0),
+            search_window_ms: rng.random_range(0..=10000),
+            max_freq_deviation_hz: value(rng, 0.0, 500.0),
+            afc: rng.random_bool(0.5),
+            conceal: rng.random_bool(0.5),
+            deskew: rng.random_bool(0.5),
+            slant_ppm: rng
+                .random_bool(0.3)
+                .then(|| value(rng, -20000.0, 20000.0) as f64),
+            palette: *pick(rng, Palette::ALL),
+            converter: ScanConverterParams {
+                blur: value(rng, 0.0, 1.0),
+                scanlines: value(rng, 0.0, 1.0),
+                phosphor: *pick(rng, Phosphor::ALL),
+                tint: value(rng, 0.0, 1.0),
+            },
+        },
+        encoder: EncoderParams {
+            backend: *pick(rng, EncoderBackend::ALL),
+            header: rng.random_bool(0.8),
+            leader_ms: value(rng, 0.0, 600.0),
+            break_ms: value(rng, 0.0, 20.0),
+            vis_code: rng.random(),
+            vis_bit_ms: value(rng, 0.0, 60.0),
+            vis_attenuation_db: value(rng, 0.0, 40.0),
+            vis_flip_bits: rng.random_range(0..=10),
+            timing_scale: value(rng, 0.5, 2.0) as f64,
+            amplitude: value(rng, 0.0, 1.0),
+            ..EncoderParams::default()
+        },
+        overlay: OverlayParams {
+            lines: (0..rng.random_range(0..3))
+                .map(|_| pick(rng, &["", "CALL 599", "{date} {time}", "Привет"]).to_string())
+                .collect(),
+            x: rng.random_range(-400..400),
+            y: rng.random_range(-300..300),
+            size: value(rng, 1.0, MAX_OVERLAY_SIZE),
+            color: OverlayColor(rng.random()),
+            font: None,
+            shadow: rng.random_bool(0.5),
+        },
+        metadata: Metadata {
+            fields: (0..rng.random_range(0..3))
+                .map(|_| {
+                    let key = pick(rng, &["operator", "grid", "qth"]).to_string();
+                    (key, pick(rng, &["R1ABC", "KO85", "", "Москва"]).to_string())
+                })
+                .collect(),
+            overlay: rng.random_bool(0.5),
+        },
+        convolution: *pick(rng, ConvolutionBackend::ALL),
+        parallel: rng.random_bool(0.5),
+        quality: *pick(rng, QualityTier::ALL),
+        // Проверка выключена: иначе она скрыла бы NaN, которые ищет прогон
+        sanitize: SanitizeMode::Off,
+        output_gain_db: value(rng, -40.0, 40.0),
+        loudness: LoudnessParams {
+            measure: *pick(rng, LoudnessMeasure::ALL),
+            target_db: value(rng, -40.0, 0.0),
+            true_peak_db: value(rng, -12.0, 0.0),
+        },
+        metric_space: *pick(rng, MetricSpace::ALL),
+        events: Vec::new(),
+        sample_rate: random_rate(rng),
+        seed: None,
+    };
+    params.events = random_events(rng, &params);
+    params
+}
+
+/// События сценария с уже разыгранными параметрами эффектов: окна могут выходить за
+/// сигнал и иметь нулевую длину
+fn random_events(rng: &mut impl Rng, params: &ProcessingParams) -> Vec<EffectEvent> {
+    (0..rng.random_range(0..3))
+        .map(|_| EffectEvent {
+            start_s: value(rng, -1.0, 5.0),
+            duration_s: value(rng, 0.0, 3.0),
+            anchor: *pick(rng, EnvelopeAnchor::ALL),
+            channel: *pick(
+                rng,
+                &[None, Some(ScanChannel::Red), Some(ScanChannel::Green)],
+            ),
+            ramp_ms: value(rng, 0.0, 100.0),
+            effect: match rng.random_range(0..4) {
+                0 => EventEffect::Noise(params.noise.clone()),
+                1 => EventEffect::Fading(params.fading.clone()),
+                2 => EventEffect::Drift(params.drift.clone()),
+                _ => EventEffect::Dropout(params.dropout.clone()),
+            },
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_passes(target: FuzzTarget, seeds: Range<u64>, max_len: usize) {
+        for seed in seeds {
+            if let Err(failure) = run_case(target, seed, max_len) {
+                panic!("{}", failure.describe());
+            }
+        }
+    }
+
+    #[test]
+    fn effects_survive_random_params() {
+        assert_passes(FuzzTarget::Effects, 0..150, 4000);
+    }
+
+    #[test]
+    fn decoder_survives_random_input() {
+        assert_passes(FuzzTarget::Decode, 0..4, 4000);
+    }
+
+    #[test]
+    fn decoders_agree_on_impaired_signal() {
+        for seed in 0..2 {
+            let difference = compare_decoders(seed)
+                .unwrap_or_else(|failure| panic!("{}", failure.describe()))
+                .expect("оба декодера отвергли передачу");
+            assert!(
+                difference.quality.psnr >= FuzzParams::default().min_agreement_db,
+                "{}",
+                difference.describe()
+            );
+        }
+    }
+
+    #[test]
+    fn same_seed_gives_same_params() {
+        let a = random_params(&mut StdRng::seed_from_u64(7));
+        let b = random_params(&mut StdRng::seed_from_u64(7));
+        assert_eq!(format!("{:?}", a), format!("{:?}", b));
+    }
+}
@@ -0,0 +1,364 @@
+use crate::buffer::SampleBuffer;
+use crate::dsp::{self, ConvolutionBackend};
+use crate::effect::{self, ChannelEffect, EffectContext, EffectEstimate, EffectMix};
+use crate::encoder::{self, EncoderParams};
+use crate::error::{Result, SstvError};
+use crate::messages;
+use crate::processor::ProcessingParams;
+use image::DynamicImage;
+use rsstv::SAMPLE_RATE;
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+use std::str::FromStr;
+use std::sync::Arc;
+
+/// Мешающая несущая: непрерывная или манипулированная (вкл/выкл) синусоида
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Carrier {
+    /// Частота, Гц
+    pub freq_hz: f32,
+    /// Амплитуда относительно пиковой амплитуды полезного сигнала
+    pub level: f32,
+    /// Доля периода манипуляции, когда несущая включена (1.0 — непрерывная)
+    pub duty: f32,
+    /// Период манипуляции, мс
+    pub period_ms: f32,
+}
+
+impl Carrier {
+    pub fn new(freq_hz: f32, level: f32) -> Self {
+        Self {
+            freq_hz,
+            level,
+            duty: 1.0,
+            period_ms: 500.0,
+        }
+    }
+
+    /// Огибающая манипуляции в момент `t` секунд при шаге сэмплов `dt` секунд: 1 —
+    /// включена, 0 — выключена. Фронты сглажены polyBLEP на соседних сэмплах, чтобы
+    /// щелчки манипуляции не заворачивались за частоту Найквиста
+    fn gate(&self, t: f64, dt: f64) -> f64 {
+        if self.duty >= 1.0 || self.period_ms <= 0.0 {
+            return 1.0;
+        }
+        let period = self.period_ms as f64 / 1000.0;
+        let phase = t % period;
+        let off = self.duty as f64 * period;
+        let naive = if phase < off { 1.0 } else { 0.0 };
+        // Включение в начале периода (и следующего), выключение после доли `duty`
+        naive + poly_blep(phase / dt) + poly_blep((phase - period) / dt)
+            - poly_blep((phase - off) / dt)
+    }
+}
+
+/// Поправка polyBLEP к единичной ступеньке на расстоянии `d` сэмплов от фронта: вблизи
+/// фронта ступенька заменяется квадратичным сплайном, дальше поправки нет
+fn poly_blep(d: f64) -> f64 {
+    if (0.0..1.0).contains(&d) {
+        -(1.0 - d) * (1.0 - d) / 2.0
+    } else if (-1.0..0.0).contains(&d) {
+        (1.0 + d) * (1.0 + d) / 2.0
+    } else {
+        0.0
+    }
+}
+
+impl FromStr for Carrier {
+    type Err = SstvError;
+
+    /// Разбирает несущую в формате `частота_Гц:уровень[:скважность[:период_мс]]`
+    fn from_str(s: &str) -> Result<Self> {
+        let parts: Vec<&str> = s.split(':').collect();
+        if !(2..=4).contains(&parts.len()) {
+            return Err(SstvError::invalid(
+                "carriers",
+                messages::expected_format(messages::format_carrier(), s),
+            ));
+        }
+
+        let parse = |v: &str, what: &str| -> Result<f32> {
+            v.trim()
+                .parse()
+                .map_err(|_| SstvError::invalid("carriers", messages::invalid_value(what, v)))
+        };
+
+        let mut carrier = Self::new(
+            parse(parts[0], messages::value_frequency())?,
+            parse(parts[1], messages::value_level())?,
+        );
+        if carrier.freq_hz <= 0.0 {
+            return Err(SstvError::invalid(
+                "carriers",
+                messages::carrier_frequency(carrier.freq_hz),
+            ));
+        }
+        if let Some(duty) = parts.get(2) {
+            carrier.duty = parse(duty, messages::value_duty())?;
+            if !(0.0..=1.0).contains(&carrier.duty) {
+                return Err(SstvError::invalid(
+                    "carriers",
+                    messages::carrier_duty(carrier.duty),
+                ));
+            }
+        }
+        if let Some(period) = parts.get(3) {
+            carrier.period_ms = parse(period, messages::value_period())?;
+            if carrier.period_ms <= 0.0 {
+                return Err(SstvError::invalid(
+                    "carriers",
+                    messages::carrier_period(carrier.period_ms),
+                ));
+            }
+        }
+        Ok(carrier)
+    }
+}
+
+/// Вторая SSTV-передача на соседней частоте (соканальная QRM)
+#[derive(Clone, Debug)]
+pub struct QrmTransmission {
+    /// Изображение, которое передаёт мешающая станция
+    pub image: Arc<DynamicImage>,
+    /// СКЗ помехи относительно СКЗ полезного сигнала
+    pub level: f32,
+    /// Сдвиг частоты относительно полезного сигнала, Гц
+    pub offset_hz: f32,
+    /// Задержка начала передачи, мс
+    pub delay_ms: f32,
+}
+
+impl QrmTransmission {
+    pub fn new(image: DynamicImage) -> Self {
+        Self {
+            image: Arc::new(image),
+            level: 0.5,
+            offset_hz: 0.0,
+            delay_ms: 0.0,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct InterferenceParams {
+    /// Мешающие несущие
+    pub carriers: Vec<Carrier>,
+    /// Мешающая SSTV-передача. В пресеты не сохраняется: изображение задаётся отдельно
+    #[serde(skip)]
+    pub qrm: Option<QrmTransmission>,
+    /// Доли смешивания выхода эффекта со входом
+    pub mix: EffectMix,
+}
+
+/// Помехи от других станций: несущие (телеграф, «свист») и чужая SSTV-передача
+pub struct InterferenceProcessor {
+    #[deprecated(since = "0.1.0", note = "используйте `params()` и `set_params()`")]
+    pub params: InterferenceParams,
+    /// Параметры кодера, которым кодируется мешающая передача
+    encoder: EncoderParams,
+}
+
+impl Default for InterferenceProcessor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[allow(deprecated)]
+impl InterferenceProcessor {
+    pub fn new() -> Self {
+        Self::new_with_params(InterferenceParams::default())
+    }
+
+    pub fn new_with_params(params: InterferenceParams) -> Self {
+        Self {
+            params,
+            encoder: EncoderParams::default(),
+        }
+    }
+
+    pub fn params(&self) -> &InterferenceParams {
+        &self.params
+    }
+
+    pub fn set_params(&mut self, params: InterferenceParams) {
+        self.params = params;
+    }
+
+    /// Подмешивает помехи. Уровни задаются относительно полезного сигнала,
+    /// поэтому результат не зависит от амплитуды кодера
+    pub fn apply_interference(
+        &self,
+        buffer: &mut SampleBuffer,
+        convolution: ConvolutionBackend,
+    ) -> Result<()> {
+        let sample_rate = buffer.rate;
+        let samples = &mut buffer.data;
+        if !self.is_enabled() || samples.is_empty() {
+            return Ok(());
+        }
+
+        let rms = (samples.iter().map(|x| x * x).sum::<f32>() / samples.len() as f32).sqrt();
+        let fs = sample_rate as f64;
+
+        // Несущая на частоте Найквиста и выше в канал с такой частотой не проходит
+        for carrier in self
+            .params
+            .carriers
+            .iter()
+            .filter(|c| c.level != 0.0 && (c.freq_hz as f64) < fs / 2.0)
+        {
+            let amplitude = carrier.level * rms * std::f32::consts::SQRT_2;
+            let step = 2.0 * PI * carrier.freq_hz as f64 / fs;
+            for (i, sample) in samples.iter_mut().enumerate() {
+                let gate = carrier.gate(i as f64 / fs, 1.0 / fs);
+                if gate != 0.0 {
+                    *sample += amplitude * (gate * (step * i as f64).sin()) as f32;
+                }
+            }
+        }
+
+        if let Some(qrm) = self.params.qrm.as_ref().filter(|q| q.level > 0.0) {
+            // Кодер выдаёт сигнал на своей частоте; в канал он попадает на частоте сигнала
+            let mut other = dsp::resample(
+                &encoder::encode(&qrm.image, &self.encoder),
+                SAMPLE_RATE as u32,
+                sample_rate,
+            );
+            if qrm.offset_hz != 0.0 {
+                let offset = qrm.offset_hz as f64;
+                dsp::shift_by_phase(&mut other, convolution, |i| {
+                    2.0 * PI * offset * i as f64 / fs
+                });
+            }
+
+            let other_rms =
+                (other.iter().map(|x| x * x).sum::<f32>() / other.len().max(1) as f32).sqrt();
+            if other_rms > 0.0 {
+                let gain = qrm.level * rms / other_rms;
+                let delay = (qrm.delay_ms / 1000.0 * sample_rate as f32).round() as usize;
+                for (sample, o) in samples.iter_mut().skip(delay).zip(&other) {
+                    *sample += gain * o;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Заменяет список несущих
+    pub fn set_carriers(&mut self, carriers: Vec<Carrier>) {
+        self.params.carriers = carriers;
+    }
+
+    /// Добавляет мешающую несущую
+    pub fn add_carrier(&mut self, carrier: Carrier) {
+        self.params.carriers.push(carrier);
+    }
+
+    /// Устанавливает мешающую SSTV-передачу
+    pub fn set_qrm(&mut self, qrm: Option<QrmTransmission>) {
+        self.params.qrm = qrm;
+    }
+
+    /// Проверяет, есть ли хотя бы одна слышимая помеха
+    pub fn is_enabled(&self) -> bool {
+        self.params
+            .carriers
+            .iter()
+            .any(|c| c.level != 0.0 && c.duty > 0.0)
+            || self.params.qrm.as_ref().is_some_and(|q| q.level > 0.0)
+    }
+
+    /// Возвращает описание текущих помех
+    pub fn describe(&self) -> String {
+        if !self.is_enabled() {
+            return messages::interference_off().to_string();
+        }
+
+        let mut parts: Vec<String> = self
+            .params
+            .carriers
+            .iter()
+            .filter(|c| c.level != 0.0 && c.duty > 0.0)
+            .map(|c| {
+                if c.duty >= 1.0 {
+                    messages::interference_carrier(c.freq_hz, c.level)
+                } else {
+                    messages::interference_keyed_carrier(
+                        c.freq_hz,
+                        c.level,
+                        c.duty * 100.0,
+                        c.period_ms,
+                    )
+                }
+            })
+            .collect();
+        if let Some(qrm) = self.params.qrm.as_ref().filter(|q| q.level > 0.0) {
+            parts.push(messages::interference_qrm(
+                qrm.offset_hz,
+                qrm.level,
+                qrm.delay_ms,
+            ));
+        }
+        messages::interference_describe(&parts.join(", "))
+    }
+}
+
+#[allow(deprecated)]
+impl ChannelEffect for InterferenceProcessor {
+    fn name(&self) -> &'static str {
+        messages::interference()
+    }
+
+    fn mix(&self) -> EffectMix {
+        self.params.mix
+    }
+
+    fn apply(&self, buffer: &mut SampleBuffer, ctx: &EffectContext) -> Result<()> {
+        self.apply_interference(buffer, ctx.convolution)
+    }
+
+    fn is_enabled(&self) -> bool {
+        InterferenceProcessor::is_enabled(self)
+    }
+
+    fn describe(&self) -> String {
+        InterferenceProcessor::describe(self)
+    }
+
+    fn sync_params(&mut self, params: &ProcessingParams) {
+        self.params = params.interference.clone();
+        self.encoder = params.encoder.clone();
+    }
+
+    /// Уровни помех заданы относительно СКЗ сигнала: несущая даёт `level²` своей
+    /// доли включения, чужая передача — `level²`
+    fn analyze(&self, _buffer: &SampleBuffer, _ctx: &EffectContext) -> EffectEstimate {
+        let carriers: f32 = self
+            .params
+            .carriers
+            .iter()
+            .map(|c| {
+                let duty = if c.duty >= 1.0 || c.period_ms <= 0.0 {
+                    1.0
+                } else {
+                    c.duty.max(0.0)
+                };
+                c.level * c.level * duty
+            })
+            .sum();
+        let qrm = self
+            .params
+            .qrm
+            .as_ref()
+            .filter(|q| q.level > 0.0)
+            .map_or(0.0, |q| q.level * q.level);
+        let power = carriers + qrm;
+        EffectEstimate {
+            added_power_db: (power > 0.0).then(|| effect::power_db(power)),
+            ..EffectEstimate::new(self.name(), self.describe())
+        }
+    }
+}
@@ -0,0 +1,351 @@
+use crate::decoder::DecoderBackend;
+use crate::encoder::EncoderBackend;
+use crate::error::{Result, SstvError};
+use crate::messages;
+use crate::modes::MARTIN_M1;
+use crate::processor::ProcessingParams;
+use std::path::Path;
+
+/// Частоты звуковых карт, к ближайшей из которых приводится частота из файла, если
+/// номинальная не указана
+const STANDARD_RATES: &[f64] = &[
+    8000.0, 11025.0, 12000.0, 16000.0, 22050.0, 24000.0, 32000.0, 44100.0, 48000.0, 96000.0,
+];
+
+/// Самое большое правдоподобное отклонение часов карты, ppm: больше — значит, в файле
+/// не та номинальная частота
+const MAX_CLOCK_PPM: f64 = 50_000.0;
+
+/// Названия режимов в MMSSTV (`Martin 1`, `Scottie DX`) и QSSTV (`M1`, `SDX`) с кодом VIS.
+/// Сравниваются без регистра, пробелов, дефисов и подчёркиваний
+const MODES: &[(&[&str], &str, u8)] = &[
+    (&["martin1", "martinm1", "m1"], "Martin M1", 44),
+    (&["martin2", "martinm2", "m2"], "Martin M2", 40),
+    (&["scottie1", "scotties1", "s1"], "Scottie 1", 60),
+    (&["scottie2", "scotties2", "s2"], "Scottie 2", 56),
+    (&["scottiedx", "sdx"], "Scottie DX", 76),
+    (&["robot36", "r36"], "Robot 36", 8),
+    (&["robot72", "r72"], "Robot 72", 12),
+    (&["pd90"], "PD90", 99),
+    (&["pd120"], "PD120", 95),
+    (&["pd180"], "PD180", 96),
+    (&["sc2180", "wraasesc2180"], "Wraase SC2-180", 55),
+];
+
+/// Ключи файла, которые понимает импорт; каждый — несколько написаний из MMSSTV
+/// и QSSTV, сравниваются так же, как названия режимов
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Key {
+    Mode,
+    Vis,
+    /// Номинальная частота карты, Гц
+    SampleRate,
+    /// Измеренная частота приёма, Гц (калибровка часов)
+    RxClock,
+    /// Измеренная частота передачи, Гц
+    TxClock,
+    /// Поправка частоты передачи к номинальной, Гц (`TxSampOffset` в MMSSTV)
+    TxClockOffset,
+    /// Наклон приёма, ppm
+    RxSlant,
+    /// Наклон передачи, ppm
+    TxSlant,
+    /// Расстройка, Гц
+    FreqOffset,
+}
+
+const KEYS: &[(&[&str], Key)] = &[
+    (
+        &["mode", "txmode", "sstvmode", "defaultmode", "modename"],
+        Key::Mode,
+    ),
+    (&["vis", "viscode"], Key::Vis),
+    (
+        &["samplerate", "samplingrate", "basefreq", "soundrate"],
+        Key::SampleRate,
+    ),
+    (
+        &["rxclock", "clock", "sampfreq", "rxsampfreq", "samplefreq"],
+        Key::RxClock,
+    ),
+    (&["txclock", "txsampfreq"], Key::TxClock),
+    (&["txsampoffset", "txclockoffset"], Key::TxClockOffset),
+    (&["slant", "slantppm", "rxslant", "rxppm"], Key::RxSlant),
+    (&["txslant", "txppm"], Key::TxSlant),
+    (
+        &[
+            "freqoffset",
+            "frequencyoffset",
+            "txfreqoffset",
+            "tuneoffset",
+            "afcoffset",
+        ],
+        Key::FreqOffset,
+    ),
+];
+
+/// Режим из файла: название в этой библиотеке и код VIS
+#[derive(Clone, Debug, PartialEq)]
+pub struct ForeignMode {
+    /// Название режима, как его пишет этот файл
+    pub original: String,
+    /// Название режима, если оно узнано
+    pub name: Option<&'static str>,
+    pub vis_code: Option<u8>,
+}
+
+/// Настройки станции из конфигурации MMSSTV (`MMSSTV.ini`) или QSSTV (`qsstv.conf`):
+/// режим, калибровка часов звуковой карты и расстройка. Читаются строки `ключ=значение`,
+/// разделы `[...]`, комментарии и незнакомые ключи пропускаются
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct StationImport {
+    pub mode: Option<ForeignMode>,
+    /// Наклон приёма: отклонение часов карты при приёме, ppm
+    pub rx_slant_ppm: Option<f64>,
+    /// Наклон передачи: отклонение часов карты при передаче, ppm
+    pub tx_slant_ppm: Option<f64>,
+    /// Расстройка станции, Гц
+    pub freq_offset_hz: Option<f32>,
+    /// Ключи файла, которые импорт не понимает, по порядку
+    pub ignored: Vec<String>,
+}
+
+impl StationImport {
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path).map_err(|e| {
+            SstvError::io(messages::import_read_failed(&path.display().to_string()), e)
+        })?;
+        Self::parse(&text)
+    }
+
+    /// Разбирает текст конфигурации. Ошибка — если значение узнанного ключа не число
+    /// или в файле нет ни одного узнанного ключа
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut import = Self::default();
+        let mut rate = None;
+        let (mut rx_clock, mut tx_clock, mut tx_offset) = (None, None, None);
+
+        for line in text.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with(['#', ';', '[']) {
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            // QSettings пишет ключи групп через обратную косую черту: `SOUND\rxClock`
+            let key = key.trim().rsplit(['\\', '/']).next().unwrap_or_default();
+            let value = value.trim().trim_matches('"');
+            let Some(known) = KEYS
+                .iter()
+                .find(|(aliases, _)| aliases.contains(&normalize(key).as_str()))
+                .map(|&(_, known)| known)
+            else {
+                import.ignored.push(key.to_string());
+                continue;
+            };
+            match known {
+                Key::Mode => {
+                    let name = normalize(value);
+                    let found = MODES
+                        .iter()
+                        .find(|(aliases, _, _)| aliases.contains(&name.as_str()));
+                    import.mode = Some(ForeignMode {
+                        original: value.to_string(),
+                        name: found.map(|&(_, name, _)| name),
+                        vis_code: found.map(|&(_, _, vis)| vis),
+                    });
+                }
+                Key::Vis => {
+                    let vis = number(key, value)?;
+                    if !(0.0..128.0).contains(&vis) {
+                        return Err(SstvError::invalid(
+                            "import",
+                            messages::import_bad_vis(value),
+                        ));
+                    }
+                    // Код без названия режима тоже узнаёт режим
+                    let vis = vis as u8;
+                    let mode = import.mode.get_or_insert_with(|| ForeignMode {
+                        original: format!("VIS {}", vis),
+                        name: MODES
+                            .iter()
+                            .find(|&&(_, _, code)| code == vis)
+                            .map(|&(_, name, _)| name),
+                        vis_code: None,
+                    });
+                    mode.vis_code = Some(vis);
+                }
+                Key::SampleRate => rate = Some(number(key, value)?),
+                Key::RxClock => rx_clock = Some(number(key, value)?),
+                Key::TxClock => tx_clock = Some(number(key, value)?),
+                Key::TxClockOffset => tx_offset = Some(number(key, value)?),
+                Key::RxSlant => import.rx_slant_ppm = Some(number(key, value)?),
+                Key::TxSlant => import.tx_slant_ppm = Some(number(key, value)?),
+                Key::FreqOffset => import.freq_offset_hz = Some(number(key, value)? as f32),
+            }
+        }
+
+        // Часы — измеренная частота карты: наклон — её отклонение от номинальной
+        if let Some(clock) = rx_clock {
+            import.rx_slant_ppm = Some(clock_ppm(clock, rate)?);
+        }
+        // Поправка передачи в MMSSTV отсчитывается от частоты приёма
+        let tx_clock = tx_clock
+            .or(tx_offset.map(|offset| rx_clock.or(rate).unwrap_or(STANDARD_RATES[1]) + offset));
+        if let Some(clock) = tx_clock {
+            import.tx_slant_ppm = Some(clock_ppm(clock, rate)?);
+        }
+
+        if import.mode.is_none()
+            && import.rx_slant_ppm.is_none()
+            && import.tx_slant_ppm.is_none()
+            && import.freq_offset_hz.is_none()
+        {
+            return Err(SstvError::invalid("import", messages::import_nothing()));
+        }
+        Ok(import)
+    }
+
+    /// Переносит настройки в `params`: ненулевой наклон приёма — в ручной наклон
+    /// встроенного декодера, наклон передачи — в масштаб сетки встроенного кодера,
+    /// расстройку — в постоянный уход частоты канала. Возвращает замечания: что сменилось помимо
+    /// этого и что перенести нельзя
+    pub fn apply(&self, params: &mut ProcessingParams) -> Vec<String> {
+        let mut notes = Vec::new();
+        if let Some(mode) = &self.mode {
+            match mode.name {
+                Some(name) if name == MARTIN_M1.name => {}
+                _ => notes.push(messages::import_mode_unsupported(
+                    mode.name.unwrap_or(&mode.original),
+                )),
+            }
+        }
+        if let Some(ppm) = self.rx_slant_ppm.filter(|&ppm| ppm != 0.0) {
+            params.decoder.slant_ppm = Some(ppm);
+            if params.decoder.backend != DecoderBackend::Native {
+                params.decoder.backend = DecoderBackend::Native;
+                notes.push(messages::import_native_decoder());
+            }
+        }
+        if let Some(ppm) = self.tx_slant_ppm.filter(|&ppm| ppm != 0.0) {
+            // Карта с быстрыми часами играет сигнал быстрее: строки короче
+            params.encoder.timing_scale = 1.0 / (1.0 + ppm * 1e-6);
+            if params.encoder.backend != EncoderBackend::Native {
+                params.encoder.backend = EncoderBackend::Native;
+                notes.push(messages::import_native_encoder());
+            }
+        }
+        if let Some(offset) = self.freq_offset_hz {
+            params.drift.offset_hz = offset;
+        }
+        if !self.ignored.is_empty() {
+            notes.push(messages::import_ignored(self.ignored.len()));
+        }
+        notes
+    }
+
+    pub fn describe(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some(mode) = &self.mode {
+            let name = mode.name.unwrap_or(&mode.original);
+            parts.push(match mode.vis_code {
+                Some(vis) => messages::import_mode_vis(name, vis),
+                None => messages::import_mode(name),
+            });
+        }
+        if let Some(ppm) = self.rx_slant_ppm {
+            parts.push(messages::import_rx_slant(ppm));
+        }
+        if let Some(ppm) = self.tx_slant_ppm {
+            parts.push(messages::import_tx_slant(ppm));
+        }
+        if let Some(offset) = self.freq_offset_hz {
+            parts.push(messages::import_offset(offset));
+        }
+        parts.join(", ")
+    }
+}
+
+/// Ключ или название без регистра, пробелов, дефисов и подчёркиваний
+fn normalize(s: &str) -> String {
+    s.chars()
+        .filter(|c| !matches!(c, ' ' | '-' | '_'))
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Число из значения ключа `key`; десятичная запятая тоже понимается
+fn number(key: &str, value: &str) -> Result<f64> {
+    value
+        .replace(',', ".")
+        .parse::<f64>()
+        .ok()
+        .filter(|v| v.is_finite())
+        .ok_or_else(|| SstvError::invalid("import", messages::import_bad_value(key, value)))
+}
+
+/// Отклонение измеренной частоты карты `clock` от номинальной `rate`, ppm. Без
+/// номинальной берётся ближайшая из стандартных
+fn clock_ppm(clock: f64, rate: Option<f64>) -> Result<f64> {
+    let nominal = rate.unwrap_or_else(|| {
+        STANDARD_RATES
+            .iter()
+            .copied()
+            .min_by(|a, b| (a - clock).abs().total_cmp(&(b - clock).abs()))
+            .unwrap_or(clock)
+    });
+    let ppm = (clock / nominal - 1.0) * 1e6;
+    if !(nominal > 0.0 && ppm.abs() <= MAX_CLOCK_PPM) {
+        return Err(SstvError::invalid(
+            "import",
+            messages::import_clock_too_far(clock, nominal),
+        ));
+    }
+    Ok(ppm)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn imports_mmsstv_and_qsstv_settings() {
+        let mmsstv = StationImport::parse(
+            "[Setup]\nMode=Martin 1\nClock=11025.55\nTxSampOffset=-0,55\nFreqOffset=-12.5\nSkin=3\n",
+        )
+        .unwrap();
+        let mode = mmsstv.mode.as_ref().unwrap();
+        assert_eq!((mode.name, mode.vis_code), (Some("Martin M1"), Some(44)));
+        assert!((mmsstv.rx_slant_ppm.unwrap() - 49.9).abs() < 0.1);
+        assert!(mmsstv.tx_slant_ppm.unwrap().abs() < 1e-6);
+        assert_eq!(mmsstv.freq_offset_hz, Some(-12.5));
+        assert_eq!(mmsstv.ignored, ["Skin"]);
+
+        let mut params = ProcessingParams::default();
+        let notes = mmsstv.apply(&mut params);
+        assert_eq!(params.decoder.slant_ppm, mmsstv.rx_slant_ppm);
+        assert_eq!(params.decoder.backend, DecoderBackend::Native);
+        assert_eq!(params.drift.offset_hz, -12.5);
+        // Передача без поправки: кодер остаётся прежним
+        assert_eq!(params.encoder.backend, EncoderBackend::Rsstv);
+        assert_eq!(notes.len(), 2);
+
+        let qsstv =
+            StationImport::parse("[SOUND]\nSOUND\\rxClock=48000\ntxClock=47995.2\nmode=\"S1\"\n")
+                .unwrap();
+        assert_eq!(qsstv.rx_slant_ppm, Some(0.0));
+        assert!((qsstv.tx_slant_ppm.unwrap() + 100.0).abs() < 1e-6);
+        assert_eq!(qsstv.mode.as_ref().unwrap().name, Some("Scottie 1"));
+        let mut params = ProcessingParams::default();
+        let notes = qsstv.apply(&mut params);
+        assert_eq!(params.decoder.slant_ppm, None);
+        assert!(params.encoder.timing_scale > 1.0);
+        assert_eq!(params.encoder.backend, EncoderBackend::Native);
+        assert!(notes.iter().any(|n| n.contains("Scottie 1")));
+
+        assert!(StationImport::parse("[Setup]\nSkin=3\n").is_err());
+        assert!(StationImport::parse("Clock=abc\n").is_err());
+        assert!(StationImport::parse("Clock=9000\nSampleRate=11025\n").is_err());
+    }
+}
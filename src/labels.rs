@@ -0,0 +1,229 @@
+use crate::encoder;
+use crate::error::{Result, SstvError};
+use crate::messages;
+use crate::modes::{MARTIN_M1, ModeTiming};
+use crate::processor::ProcessingParams;
+use crate::retarder::RetarderSource;
+use crate::timeline::EventEffect;
+use image::{DynamicImage, GrayImage, ImageFormat, Luma, Rgb, RgbImage};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+/// Разметка одного искажения для обучающей выборки: какие пиксели изображения режима
+/// передавались, пока оно действовало. Каналы маски — цвета пикселя: развёртка красного,
+/// зелёного и синего идёт в разное время, и событие на одной развёртке портит один цвет
+#[derive(Clone, Debug)]
+pub struct ImpairmentMask {
+    /// Имя для файла: ключ эффекта, у событий сценария — с номером, `event2_fading`
+    pub key: String,
+    /// Название эффекта, как в описании канала
+    pub name: &'static str,
+    /// Маска размера режима: 255 в канале цвета, развёртка которого попала под искажение
+    pub mask: RgbImage,
+}
+
+impl ImpairmentMask {
+    fn empty(key: String, name: &'static str, timing: &ModeTiming) -> Self {
+        Self {
+            key,
+            name,
+            mask: RgbImage::new(timing.spec.width, timing.spec.height),
+        }
+    }
+
+    /// Доля затронутых значений пикселей, 0–1
+    pub fn coverage(&self) -> f32 {
+        let raw = self.mask.as_raw();
+        raw.iter().filter(|&&v| v > 0).count() as f32 / raw.len().max(1) as f32
+    }
+
+    /// Строки изображения, в которых затронут хотя бы один пиксель
+    pub fn lines(&self) -> Vec<u32> {
+        let (width, height) = self.mask.dimensions();
+        (0..height)
+            .filter(|&y| (0..width).any(|x| self.mask.get_pixel(x, y).0 != [0; 3]))
+            .collect()
+    }
+
+    pub fn describe(&self) -> String {
+        messages::labels_mask(self.name, self.coverage() * 100.0, self.lines().len())
+    }
+
+    /// Отмечает пиксели, переданные сэмплами `window`; `channel` — только этот цвет
+    fn mark(&mut self, timing: &ModeTiming, window: Range<usize>, channel: Option<usize>) {
+        let span = timing.image_span();
+        for i in window.start.max(span.start)..window.end.min(span.end) {
+            if let Some((x, y, c)) = timing.sample_to_pixel(i)
+                && channel.is_none_or(|only| only == c)
+            {
+                self.mask.get_pixel_mut(x, y).0[c] = 255;
+            }
+        }
+    }
+}
+
+/// Маски искажений прогона с параметрами `params` для сигнала длиной `len` с частотой
+/// `sample_rate` (после эффектов, см. [`ProcessingParams::signal_rate`]). Маски строятся
+/// по расписанию эффектов и разметке режима, сам сигнал не нужен:
+///
+/// - эффекты канала на всё время (ретардер, многолучёвость, замирания, уход частоты,
+///   помехи, шум) — всё изображение. Ретардер с источником
+///   [`RetarderSource::Image`] без картинки призрака (`retarder_image`) ничего не
+///   подмешивает и маски не получает;
+/// - события сценария — пиксели в окне события, с `channel` — только этого цвета;
+/// - выпадения — пиксели в явных окнах `dropout.windows`. Случайные выпадения
+///   (`rate_per_min`) тянутся из генератора прогона вместе с шумом, заранее они не
+///   известны и в маску не попадают
+///
+/// Тракт приёмника — подавитель помех, фильтры, АРУ — искажением не считается
+pub fn masks(
+    params: &ProcessingParams,
+    retarder_image: bool,
+    len: usize,
+    sample_rate: u32,
+) -> Vec<ImpairmentMask> {
+    let timing = encoder::timing(&params.encoder, sample_rate, len);
+    let span = timing.image_span();
+    let mut masks = Vec::new();
+
+    let ghost = retarder_image || params.retarder.source != RetarderSource::Image;
+    let channel_effects = [
+        ghost.then(|| EventEffect::Retarder(params.retarder.clone())),
+        Some(EventEffect::Multipath(params.multipath.clone())),
+        Some(EventEffect::Fading(params.fading.clone())),
+        Some(EventEffect::Drift(params.drift.clone())),
+        Some(EventEffect::Interference(params.interference.clone())),
+        Some(EventEffect::Noise(params.noise.clone())),
+    ];
+    for effect in channel_effects.into_iter().flatten() {
+        if effect.processor().is_enabled() {
+            let mut mask = ImpairmentMask::empty(effect.key().to_string(), effect.name(), &timing);
+            mask.mask.pixels_mut().for_each(|p| *p = Rgb([255; 3]));
+            masks.push(mask);
+        }
+    }
+
+    for (k, event) in params.events.iter().enumerate() {
+        if !event.effect.processor().is_enabled() {
+            continue;
+        }
+        let mut mask = ImpairmentMask::empty(
+            format!("event{}_{}", k + 1, event.effect.key()),
+            event.effect.name(),
+            &timing,
+        );
+        mask.mark(
+            &timing,
+            event.window(len, sample_rate, &span),
+            event.channel.map(|c| c.index()),
+        );
+        masks.push(mask);
+    }
+
+    let dropout = &params.dropout;
+    if !dropout.windows.is_empty() {
+        let mut mask = ImpairmentMask::empty("dropout".to_string(), messages::dropout(), &timing);
+        let fs = sample_rate as f64 / 1000.0;
+        for window in &dropout.windows {
+            let start = span.start as f64 + window.start_ms as f64 * fs;
+            let end = start + window.duration_ms.max(0.0) as f64 * fs;
+            let at = |t: f64| t.clamp(0.0, len as f64) as usize;
+            mask.mark(&timing, at(start)..at(end), None);
+        }
+        masks.push(mask);
+    }
+    masks
+}
+
+/// Объединение масок в одну полутоновую: 255, если пиксель затронут хоть одним
+/// искажением хоть в одном цвете. Без масок — чёрная маска размера `width`×`height`
+pub fn union(masks: &[ImpairmentMask], width: u32, height: u32) -> GrayImage {
+    GrayImage::from_fn(width, height, |x, y| {
+        let hit = masks.iter().any(|m| {
+            m.mask
+                .get_pixel_checked(x, y)
+                .is_some_and(|p| p.0 != [0; 3])
+        });
+        Luma([if hit { 255 } else { 0 }])
+    })
+}
+
+/// Пишет объединение масок в `<base>.png` и каждую маску в `<base>.<ключ>.png`, например
+/// `photo.mask.png` и `photo.mask.noise.png`; возвращает пути записанных файлов
+pub fn save(masks: &[ImpairmentMask], base: &Path) -> Result<Vec<PathBuf>> {
+    let save = |image: DynamicImage, suffix: &str| -> Result<PathBuf> {
+        let mut name = base.as_os_str().to_owned();
+        name.push(suffix);
+        let path = PathBuf::from(name);
+        image
+            .save_with_format(&path, ImageFormat::Png)
+            .map_err(|e| SstvError::io(messages::save_failed(&path.display().to_string()), e))?;
+        Ok(path)
+    };
+
+    let all = union(masks, MARTIN_M1.width, MARTIN_M1.height);
+    let mut written = vec![save(DynamicImage::ImageLuma8(all), ".png")?];
+    for mask in masks {
+        written.push(save(
+            DynamicImage::ImageRgb8(mask.mask.clone()),
+            &format!(".{}.png", mask.key),
+        )?);
+    }
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dropout::{DropoutParams, DropoutWindow};
+    use crate::envelope::EnvelopeAnchor;
+    use crate::noise::NoiseParams;
+    use crate::timeline::{EffectEvent, ScanChannel};
+
+    #[test]
+    fn masks_follow_effect_schedule() {
+        let noise = NoiseParams {
+            level: 30,
+            ..NoiseParams::default()
+        };
+        let params = ProcessingParams {
+            noise: noise.clone(),
+            events: vec![EffectEvent {
+                start_s: 0.0,
+                duration_s: 1.0,
+                anchor: EnvelopeAnchor::Image,
+                channel: Some(ScanChannel::Green),
+                ramp_ms: 0.0,
+                effect: EventEffect::Noise(noise),
+            }],
+            dropout: DropoutParams {
+                windows: vec![DropoutWindow::new(10_000.0, 500.0)],
+                ..DropoutParams::default()
+            },
+            ..ProcessingParams::default()
+        };
+        let masks = masks(&params, false, 8000 * 120, 8000);
+        let keys: Vec<&str> = masks.iter().map(|m| m.key.as_str()).collect();
+        assert_eq!(keys, ["noise", "event1_noise", "dropout"]);
+
+        assert_eq!(masks[0].coverage(), 1.0);
+        assert_eq!(masks[0].lines().len(), 256);
+
+        // Секунда от начала тела — первые строки и только зелёная развёртка
+        let event = &masks[1];
+        let lines = event.lines();
+        assert_eq!(lines.first(), Some(&0));
+        assert!(lines.len() < 5, "{:?}", lines);
+        assert!(event.mask.pixels().all(|p| p.0[0] == 0 && p.0[2] == 0));
+        assert!(event.coverage() > 0.0);
+
+        // Выпадение на десятой секунде — строки ближе к середине, все цвета
+        let dropout = &masks[2].lines();
+        assert!(dropout.first().is_some_and(|&y| y > 10), "{:?}", dropout);
+        assert!(dropout.len() < 5);
+
+        let all = union(&masks, 320, 256);
+        assert!(all.pixels().all(|p| p.0 == [255]));
+        assert!(super::masks(&ProcessingParams::default(), false, 8000 * 120, 8000).is_empty());
+    }
+}
@@ -1,9 +1,179 @@
+pub mod advice;
+pub mod app_params;
+pub mod archive;
+#[cfg(feature = "audio")]
+pub mod audio;
+pub mod blanker;
+pub mod buffer;
+pub mod builder;
+pub mod command;
+pub mod conceal;
+pub mod conformance;
+pub mod curve;
+#[cfg(feature = "audio")]
+pub mod dashboard;
+pub mod deadline;
+pub mod decoder;
+pub mod drift;
+pub mod dropout;
+pub mod dsp;
+pub mod effect;
+pub mod encoder;
+pub mod ensemble;
 pub mod envelope;
+pub mod error;
+pub mod export;
+pub mod fading;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod filters;
+pub mod fuzz;
+pub mod interference;
+pub mod interop;
+pub mod labels;
+#[cfg(feature = "audio")]
+pub mod live;
+pub mod location;
+pub mod loudness;
+pub mod messages;
+pub mod metadata;
+pub mod metrics;
+pub mod modes;
+#[cfg(feature = "audio")]
+pub mod monitor;
+pub mod multipath;
+pub mod native_decoder;
 pub mod noise;
+pub mod nulling;
+pub mod occupancy;
+pub mod overlay;
+pub mod palette;
+pub mod preset;
 pub mod processor;
+pub mod quality;
+pub mod report;
 pub mod retarder;
+pub mod salvage;
+pub mod scan;
+pub mod scan_converter;
+#[cfg(feature = "script")]
+pub mod script;
+pub mod sequence;
+pub mod serve;
+pub mod signal;
+pub mod sink;
+pub mod snapshot;
+pub mod soak;
+pub mod spectrogram;
+pub mod squelch;
+pub mod station_id;
+pub mod stream;
+pub mod sweep;
+pub mod telemetry;
+#[cfg(test)]
+mod testing;
+pub mod threads;
+pub mod timeline;
+pub mod transceiver;
+pub mod validate;
+#[cfg(feature = "wav")]
+pub mod vectors;
+pub mod watermark;
+#[cfg(feature = "wav")]
+pub mod wav;
+#[cfg(feature = "webhook")]
+pub mod webhook;
 
-pub use envelope::EnvelopeKind;
-pub use noise::{NoiseParams, NoiseProcessor};
-pub use processor::{ProcessingParams, SSTVProcessor};
-pub use retarder::{RetarderParams, RetarderProcessor};
+pub use advice::{ProbeResult, Severity, Suggestion, Suggestions};
+pub use app_params::{AppParams, DecoderArgs, EncoderArgs};
+pub use blanker::{BlankerFill, BlankerParams, BlankerProcessor};
+pub use buffer::SampleBuffer;
+pub use builder::{EffectParams, SSTVProcessorBuilder};
+pub use command::CommandPaths;
+pub use conformance::{Check, ConformanceParams, ConformanceReport};
+pub use curve::{CurvePoint, DegradationCurve, LogisticFit};
+#[cfg(feature = "audio")]
+pub use dashboard::Dashboard;
+pub use deadline::{StageLimit, TimeLimits};
+pub use decoder::{Decoder, DecoderBackend, DecoderParams, RsstvDecoder, VisReport};
+pub use drift::{DriftParams, DriftProcessor};
+pub use dropout::{DropoutKind, DropoutParams, DropoutProcessor, DropoutWindow};
+pub use dsp::{ConvolutionBackend, Sideband, SimdLevel};
+pub use dsp::{ResampleStream, Resampler, align, resample};
+pub use effect::{
+    BlockProcessor, Bypass, ChannelEffect, ChannelSummary, EffectContext, EffectEstimate,
+    EffectMix, ImageEffect, MixSetting, MixTarget, SanitizeEvent, SanitizeMode, StreamContext,
+    checkpoint_rng, from_block_state, to_block_state,
+};
+pub use encoder::{
+    EncoderBackend, EncoderParams, LineHook, NativeEncoder, Segment, SegmentPurpose, Tone,
+};
+pub use ensemble::Ensemble;
+pub use envelope::{
+    AutomationRecording, CustomEnvelope, EnvelopeAnchor, EnvelopeKind, Interpolation,
+};
+pub use error::SstvError;
+pub use export::{RunArtifacts, RunExporter, RunFiles, RunManifest, RunMetrics};
+pub use fading::{FadingModel, FadingParams, FadingProcessor};
+pub use filters::{
+    AgcParams, AgcProcessor, FilterKind, FilterParams, FilterProcessor, NotchParams, NotchProcessor,
+};
+pub use fuzz::{DecoderDifference, FuzzFailure, FuzzParams, FuzzReport, FuzzTarget};
+pub use interference::{Carrier, InterferenceParams, InterferenceProcessor, QrmTransmission};
+pub use interop::{ForeignMode, StationImport};
+pub use labels::ImpairmentMask;
+#[cfg(feature = "audio")]
+pub use live::{LiveParams, LiveReport};
+pub use location::{Location, LocationSource, Stamp};
+pub use loudness::{LoudnessMeasure, LoudnessParams, Normalization};
+pub use messages::Language;
+pub use metadata::{MetaField, Metadata};
+pub use metrics::{
+    MetricSpace, QualityReport, Registration, SignalQuality, compare_signals, diff_image,
+};
+pub use modes::{MARTIN_M1, ModeSpec, ModeTiming, ScanOffset};
+#[cfg(feature = "audio")]
+pub use monitor::{Monitor, MonitorParams, MonitorSource, MonitorState, PcmFormat, ReceivedImage};
+pub use multipath::{MultipathParams, MultipathProcessor, MultipathTap};
+pub use native_decoder::{NativeDecoder, StreamDecoder};
+pub use noise::{
+    AmplitudeDistribution, ImpulseParams, NoiseColor, NoiseKind, NoiseParams, NoiseProcessor,
+};
+pub use nulling::NullReport;
+pub use occupancy::{Occupancy, SpectralMask};
+pub use overlay::{OverlayColor, OverlayParams};
+pub use palette::Palette;
+pub use preset::PresetFormat;
+pub use processor::{ProcessingParams, SSTVProcessor, Stage};
+pub use quality::QualityTier;
+pub use report::{DecodeReport, LevelMeter};
+pub use retarder::{RetarderParams, RetarderProcessor, RetarderSource};
+pub use rsstv::SAMPLE_RATE;
+pub use salvage::Salvage;
+pub use scan::{ScanParams, ScannedImage, Transmission};
+pub use scan_converter::{Phosphor, ScanConverterParams};
+#[cfg(feature = "script")]
+pub use script::Script;
+pub use sequence::{Frame, Ramp, SequenceRamps, SequenceRunner};
+pub use serve::{ServeJob, ServeLimits, ServeResult, Server};
+pub use signal::Signal;
+pub use sink::{BufferSink, CallbackSink, FileSink, ImageSink, MemorySink, StreamSink};
+pub use snapshot::Snapshot;
+pub use soak::{SoakCycle, SoakParams, SoakStats};
+pub use spectrogram::Spectrogram;
+pub use squelch::{Squelch, SquelchParams};
+pub use station_id::{IdMode, IdParams};
+pub use stream::{BlockChain, BlockEncoder, ChainState, SampleStream};
+pub use sweep::{ManifestFormat, SweepAxes, SweepEntry, SweepRange, SweepRunner};
+pub use telemetry::{Telemetry, TelemetryStrip};
+pub use threads::{ThreadParams, ThreadPriority};
+pub use timeline::{EffectEvent, EventEffect, ScanChannel, Timeline, TimelineProcessor};
+pub use transceiver::{
+    TransceiverOutput, TransceiverParams, TransceiverProcessor, TransceiverStage,
+};
+pub use validate::ParamWarning;
+#[cfg(feature = "wav")]
+pub use vectors::{VectorCase, VectorEntry, VectorSuite};
+pub use watermark::{Watermark, WatermarkParams, WatermarkReport};
+#[cfg(feature = "webhook")]
+pub use webhook::{Notifier, WebhookKind, WebhookParams};
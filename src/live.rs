@@ -0,0 +1,338 @@
+use crate::audio;
+use crate::effect::SanitizeEvent;
+use crate::error::{Result, SstvError};
+use crate::messages;
+use crate::processor::SSTVProcessor;
+use crate::report::LevelMeter;
+use crate::stream::{BlockChain, ChainState};
+use crate::wav;
+use cpal::traits::{DeviceTrait, StreamTrait};
+use cpal::{Device, FromSample, SampleFormat, SizedSample, StreamConfig};
+use image::DynamicImage;
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Наибольшая очередь входа, с: если обработка отстаёт дольше, старые сэмплы
+/// выбрасываются, иначе задержка канала росла бы без конца
+const MAX_BACKLOG_SECS: f32 = 1.0;
+/// Сколько ещё ждать после срока работы, пока устройства отдадут последние блоки
+const DRAIN_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Параметры живого канала между двумя звуковыми устройствами
+#[derive(Clone, Debug)]
+pub struct LiveParams {
+    /// Устройство ввода, куда приходит передача; `None` — системное
+    pub input_device: Option<String>,
+    /// Устройство вывода, где её слушает приёмная программа; `None` — системное
+    pub output_device: Option<String>,
+    /// Длина блока обработки, мс. Задержка канала — около двух блоков плюс задержка
+    /// эффектов и буферы устройств
+    pub block_ms: f32,
+    /// Сколько работать канал. По этой длине раскладываются огибающие и случайные выпадения
+    pub duration: Duration,
+    /// Ожидаемое СКЗ входа, 0–1 полной шкалы: от него шум выбирает свой уровень
+    pub input_rms: f32,
+    /// Куда писать контрольную точку эффектов, см. [`ChainState`]: в конце работы и,
+    /// если задан `checkpoint_every`, по ходу
+    pub checkpoint: Option<PathBuf>,
+    /// Как часто обновлять контрольную точку; `None` — только в конце
+    pub checkpoint_every: Option<Duration>,
+    /// Контрольная точка, с которой продолжить: эффекты и огибающие идут дальше с её
+    /// сэмпла, как будто канал не останавливался. Процессор, его зерно и `duration`
+    /// должны быть те же, что у сохранившего её канала
+    pub resume: Option<PathBuf>,
+}
+
+impl Default for LiveParams {
+    fn default() -> Self {
+        Self {
+            input_device: None,
+            output_device: None,
+            block_ms: 20.0,
+            duration: Duration::from_secs(180),
+            input_rms: 0.25,
+            checkpoint: None,
+            checkpoint_every: None,
+            resume: None,
+        }
+    }
+}
+
+/// Итоги живого канала
+#[derive(Clone, Debug)]
+pub struct LiveReport {
+    /// Частота дискретизации обоих устройств и эффектов, Гц
+    pub sample_rate: u32,
+    /// Сэмплов пропущено через эффекты
+    pub processed: usize,
+    /// Сэмплов входа, выброшенных из-за отставания обработки
+    pub dropped: usize,
+    /// Сэмплов тишины, отданных выходу, когда обработанных не хватило
+    pub underruns: usize,
+    /// Задержка канала без буферов устройств, мс
+    pub latency_ms: f32,
+    /// Уровень выхода до ограничения
+    pub level: LevelMeter,
+    /// Эффекты, выдавшие испорченные сэмплы, см. [`SanitizeMode`](crate::SanitizeMode)
+    pub sanitized: Vec<SanitizeEvent>,
+}
+
+impl LiveReport {
+    pub fn describe(&self) -> String {
+        messages::live_report(
+            self.processed as f32 / self.sample_rate as f32,
+            self.latency_ms,
+            self.dropped,
+            self.underruns,
+            &self.level.describe(),
+        )
+    }
+}
+
+/// Живой канал: звук с устройства ввода блоками проходит через включённые эффекты
+/// процессора и уходит на устройство вывода — имитатор канала между двумя настоящими
+/// программами SSTV. Работает, пока не выйдет `params.duration` или не будет поднят
+/// `cancel`. Эффекты считаются на частоте устройства ввода; устройство вывода должно её
+/// поддерживать. Только эффекты, умеющие обрабатывать блоки, см.
+/// [`SSTVProcessor::block_chain`]
+pub fn run(
+    processor: &mut SSTVProcessor,
+    retarder_image: Option<&DynamicImage>,
+    params: &LiveParams,
+    cancel: &AtomicBool,
+) -> Result<LiveReport> {
+    let input = audio::find_input_device(params.input_device.as_deref())?;
+    let supported = input
+        .default_input_config()
+        .map_err(|e| audio::device_error(messages::device_input_config(), e))?;
+    let input_format = supported.sample_format();
+    let input_config: StreamConfig = supported.into();
+    let sample_rate = input_config.sample_rate.0;
+
+    let output = audio::find_output_device(params.output_device.as_deref())?;
+    let (output_format, output_config) = output_config(&output, sample_rate)?;
+
+    let block_len =
+        ((params.block_ms.max(1.0) / 1000.0 * sample_rate as f32).round() as usize).max(1);
+    let len = (params.duration.as_secs_f64() * sample_rate as f64) as usize;
+    let mut chain = processor.block_chain(sample_rate, len, params.input_rms, retarder_image)?;
+    let mut processed = 0;
+    if let Some(path) = &params.resume {
+        chain.restore_state(&ChainState::from_file(path)?)?;
+        processed = chain.position();
+        tracing::info!(
+            "{}",
+            messages::live_resumed(
+                &path.display().to_string(),
+                processed as f32 / sample_rate as f32
+            )
+        );
+    }
+    let every = params
+        .checkpoint_every
+        .map(|every| ((every.as_secs_f64() * sample_rate as f64) as usize).max(block_len));
+    let mut next_checkpoint = every.map(|every| processed + every);
+
+    let backlog = (MAX_BACKLOG_SECS * sample_rate as f32) as usize;
+    let captured = Arc::new(Mutex::new(VecDeque::new()));
+    let dropped = Arc::new(AtomicUsize::new(0));
+    // Один блок тишины впереди выхода: запас на неровные вызовы устройств
+    let playback = Arc::new(Mutex::new(VecDeque::from(vec![0.0; block_len])));
+    let underruns = Arc::new(AtomicUsize::new(0));
+
+    let input_stream = match input_format {
+        SampleFormat::F32 => {
+            build_input::<f32>(&input, &input_config, &captured, &dropped, backlog)
+        }
+        SampleFormat::I16 => {
+            build_input::<i16>(&input, &input_config, &captured, &dropped, backlog)
+        }
+        SampleFormat::U16 => {
+            build_input::<u16>(&input, &input_config, &captured, &dropped, backlog)
+        }
+        other => Err(unsupported_format(other)),
+    }?;
+    let output_stream = match output_format {
+        SampleFormat::F32 => build_output::<f32>(&output, &output_config, &playback, &underruns),
+        SampleFormat::I16 => build_output::<i16>(&output, &output_config, &playback, &underruns),
+        SampleFormat::U16 => build_output::<u16>(&output, &output_config, &playback, &underruns),
+        other => Err(unsupported_format(other)),
+    }?;
+    output_stream
+        .play()
+        .map_err(|e| audio::device_error(messages::device_start_playback(), e))?;
+    input_stream
+        .play()
+        .map_err(|e| audio::device_error(messages::device_start_recording(), e))?;
+
+    let deadline = Instant::now() + params.duration + DRAIN_TIMEOUT;
+    let idle = Duration::from_secs_f32(params.block_ms.max(1.0) / 4000.0);
+    while processed < len && !cancel.load(Ordering::Relaxed) && Instant::now() < deadline {
+        let block = {
+            let mut queue = captured.lock().unwrap();
+            let take = block_len.min(len - processed);
+            (queue.len() >= take).then(|| queue.drain(..take).collect::<Vec<f32>>())
+        };
+        let Some(mut block) = block else {
+            std::thread::sleep(idle);
+            continue;
+        };
+        chain.process(&mut block, processed)?;
+        processed += block.len();
+        playback.lock().unwrap().extend(block);
+        if let (Some(path), Some(every), Some(next)) = (&params.checkpoint, every, next_checkpoint)
+            && processed >= next
+        {
+            save_checkpoint(&mut chain, path, sample_rate)?;
+            next_checkpoint = Some(processed + every);
+        }
+    }
+    drop(input_stream);
+    if let Some(path) = &params.checkpoint {
+        save_checkpoint(&mut chain, path, sample_rate)?;
+    }
+
+    // Выход доигрывает то, что уже обработано
+    while !playback.lock().unwrap().is_empty()
+        && !cancel.load(Ordering::Relaxed)
+        && Instant::now() < deadline
+    {
+        std::thread::sleep(idle);
+    }
+    drop(output_stream);
+
+    Ok(LiveReport {
+        sample_rate,
+        processed,
+        dropped: dropped.load(Ordering::Relaxed),
+        underruns: underruns.load(Ordering::Relaxed),
+        latency_ms: (2 * block_len + chain.latency()) as f32 / sample_rate as f32 * 1000.0,
+        level: chain.level(),
+        sanitized: chain.take_sanitized(),
+    })
+}
+
+/// Пишет контрольную точку цепочки в `path`: сначала во временный файл рядом, чтобы
+/// прерванная запись не испортила прежнюю точку
+fn save_checkpoint(chain: &mut BlockChain, path: &Path, sample_rate: u32) -> Result<()> {
+    let partial = path.with_extension("partial");
+    chain.save_state()?.to_file(&partial)?;
+    std::fs::rename(&partial, path).map_err(|e| {
+        SstvError::io(
+            messages::checkpoint_write_failed(&path.display().to_string()),
+            e,
+        )
+    })?;
+    tracing::info!(
+        "{}",
+        messages::live_checkpoint(
+            &path.display().to_string(),
+            chain.position() as f32 / sample_rate as f32
+        )
+    );
+    Ok(())
+}
+
+/// Конфигурация вывода на частоте `sample_rate`: формат сэмплов, который умеют
+/// обработчики, и та же частота, что у ввода
+fn output_config(device: &Device, sample_rate: u32) -> Result<(SampleFormat, StreamConfig)> {
+    let rate = cpal::SampleRate(sample_rate);
+    let supported = device
+        .supported_output_configs()
+        .map_err(|e| audio::device_error(messages::device_output_configs(), e))?
+        .filter(|c| c.min_sample_rate() <= rate && rate <= c.max_sample_rate())
+        .filter(|c| {
+            matches!(
+                c.sample_format(),
+                SampleFormat::F32 | SampleFormat::I16 | SampleFormat::U16
+            )
+        })
+        .max_by_key(|c| c.sample_format() == SampleFormat::F32)
+        .ok_or_else(|| SstvError::AudioDevice(messages::device_rate_unsupported(sample_rate)))?
+        .with_sample_rate(rate);
+    Ok((supported.sample_format(), supported.into()))
+}
+
+pub(crate) fn unsupported_format(format: SampleFormat) -> SstvError {
+    SstvError::AudioDevice(messages::device_sample_format(&format!("{:?}", format)))
+}
+
+/// Поток ввода, складывающий моно-сэмплы в очередь `captured`; сверх `backlog` сэмплов
+/// старые выбрасываются и считаются в `dropped`
+pub(crate) fn build_input<T>(
+    device: &Device,
+    config: &StreamConfig,
+    captured: &Arc<Mutex<VecDeque<f32>>>,
+    dropped: &Arc<AtomicUsize>,
+    backlog: usize,
+) -> Result<cpal::Stream>
+where
+    T: SizedSample,
+    f32: FromSample<T>,
+{
+    let channels = config.channels as usize;
+    let captured = Arc::clone(captured);
+    let dropped = Arc::clone(dropped);
+
+    let stream = device
+        .build_input_stream(
+            config,
+            move |input: &[T], _: &cpal::InputCallbackInfo| {
+                let mono: Vec<f32> = input.iter().map(|s| s.to_sample::<f32>()).collect();
+                if let Ok(mut queue) = captured.lock() {
+                    queue.extend(wav::downmix(&mono, channels));
+                    let excess = queue.len().saturating_sub(backlog);
+                    if excess > 0 {
+                        queue.drain(..excess);
+                        dropped.fetch_add(excess, Ordering::Relaxed);
+                    }
+                }
+            },
+            |e| tracing::error!("{}", messages::input_stream_failed(&e.to_string())),
+            None,
+        )
+        .map_err(|e| audio::device_error(messages::device_open_input(), e))?;
+    Ok(stream)
+}
+
+/// Поток вывода, берущий моно-сэмплы из очереди `playback` во все каналы кадра; когда
+/// очередь пуста, выводит тишину и считает её в `underruns`
+fn build_output<T>(
+    device: &Device,
+    config: &StreamConfig,
+    playback: &Arc<Mutex<VecDeque<f32>>>,
+    underruns: &Arc<AtomicUsize>,
+) -> Result<cpal::Stream>
+where
+    T: SizedSample + FromSample<f32>,
+{
+    let channels = config.channels as usize;
+    let playback = Arc::clone(playback);
+    let underruns = Arc::clone(underruns);
+
+    let stream = device
+        .build_output_stream(
+            config,
+            move |out: &mut [T], _: &cpal::OutputCallbackInfo| {
+                let Ok(mut queue) = playback.lock() else {
+                    return;
+                };
+                let mut silent = 0;
+                for frame in out.chunks_mut(channels) {
+                    let value = queue.pop_front().unwrap_or_else(|| {
+                        silent += 1;
+                        0.0
+                    });
+                    frame.iter_mut().for_each(|s| *s = T::from_sample(value));
+                }
+                underruns.fetch_add(silent, Ordering::Relaxed);
+            },
+            |e| tracing::error!("{}", messages::output_stream_failed(&e.to_string())),
+            None,
+        )
+        .map_err(|e| audio::device_error(messages::device_open_output(), e))?;
+    Ok(stream)
+}
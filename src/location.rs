@@ -0,0 +1,259 @@
+use crate::error::{Result, SstvError};
+use crate::messages;
+use crate::overlay;
+use std::collections::BTreeMap;
+use std::io::{BufRead, BufReader, ErrorKind, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::str::FromStr;
+use std::time::{Duration, SystemTime};
+
+/// Адрес gpsd по умолчанию
+pub const GPSD_DEFAULT: &str = "localhost:2947";
+/// Сколько ждать подключения к gpsd, с
+const GPSD_CONNECT_SECS: u64 = 3;
+/// Как часто чтение gpsd проверяет, не пора ли остановиться, мс
+const GPSD_POLL_MS: u64 = 500;
+
+/// Место приёма: широта и долгота в градусах, север и восток положительны
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Location {
+    pub lat: f64,
+    pub lon: f64,
+}
+
+impl Location {
+    /// Широта в ±90°, долгота в ±180°
+    pub fn validate(&self) -> Result<()> {
+        if !(-90.0..=90.0).contains(&self.lat) || !(-180.0..=180.0).contains(&self.lon) {
+            return Err(SstvError::invalid(
+                "location",
+                messages::location_out_of_range(self.lat, self.lon),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Центр квадрата QTH-локатора Maidenhead из 4 или 6 знаков, например `KO85` или `KO85ts`
+    pub fn from_grid(grid: &str) -> Result<Self> {
+        let invalid = || SstvError::invalid("location", messages::location_bad_grid(grid));
+        let chars: Vec<char> = grid.trim().to_ascii_uppercase().chars().collect();
+        if chars.len() != 4 && chars.len() != 6 {
+            return Err(invalid());
+        }
+        // Пары знаков: поле 20°×10°, квадрат 2°×1°, подквадрат 5′×2,5′
+        let mut lon = -180.0;
+        let mut lat = -90.0;
+        let (mut lon_size, mut lat_size) = (360.0, 180.0);
+        for &(start, base, count) in [(0, 'A', 18), (2, '0', 10), (4, 'A', 24)]
+            .iter()
+            .take(chars.len() / 2)
+        {
+            let index = |c: char| {
+                let i = c as i64 - base as i64;
+                (0..count)
+                    .contains(&i)
+                    .then_some(i as f64)
+                    .ok_or_else(invalid)
+            };
+            lon_size /= count as f64;
+            lat_size /= count as f64;
+            lon += index(chars[start])? * lon_size;
+            lat += index(chars[start + 1])? * lat_size;
+        }
+        Ok(Self {
+            lat: lat + lat_size / 2.0,
+            lon: lon + lon_size / 2.0,
+        })
+    }
+
+    /// QTH-локатор Maidenhead из 6 знаков: `KO85ts`
+    pub fn grid(&self) -> String {
+        // Восточный и северный края попадают в последний квадрат, а не за сетку
+        let lon = (self.lon + 180.0).clamp(0.0, 360.0 - 1e-9);
+        let lat = (self.lat + 90.0).clamp(0.0, 180.0 - 1e-9);
+        let letter = |base: u8, i: f64| (base + i as u8) as char;
+        [
+            letter(b'A', lon / 20.0),
+            letter(b'A', lat / 10.0),
+            letter(b'0', lon % 20.0 / 2.0),
+            letter(b'0', lat % 10.0),
+            letter(b'a', lon % 2.0 * 12.0),
+            letter(b'a', lat % 1.0 * 24.0),
+        ]
+        .iter()
+        .collect()
+    }
+
+    /// `KO85ts (55.75000, 37.62000)`
+    pub fn describe(&self) -> String {
+        format!("{} ({:.5}, {:.5})", self.grid(), self.lat, self.lon)
+    }
+}
+
+impl FromStr for Location {
+    type Err = SstvError;
+
+    /// Разбирает `широта,долгота` в градусах (`55.75,37.62`) или QTH-локатор (`KO85ts`)
+    fn from_str(s: &str) -> Result<Self> {
+        let Some((lat, lon)) = s.split_once(',') else {
+            return Self::from_grid(s);
+        };
+        let degrees = |v: &str| {
+            v.trim().parse::<f64>().map_err(|_| {
+                SstvError::invalid("location", messages::location_bad_degrees(v.trim()))
+            })
+        };
+        let location = Self {
+            lat: degrees(lat)?,
+            lon: degrees(lon)?,
+        };
+        location.validate()?;
+        Ok(location)
+    }
+}
+
+/// Откуда приёмник берёт своё место
+#[derive(Clone, Debug, PartialEq)]
+pub enum LocationSource {
+    /// Заданное место
+    Static(Location),
+    /// Последний фикс демона gpsd по адресу `host:port`, см. [`watch_gpsd`]
+    Gpsd(String),
+}
+
+/// Отметка приёма: время UTC и место приёмника, если оно известно. Её требуют сайты
+/// отчётов SWL
+#[derive(Clone, Debug, PartialEq)]
+pub struct Stamp {
+    pub time: SystemTime,
+    pub location: Option<Location>,
+}
+
+impl Stamp {
+    /// Отметка текущего момента
+    pub fn now(location: Option<Location>) -> Self {
+        Self {
+            time: SystemTime::now(),
+            location,
+        }
+    }
+
+    /// Время в ISO 8601: `2026-10-15T12:34:56Z`
+    pub fn utc(&self) -> String {
+        let (year, month, day, secs) = overlay::civil_utc(self.time);
+        format!(
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+            year,
+            month,
+            day,
+            secs / 3600,
+            secs % 3600 / 60,
+            secs % 60
+        )
+    }
+
+    /// Пары для текстовых блоков PNG: `rx_time`, а с местом ещё `rx_grid`, `rx_lat` и
+    /// `rx_lon`. Приставка отделяет их от метаданных передающей станции
+    pub fn fields(&self) -> BTreeMap<String, String> {
+        let mut fields = BTreeMap::from([("rx_time".to_string(), self.utc())]);
+        if let Some(location) = &self.location {
+            fields.insert("rx_grid".to_string(), location.grid());
+            fields.insert("rx_lat".to_string(), format!("{:.5}", location.lat));
+            fields.insert("rx_lon".to_string(), format!("{:.5}", location.lon));
+        }
+        fields
+    }
+
+    /// `2026-10-15T12:34:56Z KO85ts (55.75000, 37.62000)`
+    pub fn describe(&self) -> String {
+        match &self.location {
+            Some(location) => format!("{} {}", self.utc(), location.describe()),
+            None => self.utc(),
+        }
+    }
+}
+
+/// Читает отчёты TPV демона gpsd по адресу `addr` и передаёт `on_fix` каждое место с
+/// фиксом 2D или 3D. Работает, пока `running` возвращает `true`; обрыв связи — ошибка
+pub fn watch_gpsd(
+    addr: &str,
+    running: impl Fn() -> bool,
+    mut on_fix: impl FnMut(Location),
+) -> Result<()> {
+    let context = || messages::gpsd_read_failed(addr);
+    let addrs = addr
+        .to_socket_addrs()
+        .map_err(|e| SstvError::io(context(), e))?;
+    let mut last_error = None;
+    let mut stream = None;
+    for socket in addrs {
+        match TcpStream::connect_timeout(&socket, Duration::from_secs(GPSD_CONNECT_SECS)) {
+            Ok(s) => {
+                stream = Some(s);
+                break;
+            }
+            Err(e) => last_error = Some(e),
+        }
+    }
+    let mut stream = match (stream, last_error) {
+        (Some(stream), _) => stream,
+        (None, Some(e)) => return Err(SstvError::io(context(), e)),
+        (None, None) => return Err(SstvError::io(context(), messages::gpsd_no_address())),
+    };
+    stream
+        .set_read_timeout(Some(Duration::from_millis(GPSD_POLL_MS)))
+        .and_then(|()| stream.write_all(b"?WATCH={\"enable\":true,\"json\":true};\n"))
+        .map_err(|e| SstvError::io(context(), e))?;
+
+    let mut reader = BufReader::new(stream);
+    let mut line = Vec::new();
+    while running() {
+        match reader.read_until(b'\n', &mut line) {
+            Ok(0) => return Err(SstvError::io(context(), messages::gpsd_closed())),
+            // Без перевода строки отчёт ещё не дочитан
+            Ok(_) if line.last() != Some(&b'\n') => continue,
+            Ok(_) => {
+                if let Some(location) = parse_tpv(&line) {
+                    on_fix(location);
+                }
+                line.clear();
+            }
+            Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {}
+            Err(e) => return Err(SstvError::io(context(), e)),
+        }
+    }
+    Ok(())
+}
+
+/// Место из отчёта TPV gpsd с фиксом 2D или 3D
+fn parse_tpv(line: &[u8]) -> Option<Location> {
+    let report: serde_json::Value = serde_json::from_slice(line).ok()?;
+    if report["class"] != "TPV" || report["mode"].as_u64()? < 2 {
+        return None;
+    }
+    let location = Location {
+        lat: report["lat"].as_f64()?,
+        lon: report["lon"].as_f64()?,
+    };
+    location.validate().ok()?;
+    Some(location)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn location_grid_round_trip() {
+        let moscow: Location = "55.75, 37.62".parse().unwrap();
+        assert_eq!(moscow.grid(), "KO85ts");
+        let center = Location::from_grid("ko85TS").unwrap();
+        assert_eq!(center.grid(), "KO85ts");
+        assert!((center.lat - moscow.lat).abs() < 1.0 / 24.0);
+        assert!((center.lon - moscow.lon).abs() < 2.0 / 24.0);
+        assert_eq!("-90,-180".parse::<Location>().unwrap().grid(), "AA00aa");
+        assert_eq!("90,180".parse::<Location>().unwrap().grid(), "RR99xx");
+        assert!("KO8".parse::<Location>().is_err());
+        assert!("91,0".parse::<Location>().is_err());
+    }
+}
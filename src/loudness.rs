@@ -0,0 +1,298 @@
+use crate::buffer::SampleBuffer;
+use crate::dsp::{Biquad, Resampler};
+use crate::effect;
+use crate::messages;
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+
+/// Кратность передискретизации при поиске истинного пика (ITU-R BS.1770, прил. 2)
+const TRUE_PEAK_OVERSAMPLING: u32 = 4;
+/// Блок стробирования громкости, с, и его шаг — с перекрытием 75%
+const GATE_BLOCK_SECS: f64 = 0.4;
+const GATE_STEP_SECS: f64 = 0.1;
+/// Абсолютный порог стробирования, LUFS: тишина не занижает громкость
+const ABSOLUTE_GATE_LUFS: f64 = -70.0;
+/// Относительный порог, LU ниже громкости блоков над абсолютным порогом
+const RELATIVE_GATE_LU: f64 = -10.0;
+
+/// Чем меряется громкость при нормировке
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LoudnessMeasure {
+    /// Без нормировки
+    #[default]
+    Off,
+    /// Среднеквадратичный уровень всего сигнала, dBFS
+    Rms,
+    /// Громкость по ITU-R BS.1770: K-взвешивание и стробирование блоков по 400 мс, LUFS.
+    /// Канал один, поэтому это скорее «LUFS для моно», чем громкость программы вещания
+    Lufs,
+}
+
+impl LoudnessMeasure {
+    pub const ALL: &'static [LoudnessMeasure] = &[
+        LoudnessMeasure::Off,
+        LoudnessMeasure::Rms,
+        LoudnessMeasure::Lufs,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            LoudnessMeasure::Off => messages::loudness_off(),
+            LoudnessMeasure::Rms => "RMS",
+            LoudnessMeasure::Lufs => "LUFS",
+        }
+    }
+
+    /// Единица громкости в отчёте
+    pub fn unit(&self) -> &'static str {
+        match self {
+            LoudnessMeasure::Off | LoudnessMeasure::Rms => messages::unit_dbfs(),
+            LoudnessMeasure::Lufs => "LUFS",
+        }
+    }
+
+    /// Громкость `samples` с частотой `sample_rate`; `None` для тишины и без меры
+    pub fn measure(&self, samples: &[f32], sample_rate: u32) -> Option<f32> {
+        match self {
+            LoudnessMeasure::Off => None,
+            LoudnessMeasure::Rms => rms_db(samples),
+            LoudnessMeasure::Lufs => lufs(samples, sample_rate),
+        }
+    }
+}
+
+impl clap::ValueEnum for LoudnessMeasure {
+    fn value_variants<'a>() -> &'a [Self] {
+        Self::ALL
+    }
+
+    fn to_possible_value<'a>(&self) -> Option<clap::builder::PossibleValue> {
+        Some(match self {
+            Self::Off => clap::builder::PossibleValue::new("off"),
+            Self::Rms => clap::builder::PossibleValue::new("rms"),
+            Self::Lufs => clap::builder::PossibleValue::new("lufs"),
+        })
+    }
+}
+
+/// Нормировка громкости записываемого звука: тракт передатчика ждёт одного уровня
+/// раскачки, какими бы ни были изображение и шум. Первый проход меряет громкость и
+/// истинный пик, второй умножает весь сигнал на одно усиление
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LoudnessParams {
+    pub measure: LoudnessMeasure,
+    /// Целевая громкость, dBFS для RMS или LUFS
+    pub target_db: f32,
+    /// Потолок истинного пика, dBTP: усиление урезается, чтобы пик его не превысил,
+    /// даже если громкость тогда не дойдёт до цели
+    pub true_peak_db: f32,
+}
+
+impl Default for LoudnessParams {
+    fn default() -> Self {
+        Self {
+            measure: LoudnessMeasure::Off,
+            target_db: -16.0,
+            true_peak_db: -1.0,
+        }
+    }
+}
+
+impl LoudnessParams {
+    pub fn enabled(&self) -> bool {
+        self.measure != LoudnessMeasure::Off
+    }
+
+    /// Приводит `buffer` к целевой громкости. `None`, если нормировка выключена или
+    /// сигнал — тишина; тогда буфер не меняется
+    pub fn apply(&self, buffer: &mut SampleBuffer) -> Option<Normalization> {
+        let before_db = self.measure.measure(&buffer.data, buffer.rate)?;
+        let peak_db = true_peak_db(&buffer.data, buffer.rate);
+        let mut gain_db = self.target_db - before_db;
+        let peak_limited = peak_db + gain_db > self.true_peak_db;
+        if peak_limited {
+            gain_db = self.true_peak_db - peak_db;
+        }
+
+        let gain = 10f32.powf(gain_db / 20.0);
+        buffer.data.iter_mut().for_each(|s| *s *= gain);
+        Some(Normalization {
+            measure: self.measure,
+            before_db,
+            after_db: before_db + gain_db,
+            gain_db,
+            true_peak_db: peak_db + gain_db,
+            peak_limited,
+        })
+    }
+}
+
+/// Итог нормировки громкости
+#[derive(Copy, Clone, Debug, PartialEq, Serialize)]
+pub struct Normalization {
+    pub measure: LoudnessMeasure,
+    /// Громкость до нормировки, в единицах [`LoudnessMeasure::unit`]
+    pub before_db: f32,
+    /// Громкость после нормировки
+    pub after_db: f32,
+    /// Применённое усиление, дБ
+    pub gain_db: f32,
+    /// Истинный пик после нормировки, dBTP
+    pub true_peak_db: f32,
+    /// Усиление урезано потолком истинного пика: громкость ниже цели
+    pub peak_limited: bool,
+}
+
+impl Normalization {
+    pub fn describe(&self) -> String {
+        let text = messages::loudness_describe(
+            self.measure.unit(),
+            self.before_db,
+            self.after_db,
+            self.gain_db,
+            self.true_peak_db,
+        );
+        if self.peak_limited {
+            format!("{} {}", text, messages::loudness_peak_limited())
+        } else {
+            text
+        }
+    }
+}
+
+/// Среднеквадратичный уровень, dBFS; `None` для тишины
+pub fn rms_db(samples: &[f32]) -> Option<f32> {
+    let energy: f64 = samples.iter().map(|&s| s as f64 * s as f64).sum();
+    (energy > 0.0).then(|| effect::power_db((energy / samples.len() as f64) as f32))
+}
+
+/// Громкость моно-сигнала по ITU-R BS.1770-4, LUFS: K-фильтр, блоки по 400 мс с шагом
+/// 100 мс, абсолютный порог −70 LUFS и относительный −10 LU. Сигнал короче блока
+/// меряется целиком. `None` для тишины
+pub fn lufs(samples: &[f32], sample_rate: u32) -> Option<f32> {
+    if samples.is_empty() {
+        return None;
+    }
+    let mut weighted = samples.to_vec();
+    let (mut shelf, mut highpass) = k_filter(sample_rate);
+    shelf.process_slice(&mut weighted);
+    highpass.process_slice(&mut weighted);
+
+    let block = ((GATE_BLOCK_SECS * sample_rate as f64) as usize).clamp(1, weighted.len());
+    let step = ((GATE_STEP_SECS * sample_rate as f64) as usize).max(1);
+    let powers: Vec<f64> = (0..=weighted.len().saturating_sub(block))
+        .step_by(step)
+        .map(|start| {
+            let frame = &weighted[start..start + block];
+            frame.iter().map(|&s| s as f64 * s as f64).sum::<f64>() / block as f64
+        })
+        .collect();
+
+    let loudness = |power: f64| -0.691 + 10.0 * power.log10();
+    let gated_mean = |threshold: f64| {
+        let passed: Vec<f64> = powers
+            .iter()
+            .copied()
+            .filter(|&p| p > 0.0 && loudness(p) > threshold)
+            .collect();
+        (!passed.is_empty()).then(|| passed.iter().sum::<f64>() / passed.len() as f64)
+    };
+    let absolute = gated_mean(ABSOLUTE_GATE_LUFS)?;
+    let relative = gated_mean(loudness(absolute) + RELATIVE_GATE_LU)?;
+    Some(loudness(relative) as f32)
+}
+
+/// Звенья K-фильтра для частоты `sample_rate`: коэффициенты BS.1770 заданы для 48 кГц,
+/// здесь они выводятся из аналоговых прототипов (как в libebur128) и на 48 кГц совпадают
+fn k_filter(sample_rate: u32) -> (Biquad, Biquad) {
+    let rate = sample_rate as f64;
+
+    // Полка: +4 дБ выше ~1,7 кГц, модель головы слушателя
+    let (f0, gain_db, q) = (1681.974450955533, 3.999843853973347, 0.7071752369554196);
+    let k = (PI * f0 / rate).tan();
+    let vh = 10f64.powf(gain_db / 20.0);
+    let vb = vh.powf(0.4996667741545416);
+    let shelf = Biquad::from_coefficients(
+        [
+            vh + vb * k / q + k * k,
+            2.0 * (k * k - vh),
+            vh - vb * k / q + k * k,
+        ],
+        [
+            1.0 + k / q + k * k,
+            2.0 * (k * k - 1.0),
+            1.0 - k / q + k * k,
+        ],
+    );
+
+    // ФВЧ RLB: срез ~38 Гц
+    let (f0, q) = (38.13547087602444, 0.5003270373238773);
+    let k = (PI * f0 / rate).tan();
+    let a0 = 1.0 + k / q + k * k;
+    let highpass = Biquad::from_coefficients(
+        [a0, -2.0 * a0, a0],
+        [a0, 2.0 * (k * k - 1.0), 1.0 - k / q + k * k],
+    );
+    (shelf, highpass)
+}
+
+/// Истинный пик, dBTP: наибольший модуль сигнала, передискретизированного в
+/// [`TRUE_PEAK_OVERSAMPLING`] раз, — пик между сэмплами, который увидит ЦАП
+pub fn true_peak_db(samples: &[f32], sample_rate: u32) -> f32 {
+    let oversampled =
+        Resampler::new(sample_rate, sample_rate * TRUE_PEAK_OVERSAMPLING).process(samples);
+    let peak = samples
+        .iter()
+        .chain(&oversampled)
+        .fold(0.0f32, |peak, s| peak.max(s.abs()));
+    effect::power_db(peak * peak)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn loudness_normalization_meets_target_and_ceiling() {
+        let rate = 48000;
+        let sine = |freq: f32, amplitude: f32, phase: f32| -> Vec<f32> {
+            (0..3 * rate)
+                .map(|i| {
+                    let t = i as f32 / rate as f32;
+                    amplitude * (2.0 * std::f32::consts::PI * freq * t + phase).sin()
+                })
+                .collect()
+        };
+        // BS.1770: синус 1 кГц полной шкалы — −3,01 LUFS
+        let loudness = lufs(&sine(997.0, 1.0, 0.0), rate).unwrap();
+        assert!((loudness + 3.01).abs() < 0.3, "{loudness}");
+        // Сэмплы синуса fs/4 со сдвигом 45° — ±0,707, а пик между ними — полная шкала
+        let peak = true_peak_db(&sine(rate as f32 / 4.0, 1.0, 0.785), rate);
+        assert!(peak > -0.5, "{peak}");
+        assert!(lufs(&[0.0; 100], rate).is_none());
+
+        let params = LoudnessParams {
+            measure: LoudnessMeasure::Rms,
+            target_db: -20.0,
+            true_peak_db: -1.0,
+        };
+        let mut buffer = SampleBuffer::mono(sine(1500.0, 0.5, 0.0), rate);
+        let done = params.apply(&mut buffer).unwrap();
+        assert!(!done.peak_limited, "{done:?}");
+        let rms = rms_db(&buffer.data).unwrap();
+        assert!((rms + 20.0).abs() < 0.05, "{rms}");
+
+        // −3 dBFS RMS синуса — это пик 0 dBFS, выше потолка: цель не достигается
+        let loud = LoudnessParams {
+            target_db: -3.0,
+            ..params
+        };
+        let mut buffer = SampleBuffer::mono(sine(1500.0, 0.5, 0.0), rate);
+        let done = loud.apply(&mut buffer).unwrap();
+        assert!(done.peak_limited, "{done:?}");
+        assert!((done.true_peak_db + 1.0).abs() < 0.01, "{done:?}");
+        assert!(buffer.data.iter().all(|s| s.abs() < 0.9), "{done:?}");
+    }
+}
@@ -0,0 +1,1348 @@
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// Язык человекочитаемых сообщений библиотеки: описаний эффектов (`describe()`), отчётов
+/// декодера и проверок, советов пробного прогона и сообщений об ошибках. Выбирается на весь
+/// процесс через [`set_language`]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Language {
+    #[default]
+    En,
+    Ru,
+}
+
+impl Language {
+    pub const ALL: &'static [Language] = &[Language::En, Language::Ru];
+
+    /// Название языка на нём самом
+    pub fn name(&self) -> &'static str {
+        match self {
+            Language::En => "English",
+            Language::Ru => "Русский",
+        }
+    }
+}
+
+impl clap::ValueEnum for Language {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[Self::En, Self::Ru]
+    }
+
+    fn to_possible_value<'a>(&self) -> Option<clap::builder::PossibleValue> {
+        Some(match self {
+            Self::En => clap::builder::PossibleValue::new("en"),
+            Self::Ru => clap::builder::PossibleValue::new("ru"),
+        })
+    }
+}
+
+/// Текущий язык, индекс в [`Language::ALL`]
+static LANGUAGE: AtomicU8 = AtomicU8::new(0);
+
+/// Выбирает язык сообщений для всего процесса, включая рабочие потоки
+pub fn set_language(language: Language) {
+    let index = Language::ALL
+        .iter()
+        .position(|&l| l == language)
+        .unwrap_or(0);
+    LANGUAGE.store(index as u8, Ordering::Relaxed);
+}
+
+/// Текущий язык сообщений
+pub fn language() -> Language {
+    Language::ALL
+        .get(LANGUAGE.load(Ordering::Relaxed) as usize)
+        .copied()
+        .unwrap_or_default()
+}
+
+/// Постоянные строки: функция без аргументов для каждой пары «английский, русский»
+macro_rules! texts {
+    ($($name:ident => $en:literal, $ru:literal;)*) => {
+        $(
+            pub fn $name() -> &'static str {
+                match language() {
+                    Language::En => $en,
+                    Language::Ru => $ru,
+                }
+            }
+        )*
+    };
+}
+
+/// Строки с подстановкой: аргументы функции подставляются в шаблоны по имени, вместе
+/// с форматом (`{snr:.1}`), поэтому шаблоны проверяются при сборке
+macro_rules! messages {
+    ($($name:ident($($arg:ident: $ty:ty),* $(,)?) => $en:literal, $ru:literal;)*) => {
+        $(
+            pub fn $name($($arg: $ty),*) -> String {
+                match language() {
+                    Language::En => format!($en),
+                    Language::Ru => format!($ru),
+                }
+            }
+        )*
+    };
+}
+
+/// Справка CLI: описания флагов и команд
+pub mod help;
+
+/// Надписи окна GUI
+pub mod gui;
+
+// ── Эффекты канала ───────────────────────────────────────────
+texts! {
+    noise => "Noise", "Шум";
+    noise_off => "Noise off", "Шум отключен";
+    retarder => "Retarder", "Ретардер";
+    retarder_off => "Retarder off", "Ретардер отключен";
+    multipath => "Multipath", "Многолучёвость";
+    multipath_off => "Multipath off", "Многолучёвость отключена";
+    fading => "Fading", "Замирания";
+    fading_off => "Fading off", "Замирания отключены";
+    drift => "Frequency drift", "Уход частоты";
+    drift_off => "Frequency drift off", "Уход частоты отключен";
+    interference => "Interference", "Помехи";
+    interference_off => "Interference off", "Помехи отключены";
+    blanker => "Noise blanker", "Подавитель помех";
+    blanker_off => "Noise blanker off", "Подавитель помех отключен";
+    filter => "Audio filter", "Звуковой фильтр";
+    filter_off => "Audio filter off", "Звуковой фильтр отключен";
+    notch => "Notch filter", "Режекция";
+    notch_off => "Notch filter off", "Режекция отключена";
+    agc => "AGC", "АРУ";
+    agc_off => "AGC off", "АРУ отключена";
+    transmitter => "SSB transmitter", "Передатчик SSB";
+    receiver => "SSB receiver", "Приёмник SSB";
+    transceiver_off => "SSB chain off", "Тракт SSB отключен";
+    dropout => "Dropouts", "Выпадения";
+    dropout_off => "Dropouts off", "Выпадения отключены";
+    timeline => "Event timeline", "События по времени";
+
+    blanker_fill_zero => "Silence", "Тишина";
+    blanker_fill_hold => "Hold", "Удержание";
+    blanker_fill_interpolate => "Interpolation", "Интерполяция";
+    blanker_fill_repeat => "Period repeat", "Повтор периода";
+    filter_kind_lowpass => "Low-pass", "ФНЧ";
+    filter_kind_highpass => "High-pass", "ФВЧ";
+    filter_kind_bandpass => "Band-pass", "Полосовой";
+    dropout_kind_zero => "Silence", "Тишина";
+    dropout_kind_mute => "Attenuation", "Ослабление";
+    loudness_off => "Off", "Выкл.";
+    squelch_off => "Squelch off", "Шумоподавитель выключен";
+    squelch_closed => "Squelch closed", "Шумоподавитель закрыт";
+    monitor_squelch_rejected =>
+        "Image dropped: no valid VIS",
+        "Изображение брошено: нет верного VIS";
+    unit_dbfs => "dBFS", "дБFS";
+    metric_space_linear => "Linear RGB", "Линейный RGB";
+    segment_leader => "leader tone", "лидер-тон";
+    segment_break => "break", "разрыв";
+    segment_vis_start => "VIS start bit", "стартовый бит VIS";
+    segment_vis_parity => "VIS parity bit", "бит чётности VIS";
+    segment_vis_stop => "VIS stop bit", "стоповый бит VIS";
+    segment_station_id => "station ID", "опознаватель";
+    segment_id_text => "FSK ID text", "посылка FSK ID с текстом";
+    loudness_peak_limited =>
+        "(held back by the true-peak ceiling)",
+        "(урезано потолком истинного пика)";
+    pcm_format_s16le => "16-bit integer", "16 бит, целые";
+    pcm_format_f32le => "32-bit float", "32 бита, плавающая точка";
+    thread_priority_normal => "Normal", "Обычный";
+    thread_priority_low => "Low", "Пониженный";
+    thread_priority_idle => "Idle", "Фоновый";
+}
+
+messages! {
+    noise_describe(kind: &str, color: &str, level: u8, envelope: &str, repeat: f32, snr: f32) =>
+        "Noise: {kind} {level}%, color: {color}, envelope: {envelope}, repeat: {repeat:.1}x, SNR: {snr:.1} dB",
+        "Шум: {kind} {level}%, окраска: {color}, огибающая: {envelope}, повторение: {repeat:.1}x, SNR: {snr:.1} дБ";
+    retarder_describe(percent: f32, envelope: &str, repeat: f32, delay_ms: u32) =>
+        "Retarder: {percent:.1}%, envelope: {envelope}, repeat: {repeat:.1}x, delay: {delay_ms} ms",
+        "Ретардер: {percent:.1}%, огибающая: {envelope}, повторение: {repeat:.1}x, задержка: {delay_ms} мс";
+    retarder_source(source: &str) => "source: {source}", "источник: {source}";
+    multipath_tap(delay_ms: f32, gain: f32, phase_deg: f32) =>
+        "{delay_ms:.1} ms × {gain:.2} ∠{phase_deg:.0}°",
+        "{delay_ms:.1} мс × {gain:.2} ∠{phase_deg:.0}°";
+    multipath_describe(taps: &str) => "Multipath: {taps}", "Многолучёвость: {taps}";
+    fading_describe(model: &str, percent: f32, rate_hz: f32) =>
+        "Fading: {model}, depth: {percent:.0}%, rate: {rate_hz:.2} Hz",
+        "Замирания: {model}, глубина: {percent:.0}%, скорость: {rate_hz:.2} Гц";
+    drift_describe(offset_hz: f32, drift_hz_per_s: f32, wobble_hz: f32, wobble_rate_hz: f32) =>
+        "Frequency drift: offset {offset_hz:+.1} Hz, drift {drift_hz_per_s:+.2} Hz/s, wobble ±{wobble_hz:.1} Hz @ {wobble_rate_hz:.2} Hz",
+        "Уход частоты: расстройка {offset_hz:+.1} Гц, дрейф {drift_hz_per_s:+.2} Гц/с, плавание ±{wobble_hz:.1} Гц @ {wobble_rate_hz:.2} Гц";
+    interference_carrier(freq_hz: f32, level: f32) =>
+        "{freq_hz:.0} Hz × {level:.2}",
+        "{freq_hz:.0} Гц × {level:.2}";
+    interference_keyed_carrier(freq_hz: f32, level: f32, percent: f32, period_ms: f32) =>
+        "{freq_hz:.0} Hz × {level:.2} ({percent:.0}% of {period_ms:.0} ms)",
+        "{freq_hz:.0} Гц × {level:.2} ({percent:.0}% из {period_ms:.0} мс)";
+    interference_qrm(offset_hz: f32, level: f32, delay_ms: f32) =>
+        "SSTV {offset_hz:+.0} Hz × {level:.2}, delay {delay_ms:.0} ms",
+        "SSTV {offset_hz:+.0} Гц × {level:.2}, задержка {delay_ms:.0} мс";
+    interference_describe(parts: &str) => "Interference: {parts}", "Помехи: {parts}";
+    blanker_describe(threshold: f32, hold_ms: f32, average_ms: f32, fill: &str) =>
+        "Noise blanker: threshold {threshold:.1}× RMS, hold {hold_ms:.1} ms, average {average_ms:.0} ms, fill: {fill}",
+        "Подавитель помех: порог {threshold:.1}× СКЗ, удержание {hold_ms:.1} мс, среднее {average_ms:.0} мс, заполнение: {fill}";
+    filter_describe(kind: &str, freq_hz: f32, q: f32, stages: u32) =>
+        "Audio filter: {kind} {freq_hz:.0} Hz, Q {q:.2}, stages: {stages}",
+        "Звуковой фильтр: {kind} {freq_hz:.0} Гц, добротность {q:.2}, звеньев: {stages}";
+    notch_describe(freq_hz: f32, q: f32) =>
+        "Notch filter: {freq_hz:.0} Hz, Q {q:.1}",
+        "Режекция: {freq_hz:.0} Гц, добротность {q:.1}";
+    agc_describe(target: f32, attack_ms: f32, release_ms: f32, max_gain_db: f32) =>
+        "AGC: target {target:.2}, attack {attack_ms:.1} ms, release {release_ms:.0} ms, max gain {max_gain_db:.0} dB",
+        "АРУ: уровень {target:.2}, атака {attack_ms:.1} мс, восстановление {release_ms:.0} мс, усиление до {max_gain_db:.0} дБ";
+    transmitter_describe(low_hz: f32, high_hz: f32, drive_db: f32, alc_ms: f32, carrier: f32) =>
+        "SSB transmitter: passband {low_hz:.0}–{high_hz:.0} Hz, drive {drive_db:+.1} dB, ALC {alc_ms:.0} ms, carrier {carrier:.3}",
+        "Передатчик SSB: полоса {low_hz:.0}–{high_hz:.0} Гц, перегрузка {drive_db:+.1} дБ, АРМ {alc_ms:.0} мс, несущая {carrier:.3}";
+    receiver_describe(sideband: &str, low_hz: f32, high_hz: f32, output: &str) =>
+        "SSB receiver: {sideband} {low_hz:.0}–{high_hz:.0} Hz, output {output}",
+        "Приёмник SSB: {sideband} {low_hz:.0}–{high_hz:.0} Гц, выход {output}";
+    dropout_windows(count: usize) => "windows: {count}", "окон: {count}";
+    dropout_rate(rate_per_min: f32, duration_ms: f32) =>
+        "{rate_per_min:.1} per minute, {duration_ms:.0} ms each",
+        "{rate_per_min:.1} в минуту по {duration_ms:.0} мс";
+    dropout_mute(kind: &str, attenuation_db: f32) =>
+        "{kind} by {attenuation_db:.0} dB",
+        "{kind} на {attenuation_db:.0} дБ";
+    dropout_describe(parts: &str, kind: &str) => "Dropouts: {parts}, {kind}", "Выпадения: {parts}, {kind}";
+    timeline_event(effect: &str, start_s: f32, end_s: f32) =>
+        "{effect} {start_s:.1}–{end_s:.1} s",
+        "{effect} {start_s:.1}–{end_s:.1} с";
+    timeline_describe(events: &str) => "Events: {events}", "События: {events}";
+}
+
+// ── Конвейер и проверка NaN ──────────────────────────────────
+texts! {
+    stage_encode => "Encoding", "Кодирование";
+    stage_effects => "Channel effects", "Эффекты канала";
+    stage_decode => "Decoding", "Декодирование";
+    cancelled => "Processing cancelled", "Обработка отменена";
+    sanitize_off => "Off", "Выключена";
+    sanitize_repair => "Repair", "Исправлять";
+    sanitize_strict => "Strict", "Прерывать";
+    estimate_clean => "Clean channel: no effects enabled", "Чистый канал: эффекты выключены";
+}
+
+messages! {
+    timeout_stage(stage: &str, secs: f64) =>
+        "Stage \"{stage}\" exceeded its {secs:.1} s limit",
+        "Этап «{stage}» не уложился в {secs:.1} с";
+    timeout_total(secs: f64) =>
+        "Processing exceeded its {secs:.1} s limit",
+        "Обработка не уложилась в {secs:.1} с";
+    timeout_expected_stage(value: &str) =>
+        "Expected stage:seconds, got \"{value}\"",
+        "Ожидается этап:секунды, получено «{value}»";
+    timeout_unknown_stage(stage: &str, known: &str) =>
+        "Unknown stage \"{stage}\", expected one of: {known}",
+        "Неизвестный этап «{stage}», ожидается один из: {known}";
+    timeout_expected_seconds(value: &str) =>
+        "Expected a positive number of seconds: \"{value}\"",
+        "Ожидается положительное число секунд: «{value}»";
+    sanitize_describe(effect: &str, nan: usize, infinite: usize, first: usize) =>
+        "\"{effect}\": NaN {nan}, infinities {infinite}, first at sample {first}",
+        "«{effect}»: NaN {nan}, бесконечностей {infinite}, первый в сэмпле {first}";
+    estimate_added_power(db: f32) => "adds {db:+.1} dB re signal", "добавка {db:+.1} дБ к сигналу";
+    estimate_gain(db: f32) => "signal {db:+.1} dB", "сигнал {db:+.1} дБ";
+    estimate_delay(ms: f32) => "delay {ms:.0} ms", "задержка {ms:.0} мс";
+    estimate_passband(low: f32, high: f32) => "passband {low:.0}–{high:.0} Hz", "полоса {low:.0}–{high:.0} Гц";
+    estimate_offset(min: f32, max: f32) => "offset {min:+.0}…{max:+.0} Hz", "расстройка {min:+.0}…{max:+.0} Гц";
+    estimate_affected(percent: f32) => "{percent:.1}% of the signal", "{percent:.1}% сигнала";
+    estimate_snr(snr: f32) => "Estimated SNR: {snr:.1} dB", "Ожидаемое С/Ш: {snr:.1} дБ";
+    estimate_latency(samples: usize) => "{samples} samples late", "запаздывание {samples} сэмпл.";
+    estimate_group_delay(samples: f32) => "group delay {samples:.1} samples", "групповая задержка {samples:.1} сэмпл.";
+    estimate_total_group_delay(samples: f32, ms: f32) =>
+        "Group delay: {samples:.1} samples ({ms:.2} ms)",
+        "Групповая задержка: {samples:.1} сэмпл. ({ms:.2} мс)";
+    estimate_added(samples: usize) => "lengthens by {samples} samples", "удлиняет на {samples} сэмпл.";
+    estimate_ghost_offset(offset: &str) => "copy shifted {offset}", "копия сдвинута на {offset}";
+    estimate_length(secs: f32, rate: u32, added_ms: f32) =>
+        "Signal: {secs:.2} s at {rate} Hz, effects add {added_ms:.0} ms",
+        "Сигнал: {secs:.2} с при {rate} Гц, эффекты добавляют {added_ms:.0} мс";
+    offset_lines(lines: u32) => "{lines} lines", "{lines} стр.";
+    offset_channels(channels: u32) => "{channels} colour channels", "{channels} цв. кан.";
+    offset_pixels(pixels: f32) => "{pixels:.1} px", "{pixels:.1} пикс.";
+    effect_mix(wet: f32, dry: f32) => "wet {wet:.2}, dry {dry:.2}", "wet {wet:.2}, dry {dry:.2}";
+}
+
+// ── Эффекты изображения ──────────────────────────────────────
+texts! {
+    palette => "Palette", "Палитра";
+    palette_full => "24-bit", "24 бита";
+    palette_rgb666 => "18-bit (Robot 1200C)", "18 бит (Robot 1200C)";
+    palette_rgb444 => "12-bit", "12 бит";
+    palette_rgb332 => "8-bit (3-3-2)", "8 бит (3-3-2)";
+    palette_robot => "Robot 400 (16 greys)", "Robot 400 (16 серых)";
+    vertical_blur => "Vertical blur", "Вертикальное размытие";
+    scanlines => "Scanlines", "Строки развёртки";
+    phosphor => "Phosphor", "Люминофор";
+    phosphor_p22 => "P22 (colour)", "P22 (цветной)";
+    phosphor_p4 => "P4 (white)", "P4 (белый)";
+    phosphor_p1 => "P1 (green)", "P1 (зелёный)";
+    phosphor_p3 => "P3 (amber)", "P3 (янтарный)";
+    scan_converter_off => "Scan converter off", "Преобразователь развёртки отключён";
+}
+
+messages! {
+    palette_describe(name: &str) => "Palette: {name}", "Палитра: {name}";
+    blur_describe(percent: f32) => "blur {percent:.0}%", "размытие {percent:.0}%";
+    scanlines_describe(percent: f32) => "scanlines {percent:.0}%", "строки {percent:.0}%";
+    phosphor_describe(name: &str, percent: f32) =>
+        "phosphor {name} {percent:.0}%",
+        "люминофор {name} {percent:.0}%";
+    scan_converter_describe(parts: &str) =>
+        "Scan converter: {parts}",
+        "Преобразователь развёртки: {parts}";
+}
+
+// ── Отчёты ───────────────────────────────────────────────────
+texts! {
+    report_partial => "image incomplete", "изображение неполное";
+    vis_unknown_mode => "unknown mode", "неизвестный режим";
+    vis_parity_ok => "correct", "верна";
+    vis_parity_bad => "WRONG", "НЕ верна";
+    null_identical => "∞ (signals match)", "∞ (сигналы совпадают)";
+    decode_not_found =>
+        "The decoder found no image: lower the noise or the retarder",
+        "Декодер не нашёл изображение — уменьшите Шум или Ретардер";
+    decode_no_first_sync =>
+        "The decoder found no sync pulse of the first line",
+        "Декодер не нашёл синхроимпульс первой строки";
+}
+
+messages! {
+    report_lines(lines: u32) => "Lines: {lines}", "Строк: {lines}";
+    report_sync(percent: f32) => "sync {percent:.0}%", "синхронизация {percent:.0}%";
+    report_confidence(percent: f32) =>
+        "mean confidence {percent:.0}%",
+        "средняя уверенность {percent:.0}%";
+    report_slant(ppm: f64) => "slant {ppm:+.0} ppm", "наклон {ppm:+.0} ppm";
+    report_snr(snr_db: f32) => "SNR ≈ {snr_db:.0} dB", "С/Ш ≈ {snr_db:.0} дБ";
+    occupancy_bandwidth(bandwidth: f32, low: f32, high: f32) =>
+        "occupied bandwidth {bandwidth:.0} Hz ({low:.0}–{high:.0} Hz)",
+        "занимаемая полоса {bandwidth:.0} Гц ({low:.0}–{high:.0} Гц)";
+    occupancy_mask_ok(margin_db: f32, freq: f32) =>
+        "within the mask, least margin {margin_db:.1} dB at {freq:.0} Hz",
+        "в пределах маски, наименьший запас {margin_db:.1} дБ на {freq:.0} Гц";
+    occupancy_mask_violated(excess_db: f32, freq: f32) =>
+        "OUTSIDE the mask by {excess_db:.1} dB at {freq:.0} Hz",
+        "ВНЕ маски на {excess_db:.1} дБ на {freq:.0} Гц";
+    mask_describe(low: f32, high: f32, skirt: f32, skirt_db: f32, floor_db: f32) =>
+        "channel {low:.0}–{high:.0} Hz, {skirt_db:.0} dB within {skirt:.0} Hz of its edges, {floor_db:.0} dB beyond",
+        "канал {low:.0}–{high:.0} Гц, {skirt_db:.0} дБ в {skirt:.0} Гц от краёв, {floor_db:.0} дБ дальше";
+    loudness_describe(unit: &str, before_db: f32, after_db: f32, gain_db: f32, true_peak_db: f32) =>
+        "loudness {before_db:.1} → {after_db:.1} {unit} (gain {gain_db:+.1} dB), true peak {true_peak_db:.1} dBTP",
+        "громкость {before_db:.1} → {after_db:.1} {unit} (усиление {gain_db:+.1} дБ), истинный пик {true_peak_db:.1} дБTP";
+    signal_quality(offset: isize, offset_ms: f64, scale: f32, snr_db: f64) =>
+        "signal: offset {offset} samples ({offset_ms:.2} ms), gain ×{scale:.3}, SNR after alignment {snr_db:.1} dB",
+        "сигнал: сдвиг {offset} сэмплов ({offset_ms:.2} мс), усиление ×{scale:.3}, С/Ш после выравнивания {snr_db:.1} дБ";
+    report_level(peak_db: f32, rms_db: f32) =>
+        "output peak {peak_db:.1} dBFS, RMS {rms_db:.1} dBFS",
+        "выход: пик {peak_db:.1} дБFS, RMS {rms_db:.1} дБFS";
+    report_clipped(percent: f32) =>
+        "clipped {percent:.2}% of samples",
+        "обрезано {percent:.2}% сэмплов";
+    report_sanitized(effects: &str) =>
+        "NaN/∞ repaired after: {effects}",
+        "исправлены NaN/∞ после: {effects}";
+    report_metadata(fields: &str) => "metadata: {fields}", "метаданные: {fields}";
+    vis_describe(code: u8, mode: &str, parity: &str, percent: f32) =>
+        "VIS {code} ({mode}), parity {parity}, confidence at least {percent:.0}%",
+        "VIS {code} ({mode}), чётность {parity}, уверенность не ниже {percent:.0}%";
+    segment_vis_bit(bit: u8) => "VIS bit {bit}", "бит VIS {bit}";
+    segment_sync(line: u32) => "line {line}: sync", "строка {line}: синхроимпульс";
+    segment_porch(line: u32) => "line {line}: porch", "строка {line}: площадка";
+    segment_pixel(line: u32, channel: &str, x: u32) =>
+        "line {line}: {channel} pixel {x}",
+        "строка {line}: пиксель {x} канала {channel}";
+    segment_separator(line: u32, channel: &str) =>
+        "line {line}: separator after {channel}",
+        "строка {line}: разделитель после {channel}";
+    registration_describe(lines: i32, offset_px: f64, slant_px: f64) =>
+        "registered: shifted {lines:+} lines, {offset_px:+.1} px, slant {slant_px:+.1} px top to bottom",
+        "совмещение: сдвиг {lines:+} строк, {offset_px:+.1} пикс., наклон {slant_px:+.1} пикс. сверху вниз";
+    quality_psnr(psnr_db: f64) => "{psnr_db:.2} dB", "{psnr_db:.2} дБ";
+    null_depth(depth_db: f64) => "{depth_db:.1} dB", "{depth_db:.1} дБ";
+    null_describe(offset: isize, depth: &str, peak: f32, energy: f64, unmatched: usize) =>
+        "offset {offset} samples, suppression {depth}, residual peak {peak:.6}, residual energy {energy:.3e}, mismatched samples {unmatched}",
+        "сдвиг {offset} сэмплов, подавление {depth}, пик остатка {peak:.6}, энергия остатка {energy:.3e}, несовпадающих сэмплов {unmatched}";
+    telemetry_describe(temperature_c: f32, latitude: f64, longitude: f64, altitude_m: f32) =>
+        "{temperature_c:.1} °C, {latitude:.5}°, {longitude:.5}°, {altitude_m:.0} m",
+        "{temperature_c:.1} °C, {latitude:.5}°, {longitude:.5}°, {altitude_m:.0} м";
+}
+
+// ── Проверка соответствия ────────────────────────────────────
+texts! {
+    unit_hz => "Hz", "Гц";
+    unit_us => "µs", "мкс";
+    check_leader_freq => "Leader tone, frequency", "Лидер-тон, частота";
+    check_header_sync_freq => "Break, start and stop bits, frequency", "Разрыв, стартовый и стоповый биты, частота";
+    check_vis_freq => "VIS bits, frequency", "Биты VIS, частота";
+    check_vis_code => "VIS code and parity", "Код VIS и чётность";
+    check_leader_len => "First leader tone, length", "Первый лидер-тон, длительность";
+    check_header_edges => "Header, tone edges", "Заголовок, границы тонов";
+    check_sync_freq => "Line sync pulse, frequency", "Синхроимпульс строки, частота";
+    check_sync_len => "Line sync pulse, length", "Синхроимпульс строки, длительность";
+    check_pixel_freq => "Pixel brightness, frequency", "Яркость пикселей, частота";
+    check_line_edges => "Line, tone edges", "Строка, границы тонов";
+    check_period => "Line period", "Период строки";
+    check_lines => "Lines found", "Найдено строк";
+    check_no_header =>
+        "No header found: a leader tone with a break",
+        "Не найден заголовок: лидер-тон с разрывом";
+}
+
+messages! {
+    check_unknown_vis(code: u8) =>
+        "VIS code {code} belongs to no known mode",
+        "Код VIS {code} не относится ни к одному известному режиму";
+    check_unmeasured(mark: &str, name: &str) => "{mark} {name}: not measured", "{mark} {name}: не измерено";
+    check_describe(mark: &str, name: &str, count: usize, mean: &str, worst: &str, tolerance: &str) =>
+        "{mark} {name}: {count} measurements, mean deviation {mean}, worst {worst} (tolerance ±{tolerance})",
+        "{mark} {name}: измерений {count}, среднее отклонение {mean}, наибольшее {worst} (допуск ±{tolerance})";
+}
+
+// ── Поиск сбоев ──────────────────────────────────────────────
+texts! {
+    fuzz_effects => "Effects", "Эффекты";
+    fuzz_decode => "Decoder", "Декодер";
+    fuzz_process => "Full run", "Полный прогон";
+    fuzz_differential => "Decoder comparison", "Сравнение декодеров";
+    fuzz_no_message => "no message", "без сообщения";
+}
+
+messages! {
+    fuzz_failure(target: &str, seed: u64, message: &str) =>
+        "{target}, seed {seed}: {message}",
+        "{target}, зерно {seed}: {message}";
+    fuzz_panic(message: &str) => "panic: {message}", "паника: {message}";
+    fuzz_sanitized(event: &str) => "corrupted signal after {event}", "испорченный сигнал после {event}";
+    fuzz_length(len: usize, expected: usize, from_rate: u32, to_rate: u32) =>
+        "length {len} instead of {expected} ({from_rate} → {to_rate} Hz)",
+        "длина {len} вместо {expected} ({from_rate} → {to_rate} Гц)";
+    fuzz_rate(rate: u32, expected: u32) =>
+        "rate {rate} Hz instead of {expected} Hz",
+        "частота {rate} Гц вместо {expected} Гц";
+    fuzz_span(span: &str, len: usize) =>
+        "image span {span} outside the {len}-sample signal",
+        "тело изображения {span} вне сигнала из {len} сэмплов";
+    fuzz_not_finite(index: usize, value: f32) => "sample {index} is {value}", "сэмпл {index} равен {value}";
+    fuzz_empty_image(width: u32, height: u32) => "empty image {width}×{height}", "пустое изображение {width}×{height}";
+    fuzz_only_decoded(decoded: &str, failed: &str, error: &str) =>
+        "only {decoded} decoded the transmission, {failed}: {error}",
+        "передачу декодировал только {decoded}, {failed}: {error}";
+    fuzz_disagreement(quality: &str, min_db: f64) =>
+        "decoders disagree: {quality} (need at least {min_db} dB)",
+        "декодеры расходятся: {quality} (нужно не меньше {min_db} дБ)";
+    fuzz_difference(seed: u64, quality: &str, registration: &str, rsstv_lines: u32, native_lines: u32) =>
+        "seed {seed}: {quality}; {registration}; lines rsstv {rsstv_lines}, native {native_lines}",
+        "зерно {seed}: {quality}; {registration}; строк rsstv {rsstv_lines}, встроенный {native_lines}";
+}
+
+// ── Долгий прогон ────────────────────────────────────────────
+messages! {
+    soak_cycle(seed: u64, lines: u32, psnr: f64, secs: f32) =>
+        "seed {seed}: {lines} lines, PSNR {psnr:.1} dB, {secs:.1} s",
+        "зерно {seed}: строк {lines}, PSNR {psnr:.1} дБ, {secs:.1} с";
+    soak_cycle_error(seed: u64, error: &str) => "seed {seed}: {error}", "зерно {seed}: {error}";
+    soak_describe(cycles: usize, successes: usize, ratio: f32, errors: usize, psnr: f64) =>
+        "cycles {cycles}, received {successes} ({ratio:.1}%), errors {errors}, mean PSNR {psnr:.1} dB",
+        "циклов {cycles}, принято {successes} ({ratio:.1}%), ошибок {errors}, средний PSNR {psnr:.1} дБ";
+    soak_memory(memory: f64, peak: f64, growth: f64) =>
+        "memory {memory:.1} MB, peak {peak:.1} MB, growth {growth:+.1} MB",
+        "память {memory:.1} МБ, пик {peak:.1} МБ, рост {growth:+.1} МБ";
+    soak_errors(errors: usize) => "cycles with errors: {errors}", "циклов с ошибками: {errors}";
+    soak_memory_grew(growth: f64, limit: f64) =>
+        "memory grew by {growth:.1} MB after warm-up (limit {limit:.1} MB)",
+        "память выросла на {growth:.1} МБ после разогрева (предел {limit:.1} МБ)";
+    soak_started(hours: f64, seed: u64) =>
+        "Soak run for {hours} h from seed {seed}",
+        "Долгий прогон на {hours} ч с зерна {seed}";
+    soak_progress(hours: u64, minutes: u64, stats: &str) =>
+        "[{hours}:{minutes:02}] {stats}",
+        "[{hours}:{minutes:02}] {stats}";
+    soak_passed() => "Soak run passed", "Долгий прогон пройден";
+    soak_failed(problems: usize) => "Violations: {problems}", "Нарушений: {problems}";
+    soak_open_failed(path: &str) => "Could not open {path}", "Не удалось открыть {path}";
+    soak_create_failed(path: &str) => "Could not create {path}", "Не удалось создать {path}";
+    soak_log_failed() => "Could not write the cycle log", "Не удалось записать журнал циклов";
+    soak_low_success(ratio: f32, min: f32) =>
+        "received {ratio:.1}% of transmissions, need at least {min:.1}%",
+        "принято {ratio:.1}% передач, нужно не меньше {min:.1}%";
+}
+
+// ── Советы пробного прогона ──────────────────────────────────
+texts! {
+    severity_info => "Hint", "Совет";
+    severity_warning => "Warning", "Предупреждение";
+    severity_fatal => "Failure", "Сбой";
+    advice_noise_will_fail => "otherwise decoding will fail", "иначе декодирование сорвётся";
+    advice_noise_may_fail => "the decoder may lose the image", "декодер может потерять изображение";
+    advice_afc_rsstv =>
+        "AFC with rsstv only removes the initial mistuning; drift is tracked by the built-in decoder",
+        "АПЧ с rsstv убирает только начальную расстройку; за уходом частоты следит встроенный декодер";
+    advice_slant_rsstv =>
+        "slant correction only works in the built-in decoder",
+        "коррекция наклона работает только во встроенном декодере";
+}
+
+messages! {
+    advice_probe_failed(error: &str) =>
+        "the probe run did not decode the image ({error})",
+        "пробный прогон не декодировал изображение ({error})";
+    advice_noise(level: u8, limit: u8, decoder: &str, outcome: &str) =>
+        "noise level {level} is above the limit {limit} for the {decoder} decoder — lower the noise, {outcome}",
+        "уровень шума {level} выше предела {limit} для декодера {decoder} — уменьшите шум, {outcome}";
+    advice_ghost_delay(delay_ms: u32, limit_lines: f64, delay_lines: f64) =>
+        "ghost delay {delay_ms} ms exceeds {limit_lines:.0} lines ({delay_lines:.1}): the ghost will slide far down and the probe will not see it",
+        "задержка призрака {delay_ms} мс больше {limit_lines:.0} строк ({delay_lines:.1}): призрак сползёт далеко вниз, а проба его не увидит";
+    advice_clip_alias(rate: u32, min_rate: u32) =>
+        "at {rate} Hz the harmonics of the clipped rsstv signal fold into the SSTV band — pick the built-in encoder or a rate of {min_rate} Hz or more",
+        "на частоте {rate} Гц гармоники обрезанного сигнала rsstv попадут в полосу SSTV — выберите встроенный кодер или частоту от {min_rate} Гц";
+    advice_passband(low_hz: f32, high_hz: f32, sync_hz: f32, white_hz: f32) =>
+        "the transceiver passband {low_hz:.0}–{high_hz:.0} Hz does not pass the SSTV tones {sync_hz:.0}–{white_hz:.0} Hz — widen it",
+        "полоса трансивера {low_hz:.0}–{high_hz:.0} Гц не пропускает тоны SSTV {sync_hz:.0}–{white_hz:.0} Гц — расширьте её";
+    advice_deviation(worst_hz: f32, max_hz: f32) =>
+        "mistuning reaches {worst_hz:.0} Hz but the decoder allows {max_hz:.0} Hz: raise the allowed deviation",
+        "расстройка доходит до {worst_hz:.0} Гц, а декодер допускает {max_hz:.0} Гц: увеличьте допустимое отклонение";
+    advice_afc(worst_hz: f32) =>
+        "mistuning of up to {worst_hz:.0} Hz will shift the image brightness — turn on AFC",
+        "расстройка до {worst_hz:.0} Гц сдвинет яркость изображения — включите АПЧ";
+    advice_slant(error_percent: f64, search_percent: f64) =>
+        "lines are {error_percent:.1}% off nominal but the decoder clock searches within {search_percent:.0}% — turn on slant auto-correction or set the slant manually",
+        "строки длиннее или короче номинала на {error_percent:.1}%, а часы декодера ищут период в пределах {search_percent:.0}% — включите автокоррекцию наклона или задайте наклон вручную";
+    advice_fading(depth: f32) =>
+        "fading depth {depth:.2}: lines in the fades will be lost — turn on concealment of damaged lines",
+        "глубина замираний {depth:.2}: строки в провалах пропадут — включите маскировку испорченных строк";
+    advice_sync(percent: f32, threshold_percent: f32) =>
+        "sync in the probe is unstable: mean confidence {percent:.0}% is below the threshold {threshold_percent:.0}%",
+        "синхронизация в пробе неустойчива: средняя уверенность {percent:.0}% ниже порога {threshold_percent:.0}%";
+    advice_ssim(ssim: f64) =>
+        "SSIM of the first lines in the probe is {ssim:.2} — the image will be badly distorted",
+        "SSIM первых строк в пробе {ssim:.2} — изображение будет сильно искажено";
+}
+
+// ── Проверка параметров ──────────────────────────────────────
+messages! {
+    validate_repeat(repeat: f32) =>
+        "repeat {repeat} is not positive: the envelope freezes on its first point — use 1 to play it once",
+        "повтор {repeat} не положителен: огибающая застынет на первой точке — 1 проигрывает её один раз";
+    validate_delay(delay_ms: f32, transmission_ms: f64) =>
+        "delay {delay_ms:.0} ms is longer than the whole transmission ({transmission_ms:.0} ms): the echo never starts",
+        "задержка {delay_ms:.0} мс длиннее всей передачи ({transmission_ms:.0} мс): эхо так и не начнётся";
+    validate_snr(snr_db: f32, limit_db: f32, decoder: &str) =>
+        "SNR {snr_db:.1} dB is below {limit_db:.1} dB, where the {decoder} decoder still finds the image — lower the noise",
+        "С/Ш {snr_db:.1} дБ ниже {limit_db:.1} дБ, при которых декодер {decoder} ещё находит изображение — уменьшите шум";
+    validate_unit_range(value: f32, clamped: f32) =>
+        "{value} is outside 0–1 and will be silently clamped to {clamped}",
+        "{value} вне 0–1 и будет молча обрезано до {clamped}";
+    validate_output_gain(gain_db: f32, limit_db: f32) =>
+        "{gain_db} dB is beyond ±{limit_db} dB and will be limited",
+        "{gain_db} дБ за пределами ±{limit_db} дБ, усиление будет ограничено";
+    validate_true_peak(ceiling_db: f32) =>
+        "true-peak ceiling {ceiling_db} dBTP is above full scale: the WAV clips the peaks",
+        "потолок истинного пика {ceiling_db} дБTP выше полной шкалы: WAV обрежет пики";
+    validate_carrier_nyquist(freq_hz: f32, nyquist_hz: f32) =>
+        "carrier at {freq_hz} Hz is not below the channel's Nyquist frequency ({nyquist_hz} Hz) and is left out — raise --sample-rate",
+        "несущая {freq_hz} Гц не ниже частоты Найквиста канала ({nyquist_hz} Гц) и не добавляется — увеличьте частоту дискретизации";
+    validate_filter_nyquist(edge_hz: f32, nyquist_hz: f32) =>
+        "filter edge {edge_hz} Hz is above the channel's Nyquist frequency ({nyquist_hz} Hz) and will be limited to it",
+        "край фильтра {edge_hz} Гц выше частоты Найквиста канала ({nyquist_hz} Гц) и будет ограничен ею";
+    validate_overlay_ascii(line: &str) =>
+        "\"{line}\" has non-ASCII characters that the built-in font draws as ?; set a TTF font",
+        "в «{line}» есть знаки вне ASCII, встроенный шрифт нарисует их как ?; укажите шрифт TTF";
+}
+
+// ── Архив прогона ────────────────────────────────────────────
+texts! {
+    archive_empty => "the archive is empty", "архив пуст";
+    archive_truncated => "the archive is truncated", "архив обрезан";
+    archive_not_zip => "not a ZIP: no end of central directory", "это не ZIP: нет конца каталога";
+    archive_directory_damaged => "the archive directory is damaged", "каталог архива повреждён";
+    archive_too_large =>
+        "The archive is larger than 4 GiB: ZIP64 is not supported",
+        "Архив больше 4 ГиБ: ZIP64 не поддерживается";
+    archive_too_many_files =>
+        "More than 65535 files in the archive",
+        "Больше 65535 файлов в архиве";
+}
+
+messages! {
+    archive_not_run(dir: &str, manifest: &str) =>
+        "{dir} is not a run folder: could not read {manifest}",
+        "{dir} — не папка прогона: не удалось прочитать {manifest}";
+    archive_bad_manifest(error: &str) =>
+        "Invalid run manifest: {error}",
+        "Неверная опись прогона: {error}";
+    archive_read_failed(path: &str) => "Could not read {path}", "Не удалось прочитать {path}";
+    archive_write_failed(path: &str) => "Could not write {path}", "Не удалось записать {path}";
+    archive_create_dir_failed(path: &str) =>
+        "Could not create the directory {path}",
+        "Не удалось создать каталог {path}";
+    archive_bad_name(name: &str) =>
+        "invalid entry name \"{name}\"",
+        "недопустимое имя записи «{name}»";
+    archive_exists(path: &str) =>
+        "{path} already exists, unpack into another directory",
+        "{path} уже существует, распакуйте в другой каталог";
+    archive_no_manifest(manifest: &str) =>
+        "the archive has no {manifest}",
+        "в архиве нет {manifest}";
+    archive_file_too_large(name: &str) =>
+        "{name} is larger than 4 GiB: ZIP64 is not supported",
+        "{name} больше 4 ГиБ: ZIP64 не поддерживается";
+    archive_name_too_long(name: &str, len: usize) =>
+        "The entry name {name} is {len} bytes long, ZIP allows at most 65535",
+        "Имя записи {name} длиной {len} байт, а ZIP допускает не больше 65535";
+    archive_compressed(name: &str, method: usize) =>
+        "{name} is compressed (method {method}); only pack archives are unpacked",
+        "{name} сжат (метод {method}); распаковываются только архивы pack";
+    archive_header_damaged(name: &str) =>
+        "the header of {name} is damaged",
+        "заголовок {name} повреждён";
+    archive_crc_mismatch(name: &str) =>
+        "{name} is damaged: the CRC does not match",
+        "{name} повреждён: не сходится CRC";
+}
+
+// ── Ошибки ввода-вывода ──────────────────────────────────────
+texts! {
+    wav_no_channels => "the file has no channels", "файл не содержит каналов";
+    gpsd_no_address => "address not found", "адрес не найден";
+    gpsd_closed => "gpsd closed the connection", "gpsd закрыл соединение";
+    dashboard_png_failed => "Could not encode a PNG for the dashboard", "Не удалось закодировать PNG для панели";
+    dashboard_reply_failed => "Could not reply to the browser", "Не удалось ответить браузеру";
+    webhook_client_failed => "Could not create the HTTP client", "Не удалось создать HTTP-клиент";
+    webhook_png_failed => "Could not encode a PNG for the webhook", "Не удалось закодировать PNG для вебхука";
+    webhook_form_failed => "Could not build the webhook request", "Не удалось собрать запрос вебхука";
+    webhook_no_chat =>
+        "A Telegram bot needs a chat to send the images to",
+        "Боту Telegram нужен чат, куда слать изображения";
+    manifest_serialize_failed => "Could not serialize the manifest", "Не удалось сериализовать опись";
+    run_manifest_serialize_failed =>
+        "Could not serialize the run manifest",
+        "Не удалось сериализовать опись прогона";
+    preset_serialize_failed => "Could not serialize the preset", "Не удалось сериализовать пресет";
+    image_lines_send_failed => "Could not send the image lines", "Не удалось отправить строки изображения";
+    thread_pool_global_failed =>
+        "Could not configure the global thread pool",
+        "Не удалось настроить общий пул потоков";
+    thread_pool_failed => "Could not create the thread pool", "Не удалось создать пул потоков";
+    file_preset => "preset", "пресет";
+    file_timeline => "timeline", "сценарий";
+    image_retarder => "the retarder image", "ретардер-картинку";
+    image_qrm => "the interfering transmission image", "картинку мешающей передачи";
+}
+
+messages! {
+    open_failed(path: &str) => "Could not open {path}", "Не удалось открыть {path}";
+    read_failed(path: &str) => "Could not read {path}", "Не удалось прочитать {path}";
+    write_failed(path: &str) => "Could not write {path}", "Не удалось записать {path}";
+    create_failed(path: &str) => "Could not create {path}", "Не удалось создать {path}";
+    save_failed(path: &str) => "Could not save {path}", "Не удалось сохранить {path}";
+    decode_failed(what: &str) => "Could not decode {what}", "Не удалось декодировать {what}";
+    create_dir_failed(path: &str) =>
+        "Could not create the directory {path}",
+        "Не удалось создать каталог {path}";
+    read_dir_failed(path: &str) =>
+        "Could not read the directory {path}",
+        "Не удалось прочитать каталог {path}";
+    wav_open_failed(path: &str) => "Could not open the WAV file {path}", "Не удалось открыть WAV файл {path}";
+    wav_read_failed(path: &str) => "Could not read the WAV file {path}", "Не удалось прочитать WAV файл {path}";
+    wav_samples_failed(path: &str) =>
+        "Could not read samples from {path}",
+        "Не удалось прочитать сэмплы из {path}";
+    wav_metadata_failed(path: &str) =>
+        "Could not write metadata to {path}",
+        "Не удалось записать метаданные в {path}";
+    metadata_expected_pair(value: &str) =>
+        "Expected key=value, got \"{value}\"",
+        "Ожидается ключ=значение, получено «{value}»";
+    metadata_bad_key(key: &str, max: usize) =>
+        "Key \"{key}\" must be 1–{max} Latin letters, digits, \"_\", \"-\" or \".\"",
+        "Ключ «{key}» должен быть из 1–{max} латинских букв, цифр, «_», «-» или «.»";
+    manifest_write_failed(path: &str) =>
+        "Could not write the manifest {path}",
+        "Не удалось записать опись {path}";
+    image_save_failed(path: &str) =>
+        "Could not save the image to {path}",
+        "Не удалось сохранить изображение в {path}";
+    result_save_failed(path: &str) =>
+        "Could not save the result to {path}",
+        "Не удалось сохранить результат в {path}";
+    preset_unknown_format(path: &str) =>
+        "Unknown preset format {path}: expected .toml or .json",
+        "Неизвестный формат пресета {path}: ожидается .toml или .json";
+    preset_write_failed(path: &str) => "Could not write the preset {path}", "Не удалось записать пресет {path}";
+    file_read_failed(what: &str, path: &str) =>
+        "Could not read the {what} {path}",
+        "Не удалось прочитать {what} {path}";
+    file_invalid(what: &str, path: &str, error: &str) =>
+        "Invalid {what} {path}: {error}",
+        "Неверный {what} {path}: {error}";
+    envelope_read_failed(path: &str) =>
+        "Could not read the envelope {path}",
+        "Не удалось прочитать огибающую {path}";
+    envelope_write_failed(path: &str) =>
+        "Could not write the envelope {path}",
+        "Не удалось записать огибающую {path}";
+    envelope_invalid(path: &str, error: &str) =>
+        "Invalid envelope {path}: {error}",
+        "Неверная огибающая {path}: {error}";
+    checkpoint_write_failed(path: &str) =>
+        "Could not write the checkpoint {path}",
+        "Не удалось записать контрольную точку {path}";
+    checkpoint_read_failed(path: &str) =>
+        "Could not read the checkpoint {path}",
+        "Не удалось прочитать контрольную точку {path}";
+    checkpoint_invalid(path: &str, error: &str) =>
+        "{path} is not a checkpoint: {error}",
+        "Файл {path} — не контрольная точка: {error}";
+    gif_write_failed(path: &str) => "Could not write the GIF {path}", "Не удалось записать GIF {path}";
+    frame_save_failed(path: &str) => "Could not save the frame {path}", "Не удалось сохранить кадр {path}";
+    gpsd_read_failed(addr: &str) => "Could not read gpsd {addr}", "Не удалось прочитать gpsd {addr}";
+}
+
+// ── Звуковые устройства ──────────────────────────────────────
+texts! {
+    device_list_outputs => "Could not list the output devices", "Не удалось получить список устройств вывода";
+    device_list_inputs => "Could not list the input devices", "Не удалось получить список устройств ввода";
+    device_output_config =>
+        "Could not get the output device configuration",
+        "Не удалось получить конфигурацию устройства вывода";
+    device_input_config =>
+        "Could not get the input device configuration",
+        "Не удалось получить конфигурацию устройства ввода";
+    device_output_configs =>
+        "Could not get the output device configurations",
+        "Не удалось получить конфигурации устройства вывода";
+    device_start_playback => "Could not start playback", "Не удалось запустить воспроизведение";
+    device_start_recording => "Could not start recording", "Не удалось запустить запись";
+    device_open_output => "Could not open the output stream", "Не удалось открыть поток вывода";
+    device_open_input => "Could not open the input stream", "Не удалось открыть поток ввода";
+    device_no_default_output => "No default output device", "Нет устройства вывода по умолчанию";
+    device_no_default_input => "No default input device", "Нет устройства ввода по умолчанию";
+}
+
+messages! {
+    device_sample_format(format: &str) =>
+        "Unsupported device sample format: {format}",
+        "Неподдерживаемый формат сэмплов устройства: {format}";
+    device_output_not_found(name: &str) => "Output device not found: {name}", "Устройство вывода не найдено: {name}";
+    device_input_not_found(name: &str) => "Input device not found: {name}", "Устройство ввода не найдено: {name}";
+    device_rate_unsupported(rate: u32) =>
+        "The output device does not support the input device's rate of {rate} Hz",
+        "Устройство вывода не поддерживает частоту устройства ввода {rate} Гц";
+}
+
+// ── Ошибки параметров ────────────────────────────────────────
+texts! {
+    rate_not_positive => "must be greater than zero", "должна быть больше нуля";
+    value_delay => "delay", "задержки";
+    value_gain => "gain", "усиления";
+    value_phase => "phase", "фазы";
+    value_start => "start", "начала";
+    value_duration => "duration", "длительности";
+    value_frequency => "frequency", "частоты";
+    value_level => "level", "уровня";
+    value_duty => "duty cycle", "скважности";
+    value_period => "period", "периода";
+    value_leader => "leader tone", "лидер-тона";
+    value_break => "break", "разрыва";
+    value_vis_bit => "VIS bit", "бита VIS";
+    format_adsr => "attack:decay:sustain:release", "атака:спад:удержание:затухание";
+    format_bursts => "on_ms:period_ms[:jitter_ms[:ramp_ms]]", "вкл_мс:период_мс[:разброс_мс[:фронт_мс]]";
+    format_mix => "effect:wet[:dry]", "эффект:wet[:dry]";
+    format_noise_color => "white, pink, brown or band:low:high", "white, pink, brown или band:нижняя:верхняя";
+    format_tap => "delay_ms:gain[:phase_deg]", "задержка_мс:усиление[:фаза_град]";
+    format_window => "start_ms:duration_ms", "начало_мс:длительность_мс";
+    format_carrier =>
+        "frequency_hz:level[:duty[:period_ms]]",
+        "частота_Гц:уровень[:скважность[:период_мс]]";
+    format_telemetry => "temperature,latitude,longitude,altitude", "температура,широта,долгота,высота";
+    envelope_empty => "The envelope has no points", "В огибающей нет ни одной точки";
+    retarder_no_wav =>
+        "Reading WAV is unavailable: the crate is built without the wav feature",
+        "Чтение WAV недоступно: крейт собран без функции wav";
+    retarder_source_expected =>
+        "Expected image, self, flip, invert or a path to a WAV",
+        "Ожидается image, self, flip, invert или путь к WAV";
+    streaming_wav_ghost =>
+        "Streaming does not support a ghost from a WAV",
+        "Потоковая обработка не поддерживает призрак из WAV";
+    streaming_native_only =>
+        "Streaming supports only the built-in decoder",
+        "Потоковая обработка поддерживает только встроенный декодер";
+    telemetry_empty => "The telemetry strip is empty", "Полоса телеметрии пуста";
+    telemetry_bad_crc =>
+        "The telemetry strip is damaged: the checksum does not match",
+        "Полоса телеметрии повреждена: не совпала контрольная сумма";
+}
+
+messages! {
+    expected_format(format: &str, value: &str) =>
+        "Expected {format}, got \"{value}\"",
+        "Ожидается {format}, получено «{value}»";
+    invalid_value(what: &str, value: &str) =>
+        "Invalid {what}: \"{value}\"",
+        "Неверное значение {what}: «{value}»";
+    invalid_number(value: &str) => "Invalid number: \"{value}\"", "Неверное число: «{value}»";
+    invalid_number_in(value: &str, text: &str) =>
+        "Invalid number \"{value}\" in \"{text}\"",
+        "Неверное число «{value}» в «{text}»";
+    range_bad_number(value: &str) =>
+        "Invalid number in the range: \"{value}\"",
+        "Неверное число в диапазоне: «{value}»";
+    range_bad_step(range: &str) =>
+        "The range step must be positive: \"{range}\"",
+        "Шаг диапазона должен быть положительным: «{range}»";
+    range_reversed(range: &str) =>
+        "The range ends before it starts: \"{range}\"",
+        "Конец диапазона меньше начала: «{range}»";
+    ramp_bad_number(value: &str) =>
+        "Invalid number in the ramp: \"{value}\"",
+        "Неверное число в изменении: «{value}»";
+    noise_level_invalid(error: &str) => "Invalid noise level: {error}", "Неверный уровень шума: {error}";
+    row_outside(y: u32) => "Row {y} is outside the image", "Строка {y} за пределами изображения";
+    buffer_too_small(len: usize, width: u32, height: u32, needed: usize) =>
+        "The buffer holds {len} bytes, but a {width}×{height} image needs {needed}",
+        "Буфер на {len} байт, а изображению {width}×{height} нужно {needed}";
+    unsupported_sample_rate(rate: u32, min: u32) =>
+        "Sample rate {rate} Hz is below {min} Hz: the SSTV tones do not fit into the band",
+        "Частота дискретизации {rate} Гц ниже {min} Гц: тоны SSTV не помещаются в полосу";
+    mode_unsupported(mode: &str, available: &str) =>
+        "Mode {mode} is not supported: only {available} is available",
+        "Режим {mode} не поддерживается: доступен только {available}";
+    buffer_not_mono(channels: u16) =>
+        "Channel effects work on a mono signal, but the buffer has {channels} channel(s)",
+        "Эффекты канала работают с моно-сигналом, а в буфере {channels} канала(ов)";
+    simd_unsupported(level: &str) => "The CPU does not support {level}", "Процессор не поддерживает {level}";
+    header_duration(what: &str, ms: f32, max: f32) =>
+        "The {what} duration {ms} ms is outside 0–{max} ms",
+        "Длительность {what} {ms} мс вне 0–{max} мс";
+    timing_scale_range(scale: f64, max: f64) =>
+        "The timing scale {scale} is outside (0, {max}]",
+        "Масштаб временной сетки {scale} вне (0, {max}]";
+    adsr_negative(value: &str) =>
+        "ADSR shares cannot be negative: \"{value}\"",
+        "Доли ADSR не могут быть отрицательными: «{value}»";
+    adsr_sustain(level: f32) =>
+        "The sustain level must be between 0 and 1: {level}",
+        "Уровень удержания должен быть от 0 до 1: {level}";
+    bursts_negative(value: &str) =>
+        "Burst durations cannot be negative: \"{value}\"",
+        "Длительности пачек не могут быть отрицательными: «{value}»";
+    burst_period(on_ms: f32, period_ms: f32) =>
+        "A {on_ms} ms burst must fit into the {period_ms} ms period",
+        "Пачка {on_ms} мс должна укладываться в период {period_ms} мс";
+    envelope_line(number: usize, line: &str) =>
+        "Line {number}: expected \"time level\", got \"{line}\"",
+        "Строка {number}: ожидается «время уровень», получено «{line}»";
+    mix_unknown_effect(effect: &str, known: &str) =>
+        "Unknown effect \"{effect}\", expected one of: {known}",
+        "Неизвестный эффект «{effect}», ожидается один из: {known}";
+    mix_share(what: &str, value: &str) =>
+        "The {what} share must be 0–1: \"{value}\"",
+        "Доля {what} должна быть 0–1: «{value}»";
+    noise_band_invalid(low_hz: f32, high_hz: f32) =>
+        "The noise band must satisfy 0 ≤ low < high, got {low_hz}–{high_hz} Hz",
+        "Полоса шума должна быть 0 ≤ нижняя < верхняя, получено {low_hz}–{high_hz} Гц";
+    impulse_overlap(overlap: f32, max: f32) =>
+        "Impulses overlap {overlap:.0} times on average, at most {max:.0} is allowed: lower the rate or the duration",
+        "Импульсы перекрываются в среднем {overlap:.0} раз, допустимо до {max:.0}: уменьшите частоту или длительность";
+    tap_delay_negative(delay_ms: f32) =>
+        "A path delay cannot be negative: {delay_ms}",
+        "Задержка пути не может быть отрицательной: {delay_ms}";
+    dropout_duration(duration_ms: f32) =>
+        "The dropout duration must be positive: {duration_ms}",
+        "Длительность выпадения должна быть положительной: {duration_ms}";
+    carrier_frequency(freq_hz: f32) =>
+        "The carrier frequency must be positive: {freq_hz}",
+        "Частота несущей должна быть положительной: {freq_hz}";
+    carrier_duty(duty: f32) =>
+        "The duty cycle must be between 0 and 1: {duty}",
+        "Скважность должна быть от 0 до 1: {duty}";
+    carrier_period(period_ms: f32) =>
+        "The keying period must be positive: {period_ms}",
+        "Период манипуляции должен быть положительным: {period_ms}";
+    telemetry_too_long(len: usize, capacity: usize) =>
+        "Telemetry does not fit into the strip: {len} bytes with a capacity of {capacity}",
+        "Телеметрия не помещается в полосу: {len} байт при ёмкости {capacity}";
+    telemetry_bad_length(len: usize) =>
+        "The telemetry strip is damaged: invalid length {len}",
+        "Полоса телеметрии повреждена: неверная длина {len}";
+    telemetry_record_length(len: usize, expected: usize) =>
+        "Invalid telemetry record length: {len} bytes instead of {expected}",
+        "Неверная длина записи телеметрии: {len} байт вместо {expected}";
+    location_out_of_range(lat: f64, lon: f64) =>
+        "Coordinates {lat:.5}, {lon:.5} are outside ±90° latitude and ±180° longitude",
+        "Координаты {lat:.5}, {lon:.5} вне ±90° широты и ±180° долготы";
+    location_bad_grid(grid: &str) =>
+        "\"{grid}\" is not a 4 or 6 character QTH locator",
+        "«{grid}» — не QTH-локатор из 4 или 6 знаков";
+    location_bad_degrees(value: &str) =>
+        "\"{value}\" is not a number of degrees",
+        "«{value}» — не число градусов";
+}
+
+// ── Потоковая обработка ──────────────────────────────────────
+messages! {
+    streaming_rate(rate: u32) =>
+        "Streaming runs only at the encoder rate of {rate} Hz",
+        "Потоковая обработка идёт только на частоте кодера {rate} Гц";
+    streaming_unsupported(effect: &str) =>
+        "Effect \"{effect}\" does not support streaming",
+        "Эффект «{effect}» не поддерживает потоковую обработку";
+    streaming_gaussian_only(kind: &str) =>
+        "Streaming supports only Gaussian noise, not {kind}",
+        "Потоковая обработка поддерживает только гауссов шум, не {kind}";
+    streaming_tap_phase(phase_deg: f32) =>
+        "Streaming does not support a path phase shift: {phase_deg}°",
+        "Потоковая обработка не поддерживает сдвиг фазы пути: {phase_deg}°";
+    streaming_repeat(repeat: f32) =>
+        "Streaming supports only repeat 1, not {repeat}",
+        "Потоковая обработка поддерживает только повторение 1, не {repeat}";
+    effect_corrupted(event: &str) =>
+        "The effect produced a corrupted signal: {event}",
+        "Эффект выдал испорченный сигнал: {event}";
+    state_mismatch(effect: &str, error: &str) =>
+        "The state of effect \"{effect}\" does not fit: {error}",
+        "Состояние эффекта «{effect}» не подходит: {error}";
+    checkpoint_stages(saved: usize, chain: usize) =>
+        "The checkpoint was saved for {saved} effects, but the chain has {chain}",
+        "Контрольная точка сохранена для {saved} эффектов, а в цепочке их {chain}";
+    checkpoint_mix_delay(effect: &str, saved: usize, needed: usize) =>
+        "The mix delay of effect \"{effect}\" in the checkpoint is {saved} samples, {needed} are needed",
+        "Задержка смешивания эффекта «{effect}» в контрольной точке — {saved} сэмплов, а нужна {needed}";
+    noise_state_stages(saved: usize, stages: usize) =>
+        "The noise shaping state is for {saved} stages, but there are {stages}",
+        "Состояние окраски шума для {saved} звеньев, а звеньев {stages}";
+    filter_state_stages(saved: usize, stages: usize) =>
+        "The filter state is for {saved} stages, but there are {stages}",
+        "Состояние фильтра для {saved} звеньев, а звеньев {stages}";
+    fading_state_points(saved: usize, needed: usize) =>
+        "The fading envelope has {saved} points, {needed} are needed",
+        "Огибающая замираний из {saved} точек, а нужна из {needed}";
+    multipath_state_samples(saved: usize, needed: usize) =>
+        "The multipath history has {saved} samples, {needed} are needed",
+        "История многолучёвости из {saved} сэмплов, а нужна из {needed}";
+}
+
+// ── Сценарии ─────────────────────────────────────────────────
+texts! {
+    file_script => "script", "сценарий";
+    script_type_number => "number", "число";
+    script_type_string => "string", "строка";
+    script_type_array => "array", "массив";
+    script_type_map => "map", "словарь";
+    script_end_of_file => "end of file", "конец файла";
+    script_unclosed_comment => "unclosed comment /*", "незакрытый комментарий /*";
+    script_unclosed_string => "unclosed string", "незакрытая строка";
+    script_unclosed_block => "unclosed block {", "незакрытый блок {";
+    script_expected_in => "expected \"in\"", "ожидалось «in»";
+    script_step_not_positive => "the step must be positive", "шаг должен быть положительным";
+    script_unknown_function => "no such function", "нет такой функции";
+}
+
+messages! {
+    script_error(line: usize, reason: &str) => "line {line}: {reason}", "строка {line}: {reason}";
+    script_bad_number(text: &str) => "invalid number \"{text}\"", "неверное число «{text}»";
+    script_unexpected_char(c: char) => "unexpected character \"{c}\"", "неожиданный символ «{c}»";
+    script_token_number(n: f64) => "number {n}", "число {n}";
+    script_token_string(text: &str) => "string \"{text}\"", "строка \"{text}\"";
+    script_token_quoted(text: &str) => "\"{text}\"", "«{text}»";
+    script_expected_symbol(symbol: &str, found: &str) =>
+        "expected \"{symbol}\", found {found}",
+        "ожидалось «{symbol}», а найдено {found}";
+    script_expected_name(found: &str) => "expected a name, found {found}", "ожидалось имя, а найдено {found}";
+    script_expected_key(found: &str) =>
+        "expected a map key, found {found}",
+        "ожидался ключ словаря, а найдено {found}";
+    script_expected_expression(found: &str) =>
+        "expected an expression, found {found}",
+        "ожидалось выражение, а найдено {found}";
+    script_undeclared(name: &str) => "variable \"{name}\" is not declared", "переменная «{name}» не объявлена";
+    script_condition_type(type_name: &str) =>
+        "the condition must be bool, not {type_name}",
+        "условие должно быть bool, а не {type_name}";
+    script_for_type(type_name: &str) =>
+        "for iterates over an array or a map, not {type_name}",
+        "for обходит массив или словарь, а не {type_name}";
+    script_unary_type(op: &str, type_name: &str) =>
+        "\"{op}\" does not apply to {type_name}",
+        "«{op}» не применим к {type_name}";
+    script_binary_type(op: &str, left: &str, right: &str) =>
+        "\"{op}\" does not apply to {left} and {right}",
+        "«{op}» не применим к {left} и {right}";
+    script_missing_key(key: &str) => "the map has no key \"{key}\"", "в словаре нет ключа «{key}»";
+    script_no_field(type_name: &str, field: &str) =>
+        "{type_name} has no field \"{field}\"",
+        "у {type_name} нет поля «{field}»";
+    script_index_outside(index: f64) => "index {index} is outside the array", "индекс {index} за пределами массива";
+    script_index_type(target: &str, index: &str) =>
+        "{target} cannot be indexed by a value of type {index}",
+        "{target} не индексируется значением типа {index}";
+    script_expected_bool(type_name: &str) => "expected bool, not {type_name}", "ожидалось bool, а не {type_name}";
+    script_expected_number(type_name: &str) =>
+        "expected a number, not {type_name}",
+        "ожидалось число, а не {type_name}";
+    script_arity(expected: usize, given: usize) =>
+        "expected arguments: {expected}, given: {given}",
+        "ожидается аргументов: {expected}, передано: {given}";
+    script_argument_number(index: usize, type_name: &str) =>
+        "argument {index} must be a number, not {type_name}",
+        "аргумент {index} должен быть числом, а не {type_name}";
+    script_argument_string(index: usize, type_name: &str) =>
+        "argument {index} must be a string, not {type_name}",
+        "аргумент {index} должен быть строкой, а не {type_name}";
+    script_no_length(type_name: &str) => "{type_name} has no length", "нет длины у {type_name}";
+    script_unknown_stage(stage: &str, known: &str) =>
+        "unknown stage \"{stage}\", available: {known}",
+        "неизвестная ступень «{stage}», есть: {known}";
+    script_expected_stages(type_name: &str) =>
+        "expected an array of stage names, not {type_name}",
+        "ожидается массив имён ступеней, а не {type_name}";
+    script_range_too_long(max: usize) =>
+        "the range is longer than {max} elements",
+        "диапазон длиннее {max} элементов";
+}
+
+// ── C-интерфейс ──────────────────────────────────────────────
+texts! {
+    ffi_panic => "panic", "паника";
+    ffi_null_input => "input is NULL", "input равен NULL";
+    ffi_image_failed => "Could not decode the image", "Не удалось декодировать изображение";
+    ffi_png_failed => "Could not encode the PNG", "Не удалось закодировать PNG";
+}
+
+messages! {
+    ffi_null_samples(len: usize) => "data is NULL, but len is {len}", "data равен NULL, а len — {len}";
+    ffi_image_span(start: usize, end: usize, len: usize) =>
+        "image span {start}..{end} is outside the {len} samples",
+        "тело изображения {start}..{end} за пределами {len} сэмплов";
+}
+
+// ── Надписи ──────────────────────────────────────────────────
+messages! {
+    overlay_bad_color(value: &str) =>
+        "Expected a color #rrggbb or r,g,b, got \"{value}\"",
+        "Ожидается цвет #rrggbb или r,g,b, получено «{value}»";
+    overlay_bad_size(size: f32, max: f32) =>
+        "The overlay line height {size} is outside 1–{max} pixels",
+        "Высота строки надписи {size} вне 1–{max} пикселей";
+    overlay_font_read_failed(path: &str) => "Could not read the font {path}", "Не удалось прочитать шрифт {path}";
+    overlay_not_font(path: &str) => "{path} is not a TTF/OTF font", "{path} — не шрифт TTF/OTF";
+}
+
+// ── Вывод CLI ────────────────────────────────────────────────
+texts! {
+    cli_playing => "Playing...", "Воспроизведение...";
+    cli_channel_passthrough => "no effects, passthrough", "без эффектов, напрямую";
+    cli_serve_stdin => "Waiting for JSON-RPC requests on stdin, one per line", "Жду запросы JSON-RPC на stdin, по одному на строку";
+    serve_no_method => "Request has no method", "В запросе нет метода";
+    serve_no_image => "The job has no image", "Не указано изображение задания";
+    serve_no_processors => "At least one processor is needed", "Нужен хотя бы один процессор";
+    serve_no_connections => "At least one connection is needed", "Нужно хотя бы одно соединение";
+    serve_send_failed => "Could not send the reply", "Не удалось отправить ответ";
+    serve_read_failed => "Could not read the request", "Не удалось прочитать запрос";
+    serve_png_failed => "Could not encode the PNG", "Не удалось закодировать PNG";
+    cli_vis_missing => "VIS not read", "VIS не прочитан";
+    cli_signals_match => "Signals match", "Сигналы совпадают";
+    cli_conforms => "Conforms to the specification", "Соответствует спецификации";
+    watermark_no_cell =>
+        "The watermark cell side must be greater than zero",
+        "Сторона клетки метки должна быть больше нуля";
+    import_nothing =>
+        "The file has no mode, clock calibration or frequency offset",
+        "В файле нет ни режима, ни калибровки часов, ни расстройки";
+    watermark_bad_strength =>
+        "The watermark strength must be from 0 to 255 levels",
+        "Сила метки должна быть от 0 до 255 уровней";
+    curve_no_noise =>
+        "A curve needs a noise level sweep above zero",
+        "Для кривой нужен перебор уровня шума больше нуля";
+    curve_none => "No curves to chart", "Нет кривых для графика";
+    ensemble_no_runs =>
+        "An ensemble needs at least one run",
+        "В ансамбле должен быть хотя бы один прогон";
+    curve_chart_failed => "Could not draw the curve chart", "Не удалось нарисовать график кривых";
+    curve_bad_buffer => "invalid buffer size", "неверный размер буфера";
+}
+
+messages! {
+    cli_sanitized(event: &str) =>
+        "Repaired a corrupted signal after {event}",
+        "Исправлен испорченный сигнал после {event}";
+    cli_afc(first: f32, last: f32, min: f32, max: f32) =>
+        "AFC: correction {first:+.1} → {last:+.1} Hz (from {min:+.1} to {max:+.1} Hz)",
+        "АПЧ: поправка {first:+.1} → {last:+.1} Гц (от {min:+.1} до {max:+.1} Гц)";
+    cli_concealed(lines: usize) => "Concealed lines: {lines}", "Замаскировано строк: {lines}";
+    cli_telemetry(telemetry: &str) => "Telemetry: {telemetry}", "Телеметрия: {telemetry}";
+    cli_telemetry_failed(error: &str) => "Telemetry not read: {error}", "Телеметрия не прочитана: {error}";
+    cli_residual(report: &str) => "Residual: {report}", "Остаток: {report}";
+    cli_golden_null(report: &str) => "Encoder against the golden file: {report}", "Кодер против эталона: {report}";
+    cli_nonconforming(failures: usize) =>
+        "Does not conform to the specification: {failures} checks",
+        "Не соответствует спецификации: проверок {failures}";
+    cli_done(output: &str) => "Done: {output}", "Готово: {output}";
+    cli_bundle(dir: &str) => "Run saved to {dir}", "Прогон сохранён в {dir}";
+    cli_packed(files: usize, archive: &str) =>
+        "Packed {files} files into {archive}",
+        "Файлов упаковано: {files}, архив {archive}";
+    watermark_bad_bits(max_bits: usize) =>
+        "The watermark must have 1 to {max_bits} bits: at least one cell per bit",
+        "В метке должно быть от 1 до {max_bits} бит: хотя бы по клетке на бит";
+    watermark_describe(robustness: f64, recovered: usize, bits: usize, correlation: f64) =>
+        "watermark robustness {robustness:.2}: {recovered}/{bits} bits recovered, correlation {correlation:.2}",
+        "стойкость метки {robustness:.2}: прочитано бит {recovered}/{bits}, корреляция {correlation:.2}";
+    ensemble_describe(runs: usize, run_psnr: f64, combined_psnr: f64, gain_db: f64, std: f64) =>
+        "ensemble of {runs} runs: PSNR {run_psnr:.2} dB per run, {combined_psnr:.2} dB averaged ({gain_db:+.2} dB), per-pixel spread {std:.1} levels",
+        "ансамбль из {runs} прогонов: PSNR {run_psnr:.2} дБ на прогон, {combined_psnr:.2} дБ у среднего ({gain_db:+.2} дБ), разброс пикселя {std:.1} уровня";
+    labels_mask(effect: &str, coverage: f32, lines: usize) =>
+        "{effect}: {coverage:.1}% of pixel values, {lines} lines",
+        "{effect}: {coverage:.1}% значений пикселей, строк {lines}";
+    import_mode(mode: &str) => "mode {mode}", "режим {mode}";
+    import_mode_vis(mode: &str, vis: u8) => "mode {mode} (VIS {vis})", "режим {mode} (VIS {vis})";
+    import_rx_slant(ppm: f64) => "receive slant {ppm:+.1} ppm", "наклон приёма {ppm:+.1} ppm";
+    import_tx_slant(ppm: f64) => "transmit slant {ppm:+.1} ppm", "наклон передачи {ppm:+.1} ppm";
+    import_offset(hz: f32) => "frequency offset {hz:+.1} Hz", "расстройка {hz:+.1} Гц";
+    import_mode_unsupported(mode: &str) =>
+        "mode {mode} is not supported, the preset stays on Martin M1",
+        "режим {mode} не поддерживается, пресет остаётся на Martin M1";
+    import_native_decoder() =>
+        "decoder switched to native: only it takes a manual slant",
+        "декодер переключён на встроенный: ручной наклон понимает только он";
+    import_native_encoder() =>
+        "encoder switched to native: only it scales the line timing",
+        "кодер переключён на встроенный: масштаб сетки строки понимает только он";
+    import_ignored(keys: usize) =>
+        "unrecognized keys skipped: {keys}",
+        "пропущено незнакомых ключей: {keys}";
+    import_read_failed(path: &str) =>
+        "Could not read the settings {path}",
+        "Не удалось прочитать настройки {path}";
+    import_bad_vis(value: &str) => "VIS code {value} is outside 0–127", "Код VIS {value} вне 0–127";
+    import_bad_value(key: &str, value: &str) =>
+        "Invalid value of {key}: «{value}»",
+        "Неверное значение {key}: «{value}»";
+    import_clock_too_far(clock: f64, nominal: f64) =>
+        "Sound card rate {clock} Hz is too far from the nominal {nominal} Hz",
+        "Частота карты {clock} Гц слишком далека от номинальной {nominal} Гц";
+    cli_imported(settings: &str, preset: &str) =>
+        "Imported {settings}; preset written to {preset}",
+        "Импортировано: {settings}; пресет записан в {preset}";
+    curve_fit(mid: f64, width: f64, low: f64, high: f64) =>
+        "threshold {mid:.1} dB, width {width:.1} dB, SSIM {low:.2}–{high:.2}",
+        "порог {mid:.1} дБ, ширина {width:.1} дБ, SSIM {low:.2}–{high:.2}";
+    curve_no_fit() =>
+        "fewer than 3 SNR values, no curve fitted",
+        "меньше трёх значений С/Ш, кривая не подобрана";
+    curve_describe(mode: &str, level: f32, delay: u32, fit: &str) =>
+        "{mode}, retarder {level:.2} / {delay} ms: {fit}",
+        "{mode}, ретардер {level:.2} / {delay} мс: {fit}";
+    cli_curve_written(csv: &str) => "Quality-vs-SNR curves: {csv}", "Кривые качества от С/Ш: {csv}";
+    cli_vectors_start(files: usize, dir: &str) =>
+        "Writing {files} test vectors to {dir}",
+        "Тестовых сигналов: {files}, каталог {dir}";
+    cli_vectors_done(manifest: &str) =>
+        "Done, manifest: {manifest}",
+        "Готово, опись: {manifest}";
+    cli_unpacked(dir: &str, command: &str) =>
+        "Run restored to {dir}; to repeat it, run there: {command}",
+        "Прогон восстановлен в {dir}; чтобы повторить его, выполните там: {command}";
+    cli_salvaged(reason: &str, files: &str) =>
+        "No image decoded ({reason}); saved for diagnosis: {files}",
+        "Изображение не декодировано ({reason}); для разбора сохранены: {files}";
+    cli_recording(secs: u64) => "Recording {secs} s...", "Запись {secs} с...";
+    cli_channel_running(secs: u64, effects: &str) =>
+        "Live channel for {secs} s through: {effects}",
+        "Живой канал на {secs} с через: {effects}";
+    cli_serve_listening(addr: &str) =>
+        "Waiting for JSON-RPC requests on {addr}, one per line",
+        "Жду запросы JSON-RPC на {addr}, по одному на строку";
+    cli_serve_done(jobs: usize) => "Server stopped after {jobs} jobs", "Сервер остановлен, заданий: {jobs}";
+    cli_monitor_dashboard(addr: &str) =>
+        "Dashboard at http://{addr}/",
+        "Панель приёмника: http://{addr}/";
+    cli_monitor_saved(path: &str, stamp: &str) => "Saved {path} ({stamp})", "Сохранено {path} ({stamp})";
+    serve_unknown_method(method: &str) => "Unknown method {method}", "Неизвестный метод {method}";
+    serve_accept_failed(error: &str) =>
+        "Could not accept a connection: {error}",
+        "Не удалось принять соединение: {error}";
+    serve_busy(queued: usize, workers: usize) =>
+        "Server busy: all {workers} processors are working and {queued} jobs are waiting; try again later",
+        "Сервер занят: все процессоры ({workers}) работают и ждут заданий: {queued}; повторите позже";
+    serve_connections_full(max: usize) =>
+        "Server busy: all {max} connections are open; try again later",
+        "Сервер занят: открыты все соединения ({max}); повторите позже";
+    serve_request_too_large(limit: usize) =>
+        "Request is longer than {limit} bytes",
+        "Запрос длиннее {limit} байт";
+    serve_image_too_large(width: u32, height: u32, max_pixels: u64) =>
+        "Image {width}×{height} is larger than the server allows ({max_pixels} pixels)",
+        "Изображение {width}×{height} больше, чем принимает сервер ({max_pixels} пикселей)";
+    serve_sample_rate_too_high(rate: u32, max_rate: u32) =>
+        "Sample rate {rate} Hz is higher than the server allows ({max_rate} Hz)",
+        "Частота дискретизации {rate} Гц выше, чем принимает сервер ({max_rate} Гц)";
+    serve_signal_too_long(samples: u64, max_samples: u64) =>
+        "The transmission would take {samples} samples, more than the server allows ({max_samples})",
+        "Передача заняла бы {samples} сэмплов — больше, чем принимает сервер ({max_samples})";
+    serve_root_failed(root: &str) =>
+        "Could not open the job file root {root}",
+        "Не удалось открыть каталог файлов заданий {root}";
+    serve_open_failed(path: &str) => "Could not open {path}", "Не удалось открыть {path}";
+    serve_bind_failed(addr: &str) => "Could not open port {addr}", "Не удалось открыть порт {addr}";
+    serve_decode_failed(name: &str) => "Could not decode {name}", "Не смог декодировать {name}";
+    serve_bad_base64(c: char) =>
+        "Invalid base64 character «{c}»",
+        "Недопустимый символ base64: «{c}»";
+    serve_path_refused(field: &str) =>
+        "Paths are not accepted over the network: send {field}_base64 (or return_image instead of output), or start the server with --root",
+        "По сети пути не принимаются: пришлите {field}_base64 (или return_image вместо output) либо запустите сервер с --root";
+    serve_param_path_refused(field: &str) =>
+        "Paths in {field} are not accepted over the network: start the server with --root",
+        "Путь в {field} по сети не принимается: запустите сервер с --root";
+    serve_path_outside(path: &str) =>
+        "Path {path} leaves the server's file root: only relative paths without .. are accepted",
+        "Путь {path} выходит за каталог файлов сервера: принимаются только относительные пути без ..";
+    output_stream_failed(error: &str) => "Output stream error: {error}", "Ошибка потока вывода: {error}";
+    input_stream_failed(error: &str) => "Input stream error: {error}", "Ошибка потока ввода: {error}";
+    live_checkpoint(path: &str, secs: f32) =>
+        "Checkpoint {path} saved at {secs:.1} s",
+        "Контрольная точка {path} сохранена на {secs:.1} с";
+    live_resumed(path: &str, secs: f32) =>
+        "Resuming from checkpoint {path} at {secs:.1} s",
+        "Продолжение с контрольной точки {path} с {secs:.1} с";
+    thread_priority_unsupported(priority: &str) =>
+        "This system can't lower the priority of single threads, {priority} is ignored",
+        "Система не умеет менять приоритет отдельных потоков, {priority} не действует";
+    monitor_started(source: &str, rate: u32) =>
+        "Receiving from {source} at {rate} Hz",
+        "Приём с {source} на {rate} Гц";
+    monitor_image(lines: u32, vis: &str, stamp: &str) =>
+        "Image received: {lines} lines, {vis}, {stamp}",
+        "Принято изображение: строк {lines}, {vis}, {stamp}";
+    monitor_location(location: &str) =>
+        "Receiver location: {location}",
+        "Место приёмника: {location}";
+    monitor_lines(lines: u32) => "{lines} lines", "строк {lines}";
+    squelch_describe(open_db: f32, close_db: f32, min_level_db: f32) =>
+        "Squelch: opens at leader share {open_db:.0} dB, closes below {close_db:.0} dB, signal from {min_level_db:.0} dBFS",
+        "Шумоподавитель: открывается при доле лидер-тона {open_db:.0} дБ, закрывается ниже {close_db:.0} дБ, сигнал от {min_level_db:.0} дБFS";
+    squelch_opened(leader_db: f32) =>
+        "Squelch opened: leader share {leader_db:.1} dB",
+        "Шумоподавитель открыт: доля лидер-тона {leader_db:.1} дБ";
+    monitor_metadata(fields: &str) =>
+        "Metadata received: {fields}",
+        "Приняты метаданные: {fields}";
+    webhook_sent(host: &str) => "Image sent to {host}", "Изображение отправлено на {host}";
+    webhook_retry(host: &str, attempt: u32, error: &str) =>
+        "Sending to {host} failed (attempt {attempt}): {error}; retrying",
+        "Не удалось отправить на {host} (попытка {attempt}): {error}; повторяю";
+    webhook_send_failed(host: &str) =>
+        "Could not send the image to {host}",
+        "Не удалось отправить изображение на {host}";
+    webhook_bad_url(error: &str) => "Invalid address: {error}", "Неверный адрес: {error}";
+    webhook_bad_scheme(scheme: &str) =>
+        "An http or https address is required, not {scheme}",
+        "Нужен адрес http или https, а не {scheme}";
+    monitor_stopped(secs: f32) => "Receiver stopped after {secs:.0} s", "Приёмник остановлен через {secs:.0} с";
+    live_report(secs: f32, latency_ms: f32, dropped: usize, underruns: usize, level: &str) =>
+        "{secs:.1} s processed, latency {latency_ms:.0} ms, dropped {dropped} input samples, {underruns} silent output samples, output {level}",
+        "обработано {secs:.1} с, задержка {latency_ms:.0} мс, выброшено сэмплов входа: {dropped}, тишины на выходе: {underruns}, выход {level}";
+    cli_quality(report: &str) => "Quality: {report}", "Качество: {report}";
+    cli_quality_registered(registration: &str, report: &str) =>
+        "Quality after registration ({registration}): {report}",
+        "Качество после совмещения ({registration}): {report}";
+    cli_confidence(percent: f32) => "confidence {percent:.0}%", "уверенность {percent:.0}%";
+    cli_transmission(number: usize, at_s: f64, details: &str) =>
+        "Transmission {number} ({at_s:.1} s): {details}",
+        "Передача {number} ({at_s:.1} с): {details}";
+    cli_transmission_failed(number: usize, at_s: f64, error: &str) =>
+        "Transmission {number} ({at_s:.1} s): error: {error}",
+        "Передача {number} ({at_s:.1} с): ошибка: {error}";
+    cli_decoded_transmissions(decoded: usize, total: usize, secs: f64) =>
+        "Decoded {decoded} of {total} transmissions in {secs:.1} s",
+        "Декодировано {decoded} из {total} передач за {secs:.1} с";
+    cli_golden_skipped(dir: &str) =>
+        "Golden file not written to {dir}: the signal does not conform to the specification",
+        "Эталон в {dir} не записан: сигнал не соответствует спецификации";
+    cli_golden_written(path: &str) => "Golden file written: {path}", "Эталон записан: {path}";
+    cli_golden_mismatch(path: &str) =>
+        "✗ Encoder output differs from the golden file {path}",
+        "✗ Вывод кодера отличается от эталона {path}";
+    cli_batch_start(files: usize, threads: usize) =>
+        "Files: {files}, threads: {threads}",
+        "Файлов: {files}, потоков: {threads}";
+    cli_batch_done(done: usize, total: usize, secs: f32, failed: usize) =>
+        "Done: {done} of {total} in {secs:.1} s, errors: {failed}",
+        "Готово: {done} из {total} за {secs:.1} с, ошибок: {failed}";
+    cli_sweep_start(points: usize, threads: usize) =>
+        "Points: {points}, threads: {threads}",
+        "Точек: {points}, потоков: {threads}";
+    cli_sweep_done(secs: f32, manifest: &str) =>
+        "Done in {secs:.1} s, manifest: {manifest}",
+        "Готово за {secs:.1} с, опись: {manifest}";
+    cli_sequence_start(frames: usize, threads: usize) =>
+        "Frames: {frames}, threads: {threads}",
+        "Кадров: {frames}, потоков: {threads}";
+    cli_sequence_frame(frame: usize, noise: u8) =>
+        "frame {frame}: noise {noise}",
+        "кадр {frame}: шум {noise}";
+    cli_sequence_frame_retarder(frame: usize, noise: u8, level: f32, delay_ms: u32) =>
+        "frame {frame}: noise {noise}, retarder {level:.2}, delay {delay_ms} ms",
+        "кадр {frame}: шум {noise}, ретардер {level:.2}, задержка {delay_ms} мс";
+    cli_sequence_done(secs: f32, output: &str) =>
+        "Done in {secs:.1} s: {output}",
+        "Готово за {secs:.1} с: {output}";
+    cli_fuzz_progress(done: usize, total: usize, target: &str, seed: u64) =>
+        "{done}/{total} — {target}, seed {seed}",
+        "{done}/{total} — {target}, зерно {seed}";
+    cli_fuzz_worst_difference(difference: &str) =>
+        "Largest decoder difference, {difference}",
+        "Наибольшее расхождение декодеров, {difference}";
+    cli_fuzz_summary(cases: usize, rejected: usize, failures: usize) =>
+        "Cases: {cases}, rejected with an error: {rejected}, violations: {failures}",
+        "Случаев: {cases}, отвергнуто с ошибкой: {rejected}, нарушений: {failures}";
+}
+
+// ── Ошибки CLI ───────────────────────────────────────────────
+texts! {
+    cli_no_input => "No source image given", "Не указана исходная картинка";
+    cli_source_image => "the source image", "исходное изображение";
+    cli_no_transmissions => "No transmissions found in the recording", "В записи не найдено ни одной передачи";
+}
+
+messages! {
+    cli_connect_failed(addr: &str) => "Could not connect to {addr}", "Не удалось подключиться к {addr}";
+    cli_snapshot_save_failed(path: &str) =>
+        "Could not save the snapshot {path}",
+        "Не удалось сохранить снимок {path}";
+    cli_transmissions_failed(failed: usize) =>
+        "Transmissions not decoded: {failed}",
+        "Не декодировано передач: {failed}";
+    cli_rates_differ(reference: u32, candidate: u32) =>
+        "Sample rates differ: {reference} Hz and {candidate} Hz",
+        "Частоты дискретизации различаются: {reference} Гц и {candidate} Гц";
+    cli_signals_differ(depth_db: f64, min_db: f64) =>
+        "Signals differ: the null depth {depth_db:.1} dB is below {min_db:.1} dB",
+        "Сигналы различаются: подавление {depth_db:.1} дБ меньше {min_db:.1} дБ";
+    cli_encoder_target(name: &str) => "encoder {name}", "кодер {name}";
+    cli_checks_failed(failed: usize) => "Checks failed: {failed}", "Не пройдено проверок: {failed}";
+    cli_not_conforming(what: &str, mode: &str) =>
+        "{what} does not conform to the {mode} specification",
+        "{what} не соответствует спецификации {mode}";
+    cli_fuzz_failed(failures: usize) => "Violations: {failures}", "Нарушений: {failures}";
+    cli_no_images(pattern: &str) =>
+        "No images found for \"{pattern}\"",
+        "Не найдено ни одного изображения по «{pattern}»";
+    cli_files_failed(failed: usize) => "Files not processed: {failed}", "Не обработано файлов: {failed}";
+    cli_no_frames(input: &str) =>
+        "The input \"{input}\" has no frames",
+        "Во входе «{input}» нет ни одного кадра";
+    cli_bad_pattern(pattern: &str) => "Invalid pattern \"{pattern}\"", "Неверный шаблон «{pattern}»";
+}
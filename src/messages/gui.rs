@@ -0,0 +1,657 @@
+use super::{Language, language};
+
+texts! {
+    // ── Вкладки и журнал ────────────────────────────────────
+    tab_parameters => "⚙ Parameters", "⚙ Параметры";
+    tab_images => "🖼 Images", "🖼 Изображения";
+    tab_waterfall => "🌊 Waterfall", "🌊 Водопад";
+    tab_metrics => "📊 Metrics", "📊 Метрики";
+    tab_log => "📜 Log", "📜 Журнал";
+    tab_receive => "📡 Receive", "📡 Приём";
+    log_errors => "Errors", "Ошибки";
+    log_warnings => "Warnings", "Предупреждения";
+    log_info => "Info", "Сведения";
+    log_debug => "Debug", "Отладка";
+    // ── Загрузка изображений ────────────────────────────────
+    log_clipboard_corrupted =>
+        "The clipboard image is corrupted",
+        "Изображение в буфере обмена повреждено";
+    log_clipboard_empty => "No image in the clipboard", "В буфере обмена нет изображения";
+    main_image => "Main image", "Основное изображение";
+    // ── Обработка ───────────────────────────────────────────
+    lock_hover => "Lock against randomization", "Закрепить для случайного подбора";
+    log_processing_started => "Processing started (320×256)", "Обработка запущена (320×256)";
+    log_worker_unavailable => "The processing thread is unavailable", "Поток обработки недоступен";
+    log_encoder_cached => " (encoder from cache)", " (кодер из кэша)";
+    // ── Витрина ─────────────────────────────────────────────
+    soak_folder => "📁 Image folder…", "📁 Папка с изображениями…";
+    soak_folder_title => "Image folder for the showcase", "Папка изображений для витрины";
+    soak_dwell => "Show result (s)", "Показ результата (с)";
+    soak_randomize =>
+        "Random parameters for each image",
+        "Случайные параметры для каждого изображения";
+    soak_memory_limit => "Memory limit (MB):", "Предел памяти (МБ):";
+    soak_stop => "⏹ Stop showcase", "⏹ Остановить витрину";
+    soak_start => "▶ Start showcase", "▶ Запустить витрину";
+    // ── История и звук ──────────────────────────────────────
+    history_label =>
+        "History (click to restore parameters and result):",
+        "История (клик — вернуть параметры и результат):";
+    history_previous => "Previous result (Ctrl+Z)", "Предыдущий результат (Ctrl+Z)";
+    history_next => "Next result (Ctrl+Shift+Z)", "Следующий результат (Ctrl+Shift+Z)";
+    history_keep => "keep: ", "хранить: ";
+    history_size_hover =>
+        "How many recent results to keep in the history",
+        "Сколько последних результатов держать в истории";
+    log_no_signal =>
+        "No signal to play. Process an image first.",
+        "Нет сигнала для воспроизведения. Сначала обработайте изображение.";
+    log_capture_decoded => "Input device capture decoded", "Приём с устройства ввода декодирован";
+    default_device => "Default", "По умолчанию";
+    // ── Панели изображений ──────────────────────────────────
+    export_hover => "Right click to export", "Правый клик — экспорт";
+    save_as => "💾 Save as…", "💾 Сохранить как…";
+    copy_image => "📋 Copy image", "📋 Копировать изображение";
+    open_external => "🖼 Open in external viewer", "🖼 Открыть во внешнем просмотрщике";
+    copy_cli => "⌨ Copy parameters as a CLI command", "⌨ Копировать параметры как команду CLI";
+    wipe_hover => "Drag to move the wipe", "Тяните, чтобы сдвинуть шторку";
+    wipe => "Wipe", "Шторка";
+    noise_envelope_legend => "— noise envelope", "— огибающая шума";
+    ghost_envelope_legend => "— ghost envelope", "— огибающая призрака";
+    spectrogram_outside => "Signal header or tail", "Заголовок или хвост сигнала";
+    inspector => "Tone inspector", "Инспектор тонов";
+    inspector_hint =>
+        "Click the waveform or spectrogram to see what that moment of the signal encodes",
+        "Щёлкните по осциллограмме или спектрограмме, чтобы увидеть, что кодирует этот момент сигнала";
+    inspector_outside =>
+        "No encoder tones here: the signal tail after effects",
+        "Тонов кодера здесь нет: хвост сигнала после эффектов";
+    inspector_rsstv =>
+        "Tones are known only for the built-in encoder",
+        "Тоны известны только у встроенного кодера";
+    // ── Прогоны, огибающие и пресеты ────────────────────────
+    log_no_run =>
+        "No run to save. Process an image first.",
+        "Нет прогона для сохранения. Сначала обработайте изображение.";
+    runs_folder_title => "Folder for run directories", "Каталог для папок прогонов";
+    adsr_attack => "Attack", "Атака";
+    adsr_decay => "Decay", "Спад";
+    adsr_sustain => "Sustain", "Удержание";
+    adsr_release => "Release", "Затухание";
+    burst_on => "Burst", "Пачка";
+    burst_period => "Period", "Период";
+    burst_jitter => "Jitter", "Разброс";
+    burst_ramp => "Ramp", "Фронт";
+    suffix_ms => " ms", " мс";
+    filter_envelope => "Envelope", "Огибающая";
+    load_envelope_title => "Load a point envelope", "Загрузить огибающую по точкам";
+    filter_timeline => "Timeline", "Сценарий";
+    load_timeline_title => "Load an event timeline", "Загрузить сценарий событий";
+    filter_preset => "Preset", "Пресет";
+    save_preset_title => "Save preset", "Сохранить пресет";
+    load_preset_title => "Load preset", "Загрузить пресет";
+    log_no_result => "No result to save", "Нет результата для сохранения";
+    // ── Вкладка «Параметры» ─────────────────────────────────
+    heading => "SSTV parameters", "Параметры SSTV";
+    files => "Files:", "Файлы:";
+    main_image_label => "Main image:", "Основное изображение:";
+    main_image_hover => "The image to process", "Изображение для обработки";
+    choose => "Choose", "Выбрать";
+    filter_images => "Images", "Изображения";
+    choose_main_title => "Choose the main image", "Выберите основное изображение";
+    paste => "📋 Paste", "📋 Вставить";
+    paste_main_hover =>
+        "An image or file path from the clipboard. A file can also be dropped on the “Original” panel",
+        "Изображение или путь к файлу из буфера обмена. Файл можно и перетащить на панель «Исходное»";
+    load_path => "Load from path", "Загрузить по пути";
+    retarder_label => "Retarder:", "Ретардер:";
+    choose_retarder_title => "Choose the retarder image", "Выберите изображение ретардера";
+    paste_retarder_hover =>
+        "An image or file path from the clipboard. A file can also be dropped on the “Retarder” panel",
+        "Изображение или путь к файлу из буфера обмена. Файл можно и перетащить на панель «Ретардер»";
+    clear => "Clear", "Очистить";
+    link_envelopes => "🔗 Link noise and retarder envelopes", "🔗 Связать огибающие шума и ретардера";
+    link_envelopes_hover =>
+        "Envelope type and repeat change together",
+        "Тип огибающей и повторение меняются одновременно";
+    noise_label => "Noise:", "Шум:";
+    level => "Level", "Уровень";
+    noise_kind => "Noise type", "Вид шума";
+    noise_color => "Noise color", "Окраска шума";
+    band_low => "Lower edge (Hz)", "Нижняя граница (Гц)";
+    band_high => "Upper edge (Hz)", "Верхняя граница (Гц)";
+    impulse_rate => "Impulses per second", "Импульсов в секунду";
+    impulse_ms => "Duration (ms)", "Длительность (мс)";
+    impulse_dist => "Impulse amplitudes", "Амплитуды импульсов";
+    noise_env => "Noise envelope", "Огибающая шума";
+    curve => "📂 Curve…", "📂 Кривая…";
+    curve_hover =>
+        "Point envelope “time level” from a file",
+        "Огибающая по точкам «время уровень» из файла";
+    repeat => "Repeat", "Повторение";
+    noise_anchor => "Noise envelope anchor", "Привязка огибающей шума";
+    ghost_source => "Ghost source", "Источник призрака";
+    ghost_wav_hover =>
+        "Ghost from a recorded WAV, e.g. debug.wav of a previous run",
+        "Призрак из записанного WAV, например debug.wav прошлого прогона";
+    ghost_wav_title => "Choose the ghost WAV", "Выберите WAV призрака";
+    retarder_env => "Retarder envelope", "Огибающая ретардера";
+    retarder_anchor => "Retarder envelope anchor", "Привязка огибающей ретардера";
+    delay_ms => "Delay (ms)", "Задержка (мс)";
+    multipath => "Multipath:", "Многолучёвость:";
+    gain => "Gain", "Усиление";
+    phase_deg => "Phase (°)", "Фаза (°)";
+    add_path => "Add path", "Добавить путь";
+    fading => "Fading:", "Замирания:";
+    depth => "Depth", "Глубина";
+    rate_hz => "Rate (Hz)", "Скорость (Гц)";
+    fade_model => "Fading model", "Модель замираний";
+    frequency_drift => "Frequency drift:", "Уход частоты:";
+    offset_hz => "Offset (Hz)", "Расстройка (Гц)";
+    drift_hz_per_s => "Drift (Hz/s)", "Дрейф (Гц/с)";
+    wobble_hz => "Wobble (Hz)", "Плавание (Гц)";
+    interference => "Interference:", "Помехи:";
+    frequency_hz => "Frequency (Hz)", "Частота (Гц)";
+    duty => "Duty cycle", "Скважность";
+    period_ms => "Period (ms)", "Период (мс)";
+    add_carrier => "Add carrier", "Добавить несущую";
+    qrm_label => "Interfering SSTV:", "Мешающая SSTV:";
+    qrm_title =>
+        "Choose the interfering transmission image",
+        "Выберите изображение мешающей передачи";
+    qrm_level => "QRM level", "Уровень QRM";
+    qrm_offset => "QRM offset (Hz)", "Сдвиг QRM (Гц)";
+    qrm_delay => "QRM delay (ms)", "Задержка QRM (мс)";
+    ssb => "SSB transceiver", "Трансивер SSB";
+    ssb_hover =>
+        "Transmitter limiter, sideband filter and ALC, receiver filter",
+        "Ограничитель, фильтр боковой полосы и АРМ передатчика, фильтр приёмника";
+    ssb_drive => "Overdrive (dB)", "Перегрузка (дБ)";
+    ssb_alc => "ALC (ms)", "АРМ (мс)";
+    ssb_output => "Transceiver output", "Выход трансивера";
+    ssb_output_hover =>
+        "IQ: the WAV holds quadratures around the suppressed carrier (stereo)",
+        "IQ: в WAV пишутся квадратуры вокруг подавленной несущей (стерео)";
+    sideband => "Sideband", "Боковая полоса";
+    carrier_leak => "Carrier leak", "Остаток несущей";
+    iq_gain => "I/Q imbalance (dB)", "Разбаланс I/Q (дБ)";
+    iq_phase => "I/Q imbalance (°)", "Разбаланс I/Q (°)";
+    blanker => "Noise blanker", "Подавитель помех";
+    blanker_hover =>
+        "Cuts amplitude spikes before the receiver filter and fills the holes",
+        "Вырезает выбросы амплитуды перед фильтром приёмника и заполняет дыры";
+    blanker_threshold => "Threshold (× RMS)", "Порог (× СКЗ)";
+    blanker_threshold_hover =>
+        "Below 2 the blanker clips the peaks of the signal itself, white the most",
+        "Ниже 2 подавитель режет пики самого сигнала, сильнее всего белый";
+    hold_ms => "Hold (ms)", "Удержание (мс)";
+    blanker_average => "Level averaging (ms)", "Усреднение уровня (мс)";
+    blanker_fill => "Fill", "Заполнение";
+    filter => "Audio filter", "Звуковой фильтр";
+    filter_hover =>
+        "Filter after the receiver: a narrow one cuts the edges of the SSTV band — black, white and sync pulses",
+        "Фильтр после приёмника: узкий режет края полосы SSTV — чёрный, белый и синхроимпульсы";
+    filter_kind => "Filter type", "Вид фильтра";
+    q_factor => "Q", "Добротность";
+    filter_stages => "Stages", "Звеньев";
+    notch => "Notch", "Режекция";
+    notch_hover =>
+        "Cuts a narrow band; inside 1100–2300 Hz it eats some brightness levels",
+        "Вырезает узкую полосу; внутри 1100–2300 Гц выедает часть яркостей";
+    notch_hz => "Notch frequency (Hz)", "Частота режекции (Гц)";
+    notch_q => "Notch Q", "Добротность режекции";
+    agc => "AGC", "АРУ";
+    agc_hover =>
+        "Brings the envelope to the target level; lifts fades together with the noise",
+        "Приводит огибающую к заданному уровню; подтягивает замирания вместе с шумом";
+    attack_ms => "Attack (ms)", "Атака (мс)";
+    release_ms => "Release (ms)", "Восстановление (мс)";
+    max_gain => "Maximum gain (dB)", "Наибольшее усиление (дБ)";
+    dropouts => "Dropouts:", "Выпадения:";
+    dropout_rate => "Random per minute", "Случайных в минуту";
+    start_ms => "Start (ms)", "Начало (мс)";
+    dropout_start_hover =>
+        "From the start of the image; negative falls in the header",
+        "От начала изображения; отрицательное — в заголовке";
+    add_dropout => "Add dropout", "Добавить выпадение";
+    dropout_kind => "Dropout type", "Вид выпадения";
+    dropout_kind_hover =>
+        "Silence to the end of the signal cuts the transmission — the decoder returns a partial image",
+        "Тишина до конца сигнала обрывает передачу — декодер отдаёт неполное изображение";
+    attenuation_db => "Attenuation (dB)", "Ослабление (дБ)";
+    timeline => "Event timeline:", "Сценарий событий:";
+    timeline_button => "📂 Timeline…", "📂 Сценарий…";
+    timeline_hover =>
+        "Effects on stretches of the transmission from an [[events]] TOML or JSON file",
+        "Эффекты на отрезках передачи из файла [[events]] TOML или JSON";
+    reset => "Reset", "Сбросить";
+    no_events => "No events", "Событий нет";
+    mix_and_output => "Mix and output:", "Смешивание и выход:";
+    wet_hover => "Share of the effect output", "Доля выхода эффекта";
+    dry_hover => "Share of the signal before the effect", "Доля сигнала до эффекта";
+    output_gain => "Output gain (dB)", "Выходное усиление (дБ)";
+    output_gain_hover =>
+        "Overall gain after all effects before the −1…1 clip: a negative value removes the overload from the sum of ghost, interference and noise. The output level is in the report",
+        "Общее усиление после всех эффектов перед ограничением −1…1: отрицательное убирает перегрузку от суммы призрака, помех и шума. Уровень выхода — в отчёте";
+    normalize => "WAV loudness", "Громкость WAV";
+    normalize_hover =>
+        "Second pass over written WAV files: one gain brings the loudness to the target, held back by the true-peak ceiling. The playback and the decoder are not affected",
+        "Второй проход по записываемым WAV: одно усиление приводит громкость к цели, не выше потолка истинного пика. Воспроизведение и декодер это не затрагивает";
+    normalize_target => "Target (dBFS / LUFS)", "Цель (дБFS / LUFS)";
+    true_peak_db => "True-peak ceiling (dBTP)", "Потолок истинного пика (дБTP)";
+    // ── Кодер, надпись и декодер ────────────────────────────
+    encoder => "Encoder:", "Кодер:";
+    encoder_backend => "Encoder implementation", "Реализация кодера";
+    header_and_vis => "Header and VIS", "Заголовок и VIS";
+    leader_ms => "Leader tone (ms)", "Лидер-тон (мс)";
+    vis_code => "VIS code", "Код VIS";
+    vis_attenuation => "VIS attenuation (dB)", "Ослабление VIS (дБ)";
+    vis_flip_bits => "Corrupted VIS bits", "Искажённых бит VIS";
+    timing_scale => "Time scale", "Масштаб времени";
+    callsign => "Callsign:", "Позывной:";
+    callsign_hover =>
+        "Identifier after the image; empty for none",
+        "Опознаватель после изображения; пустое поле — без него";
+    id_mode => "Identifier", "Опознаватель";
+    id_wpm => "CW speed (WPM)", "Скорость CW (WPM)";
+    id_tone => "CW tone (Hz)", "Тон CW (Гц)";
+    overlay => "Text overlay:", "Надпись:";
+    overlay_hover =>
+        "Lines over the image before encoding; {date} and {time} are UTC",
+        "Строки поверх изображения до кодирования; {date} и {time} — UTC";
+    metadata => "Metadata:", "Метаданные:";
+    metadata_key => "key", "ключ";
+    metadata_value => "value", "значение";
+    add_metadata => "➕ Add field", "➕ Добавить поле";
+    metadata_overlay => "Draw over the image", "Рисовать на изображении";
+    metadata_fsk => "Send as FSK after the image", "Передать FSK после изображения";
+    metadata_fsk_hover =>
+        "FSK ID carries uppercase ASCII only; the decode report shows what was received",
+        "FSK ID передаёт только заглавную латиницу; что принято, видно в отчёте декодера";
+    overlay_x_hover =>
+        "A negative value is an offset from the right edge",
+        "Отрицательное значение — отступ от правого края";
+    overlay_y_hover =>
+        "A negative value is an offset from the bottom edge",
+        "Отрицательное значение — отступ от нижнего края";
+    overlay_size => "Size (px)", "Размер (пикс.)";
+    color => "Color:", "Цвет:";
+    shadow => "Shadow", "Тень";
+    builtin_font => "built-in 5×7", "встроенный 5×7";
+    font_button => "📂 Font…", "📂 Шрифт…";
+    font_hover =>
+        "TTF or OTF; the built-in font only knows ASCII",
+        "TTF или OTF; встроенный шрифт знает только ASCII";
+    filter_font => "Font", "Шрифт";
+    font_title => "Overlay font", "Шрифт надписи";
+    decoder => "Decoder:", "Декодер:";
+    decoder_backend => "Decoder implementation", "Реализация декодера";
+    sync_threshold => "Sync threshold", "Порог синхро";
+    search_window => "Search window (ms)", "Окно поиска (мс)";
+    max_deviation => "Deviation (Hz)", "Отклонение (Гц)";
+    afc => "AFC", "АПЧ";
+    afc_hover =>
+        "Automatic frequency control: estimates and removes the receiver offset",
+        "Автоподстройка частоты: оценивает и убирает расстройку приёмника";
+    conceal => "Conceal damaged lines", "Маскировать испорченные строки";
+    conceal_hover =>
+        "Lines without a sync pulse or sharply unlike their neighbours are replaced by interpolation",
+        "Строки без синхроимпульса или резко отличающиеся от соседних заменяются интерполяцией";
+    deskew => "Automatic slant correction", "Автокоррекция наклона";
+    deskew_hover =>
+        "The line period is found from the sync pulses of the whole image, a large slant included",
+        "Период строки ищется по синхроимпульсам всего изображения, находится и большой наклон";
+    manual_slant => "Manual slant (ppm)", "Наклон вручную (ppm)";
+    manual_slant_hover =>
+        "The line period is fixed; sync pulses only refine the line phase",
+        "Период строки фиксируется, синхроимпульсы уточняют только фазу строк";
+    palette => "Palette", "Палитра";
+    palette_hover =>
+        "Reduces the received image to the memory depth of vintage scan converters",
+        "Приводит принятое изображение к разрядности памяти старинных преобразователей развёртки";
+    scan_converter => "Scan converter:", "Преобразователь развёртки:";
+    blur => "Vertical blur", "Вертикальное размытие";
+    blur_hover =>
+        "Each line is mixed with its neighbours, as when read out of the converter memory",
+        "Строка смешивается с соседними, как при выводе из памяти преобразователя";
+    scanlines => "Scanline gaps", "Промежутки строк";
+    scanlines_hover =>
+        "How much every other scan line darkens",
+        "Насколько темнеет каждая вторая строка развёртки";
+    phosphor => "Phosphor", "Люминофор";
+    tint => "Tint", "Окраска";
+    robot_1200c => "Like Robot 1200C", "Как Robot 1200C";
+    robot_1200c_hover =>
+        "18-bit palette, soft lines with gaps and the warm white of a colour CRT",
+        "Палитра 18 бит, мягкие строки с промежутками и тёплый белый цветного кинескопа";
+    fixed_seed => "Fixed seed", "Фиксированное зерно";
+    fixed_seed_hover =>
+        "Random effects give the same result on every run",
+        "Случайные эффекты дают одинаковый результат при каждом прогоне";
+    convolution => "Convolution", "Свёртка";
+    convolution_hover =>
+        "Auto picks direct convolution or FFT by the signal and kernel length",
+        "Auto выбирает прямую свёртку или БПФ по длине сигнала и ядра";
+    parallel => "Parallel effects", "Параллельные эффекты";
+    parallel_hover =>
+        "Noise and retarder are computed in chunks on all cores; the result matches a single thread",
+        "Шум и ретардер считаются кусками на всех ядрах; результат тот же, что и в одном потоке";
+    sample_rate => "Sample rate", "Частота дискретизации";
+    sample_rate_hover =>
+        "Output signal rate: WAV, playback and effect delays",
+        "Частота выходного сигнала: WAV, воспроизведение и задержки эффектов";
+    quality => "Quality", "Качество";
+    quality_hover =>
+        "Preview is fast and rough, High resamples more accurately",
+        "Preview — быстро и грубо, High — точнее передискретизация";
+    sanitize => "NaN check", "Проверка NaN";
+    sanitize_hover =>
+        "NaN and infinities after each effect: fix them naming the effect, or abort processing",
+        "NaN и бесконечности после каждого эффекта: исправлять с указанием эффекта или прерывать обработку";
+    metric_space => "Metric color space", "Пространство метрик";
+    metric_space_hover =>
+        "Where PSNR, SSIM and MSE are computed. YCbCr weighs luma errors over chroma, as Robot modes transmit it",
+        "В чём считаются PSNR, SSIM и MSE. YCbCr ставит ошибки яркости выше цветоразностей — так их передают режимы Robot";
+    language_label => "Language", "Язык";
+    language_hover =>
+        "Window labels, decoder report, advice and effect descriptions",
+        "Надписи окна, отчёт декодера, советы и описания эффектов";
+    // ── Обработка и звук ────────────────────────────────────
+    auto_process => "Auto-process (slow!)", "Автообработка (медленно!)";
+    fast_preview => "Fast preview", "Быстрый предпросмотр";
+    fast_preview_hover =>
+        "Auto-processing runs at the Preview tier, the button at the selected one",
+        "Автообработка идёт на уровне Preview, кнопка — на выбранном";
+    process_now => "🔄 Process now", "🔄 Обработать сейчас";
+    randomize => "🎲 Random parameters", "🎲 Случайные параметры";
+    randomize_hover =>
+        "Parameters locked with 🔒 do not change",
+        "Закреплённые 🔒 параметры не меняются";
+    save_preset => "💾 Save preset", "💾 Сохранить пресет";
+    save_preset_hover =>
+        "Channel parameters as TOML or JSON, without images",
+        "Параметры канала в TOML или JSON, без картинок";
+    load_preset => "📂 Load preset", "📂 Загрузить пресет";
+    soak => "🖥 Showcase", "🖥 Витрина";
+    auto_process_off =>
+        "💡 Auto-processing is off. Press '🔄 Process now' to get a result.",
+        "💡 Автообработка отключена. Нажмите '🔄 Обработать сейчас' для получения результата.";
+    queued => "⏳ Queued…", "⏳ В очереди…";
+    cancel => "⏹ Cancel", "⏹ Отменить";
+    encoder_cached => "⚡ Encoder signal taken from the cache", "⚡ Сигнал кодера взят из кэша";
+    encoder_cached_hover =>
+        "The image and encoder parameters are unchanged: only the effects and the decoder were reapplied",
+        "Картинка и параметры кодера не менялись: заново применены только эффекты и декодер";
+    sound => "Sound:", "Звук:";
+    output_device => "Output", "Вывод";
+    input_device => "Input", "Ввод";
+    refresh_devices => "Refresh devices", "Обновить устройства";
+    listen_secs => "Recording (s)", "Запись (с)";
+    play => "▶ Play", "▶ Воспроизвести";
+    listen => "🎙 Listen", "🎙 Слушать";
+    automation => "Automation", "Автоматизация";
+    record => "⏺ Record", "⏺ Записать";
+    record_hover =>
+        "Play the signal and record the slider movements into an envelope; it applies on the next full processing",
+        "Воспроизвести сигнал и записать движения ползунка в огибающую; она применится при следующей полной обработке";
+    audio_busy => "🔊 Sound device busy...", "🔊 Звуковое устройство занято...";
+    output_file => "Output file:", "Выходной файл:";
+    choose_folder => "Choose folder", "Выбрать папку";
+    choose_folder_title => "Choose a folder to save to", "Выберите папку для сохранения";
+    save_result => "💾 Save result", "💾 Сохранить результат";
+    log_no_result_yet =>
+        "No result to save. Process an image first.",
+        "Нет результата для сохранения. Сначала обработайте изображение.";
+    save_run => "📦 Save run…", "📦 Сохранить прогон…";
+    save_run_hover =>
+        "A timestamped folder: original, result, WAV before and after the channel and params.json with all parameters and metrics",
+        "Папка с отметкой времени: исходное, результат, WAV до и после канала и params.json со всеми параметрами и метриками";
+    // ── Вкладки «Изображения», «Водопад» и «Метрики» ────────
+    original => "Original", "Исходное";
+    no_image =>
+        "No image loaded — drop a file here",
+        "Изображение не загружено — перетащите файл сюда";
+    no_retarder =>
+        "No retarder loaded — drop a file here",
+        "Ретардер не загружен — перетащите файл сюда";
+    result => "Result", "Результат";
+    compare => "Compare", "Сравнение";
+    compare_diff => "Difference instead of result", "Разница вместо результата";
+    compare_diff_hover =>
+        "Heat map of the absolute error: black is a match, red and yellow a visible error, white 255",
+        "Тепловая карта абсолютной ошибки: чёрный — совпадение, красный и жёлтый — заметная ошибка, белый — 255";
+    processing => "Processing...", "Обработка...";
+    no_result => "Result not ready", "Результат не готов";
+    waveform => "Signal waveform after the effects:", "Осциллограмма сигнала после эффектов:";
+    no_signal => "No signal yet — process an image", "Сигнала ещё нет — обработайте изображение";
+    spectrogram =>
+        "Spectrogram (hover to find an image line):",
+        "Спектрограмма (наведите курсор, чтобы найти строку изображения):";
+    channel => "Channel:", "Канал:";
+    will_resize =>
+        "⚠️ The image will be scaled to 320×256 for SSTV processing",
+        "⚠️ Изображение будет сжато до 320×256 для SSTV обработки";
+    ideal_size => "✅ Ideal size for SSTV (320×256)", "✅ Идеальный размер для SSTV (320×256)";
+    sstv_mode => "SSTV mode: Martin M1 (320×256)", "SSTV режим: Martin M1 (320×256)";
+    transmission_time =>
+        "Transmission time: ~114.5 seconds",
+        "Длительность передачи: ~114.5 секунд";
+    quality_heading =>
+        "Result quality against the original:",
+        "Качество результата относительно исходного:";
+    psnr_infinite => "PSNR: ∞ (images match)", "PSNR: ∞ (изображения совпадают)";
+    ssim_hover =>
+        "1 means the image structure is fully preserved",
+        "1 — структура изображения полностью сохранена";
+    decode_summary_hover =>
+        "Sync is the share of lines with a sync pulse found; slant is the deviation of the line period from nominal; SNR is estimated from the spectrum of the received signal",
+        "Синхронизация — доля строк с найденным синхроимпульсом; наклон — отклонение периода строки от номинального; С/Ш — оценка по спектру принятого сигнала";
+    vis_hover =>
+        "The VIS code is only reported: the image is decoded as Martin M1",
+        "Код VIS только сообщается: изображение декодируется как Martin M1";
+    copy => "📋 Copy", "📋 Копировать";
+    copy_log_hover =>
+        "The shown entries to the clipboard, e.g. for a bug report",
+        "Показанные записи в буфер обмена, например для отчёта об ошибке";
+    clear_log => "🗑 Clear", "🗑 Очистить";
+    attach => "⤓ To layout", "⤓ В раскладку";
+    attach_hover => "Return the tab to the main window", "Вернуть вкладку в главное окно";
+    detach => "⧉ To window", "⧉ В окно";
+    detach_hover =>
+        "Open the tab in a separate window, e.g. on a second monitor",
+        "Открыть вкладку в отдельном окне, например на втором мониторе";
+    // ── Приём ───────────────────────────────────────────────
+    receive_device => "Sound card", "Звуковая карта";
+    receive_pcm => "Raw PCM", "Сырой PCM";
+    receive_pcm_hover =>
+        "Mono little-endian samples from a file or a named pipe, '-' for standard input, e.g. rtl_fm output",
+        "Моно-сэмплы little-endian из файла или именованного канала, '-' — стандартный ввод, например вывод rtl_fm";
+    receive_pcm_path => "File or pipe:", "Файл или канал:";
+    receive_pcm_rate => "Rate (Hz)", "Частота (Гц)";
+    receive_pcm_format => "Format", "Формат";
+    receive_location => "Location:", "Место:";
+    receive_location_hover =>
+        "Grid locator (KO85ts) or latitude,longitude in degrees; stamped with UTC time on every received image",
+        "QTH-локатор (KO85ts) или широта,долгота в градусах; вместе со временем UTC отмечается на каждом принятом изображении";
+    receive_gpsd_hover =>
+        "Take the location from the last gpsd fix at host:port",
+        "Брать место из последнего фикса gpsd по адресу host:port";
+    receive_squelch => "Squelch", "Шумоподавитель";
+    receive_squelch_hover =>
+        "Decode only after a 1900 Hz leader tone is heard: band noise stays noise",
+        "Декодировать только после лидер-тона 1900 Гц: шум диапазона остаётся шумом";
+    receive_squelch_open_hover =>
+        "Leader band share of the signal power that opens the squelch",
+        "Доля мощности сигнала в полосе лидер-тона, открывающая шумоподавитель";
+    receive_squelch_hysteresis_hover =>
+        "How far below the opening threshold the share may fall before the squelch closes",
+        "Насколько доля может упасть ниже порога открытия, прежде чем шумоподавитель закроется";
+    receive_squelch_level_hover =>
+        "Quieter signals never open the squelch",
+        "Более тихий сигнал шумоподавитель не открывает";
+    receive_squelch_vis_hover =>
+        "Drop images whose VIS was not read or failed parity",
+        "Бросать изображения, VIS которых не прочитан или не сошлась чётность";
+    receive_squelch_open => "open", "открыт";
+    receive_squelch_closed => "closed", "закрыт";
+    receive_start => "📡 Start", "📡 Начать";
+    receive_stop => "⏹ Stop", "⏹ Остановить";
+    receive_reset => "↺ Next image", "↺ Следующее изображение";
+    receive_reset_hover =>
+        "Keep the lines received so far and search for a new header",
+        "Оставить принятые строки и искать новый заголовок";
+    receive_save_audio => "💾 Save audio", "💾 Сохранить звук";
+    receive_save_audio_hover =>
+        "The last seconds of the received signal as WAV",
+        "Последние секунды принятого сигнала в WAV";
+    receive_waiting => "Waiting for a VIS header…", "Ждём заголовок VIS…";
+    receive_idle => "Receiver stopped", "Приёмник остановлен";
+    receive_history => "Received", "Принятые";
+    receive_none => "Nothing received yet", "Пока ничего не принято";
+    receive_show => "🖼 To result", "🖼 В результат";
+    receive_save => "💾 Save", "💾 Сохранить";
+}
+
+messages! {
+    log_line(seconds: f32, level: &str, message: &str) =>
+        "[{seconds:>8.1} s] {level:<5} {message}",
+        "[{seconds:>8.1} с] {level:<5} {message}";
+    log_loading_image(path: &str) => "Loading image: {path}", "Загружаем изображение: {path}";
+    log_image_failed(path: &str, error: &str) =>
+        "Could not load image {path}: {error}",
+        "Не удалось загрузить изображение {path}: {error}";
+    log_image_loaded(width: u32, height: u32) =>
+        "Loaded: {width}×{height} pixels",
+        "Загружено: {width}×{height} пикселей";
+    log_clipboard_unavailable(error: &str) =>
+        "Clipboard unavailable: {error}",
+        "Буфер обмена недоступен: {error}";
+    log_clipboard_no_path(text: &str) =>
+        "No image or file path in the clipboard: “{text}”",
+        "В буфере обмена нет изображения или пути к файлу: «{text}»";
+    drop_here(target: &str) => "Drop: {target}", "Отпустите: {target}";
+    log_overlay_failed(error: &str) =>
+        "Text overlay not drawn: {error}",
+        "Надпись не нанесена: {error}";
+    log_resizing(width: u32, height: u32, sstv_width: u32, sstv_height: u32) =>
+        "Resizing from {width}×{height} to {sstv_width}×{sstv_height} for SSTV",
+        "Изменяем размер с {width}×{height} на {sstv_width}×{sstv_height} для SSTV";
+    log_params_error(error: &str) => "Parameter error: {error}", "Ошибка параметров: {error}";
+    log_processing_done(seconds: f32, note: &str) =>
+        "Processing finished in {seconds:.2}s{note}",
+        "Обработка завершена за {seconds:.2}с{note}";
+    log_processing_failed(seconds: f32, error: &str) =>
+        "Processing failed after {seconds:.2}s: {error}",
+        "Ошибка обработки за {seconds:.2}с: {error}";
+    log_processing_cancelled(seconds: f32) =>
+        "Processing cancelled after {seconds:.2}s",
+        "Обработка отменена через {seconds:.2}с";
+    log_soak_cleanup(megabytes: u64) =>
+        "Showcase: {megabytes} MB in use, dropping caches",
+        "Витрина: занято {megabytes} МБ, сбрасываем кэши";
+    log_folder_failed(folder: &str, error: &str) =>
+        "Could not read folder {folder}: {error}",
+        "Не удалось прочитать папку {folder}: {error}";
+    soak_queued(count: usize) => "Queued: {count}", "В очереди: {count}";
+    soak_uptime(hours: u64, minutes: u64, seconds: u64, cycles: u64, errors: u64) =>
+        "Running {hours}:{minutes:02}:{seconds:02}, images: {cycles}, errors: {errors}",
+        "Работает {hours}:{minutes:02}:{seconds:02}, изображений: {cycles}, ошибок: {errors}";
+    soak_memory(megabytes: f64, peak: f64, cleanups: u64) =>
+        "Memory: {megabytes:.0} MB (peak {peak:.0} MB), cache drops: {cleanups}",
+        "Память: {megabytes:.0} МБ (пик {peak:.0} МБ), сбросов кэша: {cleanups}";
+    soak_textures(count: usize, megabytes: f64, history: usize) =>
+        "Textures: {count} ({megabytes:.1} MB), in history: {history}",
+        "Текстур: {count} ({megabytes:.1} МБ), в истории: {history}";
+    history_latest(command: &str) => "Latest run\n{command}", "Последний прогон\n{command}";
+    history_ago(age: usize, command: &str) => "{age} back\n{command}", "{age} назад\n{command}";
+    log_playback_failed(error: &str) =>
+        "Playback error: {error}",
+        "Ошибка воспроизведения: {error}";
+    automation_save_failed(error: &str) =>
+        "Could not save the automation: {error}",
+        "Ошибка сохранения автоматизации: {error}";
+    log_automation_saved(path: &str) =>
+        "Automation recorded: {path}",
+        "Автоматизация записана: {path}";
+    log_capture_failed(error: &str) => "Capture error: {error}", "Ошибка приёма: {error}";
+    waterfall_hover(hz: f32, seconds: f32) =>
+        "{hz:.0} Hz, {seconds:.1} s",
+        "{hz:.0} Гц, {seconds:.1} с";
+    waterfall_range(low: f32, high: f32) =>
+        "{low:.0}–{high:.0} Hz, time runs top to bottom",
+        "{low:.0}–{high:.0} Гц, время сверху вниз";
+    seconds_hover(seconds: f32) => "{seconds:.1} s", "{seconds:.1} с";
+    waveform_peak(peak: f32, seconds: f32) =>
+        "Amplitude, peak {peak:.2}, {seconds:.1} s",
+        "Амплитуда, пик {peak:.2}, {seconds:.1} с";
+    spectrogram_range(low: f32, high: f32, seconds: f32) =>
+        "{low:.0}–{high:.0} Hz, {seconds:.1} s",
+        "{low:.0}–{high:.0} Гц, {seconds:.1} с";
+    spectrogram_line(line: u32) => "Line {line}", "Строка {line}";
+    inspector_span(from: f64, to: f64) => "{from:.4}–{to:.4} s:", "{from:.4}–{to:.4} с:";
+    inspector_tone(tone: &str, freq: f32, ms: f64) =>
+        "{tone}, {freq:.0} Hz, {ms:.3} ms",
+        "{tone}, {freq:.0} Гц, {ms:.3} мс";
+    inspector_pixels(first: &str, last: u32, low: f32, high: f32) =>
+        "{first}…{last}, {low:.0}–{high:.0} Hz",
+        "{first}…{last}, {low:.0}–{high:.0} Гц";
+    inspector_more(count: usize) => "…and {count} more", "…и ещё {count}";
+    log_command_copied(command: &str) =>
+        "Command copied: {command}",
+        "Команда скопирована: {command}";
+    log_error(error: &str) => "Error: {error}", "Ошибка: {error}";
+    log_saved(path: &str) => "Saved to: {path}", "Сохранено в: {path}";
+    log_run_saved(dir: &str) => "Run saved: {dir}", "Прогон сохранён: {dir}";
+    log_run_failed(error: &str) =>
+        "Could not save the run: {error}",
+        "Ошибка сохранения прогона: {error}";
+    log_envelope_failed(error: &str) =>
+        "Could not load the envelope: {error}",
+        "Ошибка загрузки огибающей: {error}";
+    log_timeline_failed(error: &str) =>
+        "Could not load the timeline: {error}",
+        "Ошибка загрузки сценария: {error}";
+    log_preset_saved(path: &str) => "Preset saved: {path}", "Пресет сохранён: {path}";
+    log_preset_save_failed(error: &str) =>
+        "Could not save the preset: {error}",
+        "Ошибка сохранения пресета: {error}";
+    log_preset_loaded(path: &str) => "Preset loaded: {path}", "Пресет загружен: {path}";
+    log_preset_load_failed(error: &str) =>
+        "Could not load the preset: {error}",
+        "Ошибка загрузки пресета: {error}";
+    file_saved(path: &str) => "File saved: {path}", "Файл сохранен: {path}";
+    log_save_failed(error: &str) => "Could not save: {error}", "Ошибка сохранения: {error}";
+    path_number(number: usize) => "Path {number}", "Путь {number}";
+    carrier_number(number: usize) => "Carrier {number}", "Несущая {number}";
+    dropout_number(number: usize) => "Dropout {number}", "Выпадение {number}";
+    font_label(font: &str) => "Font: {font}", "Шрифт: {font}";
+    hz(value: u32) => "{value} Hz", "{value} Гц";
+    auto_process_in(seconds: f32) =>
+        "⏱️ Auto-processing in {seconds:.1}s",
+        "⏱️ Автообработка через {seconds:.1}с";
+    recording(target: &str, elapsed: f32, duration: f32) =>
+        "⏺ Recording: {target} {elapsed:.0} / {duration:.0} s",
+        "⏺ Запись: {target} {elapsed:.0} / {duration:.0} с";
+    metrics_noise(percent: u8) => "Noise: {percent}%", "Шум: {percent}%";
+    metrics_retarder(percent: f32) => "Retarder: {percent:.1}%", "Ретардер: {percent:.1}%";
+    metrics_delay(ms: u32) => "Delay: {ms} ms", "Задержка: {ms} мс";
+    source_resolution(width: u32, height: u32) =>
+        "Source resolution: {width}×{height}",
+        "Исходное разрешение: {width}×{height}";
+    snr(db: f32) => "Signal-to-noise ratio: {db:.1} dB", "Отношение сигнал/шум: {db:.1} дБ";
+    psnr(db: f64) => "PSNR: {db:.2} dB", "PSNR: {db:.2} дБ";
+    registered_quality(psnr: f64, ssim: f64) =>
+        "After registration: PSNR {psnr:.2} dB, SSIM {ssim:.4}",
+        "После совмещения: PSNR {psnr:.2} дБ, SSIM {ssim:.4}";
+    concealed_lines(count: usize) => "Concealed lines: {count}", "Замаскировано строк: {count}";
+    log_entries(count: usize) => "Entries: {count}", "Записей: {count}";
+    receive_lines(lines: u32, height: u32) =>
+        "Receiving: line {lines} of {height}",
+        "Приём: строка {lines} из {height}";
+    receive_squelch_state(state: &str, leader_db: f32) =>
+        "Squelch {state}, leader {leader_db:.0} dB", "Шумоподавитель {state}, лидер-тон {leader_db:.0} дБ";
+    receive_level(level_db: f32, elapsed: f32) =>
+        "Level {level_db:.0} dBFS, listening {elapsed:.0} s",
+        "Уровень {level_db:.0} дБFS, слушаем {elapsed:.0} с";
+    receive_entry(number: usize, lines: u32) => "#{number}: {lines} lines", "№{number}: строк {lines}";
+}
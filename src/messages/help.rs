@@ -0,0 +1,702 @@
+use super::{Language, language};
+
+texts! {
+    // ── Параметры обработки ──────────────────────────────────
+    preset =>
+        "Parameter preset, TOML/JSON; flags given explicitly override the preset",
+        "Пресет параметров TOML/JSON; флаги, заданные явно, важнее значений пресета";
+    noise => "Noise level 0–100", "Уровень шума 0–100";
+    noise_kind => "Noise kind", "Вид шума";
+    noise_color =>
+        "Noise color: white, pink, brown or a band band:low:high, Hz",
+        "Окраска шума: white, pink, brown или полоса band:нижняя:верхняя, Гц";
+    impulse_rate =>
+        "Mean impulse rate, per second (impulse, crackle)",
+        "Средняя частота импульсов, в секунду (impulse, crackle)";
+    impulse_ms => "Impulse noise burst length, ms", "Длительность пачки импульсного шума, мс";
+    impulse_dist => "Impulse amplitude distribution", "Распределение амплитуд импульсов";
+    noise_env => "Noise envelope", "Огибающая шума";
+    noise_env_file =>
+        "Noise envelope file of \"time level\" points (.txt/.csv, .toml, .json); replaces --noise-env",
+        "Файл огибающей шума по точкам «время уровень» (.txt/.csv, .toml, .json); заменяет --noise-env";
+    noise_adsr =>
+        "ADSR noise envelope: attack, decay and release as shares of the period, and the sustain level; replaces --noise-env",
+        "Огибающая шума ADSR: доли периода атаки, спада, затухания и уровень удержания; заменяет --noise-env";
+    noise_burst =>
+        "Noise bursts: on time and period, ms, start jitter and ramps, ms; replaces --noise-env",
+        "Пачки шума: включение и период, мс, разброс начала и фронты, мс; заменяет --noise-env";
+    noise_repeat => "Noise envelope repeat factor", "Коэффициент повторения огибающей шума";
+    noise_anchor =>
+        "Noise envelope anchor: the whole audio or only the image body",
+        "Привязка огибающей шума: весь звук или только тело изображения";
+    retarder => "\"Ghost\" image", "Картинка-\"призрак\"";
+    level => "Retarder level 0–1", "Уровень ретардера 0–1";
+    ret_env => "Retarder envelope", "Огибающая ретардера";
+    ret_env_file =>
+        "Retarder envelope file of \"time level\" points; replaces --ret-env",
+        "Файл огибающей ретардера по точкам «время уровень»; заменяет --ret-env";
+    ret_adsr =>
+        "ADSR retarder envelope, see --noise-adsr; replaces --ret-env",
+        "Огибающая ретардера ADSR, см. --noise-adsr; заменяет --ret-env";
+    ret_burst =>
+        "Retarder bursts, see --noise-burst; replaces --ret-env",
+        "Пачки ретардера, см. --noise-burst; заменяет --ret-env";
+    ret_repeat => "Retarder repeat factor", "Коэффициент повторения ретардера";
+    ret_anchor =>
+        "Retarder envelope anchor: the whole audio or only the image body",
+        "Привязка огибающей ретардера: весь звук или только тело изображения";
+    delay_ms => "Retarder delay, ms", "Задержка ретардера, мс";
+    ret_source =>
+        "Ghost source: image — the -r picture, self — an echo of the main signal, flip and invert — the main image mirrored or in negative; otherwise a path to a WAV",
+        "Источник призрака: image — картинка -r, self — эхо основного сигнала, flip и invert — основное изображение, отражённое или в негативе; иначе путь к WAV";
+    tap =>
+        "Propagation path delay_ms:gain[:phase_deg], may be given several times",
+        "Путь распространения задержка_мс:усиление[:фаза_град], можно указать несколько раз";
+    fade_depth => "Fading depth 0–1 (0 — off)", "Глубина замираний 0–1 (0 — отключено)";
+    fade_rate => "Fading rate, Hz", "Скорость замираний, Гц";
+    fade_model => "Fading model", "Модель замираний";
+    offset_hz => "Constant frequency offset, Hz", "Постоянная расстройка, Гц";
+    drift_hz_per_s => "Linear frequency drift, Hz/s", "Линейный уход частоты, Гц/с";
+    wobble_hz => "Frequency wobble amplitude, Hz", "Размах плавания частоты, Гц";
+    wobble_rate => "Frequency wobble rate, Hz", "Частота плавания, Гц";
+    carrier =>
+        "Interfering carrier: freq_Hz:level[:duty[:period_ms]] (may be repeated)",
+        "Мешающая несущая: частота_Гц:уровень[:скважность[:период_мс]] (можно повторять)";
+    qrm => "Image of an interfering SSTV transmission", "Изображение мешающей SSTV-передачи";
+    qrm_level =>
+        "Interfering transmission level relative to the wanted signal",
+        "Уровень мешающей передачи относительно полезного сигнала";
+    qrm_offset_hz =>
+        "Interfering transmission frequency offset, Hz",
+        "Сдвиг частоты мешающей передачи, Гц";
+    qrm_delay_ms =>
+        "Interfering transmission start delay, ms",
+        "Задержка начала мешающей передачи, мс";
+    ssb =>
+        "Pass the signal through an SSB transceiver chain: clipper, filter, ALC",
+        "Пропустить сигнал через тракт SSB-трансивера: ограничитель, фильтр, АРМ";
+    ssb_low_hz => "Transceiver passband lower edge, Hz", "Нижняя граница полосы трансивера, Гц";
+    ssb_high_hz => "Transceiver passband upper edge, Hz", "Верхняя граница полосы трансивера, Гц";
+    ssb_drive_db =>
+        "Overdrive into the transmitter soft clipper, dB",
+        "Перегрузка перед мягким ограничителем передатчика, дБ";
+    ssb_alc_ms =>
+        "Transmitter ALC release time, ms (0 — no ALC)",
+        "Время восстановления АРМ передатчика, мс (0 — без АРМ)";
+    ssb_carrier =>
+        "Residual carrier relative to the signal peak 0–1 (visible in IQ)",
+        "Остаток несущей относительно пика сигнала 0–1 (виден в IQ)";
+    ssb_iq_gain_db =>
+        "Quadrature amplitude imbalance, dB (visible in IQ)",
+        "Разбаланс квадратур по амплитуде, дБ (виден в IQ)";
+    ssb_iq_phase_deg =>
+        "Quadrature phase imbalance, degrees (visible in IQ)",
+        "Разбаланс квадратур по фазе, градусов (виден в IQ)";
+    ssb_sideband =>
+        "Transceiver sideband: usb or lsb (in IQ lsb gives negative frequencies)",
+        "Боковая полоса трансивера: usb или lsb (в IQ lsb даёт отрицательные частоты)";
+    ssb_output =>
+        "Transceiver output WAV: mono — receiver audio, iq — stereo I/Q",
+        "Выходной WAV трансивера: mono — звук приёмника, iq — стерео I/Q";
+    blanker =>
+        "Receiver impulse noise blanker: cut amplitude spikes before the decoder",
+        "Подавитель импульсных помех приёмника: вырезать выбросы амплитуды перед декодером";
+    blanker_threshold =>
+        "Blanker threshold, how many times a spike exceeds the average level (below 2 cuts the signal itself)",
+        "Порог подавителя, во сколько раз выброс выше среднего уровня (ниже 2 режет сам сигнал)";
+    blanker_hold_ms =>
+        "Blanker hold before and after a spike, ms",
+        "Удержание подавителя до и после выброса, мс";
+    blanker_average_ms =>
+        "Time constant of the level the blanker compares the amplitude with, ms",
+        "Постоянная времени уровня, с которым подавитель сравнивает амплитуду, мс";
+    blanker_fill =>
+        "Fill of the cut span: zero, hold, interp, repeat — repeat a tone period",
+        "Заполнение вырезанного участка: zero, hold, interp, repeat — повтор периода тона";
+    filter =>
+        "Audio filter after the receiver, built from second-order sections",
+        "Звуковой фильтр после приёмника из звеньев второго порядка";
+    filter_kind =>
+        "Audio filter kind: lowpass, highpass, bandpass",
+        "Вид звукового фильтра: lowpass, highpass, bandpass";
+    filter_hz =>
+        "Audio filter cutoff or band centre frequency, Hz",
+        "Частота среза или центральная частота полосы звукового фильтра, Гц";
+    filter_q =>
+        "Filter section Q (for the band-pass — frequency / bandwidth)",
+        "Добротность звена фильтра (у полосового — частота / ширина полосы)";
+    filter_stages =>
+        "Number of filter sections in a row, 12 dB/octave each",
+        "Число звеньев фильтра подряд, по 12 дБ/октаву каждое";
+    notch =>
+        "Receiver notch filter: inside 1100–2300 Hz it cuts out some brightness levels",
+        "Режекторный фильтр приёмника: внутри 1100–2300 Гц вырезает часть яркостей";
+    notch_hz => "Notch frequency, Hz", "Частота режекции, Гц";
+    notch_q => "Notch Q: frequency / notch width", "Добротность режекции: частота / ширина вырезки";
+    agc =>
+        "Receiver AGC: brings the signal envelope to the set level",
+        "АРУ приёмника: приводит огибающую сигнала к заданному уровню";
+    agc_target =>
+        "AGC output envelope level, 0–1 of full scale",
+        "Уровень огибающей на выходе АРУ, 0–1 полной шкалы";
+    agc_attack_ms => "AGC attack time constant, ms", "Постоянная времени атаки АРУ, мс";
+    agc_release_ms => "AGC release time constant, ms", "Постоянная времени восстановления АРУ, мс";
+    agc_max_gain_db => "Maximum AGC gain, dB", "Наибольшее усиление АРУ, дБ";
+    dropout =>
+        "Signal dropout: start_ms:duration_ms from the start of the image (may be repeated)",
+        "Выпадение сигнала: начало_мс:длительность_мс от начала изображения (можно повторять)";
+    dropout_rate =>
+        "Random dropouts in the image body, per minute",
+        "Случайные выпадения в теле изображения, в минуту";
+    dropout_ms => "Mean random dropout length, ms", "Средняя длительность случайного выпадения, мс";
+    dropout_kind =>
+        "Dropout: zero — silence (at the end it cuts the transmission off), mute — attenuation",
+        "Выпадение: zero — тишина (у конца обрывает передачу), mute — ослабление";
+    dropout_db =>
+        "Attenuation with --dropout-kind mute, dB",
+        "Ослабление при --dropout-kind mute, дБ";
+    timeline =>
+        "Event script, TOML/JSON: effects on spans of the transmission, [[events]] tables",
+        "Сценарий событий TOML/JSON: эффекты на отрезках передачи, таблицы [[events]]";
+    seed =>
+        "Random generator seed for noise, fading and random envelopes",
+        "Зерно генератора случайных чисел для шума, замираний и случайных огибающих";
+    sample_rate =>
+        "Output signal sample rate (WAV, playback), Hz",
+        "Частота дискретизации выходного сигнала (WAV, воспроизведение), Гц";
+    output_gain_db =>
+        "Overall output gain after all effects, dB, before limiting to −1…1",
+        "Общее усиление выхода после всех эффектов, дБ, до ограничения диапазоном −1…1";
+    normalize =>
+        "Normalize written WAV files to a target loudness in a second pass: RMS over the whole signal or BS.1770 LUFS",
+        "Нормировать громкость записываемых WAV вторым проходом: RMS по всему сигналу или LUFS по BS.1770";
+    normalize_target =>
+        "Target loudness of --normalize, dBFS RMS or LUFS",
+        "Целевая громкость --normalize, дБFS RMS или LUFS";
+    true_peak_db =>
+        "True-peak ceiling of --normalize, dBTP: the gain is held back so that the 4× oversampled peak stays below it",
+        "Потолок истинного пика для --normalize, дБTP: усиление урезается, чтобы пик при 4-кратной передискретизации его не превысил";
+    mix =>
+        "Mix of an effect with its input effect:wet[:dry], may be given several times. Effects: retarder, multipath, fading, drift, interference, noise, blanker, filter, notch, agc, dropout",
+        "Доли смешивания эффекта с его входом эффект:wet[:dry], можно указать несколько раз. Эффекты: retarder, multipath, fading, drift, interference, noise, blanker, filter, notch, agc, dropout";
+    convolution =>
+        "Convolution method: auto — by signal and kernel length, direct, fft",
+        "Способ вычисления свёрток: auto — по длине сигнала и ядра, direct, fft";
+    parallel =>
+        "Compute noise and retarder in chunks on all cores; the result is the same as single-threaded",
+        "Считать шум и ретардер кусками на всех ядрах; результат тот же, что и в одном потоке";
+    quality =>
+        "Quality: preview — a fast draft run, normal, high — a long resampling kernel",
+        "Качество: preview — быстрый черновой прогон, normal, high — длинное ядро передискретизации";
+    sanitize =>
+        "NaN and infinities after effects: off — don't check, repair — replace and report the effect, strict — abort processing with an error",
+        "NaN и бесконечности после эффектов: off — не проверять, repair — заменять и сообщать эффект, strict — прерывать обработку ошибкой";
+    metric_space =>
+        "Color space of PSNR, SSIM and MSE: srgb — values as stored, linear — linear light, ycbcr — luma weighs 6:1:1 against chroma, for Robot modes",
+        "Цветовое пространство PSNR, SSIM и MSE: srgb — значения как есть, linear — линейный свет, ycbcr — яркость весит 6:1:1 к цветоразностям, для режимов Robot";
+    telemetry =>
+        "Telemetry strip in the top lines: temperature,latitude,longitude,altitude",
+        "Полоса телеметрии в верхних строках: температура,широта,долгота,высота";
+
+    // ── Метаданные ───────────────────────────────────────────
+    meta =>
+        "Transmission metadata KEY=VALUE (operator, location, grid…): written to PNG text chunks, WAV INFO and the bundle; repeatable",
+        "Метаданные передачи КЛЮЧ=ЗНАЧЕНИЕ (оператор, место, локатор…): пишутся в текстовые блоки PNG, INFO WAV и пакет прогона; можно повторять";
+    meta_overlay =>
+        "Draw the metadata as \"key: value\" lines below the overlay text",
+        "Рисовать метаданные строками «ключ: значение» под надписью";
+    meta_fsk =>
+        "Send the metadata as an FSK ID burst after the image (uppercase ASCII only); decoding reads it back",
+        "Передать метаданные посылкой FSK ID после изображения (только заглавная латиница); декодер читает её обратно";
+
+    // ── Пределы времени ──────────────────────────────────────
+    timeout =>
+        "Wall-clock limit for the whole run, seconds; on expiry processing stops with a timeout error",
+        "Предел всего прогона по настенным часам, секунды; по истечении обработка прерывается ошибкой";
+    stage_timeout =>
+        "Wall-clock limit for one stage, STAGE:SECS, stage is encode, effects or decode; repeatable",
+        "Предел одного этапа, ЭТАП:СЕКУНДЫ, этап — encode, effects или decode; можно повторять";
+
+    // ── Кодер ────────────────────────────────────────────────
+    encoder => "Encoder implementation", "Реализация кодера";
+    no_header =>
+        "Don't send the calibration header and VIS (native only)",
+        "Не передавать калибровочный заголовок и VIS (только native)";
+    leader_ms =>
+        "Leader tone length, ms (native only)",
+        "Длительность лидер-тона, мс (только native)";
+    vis_code => "VIS code 0–127 (native only)", "Код VIS 0–127 (только native)";
+    vis_attenuation_db =>
+        "VIS attenuation relative to the rest of the signal, dB (native only)",
+        "Ослабление VIS относительно остального сигнала, дБ (только native)";
+    vis_flip_bits =>
+        "Number of corrupted VIS bits 0–8, counting from the first data bit (native only)",
+        "Число искажённых бит VIS 0–8, считая от первого бита данных (только native)";
+    timing_scale =>
+        "Line timing scale (native only)",
+        "Масштаб временной сетки строки (только native)";
+    id =>
+        "Callsign sent after the image (by any encoder)",
+        "Позывной, передаваемый после изображения (любым кодером)";
+    id_mode =>
+        "Callsign mode: cw — Morse code, fsk — FSK ID as in MMSSTV",
+        "Способ передачи позывного: cw — азбука Морзе, fsk — FSK ID как в MMSSTV";
+    id_wpm => "CW speed, words per minute", "Скорость CW, слов в минуту";
+    id_tone_hz => "CW tone, Hz", "Тон CW, Гц";
+
+    // ── Надпись на изображении ───────────────────────────────
+    overlay =>
+        "Text line drawn on the image before encoding, may be given several times; {date} and {time} become the UTC date and time",
+        "Строка надписи на изображении перед кодированием, можно указать несколько раз; {date} и {time} заменяются датой и временем UTC";
+    overlay_x =>
+        "Left edge of the text, mode pixels; negative — offset from the right edge",
+        "Левый край надписи, пиксели режима; отрицательный — отступ от правого края";
+    overlay_y =>
+        "Top edge of the text, mode pixels; negative — offset from the bottom edge",
+        "Верхний край надписи, пиксели режима; отрицательный — отступ от нижнего края";
+    overlay_size => "Text line height, pixels", "Высота строки надписи, пиксели";
+    overlay_color => "Text color: #rrggbb or r,g,b", "Цвет надписи: #rrggbb или r,g,b";
+    overlay_font =>
+        "TTF/OTF font; without it the built-in bitmap font, ASCII only",
+        "Шрифт TTF/OTF; без него — встроенный растровый, только ASCII";
+    overlay_shadow => "Shadow under the text", "Тень под надписью";
+
+    // ── Декодер ──────────────────────────────────────────────
+    decoder => "Decoder implementation", "Реализация декодера";
+    sync_threshold => "Sync correlation threshold 0–1", "Порог корреляции синхросигнала 0–1";
+    search_window_ms =>
+        "Header search window, ms (0 — the whole signal)",
+        "Окно поиска заголовка, мс (0 — весь сигнал)";
+    max_deviation_hz =>
+        "Maximum tone frequency deviation, Hz",
+        "Максимальное отклонение частоты тона, Гц";
+    afc =>
+        "Automatic frequency control: estimate and remove the receiver offset",
+        "Автоподстройка частоты: оценивать и убирать расстройку приёмника";
+    conceal =>
+        "Conceal corrupted lines by interpolating their neighbours",
+        "Маскировать испорченные строки интерполяцией соседних";
+    deskew =>
+        "Automatic slant correction by searching the line period over the sync pulses",
+        "Автокоррекция наклона перебором периода строки по синхроимпульсам";
+    slant =>
+        "Manual slant, ppm: fixes the line period, overrides --deskew",
+        "Наклон вручную, ppm: фиксирует период строки, перекрывает --deskew";
+    palette =>
+        "Vintage scan converter palette: full, rgb666 (Robot 1200C), rgb444, rgb332, robot (Robot 400)",
+        "Палитра старинного преобразователя: full, rgb666 (Robot 1200C), rgb444, rgb332, robot (Robot 400)";
+    blur =>
+        "Vertical line blur 0–1, as on a scan converter monitor",
+        "Вертикальное размытие строк 0–1, как на мониторе преобразователя развёртки";
+    scanlines =>
+        "Depth of the gaps between scan lines 0–1",
+        "Глубина промежутков между строками развёртки 0–1";
+    phosphor =>
+        "Monitor phosphor: p22 (colour), p4 (white), p1 (green), p3 (amber)",
+        "Люминофор монитора: p22 (цветной), p4 (белый), p1 (зелёный), p3 (янтарный)";
+    phosphor_tint => "Phosphor tint strength 0–1", "Сила окраски люминофором 0–1";
+
+    // ── Основная команда ─────────────────────────────────────
+    input => "Main image, PNG/JPG", "Главная картинка PNG/JPG";
+    output => "Output file", "Итоговый файл";
+    stream =>
+        "Send decoder lines as PPM as they arrive: a file or tcp://HOST:PORT",
+        "Отдавать строки декодера в PPM по мере приёма: файл или tcp://ХОСТ:ПОРТ";
+    no_probe =>
+        "Skip the probe run over the first lines before processing",
+        "Не делать пробный прогон по первым строкам перед обработкой";
+    estimate =>
+        "Only estimate the channel: print what each effect would do, without processing",
+        "Только оценить канал: вывести, что сделал бы каждый эффект, без обработки";
+    blocks =>
+        "Process in blocks: encoder, effects and decoder run as one stream, the whole signal is never stored. Built-in decoder, encoder rate and block-capable effects only",
+        "Обрабатывать блоками: кодер, эффекты и декодер идут одним потоком, сигнал целиком не хранится. Только встроенный декодер, частота кодера и эффекты, умеющие блоки";
+    bundle =>
+        "Save the run to a timestamped folder inside DIR: original, result, WAV before and after the channel and params.json with all parameters and metrics",
+        "Сохранить прогон в папку с отметкой времени внутри DIR: исходное, результат, WAV до и после канала и params.json со всеми параметрами и метриками";
+    salvage =>
+        "If the decoder finds no image, still save a raster read without the header to OUTPUT, the spectrogram to OUTPUT.spectrogram.png and the degraded signal to OUTPUT.wav",
+        "Если декодер не нашёл изображение, всё равно сохранить растр, прочитанный без заголовка, в OUTPUT, водопад в OUTPUT.spectrogram.png и искажённый сигнал в OUTPUT.wav";
+    debug_wav =>
+        "Also write the signal after the channel to FILE (debug.wav if FILE is omitted)",
+        "Записать ещё и сигнал после канала в FILE (без FILE — в debug.wav)";
+    ensemble =>
+        "Run the channel N times with noise seeds from --seed onwards and save the average of the decoded images: independent noise averages out, the gain over a single run is printed",
+        "Прогнать канал N раз с зёрнами шума от --seed и сохранить среднее декодированных изображений: независимый шум усредняется, выигрыш над одним прогоном выводится";
+    ensemble_variance =>
+        "With --ensemble: save the per-pixel spread across runs as a heat map",
+        "С --ensemble: сохранить разброс каждого пикселя по прогонам тепловой картой";
+    lang =>
+        "Language of reports, descriptions, advice, errors and help: en, ru",
+        "Язык отчётов, описаний, советов, ошибок и справки: en, ru";
+    simd =>
+        "SIMD kernels for DSP loops: auto picks the best the CPU has; scalar, sse2, avx2, neon pin one",
+        "Векторные ядра циклов DSP: auto выбирает лучшие из доступных процессору; scalar, sse2, avx2, neon — задать явно";
+    threads =>
+        "Threads for parallel effects, batch, sweep, sequence and scan pools (0 — one per core); -j of a command overrides it",
+        "Потоки параллельных эффектов и пулов пакета, перебора, последовательности и поиска передач (0 — по числу ядер); -j команды важнее";
+    priority =>
+        "Priority of worker threads: normal, low, idle. Lower keeps cores free for a live receive or sound on the same machine (Linux, Windows)",
+        "Приоритет рабочих потоков: normal, low, idle. Пониженный оставляет ядра живому приёму и звуку на той же машине (Linux, Windows)";
+    play =>
+        "Play the final signal on a sound device",
+        "Воспроизвести итоговый сигнал на звуковом устройстве";
+    output_device =>
+        "Output device (system default if omitted)",
+        "Устройство вывода (по умолчанию — системное)";
+
+    // ── Команды ──────────────────────────────────────────────
+    command_decode =>
+        "Decode an image from a recorded WAV file",
+        "Декодировать изображение из записанного WAV файла";
+    command_batch =>
+        "Process every image of a folder or pattern in parallel",
+        "Обработать все изображения каталога или шаблона параллельно";
+    command_sweep =>
+        "Process an image for every combination of parameter values",
+        "Обработать изображение для каждого сочетания значений параметров";
+    command_sequence =>
+        "Degrade every frame of an animated GIF or a folder of frames",
+        "Исказить каждый кадр анимированного GIF или каталога кадров";
+    command_script => "Run a processing script (.rhai)", "Выполнить сценарий обработки (.rhai)";
+    command_null =>
+        "Subtract one WAV from another after alignment and show the residual",
+        "Вычесть один WAV из другого после выравнивания и показать остаток";
+    command_conformance =>
+        "Check the encoder's frequencies and timing against the mode spec",
+        "Проверить частоты и временную сетку кодера по спецификации режима";
+    command_fuzz =>
+        "Run processing with random parameters and buffers looking for panics and NaN",
+        "Прогнать обработку со случайными параметрами и буферами в поисках паник и NaN";
+    command_snapshot =>
+        "Save a comparison snapshot as in the GUI: original, result, difference and metrics",
+        "Сохранить снимок сравнения как в GUI: исходное, результат, разница и метрики";
+    command_watermark =>
+        "Embed a pseudo-random watermark, run the channel and report how much of it the decoded image keeps",
+        "Внести псевдослучайную метку, прогнать канал и показать, сколько её осталось в декодированном изображении";
+    command_channel =>
+        "Live channel: audio from an input device through the effects to an output device",
+        "Живой канал: звук с устройства ввода через эффекты на устройство вывода";
+    command_serve =>
+        "JSON-RPC job server on stdin/stdout or TCP: the processor is kept between jobs",
+        "Сервер заданий JSON-RPC на stdin/stdout или TCP: процессор не пересоздаётся";
+    command_pack =>
+        "Pack a run bundle folder with its images, WAVs, report and preset into one ZIP",
+        "Упаковать папку прогона с изображениями, WAV, отчётом и пресетом в один ZIP";
+    command_unpack =>
+        "Restore a run bundle folder from a ZIP made by pack",
+        "Восстановить папку прогона из ZIP, сделанного pack";
+    command_vectors =>
+        "Write a fixed set of impaired WAVs (noise, frequency offset, slant) with a manifest to benchmark other SSTV decoders",
+        "Записать постоянный набор искажённых WAV (шум, расстройка, наклон) с описью для проверки сторонних декодеров SSTV";
+    command_import =>
+        "Convert an MMSSTV or QSSTV settings file (mode, sound-card clock, frequency offset) into a preset",
+        "Перевести файл настроек MMSSTV или QSSTV (режим, часы звуковой карты, расстройку) в пресет";
+    command_monitor =>
+        "Headless receiver: decode every transmission from a sound card or raw PCM, optionally with a web dashboard",
+        "Приёмник без окна: декодировать каждую передачу со звуковой карты или сырого PCM, по желанию с веб-панелью";
+
+    // ── pack / unpack ────────────────────────────────────────
+    pack_dir => "Run bundle folder written by --bundle", "Папка прогона, записанная --bundle";
+    pack_out =>
+        "Archive to write (the folder name with .zip if omitted)",
+        "Куда записать архив (по умолчанию — имя папки с .zip)";
+    unpack_archive => "Archive made by pack", "Архив, сделанный pack";
+    unpack_out =>
+        "Folder to restore the run into; existing files are not overwritten",
+        "Каталог, куда восстановить прогон; существующие файлы не перезаписываются";
+    import_file =>
+        "MMSSTV.ini, qsstv.conf or any key=value dump of their settings",
+        "MMSSTV.ini, qsstv.conf или любой их дамп настроек вида ключ=значение";
+    import_out =>
+        "Preset to write, .toml or .json",
+        "Куда записать пресет, .toml или .json";
+    import_base =>
+        "Preset to start from instead of the defaults",
+        "Пресет, от которого начинать вместо умолчаний";
+    vectors_out => "Folder for the WAVs, test patterns and manifest", "Каталог для WAV, тестовых таблиц и описи";
+    vectors_manifest => "Manifest format", "Формат описи";
+    vectors_seed =>
+        "Noise seed; the same seed and version write the same files",
+        "Зерно шума; то же зерно и та же версия пишут те же файлы";
+
+    // ── monitor ──────────────────────────────────────────────
+    monitor_input_device =>
+        "Input device to listen on (system default if omitted)",
+        "Устройство ввода, которое слушать (по умолчанию — системное)";
+    monitor_pcm =>
+        "Raw mono little-endian PCM from a file or a named pipe instead of a sound card, '-' for stdin",
+        "Сырой моно-PCM little-endian из файла или именованного канала вместо звуковой карты, '-' — stdin";
+    monitor_pcm_rate => "Sample rate of --pcm, Hz", "Частота дискретизации --pcm, Гц";
+    monitor_pcm_format => "Sample format of --pcm: s16le, f32le", "Формат сэмплов --pcm: s16le, f32le";
+    monitor_out =>
+        "Folder for received images, named by reception time",
+        "Папка для принятых изображений, имена — по времени приёма";
+    monitor_secs =>
+        "Stop after this many seconds (run until Ctrl+C if omitted)",
+        "Остановиться через столько секунд (по умолчанию — до Ctrl+C)";
+    monitor_location =>
+        "Receiver location for reception stamps: grid locator (KO85ts) or lat,lon in degrees",
+        "Место приёмника для отметок приёма: QTH-локатор (KO85ts) или широта,долгота в градусах";
+    monitor_gpsd =>
+        "Take the receiver location from gpsd, host:port (localhost:2947 if no value)",
+        "Брать место приёмника у gpsd, host:port (без значения — localhost:2947)";
+    monitor_http =>
+        "Serve a web dashboard on this address, e.g. 0.0.0.0:8080: status, latest images, waterfall and log",
+        "Отдавать веб-панель на этом адресе, например 0.0.0.0:8080: состояние, последние изображения, водопад и журнал";
+    monitor_squelch =>
+        "Feed the decoder only while a 1900 Hz leader tone is heard, so band noise doesn't turn into images",
+        "Отдавать сигнал декодеру, только когда слышен лидер-тон 1900 Гц, чтобы шум диапазона не становился изображениями";
+    monitor_squelch_open_db =>
+        "Leader band share of the signal power that opens the squelch, dB",
+        "Доля мощности сигнала в полосе лидер-тона, при которой шумоподавитель открывается, дБ";
+    monitor_squelch_hysteresis =>
+        "Squelch stays open until the leader share falls this many dB below the opening threshold",
+        "Шумоподавитель открыт, пока доля лидер-тона не упадёт на столько дБ ниже порога открытия";
+    monitor_squelch_min_level =>
+        "Quietest signal that can open the squelch, dBFS",
+        "Самый тихий сигнал, который может открыть шумоподавитель, дБFS";
+    monitor_squelch_hold =>
+        "How long the squelch waits for an image after the leader tone fades, s",
+        "Сколько шумоподавитель ждёт изображение после пропажи лидер-тона, с";
+    monitor_squelch_any_vis =>
+        "Keep images whose VIS was not read or failed parity",
+        "Оставлять изображения, VIS которых не прочитан или не сошлась чётность";
+    monitor_webhook =>
+        "Post each received image with its stamp and metadata to this webhook: Discord, Telegram sendPhoto or any server (feature webhook)",
+        "Отправлять каждое принятое изображение с отметкой и метаданными на этот вебхук: Discord, sendPhoto Telegram или свой сервер (функция webhook)";
+    monitor_webhook_kind =>
+        "Webhook format: auto (by URL), discord, telegram, generic",
+        "Вид вебхука: auto (по адресу), discord, telegram, generic";
+    monitor_webhook_chat =>
+        "Telegram chat the bot posts to",
+        "Чат Telegram, куда пишет бот";
+
+    // ── serve ────────────────────────────────────────────────
+    serve_listen =>
+        "TCP address for requests, e.g. 127.0.0.1:7300 (stdin/stdout if omitted)",
+        "Адрес TCP для запросов, например 127.0.0.1:7300 (по умолчанию — stdin/stdout)";
+    serve_root =>
+        "Directory that job paths (image, retarder, output, retarder.source, overlay.font) are confined to; without it paths work only on stdin",
+        "Каталог, внутри которого лежат пути заданий (image, retarder, output, retarder.source, overlay.font); без него пути принимаются только с stdin";
+    serve_max_jobs =>
+        "Jobs processed at once, one warm processor each; with --listen clients are served in parallel",
+        "Сколько заданий идёт одновременно, по процессору на каждое; с --listen клиенты обслуживаются параллельно";
+    serve_max_queue =>
+        "Jobs that may wait for a processor; the next one is refused as busy (code -32001, like HTTP 429)",
+        "Сколько заданий может ждать процессор; следующее получает отказ «занято» (код -32001, как HTTP 429)";
+    serve_max_pixels =>
+        "Largest job image in pixels, checked before decoding (code -32002, like HTTP 413)",
+        "Наибольшее изображение задания в пикселях, проверяется до декодирования (код -32002, как HTTP 413)";
+    serve_max_request_bytes =>
+        "Longest request line in bytes, base64 images included",
+        "Наибольшая строка запроса в байтах вместе с изображениями в base64";
+    serve_max_sample_rate =>
+        "Highest job sample rate in Hz",
+        "Наибольшая частота дискретизации задания в Гц";
+    serve_max_signal_samples =>
+        "Longest job transmission in samples at its sample rate",
+        "Наибольшая длина передачи задания в сэмплах на её частоте";
+    serve_max_connections =>
+        "TCP connections served at once; the next one is refused as busy (code -32001) and closed",
+        "Сколько соединений TCP обслуживается одновременно; следующее получает отказ «занято» (код -32001) и закрывается";
+
+    // ── channel ──────────────────────────────────────────────
+    channel_secs =>
+        "How long the channel runs, seconds; envelopes and dropouts are laid out over this length",
+        "Сколько работать канал, секунд; по этой длине раскладываются огибающие и выпадения";
+    channel_input_device =>
+        "Input device the transmission comes to (system default if omitted)",
+        "Устройство ввода, куда приходит передача (по умолчанию — системное)";
+    channel_output_device =>
+        "Output device for the receiving program (system default if omitted)",
+        "Устройство вывода для приёмной программы (по умолчанию — системное)";
+    channel_block_ms =>
+        "Processing block length, ms: channel latency is about two blocks",
+        "Длина блока обработки, мс: задержка канала около двух блоков";
+    channel_input_rms =>
+        "Expected input RMS 0–1, noise sets its level from it",
+        "Ожидаемое СКЗ входа 0–1, от него шум выбирает уровень";
+    channel_checkpoint =>
+        "JSON file for a checkpoint of effect state (RNG seeds, filter history, fading), written on exit",
+        "Файл JSON для контрольной точки эффектов (зёрна, истории фильтров, замирания), пишется при выходе";
+    channel_checkpoint_every =>
+        "Also update the checkpoint every this many seconds",
+        "Обновлять контрольную точку ещё и каждые столько секунд";
+    channel_resume =>
+        "Resume effects from a checkpoint; use the same seed, preset and --secs",
+        "Продолжить эффекты с контрольной точки; зерно, пресет и --secs — те же";
+
+    // ── snapshot ─────────────────────────────────────────────
+    snapshot_out => "Snapshot PNG file", "Файл снимка PNG";
+    watermark_seed =>
+        "Seed of the watermark bits and their layout",
+        "Зерно бит метки и их раскладки";
+    watermark_bits => "Watermark length, bits", "Длина метки, бит";
+    watermark_cell =>
+        "Side of a watermark cell, pixels; each bit is spread over many cells",
+        "Сторона клетки метки, пикселей; каждый бит разнесён по многим клеткам";
+    watermark_strength =>
+        "Brightness shift of a cell, levels 0–255",
+        "Сдвиг яркости клетки, уровней 0–255";
+
+    // ── fuzz ─────────────────────────────────────────────────
+    fuzz_seed =>
+        "Seed of the first case; cases are numbered in a row, so a failure repeats with its seed and one case of the right kind",
+        "Зерно первого случая; случаи нумеруются подряд, поэтому сбой повторяется с его зерном и одним случаем нужного вида";
+    fuzz_effect_cases => "Effect pipeline cases", "Случаев конвейера эффектов";
+    fuzz_decode_cases => "Decoder cases", "Случаев декодера";
+    fuzz_process_cases =>
+        "Full encoder — effects — decoder runs",
+        "Полных прогонов кодер — эффекты — декодер";
+    fuzz_differential_cases =>
+        "Comparisons of the built-in decoder and rsstv on the same degraded transmission",
+        "Сравнений встроенного декодера и rsstv на одной искажённой передаче";
+    fuzz_min_agreement_db =>
+        "Lowest PSNR between the two decoders' images, dB",
+        "Наименьший PSNR между изображениями двух декодеров, дБ";
+    fuzz_max_len =>
+        "Largest random buffer length, samples",
+        "Наибольшая длина случайного буфера, сэмплов";
+
+    // ── soak ─────────────────────────────────────────────────
+    soak_about =>
+        "Long streaming run: encoder → random channel → decoder in a loop for hours, with memory growth and reception statistics",
+        "Долгий потоковый прогон: кодер → случайный канал → декодер по кругу часами, с ростом памяти и статистикой приёма";
+    soak_image =>
+        "Image to transmit every cycle; without it, the Martin M1 test chart",
+        "Изображение, передаваемое в каждом цикле; без него — тестовая таблица Martin M1";
+    soak_hours => "How long to run, hours", "Сколько работать, часов";
+    soak_cycles =>
+        "Stop after this many cycles, even if time is left",
+        "Остановиться после стольких циклов, даже если время осталось";
+    soak_seed =>
+        "Seed of the first cycle's channel; cycle k uses seed + k, so a failed cycle repeats with its seed",
+        "Зерно канала первого цикла; цикл k берёт зерно + k, поэтому неудачный цикл повторяется с его зерном";
+    soak_min_psnr_db =>
+        "Lowest PSNR of a received image against the sent one to count as received, dB",
+        "Наименьший PSNR принятого изображения относительно переданного, чтобы приём считался удачным, дБ";
+    soak_warmup =>
+        "Cycles before memory is taken as the baseline",
+        "Циклов до того, как память берётся за исходную";
+    soak_max_growth_mb =>
+        "Fail if memory grows by more than this after warm-up, MB",
+        "Считать прогон проваленным, если память после разогрева выросла больше, МБ";
+    soak_min_success =>
+        "Fail if fewer transmissions than this share are received, 0–1",
+        "Считать прогон проваленным, если принято меньше этой доли передач, 0–1";
+    soak_report_every =>
+        "Print the statistics every this many seconds",
+        "Печатать статистику раз в столько секунд";
+    soak_log =>
+        "CSV with a row per cycle: seed, time, lines, PSNR, memory",
+        "CSV со строкой на цикл: зерно, время, строки, PSNR, память";
+
+    // ── conformance ──────────────────────────────────────────
+    conformance_encoder => "Encoder under test", "Проверяемый кодер";
+    conformance_input =>
+        "Check a recorded test chart from a WAV instead of the encoder; the mode is taken from VIS",
+        "Проверить запись тестовой таблицы из WAV вместо кодера; режим берётся по VIS";
+    conformance_write_golden =>
+        "Write golden signals of every mode to a folder",
+        "Записать эталонные сигналы всех режимов в каталог";
+    conformance_golden =>
+        "Check golden signals from a folder and compare the encoder output with them",
+        "Проверить эталонные сигналы из каталога и сравнить с ними вывод кодера";
+    conformance_freq_tolerance_hz => "Tone frequency tolerance, Hz", "Допуск частоты тона, Гц";
+    conformance_edge_tolerance_us =>
+        "Tone edge position tolerance, µs",
+        "Допуск положения границы тона, мкс";
+    conformance_period_tolerance_ppm => "Line period tolerance, ppm", "Допуск периода строки, ppm";
+
+    // ── null ─────────────────────────────────────────────────
+    null_reference => "Reference WAV signal", "Эталонный сигнал WAV";
+    null_candidate => "WAV signal under test", "Проверяемый сигнал WAV";
+    null_max_offset_ms =>
+        "Largest offset searched between the signals, ms",
+        "Наибольший искомый сдвиг между сигналами, мс";
+    null_min_depth_db =>
+        "Null depth below which the signals count as different and the command fails, dB",
+        "Подавление, ниже которого сигналы считаются разными и команда завершается ошибкой, дБ";
+
+    // ── batch ────────────────────────────────────────────────
+    batch_input =>
+        "Folder of images or a name pattern (`photos/*.jpg`)",
+        "Каталог с изображениями или шаблон имён (`photos/*.jpg`)";
+    output_dir => "Folder for the results", "Каталог для результатов";
+    wav =>
+        "Save the degraded signal as WAV next to the image",
+        "Сохранять рядом с картинкой искажённый сигнал в WAV";
+    masks =>
+        "Also save training masks of the regions each impairment affected: NAME.mask.png for all of them and NAME.mask.EFFECT.png per effect, one colour channel per scan",
+        "Сохранять ещё маски областей, задетых каждым искажением, для обучения: NAME.mask.png для всех и NAME.mask.ЭФФЕКТ.png для каждого, по каналу цвета на развёртку";
+    jobs => "Number of threads (0 — as --threads)", "Число потоков (0 — как --threads)";
+
+    // ── sweep ────────────────────────────────────────────────
+    sweep_output_dir =>
+        "Folder for the results and the manifest",
+        "Каталог для результатов и описи";
+    sweep_noise_range =>
+        "Noise level range `start..end[:step]`",
+        "Диапазон уровня шума `начало..конец[:шаг]`";
+    sweep_level_range =>
+        "Retarder level range `start..end[:step]`",
+        "Диапазон уровня ретардера `начало..конец[:шаг]`";
+    sweep_delay_range =>
+        "Retarder delay range in ms `start..end[:step]`",
+        "Диапазон задержки ретардера в мс `начало..конец[:шаг]`";
+    sweep_manifest => "Results manifest format", "Формат описи результатов";
+    sweep_coarse =>
+        "First quickly go over the whole grid at preview quality (in DIR/preview), then the main pass",
+        "Сначала быстро пройти всю сетку с качеством preview (в DIR/preview), затем основной проход";
+    sweep_curve =>
+        "Fit SSIM against SNR over the noise range and write the curves to DIR/curve.csv",
+        "Подобрать кривую SSIM от С/Ш по диапазону шума и записать кривые в DIR/curve.csv";
+    sweep_curve_chart =>
+        "As --curve, and also draw the curves to DIR/curve.png",
+        "Как --curve, и ещё нарисовать кривые в DIR/curve.png";
+
+    // ── sequence ─────────────────────────────────────────────
+    sequence_input =>
+        "Animated GIF, a folder of frames or a single image",
+        "Анимированный GIF, каталог кадров или одна картинка";
+    sequence_output =>
+        "Animated GIF (.gif extension) or a folder for frame_0001.png, ...",
+        "Анимированный GIF (расширение .gif) или каталог для frame_0001.png, ...";
+    sequence_noise_ramp =>
+        "Noise level from the first frame to the last `start..end`",
+        "Уровень шума от первого кадра к последнему `начало..конец`";
+    sequence_level_ramp =>
+        "Retarder level from the first frame to the last `start..end`",
+        "Уровень ретардера от первого кадра к последнему `начало..конец`";
+    sequence_delay_ramp =>
+        "Retarder delay in ms from the first frame to the last `start..end`",
+        "Задержка ретардера в мс от первого кадра к последнему `начало..конец`";
+    sequence_frame_ms =>
+        "Frame length if the input doesn't set it (a folder, not a GIF), ms",
+        "Длительность кадра, если вход её не задаёт (каталог, не GIF), мс";
+
+    // ── script ───────────────────────────────────────────────
+    script_path => "Script file", "Файл сценария";
+
+    // ── decode ───────────────────────────────────────────────
+    decode_input => "SSTV recording in WAV format", "Запись SSTV в формате WAV";
+    decode_listen =>
+        "Record the signal from an input device instead of a file, seconds",
+        "Записать сигнал с устройства ввода вместо файла, секунд";
+    decode_input_device =>
+        "Input device (system default if omitted)",
+        "Устройство ввода (по умолчанию — системное)";
+    decode_telemetry =>
+        "Extract the telemetry strip from the decoded image",
+        "Извлечь полосу телеметрии из декодированного изображения";
+    decode_salvage =>
+        "If the decoder finds no image, still save a raster read without the header to OUTPUT and the spectrogram to OUTPUT.spectrogram.png; a --listen recording also goes to OUTPUT.wav",
+        "Если декодер не нашёл изображение, всё равно сохранить растр, прочитанный без заголовка, в OUTPUT и водопад в OUTPUT.spectrogram.png; запись --listen — ещё и в OUTPUT.wav";
+    decode_all =>
+        "Find every transmission in the recording and decode each into OUTPUT_1.png, OUTPUT_2.png, ...",
+        "Найти в записи все передачи и декодировать каждую в OUTPUT_1.png, OUTPUT_2.png, ...";
+    decode_jobs =>
+        "Largest number of transmissions decoded at once (0 — as --threads)",
+        "Наибольшее число передач, декодируемых одновременно (0 — как --threads)";
+}
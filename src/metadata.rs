@@ -0,0 +1,230 @@
+use crate::error::{Result, SstvError};
+use crate::messages;
+use crate::overlay::OverlayParams;
+use crate::station_id;
+use image::{DynamicImage, ImageFormat};
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::io::Cursor;
+use std::str::FromStr;
+
+/// Самый длинный ключ: предел ключевого слова текстового блока PNG
+pub const MAX_KEY_LEN: usize = 79;
+/// Разделители пар и ключа со значением в посылке FSK
+const FSK_PAIR_SEP: char = ';';
+const FSK_KEY_SEP: char = '=';
+
+/// Метаданные передачи: пары ключ — значение вроде оператора, места и QTH-локатора.
+/// Пишутся в текстовые блоки PNG и в INFO выходного WAV, попадают в пакет прогона и, если
+/// включено, рисуются поверх изображения. Посылкой FSK после изображения их передаёт
+/// кодер: текст из [`fsk_text`](Self::fsk_text) кладётся в [`IdParams::text`](crate::IdParams::text)
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Metadata {
+    /// Пары в порядке ключей
+    pub fields: BTreeMap<String, String>,
+    /// Рисовать пары строками «ключ: значение» под надписью [`OverlayParams`]
+    pub overlay: bool,
+}
+
+impl Metadata {
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    /// Добавляет пару; такой же ключ заменяется
+    pub fn insert(&mut self, field: MetaField) {
+        self.fields.insert(field.key, field.value);
+    }
+
+    /// Ключи непусты, не длиннее [`MAX_KEY_LEN`] и состоят из латиницы, цифр, `_`, `-` и `.`
+    pub fn validate(&self) -> Result<()> {
+        self.fields.keys().try_for_each(|key| check_key(key))
+    }
+
+    /// Пары через запятую: `grid=KO85, operator=R1ABC`
+    pub fn describe(&self) -> String {
+        describe(&self.fields)
+    }
+
+    /// Строки надписи: по одной «ключ: значение» на пару
+    pub fn overlay_lines(&self) -> Vec<String> {
+        self.fields
+            .iter()
+            .map(|(key, value)| format!("{}: {}", key, value))
+            .collect()
+    }
+
+    /// Надпись `overlay` и под её строками пары, если они рисуются
+    pub fn overlay_on<'a>(&self, overlay: &'a OverlayParams) -> Cow<'a, OverlayParams> {
+        if !self.overlay || self.is_empty() {
+            return Cow::Borrowed(overlay);
+        }
+        let mut overlay = overlay.clone();
+        overlay.lines.extend(self.overlay_lines());
+        Cow::Owned(overlay)
+    }
+
+    /// Текст посылки FSK: `GRID=KO85;OPERATOR=R1ABC`. FSK ID передаёт только заглавную
+    /// латиницу, цифры и знаки ASCII, поэтому регистр теряется, остальные символы, а
+    /// также разделители `;` и `=` внутри значений выбрасываются
+    pub fn fsk_text(&self) -> String {
+        let clean = |s: &str| -> String {
+            s.to_ascii_uppercase()
+                .chars()
+                // «!» — концевой символ FSK ID
+                .filter(|&c| (' '..='_').contains(&c) && !"!;=".contains(c))
+                .collect::<String>()
+                .trim()
+                .to_string()
+        };
+        self.fields
+            .iter()
+            .map(|(key, value)| format!("{}{}{}", clean(key), FSK_KEY_SEP, clean(value)))
+            .collect::<Vec<_>>()
+            .join(&FSK_PAIR_SEP.to_string())
+    }
+
+    /// Пары из текста посылки FSK; `None`, если это не метаданные (например, позывной).
+    /// Ключи приводятся к нижнему регистру
+    pub fn parse_fsk(text: &str) -> Option<BTreeMap<String, String>> {
+        let fields: BTreeMap<_, _> = text
+            .split(FSK_PAIR_SEP)
+            .filter_map(|pair| {
+                let (key, value) = pair.split_once(FSK_KEY_SEP)?;
+                let key = key.trim().to_ascii_lowercase();
+                check_key(&key).ok()?;
+                Some((key, value.trim().to_string()))
+            })
+            .collect();
+        (!fields.is_empty()).then_some(fields)
+    }
+
+    /// Метаданные из посылок FSK в сигнале частоты [`SAMPLE_RATE`](crate::SAMPLE_RATE); поздние
+    /// посылки дополняют ранние
+    pub fn read(samples: &[f32]) -> BTreeMap<String, String> {
+        station_id::read_fsk(samples)
+            .iter()
+            .filter_map(|text| Self::parse_fsk(text))
+            .flatten()
+            .collect()
+    }
+}
+
+/// Пары через запятую: `grid=KO85, operator=R1ABC`
+pub fn describe(fields: &BTreeMap<String, String>) -> String {
+    fields
+        .iter()
+        .map(|(key, value)| format!("{}={}", key, value))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Одна пара, как она задаётся в командной строке: `ключ=значение`
+#[derive(Clone, Debug, PartialEq)]
+pub struct MetaField {
+    pub key: String,
+    pub value: String,
+}
+
+impl FromStr for MetaField {
+    type Err = SstvError;
+
+    /// Разбирает `ключ=значение`, например `operator=R1ABC`; значение может быть пустым
+    fn from_str(s: &str) -> Result<Self> {
+        let (key, value) = s
+            .split_once('=')
+            .ok_or_else(|| SstvError::invalid("metadata", messages::metadata_expected_pair(s)))?;
+        let key = key.trim().to_string();
+        check_key(&key)?;
+        Ok(Self {
+            key,
+            value: value.trim().to_string(),
+        })
+    }
+}
+
+fn check_key(key: &str) -> Result<()> {
+    let valid = key
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || "_-.".contains(c));
+    if key.is_empty() || key.len() > MAX_KEY_LEN || !valid {
+        return Err(SstvError::invalid(
+            "metadata",
+            messages::metadata_bad_key(key, MAX_KEY_LEN),
+        ));
+    }
+    Ok(())
+}
+
+/// PNG изображения `image` с парами `fields` в текстовых блоках iTXt (UTF-8) перед IEND
+pub fn png_with_text(
+    image: &DynamicImage,
+    fields: &BTreeMap<String, String>,
+) -> image::ImageResult<Vec<u8>> {
+    let mut png = Vec::new();
+    image.write_to(&mut Cursor::new(&mut png), ImageFormat::Png)?;
+    if fields.is_empty() {
+        return Ok(png);
+    }
+    // IEND — последние 12 байт: длина, тип и CRC пустого блока
+    let iend = png.split_off(png.len() - 12);
+    for (key, value) in fields {
+        // Ключевое слово, флаг и метод сжатия, пустые язык и перевод ключа, текст
+        let mut data = key.as_bytes().to_vec();
+        data.extend_from_slice(&[0, 0, 0, 0, 0]);
+        data.extend_from_slice(value.as_bytes());
+        write_chunk(&mut png, b"iTXt", &data);
+    }
+    png.extend_from_slice(&iend);
+    Ok(png)
+}
+
+fn write_chunk(out: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = out.len();
+    out.extend_from_slice(kind);
+    out.extend_from_slice(data);
+    let crc = crc32(&out[start..]);
+    out.extend_from_slice(&crc.to_be_bytes());
+}
+
+/// CRC-32 блоков PNG и записей ZIP (многочлен 0xEDB88320)
+pub(crate) fn crc32(bytes: &[u8]) -> u32 {
+    !bytes.iter().fold(!0u32, |crc, &b| {
+        (0..8).fold(crc ^ b as u32, |c, _| {
+            if c & 1 == 1 {
+                (c >> 1) ^ 0xEDB8_8320
+            } else {
+                c >> 1
+            }
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encoder::{self, EncoderParams};
+    use crate::testing::random_image;
+
+    #[test]
+    fn metadata_survives_fsk_burst() {
+        let mut metadata = Metadata::default();
+        metadata.insert("operator=r1abc".parse().unwrap());
+        metadata.insert("grid=KO85".parse().unwrap());
+        let mut params = EncoderParams::default();
+        params.id.callsign = "R1ABC".into();
+        params.id.text = metadata.fsk_text();
+        let image = random_image(320, 256, 1);
+        let samples = encoder::encode(&image, &params);
+        assert_eq!(
+            station_id::read_fsk(&samples),
+            vec!["GRID=KO85;OPERATOR=R1ABC".to_string()]
+        );
+        let read = Metadata::read(&samples);
+        assert_eq!(read.get("operator").map(String::as_str), Some("R1ABC"));
+        assert_eq!(read.get("grid").map(String::as_str), Some("KO85"));
+    }
+}
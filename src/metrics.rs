@@ -0,0 +1,597 @@
+use crate::dsp;
+use crate::messages;
+use crate::spectrogram;
+use image::imageops::FilterType;
+use image::{DynamicImage, Rgb, RgbImage};
+use serde::{Deserialize, Serialize};
+
+/// Окно SSIM: гауссиана 11×11 с σ = 1.5, как в исходной работе Wang et al.
+const SSIM_RADIUS: usize = 5;
+const SSIM_SIGMA: f64 = 1.5;
+/// Стабилизирующие константы SSIM для 8-битных значений
+const SSIM_C1: f64 = (0.01 * 255.0) * (0.01 * 255.0);
+const SSIM_C2: f64 = (0.03 * 255.0) * (0.03 * 255.0);
+/// Наибольший сдвиг изображения по вертикали при регистрации, строк
+const MAX_SHIFT_LINES: i32 = 16;
+/// Наибольший сдвиг строки по горизонтали при регистрации, доля ширины
+const MAX_SHIFT_SHARE: f64 = 0.125;
+/// Наименьший разброс яркости строки (дисперсия, уровни²), по которой ищется её сдвиг:
+/// на ровном фоне он не определён
+const MIN_ROW_VARIANCE: f64 = 25.0;
+/// Отклонение сдвига строки от прямой наклона, пикселей, после которого строка не
+/// участвует в уточнении прямой
+const SLANT_OUTLIER_PX: f64 = 2.0;
+
+/// Цветовое пространство, в котором считаются PSNR, MSE и SSIM
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MetricSpace {
+    /// Значения sRGB как есть, каналы поровну; SSIM по яркости BT.601
+    #[default]
+    Srgb,
+    /// Линейный свет: каналы sRGB без гамма-кривой, SSIM по яркости BT.709. Ошибки в
+    /// тенях весят меньше, в светах — больше
+    Linear,
+    /// YCbCr BT.601, ошибка яркости весит 6 : 1 : 1 к цветоразностям, SSIM по Y. Режимы
+    /// Robot передают цветоразности с половинным разрешением, и их ошибки на глаз
+    /// заметнее всего по яркости
+    YCbCr,
+}
+
+impl MetricSpace {
+    pub const ALL: &'static [MetricSpace] =
+        &[MetricSpace::Srgb, MetricSpace::Linear, MetricSpace::YCbCr];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            MetricSpace::Srgb => "sRGB",
+            MetricSpace::Linear => messages::metric_space_linear(),
+            MetricSpace::YCbCr => "YCbCr",
+        }
+    }
+
+    /// Каналы пикселя в этом пространстве, уровни 0–255
+    fn channels(&self, Rgb(p): Rgb<u8>) -> [f64; 3] {
+        let [r, g, b] = p.map(f64::from);
+        match self {
+            MetricSpace::Srgb => [r, g, b],
+            MetricSpace::Linear => p.map(|c| 255.0 * srgb_to_linear(c as f64 / 255.0)),
+            MetricSpace::YCbCr => [
+                0.299 * r + 0.587 * g + 0.114 * b,
+                128.0 - 0.168736 * r - 0.331264 * g + 0.5 * b,
+                128.0 + 0.5 * r - 0.418688 * g - 0.081312 * b,
+            ],
+        }
+    }
+
+    /// Веса каналов в MSE, в сумме 1
+    fn weights(&self) -> [f64; 3] {
+        match self {
+            MetricSpace::Srgb | MetricSpace::Linear => [1.0 / 3.0; 3],
+            MetricSpace::YCbCr => [0.75, 0.125, 0.125],
+        }
+    }
+
+    /// Яркость для SSIM по каналам из [`channels`](Self::channels)
+    fn luma(&self, [a, b, c]: [f64; 3]) -> f64 {
+        match self {
+            MetricSpace::Srgb => 0.299 * a + 0.587 * b + 0.114 * c,
+            MetricSpace::Linear => 0.2126 * a + 0.7152 * b + 0.0722 * c,
+            MetricSpace::YCbCr => a,
+        }
+    }
+}
+
+impl clap::ValueEnum for MetricSpace {
+    fn value_variants<'a>() -> &'a [Self] {
+        Self::ALL
+    }
+
+    fn to_possible_value<'a>(&self) -> Option<clap::builder::PossibleValue> {
+        Some(match self {
+            Self::Srgb => clap::builder::PossibleValue::new("srgb"),
+            Self::Linear => clap::builder::PossibleValue::new("linear"),
+            Self::YCbCr => clap::builder::PossibleValue::new("ycbcr"),
+        })
+    }
+}
+
+/// Снимает гамма-кривую sRGB (IEC 61966-2-1) со значения 0–1
+fn srgb_to_linear(v: f64) -> f64 {
+    if v <= 0.04045 {
+        v / 12.92
+    } else {
+        ((v + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Оценка искажения декодированного изображения относительно исходного
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct QualityReport {
+    /// Пиковое отношение сигнал/шум в пространстве `space`, дБ (бесконечность для
+    /// одинаковых изображений)
+    pub psnr: f64,
+    /// Структурное сходство по яркости, от −1 до 1 (1 — совпадение)
+    pub ssim: f64,
+    /// Взвешенная среднеквадратичная ошибка по каналам `space`, уровни 0–255
+    pub mse: f64,
+    pub space: MetricSpace,
+}
+
+impl QualityReport {
+    /// Возвращает описание оценки
+    pub fn describe(&self) -> String {
+        let psnr = if self.psnr.is_finite() {
+            messages::quality_psnr(self.psnr)
+        } else {
+            "∞".to_string()
+        };
+        let text = format!("PSNR {}, SSIM {:.4}, MSE {:.2}", psnr, self.ssim, self.mse);
+        if self.space == MetricSpace::Srgb {
+            text
+        } else {
+            format!("{} ({})", text, self.space.name())
+        }
+    }
+}
+
+/// Сравнивает декодированное изображение с исходным в sRGB. Исходное приводится к
+/// размеру декодированного тем же фильтром, что и перед кодированием
+pub fn compare(original: &DynamicImage, decoded: &DynamicImage) -> QualityReport {
+    compare_in(original, decoded, MetricSpace::Srgb)
+}
+
+/// То же, что [`compare`], в цветовом пространстве `space`
+pub fn compare_in(
+    original: &DynamicImage,
+    decoded: &DynamicImage,
+    space: MetricSpace,
+) -> QualityReport {
+    let original = fit(original, decoded.width(), decoded.height());
+    quality(&original, &decoded.to_rgb8(), space)
+}
+
+/// Сравнивает декодированное изображение с исходным после регистрации (см.
+/// [`register`]): сдвиг на несколько строк или наклон от ошибок синхронизации и часов
+/// иначе обрушили бы PSNR и SSIM при почти нетронутой картинке
+pub fn compare_registered(
+    original: &DynamicImage,
+    decoded: &DynamicImage,
+    space: MetricSpace,
+) -> (QualityReport, Registration) {
+    let original = fit(original, decoded.width(), decoded.height());
+    let decoded = decoded.to_rgb8();
+    let registration = register_rgb(&original, &decoded);
+    (
+        quality(&original, &registration.apply(&decoded), space),
+        registration,
+    )
+}
+
+/// PSNR, SSIM и MSE двух изображений одного размера в пространстве `space`
+fn quality(original: &RgbImage, decoded: &RgbImage, space: MetricSpace) -> QualityReport {
+    let (width, height) = decoded.dimensions();
+    let channels =
+        |img: &RgbImage| -> Vec<[f64; 3]> { img.pixels().map(|&p| space.channels(p)).collect() };
+    let (a, b) = (channels(original), channels(decoded));
+    let weights = space.weights();
+    let count = a.len().max(1) as f64;
+    let mse = a
+        .iter()
+        .zip(&b)
+        .map(|(p, q)| {
+            (0..3)
+                .map(|c| weights[c] * (p[c] - q[c]).powi(2))
+                .sum::<f64>()
+        })
+        .sum::<f64>()
+        / count;
+    let psnr = if mse > 0.0 {
+        10.0 * (255.0 * 255.0 / mse).log10()
+    } else {
+        f64::INFINITY
+    };
+
+    let luma = |img: &[[f64; 3]]| -> Vec<f64> { img.iter().map(|&p| space.luma(p)).collect() };
+    let ssim = ssim(&luma(&a), &luma(&b), width as usize, height as usize);
+
+    QualityReport {
+        psnr,
+        ssim,
+        mse,
+        space,
+    }
+}
+
+fn luma(img: &RgbImage) -> Vec<f64> {
+    img.pixels()
+        .map(|p| 0.299 * p[0] as f64 + 0.587 * p[1] as f64 + 0.114 * p[2] as f64)
+        .collect()
+}
+
+/// Пространственное совмещение декодированного изображения с исходным: пиксель
+/// `(x, y)` исходного лежит в декодированном в `(x + shift_px(y), y + shift_lines)`
+#[derive(Copy, Clone, Debug, Default, PartialEq, Serialize)]
+pub struct Registration {
+    /// На сколько строк декодированное изображение сдвинуто вниз
+    pub shift_lines: i32,
+    /// Сдвиг вправо верхней строки, пикселей
+    pub offset_px: f64,
+    /// Наклон: насколько нижняя строка сдвинута вправо сильнее верхней, пикселей
+    pub slant_px: f64,
+}
+
+impl Registration {
+    /// Сдвиг вправо строки `y` изображения высотой `height`, пикселей
+    pub fn shift_px(&self, y: u32, height: u32) -> f64 {
+        self.offset_px + self.slant_px * y as f64 / height.saturating_sub(1).max(1) as f64
+    }
+
+    /// Совмещение ничего не сдвигает и на полпикселя
+    pub fn is_identity(&self) -> bool {
+        self.shift_lines == 0
+            && self.offset_px.abs() < 0.5
+            && (self.offset_px + self.slant_px).abs() < 0.5
+    }
+
+    /// Декодированное изображение, совмещённое с исходным: строки сдвигаются обратно с
+    /// линейной интерполяцией, за краями повторяются крайние пиксели
+    pub fn apply(&self, decoded: &RgbImage) -> RgbImage {
+        let (width, height) = decoded.dimensions();
+        if width == 0 || height == 0 {
+            return decoded.clone();
+        }
+        RgbImage::from_fn(width, height, |x, y| {
+            let row = (y as i64 + self.shift_lines as i64).clamp(0, height as i64 - 1) as u32;
+            let at = (x as f64 + self.shift_px(y, height)).clamp(0.0, (width - 1) as f64);
+            let (left, frac) = (at.floor() as u32, at.fract());
+            let right = (left + 1).min(width - 1);
+            let (Rgb(p), Rgb(q)) = (decoded.get_pixel(left, row), decoded.get_pixel(right, row));
+            Rgb(std::array::from_fn(|c| {
+                (p[c] as f64 * (1.0 - frac) + q[c] as f64 * frac).round() as u8
+            }))
+        })
+    }
+
+    pub fn describe(&self) -> String {
+        messages::registration_describe(self.shift_lines, self.offset_px, self.slant_px)
+    }
+}
+
+/// Находит сдвиг декодированного изображения относительно исходного (исходное
+/// приводится к размеру декодированного): по вертикали — по профилю средней яркости
+/// строк, до [`MAX_SHIFT_LINES`]; по горизонтали — для каждой строки с деталями, до
+/// [`MAX_SHIFT_SHARE`] ширины, и прямая по этим сдвигам даёт смещение и наклон
+pub fn register(original: &DynamicImage, decoded: &DynamicImage) -> Registration {
+    let original = fit(original, decoded.width(), decoded.height());
+    register_rgb(&original, &decoded.to_rgb8())
+}
+
+fn register_rgb(original: &RgbImage, decoded: &RgbImage) -> Registration {
+    let (width, height) = (decoded.width() as usize, decoded.height() as usize);
+    if width < 2 || height < 2 {
+        return Registration::default();
+    }
+    let (a, b) = (luma(original), luma(decoded));
+    let rows = |img: &[f64]| -> Vec<f64> {
+        img.chunks(width)
+            .map(|row| row.iter().sum::<f64>() / width as f64)
+            .collect()
+    };
+
+    // По вертикали: сдвиг принимается, только если он заметно лучше нулевого — на
+    // изображении из одинаковых строк профиль ровный и сдвиг не определён
+    let max_lines = MAX_SHIFT_LINES.min(height as i32 / 4);
+    let (profile_a, profile_b) = (rows(&a), rows(&b));
+    let (best, best_error) = best_shift(&profile_a, &profile_b, max_lines);
+    let zero_error = best_shift(&profile_a, &profile_b, 0).1;
+    let shift_lines = if best_error < 0.5 * zero_error {
+        best
+    } else {
+        0
+    };
+
+    // По горизонтали: строки без деталей сдвига не показывают
+    let max_px = (width as f64 * MAX_SHIFT_SHARE) as i32;
+    let mut points = Vec::new();
+    for y in 0..height {
+        let Some(row_b) = y
+            .checked_add_signed(shift_lines as isize)
+            .filter(|&row| row < height)
+        else {
+            continue;
+        };
+        let row_a = &a[y * width..(y + 1) * width];
+        let mean = row_a.iter().sum::<f64>() / width as f64;
+        let variance = row_a.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / width as f64;
+        if variance >= MIN_ROW_VARIANCE {
+            let row_b = &b[row_b * width..(row_b + 1) * width];
+            points.push((y as f64, best_shift(row_a, row_b, max_px).0 as f64));
+        }
+    }
+
+    // Прямая по наименьшим квадратам; строки, далёкие от неё, отбрасываются, и прямая
+    // проводится заново
+    let min_points = (height / 16).max(8);
+    let Some((mut offset, mut slope)) = fit_line(&points, min_points) else {
+        return Registration {
+            shift_lines,
+            ..Default::default()
+        };
+    };
+    points.retain(|&(y, shift)| (shift - offset - slope * y).abs() <= SLANT_OUTLIER_PX);
+    if let Some((o, s)) = fit_line(&points, min_points) {
+        (offset, slope) = (o, s);
+    }
+    Registration {
+        shift_lines,
+        offset_px: offset,
+        slant_px: slope * (height - 1) as f64,
+    }
+}
+
+/// Сдвиг `d` в пределах ±`max`, при котором `b[i + d]` ближе всего к `a[i]` по
+/// среднему квадрату разности, и сама эта ошибка. Из равных выбирается ближайший к нулю
+fn best_shift(a: &[f64], b: &[f64], max: i32) -> (i32, f64) {
+    let error = |d: i32| {
+        let (sum, count) = (0..a.len() as i64)
+            .filter_map(|i| {
+                let j = usize::try_from(i + d as i64).ok()?;
+                b.get(j).map(|&v| (a[i as usize] - v).powi(2))
+            })
+            .fold((0.0, 0usize), |(sum, count), e| (sum + e, count + 1));
+        if count > 0 {
+            sum / count as f64
+        } else {
+            f64::INFINITY
+        }
+    };
+    (1..=max)
+        .flat_map(|k| [k, -k])
+        .fold((0, error(0)), |(best, best_error), d| {
+            let e = error(d);
+            if e < best_error {
+                (d, e)
+            } else {
+                (best, best_error)
+            }
+        })
+}
+
+/// Прямая `offset + slope · y` по точкам `(y, value)` наименьшими квадратами; `None`,
+/// если точек меньше `min_points` или все они на одной строке
+fn fit_line(points: &[(f64, f64)], min_points: usize) -> Option<(f64, f64)> {
+    if points.len() < min_points {
+        return None;
+    }
+    let n = points.len() as f64;
+    let (mean_y, mean_v) = points
+        .iter()
+        .fold((0.0, 0.0), |(y, v), &(py, pv)| (y + py / n, v + pv / n));
+    let (cov, var) = points.iter().fold((0.0, 0.0), |(cov, var), &(y, v)| {
+        (
+            cov + (y - mean_y) * (v - mean_v),
+            var + (y - mean_y).powi(2),
+        )
+    });
+    (var > 0.0).then(|| {
+        let slope = cov / var;
+        (mean_v - slope * mean_y, slope)
+    })
+}
+
+/// Сравнение искажённого сигнала с чистым после выравнивания, см. [`compare_signals`]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SignalQuality {
+    /// Запаздывание искажённого сигнала относительно чистого, сэмплов
+    pub offset: isize,
+    /// Масштаб, приводящий искажённый сигнал к уровню чистого
+    pub scale: f32,
+    /// Отношение мощности чистого сигнала к мощности остатка на общем отрезке, дБ
+    /// (бесконечность при полном совпадении)
+    pub snr_db: f64,
+    /// Сэмплов, общих для обоих сигналов после сдвига
+    pub overlap: usize,
+}
+
+impl SignalQuality {
+    /// Запаздывание в миллисекундах при частоте `sample_rate`
+    pub fn offset_ms(&self, sample_rate: u32) -> f64 {
+        self.offset as f64 * 1000.0 / sample_rate as f64
+    }
+
+    pub fn describe(&self, sample_rate: u32) -> String {
+        messages::signal_quality(
+            self.offset,
+            self.offset_ms(sample_rate),
+            self.scale,
+            self.snr_db,
+        )
+    }
+}
+
+/// Сравнивает искажённый сигнал `impaired` с чистым `clean` той же частоты. Сначала
+/// он выравнивается по сдвигу и масштабу (см. [`dsp::align`]): иначе эффект с
+/// задержкой (фильтр, многолучёвость) или другим усилением дал бы огромный остаток при
+/// почти нетронутом сигнале
+pub fn compare_signals(clean: &[f32], impaired: &[f32]) -> SignalQuality {
+    let (offset, scale) = dsp::align(clean, impaired);
+    let (mut signal, mut residual, mut overlap) = (0.0f64, 0.0f64, 0);
+    for (a, b) in dsp::overlapping(clean, impaired, offset) {
+        let diff = a as f64 - scale as f64 * b as f64;
+        signal += a as f64 * a as f64;
+        residual += diff * diff;
+        overlap += 1;
+    }
+    let snr_db = if residual > 0.0 {
+        10.0 * (signal / residual).log10()
+    } else {
+        f64::INFINITY
+    };
+    SignalQuality {
+        offset,
+        scale,
+        snr_db,
+        overlap,
+    }
+}
+
+/// Тепловая карта разницы изображений: в каждом пикселе — наибольшая по каналам
+/// абсолютная ошибка, от чёрного (совпадение) через красный и жёлтый к белому (255).
+/// Шкала постоянная и корневая: ошибка в 16 уровней уже ярко-красная, а карты разных
+/// прогонов сравнимы. Как и в [`compare`], `a` приводится к размеру `b`
+pub fn diff_image(a: &DynamicImage, b: &DynamicImage) -> DynamicImage {
+    let (width, height) = (b.width(), b.height());
+    let a = fit(a, width, height);
+    let b = b.to_rgb8();
+    let heatmap = RgbImage::from_fn(width, height, |x, y| {
+        let (Rgb(p), Rgb(q)) = (a.get_pixel(x, y), b.get_pixel(x, y));
+        let error = (0..3).map(|c| p[c].abs_diff(q[c])).max().unwrap_or(0);
+        spectrogram::heat((error as f32 / 255.0).sqrt())
+    });
+    DynamicImage::ImageRgb8(heatmap)
+}
+
+/// Изображение в RGB размером `width`×`height`; размер меняется тем же фильтром,
+/// что и перед кодированием
+pub(crate) fn fit(image: &DynamicImage, width: u32, height: u32) -> RgbImage {
+    if (image.width(), image.height()) == (width, height) {
+        image.to_rgb8()
+    } else {
+        image
+            .resize_exact(width, height, FilterType::Lanczos3)
+            .to_rgb8()
+    }
+}
+
+/// Средний SSIM по всем пикселям; локальные статистики считаются гауссовым окном
+fn ssim(x: &[f64], y: &[f64], width: usize, height: usize) -> f64 {
+    if x.is_empty() {
+        return 1.0;
+    }
+
+    let product =
+        |a: &[f64], b: &[f64]| -> Vec<f64> { a.iter().zip(b).map(|(p, q)| p * q).collect() };
+    let mu_x = blur(x, width, height);
+    let mu_y = blur(y, width, height);
+    let xx = blur(&product(x, x), width, height);
+    let yy = blur(&product(y, y), width, height);
+    let xy = blur(&product(x, y), width, height);
+
+    let total: f64 = (0..x.len())
+        .map(|i| {
+            let (mx, my) = (mu_x[i], mu_y[i]);
+            let var_x = xx[i] - mx * mx;
+            let var_y = yy[i] - my * my;
+            let cov = xy[i] - mx * my;
+            ((2.0 * mx * my + SSIM_C1) * (2.0 * cov + SSIM_C2))
+                / ((mx * mx + my * my + SSIM_C1) * (var_x + var_y + SSIM_C2))
+        })
+        .sum();
+    total / x.len() as f64
+}
+
+/// Разделимое гауссово размытие; у краёв окно обрезается и перенормируется
+fn blur(data: &[f64], width: usize, height: usize) -> Vec<f64> {
+    let kernel: Vec<f64> = (0..=2 * SSIM_RADIUS)
+        .map(|k| {
+            let d = k as f64 - SSIM_RADIUS as f64;
+            (-d * d / (2.0 * SSIM_SIGMA * SSIM_SIGMA)).exp()
+        })
+        .collect();
+
+    let pass = |src: &[f64], len: usize, at: &dyn Fn(usize, usize) -> usize, lines: usize| {
+        let mut out = vec![0.0; src.len()];
+        for line in 0..lines {
+            for pos in 0..len {
+                let (mut sum, mut weight) = (0.0, 0.0);
+                for (k, w) in kernel.iter().enumerate() {
+                    let Some(p) = (pos + k).checked_sub(SSIM_RADIUS).filter(|&p| p < len) else {
+                        continue;
+                    };
+                    sum += w * src[at(line, p)];
+                    weight += w;
+                }
+                out[at(line, pos)] = sum / weight;
+            }
+        }
+        out
+    };
+
+    let rows = pass(data, width, &|y, x| y * width + x, height);
+    pass(&rows, height, &|x, y| y * width + x, width)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::random_image;
+
+    #[test]
+    fn registration_finds_shift_and_slant() {
+        let (width, height) = (320u32, 256u32);
+        let original = random_image(width, height, 3).to_rgb8();
+        // Картинка ниже на 3 строки, строки сдвинуты вправо на 5…9 пикселей с переносом
+        let decoded = RgbImage::from_fn(width, height, |x, y| {
+            let Some(row) = y.checked_sub(3) else {
+                return Rgb([0, 0, 0]);
+            };
+            let shift = 5 + (4.0 * row as f64 / (height - 1) as f64).round() as u32;
+            *original.get_pixel((x + width - shift) % width, row)
+        });
+        let (original, decoded) = (
+            DynamicImage::ImageRgb8(original),
+            DynamicImage::ImageRgb8(decoded),
+        );
+
+        let registration = register(&original, &decoded);
+        assert_eq!(registration.shift_lines, 3, "{registration:?}");
+        assert!(
+            (registration.offset_px - 5.0).abs() < 0.5,
+            "{registration:?}"
+        );
+        assert!(
+            (registration.slant_px - 4.0).abs() < 0.5,
+            "{registration:?}"
+        );
+
+        let plain = compare(&original, &decoded);
+        let (registered, _) = compare_registered(&original, &decoded, MetricSpace::Srgb);
+        assert!(
+            registered.psnr > plain.psnr + 6.0,
+            "{plain:?} {registered:?}"
+        );
+        let (same, identity) = compare_registered(&original, &original, MetricSpace::Srgb);
+        assert!(identity.is_identity(), "{identity:?}");
+        assert!(same.psnr.is_infinite());
+    }
+
+    #[test]
+    fn metric_spaces_weight_errors_differently() {
+        let (width, height) = (64u32, 64u32);
+        let original = RgbImage::from_fn(width, height, |x, y| {
+            Rgb([(x * 2) as u8, (y * 2) as u8, ((x + y) * 3 / 2) as u8])
+        });
+        // Серый сдвиг на 8 уровней меняет только яркость: в YCbCr он весит 6/8
+        let brighter = RgbImage::from_fn(width, height, |x, y| {
+            Rgb(original.get_pixel(x, y).0.map(|c| c + 8))
+        });
+        let (original, brighter) = (
+            DynamicImage::ImageRgb8(original),
+            DynamicImage::ImageRgb8(brighter),
+        );
+
+        let srgb = compare(&original, &brighter);
+        assert!((srgb.mse - 64.0).abs() < 1e-9, "{srgb:?}");
+        let ycbcr = compare_in(&original, &brighter, MetricSpace::YCbCr);
+        assert!((ycbcr.mse - 48.0).abs() < 1e-6, "{ycbcr:?}");
+        assert!(ycbcr.psnr > srgb.psnr);
+        // В линейном свете сдвиг в тенях почти незаметен
+        let linear = compare_in(&original, &brighter, MetricSpace::Linear);
+        assert!(linear.mse < srgb.mse, "{linear:?}");
+        for &space in MetricSpace::ALL {
+            let same = compare_in(&original, &original, space);
+            assert!(same.psnr.is_infinite() && (same.ssim - 1.0).abs() < 1e-9);
+        }
+    }
+}
@@ -0,0 +1,202 @@
+use crate::messages;
+use std::ops::Range;
+
+/// Частота синхроимпульса, Гц
+pub const SYNC_HZ: f32 = 1200.0;
+/// Частота уровня чёрного, Гц
+pub const BLACK_HZ: f32 = 1500.0;
+/// Частота уровня белого, Гц
+pub const WHITE_HZ: f32 = 2300.0;
+/// Частота лидер-тона калибровочного заголовка, Гц
+pub const LEADER_HZ: f32 = 1900.0;
+/// Частота бита VIS «1», Гц
+pub const VIS_ONE_HZ: f32 = 1100.0;
+/// Частота бита VIS «0», Гц
+pub const VIS_ZERO_HZ: f32 = 1300.0;
+
+/// Длительность одного лидер-тона заголовка, мс
+pub const LEADER_MS: u32 = 300;
+/// Длительность разрыва между лидер-тонами, мс
+pub const BREAK_MS: u32 = 10;
+/// Длительность одного бита VIS, мс
+pub const VIS_BIT_MS: u32 = 30;
+/// Длительность заголовка от начала лидер-тона до первой строки: два лидер-тона, разрыв
+/// и десять бит VIS вместе со стартовым и стоповым, мс
+pub const HEADER_MS: u32 = 2 * LEADER_MS + BREAK_MS + 10 * VIS_BIT_MS;
+
+/// Временная структура строки режима SSTV
+#[derive(Clone, Debug)]
+pub struct ModeSpec {
+    pub name: &'static str,
+    pub vis_code: u8,
+    pub width: u32,
+    pub height: u32,
+    /// Синхроимпульс в начале строки, мкс
+    pub sync_us: f64,
+    /// Площадка после синхроимпульса, мкс
+    pub porch_us: f64,
+    /// Разделитель после каждого цветового канала, мкс
+    pub separator_us: f64,
+    /// Длительность одного пикселя, мкс
+    pub pixel_us: f64,
+    /// Порядок передачи цветовых каналов (индексы R=0, G=1, B=2)
+    pub channel_order: [usize; 3],
+}
+
+impl ModeSpec {
+    /// Длительность одного цветового канала строки вместе с разделителем, мкс
+    pub fn channel_us(&self) -> f64 {
+        self.width as f64 * self.pixel_us + self.separator_us
+    }
+
+    /// Полная длительность строки, мкс
+    pub fn line_us(&self) -> f64 {
+        self.sync_us + self.porch_us + 3.0 * self.channel_us()
+    }
+
+    /// Номинальная разметка сигнала: первая строка начинается с сэмпла `image_start`
+    pub fn timing(&self, sample_rate: u32, image_start: usize) -> ModeTiming {
+        ModeTiming {
+            spec: self.clone(),
+            image_start,
+            samples_per_us: sample_rate as f64 * 1e-6,
+        }
+    }
+
+    /// Разметка по фактическому диапазону тела изображения. Длительность строки берётся
+    /// из `span`, поэтому учитывает округление тонов и растяжение времени кодера
+    pub fn timing_from_span(&self, span: Range<usize>) -> ModeTiming {
+        let total_us = self.height as f64 * self.line_us();
+        ModeTiming {
+            spec: self.clone(),
+            image_start: span.start,
+            samples_per_us: span.len() as f64 / total_us,
+        }
+    }
+
+    /// Где на изображении ляжет копия сигнала, запаздывающая на `delay_ms`: целые строки,
+    /// затем цветовые каналы строки и пиксели внутри канала. Синхроимпульс и площадка
+    /// в каналы не входят, их долю остатка получает последний канал
+    pub fn scan_offset(&self, delay_ms: f32) -> ScanOffset {
+        let delay_us = delay_ms.max(0.0) as f64 * 1000.0;
+        let lines = (delay_us / self.line_us()) as u32;
+        let within = delay_us - lines as f64 * self.line_us();
+        let channels = ((within / self.channel_us()) as u32).min(2);
+        let pixels = (within - channels as f64 * self.channel_us()) / self.pixel_us;
+        ScanOffset {
+            lines,
+            channels,
+            pixels: pixels as f32,
+        }
+    }
+}
+
+/// Сдвиг запаздывающей копии на изображении режима, см. [`ModeSpec::scan_offset`]
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct ScanOffset {
+    /// Сдвиг вниз, строк
+    pub lines: u32,
+    /// Сдвиг на следующие цветовые каналы строки, 0–2: копия одного цвета ложится в другой
+    pub channels: u32,
+    /// Сдвиг вправо внутри канала, пикселей
+    pub pixels: f32,
+}
+
+impl ScanOffset {
+    pub fn describe(&self) -> String {
+        let mut parts = Vec::new();
+        if self.lines > 0 {
+            parts.push(messages::offset_lines(self.lines));
+        }
+        if self.channels > 0 {
+            parts.push(messages::offset_channels(self.channels));
+        }
+        parts.push(messages::offset_pixels(self.pixels));
+        messages::estimate_ghost_offset(&parts.join(" + "))
+    }
+}
+
+pub const MARTIN_M1: ModeSpec = ModeSpec {
+    name: "Martin M1",
+    vis_code: 44,
+    width: 320,
+    height: 256,
+    sync_us: 4862.0,
+    porch_us: 572.0,
+    separator_us: 572.0,
+    pixel_us: 457.6,
+    channel_order: [1, 2, 0],
+};
+
+/// Соответствие сэмплов сигнала и координат изображения режима
+#[derive(Clone, Debug)]
+pub struct ModeTiming {
+    pub spec: ModeSpec,
+    /// Сэмпл начала синхроимпульса первой строки
+    pub image_start: usize,
+    /// Сэмплов на микросекунду передачи
+    samples_per_us: f64,
+}
+
+impl ModeTiming {
+    /// Длительность строки, сэмплов (дробная — строки не выровнены по сэмплам)
+    pub fn samples_per_line(&self) -> f64 {
+        self.spec.line_us() * self.samples_per_us
+    }
+
+    /// Сэмплы всех строк изображения
+    pub fn image_span(&self) -> Range<usize> {
+        let len = (self.spec.height as f64 * self.samples_per_line()).round() as usize;
+        self.image_start..self.image_start + len
+    }
+
+    /// Строка изображения, которую передаёт сэмпл `idx`; `None` вне тела изображения
+    pub fn sample_to_line(&self, idx: usize) -> Option<u32> {
+        if !self.image_span().contains(&idx) {
+            return None;
+        }
+        let line = (idx - self.image_start) as f64 / self.samples_per_line();
+        Some((line as u32).min(self.spec.height - 1))
+    }
+
+    /// Сэмплы строки `y`, начиная с её синхроимпульса
+    pub fn line_to_sample_range(&self, y: u32) -> Range<usize> {
+        let at = |line: f64| self.image_start + (line * self.samples_per_line()).round() as usize;
+        at(y as f64)..at(y as f64 + 1.0)
+    }
+
+    /// Пиксель, который передаёт сэмпл `idx`: `(x, y, канал)`, где канал — индекс R=0, G=1,
+    /// B=2. `None` для синхроимпульса, площадки, разделителей и сэмплов вне изображения
+    pub fn sample_to_pixel(&self, idx: usize) -> Option<(u32, u32, usize)> {
+        let y = self.sample_to_line(idx)?;
+        let line_start = self.image_start as f64 + y as f64 * self.samples_per_line();
+        let us = (idx as f64 - line_start) / self.samples_per_us
+            - self.spec.sync_us
+            - self.spec.porch_us;
+        if us < 0.0 {
+            return None;
+        }
+
+        let slot = (us / self.spec.channel_us()) as usize;
+        let within = us - slot as f64 * self.spec.channel_us();
+        let x = (within / self.spec.pixel_us) as u32;
+        (slot < 3 && x < self.spec.width).then(|| (x, y, self.spec.channel_order[slot]))
+    }
+
+    /// Сэмплы пикселя `(x, y)` в канале `channel` (R=0, G=1, B=2)
+    pub fn pixel_to_sample_range(&self, x: u32, y: u32, channel: usize) -> Range<usize> {
+        let slot = self
+            .spec
+            .channel_order
+            .iter()
+            .position(|&c| c == channel)
+            .unwrap_or(0);
+        let us = self.spec.sync_us
+            + self.spec.porch_us
+            + slot as f64 * self.spec.channel_us()
+            + x as f64 * self.spec.pixel_us;
+        let line_start = self.image_start as f64 + y as f64 * self.samples_per_line();
+        let at = |us: f64| (line_start + us * self.samples_per_us).round() as usize;
+        at(us)..at(us + self.spec.pixel_us)
+    }
+}
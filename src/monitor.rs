@@ -0,0 +1,750 @@
+use crate::audio;
+use crate::decoder::{Decoder, DecoderParams, VisReport};
+use crate::dsp::{ResampleStream, Resampler};
+use crate::error::{Result, SstvError};
+use crate::live;
+use crate::location::{self, Location, LocationSource, Stamp};
+use crate::messages;
+use crate::metadata::{self, Metadata};
+use crate::native_decoder::StreamDecoder;
+use crate::sink::ImageSink;
+use crate::squelch::{Squelch, SquelchParams};
+use cpal::SampleFormat;
+use cpal::traits::{DeviceTrait, StreamTrait};
+use image::RgbImage;
+use rsstv::SAMPLE_RATE;
+use std::collections::{BTreeMap, VecDeque};
+use std::io::Read;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Блок, которым приёмник читает источник, мс: столько же ждёт новая строка на экране
+const BLOCK_MS: u32 = 50;
+/// Наибольшая очередь входа устройства, с: если декодер отстаёт дольше, старые сэмплы
+/// выбрасываются
+const MAX_BACKLOG_SECS: f32 = 2.0;
+/// Сколько сигнала после изображения ждать посылку метаданных, с: пауза, позывной и пары
+const METADATA_WAIT_SECS: f32 = 20.0;
+/// Как часто искать метаданные в сигнале после изображения, с
+const METADATA_SCAN_SECS: f32 = 1.0;
+/// Пауза перед новым подключением к gpsd после обрыва, с
+const GPSD_RETRY_SECS: u64 = 5;
+/// Сколько сигнала до открытия шумоподавителя отдать декодеру, мс: лидер-тон, по
+/// которому он открылся, нужен для поиска заголовка
+const PREROLL_MS: u32 = 1000;
+
+/// Формат сырых сэмплов PCM: моно, little-endian
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum PcmFormat {
+    /// 16 бит со знаком — вывод `arecord -f S16_LE` и `rtl_fm`
+    #[default]
+    S16Le,
+    /// 32-битные числа с плавающей точкой
+    F32Le,
+}
+
+impl PcmFormat {
+    pub const ALL: &'static [PcmFormat] = &[PcmFormat::S16Le, PcmFormat::F32Le];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            PcmFormat::S16Le => messages::pcm_format_s16le(),
+            PcmFormat::F32Le => messages::pcm_format_f32le(),
+        }
+    }
+
+    /// Байт на сэмпл
+    pub fn sample_bytes(&self) -> usize {
+        match self {
+            PcmFormat::S16Le => 2,
+            PcmFormat::F32Le => 4,
+        }
+    }
+
+    /// Переводит сэмплы из `bytes` в числа в долях полной шкалы
+    fn decode(&self, bytes: &[u8], out: &mut Vec<f32>) {
+        match self {
+            PcmFormat::S16Le => out.extend(
+                bytes
+                    .chunks_exact(2)
+                    .map(|b| i16::from_le_bytes([b[0], b[1]]) as f32 / 32768.0),
+            ),
+            PcmFormat::F32Le => out.extend(
+                bytes
+                    .chunks_exact(4)
+                    .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]])),
+            ),
+        }
+    }
+}
+
+impl clap::ValueEnum for PcmFormat {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[Self::S16Le, Self::F32Le]
+    }
+
+    fn to_possible_value<'a>(&self) -> Option<clap::builder::PossibleValue> {
+        Some(match self {
+            Self::S16Le => clap::builder::PossibleValue::new("s16le"),
+            Self::F32Le => clap::builder::PossibleValue::new("f32le"),
+        })
+    }
+}
+
+/// Откуда приёмник берёт звук
+#[derive(Clone, Debug, PartialEq)]
+pub enum MonitorSource {
+    /// Устройство ввода; `None` — системное
+    Device(Option<String>),
+    /// Сырые моно-сэмплы из файла или именованного канала, `-` — стандартный ввод:
+    /// например, `rtl_fm … > fifo`. Конец файла заканчивает приём
+    Pcm {
+        path: PathBuf,
+        sample_rate: u32,
+        format: PcmFormat,
+    },
+}
+
+/// Параметры непрерывного приёма
+#[derive(Clone, Debug)]
+pub struct MonitorParams {
+    pub source: MonitorSource,
+    /// Параметры встроенного декодера; окно поиска заголовка не используется — приёмник
+    /// ищет следующее изображение всё время, пока слушает
+    pub decoder: DecoderParams,
+    /// Сколько последнего сигнала хранить, с, см. [`MonitorState::recent`]
+    pub history_secs: f32,
+    /// Место приёмника для отметок принятых изображений; без него отмечается только время
+    pub location: Option<LocationSource>,
+    /// Когда декодер слушает сигнал: без шумоподавителя — всегда
+    pub squelch: SquelchParams,
+}
+
+impl Default for MonitorParams {
+    fn default() -> Self {
+        Self {
+            source: MonitorSource::Device(None),
+            decoder: DecoderParams::default(),
+            history_secs: 30.0,
+            location: None,
+            squelch: SquelchParams::default(),
+        }
+    }
+}
+
+/// Изображение, принятое приёмником
+#[derive(Clone, Debug)]
+pub struct ReceivedImage {
+    pub image: RgbImage,
+    /// Принято строк; меньше высоты, если приём сброшен посреди изображения
+    pub lines: u32,
+    pub vis: Option<VisReport>,
+    /// Метаданные из посылки FSK, пришедшей вслед за изображением
+    pub metadata: BTreeMap<String, String>,
+    /// Время конца приёма и место приёмника
+    pub stamp: Stamp,
+}
+
+impl ReceivedImage {
+    /// Отметка приёма, строки, VIS и метаданные одной строкой для журнала и подписей
+    pub fn describe(&self) -> String {
+        let mut details = vec![
+            messages::monitor_lines(self.lines),
+            self.vis
+                .as_ref()
+                .map_or(messages::cli_vis_missing().to_string(), VisReport::describe),
+        ];
+        if !self.metadata.is_empty() {
+            details.push(metadata::describe(&self.metadata));
+        }
+        format!("{}: {}", self.stamp.describe(), details.join(", "))
+    }
+
+    /// Пары для текстовых блоков файла: метаданные передачи и отметка приёма
+    pub fn text_fields(&self) -> BTreeMap<String, String> {
+        let mut fields = self.metadata.clone();
+        fields.extend(self.stamp.fields());
+        fields
+    }
+}
+
+/// Что приёмник показывает интерфейсу; обновляется после каждого блока
+#[derive(Clone, Debug, Default)]
+pub struct MonitorState {
+    /// Поток приёма ещё работает
+    pub running: bool,
+    /// Ошибка, остановившая приём
+    pub error: Option<String>,
+    /// Принимаемое изображение: строки ниже `lines` ещё чёрные. `None`, пока ищется
+    /// заголовок
+    pub image: Option<RgbImage>,
+    pub lines: u32,
+    /// VIS текущего изображения
+    pub vis: Option<VisReport>,
+    /// Растёт при каждом изменении `image`: интерфейсу незачем перерисовывать текстуру
+    /// без него
+    pub revision: u64,
+    /// СКЗ последнего блока, дБFS
+    pub level_db: f32,
+    /// Шумоподавитель открыт: декодер получает сигнал
+    pub squelch_open: bool,
+    /// Доля мощности в полосе лидер-тона в последнем блоке, дБ
+    pub leader_db: f32,
+    /// Последние `history_secs` секунд сигнала на [`SAMPLE_RATE`]
+    pub recent: VecDeque<f32>,
+    /// Законченные изображения, которые ещё не забрал интерфейс, см. [`Monitor::take_received`]
+    pub received: Vec<ReceivedImage>,
+    /// Сколько сигнала принято, с
+    pub elapsed_secs: f32,
+    /// Место приёмника: заданное или последний фикс gpsd
+    pub location: Option<Location>,
+}
+
+impl MonitorState {
+    /// Откладывает текущее изображение в принятые, если в нём есть строки
+    fn complete(&mut self, lines: u32) {
+        if let Some(received) = self.take_image(lines) {
+            self.received.push(received);
+        }
+    }
+
+    /// Забирает текущее изображение, если в нём есть строки, и готовится к следующему
+    fn take_image(&mut self, lines: u32) -> Option<ReceivedImage> {
+        let received = match self.image.take() {
+            Some(image) if lines > 0 => {
+                let stamp = Stamp::now(self.location);
+                tracing::info!(
+                    "{}",
+                    messages::monitor_image(
+                        lines,
+                        &self
+                            .vis
+                            .as_ref()
+                            .map_or(messages::cli_vis_missing().to_string(), VisReport::describe),
+                        &stamp.describe()
+                    )
+                );
+                Some(ReceivedImage {
+                    image,
+                    lines,
+                    vis: self.vis.clone(),
+                    metadata: BTreeMap::new(),
+                    stamp,
+                })
+            }
+            _ => None,
+        };
+        self.lines = 0;
+        self.vis = None;
+        self.revision += 1;
+        received
+    }
+
+    /// Откладывает изображение, дождавшееся метаданных, в принятые
+    fn release(&mut self, received: ReceivedImage) {
+        if !received.metadata.is_empty() {
+            tracing::info!(
+                "{}",
+                messages::monitor_metadata(&metadata::describe(&received.metadata))
+            );
+        }
+        self.received.push(received);
+    }
+}
+
+/// Непрерывный приём: звук с устройства ввода или из сырого PCM идёт блоками в потоковый
+/// декодер, и изображение рисуется строка за строкой, пока передаётся. После каждого
+/// изображения приёмник снова ищет заголовок. Работает в своём потоке до [`Monitor::stop`]
+/// или конца PCM; состояние читается через [`Monitor::state`]
+pub struct Monitor {
+    state: Arc<Mutex<MonitorState>>,
+    stop: Arc<AtomicBool>,
+    reset: Arc<AtomicBool>,
+}
+
+impl Monitor {
+    /// Запускает приём; `notify` вызывается из потока приёма после каждого блока —
+    /// например, чтобы перерисовать окно
+    pub fn start(params: MonitorParams, notify: impl Fn() + Send + 'static) -> Self {
+        let state = Arc::new(Mutex::new(MonitorState {
+            running: true,
+            ..Default::default()
+        }));
+        let stop = Arc::new(AtomicBool::new(false));
+        let reset = Arc::new(AtomicBool::new(false));
+        match &params.location {
+            Some(LocationSource::Static(location)) => {
+                state.lock().unwrap().location = Some(*location)
+            }
+            Some(LocationSource::Gpsd(addr)) => {
+                let (addr, state, stop) = (addr.clone(), Arc::clone(&state), Arc::clone(&stop));
+                std::thread::spawn(move || follow_gpsd(&addr, &state, &stop));
+            }
+            None => {}
+        }
+
+        let worker = Worker {
+            state: Arc::clone(&state),
+            stop: Arc::clone(&stop),
+            reset: Arc::clone(&reset),
+            history: (params.history_secs.max(0.0) * SAMPLE_RATE as f32) as usize,
+            notify: Box::new(notify),
+        };
+        std::thread::spawn(move || {
+            let started = Instant::now();
+            let result = worker.run(&params);
+            let mut state = worker.state.lock().unwrap();
+            state.running = false;
+            if let Err(e) = result {
+                tracing::error!("{}", e);
+                state.error = Some(e.to_string());
+            }
+            tracing::info!(
+                "{}",
+                messages::monitor_stopped(started.elapsed().as_secs_f32())
+            );
+            drop(state);
+            (worker.notify)();
+        });
+
+        Self { state, stop, reset }
+    }
+
+    /// Текущее состояние приёма
+    pub fn state(&self) -> std::sync::MutexGuard<'_, MonitorState> {
+        self.state.lock().unwrap()
+    }
+
+    /// Забирает законченные изображения
+    pub fn take_received(&self) -> Vec<ReceivedImage> {
+        std::mem::take(&mut self.state().received)
+    }
+
+    /// Бросает текущее изображение — принятые строки уходят в законченные — и снова
+    /// ищет заголовок
+    pub fn reset(&self) {
+        self.reset.store(true, Ordering::Relaxed);
+    }
+
+    /// Останавливает приём, не дожидаясь потока: чтение канала PCM может ждать
+    /// писателя, и поток выйдет после следующего блока
+    pub fn stop(&self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.state().running
+    }
+}
+
+impl Drop for Monitor {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// Держит [`MonitorState::location`] по фиксам gpsd, пока идёт приём; после обрыва
+/// связи переподключается через [`GPSD_RETRY_SECS`]
+fn follow_gpsd(addr: &str, state: &Mutex<MonitorState>, stop: &AtomicBool) {
+    let running = || !stop.load(Ordering::Relaxed) && state.lock().unwrap().running;
+    while running() {
+        let result = location::watch_gpsd(addr, running, |fix| {
+            let mut state = state.lock().unwrap();
+            if state.location.is_none_or(|last| last.grid() != fix.grid()) {
+                tracing::info!("{}", messages::monitor_location(&fix.describe()));
+            }
+            state.location = Some(fix);
+        });
+        if let Err(e) = result {
+            tracing::warn!("{}", e);
+        }
+        let retry = Instant::now() + Duration::from_secs(GPSD_RETRY_SECS);
+        while running() && Instant::now() < retry {
+            std::thread::sleep(Duration::from_millis(BLOCK_MS as u64));
+        }
+    }
+}
+
+/// Поток приёма
+struct Worker {
+    state: Arc<Mutex<MonitorState>>,
+    stop: Arc<AtomicBool>,
+    reset: Arc<AtomicBool>,
+    /// Длина [`MonitorState::recent`], сэмплов
+    history: usize,
+    notify: Box<dyn Fn() + Send>,
+}
+
+impl Worker {
+    fn run(&self, params: &MonitorParams) -> Result<()> {
+        let decoder = StreamDecoder::new(DecoderParams {
+            search_window_ms: 0,
+            ..params.decoder.clone()
+        });
+        let squelch = Squelch::new(params.squelch, SAMPLE_RATE as u32);
+        if params.squelch.enabled {
+            tracing::info!("{}", params.squelch.describe());
+        }
+        match &params.source {
+            MonitorSource::Device(name) => self.run_device(name.as_deref(), decoder, squelch),
+            MonitorSource::Pcm {
+                path,
+                sample_rate,
+                format,
+            } => self.run_pcm(path, *sample_rate, *format, decoder, squelch),
+        }
+    }
+
+    /// Поток cpal открывается здесь же: на части систем его нельзя передать между потоками
+    fn run_device(
+        &self,
+        name: Option<&str>,
+        decoder: StreamDecoder,
+        squelch: Squelch,
+    ) -> Result<()> {
+        let device = audio::find_input_device(name)?;
+        let supported = device
+            .default_input_config()
+            .map_err(|e| audio::device_error(messages::device_input_config(), e))?;
+        let format = supported.sample_format();
+        let config: cpal::StreamConfig = supported.into();
+        let sample_rate = config.sample_rate.0;
+
+        let backlog = (MAX_BACKLOG_SECS * sample_rate as f32) as usize;
+        let captured = Arc::new(Mutex::new(VecDeque::new()));
+        let dropped = Arc::new(AtomicUsize::new(0));
+        let stream = match format {
+            SampleFormat::F32 => {
+                live::build_input::<f32>(&device, &config, &captured, &dropped, backlog)
+            }
+            SampleFormat::I16 => {
+                live::build_input::<i16>(&device, &config, &captured, &dropped, backlog)
+            }
+            SampleFormat::U16 => {
+                live::build_input::<u16>(&device, &config, &captured, &dropped, backlog)
+            }
+            other => Err(live::unsupported_format(other)),
+        }?;
+        stream
+            .play()
+            .map_err(|e| audio::device_error(messages::device_start_recording(), e))?;
+        tracing::info!(
+            "{}",
+            messages::monitor_started(&device.name().unwrap_or_default(), sample_rate)
+        );
+
+        let block_len = block_len(sample_rate);
+        let idle = Duration::from_millis(BLOCK_MS as u64 / 4);
+        let mut receiver = Receiver::new(decoder, squelch, sample_rate);
+        while !self.stop.load(Ordering::Relaxed) {
+            let block = {
+                let mut queue = captured.lock().unwrap();
+                (queue.len() >= block_len).then(|| queue.drain(..block_len).collect::<Vec<f32>>())
+            };
+            let Some(block) = block else {
+                std::thread::sleep(idle);
+                continue;
+            };
+            self.step(&mut receiver, &block)?;
+        }
+        receiver.release_pending(&self.state);
+        Ok(())
+    }
+
+    fn run_pcm(
+        &self,
+        path: &std::path::Path,
+        sample_rate: u32,
+        format: PcmFormat,
+        decoder: StreamDecoder,
+        squelch: Squelch,
+    ) -> Result<()> {
+        if sample_rate == 0 {
+            return Err(SstvError::invalid(
+                "sample_rate",
+                messages::rate_not_positive(),
+            ));
+        }
+        let mut input: Box<dyn Read> = if path.as_os_str() == "-" {
+            Box::new(std::io::stdin().lock())
+        } else {
+            Box::new(std::fs::File::open(path).map_err(|e| {
+                SstvError::io(messages::open_failed(&path.display().to_string()), e)
+            })?)
+        };
+        tracing::info!(
+            "{}",
+            messages::monitor_started(&path.display().to_string(), sample_rate)
+        );
+
+        let mut bytes = vec![0; block_len(sample_rate) * format.sample_bytes()];
+        let mut filled = 0;
+        let mut block = Vec::new();
+        let mut receiver = Receiver::new(decoder, squelch, sample_rate);
+        while !self.stop.load(Ordering::Relaxed) {
+            let read = input.read(&mut bytes[filled..]).map_err(|e| {
+                SstvError::io(messages::read_failed(&path.display().to_string()), e)
+            })?;
+            if read == 0 {
+                break;
+            }
+            filled += read;
+            // Неполный сэмпл ждёт следующего чтения
+            let whole = filled / format.sample_bytes() * format.sample_bytes();
+            block.clear();
+            format.decode(&bytes[..whole], &mut block);
+            bytes.copy_within(whole..filled, 0);
+            filled -= whole;
+            self.step(&mut receiver, &block)?;
+        }
+        receiver.finish(&self.state)?;
+        (self.notify)();
+        Ok(())
+    }
+
+    /// Обрабатывает блок на частоте источника и сообщает интерфейсу
+    fn step(&self, receiver: &mut Receiver, block: &[f32]) -> Result<()> {
+        if self.reset.swap(false, Ordering::Relaxed) {
+            receiver.reset(&self.state);
+        }
+        receiver.push(block, &self.state, self.history)?;
+        (self.notify)();
+        Ok(())
+    }
+}
+
+/// Сэмплов в блоке чтения на частоте `sample_rate`
+fn block_len(sample_rate: u32) -> usize {
+    ((sample_rate * BLOCK_MS / 1000) as usize).max(1)
+}
+
+/// Передискретизация на частоту декодера, шумоподавитель и сам декодер
+struct Receiver {
+    resampler: ResampleStream,
+    decoder: StreamDecoder,
+    squelch: Squelch,
+    /// Последние [`PREROLL_MS`] мс сигнала, пока шумоподавитель закрыт
+    preroll: VecDeque<f32>,
+    resampled: Vec<f32>,
+    /// Принято сэмплов на [`SAMPLE_RATE`]
+    received: usize,
+    /// Законченное изображение, ждущее посылку метаданных
+    pending: Option<Pending>,
+}
+
+/// Законченное изображение и сигнал после него, в котором ищутся метаданные
+struct Pending {
+    received: ReceivedImage,
+    /// Сигнал после изображения на [`SAMPLE_RATE`]
+    tail: Vec<f32>,
+    /// Длина `tail` при последнем поиске
+    scanned: usize,
+}
+
+impl Receiver {
+    fn new(decoder: StreamDecoder, squelch: Squelch, sample_rate: u32) -> Self {
+        Self {
+            resampler: ResampleStream::new(Resampler::new(sample_rate, SAMPLE_RATE as u32)),
+            decoder,
+            squelch,
+            preroll: VecDeque::new(),
+            resampled: Vec::new(),
+            received: 0,
+            pending: None,
+        }
+    }
+
+    fn push(&mut self, block: &[f32], state: &Mutex<MonitorState>, history: usize) -> Result<()> {
+        self.resampled.clear();
+        self.resampler.push(block, &mut self.resampled);
+        self.received += self.resampled.len();
+
+        let receiving = state.lock().unwrap().image.is_some();
+        let closed = !self.squelch.is_open();
+        if self.squelch.update(&self.resampled, receiving) {
+            let mut sink = StateSink {
+                state,
+                pending: &mut self.pending,
+            };
+            let result = if closed {
+                // Только что открылся: декодер получает и лидер-тон до этого блока
+                self.preroll.extend(&self.resampled);
+                let signal: Vec<f32> = self.preroll.drain(..).collect();
+                self.decoder.push(&signal, &mut sink)
+            } else {
+                self.decoder.push(&self.resampled, &mut sink)
+            };
+            self.settle(result, state)?;
+            self.reject_without_vis(state);
+        } else {
+            if !closed {
+                self.decoder.reset();
+            }
+            self.preroll.extend(&self.resampled);
+            let excess = self
+                .preroll
+                .len()
+                .saturating_sub((PREROLL_MS * SAMPLE_RATE as u32 / 1000) as usize);
+            self.preroll.drain(..excess);
+        }
+        self.watch_metadata(state, false);
+
+        let mut state = state.lock().unwrap();
+        let power =
+            self.resampled.iter().map(|s| s * s).sum::<f32>() / self.resampled.len().max(1) as f32;
+        state.level_db = 10.0 * power.max(1e-12).log10();
+        state.squelch_open = self.squelch.is_open();
+        state.leader_db = self.squelch.leader_db();
+        state.recent.extend(&self.resampled);
+        let excess = state.recent.len().saturating_sub(history);
+        state.recent.drain(..excess);
+        state.elapsed_secs = self.received as f32 / SAMPLE_RATE as f32;
+        Ok(())
+    }
+
+    /// Конец сигнала: дописывает строки, на которые хватает принятого
+    fn finish(&mut self, state: &Mutex<MonitorState>) -> Result<()> {
+        self.resampled.clear();
+        self.resampler.finish(&mut self.resampled);
+        let mut sink = StateSink {
+            state,
+            pending: &mut self.pending,
+        };
+        let result = if self.squelch.is_open() {
+            self.decoder.push(&self.resampled, &mut sink)
+        } else {
+            Ok(())
+        }
+        .and_then(|()| self.decoder.finish(&mut sink));
+        self.settle(result, state)?;
+        self.watch_metadata(state, true);
+        Ok(())
+    }
+
+    /// Копит сигнал после законченного изображения и ищет в нём метаданные. Изображение
+    /// уходит в принятые, когда они найдены, ожидание вышло, начался новый приём или
+    /// сигнал кончился (`end`)
+    fn watch_metadata(&mut self, state: &Mutex<MonitorState>, end: bool) {
+        let Some(pending) = &mut self.pending else {
+            return;
+        };
+        pending.tail.extend(&self.resampled);
+        let step = (METADATA_SCAN_SECS * SAMPLE_RATE as f32) as usize;
+        if end || pending.tail.len() >= pending.scanned + step {
+            pending.scanned = pending.tail.len();
+            pending.received.metadata = Metadata::read(&pending.tail);
+        }
+        let waited = pending.tail.len() as f32 >= METADATA_WAIT_SECS * SAMPLE_RATE as f32;
+        let mut state = state.lock().unwrap();
+        let found = !pending.received.metadata.is_empty();
+        if (end || waited || found || state.image.is_some())
+            && let Some(pending) = self.pending.take()
+        {
+            state.release(pending.received);
+        }
+    }
+
+    /// Отдаёт ждущее изображение в принятые, не дожидаясь метаданных
+    fn release_pending(&mut self, state: &Mutex<MonitorState>) {
+        if let Some(pending) = self.pending.take() {
+            state.lock().unwrap().release(pending.received);
+        }
+    }
+
+    /// Разбирает итог шага декодера: законченное изображение или упущенный заголовок
+    /// означают, что пора искать следующее
+    fn settle(&mut self, result: Result<()>, state: &Mutex<MonitorState>) -> Result<()> {
+        match result {
+            Ok(()) if self.decoder.is_done() => {
+                self.decoder.reset();
+                self.squelch.close();
+            }
+            Ok(()) => {
+                let vis = self.decoder.vis().cloned();
+                let mut state = state.lock().unwrap();
+                if state.vis.is_none() && vis.is_some() {
+                    state.vis = vis;
+                }
+            }
+            Err(SstvError::DecodeNotFound(_)) => self.reset(state),
+            Err(e) => return Err(e),
+        }
+        Ok(())
+    }
+
+    /// Бросает текущее изображение и снова ищет заголовок
+    fn reset(&mut self, state: &Mutex<MonitorState>) {
+        let mut state = state.lock().unwrap();
+        let lines = state.lines;
+        state.complete(lines);
+        self.decoder.reset();
+        self.squelch.close();
+    }
+
+    /// С [`SquelchParams::require_vis`] бросает начатое изображение, если его VIS не
+    /// прочитан или не сошлась чётность: такой заголовок скорее нашёлся в шуме
+    fn reject_without_vis(&mut self, state: &Mutex<MonitorState>) {
+        let params = self.squelch.params();
+        if !params.enabled || !params.require_vis {
+            return;
+        }
+        let mut state = state.lock().unwrap();
+        if state.image.is_none() || state.vis.as_ref().is_some_and(VisReport::parity_ok) {
+            return;
+        }
+        tracing::info!("{}", messages::monitor_squelch_rejected());
+        state.image = None;
+        state.lines = 0;
+        state.vis = None;
+        state.revision += 1;
+        self.decoder.reset();
+        self.squelch.close();
+    }
+}
+
+/// Приёмник строк, рисующий их прямо в [`MonitorState::image`]. Законченное изображение
+/// ждёт метаданных в `pending`
+struct StateSink<'a> {
+    state: &'a Mutex<MonitorState>,
+    pending: &'a mut Option<Pending>,
+}
+
+impl ImageSink for StateSink<'_> {
+    fn begin(&mut self, width: u32, height: u32) -> Result<()> {
+        let mut state = self.state.lock().unwrap();
+        state.image = Some(RgbImage::new(width, height));
+        state.lines = 0;
+        state.revision += 1;
+        Ok(())
+    }
+
+    fn write_row(&mut self, y: u32, row: &[u8]) -> Result<()> {
+        let mut state = self.state.lock().unwrap();
+        if let Some(image) = &mut state.image {
+            let stride = image.width() as usize * 3;
+            let start = y as usize * stride;
+            let Some(dst) = image.get_mut(start..start + stride) else {
+                return Err(SstvError::invalid("y", messages::row_outside(y)));
+            };
+            dst.copy_from_slice(&row[..stride]);
+        }
+        state.lines = state.lines.max(y + 1);
+        state.revision += 1;
+        Ok(())
+    }
+
+    fn finish(&mut self, lines: u32) -> Result<()> {
+        let mut state = self.state.lock().unwrap();
+        if let Some(previous) = self.pending.take() {
+            state.release(previous.received);
+        }
+        *self.pending = state.take_image(lines).map(|received| Pending {
+            received,
+            tail: Vec::new(),
+            scanned: 0,
+        });
+        Ok(())
+    }
+}
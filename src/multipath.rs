@@ -0,0 +1,290 @@
+use crate::buffer::SampleBuffer;
+use crate::dsp::{self, ConvolutionBackend};
+use crate::effect::{
+    self, BlockProcessor, Bypass, ChannelEffect, EffectContext, EffectEstimate, EffectMix,
+    StreamContext,
+};
+use crate::error::{Result, SstvError};
+use crate::messages;
+use crate::processor::ProcessingParams;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+/// Один путь распространения: задержанная и ослабленная копия основного сигнала
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MultipathTap {
+    /// Задержка относительно прямого сигнала, мс
+    pub delay_ms: f32,
+    /// Коэффициент усиления копии (может быть отрицательным)
+    pub gain: f32,
+    /// Сдвиг фазы несущей, градусы
+    pub phase_deg: f32,
+}
+
+impl FromStr for MultipathTap {
+    type Err = SstvError;
+
+    /// Разбирает путь в формате `задержка_мс:усиление[:фаза_град]`
+    fn from_str(s: &str) -> Result<Self> {
+        let parts: Vec<&str> = s.split(':').collect();
+        if !(2..=3).contains(&parts.len()) {
+            return Err(SstvError::invalid(
+                "taps",
+                messages::expected_format(messages::format_tap(), s),
+            ));
+        }
+
+        let parse = |v: &str, what: &str| -> Result<f32> {
+            v.trim()
+                .parse()
+                .map_err(|_| SstvError::invalid("taps", messages::invalid_value(what, v)))
+        };
+
+        let delay_ms = parse(parts[0], messages::value_delay())?;
+        if delay_ms < 0.0 {
+            return Err(SstvError::invalid(
+                "taps",
+                messages::tap_delay_negative(delay_ms),
+            ));
+        }
+
+        Ok(Self {
+            delay_ms,
+            gain: parse(parts[1], messages::value_gain())?,
+            phase_deg: match parts.get(2) {
+                Some(p) => parse(p, messages::value_phase())?,
+                None => 0.0,
+            },
+        })
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MultipathParams {
+    /// Дополнительные пути; прямой сигнал всегда сохраняется
+    pub taps: Vec<MultipathTap>,
+    /// Доли смешивания выхода эффекта со входом
+    pub mix: EffectMix,
+}
+
+/// Эффект многолучевого распространения (КВ): смешивает несколько задержанных копий
+/// основного сигнала со своими усилением и фазой
+pub struct MultipathProcessor {
+    #[deprecated(since = "0.1.0", note = "используйте `params()` и `set_params()`")]
+    pub params: MultipathParams,
+}
+
+impl Default for MultipathProcessor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[allow(deprecated)]
+impl MultipathProcessor {
+    pub fn new() -> Self {
+        Self {
+            params: MultipathParams::default(),
+        }
+    }
+
+    pub fn new_with_params(params: MultipathParams) -> Self {
+        Self { params }
+    }
+
+    pub fn params(&self) -> &MultipathParams {
+        &self.params
+    }
+
+    pub fn set_params(&mut self, params: MultipathParams) {
+        self.params = params;
+    }
+
+    /// Подмешивает копии сигнала. Уровень не ограничивается — это делают последующие этапы,
+    /// иначе пропадёт интерференция копий. Пути собираются в разреженные ядра свёртки для
+    /// синфазной и квадратурной составляющих
+    pub fn apply_multipath(
+        &self,
+        buffer: &mut SampleBuffer,
+        convolution: ConvolutionBackend,
+    ) -> Result<()> {
+        let sample_rate = buffer.rate;
+        let samples = &mut buffer.data;
+        if !self.is_enabled() {
+            return Ok(());
+        }
+
+        let len = samples.len();
+        let mut in_phase = Vec::new();
+        let mut quadrature = Vec::new();
+        for tap in self.params.taps.iter().filter(|t| t.gain != 0.0) {
+            let delay = (tap.delay_ms / 1000.0 * sample_rate as f32).round() as usize;
+            if delay >= len {
+                continue;
+            }
+            if in_phase.len() <= delay {
+                in_phase.resize(delay + 1, 0.0);
+                quadrature.resize(delay + 1, 0.0);
+            }
+            // Re{(x + jH{x}) · e^{jφ}} = x·cos φ − H{x}·sin φ
+            let phase = tap.phase_deg.to_radians();
+            in_phase[delay] += tap.gain * phase.cos();
+            quadrature[delay] -= tap.gain * phase.sin();
+        }
+
+        let echoes = dsp::convolve(samples, &in_phase, 0, convolution);
+        // Квадратурная составляющая нужна только при ненулевых сдвигах фазы
+        let shifted = (quadrature.iter().any(|q| q.abs() > 1e-6)).then(|| {
+            let h = dsp::hilbert(samples, convolution);
+            dsp::convolve(&h, &quadrature, 0, convolution)
+        });
+
+        for (i, (sample, echo)) in samples.iter_mut().zip(echoes).enumerate() {
+            *sample += echo + shifted.as_ref().map_or(0.0, |q| q[i]);
+        }
+
+        Ok(())
+    }
+
+    /// Заменяет список путей
+    pub fn set_taps(&mut self, taps: Vec<MultipathTap>) {
+        self.params.taps = taps;
+    }
+
+    /// Добавляет путь распространения
+    pub fn add_tap(&mut self, tap: MultipathTap) {
+        self.params.taps.push(tap);
+    }
+
+    /// Проверяет, есть ли хотя бы один слышимый путь
+    pub fn is_enabled(&self) -> bool {
+        self.params.taps.iter().any(|t| t.gain != 0.0)
+    }
+
+    /// Возвращает описание текущих настроек многолучёвости
+    pub fn describe(&self) -> String {
+        if !self.is_enabled() {
+            return messages::multipath_off().to_string();
+        }
+
+        let taps: Vec<String> = self
+            .params
+            .taps
+            .iter()
+            .map(|t| messages::multipath_tap(t.delay_ms, t.gain, t.phase_deg))
+            .collect();
+        messages::multipath_describe(&taps.join(", "))
+    }
+}
+
+#[allow(deprecated)]
+impl ChannelEffect for MultipathProcessor {
+    fn name(&self) -> &'static str {
+        messages::multipath()
+    }
+
+    fn mix(&self) -> EffectMix {
+        self.params.mix
+    }
+
+    fn apply(&self, buffer: &mut SampleBuffer, ctx: &EffectContext) -> Result<()> {
+        self.apply_multipath(buffer, ctx.convolution)
+    }
+
+    fn is_enabled(&self) -> bool {
+        MultipathProcessor::is_enabled(self)
+    }
+
+    fn describe(&self) -> String {
+        MultipathProcessor::describe(self)
+    }
+
+    fn sync_params(&mut self, params: &ProcessingParams) {
+        self.params = params.multipath.clone();
+    }
+
+    /// Мощность эха — сумма квадратов усилений путей, задержка — наибольшая из них.
+    /// Пути длиннее сигнала не слышны и не учитываются
+    fn analyze(&self, buffer: &SampleBuffer, _ctx: &EffectContext) -> EffectEstimate {
+        let duration_ms = buffer.duration_secs() as f32 * 1000.0;
+        let taps: Vec<&MultipathTap> = self
+            .params
+            .taps
+            .iter()
+            .filter(|t| t.gain != 0.0 && t.delay_ms < duration_ms)
+            .collect();
+        let power: f32 = taps.iter().map(|t| t.gain * t.gain).sum();
+        EffectEstimate {
+            added_power_db: (power > 0.0).then(|| effect::power_db(power)),
+            delay_ms: taps.iter().map(|t| t.delay_ms).reduce(f32::max),
+            ..EffectEstimate::new(self.name(), self.describe())
+        }
+    }
+
+    /// Блоками считаются только пути без сдвига фазы: квадратурной составляющей нужен
+    /// фильтр Гильберта по всему сигналу. Копии берутся из кольцевого буфера длиной
+    /// в наибольшую задержку
+    fn block_processor(&self, ctx: &StreamContext) -> Result<Box<dyn BlockProcessor>> {
+        let mut taps = Vec::new();
+        for tap in self.params.taps.iter().filter(|t| t.gain != 0.0) {
+            if tap.phase_deg.to_radians().sin().abs() > 1e-6 {
+                return Err(SstvError::invalid(
+                    "taps",
+                    messages::streaming_tap_phase(tap.phase_deg),
+                ));
+            }
+            let delay = (tap.delay_ms / 1000.0 * ctx.sample_rate as f32).round() as usize;
+            if delay < ctx.len {
+                taps.push((delay, tap.gain * tap.phase_deg.to_radians().cos()));
+            }
+        }
+        let Some(longest) = taps.iter().map(|&(delay, _)| delay).max() else {
+            return Ok(Box::new(Bypass));
+        };
+        Ok(Box::new(MultipathBlocks {
+            taps,
+            history: vec![0.0; longest + 1],
+        }))
+    }
+}
+
+/// Многолучёвость блоками: пути (задержка в сэмплах, усиление) и кольцевой буфер
+/// входного сигнала
+struct MultipathBlocks {
+    taps: Vec<(usize, f32)>,
+    history: Vec<f32>,
+}
+
+impl BlockProcessor for MultipathBlocks {
+    fn process_block(&mut self, block: &mut [f32], start: usize) -> Result<()> {
+        let size = self.history.len();
+        for (k, sample) in block.iter_mut().enumerate() {
+            let i = start + k;
+            self.history[i % size] = *sample;
+            let echo: f32 = self
+                .taps
+                .iter()
+                .filter(|&&(delay, _)| delay <= i)
+                .map(|&(delay, gain)| gain * self.history[(i - delay) % size])
+                .sum();
+            *sample += echo;
+        }
+        Ok(())
+    }
+    fn save_state(&mut self) -> Result<serde_json::Value> {
+        effect::to_block_state(&self.history)
+    }
+
+    fn restore_state(&mut self, state: &serde_json::Value) -> Result<()> {
+        let history: Vec<f32> = effect::from_block_state(state, messages::multipath())?;
+        if history.len() != self.history.len() {
+            return Err(SstvError::invalid(
+                "state",
+                messages::multipath_state_samples(history.len(), self.history.len()),
+            ));
+        }
+        self.history = history;
+        Ok(())
+    }
+}
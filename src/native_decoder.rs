@@ -0,0 +1,1111 @@
+use crate::decoder::{
+    Decoder, DecoderBackend, DecoderParams, HeaderMatch, VisReport, header_search_hop,
+    locate_header, read_vis, trim_silence,
+};
+use crate::dsp::fir::{self, Window};
+use crate::error::{Result, SstvError};
+use crate::messages;
+use crate::modes::{
+    BLACK_HZ, HEADER_MS, LEADER_HZ, LEADER_MS, MARTIN_M1, ModeSpec, SYNC_HZ, WHITE_HZ,
+};
+use crate::sink::{ImageSink, MemorySink};
+use crate::stream::DEFAULT_BLOCK_LEN;
+use image::{DynamicImage, RgbImage};
+use rsstv::SAMPLE_RATE;
+use std::collections::VecDeque;
+use std::f64::consts::PI;
+use std::ops::Range;
+use std::sync::atomic::AtomicBool;
+
+/// Коэффициент децимации после переноса в основную полосу
+const DECIMATION: usize = 4;
+/// Число отводов ФНЧ комплексной огибающей
+const LOWPASS_TAPS: usize = 31;
+/// Частота среза ФНЧ комплексной огибающей, Гц
+const LOWPASS_CUTOFF_HZ: f64 = 1000.0;
+/// Допуск поиска синхроимпульса вокруг ожидаемой позиции, мс
+const SYNC_TOLERANCE_MS: f64 = 1.5;
+/// Окно поиска первой строки после начала заголовка, мс
+const FIRST_LINE_WINDOW_MS: (f64, f64) = (600.0, 1300.0);
+/// Стоимость, при которой синхроимпульс считается найденным, Гц
+const SYNC_COST_ACCEPT_HZ: f64 = 80.0;
+/// Допустимое отличие реального периода строки от номинального
+pub(crate) const CLOCK_SEARCH_RATIO: f64 = 0.03;
+/// Стоимость, соответствующая нулевой уверенности, Гц
+const SYNC_COST_ZERO_HZ: f64 = BLACK_HZ as f64 - SYNC_HZ as f64;
+/// Доля расхождения оценки расстройки с измерением, исправляемая АПЧ за одну строку
+const AFC_GAIN: f64 = 0.3;
+/// Участок лидер-тона для начальной оценки расстройки, мс от начала заголовка
+const AFC_LEADER_WINDOW_MS: (f64, f64) = (20.0, 180.0);
+/// Отступ от краёв синхроимпульса при измерении расстройки, мс: переходный процесс ФНЧ.
+/// Площадка M1 (0.572 мс) короче переходного процесса, поэтому меряется только синхроимпульс
+const AFC_EDGE_MS: f64 = 1.5;
+/// Наименьшее число уверенных синхроимпульсов для оценки наклона
+const MIN_SLANT_SYNCS: f64 = 3.0;
+/// Пределы перебора периода строки при автокоррекции наклона, ppm
+const DESKEW_RANGE_PPM: f64 = 50_000.0;
+/// Как часто повторяется поиск заголовка в потоке, пока он не найден, мс сигнала
+const HEADER_RETRY_MS: usize = 250;
+/// Сколько конца сигнала хранит поток, пока заголовок не найден, мс: с запасом на
+/// лидер-тон, начатый до отброшенной части
+const HEADER_KEEP_MS: usize = 3000;
+/// Сколько сигнала с начала приёма идёт в оценку С/Ш потока, мс
+const SNR_WINDOW_MS: usize = 10_000;
+
+/// Встроенный декодер Martin M1 на основе квадратурного частотного дискриминатора
+pub struct NativeDecoder {
+    #[deprecated(since = "0.1.0", note = "используйте `params()`")]
+    pub params: DecoderParams,
+    line_confidence: Vec<f32>,
+    afc_trace: Vec<f32>,
+    vis: Option<VisReport>,
+    slant_ppm: Option<f64>,
+}
+
+impl Default for NativeDecoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[allow(deprecated)]
+impl NativeDecoder {
+    pub fn new() -> Self {
+        Self::new_with_params(DecoderParams {
+            backend: DecoderBackend::Native,
+            ..Default::default()
+        })
+    }
+
+    pub fn new_with_params(params: DecoderParams) -> Self {
+        Self {
+            params,
+            line_confidence: Vec::new(),
+            afc_trace: Vec::new(),
+            vis: None,
+            slant_ppm: None,
+        }
+    }
+
+    /// Параметры, с которыми создан; для других нужен новый экземпляр
+    pub fn params(&self) -> &DecoderParams {
+        &self.params
+    }
+
+    /// Средняя уверенность синхронизации по декодированным строкам
+    pub fn mean_confidence(&self) -> f32 {
+        if self.line_confidence.is_empty() {
+            return 0.0;
+        }
+        self.line_confidence.iter().sum::<f32>() / self.line_confidence.len() as f32
+    }
+}
+
+#[allow(deprecated)]
+impl Decoder for NativeDecoder {
+    fn decode(&mut self, samples: &[f32]) -> Result<DynamicImage> {
+        self.decode_with_progress(samples, &|_| {}, &AtomicBool::new(false))
+    }
+
+    fn decode_with_progress(
+        &mut self,
+        samples: &[f32],
+        progress: &dyn Fn(f32),
+        cancel: &AtomicBool,
+    ) -> Result<DynamicImage> {
+        let mut sink = MemorySink::new();
+        self.decode_into(samples, &mut sink, progress, cancel)?;
+        Ok(DynamicImage::ImageRgb8(sink.into_image()))
+    }
+
+    /// Дискриминатор считается половиной работы, вторая половина — строки. Каждая
+    /// строка уходит в `sink`, как только декодирована
+    fn decode_into(
+        &mut self,
+        samples: &[f32],
+        sink: &mut dyn ImageSink,
+        progress: &dyn Fn(f32),
+        cancel: &AtomicBool,
+    ) -> Result<()> {
+        self.line_confidence.clear();
+        self.afc_trace.clear();
+        self.vis = None;
+        self.slant_ppm = None;
+        let samples = trim_silence(samples);
+
+        let Some(header) = locate_header(samples, &self.params) else {
+            return Err(SstvError::DecodeNotFound(
+                messages::decode_not_found().into(),
+            ));
+        };
+        self.vis = read_vis(samples, &header);
+
+        let track = FrequencyTrack::new(&discriminate(samples));
+        progress(0.5);
+        let spec = &MARTIN_M1;
+        let mut scanner = LineScanner::start(
+            &track,
+            header.start / DECIMATION,
+            &self.params,
+            self.params.deskew,
+        )?;
+
+        sink.begin(spec.width, spec.height)?;
+        while scanner.y < spec.height {
+            SstvError::check_cancelled(cancel)?;
+            progress(0.5 + 0.5 * scanner.y as f32 / spec.height as f32);
+            if scanner.past_end(&track) {
+                break;
+            }
+            let y = scanner.y;
+            let row = scanner.next_line(&track, &mut self.line_confidence, &mut self.afc_trace);
+            sink.write_row(y, row)?;
+        }
+
+        if self.line_confidence.is_empty() {
+            return Err(SstvError::DecodeNotFound(
+                messages::decode_not_found().into(),
+            ));
+        }
+        self.slant_ppm = scanner.slant_ppm();
+
+        sink.finish(self.line_confidence.len() as u32)
+    }
+
+    fn name(&self) -> &'static str {
+        DecoderBackend::Native.name()
+    }
+
+    fn line_confidence(&self) -> Option<&[f32]> {
+        Some(&self.line_confidence)
+    }
+
+    fn afc_trace(&self) -> Option<&[f32]> {
+        self.params.afc.then_some(self.afc_trace.as_slice())
+    }
+
+    fn vis(&self) -> Option<&VisReport> {
+        self.vis.as_ref()
+    }
+
+    fn slant_ppm(&self) -> Option<f64> {
+        self.slant_ppm
+    }
+
+    fn partial(&self) -> bool {
+        !self.line_confidence.is_empty() && self.line_confidence.len() < MARTIN_M1.height as usize
+    }
+}
+
+/// Растр без заголовка для разбора приёма, в котором декодер не нашёл изображение:
+/// строки читаются от лучшего синхроимпульса в первой строке сигнала, часы уточняются по
+/// найденным синхроимпульсам и идут по инерции там, где их нет. Строки, на которые
+/// сигнала не хватило, остаются чёрными. Картинка может оказаться мусором — это
+/// диагностика, а не декодирование
+pub fn raster(samples: &[f32], params: &DecoderParams) -> RgbImage {
+    let spec = &MARTIN_M1;
+    let mut image = RgbImage::new(spec.width, spec.height);
+    let track = FrequencyTrack::new(&discriminate(trim_silence(samples)));
+
+    let rate = SAMPLE_RATE as f64 / DECIMATION as f64;
+    let sync_len = (spec.sync_us * 1e-6 * rate).round() as usize;
+    let porch_len = (spec.porch_us * 1e-6 * rate).round().max(1.0) as usize;
+    let line_len = (spec.line_us() * 1e-6 * rate).round() as usize;
+    let (first_sync, _) = track.best_sync(sync_len, sync_len + line_len, sync_len, porch_len, 0.0);
+
+    let mut scanner = LineScanner::from_sync(&track, first_sync, 0.0, params, params.deskew);
+    let (mut confidence, mut afc_trace) = (Vec::new(), Vec::new());
+    while scanner.y < spec.height && !scanner.past_end(&track) {
+        let y = scanner.y;
+        let row = scanner.next_line(&track, &mut confidence, &mut afc_trace);
+        if let Some(dst) = image.get_mut(y as usize * row.len()..(y as usize + 1) * row.len()) {
+            dst.copy_from_slice(row);
+        }
+    }
+    image
+}
+
+/// Состояние [`StreamDecoder`]
+enum StreamState {
+    /// Ищется заголовок
+    Header,
+    /// Заголовок найден с отсчёта дискриминатора `header_pos`, ждём первую строку
+    FirstLine { header_pos: usize },
+    /// Идут строки
+    Lines(LineScanner),
+    /// Изображение закончено
+    Done,
+}
+
+/// Встроенный декодер, принимающий сигнал блоками по мере передачи. Строка уходит в
+/// приёмник, как только дискриминатор выдал всё, что она читает, а в памяти держится
+/// лишь несколько строк частоты. Строки те же, что у [`NativeDecoder`] по всему сигналу,
+/// кроме автокоррекции наклона: перебору периода нужен весь сигнал, поэтому период
+/// оценивают сами часы строк. С/Ш оценивается по первым [`SNR_WINDOW_MS`] мс сигнала
+pub struct StreamDecoder {
+    decoder: NativeDecoder,
+    discriminator: Discriminator,
+    track: FrequencyTrack,
+    state: StreamState,
+    /// Сигнал, в котором ищется заголовок, начиная с сэмпла `raw_origin`
+    raw: Vec<f32>,
+    raw_origin: usize,
+    /// Длина сигнала, после которой поиск заголовка повторится
+    next_search: usize,
+    /// Начало сигнала для оценки С/Ш
+    snr: Vec<f32>,
+    freq: Vec<f32>,
+}
+
+#[allow(deprecated)]
+impl StreamDecoder {
+    pub fn new(params: DecoderParams) -> Self {
+        Self {
+            decoder: NativeDecoder::new_with_params(params),
+            discriminator: Discriminator::new(),
+            track: FrequencyTrack::new(&[]),
+            state: StreamState::Header,
+            raw: Vec::new(),
+            raw_origin: 0,
+            next_search: 0,
+            snr: Vec::new(),
+            freq: Vec::new(),
+        }
+    }
+
+    pub fn params(&self) -> &DecoderParams {
+        &self.decoder.params
+    }
+
+    /// Начинает новый приём
+    pub fn reset(&mut self) {
+        *self = Self::new(self.decoder.params.clone());
+    }
+
+    /// Изображение закончено: приняты все строки или сигнал кончился
+    pub fn is_done(&self) -> bool {
+        matches!(self.state, StreamState::Done)
+    }
+
+    /// Начало сигнала, по которому оценивается С/Ш, см.
+    /// [`DecodeReport::from_decoder`](crate::report::DecodeReport::from_decoder)
+    pub fn snr_samples(&self) -> &[f32] {
+        &self.snr
+    }
+
+    /// Принимает следующий блок сигнала и отдаёт в `sink` строки, которые уже можно
+    /// декодировать
+    pub fn push(&mut self, block: &[f32], sink: &mut dyn ImageSink) -> Result<()> {
+        if self.is_done() {
+            return Ok(());
+        }
+        let room = ms_to_samples(SNR_WINDOW_MS).saturating_sub(self.snr.len());
+        self.snr.extend_from_slice(&block[..room.min(block.len())]);
+        if matches!(self.state, StreamState::Header) {
+            self.raw.extend_from_slice(block);
+        }
+        self.freq.clear();
+        self.discriminator.push(block, &mut self.freq);
+        self.track.extend(&self.freq);
+        self.advance(sink, false)
+    }
+
+    /// Конец сигнала: декодирует оставшиеся строки и закрывает `sink`. Ошибка, если
+    /// изображение так и не найдено
+    pub fn finish(&mut self, sink: &mut dyn ImageSink) -> Result<()> {
+        if self.is_done() {
+            return Ok(());
+        }
+        self.freq.clear();
+        self.discriminator.finish(&mut self.freq);
+        self.track.extend(&self.freq);
+        self.advance(sink, true)
+    }
+
+    /// Продвигает приём по уже полученному сигналу; `last` — сигнал кончился
+    fn advance(&mut self, sink: &mut dyn ImageSink, last: bool) -> Result<()> {
+        if matches!(self.state, StreamState::Header) && !self.search_header(last)? {
+            return Ok(());
+        }
+        if let StreamState::FirstLine { header_pos } = self.state {
+            if !last && self.track.len() < LineScanner::start_reach(header_pos) {
+                return Ok(());
+            }
+            let scanner = LineScanner::start(&self.track, header_pos, &self.decoder.params, false)?;
+            sink.begin(MARTIN_M1.width, MARTIN_M1.height)?;
+            self.state = StreamState::Lines(scanner);
+        }
+        let StreamState::Lines(scanner) = &mut self.state else {
+            return Ok(());
+        };
+
+        while scanner.y < MARTIN_M1.height {
+            let available = if last {
+                !scanner.past_end(&self.track)
+            } else {
+                scanner.ready(&self.track)
+            };
+            if !available {
+                break;
+            }
+            let y = scanner.y;
+            let row = scanner.next_line(
+                &self.track,
+                &mut self.decoder.line_confidence,
+                &mut self.decoder.afc_trace,
+            );
+            sink.write_row(y, row)?;
+            self.track.discard_before(scanner.keep_from());
+        }
+        if scanner.y < MARTIN_M1.height && !last {
+            return Ok(());
+        }
+
+        if self.decoder.line_confidence.is_empty() {
+            return Err(SstvError::DecodeNotFound(
+                messages::decode_not_found().into(),
+            ));
+        }
+        self.decoder.slant_ppm = scanner.slant_ppm();
+        self.state = StreamState::Done;
+        sink.finish(self.decoder.line_confidence.len() as u32)
+    }
+
+    /// Ищет заголовок в накопленном сигнале; `true`, если он найден и прочитан. Поиск
+    /// повторяется раз в [`HEADER_RETRY_MS`] мс сигнала, а без окна поиска хранится только
+    /// конец сигнала длиной [`HEADER_KEEP_MS`] мс
+    fn search_header(&mut self, last: bool) -> Result<bool> {
+        let received = self.raw_origin + self.raw.len();
+        if !last && received < self.next_search {
+            return Ok(false);
+        }
+        self.next_search = received + ms_to_samples(HEADER_RETRY_MS);
+
+        let found = locate_header(&self.raw, &self.decoder.params);
+        // Ждём заголовок целиком и ещё лидер-тон: VIS и оценку расстройки
+        let complete = |h: &HeaderMatch| {
+            h.start + ms_to_samples((HEADER_MS + LEADER_MS) as usize) <= self.raw.len()
+        };
+        match found {
+            Some(header) if last || complete(&header) => {
+                self.decoder.vis = read_vis(&self.raw, &header).map(|vis| VisReport {
+                    start: vis.start + self.raw_origin,
+                    ..vis
+                });
+                let header_pos = (self.raw_origin + header.start) / DECIMATION;
+                self.track.discard_before(header_pos);
+                self.raw = Vec::new();
+                self.state = StreamState::FirstLine { header_pos };
+                return Ok(true);
+            }
+            Some(_) => return Ok(false),
+            None => {}
+        }
+
+        let window = self.decoder.params.search_window_ms as usize;
+        let keep = ms_to_samples(HEADER_KEEP_MS);
+        // Новый лидер-тон может начаться только внутри окна поиска
+        if last || (window > 0 && received > ms_to_samples(window) + keep) {
+            return Err(SstvError::DecodeNotFound(
+                messages::decode_not_found().into(),
+            ));
+        }
+        if window == 0 && self.raw.len() > keep {
+            // Отбрасываем кратно шагу поиска, чтобы блоки анализа остались на местах
+            let hop = header_search_hop();
+            let drop = (self.raw.len() - keep) / hop * hop;
+            self.raw.drain(..drop);
+            self.raw_origin += drop;
+            self.track.discard_before(self.raw_origin / DECIMATION);
+        }
+        Ok(false)
+    }
+}
+
+impl Decoder for StreamDecoder {
+    fn decode(&mut self, samples: &[f32]) -> Result<DynamicImage> {
+        self.decode_with_progress(samples, &|_| {}, &AtomicBool::new(false))
+    }
+
+    fn decode_with_progress(
+        &mut self,
+        samples: &[f32],
+        progress: &dyn Fn(f32),
+        cancel: &AtomicBool,
+    ) -> Result<DynamicImage> {
+        let mut sink = MemorySink::new();
+        self.decode_into(samples, &mut sink, progress, cancel)?;
+        Ok(DynamicImage::ImageRgb8(sink.into_image()))
+    }
+
+    /// Подаёт сигнал блоками по [`DEFAULT_BLOCK_LEN`] сэмплов, как при приёме
+    fn decode_into(
+        &mut self,
+        samples: &[f32],
+        sink: &mut dyn ImageSink,
+        progress: &dyn Fn(f32),
+        cancel: &AtomicBool,
+    ) -> Result<()> {
+        self.reset();
+        let total = samples.len().max(1) as f32;
+        for (i, block) in samples.chunks(DEFAULT_BLOCK_LEN).enumerate() {
+            SstvError::check_cancelled(cancel)?;
+            progress((i * DEFAULT_BLOCK_LEN) as f32 / total);
+            self.push(block, sink)?;
+        }
+        self.finish(sink)
+    }
+
+    fn name(&self) -> &'static str {
+        self.decoder.name()
+    }
+
+    fn line_confidence(&self) -> Option<&[f32]> {
+        self.decoder.line_confidence()
+    }
+
+    fn afc_trace(&self) -> Option<&[f32]> {
+        self.decoder.afc_trace()
+    }
+
+    fn vis(&self) -> Option<&VisReport> {
+        self.decoder.vis()
+    }
+
+    fn slant_ppm(&self) -> Option<f64> {
+        self.decoder.slant_ppm()
+    }
+
+    fn partial(&self) -> bool {
+        self.decoder.partial()
+    }
+}
+
+fn ms_to_samples(ms: usize) -> usize {
+    ms * SAMPLE_RATE / 1000
+}
+
+/// Декодирует одну строку изображения начиная с конца синхроимпульса в `row` (RGB по
+/// три байта); `offset` — расстройка приёмника, Гц, вычитаемая из частоты тона
+fn decode_line(
+    track: &FrequencyTrack,
+    row: &mut [u8],
+    spec: &ModeSpec,
+    sync_end: f64,
+    rate: f64,
+    offset: f64,
+) {
+    let us = |us: f64| us * 1e-6 * rate;
+    let pixel = us(spec.pixel_us);
+    let range = (WHITE_HZ - BLACK_HZ) as f64;
+
+    for (slot, &channel) in spec.channel_order.iter().enumerate() {
+        let start = sync_end + us(spec.porch_us) + slot as f64 * us(spec.channel_us());
+        for x in 0..spec.width {
+            let from = start + x as f64 * pixel;
+            let freq = track.mean(from.round() as usize, (from + pixel).round() as usize);
+            let value = ((freq - offset - BLACK_HZ as f64) / range).clamp(0.0, 1.0);
+            row[x as usize * 3 + channel] = (value * 255.0).round() as u8;
+        }
+    }
+}
+
+/// Построчный разбор сигнала после первого синхроимпульса: часы строк, АПЧ и уверенность
+/// синхронизации. Общий для декодирования целиком и блоками, см. [`StreamDecoder`]
+struct LineScanner {
+    /// Частота отсчётов дискриминатора, Гц
+    rate: f64,
+    sync_len: usize,
+    porch_len: usize,
+    line_len: f64,
+    tolerance: usize,
+    edge: usize,
+    max_offset: f64,
+    /// Текущая оценка расстройки приёмника, Гц
+    offset: f64,
+    sync_threshold: f32,
+    afc: bool,
+    clock: LineClock,
+    /// Период строки известен заранее: задан вручную или найден перебором
+    period_known: bool,
+    row: Vec<u8>,
+    /// Следующая строка
+    y: u32,
+}
+
+impl LineScanner {
+    /// Сколько отсчётов дискриминатора от начала заголовка `header_pos` читает
+    /// [`start`](Self::start): оценку расстройки по лидер-тону и поиск первой строки
+    fn start_reach(header_pos: usize) -> usize {
+        let rate = SAMPLE_RATE as f64 / DECIMATION as f64;
+        let porch_len = (MARTIN_M1.porch_us * 1e-6 * rate).round().max(1.0) as usize;
+        header_pos + (FIRST_LINE_WINDOW_MS.1 * 1e-3 * rate) as usize + porch_len + 1
+    }
+
+    /// Оценивает расстройку по лидер-тону после заголовка `header_pos`, находит первый
+    /// синхроимпульс и заводит от него часы строк, см. [`from_sync`](Self::from_sync)
+    fn start(
+        track: &FrequencyTrack,
+        header_pos: usize,
+        params: &DecoderParams,
+        deskew: bool,
+    ) -> Result<Self> {
+        let spec = &MARTIN_M1;
+        let rate = SAMPLE_RATE as f64 / DECIMATION as f64;
+        let us = |us: f64| us * 1e-6 * rate;
+
+        let sync_len = us(spec.sync_us).round() as usize;
+        let porch_len = us(spec.porch_us).round().max(1.0) as usize;
+
+        let first_from = header_pos + us(FIRST_LINE_WINDOW_MS.0 * 1000.0) as usize;
+        let first_to = header_pos + us(FIRST_LINE_WINDOW_MS.1 * 1000.0) as usize;
+
+        // Расстройка приёмника: начальная оценка по лидер-тону, дальше её ведёт АПЧ
+        let max_offset = params.max_freq_deviation_hz.max(0.0) as f64;
+        let offset = if params.afc {
+            let from = header_pos + us(AFC_LEADER_WINDOW_MS.0 * 1000.0) as usize;
+            let to = header_pos + us(AFC_LEADER_WINDOW_MS.1 * 1000.0) as usize;
+            (track.mean(from, to) - LEADER_HZ as f64).clamp(-max_offset, max_offset)
+        } else {
+            0.0
+        };
+
+        let Some((first_sync, _)) =
+            track.find_first_sync(first_from, first_to, sync_len, porch_len, offset)
+        else {
+            return Err(SstvError::DecodeNotFound(
+                messages::decode_no_first_sync().into(),
+            ));
+        };
+        Ok(Self::from_sync(track, first_sync, offset, params, deskew))
+    }
+
+    /// Заводит часы строк от конца первого синхроимпульса `first_sync` при расстройке
+    /// `offset`: с периодом, заданным вручную, найденным перебором при `deskew` или
+    /// номинальным
+    fn from_sync(
+        track: &FrequencyTrack,
+        first_sync: usize,
+        offset: f64,
+        params: &DecoderParams,
+        deskew: bool,
+    ) -> Self {
+        let spec = &MARTIN_M1;
+        let rate = SAMPLE_RATE as f64 / DECIMATION as f64;
+        let us = |us: f64| us * 1e-6 * rate;
+
+        let sync_len = us(spec.sync_us).round() as usize;
+        let porch_len = us(spec.porch_us).round().max(1.0) as usize;
+        let line_len = us(spec.line_us());
+        let tolerance = us(SYNC_TOLERANCE_MS * 1000.0).round() as usize;
+        let max_offset = params.max_freq_deviation_hz.max(0.0) as f64;
+        let edge = us(AFC_EDGE_MS * 1000.0).round() as usize;
+
+        // Период строки: заданный вручную, найденный перебором по синхроимпульсам или
+        // номинальный — тогда его оценивают сами часы начиная со второй строки
+        let manual = params.slant_ppm.map(|ppm| line_len * (1.0 + ppm * 1e-6));
+        let searched = (manual.is_none() && deskew)
+            .then(|| {
+                let lines = spec.height as usize;
+                track.search_period(first_sync, line_len, lines, sync_len, porch_len, offset)
+            })
+            .flatten();
+        let clock = match manual {
+            Some(period) => LineClock::fixed(first_sync as f64, period),
+            None => LineClock::new(first_sync as f64, searched.unwrap_or(line_len)),
+        };
+
+        Self {
+            rate,
+            sync_len,
+            porch_len,
+            line_len,
+            tolerance,
+            edge,
+            max_offset,
+            offset,
+            sync_threshold: params.sync_threshold,
+            afc: params.afc,
+            clock,
+            period_known: manual.is_some() || searched.is_some(),
+            row: vec![0; spec.width as usize * 3],
+            y: 0,
+        }
+    }
+
+    /// Допуск поиска синхроимпульса следующей строки. Пока период не известен и часы не
+    /// нашли уверенного синхроимпульса после первого, он велик и растёт на
+    /// [`CLOCK_SEARCH_RATIO`] периода с каждой строкой: иначе один слабый синхроимпульс
+    /// второй строки оставлял бы номинальный период, и строки уходили бы из допуска
+    fn line_tolerance(&self) -> usize {
+        if self.y > 0 && !self.period_known && !self.clock.has_period() {
+            (self.line_len * CLOCK_SEARCH_RATIO * self.y as f64).min(self.line_len / 2.0) as usize
+        } else {
+            self.tolerance
+        }
+    }
+
+    /// Сигнал кончился раньше следующей строки. Позиция часов — конец синхроимпульса,
+    /// до конца строки остаётся line_len - sync_len
+    fn past_end(&self, track: &FrequencyTrack) -> bool {
+        let expected = self.clock.predict(self.y);
+        (expected + self.line_len.min(self.clock.period())) as usize > track.len() + self.sync_len
+    }
+
+    /// Дискриминатор выдал всё, что читает следующая строка, — поиск синхроимпульса и
+    /// пиксели, поэтому она выйдет той же, что при декодировании всего сигнала
+    fn ready(&self, track: &FrequencyTrack) -> bool {
+        let expected = self.clock.predict(self.y);
+        let after = (self.porch_len as f64).max(self.clock.period());
+        track.len() > (expected + self.line_tolerance() as f64 + after).ceil() as usize
+    }
+
+    /// Начало сигнала, которое следующим строкам уже не понадобится
+    fn keep_from(&self) -> usize {
+        let expected = self.clock.predict(self.y);
+        let margin = (self.line_tolerance() + self.sync_len) as f64 + self.line_len;
+        (expected - margin).max(0.0) as usize
+    }
+
+    /// Декодирует следующую строку, дописывая уверенность её синхроимпульса в
+    /// `confidence` и поправку АПЧ в `afc_trace`; возвращает пиксели строки
+    fn next_line(
+        &mut self,
+        track: &FrequencyTrack,
+        confidence: &mut Vec<f32>,
+        afc_trace: &mut Vec<f32>,
+    ) -> &[u8] {
+        let y = self.y;
+        let expected = self.clock.predict(y);
+        let tolerance = self.line_tolerance();
+
+        // Ищем конец синхроимпульса вблизи ожидаемой позиции
+        let center = expected.round() as usize;
+        let (found, cost) = track.best_sync(
+            center.saturating_sub(tolerance),
+            center + tolerance,
+            self.sync_len,
+            self.porch_len,
+            self.offset,
+        );
+        let line_confidence = (1.0 - cost / SYNC_COST_ZERO_HZ).clamp(0.0, 1.0) as f32;
+        confidence.push(line_confidence);
+
+        // При слабой синхронизации идём по инерции от оценённых часов
+        let sync_end = if line_confidence >= self.sync_threshold {
+            self.clock.observe(y, found as f64);
+            if self.afc
+                && let Some(measured) = track.sync_offset(found, self.sync_len, self.edge)
+            {
+                self.offset = (self.offset + AFC_GAIN * (measured - self.offset))
+                    .clamp(-self.max_offset, self.max_offset);
+            }
+            found as f64
+        } else {
+            expected
+        };
+        if self.afc {
+            afc_trace.push(self.offset as f32);
+        }
+
+        let scale = self.clock.period() / self.line_len;
+        decode_line(
+            track,
+            &mut self.row,
+            &MARTIN_M1,
+            sync_end,
+            self.rate * scale,
+            self.offset,
+        );
+        self.y += 1;
+        &self.row
+    }
+
+    /// Наклон по оценённому периоду строки, ppm; по одному-двум синхроимпульсам период
+    /// ещё не оценён
+    fn slant_ppm(&self) -> Option<f64> {
+        (self.clock.fixed || self.clock.n >= MIN_SLANT_SYNCS)
+            .then(|| (self.clock.period() / self.line_len - 1.0) * 1e6)
+    }
+}
+
+/// Оценка положения строк линейной регрессией по уверенно найденным синхроимпульсам.
+/// При заданном периоде синхроимпульсы уточняют только фазу строк
+struct LineClock {
+    nominal: f64,
+    fixed: bool,
+    origin: f64,
+    n: f64,
+    sum_y: f64,
+    sum_p: f64,
+    sum_yy: f64,
+    sum_yp: f64,
+}
+
+impl LineClock {
+    fn new(first_sync: f64, nominal: f64) -> Self {
+        Self {
+            nominal,
+            fixed: false,
+            origin: first_sync,
+            n: 1.0,
+            sum_y: 0.0,
+            sum_p: first_sync,
+            sum_yy: 0.0,
+            sum_yp: 0.0,
+        }
+    }
+
+    /// Часы с периодом `period`, который не оценивается
+    fn fixed(first_sync: f64, period: f64) -> Self {
+        Self {
+            fixed: true,
+            ..Self::new(first_sync, period)
+        }
+    }
+
+    fn observe(&mut self, y: u32, pos: f64) {
+        let y = y as f64;
+        self.n += 1.0;
+        self.sum_y += y;
+        self.sum_p += pos;
+        self.sum_yy += y * y;
+        self.sum_yp += y * pos;
+    }
+
+    /// Период задан или оценён: кроме первой строки найден синхроимпульс другой
+    fn has_period(&self) -> bool {
+        self.fixed || self.sum_yy > 0.0
+    }
+
+    /// Текущая оценка периода строки в отсчётах
+    fn period(&self) -> f64 {
+        if self.fixed {
+            return self.nominal;
+        }
+        let denom = self.n * self.sum_yy - self.sum_y * self.sum_y;
+        if denom.abs() < f64::EPSILON {
+            return self.nominal;
+        }
+        (self.n * self.sum_yp - self.sum_y * self.sum_p) / denom
+    }
+
+    /// Ожидаемая позиция конца синхроимпульса строки `y`
+    fn predict(&self, y: u32) -> f64 {
+        let period = self.period();
+        if self.n < 2.0 && !self.fixed {
+            return self.origin + y as f64 * period;
+        }
+        let intercept = (self.sum_p - period * self.sum_y) / self.n;
+        intercept + y as f64 * period
+    }
+}
+
+/// Мгновенная частота сигнала с префиксными суммами для быстрых средних. Индексы —
+/// от начала сигнала: при декодировании блоками частота дописывается в конец, а ненужное
+/// начало отбрасывается, и тогда хранится только участок от `origin`
+struct FrequencyTrack {
+    origin: usize,
+    freq: Vec<f64>,
+    /// Префиксные суммы: `sum[i]` — сумма частот до отсчёта `origin + i`
+    sum: Vec<f64>,
+}
+
+impl FrequencyTrack {
+    fn new(freq: &[f32]) -> Self {
+        let mut track = Self {
+            origin: 0,
+            freq: Vec::with_capacity(freq.len()),
+            sum: Vec::with_capacity(freq.len() + 1),
+        };
+        track.sum.push(0.0);
+        track.extend(freq);
+        track
+    }
+
+    /// Дописывает частоту следующих отсчётов
+    fn extend(&mut self, freq: &[f32]) {
+        let mut acc = self.sum.last().copied().unwrap_or(0.0);
+        for &v in freq {
+            acc += v as f64;
+            self.freq.push(v as f64);
+            self.sum.push(acc);
+        }
+    }
+
+    /// Отбрасывает отсчёты до `pos`
+    fn discard_before(&mut self, pos: usize) {
+        let count = pos.saturating_sub(self.origin).min(self.freq.len());
+        if count > 0 {
+            self.freq.drain(..count);
+            self.sum.drain(..count);
+            self.origin += count;
+        }
+    }
+
+    /// Конец известной частоты: индекс после последнего отсчёта
+    fn len(&self) -> usize {
+        self.origin + self.freq.len()
+    }
+
+    /// Частота на отрезке `range`; он должен лежать в хранимом участке
+    fn slice(&self, range: Range<usize>) -> &[f64] {
+        &self.freq[range.start - self.origin..range.end - self.origin]
+    }
+
+    /// Средняя частота на отрезке [from, to)
+    fn mean(&self, from: usize, to: usize) -> f64 {
+        let to = to.min(self.len());
+        let from = from.min(to.saturating_sub(1)).max(self.origin);
+        if to <= from {
+            return BLACK_HZ as f64;
+        }
+        (self.sum[to - self.origin] - self.sum[from - self.origin]) / (to - from) as f64
+    }
+
+    /// Стоимость гипотезы «синхроимпульс заканчивается в `end`»: отклонение от 1200 Гц до
+    /// и 1500 Гц после, с учётом расстройки `offset`
+    fn sync_cost(&self, end: usize, sync_len: usize, porch_len: usize, offset: f64) -> f64 {
+        if end < self.origin + sync_len || end + porch_len > self.len() {
+            return f64::INFINITY;
+        }
+        let deviation = |range: Range<usize>, hz: f32| {
+            let len = range.len() as f64;
+            let target = hz as f64 + offset;
+            self.slice(range)
+                .iter()
+                .map(|f| (f - target).abs())
+                .sum::<f64>()
+                / len
+        };
+        deviation(end - sync_len..end, SYNC_HZ) + deviation(end..end + porch_len, BLACK_HZ)
+    }
+
+    /// Расстройка, измеренная по середине синхроимпульса, оканчивающегося в `end`: медиана
+    /// устойчива к щелчкам дискриминатора при сильном шуме
+    fn sync_offset(&self, end: usize, sync_len: usize, edge: usize) -> Option<f64> {
+        let from = (end + edge).checked_sub(sync_len)?.max(self.origin);
+        let to = end.checked_sub(edge)?.min(self.len());
+        if to <= from {
+            return None;
+        }
+        let mut values = self.slice(from..to).to_vec();
+        values.sort_by(f64::total_cmp);
+        Some(values[values.len() / 2] - SYNC_HZ as f64)
+    }
+
+    /// Лучшая позиция конца синхроимпульса в диапазоне [from, to]
+    fn best_sync(
+        &self,
+        from: usize,
+        to: usize,
+        sync_len: usize,
+        porch_len: usize,
+        offset: f64,
+    ) -> (usize, f64) {
+        (from..=to)
+            .map(|p| (p, self.sync_cost(p, sync_len, porch_len, offset)))
+            .fold((from, f64::INFINITY), |best, cur| {
+                if cur.1 < best.1 { cur } else { best }
+            })
+    }
+
+    /// Период строки, при котором гребёнка из `lines` синхроимпульсов от `first_sync`
+    /// лучше всего ложится на сигнал: перебор в пределах [`DESKEW_RANGE_PPM`] от
+    /// номинала с шагом, за который гребёнка к последней строке сползает не больше чем
+    /// на полсинхроимпульса. Дальше период уточняют часы по найденным синхроимпульсам
+    fn search_period(
+        &self,
+        first_sync: usize,
+        nominal: f64,
+        lines: usize,
+        sync_len: usize,
+        porch_len: usize,
+        offset: f64,
+    ) -> Option<f64> {
+        let widest = nominal * (1.0 + DESKEW_RANGE_PPM * 1e-6);
+        let available = self.len().saturating_sub(first_sync + porch_len) as f64;
+        let lines = lines.min((available / widest) as usize + 1);
+        if lines < MIN_SLANT_SYNCS as usize {
+            return None;
+        }
+        let step = sync_len as f64 / (2.0 * lines as f64);
+        let reach = (nominal * DESKEW_RANGE_PPM * 1e-6 / step).ceil() as i64;
+        (-reach..=reach)
+            .map(|k| {
+                let period = nominal + k as f64 * step;
+                let cost: f64 = (1..lines)
+                    .map(|y| {
+                        let end = (first_sync as f64 + y as f64 * period).round() as usize;
+                        self.sync_cost(end, sync_len, porch_len, offset)
+                            .min(SYNC_COST_ZERO_HZ)
+                    })
+                    .sum();
+                (period, cost)
+            })
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(period, _)| period)
+    }
+
+    /// Первый уверенный синхроимпульс после заголовка, уточнённый до локального минимума
+    fn find_first_sync(
+        &self,
+        from: usize,
+        to: usize,
+        sync_len: usize,
+        porch_len: usize,
+        offset: f64,
+    ) -> Option<(usize, f64)> {
+        let to = to.min(self.len().saturating_sub(porch_len + 1));
+        if from >= to {
+            return None;
+        }
+
+        let first = (from..=to)
+            .find(|&p| self.sync_cost(p, sync_len, porch_len, offset) < SYNC_COST_ACCEPT_HZ);
+        let best = match first {
+            Some(p) => self.best_sync(p, (p + sync_len).min(to), sync_len, porch_len, offset),
+            None => self.best_sync(from, to, sync_len, porch_len, offset),
+        };
+
+        (best.1 < SYNC_COST_ZERO_HZ / 2.0).then_some(best)
+    }
+}
+
+/// Переносит сигнал в основную полосу вокруг лидер-тона и возвращает мгновенную частоту
+/// после децимации (atan2-дискриминатор)
+fn discriminate(samples: &[f32]) -> Vec<f32> {
+    let mut freq = Vec::with_capacity(samples.len() / DECIMATION);
+    let mut discriminator = Discriminator::new();
+    discriminator.push(samples, &mut freq);
+    discriminator.finish(&mut freq);
+    freq
+}
+
+/// Квадратурный дискриминатор, принимающий сигнал кусками. Выход не зависит от того,
+/// как сигнал разбит, и совпадает с [`discriminate`] по всему сигналу: отсчёт частоты
+/// выдаётся, как только ФНЧ получил все отсчёты основной полосы вокруг него
+struct Discriminator {
+    step_cos: f64,
+    step_sin: f64,
+    osc_re: f64,
+    osc_im: f64,
+    taps: Vec<f64>,
+    /// Сэмплы, которых ещё не хватает на блок децимации
+    pending: Vec<f32>,
+    /// Отсчёты основной полосы, нужные ФНЧ, начиная с отсчёта `origin`
+    baseband: VecDeque<(f64, f64)>,
+    origin: usize,
+    /// Отсчётов основной полосы получено
+    count: usize,
+    /// Следующий выдаваемый отсчёт частоты
+    next: usize,
+    prev: (f64, f64),
+}
+
+impl Discriminator {
+    fn new() -> Self {
+        let fs = SAMPLE_RATE as f64;
+        let step = 2.0 * PI * LEADER_HZ as f64 / fs;
+        let rate = fs / DECIMATION as f64;
+        let mut taps = fir::lowpass(LOWPASS_CUTOFF_HZ / rate, LOWPASS_TAPS, Window::Hamming);
+        fir::normalize_dc(&mut taps);
+        Self {
+            step_cos: step.cos(),
+            step_sin: step.sin(),
+            osc_re: 1.0,
+            osc_im: 0.0,
+            taps,
+            pending: Vec::with_capacity(DECIMATION),
+            baseband: VecDeque::with_capacity(LOWPASS_TAPS + 1),
+            origin: 0,
+            count: 0,
+            next: 0,
+            prev: (0.0, 0.0),
+        }
+    }
+
+    /// Принимает следующие сэмплы и дописывает в `freq` частоту, которую уже можно посчитать
+    fn push(&mut self, samples: &[f32], freq: &mut Vec<f32>) {
+        let mut samples = samples;
+        // Досчитываем блок, начатый прошлым куском
+        if !self.pending.is_empty() {
+            let take = (DECIMATION - self.pending.len()).min(samples.len());
+            self.pending.extend_from_slice(&samples[..take]);
+            samples = &samples[take..];
+            if self.pending.len() < DECIMATION {
+                return;
+            }
+            let block = std::mem::replace(&mut self.pending, Vec::with_capacity(DECIMATION));
+            self.mix_block(&block, freq);
+        }
+        let mut blocks = samples.chunks_exact(DECIMATION);
+        for block in &mut blocks {
+            self.mix_block(block, freq);
+        }
+        self.pending.extend_from_slice(blocks.remainder());
+    }
+
+    /// Конец сигнала: досчитывает частоту последних отсчётов, за которыми ФНЧ видит
+    /// нули. Неполный последний блок децимации отбрасывается
+    fn finish(&mut self, freq: &mut Vec<f32>) {
+        while self.next < self.count {
+            self.emit(freq);
+        }
+    }
+
+    /// Смешивает блок из DECIMATION сэмплов с гетеродином и усредняет его в отсчёт
+    /// основной полосы
+    fn mix_block(&mut self, block: &[f32], freq: &mut Vec<f32>) {
+        let (mut re, mut im) = (0.0, 0.0);
+        for &x in block {
+            re += x as f64 * self.osc_re;
+            im -= x as f64 * self.osc_im;
+            let next_re = self.osc_re * self.step_cos - self.osc_im * self.step_sin;
+            self.osc_im = self.osc_re * self.step_sin + self.osc_im * self.step_cos;
+            self.osc_re = next_re;
+        }
+        // Поддерживаем единичную амплитуду гетеродина
+        let norm = (self.osc_re * self.osc_re + self.osc_im * self.osc_im).sqrt();
+        self.osc_re /= norm;
+        self.osc_im /= norm;
+        self.baseband.push_back((re, im));
+        self.count += 1;
+
+        let half = LOWPASS_TAPS / 2;
+        while self.next + half < self.count {
+            self.emit(freq);
+        }
+    }
+
+    /// Фильтрует отсчёт `next` основной полосы и выдаёт его мгновенную частоту
+    fn emit(&mut self, freq: &mut Vec<f32>) {
+        let half = LOWPASS_TAPS / 2;
+        let n = self.next;
+        let (mut re, mut im) = (0.0, 0.0);
+        for (k, &tap) in self.taps.iter().enumerate() {
+            let Some(idx) = (n + k).checked_sub(half) else {
+                continue;
+            };
+            if idx < self.count {
+                let (r, i) = self.baseband[idx - self.origin];
+                re += r * tap;
+                im += i * tap;
+            }
+        }
+        // Разность фаз соседних отсчётов: arg(z[n] · conj(z[n−1]))
+        let dot = re * self.prev.0 + im * self.prev.1;
+        let cross = im * self.prev.0 - re * self.prev.1;
+        let dphi = cross.atan2(dot);
+        let rate = SAMPLE_RATE as f64 / DECIMATION as f64;
+        freq.push((LEADER_HZ as f64 + dphi * rate / (2.0 * PI)) as f32);
+        self.prev = (re, im);
+        self.next += 1;
+
+        // Следующему отсчёту нужны отсчёты основной полосы начиная с next − half
+        while self.origin + half < self.next {
+            self.baseband.pop_front();
+            self.origin += 1;
+        }
+    }
+}
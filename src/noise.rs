@@ -1,29 +1,348 @@
-use crate::envelope::EnvelopeKind;
-use anyhow::Result;
-use rand::rng;
-use rand_distr::{Distribution, Normal};
+use crate::buffer::SampleBuffer;
+use crate::dsp::{self, Biquad};
+use crate::effect::{
+    self, BlockProcessor, Bypass, ChannelEffect, EffectContext, EffectEstimate, EffectMix,
+    StreamContext,
+};
+use crate::envelope::{EnvelopeAnchor, EnvelopeKind};
+use crate::error::{Result, SstvError};
+use crate::messages;
+use crate::processor::ProcessingParams;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng, rng};
+use rand_distr::{Distribution, Exp1, Normal, StandardNormal};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::ops::Range;
+use std::str::FromStr;
 
-#[derive(Clone, Debug)]
+/// Отношение средней амплитуды импульса к СКЗ шума того же уровня
+const IMPULSE_CREST: f32 = 10.0;
+/// Во сколько раз треск чаще импульсных помех при той же настройке частоты
+const CRACKLE_RATE_RATIO: f32 = 25.0;
+/// Длительность затухания одного щелчка треска, мс
+const POP_MS: f32 = 0.3;
+/// Нижний полюс розового фильтра, Гц: ниже спектр ровный
+const PINK_LOWEST_HZ: f32 = 10.0;
+/// Шаг полюсов розового фильтра: две октавы
+const PINK_SPACING: f32 = 4.0;
+/// Частота, выше которой коричневый шум спадает на 6 дБ на октаву, Гц
+const BROWN_CORNER_HZ: f32 = 10.0;
+/// Наибольшее среднее число одновременно звучащих пачек импульсной помехи. Больше — это
+/// уже непрерывный шум, а время расчёта растёт пропорционально перекрытию
+pub const MAX_IMPULSE_OVERLAP: f32 = 10.0;
+
+/// Вид шума
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NoiseKind {
+    /// Белый гауссов шум (AWGN)
+    Gaussian,
+    /// Короткие случайные пачки: грозовые разряды, зажигание
+    Impulse,
+    /// Частые одиночные щелчки: треск статики
+    Crackle,
+}
+
+impl NoiseKind {
+    pub const ALL: &'static [NoiseKind] =
+        &[NoiseKind::Gaussian, NoiseKind::Impulse, NoiseKind::Crackle];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            NoiseKind::Gaussian => "Gaussian",
+            NoiseKind::Impulse => "Impulse",
+            NoiseKind::Crackle => "Crackle",
+        }
+    }
+}
+
+impl clap::ValueEnum for NoiseKind {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[Self::Gaussian, Self::Impulse, Self::Crackle]
+    }
+
+    fn to_possible_value<'a>(&self) -> Option<clap::builder::PossibleValue> {
+        Some(match self {
+            Self::Gaussian => clap::builder::PossibleValue::new("gaussian"),
+            Self::Impulse => clap::builder::PossibleValue::new("impulse"),
+            Self::Crackle => clap::builder::PossibleValue::new("crackle"),
+        })
+    }
+}
+
+/// Спектр шума: гауссов источник и пачки импульсов проходят через фильтр окраски.
+/// Мощность шума окраска не меняет, меняется только её распределение по частотам
+#[derive(Copy, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NoiseColor {
+    /// Ровный спектр
+    #[default]
+    White,
+    /// Розовый: −3 дБ на октаву, как шум эфира на КВ
+    Pink,
+    /// Коричневый: −6 дБ на октаву, рокот и фон
+    Brown,
+    /// Только полоса от `low_hz` до `high_hz`: помеха, прошедшая через тракт приёмника
+    BandLimited { low_hz: f32, high_hz: f32 },
+}
+
+impl NoiseColor {
+    /// Все окраски для выбора в интерфейсе; полоса — полоса SSTV, её границы меняют отдельно
+    pub const ALL: &'static [NoiseColor] = &[
+        NoiseColor::White,
+        NoiseColor::Pink,
+        NoiseColor::Brown,
+        NoiseColor::BandLimited {
+            low_hz: 1000.0,
+            high_hz: 2500.0,
+        },
+    ];
+
+    /// Та же окраска без учёта границ полосы
+    pub fn same_kind(&self, other: &Self) -> bool {
+        std::mem::discriminant(self) == std::mem::discriminant(other)
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            NoiseColor::White => "White",
+            NoiseColor::Pink => "Pink",
+            NoiseColor::Brown => "Brown",
+            NoiseColor::BandLimited { .. } => "Band-limited",
+        }
+    }
+
+    /// Проверяет границы полосы `BandLimited`
+    pub fn validate(&self) -> Result<()> {
+        if let NoiseColor::BandLimited { low_hz, high_hz } = *self
+            && !(low_hz >= 0.0 && low_hz < high_hz)
+        {
+            return Err(SstvError::invalid(
+                "noise.color",
+                messages::noise_band_invalid(low_hz, high_hz),
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for NoiseColor {
+    type Err = SstvError;
+
+    /// `white`, `pink`, `brown` или `band:нижняя:верхняя` в Гц
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim();
+        let color = match s {
+            "white" => NoiseColor::White,
+            "pink" => NoiseColor::Pink,
+            "brown" => NoiseColor::Brown,
+            _ => {
+                let band: Option<Vec<f32>> = s
+                    .strip_prefix("band:")
+                    .map(|rest| rest.split(':').map(|v| v.trim().parse().ok()).collect())
+                    .and_then(|values: Option<Vec<f32>>| values);
+                let Some(&[low_hz, high_hz]) = band.as_deref() else {
+                    return Err(SstvError::invalid(
+                        "noise.color",
+                        messages::expected_format(messages::format_noise_color(), s),
+                    ));
+                };
+                NoiseColor::BandLimited { low_hz, high_hz }
+            }
+        };
+        color.validate()?;
+        Ok(color)
+    }
+}
+
+impl fmt::Display for NoiseColor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NoiseColor::White => f.write_str("white"),
+            NoiseColor::Pink => f.write_str("pink"),
+            NoiseColor::Brown => f.write_str("brown"),
+            NoiseColor::BandLimited { low_hz, high_hz } => {
+                write!(f, "band:{}:{}", low_hz, high_hz)
+            }
+        }
+    }
+}
+
+/// Фильтр окраски шума на частоте `sample_rate` с усилением, возвращающим мощность белого
+/// шума на входе. Состояние звеньев переходит из блока в блок
+struct NoiseShaper {
+    stages: Vec<Biquad>,
+    gain: f32,
+}
+
+impl NoiseShaper {
+    /// `None` для белого шума: фильтровать нечего
+    fn new(color: NoiseColor, sample_rate: u32) -> Option<Self> {
+        let nyquist = sample_rate as f32 / 2.0;
+        let stages = match color {
+            NoiseColor::White => return None,
+            NoiseColor::Pink => {
+                // Звенья «полюс — ноль» через две октавы: каждое спадает на 6 дБ на
+                // двух октавах, вместе — −3 дБ на октаву с неровностью около 0.5 дБ
+                (0..)
+                    .map(|k| PINK_LOWEST_HZ * PINK_SPACING.powi(k))
+                    .take_while(|&pole| pole * PINK_SPACING.sqrt() < nyquist * 0.9)
+                    .map(|pole| Biquad::pole_zero(sample_rate, pole, pole * PINK_SPACING.sqrt()))
+                    .collect()
+            }
+            NoiseColor::Brown => vec![Biquad::lowpass1(sample_rate, BROWN_CORNER_HZ)],
+            NoiseColor::BandLimited { low_hz, high_hz } => {
+                let high = high_hz.clamp(2.0, nyquist * 0.98);
+                let low = low_hz.clamp(1.0, high * 0.99);
+                let q = std::f32::consts::FRAC_1_SQRT_2;
+                vec![
+                    Biquad::highpass(sample_rate, low, q),
+                    Biquad::highpass(sample_rate, low, q),
+                    Biquad::lowpass(sample_rate, high, q),
+                    Biquad::lowpass(sample_rate, high, q),
+                ]
+            }
+        };
+        let mut shaper = Self { stages, gain: 1.0 };
+        // Мощность белого шума после фильтра — энергия импульсной характеристики;
+        // за секунду все звенья успевают затухнуть
+        let energy: f64 = (0..sample_rate as usize)
+            .map(|i| shaper.process(if i == 0 { 1.0 } else { 0.0 }) as f64)
+            .map(|h| h * h)
+            .sum();
+        shaper.stages.iter_mut().for_each(Biquad::reset);
+        shaper.gain = (1.0 / energy.max(1e-12)).sqrt() as f32;
+        Some(shaper)
+    }
+
+    fn process(&mut self, x: f32) -> f32 {
+        let y = self.stages.iter_mut().fold(x, |y, stage| stage.process(y));
+        y * self.gain
+    }
+}
+
+/// Распределение амплитуд импульсов (среднее всегда 1)
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AmplitudeDistribution {
+    Fixed,
+    Uniform,
+    #[serde(rename = "exp")]
+    Exponential,
+}
+
+impl AmplitudeDistribution {
+    pub const ALL: &'static [AmplitudeDistribution] = &[
+        AmplitudeDistribution::Fixed,
+        AmplitudeDistribution::Uniform,
+        AmplitudeDistribution::Exponential,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            AmplitudeDistribution::Fixed => "Fixed",
+            AmplitudeDistribution::Uniform => "Uniform",
+            AmplitudeDistribution::Exponential => "Exponential",
+        }
+    }
+
+    fn sample(self, rng: &mut impl Rng) -> f32 {
+        match self {
+            Self::Fixed => 1.0,
+            Self::Uniform => rng.random_range(0.0..2.0),
+            Self::Exponential => rng.sample(Exp1),
+        }
+    }
+}
+
+impl clap::ValueEnum for AmplitudeDistribution {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[Self::Fixed, Self::Uniform, Self::Exponential]
+    }
+
+    fn to_possible_value<'a>(&self) -> Option<clap::builder::PossibleValue> {
+        Some(match self {
+            Self::Fixed => clap::builder::PossibleValue::new("fixed"),
+            Self::Uniform => clap::builder::PossibleValue::new("uniform"),
+            Self::Exponential => clap::builder::PossibleValue::new("exp"),
+        })
+    }
+}
+
+/// Параметры импульсного шума и треска
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ImpulseParams {
+    /// Средняя частота импульсов, в секунду
+    pub rate_hz: f32,
+    /// Длительность одной пачки, мс (для треска не используется)
+    pub duration_ms: f32,
+    /// Распределение амплитуд
+    pub amplitude: AmplitudeDistribution,
+}
+
+impl Default for ImpulseParams {
+    fn default() -> Self {
+        Self {
+            rate_hz: 2.0,
+            duration_ms: 5.0,
+            amplitude: AmplitudeDistribution::Exponential,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
 pub struct NoiseParams {
     pub level: u8,
+    pub kind: NoiseKind,
+    /// Окраска спектра шума
+    pub color: NoiseColor,
+    pub impulse: ImpulseParams,
     pub env: EnvelopeKind,
     pub repeat: f32,
+    /// Отрезок, на который растягивается огибающая
+    pub anchor: EnvelopeAnchor,
+    /// Доли смешивания выхода эффекта со входом
+    pub mix: EffectMix,
 }
 
 impl Default for NoiseParams {
     fn default() -> Self {
         Self {
             level: 0,
+            kind: NoiseKind::Gaussian,
+            color: NoiseColor::White,
+            impulse: ImpulseParams::default(),
             env: EnvelopeKind::Const,
             repeat: 1.0,
+            anchor: EnvelopeAnchor::Audio,
+            mix: EffectMix::default(),
         }
     }
 }
 
+/// С/Ш в дБ, который даёт уровень шума `level`: 100 — около 0 дБ, 0 — без шума
+pub fn level_snr_db(level: u8) -> f32 {
+    if level == 0 {
+        return f32::INFINITY;
+    }
+    30.0 * (1.0 - f32::from(level) / 100.0) + 0.1
+}
+
 pub struct NoiseProcessor {
+    #[deprecated(since = "0.1.0", note = "используйте `params()` и `set_params()`")]
     pub params: NoiseParams,
 }
 
+impl Default for NoiseProcessor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[allow(deprecated)]
 impl NoiseProcessor {
     pub fn new() -> Self {
         Self {
@@ -35,33 +354,164 @@ impl NoiseProcessor {
         Self { params }
     }
 
-    /// Применяет AWGN шум к массиву сэмплов
-    pub fn apply_noise(&self, samples: &mut [f32]) -> Result<()> {
-        if self.params.level == 0 {
+    pub fn params(&self) -> &NoiseParams {
+        &self.params
+    }
+
+    pub fn set_params(&mut self, params: NoiseParams) {
+        self.params = params;
+    }
+
+    /// Применяет шум выбранного вида к буферу сэмплов
+    pub fn apply_noise(&self, buffer: &mut SampleBuffer) -> Result<()> {
+        let image = 0..buffer.len();
+        self.apply_noise_anchored(buffer, &image)
+    }
+
+    /// Применяет шум, привязывая огибающую к телу изображения `image` при якоре `Image`
+    pub fn apply_noise_anchored(
+        &self,
+        buffer: &mut SampleBuffer,
+        image: &Range<usize>,
+    ) -> Result<()> {
+        self.apply_noise_with_rng(buffer, image, false, &mut rng())
+    }
+
+    /// Применяет шум к сигналу с частотой буфера, беря случайные значения из
+    /// генератора `rng`. Шум выбирается из `rng` по порядку, огибающая и смешивание
+    /// идут кусками, при `parallel` — в пуле rayon
+    pub fn apply_noise_with_rng(
+        &self,
+        buffer: &mut SampleBuffer,
+        image: &Range<usize>,
+        parallel: bool,
+        rng: &mut impl Rng,
+    ) -> Result<()> {
+        let sample_rate = buffer.rate;
+        let samples = &mut buffer.data;
+        if self.params.level == 0 || samples.is_empty() {
             return Ok(());
         }
+        let impulse = &self.params.impulse;
+        let overlap = impulse.rate_hz * impulse.duration_ms / 1000.0;
+        if self.params.kind == NoiseKind::Impulse && overlap > MAX_IMPULSE_OVERLAP {
+            return Err(SstvError::invalid(
+                "impulse",
+                messages::impulse_overlap(overlap, MAX_IMPULSE_OVERLAP),
+            ));
+        }
 
         let len = samples.len();
 
         // Рассчитываем SNR и параметры шума
-        let snr_db = 30.0 * (1.0 - f32::from(self.params.level) / 100.0) + 0.1;
+        let snr_db = level_snr_db(self.params.level);
         let rms_sig = self.calculate_rms_signal(samples);
         let rms_noise = rms_sig / 10f32.powf(snr_db / 20.0);
 
-        // Создаем генератор нормального распределения
-        let normal = Normal::new(0.0, rms_noise).unwrap();
-        let mut rng = rng();
+        self.params.color.validate()?;
+        let mut noise: Vec<f32> = match self.params.kind {
+            NoiseKind::Gaussian => {
+                // Создаем генератор нормального распределения
+                let normal = Normal::new(0.0, rms_noise).unwrap();
+                (0..len).map(|_| normal.sample(rng)).collect()
+            }
+            NoiseKind::Impulse => {
+                self.impulse_noise(len, rms_noise * IMPULSE_CREST, sample_rate, rng)
+            }
+            NoiseKind::Crackle => {
+                self.crackle_noise(len, rms_noise * IMPULSE_CREST, sample_rate, rng)
+            }
+        };
 
-        // Применяем шум с огибающей
-        for (i, sample) in samples.iter_mut().enumerate() {
-            let env_factor = self.params.env.factor(i, len, self.params.repeat);
-            let noise_value = normal.sample(&mut rng) * env_factor;
-            *sample = (*sample + noise_value).clamp(-1.0, 1.0);
+        if let Some(mut shaper) = NoiseShaper::new(self.params.color, sample_rate) {
+            noise.iter_mut().for_each(|n| *n = shaper.process(*n));
         }
 
+        // Применяем шум с огибающей
+        let envelope = self.params.env.factors_with_rng(
+            len,
+            self.params.repeat,
+            sample_rate,
+            self.params.anchor,
+            image,
+            parallel,
+            rng,
+        );
+        dsp::mix(samples, &noise, envelope.as_deref(), 1.0, parallel);
+
         Ok(())
     }
 
+    /// Пачки гауссова шума с экспоненциальным спадом в моменты пуассоновского потока
+    fn impulse_noise(
+        &self,
+        len: usize,
+        amplitude: f32,
+        sample_rate: u32,
+        rng: &mut impl Rng,
+    ) -> Vec<f32> {
+        let duration = ms_to_samples(self.params.impulse.duration_ms, sample_rate).max(1);
+        let mut noise = vec![0.0; len];
+
+        for start in self.arrivals(len, self.params.impulse.rate_hz, sample_rate, rng) {
+            let peak = amplitude * self.params.impulse.amplitude.sample(rng);
+            for (k, n) in noise[start..].iter_mut().take(duration).enumerate() {
+                let decay = (-3.0 * k as f32 / duration as f32).exp();
+                let white: f32 = rng.sample(StandardNormal);
+                *n += peak * decay * white;
+            }
+        }
+        noise
+    }
+
+    /// Одиночные щелчки случайной полярности с быстрым затуханием
+    fn crackle_noise(
+        &self,
+        len: usize,
+        amplitude: f32,
+        sample_rate: u32,
+        rng: &mut impl Rng,
+    ) -> Vec<f32> {
+        let pop = ms_to_samples(POP_MS, sample_rate).max(1);
+        let rate = self.params.impulse.rate_hz * CRACKLE_RATE_RATIO;
+        let mut noise = vec![0.0; len];
+
+        for start in self.arrivals(len, rate, sample_rate, rng) {
+            let sign = if rng.random::<bool>() { 1.0 } else { -1.0 };
+            let peak = sign * amplitude * self.params.impulse.amplitude.sample(rng);
+            for (k, n) in noise[start..].iter_mut().take(pop).enumerate() {
+                *n += peak * (-4.0 * k as f32 / pop as f32).exp();
+            }
+        }
+        noise
+    }
+
+    /// Моменты событий пуассоновского потока со средней частотой `rate_hz`
+    fn arrivals(
+        &self,
+        len: usize,
+        rate_hz: f32,
+        sample_rate: u32,
+        rng: &mut impl Rng,
+    ) -> Vec<usize> {
+        if rate_hz <= 0.0 {
+            return Vec::new();
+        }
+
+        // Время копится в f64: на длинном сигнале шаг меньше сэмпла пропадал бы при
+        // округлении f32, и цикл не кончался бы
+        let mean_gap = sample_rate as f64 / rate_hz as f64;
+        let mut times = Vec::new();
+        let mut t = 0.0f64;
+        loop {
+            t += rng.sample::<f32, _>(Exp1) as f64 * mean_gap;
+            if t >= len as f64 {
+                return times;
+            }
+            times.push(t as usize);
+        }
+    }
+
     /// Рассчитывает RMS уровень сигнала
     fn calculate_rms_signal(&self, samples: &[f32]) -> f32 {
         let sum_squares: f32 = samples.iter().map(|x| x * x).sum();
@@ -70,10 +520,7 @@ impl NoiseProcessor {
 
     /// Рассчитывает SNR для заданного уровня шума
     pub fn calculate_snr_db(&self) -> f32 {
-        if self.params.level == 0 {
-            return f32::INFINITY;
-        }
-        30.0 * (1.0 - f32::from(self.params.level) / 100.0) + 0.1
+        level_snr_db(self.params.level)
     }
 
     /// Устанавливает уровень шума
@@ -81,6 +528,11 @@ impl NoiseProcessor {
         self.params.level = level.min(100);
     }
 
+    /// Устанавливает вид шума
+    pub fn set_kind(&mut self, kind: NoiseKind) {
+        self.params.kind = kind;
+    }
+
     /// Устанавливает тип огибающей
     pub fn set_envelope(&mut self, env: EnvelopeKind) {
         self.params.env = env;
@@ -91,6 +543,11 @@ impl NoiseProcessor {
         self.params.repeat = repeat.max(0.1);
     }
 
+    /// Устанавливает привязку огибающей
+    pub fn set_anchor(&mut self, anchor: EnvelopeAnchor) {
+        self.params.anchor = anchor;
+    }
+
     /// Проверяет, включен ли шум
     pub fn is_enabled(&self) -> bool {
         self.params.level > 0
@@ -99,15 +556,166 @@ impl NoiseProcessor {
     /// Возвращает описание текущих настроек шума
     pub fn describe(&self) -> String {
         if !self.is_enabled() {
-            return "Шум отключен".to_string();
+            return messages::noise_off().to_string();
         }
 
-        format!(
-            "Шум: {}%, огибающая: {}, повторение: {:.1}x, SNR: {:.1} дБ",
+        messages::noise_describe(
+            self.params.kind.name(),
+            &self.params.color.to_string(),
             self.params.level,
             self.params.env.name(),
             self.params.repeat,
-            self.calculate_snr_db()
+            self.calculate_snr_db(),
+        )
+    }
+}
+
+#[allow(deprecated)]
+impl ChannelEffect for NoiseProcessor {
+    fn name(&self) -> &'static str {
+        messages::noise()
+    }
+
+    fn mix(&self) -> EffectMix {
+        self.params.mix
+    }
+
+    fn apply(&self, buffer: &mut SampleBuffer, ctx: &EffectContext) -> Result<()> {
+        self.apply_noise_with_rng(
+            buffer,
+            &ctx.image_span,
+            ctx.parallel,
+            &mut *ctx.rng.borrow_mut(),
         )
     }
+
+    fn is_enabled(&self) -> bool {
+        NoiseProcessor::is_enabled(self)
+    }
+
+    fn describe(&self) -> String {
+        NoiseProcessor::describe(self)
+    }
+
+    fn sync_params(&mut self, params: &ProcessingParams) {
+        self.params = params.noise.clone();
+    }
+
+    /// Мощность шума — по уровню, см. [`level_snr_db`], ослабленная огибающей
+    fn analyze(&self, buffer: &SampleBuffer, ctx: &EffectContext) -> EffectEstimate {
+        let p = &self.params;
+        let envelope = p.env.mean_power(
+            buffer.len(),
+            p.repeat,
+            buffer.rate,
+            p.anchor,
+            &ctx.image_span,
+            &mut ctx.rng.borrow().clone(),
+        );
+        EffectEstimate {
+            added_power_db: Some(effect::power_db(envelope) - self.calculate_snr_db()),
+            ..EffectEstimate::new(self.name(), self.describe())
+        }
+    }
+
+    /// Блоками считается только гауссов шум: пачки и щелчки раскладываются по всему
+    /// сигналу сразу. Уровень берётся от СКЗ сигнала кодера, а не измеряется
+    fn block_processor(&self, ctx: &StreamContext) -> Result<Box<dyn BlockProcessor>> {
+        if !self.is_enabled() {
+            return Ok(Box::new(Bypass));
+        }
+        if self.params.kind != NoiseKind::Gaussian {
+            return Err(SstvError::invalid(
+                "noise.kind",
+                messages::streaming_gaussian_only(self.params.kind.name()),
+            ));
+        }
+        self.params.color.validate()?;
+        let rms_noise = ctx.signal_rms / 10f32.powf(self.calculate_snr_db() / 20.0);
+        let normal = Normal::new(0.0, rms_noise)
+            .map_err(|e| SstvError::invalid("noise.level", e.to_string()))?;
+        Ok(Box::new(NoiseBlocks {
+            params: self.params.clone(),
+            normal,
+            shaper: NoiseShaper::new(self.params.color, ctx.sample_rate),
+            len: ctx.len,
+            image: ctx.image_span.clone(),
+            sample_rate: ctx.sample_rate,
+            rng: ctx.fork_rng(),
+        }))
+    }
+}
+
+/// Гауссов шум блоками: отсчёт шума, окраска и коэффициент огибающей на каждый сэмпл
+struct NoiseBlocks {
+    params: NoiseParams,
+    normal: Normal<f32>,
+    shaper: Option<NoiseShaper>,
+    len: usize,
+    image: Range<usize>,
+    sample_rate: u32,
+    rng: StdRng,
+}
+
+impl BlockProcessor for NoiseBlocks {
+    fn process_block(&mut self, block: &mut [f32], start: usize) -> Result<()> {
+        let p = &self.params;
+        for (k, sample) in block.iter_mut().enumerate() {
+            let mut noise = self.normal.sample(&mut self.rng);
+            if let Some(shaper) = &mut self.shaper {
+                noise = shaper.process(noise);
+            }
+            let factor = p.env.factor_anchored(
+                start + k,
+                self.len,
+                p.repeat,
+                self.sample_rate,
+                p.anchor,
+                &self.image,
+                &mut self.rng,
+            );
+            *sample += noise * factor;
+        }
+        Ok(())
+    }
+    fn save_state(&mut self) -> Result<serde_json::Value> {
+        effect::to_block_state(&NoiseState {
+            seed: effect::checkpoint_rng(&mut self.rng),
+            shaper: self
+                .shaper
+                .iter()
+                .flat_map(|shaper| shaper.stages.iter().map(Biquad::state))
+                .collect(),
+        })
+    }
+
+    fn restore_state(&mut self, state: &serde_json::Value) -> Result<()> {
+        let state: NoiseState = effect::from_block_state(state, messages::noise())?;
+        let stages: &mut [Biquad] = match &mut self.shaper {
+            Some(shaper) => &mut shaper.stages,
+            None => &mut [],
+        };
+        if state.shaper.len() != stages.len() {
+            return Err(SstvError::invalid(
+                "state",
+                messages::noise_state_stages(state.shaper.len(), stages.len()),
+            ));
+        }
+        for (stage, z) in stages.iter_mut().zip(state.shaper) {
+            stage.set_state(z);
+        }
+        self.rng = StdRng::seed_from_u64(state.seed);
+        Ok(())
+    }
+}
+
+/// Контрольная точка гауссова шума: зерно генератора и состояние звеньев окраски
+#[derive(Serialize, Deserialize)]
+struct NoiseState {
+    seed: u64,
+    shaper: Vec<(f64, f64)>,
+}
+
+fn ms_to_samples(ms: f32, sample_rate: u32) -> usize {
+    (ms / 1000.0 * sample_rate as f32).round() as usize
 }
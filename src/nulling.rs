@@ -0,0 +1,152 @@
+use crate::dsp;
+use crate::effect;
+use crate::error::Result;
+use crate::messages;
+use crate::processor::{ProcessingParams, SSTVProcessor};
+use crate::signal;
+use image::DynamicImage;
+
+/// Глубина подавления, с которой два сигнала считаются совпадающими, дБ. С запасом
+/// покрывает округление 16-битного WAV (≈ 90 дБ для полной шкалы)
+pub const TRANSPARENT_DEPTH_DB: f64 = 80.0;
+
+/// Итог вычитания двух сигналов после выравнивания
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct NullReport {
+    /// Сдвиг второго сигнала относительно первого, сэмплов (положительный — запаздывает)
+    pub offset: isize,
+    /// Сэмплов, общих для обоих сигналов после сдвига
+    pub overlap: usize,
+    /// Сэмплов, которые есть только в одном из сигналов; в остаток они входят целиком
+    pub unmatched: usize,
+    /// Энергия первого сигнала (сумма квадратов)
+    pub reference_energy: f64,
+    /// Энергия разности
+    pub residual_energy: f64,
+    /// Наибольший по модулю сэмпл разности
+    pub peak_residual: f32,
+}
+
+impl NullReport {
+    /// Подавление: энергия первого сигнала к энергии остатка, дБ (бесконечность при полном
+    /// совпадении)
+    pub fn depth_db(&self) -> f64 {
+        if self.residual_energy > 0.0 {
+            10.0 * (self.reference_energy / self.residual_energy).log10()
+        } else {
+            f64::INFINITY
+        }
+    }
+
+    /// Остаток ниже первого сигнала не меньше чем на `min_depth_db`
+    pub fn is_transparent(&self, min_depth_db: f64) -> bool {
+        self.depth_db() >= min_depth_db
+    }
+
+    /// Возвращает описание итога
+    pub fn describe(&self) -> String {
+        let depth = self.depth_db();
+        let depth = if depth.is_finite() {
+            messages::null_depth(depth)
+        } else {
+            messages::null_identical().to_string()
+        };
+        messages::null_describe(
+            self.offset,
+            &depth,
+            self.peak_residual,
+            self.residual_energy,
+            self.unmatched,
+        )
+    }
+}
+
+/// Вычитает из `reference` сигнал `candidate`, выровненный с точностью до сэмпла: сдвиг
+/// ищется по взаимной корреляции в пределах ±`max_offset` сэмплов, см. [`dsp::best_lag`]
+pub fn null_test(reference: &[f32], candidate: &[f32], max_offset: usize) -> NullReport {
+    null_at(
+        reference,
+        candidate,
+        dsp::best_lag(reference, candidate, max_offset),
+    )
+}
+
+/// Вычитает из `reference` сигнал `candidate`, сдвинутый на `offset` сэмплов:
+/// `candidate[i + offset]` сравнивается с `reference[i]`. Где одного из сигналов нет,
+/// он считается нулём, поэтому разная длина тоже даёт остаток
+pub fn null_at(reference: &[f32], candidate: &[f32], offset: isize) -> NullReport {
+    let at = |signal: &[f32], i: isize| -> Option<f32> {
+        usize::try_from(i).ok().and_then(|i| signal.get(i).copied())
+    };
+    // Индексы первого сигнала, на которые приходится хотя бы один из сигналов
+    let start = (-offset).min(0);
+    let end = (reference.len() as isize).max(candidate.len() as isize - offset);
+
+    let mut report = NullReport {
+        offset,
+        overlap: 0,
+        unmatched: 0,
+        reference_energy: reference.iter().map(|&v| v as f64 * v as f64).sum(),
+        residual_energy: 0.0,
+        peak_residual: 0.0,
+    };
+    for i in start..end {
+        let (a, b) = (at(reference, i), at(candidate, i + offset));
+        if a.is_some() && b.is_some() {
+            report.overlap += 1;
+        } else if a.is_some() || b.is_some() {
+            report.unmatched += 1;
+        }
+        let diff = a.unwrap_or(0.0) - b.unwrap_or(0.0);
+        report.residual_energy += diff as f64 * diff as f64;
+        report.peak_residual = report.peak_residual.max(diff.abs());
+    }
+    report
+}
+
+/// Проверка прозрачности на полном сигнале: кодирует `main_image` и применяет эффекты с
+/// параметрами `reference` и `candidate` (например, с эффектом и с его обходом) и сравнивает
+/// результаты, см. [`null_test`]. Без зерна у обоих наборов берётся общее, чтобы случайные
+/// эффекты совпадали. Разница групповых задержек эффектов (см.
+/// [`ChannelEffect::group_delay`](effect::ChannelEffect::group_delay)) известна заранее: окно поиска сдвига расширяется до
+/// неё, а при `max_offset` = 0 сигналы сдвигаются ровно на неё
+pub fn null_params(
+    reference: &ProcessingParams,
+    candidate: &ProcessingParams,
+    main_image: &DynamicImage,
+    retarder_image: Option<&DynamicImage>,
+    max_offset: usize,
+) -> Result<NullReport> {
+    let seed = reference.seed.or(candidate.seed).unwrap_or(0);
+    let render = |params: &ProcessingParams| -> Result<Vec<f32>> {
+        let params = ProcessingParams {
+            seed: Some(params.seed.unwrap_or(seed)),
+            ..params.clone()
+        };
+        let main_image = params.overlay_params().apply(main_image)?;
+        let mut signal = signal::encode(&main_image, &params.encoder);
+        let retarder = retarder_image.map(|image| signal::encode(image, &params.encoder));
+        signal.degrade(&params, retarder.as_ref())?;
+        Ok(signal.into_samples())
+    };
+    // Групповая задержка набора, секунд
+    let delay = |params: &ProcessingParams| {
+        effect::group_delay(
+            &SSTVProcessor::default_effects(params),
+            params.signal_rate(),
+        ) as f64
+            / params.signal_rate() as f64
+    };
+    // Сдвиг в сэмплах выхода: частоты наборов должны совпадать, иначе остаток и так велик
+    let expected =
+        ((delay(candidate) - delay(reference)) * reference.signal_rate() as f64).round() as isize;
+    let (reference, candidate) = (render(reference)?, render(candidate)?);
+    if max_offset == 0 {
+        return Ok(null_at(&reference, &candidate, expected));
+    }
+    Ok(null_test(
+        &reference,
+        &candidate,
+        max_offset.max(expected.unsigned_abs()),
+    ))
+}
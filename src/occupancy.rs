@@ -0,0 +1,180 @@
+use crate::dsp;
+use crate::messages;
+use serde::Serialize;
+
+/// Разрешение спектра, Гц (округляется до степени двойки отсчётов)
+const RESOLUTION_HZ: u32 = 10;
+/// Доля мощности за каждым краем занимаемой полосы: 0,5% снизу и сверху — 99% внутри
+const EDGE_SHARE: f64 = 0.005;
+
+/// Спектральная маска канала SSB в звуковых частотах: внутри канала уровень не
+/// ограничен, в скатах по обе стороны — не выше `skirt_db`, дальше — не выше `floor_db`.
+/// Уровни — в дБ относительно пика спектра при разрешении 10 Гц
+#[derive(Copy, Clone, Debug, PartialEq, Serialize)]
+pub struct SpectralMask {
+    /// Нижний край канала, Гц
+    pub low_hz: f32,
+    /// Верхний край канала, Гц
+    pub high_hz: f32,
+    /// Ширина ската за каждым краем, Гц
+    pub skirt_hz: f32,
+    /// Предел в скатах, дБ
+    pub skirt_db: f32,
+    /// Предел дальше скатов, дБ
+    pub floor_db: f32,
+}
+
+impl Default for SpectralMask {
+    /// Канал 300–3000 Гц: 2,7 кГц — наибольшая полоса SSTV в планах диапазонов IARU
+    fn default() -> Self {
+        Self {
+            low_hz: 300.0,
+            high_hz: 3000.0,
+            skirt_hz: 250.0,
+            skirt_db: -26.0,
+            floor_db: -40.0,
+        }
+    }
+}
+
+impl SpectralMask {
+    /// Предел на частоте `freq_hz`, дБ; `None` внутри канала
+    pub fn limit_db(&self, freq_hz: f32) -> Option<f32> {
+        let outside = (self.low_hz - freq_hz).max(freq_hz - self.high_hz);
+        if outside <= 0.0 {
+            None
+        } else if outside <= self.skirt_hz {
+            Some(self.skirt_db)
+        } else {
+            Some(self.floor_db)
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        messages::mask_describe(
+            self.low_hz,
+            self.high_hz,
+            self.skirt_hz,
+            self.skirt_db,
+            self.floor_db,
+        )
+    }
+}
+
+/// Занимаемая полоса сигнала и его запас до спектральной маски
+#[derive(Copy, Clone, Debug, PartialEq, Serialize)]
+pub struct Occupancy {
+    /// Нижний край полосы с 99% мощности, Гц: ниже лежит 0,5%
+    pub low_hz: f32,
+    /// Верхний край полосы с 99% мощности, Гц: выше лежит 0,5%
+    pub high_hz: f32,
+    /// Наименьший запас до маски по всем частотам, дБ; отрицательный — маска нарушена
+    pub mask_margin_db: f32,
+    /// Частота наименьшего запаса, Гц
+    pub worst_hz: f32,
+    pub mask: SpectralMask,
+}
+
+impl Occupancy {
+    /// Занимаемая полоса (99% мощности), Гц
+    pub fn bandwidth_hz(&self) -> f32 {
+        self.high_hz - self.low_hz
+    }
+
+    /// Спектр нигде не выходит за маску
+    pub fn complies(&self) -> bool {
+        self.mask_margin_db >= 0.0
+    }
+
+    pub fn describe(&self) -> String {
+        let bandwidth =
+            messages::occupancy_bandwidth(self.bandwidth_hz(), self.low_hz, self.high_hz);
+        let mask = if self.complies() {
+            messages::occupancy_mask_ok(self.mask_margin_db, self.worst_hz)
+        } else {
+            messages::occupancy_mask_violated(-self.mask_margin_db, self.worst_hz)
+        };
+        format!("{}, {}", bandwidth, mask)
+    }
+}
+
+/// Меряет занимаемую полосу `samples` с частотой `sample_rate` (по Регламенту
+/// радиосвязи МСЭ, п. 1.153: по 0,5% мощности за краями) и запас до маски `mask` по спектру мощности, усреднённому по окнам
+/// Ханна. `None`, если сигнал короче одного окна или это тишина
+pub fn measure(samples: &[f32], sample_rate: u32, mask: &SpectralMask) -> Option<Occupancy> {
+    let fft_len = (sample_rate / RESOLUTION_HZ).max(2).next_power_of_two() as usize;
+    let frames = dsp::power_frames(samples, fft_len);
+    if frames.is_empty() {
+        return None;
+    }
+    let mut spectrum = vec![0.0f64; fft_len / 2 + 1];
+    for frame in &frames {
+        for (sum, &p) in spectrum.iter_mut().zip(frame) {
+            *sum += p as f64;
+        }
+    }
+    let total: f64 = spectrum.iter().sum();
+    let peak = spectrum.iter().copied().fold(0.0, f64::max);
+    if total <= 0.0 || peak <= 0.0 {
+        return None;
+    }
+    let bin_hz = sample_rate as f32 / fft_len as f32;
+
+    let mut below = 0.0;
+    let mut low = None;
+    let mut high = 0;
+    for (k, &p) in spectrum.iter().enumerate() {
+        if below + p > EDGE_SHARE * total && low.is_none() {
+            low = Some(k);
+        }
+        below += p;
+        if below <= (1.0 - EDGE_SHARE) * total {
+            high = k + 1;
+        }
+    }
+
+    let (mut margin, mut worst) = (f32::INFINITY, 0);
+    for (k, &p) in spectrum.iter().enumerate() {
+        let Some(limit) = mask.limit_db(k as f32 * bin_hz) else {
+            continue;
+        };
+        let level = 10.0 * (p / peak).max(1e-30).log10() as f32;
+        if limit - level < margin {
+            margin = limit - level;
+            worst = k;
+        }
+    }
+    Some(Occupancy {
+        low_hz: low.unwrap_or(0) as f32 * bin_hz,
+        high_hz: high.min(spectrum.len() - 1) as f32 * bin_hz,
+        mask_margin_db: margin,
+        worst_hz: worst as f32 * bin_hz,
+        mask: *mask,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::tone;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn occupancy_of_tone_and_noise() {
+        let rate = 8000;
+        let mask = SpectralMask::default();
+        let tone = tone(1500.0, rate, 4 * rate as usize);
+        let occupancy = measure(&tone, rate, &mask).unwrap();
+        assert!(occupancy.bandwidth_hz() < 50.0, "{occupancy:?}");
+        assert!((occupancy.low_hz - 1500.0).abs() < 20.0, "{occupancy:?}");
+        assert!(occupancy.complies(), "{occupancy:?}");
+
+        let mut rng = StdRng::seed_from_u64(1);
+        let noise: Vec<f32> = (0..4 * rate).map(|_| rng.random_range(-1.0..1.0)).collect();
+        let occupancy = measure(&noise, rate, &mask).unwrap();
+        assert!(occupancy.bandwidth_hz() > 3800.0, "{occupancy:?}");
+        assert!(!occupancy.complies(), "{occupancy:?}");
+        assert!(measure(&noise[..10], rate, &mask).is_none());
+    }
+}
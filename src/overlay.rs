@@ -0,0 +1,362 @@
+use crate::encoder::fit_to_mode;
+use crate::error::{Result, SstvError};
+use crate::messages;
+use crate::modes::MARTIN_M1;
+use ab_glyph::{Font, FontVec, PxScale, ScaleFont};
+use image::{DynamicImage, Rgb, RgbImage};
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Наибольшая высота строки надписи, пикселей: выше изображение режима уже не вмещает
+pub const MAX_OVERLAY_SIZE: f32 = 128.0;
+
+/// Ширина знакоместа встроенного шрифта 5×7 с промежутком, точек
+const PIXEL_CELL_WIDTH: u32 = 6;
+/// Высота строки встроенного шрифта с междустрочием, точек
+const PIXEL_CELL_HEIGHT: u32 = 9;
+
+/// Глифы 5×7 знаков ASCII от пробела до `~`: пять столбцов, младший бит — верхняя точка
+#[rustfmt::skip]
+const FONT: [[u8; 5]; 95] = [
+    [0x00, 0x00, 0x00, 0x00, 0x00], [0x00, 0x00, 0x5F, 0x00, 0x00], [0x00, 0x07, 0x00, 0x07, 0x00],
+    [0x14, 0x7F, 0x14, 0x7F, 0x14], [0x24, 0x2A, 0x7F, 0x2A, 0x12], [0x23, 0x13, 0x08, 0x64, 0x62],
+    [0x36, 0x49, 0x55, 0x22, 0x50], [0x00, 0x05, 0x03, 0x00, 0x00], [0x00, 0x1C, 0x22, 0x41, 0x00],
+    [0x00, 0x41, 0x22, 0x1C, 0x00], [0x08, 0x2A, 0x1C, 0x2A, 0x08], [0x08, 0x08, 0x3E, 0x08, 0x08],
+    [0x00, 0x50, 0x30, 0x00, 0x00], [0x08, 0x08, 0x08, 0x08, 0x08], [0x00, 0x60, 0x60, 0x00, 0x00],
+    [0x20, 0x10, 0x08, 0x04, 0x02], [0x3E, 0x51, 0x49, 0x45, 0x3E], [0x00, 0x42, 0x7F, 0x40, 0x00],
+    [0x42, 0x61, 0x51, 0x49, 0x46], [0x21, 0x41, 0x45, 0x4B, 0x31], [0x18, 0x14, 0x12, 0x7F, 0x10],
+    [0x27, 0x45, 0x45, 0x45, 0x39], [0x3C, 0x4A, 0x49, 0x49, 0x30], [0x01, 0x71, 0x09, 0x05, 0x03],
+    [0x36, 0x49, 0x49, 0x49, 0x36], [0x06, 0x49, 0x49, 0x29, 0x1E], [0x00, 0x36, 0x36, 0x00, 0x00],
+    [0x00, 0x56, 0x36, 0x00, 0x00], [0x08, 0x14, 0x22, 0x41, 0x00], [0x14, 0x14, 0x14, 0x14, 0x14],
+    [0x00, 0x41, 0x22, 0x14, 0x08], [0x02, 0x01, 0x51, 0x09, 0x06], [0x32, 0x49, 0x79, 0x41, 0x3E],
+    [0x7E, 0x11, 0x11, 0x11, 0x7E], [0x7F, 0x49, 0x49, 0x49, 0x36], [0x3E, 0x41, 0x41, 0x41, 0x22],
+    [0x7F, 0x41, 0x41, 0x22, 0x1C], [0x7F, 0x49, 0x49, 0x49, 0x41], [0x7F, 0x09, 0x09, 0x01, 0x01],
+    [0x3E, 0x41, 0x41, 0x51, 0x32], [0x7F, 0x08, 0x08, 0x08, 0x7F], [0x00, 0x41, 0x7F, 0x41, 0x00],
+    [0x20, 0x40, 0x41, 0x3F, 0x01], [0x7F, 0x08, 0x14, 0x22, 0x41], [0x7F, 0x40, 0x40, 0x40, 0x40],
+    [0x7F, 0x02, 0x04, 0x02, 0x7F], [0x7F, 0x04, 0x08, 0x10, 0x7F], [0x3E, 0x41, 0x41, 0x41, 0x3E],
+    [0x7F, 0x09, 0x09, 0x09, 0x06], [0x3E, 0x41, 0x51, 0x21, 0x5E], [0x7F, 0x09, 0x19, 0x29, 0x46],
+    [0x46, 0x49, 0x49, 0x49, 0x31], [0x01, 0x01, 0x7F, 0x01, 0x01], [0x3F, 0x40, 0x40, 0x40, 0x3F],
+    [0x1F, 0x20, 0x40, 0x20, 0x1F], [0x7F, 0x20, 0x18, 0x20, 0x7F], [0x63, 0x14, 0x08, 0x14, 0x63],
+    [0x03, 0x04, 0x78, 0x04, 0x03], [0x61, 0x51, 0x49, 0x45, 0x43], [0x00, 0x7F, 0x41, 0x41, 0x00],
+    [0x02, 0x04, 0x08, 0x10, 0x20], [0x00, 0x41, 0x41, 0x7F, 0x00], [0x04, 0x02, 0x01, 0x02, 0x04],
+    [0x40, 0x40, 0x40, 0x40, 0x40], [0x00, 0x01, 0x02, 0x04, 0x00], [0x20, 0x54, 0x54, 0x54, 0x78],
+    [0x7F, 0x48, 0x44, 0x44, 0x38], [0x38, 0x44, 0x44, 0x44, 0x20], [0x38, 0x44, 0x44, 0x48, 0x7F],
+    [0x38, 0x54, 0x54, 0x54, 0x18], [0x08, 0x7E, 0x09, 0x01, 0x02], [0x08, 0x14, 0x54, 0x54, 0x3C],
+    [0x7F, 0x08, 0x04, 0x04, 0x78], [0x00, 0x44, 0x7D, 0x40, 0x00], [0x20, 0x40, 0x44, 0x3D, 0x00],
+    [0x00, 0x7F, 0x10, 0x28, 0x44], [0x00, 0x41, 0x7F, 0x40, 0x00], [0x7C, 0x04, 0x18, 0x04, 0x78],
+    [0x7C, 0x08, 0x04, 0x04, 0x78], [0x38, 0x44, 0x44, 0x44, 0x38], [0x7C, 0x14, 0x14, 0x14, 0x08],
+    [0x08, 0x14, 0x14, 0x18, 0x7C], [0x7C, 0x08, 0x04, 0x04, 0x08], [0x48, 0x54, 0x54, 0x54, 0x20],
+    [0x04, 0x3F, 0x44, 0x40, 0x20], [0x3C, 0x40, 0x40, 0x20, 0x7C], [0x1C, 0x20, 0x40, 0x20, 0x1C],
+    [0x3C, 0x40, 0x30, 0x40, 0x3C], [0x44, 0x28, 0x10, 0x28, 0x44], [0x0C, 0x50, 0x50, 0x50, 0x3C],
+    [0x44, 0x64, 0x54, 0x4C, 0x44], [0x00, 0x08, 0x36, 0x41, 0x00], [0x00, 0x00, 0x7F, 0x00, 0x00],
+    [0x00, 0x41, 0x36, 0x08, 0x00], [0x08, 0x04, 0x08, 0x10, 0x08],
+];
+
+/// Цвет надписи RGB. В командной строке — `#rrggbb` или `r,g,b`
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OverlayColor(pub [u8; 3]);
+
+impl OverlayColor {
+    pub const WHITE: Self = Self([255, 255, 255]);
+}
+
+impl FromStr for OverlayColor {
+    type Err = SstvError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || SstvError::invalid("overlay.color", messages::overlay_bad_color(s));
+        if let Some(hex) = s.trim().strip_prefix('#') {
+            if hex.len() != 6 || !hex.is_ascii() {
+                return Err(invalid());
+            }
+            let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).map_err(|_| invalid());
+            return Ok(Self([channel(0)?, channel(2)?, channel(4)?]));
+        }
+        let parts: Vec<&str> = s.split(',').map(str::trim).collect();
+        let [r, g, b] = parts.as_slice() else {
+            return Err(invalid());
+        };
+        let channel = |c: &str| c.parse::<u8>().map_err(|_| invalid());
+        Ok(Self([channel(r)?, channel(g)?, channel(b)?]))
+    }
+}
+
+impl std::fmt::Display for OverlayColor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let [r, g, b] = self.0;
+        write!(f, "#{:02x}{:02x}{:02x}", r, g, b)
+    }
+}
+
+/// Надпись поверх основного изображения перед кодированием, как шаблоны MMSSTV:
+/// позывной, рапорт RSV, время. Рисуется в разрешении режима, призрак остаётся без неё
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OverlayParams {
+    /// Строки надписи сверху вниз; `{date}` и `{time}` заменяются датой и временем UTC
+    pub lines: Vec<String>,
+    /// Левый край, пиксели режима; отрицательный — отступ правого края от правого края
+    pub x: i32,
+    /// Верхний край, пиксели режима; отрицательный — отступ низа от нижнего края
+    pub y: i32,
+    /// Высота строки, пиксели
+    pub size: f32,
+    pub color: OverlayColor,
+    /// Шрифт TTF/OTF; без него — встроенный растровый 5×7, только ASCII
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub font: Option<String>,
+    /// Тёмная тень со сдвигом вправо-вниз, чтобы надпись читалась на любом фоне
+    pub shadow: bool,
+}
+
+impl Default for OverlayParams {
+    fn default() -> Self {
+        Self {
+            lines: Vec::new(),
+            x: 8,
+            y: 8,
+            size: 16.0,
+            color: OverlayColor::WHITE,
+            font: None,
+            shadow: false,
+        }
+    }
+}
+
+impl OverlayParams {
+    /// Надпись есть, если хотя бы одна строка непуста
+    pub fn is_enabled(&self) -> bool {
+        self.lines.iter().any(|line| !line.trim().is_empty())
+    }
+
+    /// Высота строки в допустимых пределах
+    pub fn validate(&self) -> Result<()> {
+        if !(1.0..=MAX_OVERLAY_SIZE).contains(&self.size) {
+            return Err(SstvError::invalid(
+                "overlay.size",
+                messages::overlay_bad_size(self.size, MAX_OVERLAY_SIZE),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Строки с подставленными датой и временем `now`
+    pub fn expand(&self, now: SystemTime) -> Vec<String> {
+        let (date, time) = utc(now);
+        self.lines
+            .iter()
+            .map(|line| line.replace("{date}", &date).replace("{time}", &time))
+            .collect()
+    }
+
+    /// Изображение с надписью в разрешении режима; без надписи — само `image`
+    pub fn apply<'a>(&self, image: &'a DynamicImage) -> Result<Cow<'a, DynamicImage>> {
+        if !self.is_enabled() {
+            return Ok(Cow::Borrowed(image));
+        }
+        self.validate()?;
+        let font = TextFont::load(self.font.as_deref(), self.size)?;
+        let mut rgb = fit_to_mode(image, &MARTIN_M1).to_rgb8();
+        // Время запрашивается только при подстановке: без часов (wasm) надпись без неё работает
+        let lines = if self
+            .lines
+            .iter()
+            .any(|l| l.contains("{date}") || l.contains("{time}"))
+        {
+            self.expand(SystemTime::now())
+        } else {
+            self.lines.clone()
+        };
+
+        let line_height = font.line_height();
+        let width = lines.iter().map(|l| font.width(l)).max().unwrap_or(0) as i32;
+        let height = (line_height * lines.len() as u32) as i32;
+        let left = if self.x < 0 {
+            rgb.width() as i32 + self.x - width
+        } else {
+            self.x
+        };
+        let top = if self.y < 0 {
+            rgb.height() as i32 + self.y - height
+        } else {
+            self.y
+        };
+        let shadow = (self.size / 16.0).round().max(1.0) as i32;
+        for (k, line) in lines.iter().enumerate() {
+            let y = top + k as i32 * line_height as i32;
+            if self.shadow {
+                font.draw(&mut rgb, left + shadow, y + shadow, line, Rgb([0, 0, 0]));
+            }
+            font.draw(&mut rgb, left, y, line, Rgb(self.color.0));
+        }
+        Ok(Cow::Owned(DynamicImage::ImageRgb8(rgb)))
+    }
+}
+
+/// Шрифт надписи: встроенный растровый с целым масштабом точки или контурный из файла
+enum TextFont {
+    Pixel { scale: u32 },
+    Outline { font: FontVec, scale: PxScale },
+}
+
+impl TextFont {
+    fn load(path: Option<&str>, size: f32) -> Result<Self> {
+        let Some(path) = path else {
+            let scale = (size / PIXEL_CELL_HEIGHT as f32).round().max(1.0) as u32;
+            return Ok(TextFont::Pixel { scale });
+        };
+        let bytes = std::fs::read(path)
+            .map_err(|e| SstvError::io(messages::overlay_font_read_failed(path), e))?;
+        let font = FontVec::try_from_vec(bytes)
+            .map_err(|_| SstvError::invalid("overlay.font", messages::overlay_not_font(path)))?;
+        Ok(TextFont::Outline {
+            font,
+            scale: PxScale::from(size),
+        })
+    }
+
+    fn line_height(&self) -> u32 {
+        match self {
+            TextFont::Pixel { scale } => PIXEL_CELL_HEIGHT * scale,
+            TextFont::Outline { font, scale } => {
+                let scaled = font.as_scaled(*scale);
+                (scaled.height() + scaled.line_gap()).ceil().max(1.0) as u32
+            }
+        }
+    }
+
+    fn width(&self, text: &str) -> u32 {
+        match self {
+            TextFont::Pixel { scale } => {
+                (text.chars().count() as u32 * PIXEL_CELL_WIDTH).saturating_sub(1) * scale
+            }
+            TextFont::Outline { font, scale } => {
+                let scaled = font.as_scaled(*scale);
+                let mut width = 0.0;
+                let mut previous = None;
+                for c in text.chars() {
+                    let id = scaled.glyph_id(c);
+                    if let Some(previous) = previous {
+                        width += scaled.kern(previous, id);
+                    }
+                    width += scaled.h_advance(id);
+                    previous = Some(id);
+                }
+                width.ceil().max(0.0) as u32
+            }
+        }
+    }
+
+    /// Пишет `text` с левым верхним углом в (`x`, `y`); что выходит за края, обрезается
+    fn draw(&self, canvas: &mut RgbImage, x: i32, y: i32, text: &str, color: Rgb<u8>) {
+        match self {
+            TextFont::Pixel { scale } => draw_pixel_text(canvas, x, y, text, *scale, color),
+            TextFont::Outline { font, scale } => {
+                let scaled = font.as_scaled(*scale);
+                let mut caret = ab_glyph::point(x as f32, y as f32 + scaled.ascent());
+                let mut previous = None;
+                for c in text.chars() {
+                    let id = scaled.glyph_id(c);
+                    if let Some(previous) = previous {
+                        caret.x += scaled.kern(previous, id);
+                    }
+                    previous = Some(id);
+                    let glyph = id.with_scale_and_position(*scale, caret);
+                    caret.x += scaled.h_advance(id);
+                    let Some(outline) = font.outline_glyph(glyph) else {
+                        continue;
+                    };
+                    let bounds = outline.px_bounds();
+                    outline.draw(|gx, gy, coverage| {
+                        let px = bounds.min.x as i32 + gx as i32;
+                        let py = bounds.min.y as i32 + gy as i32;
+                        blend(canvas, px, py, color, coverage);
+                    });
+                }
+            }
+        }
+    }
+}
+
+/// Смешивает пиксель с цветом `color` в доле `coverage`; вне изображения ничего не делает
+fn blend(canvas: &mut RgbImage, x: i32, y: i32, color: Rgb<u8>, coverage: f32) {
+    if x < 0 || y < 0 || x >= canvas.width() as i32 || y >= canvas.height() as i32 {
+        return;
+    }
+    let coverage = coverage.clamp(0.0, 1.0);
+    let pixel = canvas.get_pixel_mut(x as u32, y as u32);
+    for (p, &c) in pixel.0.iter_mut().zip(&color.0) {
+        *p = (*p as f32 + (c as f32 - *p as f32) * coverage).round() as u8;
+    }
+}
+
+/// Глиф знака; знаки вне ASCII заменяются похожими или `?`
+fn glyph(c: char) -> &'static [u8; 5] {
+    let c = match c {
+        '–' | '—' | '−' => '-',
+        '×' => 'x',
+        '±' => '+',
+        '°' => 'o',
+        '«' | '»' => '"',
+        ' '..='~' => c,
+        _ => '?',
+    };
+    &FONT[c as usize - ' ' as usize]
+}
+
+/// Пишет `text` встроенным шрифтом 5×7, каждая точка — квадрат `scale`×`scale`
+pub(crate) fn draw_pixel_text(
+    canvas: &mut RgbImage,
+    x: i32,
+    y: i32,
+    text: &str,
+    scale: u32,
+    color: Rgb<u8>,
+) {
+    let scale = scale as i32;
+    for (k, c) in text.chars().enumerate() {
+        let left = x + k as i32 * PIXEL_CELL_WIDTH as i32 * scale;
+        for (column, bits) in glyph(c).iter().enumerate() {
+            for row in 0..7 {
+                if (bits >> row) & 1 == 0 {
+                    continue;
+                }
+                for dy in 0..scale {
+                    for dx in 0..scale {
+                        let px = left + column as i32 * scale + dx;
+                        let py = y + row * scale + dy;
+                        blend(canvas, px, py, color, 1.0);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Дата `ГГГГ-ММ-ДД` и время `ЧЧ:ММZ` по UTC
+fn utc(now: SystemTime) -> (String, String) {
+    let (year, month, day, secs) = civil_utc(now);
+    (
+        format!("{:04}-{:02}-{:02}", year, month, day),
+        format!("{:02}:{:02}Z", secs / 3600, secs % 3600 / 60),
+    )
+}
+
+/// Год, месяц, день и секунда суток момента `now` по UTC
+pub(crate) fn civil_utc(now: SystemTime) -> (i64, i64, i64, u64) {
+    let secs = now.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+    let (days, rem) = ((secs / 86_400) as i64, secs % 86_400);
+    // Гражданская дата из номера дня от эпохи Unix (алгоритм Х. Хиннанта)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day, rem)
+}
@@ -0,0 +1,112 @@
+use crate::effect::ImageEffect;
+use crate::messages;
+use image::{Rgb, RgbImage};
+use serde::{Deserialize, Serialize};
+
+/// Палитра, к которой приводится декодированное изображение: разрядность памяти
+/// старинных преобразователей развёртки
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Palette {
+    /// Без изменений: 8 бит на канал
+    #[default]
+    Full,
+    /// 6 бит на канал, 262 144 цвета, как в памяти Robot 1200C
+    Rgb666,
+    /// 4 бита на канал, 4096 цветов: 12-битные видеоадаптеры
+    Rgb444,
+    /// 3-3-2 бита, 256 цветов: 8-битные кадровые буферы
+    Rgb332,
+    /// 16 градаций серого, как у чёрно-белого Robot 400
+    Robot,
+}
+
+impl Palette {
+    pub const ALL: &'static [Palette] = &[
+        Palette::Full,
+        Palette::Rgb666,
+        Palette::Rgb444,
+        Palette::Rgb332,
+        Palette::Robot,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Palette::Full => messages::palette_full(),
+            Palette::Rgb666 => messages::palette_rgb666(),
+            Palette::Rgb444 => messages::palette_rgb444(),
+            Palette::Rgb332 => messages::palette_rgb332(),
+            Palette::Robot => messages::palette_robot(),
+        }
+    }
+
+    /// Приводит каждый пиксель к ближайшему цвету палитры. Уровни растянуты на весь
+    /// диапазон 0–255, как на выходе ЦАП преобразователя
+    pub fn apply(&self, image: &mut RgbImage) {
+        for Rgb(pixel) in image.pixels_mut() {
+            *pixel = match self {
+                Palette::Full => *pixel,
+                Palette::Rgb666 => pixel.map(|v| quantize(v, 6)),
+                Palette::Rgb444 => pixel.map(|v| quantize(v, 4)),
+                Palette::Rgb332 => [
+                    quantize(pixel[0], 3),
+                    quantize(pixel[1], 3),
+                    quantize(pixel[2], 2),
+                ],
+                Palette::Robot => {
+                    // Яркость по BT.601
+                    let [r, g, b] = pixel.map(f32::from);
+                    let luma = (0.299 * r + 0.587 * g + 0.114 * b).round() as u8;
+                    [quantize(luma, 4); 3]
+                }
+            };
+        }
+    }
+}
+
+impl ImageEffect for Palette {
+    fn name(&self) -> &'static str {
+        messages::palette()
+    }
+
+    fn apply(&self, image: &mut RgbImage) {
+        Palette::apply(self, image);
+    }
+
+    fn is_enabled(&self) -> bool {
+        *self != Palette::Full
+    }
+
+    fn describe(&self) -> String {
+        messages::palette_describe(Palette::name(self))
+    }
+}
+
+impl clap::ValueEnum for Palette {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[
+            Self::Full,
+            Self::Rgb666,
+            Self::Rgb444,
+            Self::Rgb332,
+            Self::Robot,
+        ]
+    }
+
+    fn to_possible_value<'a>(&self) -> Option<clap::builder::PossibleValue> {
+        Some(match self {
+            Self::Full => clap::builder::PossibleValue::new("full"),
+            Self::Rgb666 => clap::builder::PossibleValue::new("rgb666"),
+            Self::Rgb444 => clap::builder::PossibleValue::new("rgb444"),
+            Self::Rgb332 => clap::builder::PossibleValue::new("rgb332"),
+            Self::Robot => clap::builder::PossibleValue::new("robot"),
+        })
+    }
+}
+
+/// Ближайший из 2^`bits` равномерных уровней 0–255
+fn quantize(value: u8, bits: u32) -> u8 {
+    let top = ((1u32 << bits) - 1) as f32;
+    let level = (value as f32 * top / 255.0).round();
+    (level * 255.0 / top).round() as u8
+}
@@ -0,0 +1,115 @@
+use crate::error::{Result, SstvError};
+use crate::messages;
+use crate::processor::ProcessingParams;
+use serde::de::DeserializeOwned;
+use std::error::Error;
+use std::path::Path;
+
+/// Формат файла пресета
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum PresetFormat {
+    Toml,
+    Json,
+}
+
+impl PresetFormat {
+    pub const ALL: &'static [PresetFormat] = &[PresetFormat::Toml, PresetFormat::Json];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            PresetFormat::Toml => "TOML",
+            PresetFormat::Json => "JSON",
+        }
+    }
+
+    /// Расширение файла пресета
+    pub fn extension(&self) -> &'static str {
+        match self {
+            PresetFormat::Toml => "toml",
+            PresetFormat::Json => "json",
+        }
+    }
+
+    /// Определяет формат по расширению файла
+    pub fn from_path(path: &Path) -> Result<Self> {
+        let extension = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(str::to_lowercase);
+        match Self::ALL
+            .iter()
+            .find(|f| extension.as_deref() == Some(f.extension()))
+        {
+            Some(&format) => Ok(format),
+            None => Err(SstvError::invalid(
+                "preset",
+                messages::preset_unknown_format(&path.display().to_string()),
+            )),
+        }
+    }
+}
+
+impl ProcessingParams {
+    /// Сохраняет параметры в пресет; формат выбирается по расширению `.toml` или `.json`.
+    /// Мешающая SSTV-передача в пресет не входит: её изображение задаётся отдельно
+    pub fn to_file(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let text = self.to_preset(PresetFormat::from_path(path)?)?;
+        std::fs::write(path, text).map_err(|e| {
+            SstvError::io(
+                messages::preset_write_failed(&path.display().to_string()),
+                e,
+            )
+        })
+    }
+
+    /// Текст пресета в формате `format`, как его пишет [`to_file`](Self::to_file)
+    pub fn to_preset(&self, format: PresetFormat) -> Result<String> {
+        let text = || -> Result<String, Box<dyn Error + Send + Sync>> {
+            Ok(match format {
+                PresetFormat::Json => serde_json::to_string_pretty(self)?,
+                PresetFormat::Toml => {
+                    // Через JSON: он пишет f32 кратчайшей записью (0.3, а не 0.30000001192092896),
+                    // а таблица TOML сама ставит вложенные таблицы после простых значений
+                    let value: toml::Value = serde_json::from_str(&serde_json::to_string(self)?)?;
+                    toml::to_string_pretty(&value)?
+                }
+            })
+        };
+        text().map_err(|e| SstvError::io(messages::preset_serialize_failed(), e))
+    }
+
+    /// Читает пресет; отсутствующие в файле поля берут значения по умолчанию
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        read_file(path.as_ref(), "preset", messages::file_preset())
+    }
+}
+
+/// Читает TOML или JSON по расширению `path`; `field` и `what` называют файл в ошибках
+pub(crate) fn read_file<T: DeserializeOwned>(
+    path: &Path,
+    field: &'static str,
+    what: &str,
+) -> Result<T> {
+    let format = PresetFormat::from_path(path)?;
+    let text = std::fs::read_to_string(path).map_err(|e| {
+        SstvError::io(
+            messages::file_read_failed(what, &path.display().to_string()),
+            e,
+        )
+    })?;
+    let value: Result<T, Box<dyn Error + Send + Sync>> = match format {
+        PresetFormat::Json => serde_json::from_str(&text).map_err(Into::into),
+        // Тоже через JSON: разбор перечислений с данными в toml 0.5 не понимает
+        // вложенные таблицы вида [noise.env.custom]
+        PresetFormat::Toml => toml::from_str::<toml::Value>(&text)
+            .map_err(Into::into)
+            .and_then(|value| Ok(serde_json::from_value(serde_json::to_value(value)?)?)),
+    };
+    value.map_err(|e| {
+        SstvError::invalid(
+            field,
+            messages::file_invalid(what, &path.display().to_string(), &e.to_string()),
+        )
+    })
+}
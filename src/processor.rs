@@ -1,18 +1,165 @@
+use crate::blanker::{BlankerParams, BlankerProcessor};
+use crate::buffer::SampleBuffer;
+use crate::builder::SSTVProcessorBuilder;
+use crate::deadline::{self, TimeLimits};
+use crate::decoder::{Decoder, DecoderBackend, DecoderParams, create_decoder};
+use crate::drift::{DriftParams, DriftProcessor};
+use crate::dropout::{DropoutParams, DropoutProcessor};
+use crate::dsp::ConvolutionBackend;
+use crate::effect::{
+    ChannelEffect, ChannelSummary, EffectMix, MixTarget, SanitizeEvent, SanitizeMode, StreamContext,
+};
+use crate::encoder::{self, EncoderParams, LineHook};
+use crate::error::{Result, SstvError};
+use crate::fading::{FadingParams, FadingProcessor};
+use crate::filters::{
+    AgcParams, AgcProcessor, FilterParams, FilterProcessor, NotchParams, NotchProcessor,
+};
+use crate::interference::{InterferenceParams, InterferenceProcessor};
+use crate::loudness::LoudnessParams;
+#[cfg(feature = "wav")]
+use crate::loudness::Normalization;
+use crate::messages;
+use crate::metadata::{self, Metadata};
+use crate::metrics::MetricSpace;
+use crate::modes::MARTIN_M1;
+use crate::multipath::{MultipathParams, MultipathProcessor};
+use crate::native_decoder::StreamDecoder;
 use crate::noise::{NoiseParams, NoiseProcessor};
-use crate::retarder::{RetarderParams, RetarderProcessor};
-use anyhow::{Context, Result, bail};
-use hound::{SampleFormat, WavSpec, WavWriter};
-use image::{DynamicImage, ImageFormat};
+use crate::occupancy::{self, Occupancy, SpectralMask};
+use crate::overlay::OverlayParams;
+use crate::quality::QualityTier;
+use crate::report::{DecodeReport, LevelMeter};
+use crate::retarder::{RetarderParams, RetarderProcessor, RetarderSource};
+use crate::salvage::Salvage;
+use crate::signal::{self, Signal};
+use crate::sink::{ImageSink, MemorySink};
+use crate::stream::{BlockChain, BlockEncoder, DEFAULT_BLOCK_LEN, SampleStream};
+use crate::timeline::{EffectEvent, TimelineProcessor};
+#[cfg(feature = "wav")]
+use crate::transceiver::TransceiverOutput;
+use crate::transceiver::{TransceiverParams, TransceiverProcessor};
+#[cfg(feature = "wav")]
+use crate::wav;
+use image::{DynamicImage, Rgb};
+use rand::{SeedableRng, rngs::StdRng};
 use rsstv::SAMPLE_RATE;
-use rsstv::{
-    common::{DecodeResult, SSTVMode},
-    martinm1::MartinM1,
-};
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::hash::{DefaultHasher, Hash, Hasher};
+#[cfg(feature = "wav")]
+use std::path::PathBuf;
+use std::sync::atomic::AtomicBool;
+
+/// Наименьшая частота дискретизации сигнала: тоны SSTV (до 2300 Гц) с боковыми полосами
+/// должны помещаться ниже частоты Найквиста
+pub const MIN_SAMPLE_RATE: u32 = 8000;
 
-#[derive(Clone, Debug)]
+/// Предел выходного усиления, дБ, в обе стороны: дальше сигнал либо весь обрезан,
+/// либо неотличим от тишины
+pub const MAX_OUTPUT_GAIN_DB: f32 = 60.0;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
 pub struct ProcessingParams {
     pub noise: NoiseParams,
     pub retarder: RetarderParams,
+    pub multipath: MultipathParams,
+    pub fading: FadingParams,
+    pub drift: DriftParams,
+    pub interference: InterferenceParams,
+    /// Подавитель импульсных помех приёмника перед его фильтром
+    pub blanker: BlankerParams,
+    /// Тракт SSB-трансивера вокруг эффектов канала и вид выходного WAV
+    pub transceiver: TransceiverParams,
+    /// Звуковой фильтр после приёмника
+    pub filter: FilterParams,
+    /// Режекторный фильтр после звукового
+    pub notch: NotchParams,
+    /// АРУ приёмника после фильтров
+    pub agc: AgcParams,
+    /// Выпадения сигнала после приёмника
+    pub dropout: DropoutParams,
+    /// Эффекты, включённые на отрезки передачи, см. [`TimelineProcessor`]
+    pub events: Vec<EffectEvent>,
+    pub decoder: DecoderParams,
+    pub encoder: EncoderParams,
+    /// Надпись поверх основного изображения перед кодированием
+    pub overlay: OverlayParams,
+    /// Метаданные передачи: в выходных файлах, отчёте и, если включено, на изображении
+    pub metadata: Metadata,
+    /// Способ вычисления свёрток в эффектах; результат от него почти не зависит
+    pub convolution: ConvolutionBackend,
+    /// Считать куски сигнала в эффектах шума и ретардера параллельно в пуле rayon;
+    /// результат от этого не зависит
+    pub parallel: bool,
+    /// Уровень качества: точность против скорости, см. [`QualityTier`]
+    pub quality: QualityTier,
+    /// Проверка сигнала на NaN и бесконечности после каждого эффекта, см. [`SanitizeMode`]
+    pub sanitize: SanitizeMode,
+    /// Общее усиление выхода после всех эффектов, дБ, до ограничения сигнала диапазоном
+    /// −1…1, не дальше [`MAX_OUTPUT_GAIN_DB`], см. [`Signal::output_stage`]
+    pub output_gain_db: f32,
+    /// Нормировка громкости записываемых WAV вторым проходом, см. [`LoudnessParams`]
+    pub loudness: LoudnessParams,
+    /// Цветовое пространство оценок качества декодированного изображения, см.
+    /// [`MetricSpace`]
+    pub metric_space: MetricSpace,
+    /// Частота дискретизации выходного сигнала, Гц. Кодер работает на [`SAMPLE_RATE`], сигнал
+    /// передискретизируется до эффектов, поэтому их задержки и длительности отсчитываются
+    /// уже в этой частоте; декодер получает сигнал, возвращённый на свою частоту
+    pub sample_rate: u32,
+    /// Зерно генератора случайных чисел; `None` — новое случайное зерно при каждом прогоне
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
+}
+
+impl ProcessingParams {
+    /// Частота, на которой работают эффекты и отдаётся сигнал: выходная частота,
+    /// пониженная для предпросмотра, см. [`QualityTier::signal_rate`]
+    pub fn signal_rate(&self) -> u32 {
+        self.quality.signal_rate(self.sample_rate)
+    }
+
+    /// Надпись поверх изображения: строки [`overlay`](Self::overlay) и под ними пары
+    /// метаданных, если они рисуются
+    pub fn overlay_params(&self) -> Cow<'_, OverlayParams> {
+        self.metadata.overlay_on(&self.overlay)
+    }
+
+    /// Доли смешивания встроенного эффекта `target`
+    pub fn mix(&self, target: MixTarget) -> EffectMix {
+        match target {
+            MixTarget::Retarder => self.retarder.mix,
+            MixTarget::Multipath => self.multipath.mix,
+            MixTarget::Fading => self.fading.mix,
+            MixTarget::Drift => self.drift.mix,
+            MixTarget::Interference => self.interference.mix,
+            MixTarget::Noise => self.noise.mix,
+            MixTarget::Blanker => self.blanker.mix,
+            MixTarget::Filter => self.filter.mix,
+            MixTarget::Notch => self.notch.mix,
+            MixTarget::Agc => self.agc.mix,
+            MixTarget::Dropout => self.dropout.mix,
+        }
+    }
+
+    pub fn mix_mut(&mut self, target: MixTarget) -> &mut EffectMix {
+        match target {
+            MixTarget::Retarder => &mut self.retarder.mix,
+            MixTarget::Multipath => &mut self.multipath.mix,
+            MixTarget::Fading => &mut self.fading.mix,
+            MixTarget::Drift => &mut self.drift.mix,
+            MixTarget::Interference => &mut self.interference.mix,
+            MixTarget::Noise => &mut self.noise.mix,
+            MixTarget::Blanker => &mut self.blanker.mix,
+            MixTarget::Filter => &mut self.filter.mix,
+            MixTarget::Notch => &mut self.notch.mix,
+            MixTarget::Agc => &mut self.agc.mix,
+            MixTarget::Dropout => &mut self.dropout.mix,
+        }
+    }
 }
 
 impl Default for ProcessingParams {
@@ -20,29 +167,164 @@ impl Default for ProcessingParams {
         Self {
             noise: NoiseParams::default(),
             retarder: RetarderParams::default(),
+            multipath: MultipathParams::default(),
+            fading: FadingParams::default(),
+            drift: DriftParams::default(),
+            interference: InterferenceParams::default(),
+            blanker: BlankerParams::default(),
+            transceiver: TransceiverParams::default(),
+            filter: FilterParams::default(),
+            notch: NotchParams::default(),
+            agc: AgcParams::default(),
+            dropout: DropoutParams::default(),
+            events: Vec::new(),
+            decoder: DecoderParams::default(),
+            encoder: EncoderParams::default(),
+            overlay: OverlayParams::default(),
+            metadata: Metadata::default(),
+            convolution: ConvolutionBackend::default(),
+            parallel: false,
+            quality: QualityTier::default(),
+            sanitize: SanitizeMode::default(),
+            output_gain_db: 0.0,
+            loudness: LoudnessParams::default(),
+            metric_space: MetricSpace::default(),
+            sample_rate: SAMPLE_RATE as u32,
+            seed: None,
+        }
+    }
+}
+
+/// Этап обработки в отчёте о ходе
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Stage {
+    Encode,
+    Effects,
+    Decode,
+}
+
+impl Stage {
+    pub const ALL: &'static [Stage] = &[Stage::Encode, Stage::Effects, Stage::Decode];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Stage::Encode => messages::stage_encode(),
+            Stage::Effects => messages::stage_effects(),
+            Stage::Decode => messages::stage_decode(),
+        }
+    }
+
+    /// Имя этапа в командной строке, см. [`StageLimit`](crate::deadline::StageLimit)
+    pub fn key(&self) -> &'static str {
+        match self {
+            Stage::Encode => "encode",
+            Stage::Effects => "effects",
+            Stage::Decode => "decode",
         }
     }
 }
 
 pub struct SSTVProcessor {
-    pub params: ProcessingParams,
-    noise_processor: NoiseProcessor,
-    retarder_processor: RetarderProcessor,
+    params: ProcessingParams,
+    /// Конвейер эффектов канала, применяемых по порядку
+    effects: Vec<Box<dyn ChannelEffect>>,
+    decoder: Box<dyn Decoder>,
+    /// Обработчик строк основного изображения при кодировании
+    line_hook: Option<Box<LineHook>>,
+    /// Получатель строк декодированного изображения по мере приёма
+    image_sink: Option<Box<dyn ImageSink + Send>>,
+    /// Сигнал последнего прогона после эффектов
+    last: SampleBuffer,
+    /// Исправления NaN и бесконечностей при последнем применении эффектов
+    last_sanitized: Vec<SanitizeEvent>,
+    /// Уровень выхода при последнем применении эффектов
+    last_level: Option<LevelMeter>,
+    /// Сводка канала при последнем применении эффектов
+    last_channel: Option<ChannelSummary>,
+    /// Занимаемая полоса выхода кодера последнего прогона
+    last_occupancy: Option<Occupancy>,
+    /// Чистые сигналы кодера последнего прогона, чтобы не кодировать то же заново
+    encode_cache: EncodeCache,
+    /// Пределы времени каждого прогона
+    limits: TimeLimits,
+    /// Куда `process` пишет сигнал после эффектов для отладки; `None` — никуда
+    #[cfg(feature = "wav")]
+    debug_wav: Option<PathBuf>,
+}
+
+/// Последний чистый сигнал основного изображения и призрака с ключами [`encode_key`]
+#[derive(Default)]
+struct EncodeCache {
+    main: Option<(u64, Signal)>,
+    retarder: Option<(u64, Signal)>,
+    /// Взял ли последний прогон все свои сигналы отсюда
+    hit: bool,
+}
+
+impl EncodeCache {
+    /// Сигнал из ячейки `slot`, если ключ совпал, иначе закодированный заново и запомненный
+    fn get_or_encode(
+        slot: &mut Option<(u64, Signal)>,
+        key: u64,
+        encode: impl FnOnce() -> Signal,
+    ) -> (Signal, bool) {
+        match slot {
+            Some((cached, signal)) if *cached == key => (signal.clone(), true),
+            _ => {
+                let signal = encode();
+                *slot = Some((key, signal.clone()));
+                (signal, false)
+            }
+        }
+    }
+}
+
+/// Ключ кэша кодера: содержимое изображения, режим и все параметры кодера
+fn encode_key(image: &DynamicImage, encoder: &EncoderParams) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    (image.width(), image.height()).hash(&mut hasher);
+    format!("{:?}", image.color()).hash(&mut hasher);
+    image.as_bytes().hash(&mut hasher);
+    MARTIN_M1.name.hash(&mut hasher);
+    serde_json::to_string(encoder)
+        .unwrap_or_default()
+        .hash(&mut hasher);
+    hasher.finish()
+}
+
+impl Default for SSTVProcessor {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl SSTVProcessor {
+    /// Построитель процессора с проверкой параметров — основной способ его создать
+    pub fn builder() -> SSTVProcessorBuilder {
+        SSTVProcessorBuilder::default()
+    }
+
     pub fn new() -> Self {
-        Self {
-            params: ProcessingParams::default(),
-            noise_processor: NoiseProcessor::new(),
-            retarder_processor: RetarderProcessor::new(),
-        }
+        Self::new_with_params(ProcessingParams::default())
     }
 
+    /// Процессор со стандартным конвейером без проверки параметров: ошибки в них
+    /// всплывут только при обработке. Проверяет их сразу [`builder`](Self::builder)
     pub fn new_with_params(params: ProcessingParams) -> Self {
         Self {
-            noise_processor: NoiseProcessor::new_with_params(params.noise.clone()),
-            retarder_processor: RetarderProcessor::new_with_params(params.retarder.clone()),
+            effects: Self::default_effects(&params),
+            decoder: create_decoder(&params.decoder),
+            line_hook: None,
+            image_sink: None,
+            last: SampleBuffer::mono(Vec::new(), params.sample_rate),
+            last_sanitized: Vec::new(),
+            last_level: None,
+            last_channel: None,
+            last_occupancy: None,
+            encode_cache: EncodeCache::default(),
+            limits: TimeLimits::default(),
+            #[cfg(feature = "wav")]
+            debug_wav: None,
             params,
         }
     }
@@ -51,88 +333,730 @@ impl SSTVProcessor {
         &mut self,
         main_image: &DynamicImage,
         retarder_image: Option<&DynamicImage>,
+    ) -> Result<DecodeReport> {
+        self.process_with_progress(
+            main_image,
+            retarder_image,
+            |_, _| {},
+            &AtomicBool::new(false),
+        )
+    }
+
+    /// Как [`process`](Self::process), но сообщает этап и долю его выполнения (0–1)
+    /// и прерывается с ошибкой [`SstvError::Cancelled`], когда `cancel` выставлен. Отмена
+    /// проверяется между этапами, эффектами и строками встроенного декодера
+    pub fn process_with_progress(
+        &mut self,
+        main_image: &DynamicImage,
+        retarder_image: Option<&DynamicImage>,
+        progress: impl Fn(Stage, f32),
+        cancel: &AtomicBool,
+    ) -> Result<DecodeReport> {
+        let limits = self.limits;
+        deadline::guarded(&limits, cancel, &progress, |cancel, progress| {
+            let samples = self.render(main_image, retarder_image, progress, cancel)?;
+            self.decode_buffer(samples, progress, cancel)
+        })
+    }
+
+    /// Первая половина [`process_with_progress`](Self::process_with_progress): кодирование
+    /// и эффекты. Промежуточный сигнал можно показать до медленного декодирования и затем
+    /// передать в [`decode_rendered`](Self::decode_rendered)
+    pub fn render_with_progress(
+        &mut self,
+        main_image: &DynamicImage,
+        retarder_image: Option<&DynamicImage>,
+        progress: impl Fn(Stage, f32),
+        cancel: &AtomicBool,
+    ) -> Result<SampleBuffer> {
+        let limits = self.limits;
+        deadline::guarded(&limits, cancel, &progress, |cancel, progress| {
+            self.render(main_image, retarder_image, progress, cancel)
+        })
+    }
+
+    /// Вторая половина [`process_with_progress`](Self::process_with_progress): сохраняет
+    /// сигнал как последний (и в отладочный WAV, если он задан
+    /// [`set_debug_wav`](Self::set_debug_wav)) и декодирует его
+    pub fn decode_rendered(
+        &mut self,
+        buffer: SampleBuffer,
+        progress: impl Fn(Stage, f32),
+        cancel: &AtomicBool,
+    ) -> Result<DecodeReport> {
+        let limits = self.limits;
+        deadline::guarded(&limits, cancel, &progress, |cancel, progress| {
+            self.decode_buffer(buffer, progress, cancel)
+        })
+    }
+
+    fn decode_buffer(
+        &mut self,
+        buffer: SampleBuffer,
+        progress: &dyn Fn(Stage, f32),
+        cancel: &AtomicBool,
+    ) -> Result<DecodeReport> {
+        #[cfg(feature = "wav")]
+        let normalization = match &self.debug_wav {
+            Some(path) => self.write_wav(&path.to_string_lossy(), &buffer)?,
+            None => None,
+        };
+        #[cfg(not(feature = "wav"))]
+        let normalization = None;
+
+        self.last = buffer;
+        SstvError::check_cancelled(cancel)?;
+        progress(Stage::Decode, 0.0);
+        self.decoder = create_decoder(&self.params.decoder);
+        let resampled;
+        let samples = if self.last.rate == SAMPLE_RATE as u32 {
+            &self.last.data
+        } else {
+            resampled =
+                self.params
+                    .quality
+                    .resample(&self.last, self.last.rate, SAMPLE_RATE as u32);
+            &resampled
+        };
+        let image = Self::decode_image(
+            self.decoder.as_mut(),
+            self.image_sink.as_mut(),
+            samples,
+            &|fraction| progress(Stage::Decode, fraction),
+            cancel,
+        )?;
+        progress(Stage::Decode, 1.0);
+        let mut report =
+            DecodeReport::from_decoder(image, self.decoder.as_ref(), &self.params.decoder, samples);
+        report.sanitized = std::mem::take(&mut self.last_sanitized);
+        report.level = self.last_level.take();
+        report.channel = self.last_channel.take();
+        report.occupancy = self.last_occupancy.take();
+        report.normalization = normalization;
+        Ok(report)
+    }
+
+    /// Декодирует изображение; строки по мере приёма уходят и в получатель `sink`
+    /// (см. [`set_image_sink`](Self::set_image_sink)), если он задан
+    fn decode_image(
+        decoder: &mut dyn Decoder,
+        sink: Option<&mut Box<dyn ImageSink + Send>>,
+        samples: &[f32],
+        progress: &dyn Fn(f32),
+        cancel: &AtomicBool,
     ) -> Result<DynamicImage> {
-        self.noise_processor.params = self.params.noise.clone();
-        self.retarder_processor.params = self.params.retarder.clone();
+        let Some(sink) = sink else {
+            return decoder.decode_with_progress(samples, progress, cancel);
+        };
+        let mut sinks = (MemorySink::new(), sink);
+        decoder.decode_into(samples, &mut sinks, progress, cancel)?;
+        Ok(DynamicImage::ImageRgb8(sinks.0.into_image()))
+    }
+
+    /// Стандартный конвейер: передатчик SSB, ретардер, многолучёвость, замирания, уход частоты,
+    /// помехи, шум, события по времени, затем подавитель помех, приёмник SSB, звуковой
+    /// фильтр, режекция, АРУ и выпадения
+    pub fn default_effects(params: &ProcessingParams) -> Vec<Box<dyn ChannelEffect>> {
+        vec![
+            Box::new(TransceiverProcessor::transmitter(
+                params.transceiver.clone(),
+            )),
+            Box::new(RetarderProcessor::new_with_params(params.retarder.clone())),
+            Box::new(MultipathProcessor::new_with_params(
+                params.multipath.clone(),
+            )),
+            Box::new(FadingProcessor::new_with_params(params.fading.clone())),
+            Box::new(DriftProcessor::new_with_params(params.drift.clone())),
+            Box::new(InterferenceProcessor::new_with_params(
+                params.interference.clone(),
+            )),
+            Box::new(NoiseProcessor::new_with_params(params.noise.clone())),
+            Box::new(TimelineProcessor::new_with_params(params.events.clone())),
+            Box::new(BlankerProcessor::new_with_params(params.blanker.clone())),
+            Box::new(TransceiverProcessor::receiver(params.transceiver.clone())),
+            Box::new(FilterProcessor::new_with_params(params.filter.clone())),
+            Box::new(NotchProcessor::new_with_params(params.notch.clone())),
+            Box::new(AgcProcessor::new_with_params(params.agc.clone())),
+            Box::new(DropoutProcessor::new_with_params(params.dropout.clone())),
+        ]
+    }
+
+    /// Кодирует изображение в SSTV и применяет эффекты канала, не декодируя результат
+    pub fn render_samples(
+        &mut self,
+        main_image: &DynamicImage,
+        retarder_image: Option<&DynamicImage>,
+    ) -> Result<SampleBuffer> {
+        self.render_with_progress(
+            main_image,
+            retarder_image,
+            |_, _| {},
+            &AtomicBool::new(false),
+        )
+    }
 
-        let mut enc_main = MartinM1::new();
-        let mut samples = enc_main.encode(main_image.clone()).to_samples();
+    /// Сводка канала без обработки: кодирует изображения (через кэш кодера) и спрашивает
+    /// каждый включённый эффект, что он сделал бы с сигналом, не меняя сэмплы, см.
+    /// [`ChannelEffect::analyze`]. Быстрее прогона: эффекты не выполняются
+    pub fn analyze(
+        &mut self,
+        main_image: &DynamicImage,
+        retarder_image: Option<&DynamicImage>,
+    ) -> Result<ChannelSummary> {
+        let (signal, retarder) = self.encode_inputs(
+            main_image,
+            retarder_image,
+            &|_, _| {},
+            &AtomicBool::new(false),
+        )?;
+        signal.analyze(&self.effects, retarder.as_ref(), self.params.seed)
+    }
 
-        if let Some(retarder) = retarder_image {
-            self.retarder_processor
-                .apply_retarder(&mut samples, retarder)?;
+    /// Сигнал канала блоками по [`DEFAULT_BLOCK_LEN`] сэмплов вместо буфера целиком: кодер
+    /// синтезирует строку за строкой, эффекты обрабатывают блок за блоком, см.
+    /// [`SampleStream`]. Работает на частоте кодера и только с эффектами, умеющими
+    /// обрабатывать блоки; призрак из WAV и сводка канала недоступны
+    pub fn stream(
+        &mut self,
+        main_image: &DynamicImage,
+        retarder_image: Option<&DynamicImage>,
+    ) -> Result<SampleStream> {
+        self.params.encoder.validate()?;
+        if self.params.signal_rate() != SAMPLE_RATE as u32 {
+            return Err(SstvError::invalid(
+                "sample_rate",
+                messages::streaming_rate(SAMPLE_RATE as u32),
+            ));
         }
+        for effect in &mut self.effects {
+            effect.sync_params(&self.params);
+        }
+
+        let overlaid = self.params.overlay_params().apply(main_image)?;
+        let source = &self.params.retarder.source;
+        let derived = source.image_from(overlaid.as_ref());
+        // Кодер блоками идёт по строкам сам, поэтому их обработчик применяется заранее
+        let main_image = match self.line_hook.as_deref_mut() {
+            Some(hook) => Cow::Owned(encoder::apply_line_hook(&overlaid, &MARTIN_M1, hook)),
+            None => overlaid,
+        };
+        let retarder_image = match source {
+            RetarderSource::Image => retarder_image,
+            RetarderSource::SelfEcho => Some(main_image.as_ref()),
+            _ => derived.as_ref(),
+        };
 
-        self.noise_processor.apply_noise(&mut samples)?;
+        let encoder = BlockEncoder::new(&main_image, &self.params.encoder, DEFAULT_BLOCK_LEN);
+        let rng = RefCell::new(match self.params.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_os_rng(),
+        });
+        let ctx = StreamContext {
+            sample_rate: SAMPLE_RATE as u32,
+            len: encoder.len(),
+            image_span: encoder.image_span(),
+            signal_rms: encoder.signal_rms(),
+            encoder: &self.params.encoder,
+            retarder_image,
+            rng: &rng,
+        };
+        SampleStream::new(
+            encoder,
+            &self.effects,
+            &ctx,
+            self.params.output_gain_db,
+            self.params.sanitize,
+        )
+    }
 
-        let spec = WavSpec {
-            channels: 1,
-            sample_rate: SAMPLE_RATE as u32, // 44 100 Гц :contentReference[oaicite:0]{index=0}
-            bits_per_sample: 16,
-            sample_format: SampleFormat::Int,
+    /// Цепочка включённых эффектов для сигнала, который приходит блоками снаружи, а не от
+    /// кодера: например, со звуковой карты в живом канале. Сигнал частотой `sample_rate`
+    /// считается длиной `len` сэмплов и весь — телом изображения: по ним раскладываются
+    /// огибающие и случайные выпадения. `signal_rms` — ожидаемое СКЗ входа, по нему шум
+    /// выбирает свой уровень. Призрак ретардера кодируется из `retarder_image`; без неё
+    /// ретардер ничего не подмешивает
+    pub fn block_chain(
+        &mut self,
+        sample_rate: u32,
+        len: usize,
+        signal_rms: f32,
+        retarder_image: Option<&DynamicImage>,
+    ) -> Result<BlockChain> {
+        check_sample_rate(sample_rate)?;
+        for effect in &mut self.effects {
+            effect.sync_params(&self.params);
+        }
+        let rng = RefCell::new(match self.params.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_os_rng(),
+        });
+        let ctx = StreamContext {
+            sample_rate,
+            len,
+            image_span: 0..len,
+            signal_rms,
+            encoder: &self.params.encoder,
+            retarder_image,
+            rng: &rng,
         };
+        BlockChain::new(
+            &self.effects,
+            &ctx,
+            self.params.output_gain_db,
+            self.params.sanitize,
+        )
+    }
+
+    /// Как [`process_with_progress`](Self::process_with_progress), но блоками: сигнал из
+    /// [`stream`](Self::stream) сразу уходит в [`StreamDecoder`], и первые строки
+    /// изображения приходят в получатель строк, пока остальной сигнал ещё не закодирован.
+    /// Сигнал целиком не хранится, поэтому [`last_samples`](Self::last_samples) и отладочный
+    /// WAV не обновляются. Работает со встроенным декодером
+    pub fn process_streaming(
+        &mut self,
+        main_image: &DynamicImage,
+        retarder_image: Option<&DynamicImage>,
+        progress: impl Fn(Stage, f32),
+        cancel: &AtomicBool,
+    ) -> Result<DecodeReport> {
+        let limits = self.limits;
+        deadline::guarded(&limits, cancel, &progress, |cancel, progress| {
+            self.stream_blocks(main_image, retarder_image, progress, cancel)
+        })
+    }
 
-        let mut writer = WavWriter::create("debug.wav", spec)?;
+    fn stream_blocks(
+        &mut self,
+        main_image: &DynamicImage,
+        retarder_image: Option<&DynamicImage>,
+        progress: &dyn Fn(Stage, f32),
+        cancel: &AtomicBool,
+    ) -> Result<DecodeReport> {
+        if self.params.decoder.backend != DecoderBackend::Native {
+            return Err(SstvError::invalid(
+                "decoder.backend",
+                messages::streaming_native_only(),
+            ));
+        }
+        progress(Stage::Encode, 0.0);
+        let mut stream = self.stream(main_image, retarder_image)?;
+        let mut decoder = StreamDecoder::new(self.params.decoder.clone());
+        let mut memory = MemorySink::new();
+        let total = stream.len().max(1) as f32;
+        {
+            let mut sink: Box<dyn ImageSink + '_> = match self.image_sink.as_mut() {
+                Some(extra) => Box::new((&mut memory, extra)),
+                None => Box::new(&mut memory),
+            };
+            while let Some(block) = stream.next() {
+                SstvError::check_cancelled(cancel)?;
+                decoder.push(&block?, sink.as_mut())?;
+                progress(Stage::Decode, stream.position() as f32 / total);
+            }
+            decoder.finish(sink.as_mut())?;
+        }
+        progress(Stage::Decode, 1.0);
+
+        let image = DynamicImage::ImageRgb8(memory.into_image());
+        let mut report = DecodeReport::from_decoder(
+            image,
+            &decoder,
+            &self.params.decoder,
+            decoder.snr_samples(),
+        );
+        report.sanitized = stream.take_sanitized();
+        report.level = Some(stream.level());
+        Ok(report)
+    }
 
-        let max = i16::MAX as f32;
-        for s in &samples {
-            writer.write_sample((s.clamp(-1.0, 1.0) * max) as i16)?;
+    fn render(
+        &mut self,
+        main_image: &DynamicImage,
+        retarder_image: Option<&DynamicImage>,
+        progress: &dyn Fn(Stage, f32),
+        cancel: &AtomicBool,
+    ) -> Result<SampleBuffer> {
+        let (mut signal, retarder) =
+            self.encode_inputs(main_image, retarder_image, progress, cancel)?;
+        // Полоса — того, что ушло в эфир из кодера, до искажений канала
+        self.last_occupancy = occupancy::measure(
+            signal.samples(),
+            signal.sample_rate(),
+            &SpectralMask::default(),
+        );
+        // Оценка не трогает сэмплы и генератор прогона, поэтому считается до эффектов
+        self.last_channel = signal
+            .analyze(&self.effects, retarder.as_ref(), self.params.seed)
+            .ok();
+        self.last_sanitized = signal.apply_with_progress(
+            &self.effects,
+            retarder.as_ref(),
+            self.params.seed,
+            self.params.convolution,
+            self.params.parallel,
+            self.params.sanitize,
+            &|fraction| progress(Stage::Effects, fraction),
+            cancel,
+        )?;
+        self.last_level = Some(signal.output_stage(self.params.output_gain_db));
+
+        Ok(signal.into_buffer())
+    }
+
+    /// Кодирует основное изображение и призрак и приводит оба сигнала к частоте эффектов
+    fn encode_inputs(
+        &mut self,
+        main_image: &DynamicImage,
+        retarder_image: Option<&DynamicImage>,
+        progress: &dyn Fn(Stage, f32),
+        cancel: &AtomicBool,
+    ) -> Result<(Signal, Option<Signal>)> {
+        progress(Stage::Encode, 0.0);
+        check_sample_rate(self.params.sample_rate)?;
+        self.params.encoder.validate()?;
+        for effect in &mut self.effects {
+            effect.sync_params(&self.params);
         }
-        writer.finalize()?;
 
-        let mut dec = MartinM1::new();
-        let decoded = match dec.decode(&samples) {
-            DecodeResult::Finished(img) | DecodeResult::Partial(img) => img,
-            DecodeResult::NoneFound => {
-                bail!("Декодер не нашёл изображение — уменьшите Шум или Ретардер")
+        // Надпись — часть передаваемого изображения: кэш кодера ключуется уже по нему
+        let overlaid = self.params.overlay_params().apply(main_image)?;
+        let main_image = overlaid.as_ref();
+
+        // Обработчик строк может менять изображение от прогона к прогону, с ним кэша нет
+        let encoder = &self.params.encoder;
+        let (signal, mut hit) = match self.line_hook.as_deref_mut() {
+            Some(hook) => (
+                signal::encode_with_hook(main_image, encoder, Some(hook)),
+                false,
+            ),
+            None => EncodeCache::get_or_encode(
+                &mut self.encode_cache.main,
+                encode_key(main_image, encoder),
+                || signal::encode(main_image, encoder),
+            ),
+        };
+
+        // Призрак кодируется тем же кодером, что и основной сигнал. Картинку может заменить
+        // само основное изображение или готовый сигнал, см. `RetarderSource`
+        let source = &self.params.retarder.source;
+        let derived = source.image_from(main_image);
+        let retarder_image = match source {
+            RetarderSource::Image => retarder_image,
+            _ => derived.as_ref(),
+        };
+        let retarder = match retarder_image {
+            _ if !self.has_effects() => None,
+            None => source.signal_from(&signal)?,
+            Some(retarder) => {
+                SstvError::check_cancelled(cancel)?;
+                progress(Stage::Encode, 0.5);
+                let (signal, retarder_hit) = EncodeCache::get_or_encode(
+                    &mut self.encode_cache.retarder,
+                    encode_key(retarder, encoder),
+                    || signal::encode(retarder, encoder),
+                );
+                hit &= retarder_hit;
+                Some(signal)
             }
         };
+        self.encode_cache.hit = hit;
+        progress(Stage::Encode, 1.0);
+        SstvError::check_cancelled(cancel)?;
+
+        // Эффекты работают на частоте сигнала; призрак приводится к ней тем же ядром
+        let (rate, quality) = (self.params.signal_rate(), self.params.quality);
+        let signal = signal.resampled_with(rate, quality);
+        let retarder = retarder.map(|r| r.resampled_with(rate, quality));
+        Ok((signal, retarder))
+    }
+
+    /// Декодирует буфер с произвольной частотой дискретизации; многоканальный
+    /// сводится в моно
+    pub fn decode_samples(&mut self, buffer: &SampleBuffer) -> Result<DecodeReport> {
+        check_sample_rate(buffer.rate)?;
+        let limits = self.limits;
+        let cancel = AtomicBool::new(false);
+        deadline::guarded(&limits, &cancel, &|_, _| {}, |cancel, progress| {
+            progress(Stage::Decode, 0.0);
+            self.decoder = create_decoder(&self.params.decoder);
+
+            let samples = buffer
+                .resampled_with(SAMPLE_RATE as u32, self.params.quality)
+                .data;
+
+            let image = Self::decode_image(
+                self.decoder.as_mut(),
+                self.image_sink.as_mut(),
+                &samples,
+                &|_| {},
+                cancel,
+            )?;
+            Ok(DecodeReport::from_decoder(
+                image,
+                self.decoder.as_ref(),
+                &self.params.decoder,
+                &samples,
+            ))
+        })
+    }
+
+    /// Декодирует изображение из записанного WAV файла (моно/стерео, любая частота)
+    #[cfg(feature = "wav")]
+    pub fn decode_wav(&mut self, path: &str) -> Result<DecodeReport> {
+        self.decode_samples(&wav::read_wav(path)?)
+    }
+
+    /// Сигнал последнего вызова `process` после применения эффектов
+    pub fn last_samples(&self) -> &[f32] {
+        &self.last
+    }
+
+    /// Сигнал последнего вызова `process` вместе с частотой дискретизации
+    pub fn last_buffer(&self) -> &SampleBuffer {
+        &self.last
+    }
+
+    /// Записывает сигнал этого процессора в 16-битный WAV с частотой буфера, с дизерингом,
+    /// если его требует уровень качества. Дизеринг воспроизводим при заданном зерне. При
+    /// выходе трансивера [`TransceiverOutput::Iq`] пишутся квадратуры I/Q в стерео.
+    /// Метаданные параметров уходят в блок INFO. С нормировкой громкости
+    /// [`ProcessingParams::loudness`] записывается нормированная копия сигнала, и
+    /// возвращается её итог
+    #[cfg(feature = "wav")]
+    pub fn write_wav(&self, path: &str, buffer: &SampleBuffer) -> Result<Option<Normalization>> {
+        let mut normalized;
+        let mut normalization = None;
+        let buffer = if self.params.loudness.enabled() {
+            normalized = buffer.clone();
+            normalization = self.params.loudness.apply(&mut normalized);
+            &normalized
+        } else {
+            buffer
+        };
+        let rate = buffer.rate;
+        let transceiver = &self.params.transceiver;
+        let iq = (transceiver.enabled && transceiver.output == TransceiverOutput::Iq)
+            .then(|| transceiver.to_iq(buffer, self.params.convolution));
+        if !self.params.quality.dither() {
+            match iq {
+                Some(iq) => wav::write_iq_wav(path, &iq, rate)?,
+                None => wav::write_wav(path, buffer)?,
+            }
+        } else {
+            let mut rng = match self.params.seed {
+                Some(seed) => StdRng::seed_from_u64(seed),
+                None => StdRng::from_os_rng(),
+            };
+            match iq {
+                Some(iq) => wav::write_iq_wav_dithered(path, &iq, rate, &mut rng)?,
+                None => wav::write_wav_dithered(path, buffer, &mut rng)?,
+            }
+        }
+        wav::write_info(path, &self.params.metadata.fields)?;
+        Ok(normalization)
+    }
+
+    /// Остатки последнего прогона, если он кончился ошибкой декодера `error`
+    /// [`SstvError::DecodeNotFound`] или пределом времени [`SstvError::Timeout`], а сигнал
+    /// до декодера сохранился: после [`process`](Self::process) и
+    /// [`decode_rendered`](Self::decode_rendered). Иначе `None`
+    pub fn salvage(&self, error: &SstvError) -> Option<Salvage> {
+        let failed = matches!(
+            error,
+            SstvError::DecodeNotFound(_) | SstvError::Timeout { .. }
+        );
+        (failed && !self.last.is_empty())
+            .then(|| Salvage::new(&self.last, &self.params.decoder, self.params.quality, error))
+    }
+
+    /// Частота дискретизации [`last_samples`](Self::last_samples), Гц
+    pub fn last_sample_rate(&self) -> u32 {
+        self.last.rate
+    }
+
+    /// Задаёт обработчик строк основного изображения (бегущая строка, телеметрия и т.п.)
+    pub fn set_line_hook(&mut self, hook: impl FnMut(&mut [Rgb<u8>], usize) + Send + 'static) {
+        self.line_hook = Some(Box::new(hook));
+    }
+
+    pub fn clear_line_hook(&mut self) {
+        self.line_hook = None;
+    }
 
-        Ok(decoded)
+    /// Задаёт получатель строк декодированного изображения: каждое декодирование
+    /// передаёт ему строки по мере приёма, до маскировки и эффектов изображения
+    pub fn set_image_sink(&mut self, sink: impl ImageSink + Send + 'static) {
+        self.image_sink = Some(Box::new(sink));
     }
 
-    pub fn noise_processor(&self) -> &NoiseProcessor {
-        &self.noise_processor
+    pub fn clear_image_sink(&mut self) {
+        self.image_sink = None;
     }
 
-    pub fn noise_processor_mut(&mut self) -> &mut NoiseProcessor {
-        &mut self.noise_processor
+    pub fn time_limits(&self) -> &TimeLimits {
+        &self.limits
     }
 
-    pub fn retarder_processor(&self) -> &RetarderProcessor {
-        &self.retarder_processor
+    /// Задаёт пределы времени каждого следующего прогона, см. [`TimeLimits`]
+    pub fn set_time_limits(&mut self, limits: TimeLimits) {
+        self.limits = limits;
     }
 
-    pub fn retarder_processor_mut(&mut self) -> &mut RetarderProcessor {
-        &mut self.retarder_processor
+    /// Задаёт файл, в который каждый прогон `process` пишет сигнал после эффектов, как
+    /// [`write_wav`](Self::write_wav); `None` — не писать. По умолчанию не пишется
+    #[cfg(feature = "wav")]
+    pub fn set_debug_wav(&mut self, path: Option<PathBuf>) {
+        self.debug_wav = path;
     }
 
-    pub fn update_noise_params(&mut self, params: NoiseParams) {
-        self.params.noise = params.clone();
-        self.noise_processor.params = params;
+    /// Взял ли последний прогон чистые сигналы кодера из кэша, не кодируя изображения
+    pub fn encode_cache_hit(&self) -> bool {
+        self.encode_cache.hit
     }
 
-    pub fn update_retarder_params(&mut self, params: RetarderParams) {
-        self.params.retarder = params.clone();
-        self.retarder_processor.params = params;
+    /// Забывает закэшированные сигналы кодера; следующий прогон закодирует всё заново
+    pub fn clear_cache(&mut self) {
+        self.encode_cache = EncodeCache::default();
     }
 
+    pub fn effects(&self) -> &[Box<dyn ChannelEffect>] {
+        &self.effects
+    }
+
+    /// Изменяемый конвейер эффектов: порядок, повторы, удаление и сторонние эффекты
+    pub fn effects_mut(&mut self) -> &mut Vec<Box<dyn ChannelEffect>> {
+        &mut self.effects
+    }
+
+    /// Добавляет эффект в конец конвейера
+    pub fn add_effect(&mut self, effect: Box<dyn ChannelEffect>) {
+        self.effects.push(effect);
+    }
+
+    pub fn decoder(&self) -> &dyn Decoder {
+        self.decoder.as_ref()
+    }
+
+    pub fn params(&self) -> &ProcessingParams {
+        &self.params
+    }
+
+    /// Заменяет все параметры после тех же проверок, что и в [`builder`](Self::builder):
+    /// частоты дискретизации и временных параметров кодера. При ошибке параметры остаются
+    /// прежними; декодер пересоздаётся под новые настройки
+    pub fn try_set_params(&mut self, params: ProcessingParams) -> Result<()> {
+        check_params(&params)?;
+        self.decoder = create_decoder(&params.decoder);
+        self.params = params;
+        Ok(())
+    }
+
+    /// Меняет параметры на месте: `f` правит их копию, которая заменяет текущие только
+    /// после проверок [`try_set_params`](Self::try_set_params)
+    pub fn update_params(&mut self, f: impl FnOnce(&mut ProcessingParams)) -> Result<()> {
+        let mut params = self.params.clone();
+        f(&mut params);
+        self.try_set_params(params)
+    }
+
+    /// Заменяет все параметры без проверок; декодер пересоздаётся под новые настройки
+    #[deprecated(
+        since = "0.1.0",
+        note = "не проверяет параметры, используйте `try_set_params` или `update_params`"
+    )]
+    pub fn set_params(&mut self, params: ProcessingParams) {
+        self.decoder = create_decoder(&params.decoder);
+        self.params = params;
+    }
+
+    /// Заменяет параметры одного эффекта через [`update_params`](Self::update_params): с
+    /// теми же проверками, при ошибке параметры остаются прежними
+    pub fn update_noise_params(&mut self, params: NoiseParams) -> Result<()> {
+        self.update_params(|p| p.noise = params)
+    }
+
+    pub fn update_retarder_params(&mut self, params: RetarderParams) -> Result<()> {
+        self.update_params(|p| p.retarder = params)
+    }
+
+    pub fn update_multipath_params(&mut self, params: MultipathParams) -> Result<()> {
+        self.update_params(|p| p.multipath = params)
+    }
+
+    pub fn update_fading_params(&mut self, params: FadingParams) -> Result<()> {
+        self.update_params(|p| p.fading = params)
+    }
+
+    pub fn update_drift_params(&mut self, params: DriftParams) -> Result<()> {
+        self.update_params(|p| p.drift = params)
+    }
+
+    pub fn update_interference_params(&mut self, params: InterferenceParams) -> Result<()> {
+        self.update_params(|p| p.interference = params)
+    }
+
+    pub fn update_blanker_params(&mut self, params: BlankerParams) -> Result<()> {
+        self.update_params(|p| p.blanker = params)
+    }
+
+    pub fn update_transceiver_params(&mut self, params: TransceiverParams) -> Result<()> {
+        self.update_params(|p| p.transceiver = params)
+    }
+
+    pub fn update_filter_params(&mut self, params: FilterParams) -> Result<()> {
+        self.update_params(|p| p.filter = params)
+    }
+
+    pub fn update_notch_params(&mut self, params: NotchParams) -> Result<()> {
+        self.update_params(|p| p.notch = params)
+    }
+
+    pub fn update_agc_params(&mut self, params: AgcParams) -> Result<()> {
+        self.update_params(|p| p.agc = params)
+    }
+
+    pub fn update_dropout_params(&mut self, params: DropoutParams) -> Result<()> {
+        self.update_params(|p| p.dropout = params)
+    }
+
+    /// Заменяет параметры декодера с проверками и пересоздаёт его
+    pub fn update_decoder_params(&mut self, params: DecoderParams) -> Result<()> {
+        self.update_params(|p| p.decoder = params)
+    }
+
+    /// Сохраняет результат в PNG; метаданные параметров уходят в его текстовые блоки
     pub fn save_result(&self, result: &DynamicImage, output_path: &str) -> Result<()> {
-        result
-            .save_with_format(output_path, ImageFormat::Png)
-            .with_context(|| format!("Не удалось сохранить результат в {}", output_path))
+        let context = || messages::result_save_failed(output_path);
+        let png = metadata::png_with_text(result, &self.params.metadata.fields)
+            .map_err(|e| SstvError::io(context(), e))?;
+        std::fs::write(output_path, png).map_err(|e| SstvError::io(context(), e))
     }
 
     pub fn describe(&self) -> String {
-        format!(
-            "SSTV Processor:\n{}\n{}",
-            self.noise_processor.describe(),
-            self.retarder_processor.describe()
-        )
+        let effects: Vec<String> = self.effects.iter().map(|e| e.describe()).collect();
+        format!("SSTV Processor:\n{}", effects.join("\n"))
     }
 
     /// Проверяет, включены ли какие-либо эффекты
     pub fn has_effects(&self) -> bool {
-        self.noise_processor.is_enabled() || self.retarder_processor.is_enabled()
+        self.effects.iter().any(|e| e.is_enabled())
+    }
+}
+
+/// Проверки параметров, общие для построителя и [`SSTVProcessor::try_set_params`]
+pub(crate) fn check_params(params: &ProcessingParams) -> Result<()> {
+    check_sample_rate(params.sample_rate)?;
+    params.encoder.validate()?;
+    params.metadata.validate()
+}
+
+/// Проверяет, что тоны SSTV помещаются в полосу сигнала с частотой `sample_rate`
+pub fn check_sample_rate(sample_rate: u32) -> Result<()> {
+    if sample_rate < MIN_SAMPLE_RATE {
+        return Err(SstvError::UnsupportedSampleRate {
+            rate: sample_rate,
+            min: MIN_SAMPLE_RATE,
+        });
     }
+    Ok(())
 }
@@ -0,0 +1,81 @@
+use crate::dsp::resample::{self, Resampler};
+use serde::{Deserialize, Serialize};
+
+/// Частота, на которой эффекты работают в предпросмотре, Гц. Ниже неё гармоники сигнала
+/// rsstv, обрезанного шумом, заворачиваются в полосу SSTV
+pub const PREVIEW_SAMPLE_RATE: u32 = 22050;
+
+/// Уровень качества обработки: точность в обмен на скорость
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum QualityTier {
+    /// Черновой прогон для живого предпросмотра и грубого перебора: эффекты на частоте
+    /// не выше [`PREVIEW_SAMPLE_RATE`], короткое ядро передискретизации, без дизеринга
+    Preview,
+    #[default]
+    Normal,
+    /// Длинное ядро передискретизации для итоговых записей
+    High,
+}
+
+impl QualityTier {
+    pub const ALL: &'static [QualityTier] =
+        &[QualityTier::Preview, QualityTier::Normal, QualityTier::High];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            QualityTier::Preview => "Preview",
+            QualityTier::Normal => "Normal",
+            QualityTier::High => "High",
+        }
+    }
+
+    /// Частота, на которой работают эффекты при выходной частоте `sample_rate`
+    pub fn signal_rate(&self, sample_rate: u32) -> u32 {
+        match self {
+            QualityTier::Preview => sample_rate.min(PREVIEW_SAMPLE_RATE),
+            QualityTier::Normal | QualityTier::High => sample_rate,
+        }
+    }
+
+    /// Переходов через ноль с каждой стороны ядра передискретизации
+    pub fn resampler_zero_crossings(&self) -> usize {
+        match self {
+            QualityTier::Preview => 4,
+            QualityTier::Normal => resample::ZERO_CROSSINGS,
+            QualityTier::High => 2 * resample::ZERO_CROSSINGS,
+        }
+    }
+
+    /// Передискретизатор этого уровня
+    pub fn resampler(&self, from_rate: u32, to_rate: u32) -> Resampler {
+        Resampler::with_zero_crossings(from_rate, to_rate, self.resampler_zero_crossings())
+    }
+
+    /// Передискретизирует сигнал ядром этого уровня
+    pub fn resample(&self, samples: &[f32], from_rate: u32, to_rate: u32) -> Vec<f32> {
+        if from_rate == to_rate {
+            return samples.to_vec();
+        }
+        self.resampler(from_rate, to_rate).process(samples)
+    }
+
+    /// Добавлять ли треугольный дизеринг при записи 16-битного WAV
+    pub fn dither(&self) -> bool {
+        *self != QualityTier::Preview
+    }
+}
+
+impl clap::ValueEnum for QualityTier {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[Self::Preview, Self::Normal, Self::High]
+    }
+
+    fn to_possible_value<'a>(&self) -> Option<clap::builder::PossibleValue> {
+        Some(match self {
+            Self::Preview => clap::builder::PossibleValue::new("preview"),
+            Self::Normal => clap::builder::PossibleValue::new("normal"),
+            Self::High => clap::builder::PossibleValue::new("high"),
+        })
+    }
+}
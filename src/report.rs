@@ -0,0 +1,245 @@
+use crate::conceal;
+use crate::decoder::{Decoder, DecoderParams, VisReport};
+use crate::dsp;
+use crate::effect::{self, ChannelSummary, SanitizeEvent};
+use crate::loudness::Normalization;
+use crate::messages;
+use crate::metadata::{self, Metadata};
+use crate::modes::{SYNC_HZ, WHITE_HZ};
+use crate::occupancy::Occupancy;
+use image::DynamicImage;
+use std::collections::BTreeMap;
+
+/// Разрешение спектра при оценке С/Ш, Гц (округляется до степени двойки отсчётов)
+const SNR_BIN_HZ: u32 = 20;
+/// Запас полосы сигнала за крайними тонами, Гц
+const SNR_BAND_MARGIN_HZ: f32 = 100.0;
+/// Полосы, по которым меряется шум: между краем фильтра приёмника и тонами SSTV
+const SNR_NOISE_BANDS_HZ: [(f32, f32); 2] = [(400.0, 900.0), (2600.0, 2700.0)];
+/// Пределы оценки С/Ш, дБ: на чистом сигнале шум неотличим от боковых полос ЧМ
+const SNR_RANGE_DB: (f32, f32) = (-20.0, 50.0);
+
+/// Итог декодирования: изображение и то, насколько хорошо оно принято
+#[derive(Clone, Debug)]
+pub struct DecodeReport {
+    pub image: DynamicImage,
+    /// Число декодированных строк
+    pub lines_decoded: u32,
+    /// Сигнал кончился раньше изображения (`DecodeResult::Partial` у rsstv): нижние
+    /// строки не приняты
+    pub partial: bool,
+    /// Доля строк, синхроимпульс которых найден не ниже порога, если декодер это знает
+    pub sync_lock_ratio: Option<f32>,
+    /// Наклон: отклонение периода строки от номинального, ppm, если декодер его мерил
+    pub slant_estimate: Option<f64>,
+    /// Оценка отношения сигнал/шум в полосе тонов SSTV, дБ
+    pub snr_estimate: Option<f32>,
+    /// Занимаемая полоса переданного сигнала (выхода кодера) и запас до маски канала по
+    /// умолчанию, если сигнал закодировал процессор
+    pub occupancy: Option<Occupancy>,
+    /// Принятый код VIS, если декодер его прочитал
+    pub vis_detected: Option<VisReport>,
+    /// Уверенность синхронизации по строкам, если декодер её даёт
+    pub line_confidence: Option<Vec<f32>>,
+    /// Поправка частоты АПЧ по строкам, Гц, если декодер её ведёт
+    pub afc_trace: Option<Vec<f32>>,
+    /// Строки, замаскированные после декодирования (при `conceal`)
+    pub concealed_lines: Vec<u32>,
+    /// Эффекты, после которых в сигнале исправлялись NaN и бесконечности (при
+    /// [`SanitizeMode::Repair`](crate::effect::SanitizeMode::Repair))
+    pub sanitized: Vec<SanitizeEvent>,
+    /// Уровень сигнала после эффектов и выходного усиления, если сигнал прошёл через
+    /// эффекты процессора
+    pub level: Option<LevelMeter>,
+    /// Нормировка громкости записанного WAV, если она включена
+    pub normalization: Option<Normalization>,
+    /// Сводка канала, через который прошёл сигнал: оценки эффектов, их задержки и сдвиг
+    /// копий на изображении, если сигнал прошёл через эффекты процессора
+    pub channel: Option<ChannelSummary>,
+    /// Метаданные из посылок FSK после изображения, см. [`Metadata::read`]
+    pub metadata: BTreeMap<String, String>,
+}
+
+/// Уровень выходного сигнала до ограничения диапазоном −1…1
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct LevelMeter {
+    /// Пиковый уровень, дБ относительно полной шкалы
+    pub peak_db: f32,
+    /// Среднеквадратичный уровень, дБ относительно полной шкалы
+    pub rms_db: f32,
+    /// Доля сэмплов за пределами −1…1, обрезанных ограничением, 0–1
+    pub clipped: f32,
+}
+
+impl LevelMeter {
+    /// Меряет уровень `samples`; пустой сигнал — тишина
+    pub fn measure(samples: &[f32]) -> Self {
+        let peak = samples.iter().fold(0.0f32, |m, x| m.max(x.abs()));
+        let energy: f64 = samples.iter().map(|&x| (x as f64) * (x as f64)).sum();
+        let clipped = samples.iter().filter(|x| x.abs() > 1.0).count();
+        Self::from_totals(peak, energy, clipped, samples.len())
+    }
+
+    /// Уровень по пику, сумме квадратов и числу сэмплов за пределами −1…1 из `count`
+    /// сэмплов — когда сигнал меряется по частям
+    pub(crate) fn from_totals(peak: f32, energy: f64, clipped: usize, count: usize) -> Self {
+        let mean = energy / count.max(1) as f64;
+        Self {
+            peak_db: effect::power_db(peak * peak),
+            rms_db: effect::power_db(mean as f32),
+            clipped: clipped as f32 / count.max(1) as f32,
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        let level = messages::report_level(self.peak_db, self.rms_db);
+        if self.clipped > 0.0 {
+            format!(
+                "{}, {}",
+                level,
+                messages::report_clipped(self.clipped * 100.0)
+            )
+        } else {
+            level
+        }
+    }
+}
+
+impl DecodeReport {
+    /// Собирает отчёт после `decoder.decode` изображения `image` из `samples` (частота
+    /// [`rsstv::SAMPLE_RATE`]), маскирует испорченные строки, если это включено в `params`,
+    /// и применяет эффекты изображения [`DecoderParams::image_effects`]
+    pub fn from_decoder(
+        image: DynamicImage,
+        decoder: &dyn Decoder,
+        params: &DecoderParams,
+        samples: &[f32],
+    ) -> Self {
+        let line_confidence = decoder.line_confidence().map(<[f32]>::to_vec);
+        let (image, concealed_lines) = if params.conceal {
+            let mut rgb = image.to_rgb8();
+            let lines = conceal::conceal_lines(&mut rgb, line_confidence.as_deref());
+            (DynamicImage::ImageRgb8(rgb), lines)
+        } else {
+            (image, Vec::new())
+        };
+        let image = effect::apply_image_effects(&params.image_effects(), image);
+
+        let sync_lock_ratio = line_confidence
+            .as_deref()
+            .filter(|c| !c.is_empty())
+            .map(|c| {
+                let locked = c.iter().filter(|&&v| v >= params.sync_threshold).count();
+                locked as f32 / c.len() as f32
+            });
+
+        Self {
+            lines_decoded: line_confidence
+                .as_ref()
+                .map_or(image.height(), |c| c.len() as u32),
+            image,
+            partial: decoder.partial(),
+            sync_lock_ratio,
+            slant_estimate: decoder.slant_ppm(),
+            snr_estimate: estimate_snr(samples, rsstv::SAMPLE_RATE as u32),
+            occupancy: None,
+            vis_detected: decoder.vis().cloned(),
+            line_confidence,
+            afc_trace: decoder.afc_trace().map(<[f32]>::to_vec),
+            concealed_lines,
+            sanitized: Vec::new(),
+            level: None,
+            normalization: None,
+            channel: None,
+            metadata: Metadata::read(samples),
+        }
+    }
+
+    /// Средняя уверенность синхронизации по строкам, если декодер её даёт
+    pub fn mean_confidence(&self) -> Option<f32> {
+        self.line_confidence
+            .as_deref()
+            .filter(|c| !c.is_empty())
+            .map(|c| c.iter().sum::<f32>() / c.len() as f32)
+    }
+
+    pub fn describe(&self) -> String {
+        let mut parts = vec![messages::report_lines(self.lines_decoded)];
+        if self.partial {
+            parts.push(messages::report_partial().to_string());
+        }
+        if let Some(ratio) = self.sync_lock_ratio {
+            parts.push(messages::report_sync(ratio * 100.0));
+        }
+        if let Some(mean) = self.mean_confidence() {
+            parts.push(messages::report_confidence(mean * 100.0));
+        }
+        if let Some(slant) = self.slant_estimate {
+            parts.push(messages::report_slant(slant));
+        }
+        if let Some(snr) = self.snr_estimate {
+            parts.push(messages::report_snr(snr));
+        }
+        if let Some(occupancy) = &self.occupancy {
+            parts.push(occupancy.describe());
+        }
+        if let Some(level) = &self.level {
+            parts.push(level.describe());
+        }
+        if let Some(normalization) = &self.normalization {
+            parts.push(normalization.describe());
+        }
+        if !self.sanitized.is_empty() {
+            let effects: Vec<_> = self.sanitized.iter().map(|e| e.effect).collect();
+            parts.push(messages::report_sanitized(&effects.join(", ")));
+        }
+        if !self.metadata.is_empty() {
+            parts.push(messages::report_metadata(&metadata::describe(
+                &self.metadata,
+            )));
+        }
+        parts.join(", ")
+    }
+}
+
+/// Оценка отношения сигнал/шум в полосе тонов SSTV (1100–2400 Гц), дБ. Плотность шума
+/// берётся из полос рядом, где сигнала SSTV почти нет, медианой по окнам — так импульсные
+/// помехи и паузы не сдвигают её. Мощность в полосе — тоже медиана по окнам, за вычетом
+/// шума. `None`, если сигнал короче одного окна
+pub fn estimate_snr(samples: &[f32], sample_rate: u32) -> Option<f32> {
+    let fft_len = (sample_rate / SNR_BIN_HZ).max(2).next_power_of_two() as usize;
+    let frames = dsp::power_frames(samples, fft_len);
+    if frames.is_empty() {
+        return None;
+    }
+    let bin_hz = sample_rate as f32 / fft_len as f32;
+    let bins = |(low, high): (f32, f32)| {
+        let last = fft_len / 2;
+        ((low / bin_hz).ceil() as usize).min(last)..((high / bin_hz).floor() as usize + 1).min(last)
+    };
+    let band = bins((SYNC_HZ - SNR_BAND_MARGIN_HZ, WHITE_HZ + SNR_BAND_MARGIN_HZ));
+    let noise_bins: Vec<usize> = SNR_NOISE_BANDS_HZ.into_iter().flat_map(bins).collect();
+    if band.is_empty() || noise_bins.is_empty() {
+        return None;
+    }
+
+    let median = |mut values: Vec<f32>| {
+        values.sort_by(f32::total_cmp);
+        values[values.len() / 2]
+    };
+    let density = median(
+        frames
+            .iter()
+            .map(|f| noise_bins.iter().map(|&k| f[k]).sum::<f32>() / noise_bins.len() as f32)
+            .collect(),
+    );
+    let total = median(
+        frames
+            .iter()
+            .map(|f| f[band.clone()].iter().sum::<f32>())
+            .collect(),
+    );
+    let noise = density * band.len() as f32;
+    let snr = 10.0 * ((total - noise).max(0.0) / noise.max(f32::MIN_POSITIVE)).log10();
+    Some(snr.clamp(SNR_RANGE_DB.0, SNR_RANGE_DB.1))
+}
@@ -1,14 +1,136 @@
-use crate::envelope::EnvelopeKind;
-use anyhow::Result;
+use crate::buffer::SampleBuffer;
+use crate::dsp;
+use crate::effect::{
+    self, BlockProcessor, Bypass, ChannelEffect, EffectContext, EffectEstimate, EffectMix,
+    StreamContext,
+};
+use crate::envelope::{EnvelopeAnchor, EnvelopeKind};
+use crate::error::{Result, SstvError};
+use crate::messages;
+use crate::processor::ProcessingParams;
+use crate::signal::Signal;
+use crate::stream::{BlockEncoder, DEFAULT_BLOCK_LEN};
 use image::DynamicImage;
-use rsstv::{common::SSTVMode, martinm1::MartinM1};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng, rng};
+use rsstv::{SAMPLE_RATE, common::SSTVMode, martinm1::MartinM1};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::ops::Range;
+use std::str::FromStr;
 
-#[derive(Clone, Debug)]
+/// Откуда берётся сигнал призрака
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RetarderSource {
+    /// Отдельная картинка-призрак (`-r`, панель «Ретардер»)
+    #[default]
+    Image,
+    /// Сам основной сигнал до эффектов: эхо с задержкой `delay_ms`
+    SelfEcho,
+    /// Записанный ранее WAV, например debug.wav прошлого прогона
+    WavFile(String),
+    /// Основное изображение, отражённое слева направо
+    Flipped,
+    /// Основное изображение в негативе
+    Inverted,
+}
+
+impl RetarderSource {
+    /// Варианты без параметров для выбора в интерфейсе
+    pub const SIMPLE: &'static [RetarderSource] = &[
+        RetarderSource::Image,
+        RetarderSource::SelfEcho,
+        RetarderSource::Flipped,
+        RetarderSource::Inverted,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            RetarderSource::Image => "Image",
+            RetarderSource::SelfEcho => "Self echo",
+            RetarderSource::WavFile(_) => "WAV file",
+            RetarderSource::Flipped => "Flipped",
+            RetarderSource::Inverted => "Inverted",
+        }
+    }
+
+    /// Картинка призрака, построенная из основного изображения (`Flipped`, `Inverted`)
+    pub fn image_from(&self, main: &DynamicImage) -> Option<DynamicImage> {
+        match self {
+            RetarderSource::Flipped => Some(main.fliph()),
+            RetarderSource::Inverted => {
+                let mut image = main.clone();
+                image.invert();
+                Some(image)
+            }
+            _ => None,
+        }
+    }
+
+    /// Сигнал призрака, которому не нужна картинка: сам `main` для `SelfEcho`,
+    /// прочитанный файл для `WavFile`; для остальных источников — `None`
+    pub fn signal_from(&self, main: &Signal) -> Result<Option<Signal>> {
+        match self {
+            RetarderSource::SelfEcho => Ok(Some(main.clone())),
+            #[cfg(feature = "wav")]
+            RetarderSource::WavFile(path) => Signal::from_wav(path).map(Some),
+            #[cfg(not(feature = "wav"))]
+            RetarderSource::WavFile(_) => Err(SstvError::invalid(
+                "retarder.source",
+                messages::retarder_no_wav(),
+            )),
+            _ => Ok(None),
+        }
+    }
+}
+
+impl FromStr for RetarderSource {
+    type Err = SstvError;
+
+    /// `image`, `self`, `flip`, `invert`; любое другое значение — путь к WAV
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(match s.trim() {
+            "" => {
+                return Err(SstvError::invalid(
+                    "retarder.source",
+                    messages::retarder_source_expected(),
+                ));
+            }
+            "image" => RetarderSource::Image,
+            "self" => RetarderSource::SelfEcho,
+            "flip" => RetarderSource::Flipped,
+            "invert" => RetarderSource::Inverted,
+            path => RetarderSource::WavFile(path.to_string()),
+        })
+    }
+}
+
+impl fmt::Display for RetarderSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RetarderSource::Image => f.write_str("image"),
+            RetarderSource::SelfEcho => f.write_str("self"),
+            RetarderSource::WavFile(path) => f.write_str(path),
+            RetarderSource::Flipped => f.write_str("flip"),
+            RetarderSource::Inverted => f.write_str("invert"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
 pub struct RetarderParams {
     pub level: f32,
     pub env: EnvelopeKind,
     pub repeat: f32,
     pub delay_ms: u32,
+    /// Отрезок, на который растягивается огибающая
+    pub anchor: EnvelopeAnchor,
+    /// Откуда берётся сигнал призрака
+    pub source: RetarderSource,
+    /// Доли смешивания выхода эффекта со входом
+    pub mix: EffectMix,
 }
 
 impl Default for RetarderParams {
@@ -18,14 +140,25 @@ impl Default for RetarderParams {
             env: EnvelopeKind::Const,
             repeat: 1.0,
             delay_ms: 0,
+            anchor: EnvelopeAnchor::Audio,
+            source: RetarderSource::Image,
+            mix: EffectMix::default(),
         }
     }
 }
 
 pub struct RetarderProcessor {
+    #[deprecated(since = "0.1.0", note = "используйте `params()` и `set_params()`")]
     pub params: RetarderParams,
 }
 
+impl Default for RetarderProcessor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[allow(deprecated)]
 impl RetarderProcessor {
     pub fn new() -> Self {
         Self {
@@ -37,42 +170,106 @@ impl RetarderProcessor {
         Self { params }
     }
 
-    /// Применяет эффект ретардера (призрака) к массиву сэмплов
-    pub fn apply_retarder(&self, samples: &mut [f32], retarder_image: &DynamicImage) -> Result<()> {
+    pub fn params(&self) -> &RetarderParams {
+        &self.params
+    }
+
+    pub fn set_params(&mut self, params: RetarderParams) {
+        self.params = params;
+    }
+
+    /// Применяет эффект ретардера (призрака) к буферу сэмплов
+    pub fn apply_retarder(
+        &self,
+        buffer: &mut SampleBuffer,
+        retarder_image: &DynamicImage,
+    ) -> Result<()> {
         if self.params.level <= 0.0 {
             return Ok(());
         }
 
         // Кодируем ретардер в сэмплы
-        let mut retarder_samples = self.encode_retarder_image(retarder_image)?;
+        let retarder = self.encode_retarder_image(retarder_image)?;
+
+        self.apply_retarder_samples(buffer, retarder)
+    }
+
+    /// Применяет эффект ретардера по уже закодированному сигналу призрака
+    pub fn apply_retarder_samples(
+        &self,
+        buffer: &mut SampleBuffer,
+        retarder: SampleBuffer,
+    ) -> Result<()> {
+        let image = 0..buffer.len();
+        self.apply_retarder_anchored(buffer, retarder, &image)
+    }
+
+    /// Применяет ретардер, привязывая огибающую к телу изображения `image` при якоре `Image`
+    pub fn apply_retarder_anchored(
+        &self,
+        buffer: &mut SampleBuffer,
+        retarder: SampleBuffer,
+        image: &Range<usize>,
+    ) -> Result<()> {
+        self.apply_retarder_with_rng(buffer, retarder, image, false, &mut rng())
+    }
+
+    /// Применяет ретардер, беря значения случайной огибающей из генератора `rng`.
+    /// Призрак с другой частотой сначала передискретизируется к частоте `buffer`:
+    /// задержка и огибающая считаются по ней. Смешивание идёт кусками, при `parallel` —
+    /// в пуле rayon
+    pub fn apply_retarder_with_rng(
+        &self,
+        buffer: &mut SampleBuffer,
+        retarder: SampleBuffer,
+        image: &Range<usize>,
+        parallel: bool,
+        rng: &mut impl Rng,
+    ) -> Result<()> {
+        if self.params.level <= 0.0 {
+            return Ok(());
+        }
+
+        let sample_rate = buffer.rate;
+        let mut retarder_samples = if retarder.rate == sample_rate {
+            retarder.data
+        } else {
+            retarder.resampled(sample_rate).data
+        };
+        let samples = &mut buffer.data;
         let samples_len = samples.len();
 
         // Применяем задержку
-        self.apply_delay(&mut retarder_samples);
+        self.apply_delay(&mut retarder_samples, sample_rate);
 
         // Подгоняем длину ретардера
         self.adjust_retarder_length(&mut retarder_samples, samples_len);
 
         // Микшируем с основным сигналом
-        self.mix_retarder(samples, &retarder_samples);
+        self.mix_retarder(
+            samples,
+            &retarder_samples,
+            image,
+            sample_rate,
+            parallel,
+            rng,
+        );
 
         Ok(())
     }
 
-    /// Кодирует изображение ретардера в SSTV сэмплы
-    fn encode_retarder_image(&self, retarder_image: &DynamicImage) -> Result<Vec<f32>> {
+    /// Кодирует изображение ретардера в SSTV сэмплы с частотой кодера [`SAMPLE_RATE`]
+    fn encode_retarder_image(&self, retarder_image: &DynamicImage) -> Result<SampleBuffer> {
         let mut encoder = MartinM1::new();
         let samples = encoder.encode(retarder_image.clone()).to_samples();
-        Ok(samples)
+        Ok(SampleBuffer::mono(samples, SAMPLE_RATE as u32))
     }
 
     /// Применяет задержку к сэмплам ретардера
-    fn apply_delay(&self, retarder_samples: &mut Vec<f32>) {
-        if self.params.delay_ms > 0 {
-            let delay_samples = (self.params.delay_ms as f32 / 1000.0 * 11_025.0) as usize;
-            if delay_samples > 0 {
-                retarder_samples.splice(0..0, std::iter::repeat(0.0).take(delay_samples));
-            }
+    fn apply_delay(&self, retarder_samples: &mut Vec<f32>, sample_rate: u32) {
+        let delay_samples = self.get_delay_samples(sample_rate);
+        if delay_samples > 0 {
+            retarder_samples.splice(0..0, std::iter::repeat_n(0.0, delay_samples));
         }
     }
 
@@ -80,27 +277,51 @@ impl RetarderProcessor {
     fn adjust_retarder_length(&self, retarder_samples: &mut Vec<f32>, target_length: usize) {
         if retarder_samples.len() < target_length {
             let padding = target_length - retarder_samples.len();
-            retarder_samples.extend(std::iter::repeat(0.0).take(padding));
+            retarder_samples.extend(std::iter::repeat_n(0.0, padding));
         }
     }
 
     /// Микширует ретардер с основным сигналом
-    fn mix_retarder(&self, main_samples: &mut [f32], retarder_samples: &[f32]) {
+    fn mix_retarder(
+        &self,
+        main_samples: &mut [f32],
+        retarder_samples: &[f32],
+        image: &Range<usize>,
+        sample_rate: u32,
+        parallel: bool,
+        rng: &mut impl Rng,
+    ) {
         let level = self.params.level.clamp(0.0, 1.0);
         let main_len = main_samples.len();
         let retarder_len = retarder_samples.len();
+        let repeat = self.params.repeat;
 
-        for (i, main_sample) in main_samples.iter_mut().enumerate() {
-            // Позиция в ретардере с учётом коэффициента повторения
-            let retarder_idx = ((i as f32) * self.params.repeat) as usize;
-            let retarder_value = retarder_samples[retarder_idx % retarder_len];
+        let envelope = self.params.env.factors_with_rng(
+            main_len,
+            repeat,
+            sample_rate,
+            self.params.anchor,
+            image,
+            parallel,
+            rng,
+        );
 
-            // Применяем огибающую
-            let env_factor = self.params.env.factor(i, main_len, self.params.repeat);
-
-            // Микшируем
-            *main_sample = (*main_sample + retarder_value * level * env_factor).clamp(-1.0, 1.0);
+        // Ретардер не короче основного сигнала, поэтому без повторения он берётся как есть
+        if repeat == 1.0 {
+            let retarder = &retarder_samples[..main_len];
+            dsp::mix(main_samples, retarder, envelope.as_deref(), level, parallel);
+            return;
         }
+        dsp::for_each_chunk(main_samples, parallel, |offset, chunk| {
+            for (k, main_sample) in chunk.iter_mut().enumerate() {
+                // Позиция в ретардере с учётом коэффициента повторения
+                let i = offset + k;
+                let retarder_idx = ((i as f32) * repeat) as usize;
+                let retarder_value = retarder_samples[retarder_idx % retarder_len];
+                let env_factor = envelope.as_ref().map_or(1.0, |e| e[i]);
+                *main_sample += retarder_value * level * env_factor;
+            }
+        });
     }
 
     /// Устанавливает уровень ретардера
@@ -118,6 +339,11 @@ impl RetarderProcessor {
         self.params.repeat = repeat.max(0.1);
     }
 
+    /// Устанавливает привязку огибающей
+    pub fn set_anchor(&mut self, anchor: EnvelopeAnchor) {
+        self.params.anchor = anchor;
+    }
+
     /// Устанавливает задержку в миллисекундах
     pub fn set_delay_ms(&mut self, delay_ms: u32) {
         self.params.delay_ms = delay_ms;
@@ -128,29 +354,198 @@ impl RetarderProcessor {
         self.params.level > 0.0
     }
 
-    /// Рассчитывает задержку в сэмплах
-    pub fn get_delay_samples(&self) -> usize {
-        (self.params.delay_ms as f32 / 1000.0 * 11_025.0) as usize
+    /// Рассчитывает задержку в сэмплах сигнала с частотой `sample_rate`
+    pub fn get_delay_samples(&self, sample_rate: u32) -> usize {
+        (self.params.delay_ms as f64 / 1000.0 * sample_rate as f64).round() as usize
     }
 
     /// Возвращает описание текущих настроек ретардера
     pub fn describe(&self) -> String {
         if !self.is_enabled() {
-            return "Ретардер отключен".to_string();
+            return messages::retarder_off().to_string();
         }
 
-        format!(
-            "Ретардер: {:.1}%, огибающая: {}, повторение: {:.1}x, задержка: {} мс",
+        let description = messages::retarder_describe(
             self.params.level * 100.0,
             self.params.env.name(),
             self.params.repeat,
-            self.params.delay_ms
-        )
+            self.params.delay_ms,
+        );
+        match &self.params.source {
+            RetarderSource::Image => description,
+            source => format!(
+                "{}, {}",
+                description,
+                messages::retarder_source(&source.to_string())
+            ),
+        }
+    }
+
+    /// Длина сигнала после ретардера. Призрак подмешивается только в пределах основного
+    /// сигнала: его хвост длиной в задержку за концом обрезается, поэтому длина та же
+    pub fn estimate_output_length(&self, base_length: usize, _sample_rate: u32) -> usize {
+        base_length
+    }
+}
+
+#[allow(deprecated)]
+impl ChannelEffect for RetarderProcessor {
+    fn name(&self) -> &'static str {
+        messages::retarder()
+    }
+
+    fn mix(&self) -> EffectMix {
+        self.params.mix
+    }
+
+    /// Подмешивает сигнал призрака из контекста; без призрака ничего не делает
+    fn apply(&self, buffer: &mut SampleBuffer, ctx: &EffectContext) -> Result<()> {
+        match ctx.retarder {
+            Some(retarder) => self.apply_retarder_with_rng(
+                buffer,
+                retarder.clone(),
+                &ctx.image_span,
+                ctx.parallel,
+                &mut *ctx.rng.borrow_mut(),
+            ),
+            None => Ok(()),
+        }
+    }
+
+    fn is_enabled(&self) -> bool {
+        RetarderProcessor::is_enabled(self)
+    }
+
+    fn describe(&self) -> String {
+        RetarderProcessor::describe(self)
+    }
+
+    fn sync_params(&mut self, params: &ProcessingParams) {
+        self.params = params.retarder.clone();
     }
 
-    /// Предварительная оценка влияния ретардера на длину сигнала
-    pub fn estimate_output_length(&self, base_length: usize) -> usize {
-        let delay_samples = self.get_delay_samples();
-        base_length + delay_samples
+    /// Мощность призрака из контекста с уровнем и огибающей; без призрака — только
+    /// задержка. Её сдвиг на изображении досчитывает [`effect::analyze_effects`]
+    fn analyze(&self, buffer: &SampleBuffer, ctx: &EffectContext) -> EffectEstimate {
+        let p = &self.params;
+        let added_power_db = ctx.retarder.map(|retarder| {
+            let envelope = p.env.mean_power(
+                buffer.len(),
+                p.repeat,
+                buffer.rate,
+                p.anchor,
+                &ctx.image_span,
+                &mut ctx.rng.borrow().clone(),
+            );
+            let level = p.level.clamp(0.0, 1.0);
+            let ratio = retarder.mean_power() / buffer.mean_power().max(f32::MIN_POSITIVE);
+            effect::power_db(ratio * level * level * envelope)
+        });
+        EffectEstimate {
+            added_power_db,
+            delay_ms: Some(p.delay_ms as f32),
+            latency_samples: (p.delay_ms > 0).then(|| self.get_delay_samples(buffer.rate)),
+            ..EffectEstimate::new(self.name(), self.describe())
+        }
+    }
+
+    fn output_len(&self, input_len: usize, sample_rate: u32) -> usize {
+        self.estimate_output_length(input_len, sample_rate)
     }
+
+    /// Призрак кодируется своим блочным кодером по ходу сигнала, поэтому целиком в памяти
+    /// не лежит. Блоками не считаются призрак из WAV и повторение, отличное от 1: им
+    /// нужен произвольный доступ к сигналу призрака
+    fn block_processor(&self, ctx: &StreamContext) -> Result<Box<dyn BlockProcessor>> {
+        if let RetarderSource::WavFile(_) = self.params.source {
+            return Err(SstvError::invalid(
+                "retarder.source",
+                messages::streaming_wav_ghost(),
+            ));
+        }
+        if self.params.repeat != 1.0 {
+            return Err(SstvError::invalid(
+                "retarder.repeat",
+                messages::streaming_repeat(self.params.repeat),
+            ));
+        }
+        let Some(image) = ctx.retarder_image else {
+            return Ok(Box::new(Bypass));
+        };
+        Ok(Box::new(RetarderBlocks {
+            params: self.params.clone(),
+            ghost: BlockEncoder::new(image, ctx.encoder, DEFAULT_BLOCK_LEN).flatten(),
+            delay: self.get_delay_samples(ctx.sample_rate),
+            taken: 0,
+            len: ctx.len,
+            image: ctx.image_span.clone(),
+            sample_rate: ctx.sample_rate,
+            rng: ctx.fork_rng(),
+        }))
+    }
+}
+
+/// Ретардер блоками: призрак идёт из своего кодера с задержкой `delay`, за концом
+/// призрака — тишина. `taken` — сколько сэмплов призрака уже взято
+struct RetarderBlocks {
+    params: RetarderParams,
+    ghost: std::iter::Flatten<BlockEncoder>,
+    delay: usize,
+    taken: usize,
+    len: usize,
+    image: Range<usize>,
+    sample_rate: u32,
+    rng: StdRng,
+}
+
+impl BlockProcessor for RetarderBlocks {
+    fn process_block(&mut self, block: &mut [f32], start: usize) -> Result<()> {
+        let p = &self.params;
+        let level = p.level.clamp(0.0, 1.0);
+        for (k, sample) in block.iter_mut().enumerate() {
+            let i = start + k;
+            let ghost = if i < self.delay {
+                0.0
+            } else {
+                self.taken += 1;
+                self.ghost.next().unwrap_or(0.0)
+            };
+            let factor = p.env.factor_anchored(
+                i,
+                self.len,
+                p.repeat,
+                self.sample_rate,
+                p.anchor,
+                &self.image,
+                &mut self.rng,
+            );
+            *sample += ghost * level * factor;
+        }
+        Ok(())
+    }
+    /// Кодер призрака не сохраняется: восстановленный обработчик кодирует призрак заново
+    /// и пропускает уже взятые сэмплы
+    fn save_state(&mut self) -> Result<serde_json::Value> {
+        effect::to_block_state(&RetarderState {
+            seed: effect::checkpoint_rng(&mut self.rng),
+            taken: self.taken,
+        })
+    }
+
+    fn restore_state(&mut self, state: &serde_json::Value) -> Result<()> {
+        let state: RetarderState = effect::from_block_state(state, messages::retarder())?;
+        if let Some(skip) = state.taken.checked_sub(self.taken + 1) {
+            self.ghost.nth(skip);
+        }
+        self.taken = state.taken.max(self.taken);
+        self.rng = StdRng::seed_from_u64(state.seed);
+        Ok(())
+    }
+}
+
+/// Контрольная точка ретардера: зерно генератора и число взятых сэмплов призрака
+#[derive(Serialize, Deserialize)]
+struct RetarderState {
+    seed: u64,
+    taken: usize,
 }
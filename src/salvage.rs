@@ -0,0 +1,56 @@
+use crate::buffer::SampleBuffer;
+use crate::decoder::DecoderParams;
+use crate::error::{Result, SstvError};
+use crate::messages;
+use crate::native_decoder;
+use crate::quality::QualityTier;
+use crate::spectrogram::Spectrogram;
+use image::{DynamicImage, ImageFormat};
+use rsstv::SAMPLE_RATE;
+use std::path::{Path, PathBuf};
+
+/// Что остаётся от приёма, в котором декодер не нашёл изображение: растр без заголовка
+/// и водопад сигнала. Вместе с искажённым WAV они показывают, что канал сделал с
+/// сигналом и почему декодер сдался
+#[derive(Clone, Debug)]
+pub struct Salvage {
+    /// Почему декодер не нашёл изображение
+    pub reason: String,
+    /// Строки от первого синхроимпульса сигнала, см. [`native_decoder::raster`]
+    pub raster: DynamicImage,
+    /// Водопад всего сигнала
+    pub spectrogram: DynamicImage,
+}
+
+impl Salvage {
+    /// Собирает остатки приёма сигнала `signal` любой частоты; `reason` — ошибка декодера.
+    /// Растр читается на частоте кодера ядром передискретизации уровня `quality`
+    pub fn new(
+        signal: &SampleBuffer,
+        params: &DecoderParams,
+        quality: QualityTier,
+        reason: &SstvError,
+    ) -> Self {
+        let mono = signal.resampled_with(SAMPLE_RATE as u32, quality);
+        Self {
+            reason: reason.to_string(),
+            raster: DynamicImage::ImageRgb8(native_decoder::raster(&mono.data, params)),
+            spectrogram: Spectrogram::new().render(&mono.data, mono.rate),
+        }
+    }
+
+    /// Пишет растр в `output` и водопад рядом в `<имя>.spectrogram.png`; возвращает
+    /// пути записанных файлов
+    pub fn save(&self, output: &Path) -> Result<Vec<PathBuf>> {
+        let spectrogram = output.with_extension("spectrogram.png");
+        save_png(&self.raster, output)?;
+        save_png(&self.spectrogram, &spectrogram)?;
+        Ok(vec![output.to_path_buf(), spectrogram])
+    }
+}
+
+fn save_png(image: &DynamicImage, path: &Path) -> Result<()> {
+    image
+        .save_with_format(path, ImageFormat::Png)
+        .map_err(|e| SstvError::io(messages::save_failed(&path.display().to_string()), e))
+}
@@ -0,0 +1,127 @@
+use crate::decoder::{DecoderParams, HeaderMatch, locate_header};
+use crate::error::Result;
+use crate::modes::{HEADER_MS, LEADER_MS, MARTIN_M1};
+use crate::report::DecodeReport;
+use crate::signal::{self, Signal};
+use crate::threads;
+use rayon::prelude::*;
+use rsstv::SAMPLE_RATE;
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+
+/// Запас перед лидер-тоном и после конца изображения в куске передачи, мс
+const MARGIN_MS: f64 = 100.0;
+
+/// Параметры поиска и декодирования нескольких передач в длинной записи
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScanParams {
+    /// Наибольшее число одновременно декодируемых передач (0 — как в [`threads::configure`]).
+    /// Каждый поток держит копию своего куска записи и декодер
+    pub max_workers: usize,
+}
+
+/// Передача, найденная в записи
+#[derive(Clone, Debug)]
+pub struct Transmission {
+    /// Сэмплы передачи в записи (частота декодера) с небольшим запасом по краям
+    pub range: Range<usize>,
+    /// Заголовок, по которому найдена передача; `start` — от начала записи
+    pub header: HeaderMatch,
+}
+
+impl Transmission {
+    /// Начало передачи от начала записи, секунд
+    pub fn start_secs(&self) -> f64 {
+        self.header.start as f64 / SAMPLE_RATE as f64
+    }
+}
+
+/// Передача и итог её декодирования
+#[derive(Debug)]
+pub struct ScannedImage {
+    pub transmission: Transmission,
+    pub outcome: Result<DecodeReport>,
+}
+
+/// Длина передачи от начала лидер-тона до конца изображения, сэмплов
+fn transmission_len() -> usize {
+    let image_ms = MARTIN_M1.line_us() * MARTIN_M1.height as f64 / 1000.0;
+    ms_to_samples(HEADER_MS as f64 + image_ms)
+}
+
+fn ms_to_samples(ms: f64) -> usize {
+    (ms / 1000.0 * SAMPLE_RATE as f64).round() as usize
+}
+
+/// Ищет в записи (частота декодера) все передачи. После найденного заголовка поиск
+/// продолжается с конца её изображения: ровные участки картинки на частоте лидер-тона
+/// иначе принимались бы за новый заголовок. Кусок передачи обрезается началом следующей
+pub fn find_transmissions(samples: &[f32], params: &DecoderParams) -> Vec<Transmission> {
+    let margin = ms_to_samples(MARGIN_MS);
+    let len = transmission_len();
+
+    let mut headers = Vec::new();
+    let mut pos = 0;
+    while pos < samples.len() {
+        let Some(mut header) = locate_header(&samples[pos..], params) else {
+            break;
+        };
+        header.start += pos;
+        pos = header.start + len;
+        headers.push(header);
+    }
+
+    let starts: Vec<usize> = headers.iter().map(|h| h.start).collect();
+    headers
+        .into_iter()
+        .enumerate()
+        .map(|(k, header)| {
+            let start = header.start.saturating_sub(margin);
+            let next = starts
+                .get(k + 1)
+                .map_or(samples.len(), |&s| s.saturating_sub(margin));
+            let end = (header.start + len + margin).min(next).min(samples.len());
+            Transmission {
+                range: start..end.max(start),
+                header,
+            }
+        })
+        .collect()
+}
+
+/// Находит все передачи в сигнале и декодирует их параллельно, не более чем
+/// `scan.max_workers` одновременно. Сбой одной передачи не мешает остальным;
+/// итоги идут в порядке передач
+pub fn decode_all(
+    signal: &Signal,
+    decoder: &DecoderParams,
+    scan: &ScanParams,
+) -> Result<Vec<ScannedImage>> {
+    let signal = signal.resampled(SAMPLE_RATE as u32);
+    let samples = signal.samples();
+    let transmissions = find_transmissions(samples, decoder);
+
+    let pool = threads::pool(scan.max_workers)?;
+    Ok(pool.install(|| {
+        transmissions
+            .into_par_iter()
+            .map(|transmission| {
+                let chunk = Signal::new(
+                    samples[transmission.range.clone()].to_vec(),
+                    SAMPLE_RATE as u32,
+                );
+                // Заголовок в куске уже найден, искать его по всей длине незачем
+                let params = DecoderParams {
+                    search_window_ms: (MARGIN_MS * 2.0) as u32 + LEADER_MS,
+                    ..decoder.clone()
+                };
+                let outcome = signal::decode(&chunk, &params);
+                ScannedImage {
+                    transmission,
+                    outcome,
+                }
+            })
+            .collect()
+    }))
+}
@@ -0,0 +1,241 @@
+use crate::effect::ImageEffect;
+use crate::messages;
+use crate::palette::Palette;
+use image::{Rgb, RgbImage};
+use serde::{Deserialize, Serialize};
+
+/// Люминофор монитора, которым окрашивается изображение
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Phosphor {
+    /// Цветной кинескоп P22: тёплый белый, каналы ослабляются по отдельности
+    #[default]
+    P22,
+    /// Чёрно-белый P4: голубовато-белый
+    P4,
+    /// Зелёный P1 осциллографов и первых мониторов SSTV
+    P1,
+    /// Янтарный P3
+    P3,
+}
+
+impl Phosphor {
+    pub const ALL: &'static [Phosphor] = &[Phosphor::P22, Phosphor::P4, Phosphor::P1, Phosphor::P3];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Phosphor::P22 => messages::phosphor_p22(),
+            Phosphor::P4 => messages::phosphor_p4(),
+            Phosphor::P1 => messages::phosphor_p1(),
+            Phosphor::P3 => messages::phosphor_p3(),
+        }
+    }
+
+    /// Цвет свечения люминофора при полной яркости, доли 0–1 по каналам
+    pub fn white(&self) -> [f32; 3] {
+        match self {
+            Phosphor::P22 => [1.0, 0.96, 0.86],
+            Phosphor::P4 => [0.92, 0.96, 1.0],
+            Phosphor::P1 => [0.2, 1.0, 0.3],
+            Phosphor::P3 => [1.0, 0.7, 0.1],
+        }
+    }
+
+    /// Монохромный люминофор: показывает только яркость
+    pub fn is_mono(&self) -> bool {
+        *self != Phosphor::P22
+    }
+}
+
+impl clap::ValueEnum for Phosphor {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[Self::P22, Self::P4, Self::P1, Self::P3]
+    }
+
+    fn to_possible_value<'a>(&self) -> Option<clap::builder::PossibleValue> {
+        Some(match self {
+            Self::P22 => clap::builder::PossibleValue::new("p22"),
+            Self::P4 => clap::builder::PossibleValue::new("p4"),
+            Self::P1 => clap::builder::PossibleValue::new("p1"),
+            Self::P3 => clap::builder::PossibleValue::new("p3"),
+        })
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScanConverterParams {
+    /// Вертикальное размытие 0–1: доля, которую строка берёт у соседних,
+    /// как при чересстрочном выводе из памяти преобразователя
+    pub blur: f32,
+    /// Глубина промежутков между строками развёртки 0–1: насколько темнеет каждая
+    /// вторая строка
+    pub scanlines: f32,
+    pub phosphor: Phosphor,
+    /// Сила окраски люминофором 0–1 (0 — без окраски)
+    pub tint: f32,
+}
+
+impl Default for ScanConverterParams {
+    fn default() -> Self {
+        Self {
+            blur: 0.0,
+            scanlines: 0.0,
+            phosphor: Phosphor::P22,
+            tint: 0.0,
+        }
+    }
+}
+
+impl ScanConverterParams {
+    /// Палитра памяти Robot 1200C, с которой используется [`robot_1200c`](Self::robot_1200c)
+    pub const ROBOT_1200C_PALETTE: Palette = Palette::Rgb666;
+
+    /// Вид изображения на мониторе Robot 1200C: мягкие строки с заметными промежутками
+    /// и тёплый белый цветного кинескопа
+    pub fn robot_1200c() -> Self {
+        Self {
+            blur: 0.25,
+            scanlines: 0.25,
+            phosphor: Phosphor::P22,
+            tint: 0.5,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.blur > 0.0 || self.scanlines > 0.0 || self.tint > 0.0
+    }
+
+    /// Эффекты изображения в порядке применения: размытие, промежутки строк, люминофор
+    pub fn effects(&self) -> Vec<Box<dyn ImageEffect>> {
+        vec![
+            Box::new(VerticalBlur {
+                amount: self.blur.clamp(0.0, 1.0),
+            }),
+            Box::new(Scanlines {
+                depth: self.scanlines.clamp(0.0, 1.0),
+            }),
+            Box::new(PhosphorTint {
+                phosphor: self.phosphor,
+                amount: self.tint.clamp(0.0, 1.0),
+            }),
+        ]
+    }
+
+    /// Возвращает описание текущих настроек преобразователя
+    pub fn describe(&self) -> String {
+        let parts: Vec<String> = self
+            .effects()
+            .iter()
+            .filter(|e| e.is_enabled())
+            .map(|e| e.describe())
+            .collect();
+        if parts.is_empty() {
+            return messages::scan_converter_off().to_string();
+        }
+        messages::scan_converter_describe(&parts.join(", "))
+    }
+}
+
+/// Вертикальное размытие: каждая строка смешивается с соседними
+pub struct VerticalBlur {
+    /// Доля соседних строк 0–1, поровну сверху и снизу
+    pub amount: f32,
+}
+
+impl ImageEffect for VerticalBlur {
+    fn name(&self) -> &'static str {
+        messages::vertical_blur()
+    }
+
+    fn apply(&self, image: &mut RgbImage) {
+        let source = image.clone();
+        let last = image.height().saturating_sub(1);
+        let side = self.amount / 2.0;
+        for (x, y, Rgb(pixel)) in image.enumerate_pixels_mut() {
+            let Rgb(above) = source.get_pixel(x, y.saturating_sub(1));
+            let Rgb(below) = source.get_pixel(x, (y + 1).min(last));
+            for c in 0..3 {
+                let v = (1.0 - self.amount) * pixel[c] as f32
+                    + side * (above[c] as f32 + below[c] as f32);
+                pixel[c] = v.round().clamp(0.0, 255.0) as u8;
+            }
+        }
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.amount > 0.0
+    }
+
+    fn describe(&self) -> String {
+        messages::blur_describe(self.amount * 100.0)
+    }
+}
+
+/// Промежутки между строками развёртки: нечётные строки темнее
+pub struct Scanlines {
+    /// Насколько темнеют нечётные строки, 0–1
+    pub depth: f32,
+}
+
+impl ImageEffect for Scanlines {
+    fn name(&self) -> &'static str {
+        messages::scanlines()
+    }
+
+    fn apply(&self, image: &mut RgbImage) {
+        let gain = 1.0 - self.depth;
+        for (_, _, Rgb(pixel)) in image.enumerate_pixels_mut().filter(|(_, y, _)| y % 2 == 1) {
+            *pixel = pixel.map(|v| (v as f32 * gain).round() as u8);
+        }
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.depth > 0.0
+    }
+
+    fn describe(&self) -> String {
+        messages::scanlines_describe(self.depth * 100.0)
+    }
+}
+
+/// Окраска люминофором: цветной ослабляет каналы по его белому, монохромный
+/// показывает яркость своим цветом
+pub struct PhosphorTint {
+    pub phosphor: Phosphor,
+    /// Сила окраски 0–1
+    pub amount: f32,
+}
+
+impl ImageEffect for PhosphorTint {
+    fn name(&self) -> &'static str {
+        messages::phosphor()
+    }
+
+    fn apply(&self, image: &mut RgbImage) {
+        let white = self.phosphor.white();
+        let a = self.amount;
+        for Rgb(pixel) in image.pixels_mut() {
+            let rgb = pixel.map(f32::from);
+            let tinted = if self.phosphor.is_mono() {
+                // Яркость по BT.601
+                let luma = 0.299 * rgb[0] + 0.587 * rgb[1] + 0.114 * rgb[2];
+                white.map(|w| luma * w)
+            } else {
+                [rgb[0] * white[0], rgb[1] * white[1], rgb[2] * white[2]]
+            };
+            for c in 0..3 {
+                let v = (1.0 - a) * rgb[c] + a * tinted[c];
+                pixel[c] = v.round().clamp(0.0, 255.0) as u8;
+            }
+        }
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.amount > 0.0
+    }
+
+    fn describe(&self) -> String {
+        messages::phosphor_describe(self.phosphor.name(), self.amount * 100.0)
+    }
+}
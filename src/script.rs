@@ -0,0 +1,1149 @@
+use crate::app_params::AppParams;
+use crate::blanker::BlankerProcessor;
+use crate::drift::DriftProcessor;
+use crate::dropout::DropoutProcessor;
+use crate::effect::ChannelEffect;
+use crate::error::{Result, SstvError};
+use crate::fading::FadingProcessor;
+use crate::filters::{AgcProcessor, FilterProcessor, NotchProcessor};
+use crate::interference::InterferenceProcessor;
+use crate::messages;
+use crate::metrics;
+use crate::multipath::MultipathProcessor;
+use crate::noise::NoiseProcessor;
+use crate::processor::{ProcessingParams, SSTVProcessor};
+use crate::report::DecodeReport;
+use crate::retarder::RetarderProcessor;
+use crate::sequence::Ramp;
+use crate::timeline::TimelineProcessor;
+use crate::transceiver::TransceiverProcessor;
+use clap::{Args, FromArgMatches};
+use image::ImageReader;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::path::Path;
+
+/// Имена ступеней для `chain([...])` в порядке стандартного конвейера
+pub const EFFECT_NAMES: &[&str] = &[
+    "transmitter",
+    "retarder",
+    "multipath",
+    "fading",
+    "drift",
+    "interference",
+    "noise",
+    "timeline",
+    "blanker",
+    "receiver",
+    "filter",
+    "notch",
+    "agc",
+    "dropout",
+];
+
+/// Значение сценария
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Unit,
+    Bool(bool),
+    Num(f64),
+    Str(String),
+    Array(Vec<Value>),
+    Map(BTreeMap<String, Value>),
+}
+
+impl Value {
+    fn type_name(&self) -> &'static str {
+        match self {
+            Value::Unit => "()",
+            Value::Bool(_) => "bool",
+            Value::Num(_) => messages::script_type_number(),
+            Value::Str(_) => messages::script_type_string(),
+            Value::Array(_) => messages::script_type_array(),
+            Value::Map(_) => messages::script_type_map(),
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Unit => write!(f, "()"),
+            Value::Bool(b) => write!(f, "{}", b),
+            // Целые без «.0»: так числа годятся и как значения флагов вроде `--noise 30`
+            Value::Num(n) if n.fract() == 0.0 && n.abs() < 1e15 => write!(f, "{}", *n as i64),
+            Value::Num(n) => write!(f, "{}", n),
+            Value::Str(s) => write!(f, "{}", s),
+            Value::Array(items) => {
+                let items: Vec<String> = items.iter().map(Value::to_string).collect();
+                write!(f, "[{}]", items.join(", "))
+            }
+            Value::Map(map) => {
+                let items: Vec<String> = map.iter().map(|(k, v)| format!("{}: {}", k, v)).collect();
+                write!(f, "#{{{}}}", items.join(", "))
+            }
+        }
+    }
+}
+
+// ── Лексер ───────────────────────────────────────────────────
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Num(f64),
+    Str(String),
+    Ident(String),
+    Sym(&'static str),
+    Eof,
+}
+
+/// Знаки по убыванию длины, чтобы `..=` не разбирался как `..` и `=`
+const SYMBOLS: &[&str] = &[
+    "..=", "==", "!=", "<=", ">=", "&&", "||", "..", "+=", "-=", "#{", "(", ")", "{", "}", "[",
+    "]", ",", ";", ".", ":", "=", "<", ">", "+", "-", "*", "/", "%", "!",
+];
+
+/// Самый длинный диапазон `a..b` или `range()`: элементы создаются сразу, массивом
+pub const MAX_RANGE_LEN: usize = 1_000_000;
+
+fn error(line: usize, reason: impl fmt::Display) -> SstvError {
+    SstvError::invalid("script", messages::script_error(line, &reason.to_string()))
+}
+
+fn tokenize(source: &str) -> Result<Vec<(Token, usize)>> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let (mut i, mut line) = (0, 1);
+    while i < chars.len() {
+        let c = chars[i];
+        let rest: String = chars[i..chars.len().min(i + 3)].iter().collect();
+        if c == '\n' {
+            line += 1;
+            i += 1;
+        } else if c.is_whitespace() {
+            i += 1;
+        } else if rest.starts_with("//") {
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+        } else if rest.starts_with("/*") {
+            let start = line;
+            i += 2;
+            while i < chars.len() && !(chars[i] == '*' && chars.get(i + 1) == Some(&'/')) {
+                line += usize::from(chars[i] == '\n');
+                i += 1;
+            }
+            if i >= chars.len() {
+                return Err(error(start, messages::script_unclosed_comment()));
+            }
+            i += 2;
+        } else if c.is_ascii_digit() {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '_') {
+                i += 1;
+            }
+            // Точка — дробная часть, только если за ней цифра: `0..10` — диапазон
+            if chars.get(i) == Some(&'.') && chars.get(i + 1).is_some_and(char::is_ascii_digit) {
+                i += 1;
+                while i < chars.len() && chars[i].is_ascii_digit() {
+                    i += 1;
+                }
+            }
+            let text: String = chars[start..i].iter().filter(|&&c| c != '_').collect();
+            let value = text
+                .parse()
+                .map_err(|_| error(line, messages::script_bad_number(&text)))?;
+            tokens.push((Token::Num(value), line));
+        } else if c.is_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            tokens.push((Token::Ident(chars[start..i].iter().collect()), line));
+        } else if c == '"' {
+            let start = line;
+            let mut text = String::new();
+            i += 1;
+            loop {
+                let Some(&c) = chars.get(i) else {
+                    return Err(error(start, messages::script_unclosed_string()));
+                };
+                i += 1;
+                match c {
+                    '"' => break,
+                    '\\' => {
+                        let escaped = chars.get(i).copied().unwrap_or('\\');
+                        i += 1;
+                        text.push(match escaped {
+                            'n' => '\n',
+                            't' => '\t',
+                            other => other,
+                        });
+                    }
+                    '\n' => {
+                        line += 1;
+                        text.push(c);
+                    }
+                    _ => text.push(c),
+                }
+            }
+            tokens.push((Token::Str(text), start));
+        } else if let Some(sym) = SYMBOLS.iter().find(|s| rest.starts_with(**s)) {
+            tokens.push((Token::Sym(sym), line));
+            i += sym.len();
+        } else {
+            return Err(error(line, messages::script_unexpected_char(c)));
+        }
+    }
+    tokens.push((Token::Eof, line));
+    Ok(tokens)
+}
+
+// ── Синтаксическое дерево и разбор ───────────────────────────
+
+#[derive(Clone, Debug)]
+enum Expr {
+    Lit(Value),
+    Var(String),
+    Array(Vec<Expr>),
+    Map(Vec<(String, Expr)>),
+    Unary(&'static str, Box<Expr>),
+    Binary(&'static str, Box<Expr>, Box<Expr>),
+    Range(Box<Expr>, Box<Expr>, bool),
+    Call(String, Vec<Expr>),
+    Field(Box<Expr>, String),
+    Index(Box<Expr>, Box<Expr>),
+}
+
+#[derive(Clone, Debug)]
+enum StmtKind {
+    Let(String, Expr),
+    Assign(String, Expr),
+    Expr(Expr),
+    If(Expr, Vec<Stmt>, Vec<Stmt>),
+    For(String, Expr, Vec<Stmt>),
+    Block(Vec<Stmt>),
+}
+
+#[derive(Clone, Debug)]
+struct Stmt {
+    line: usize,
+    kind: StmtKind,
+}
+
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> &Token {
+        &self.tokens[self.pos].0
+    }
+
+    fn line(&self) -> usize {
+        self.tokens[self.pos].1
+    }
+
+    fn next(&mut self) -> Token {
+        let token = self.tokens[self.pos].0.clone();
+        if self.pos + 1 < self.tokens.len() {
+            self.pos += 1;
+        }
+        token
+    }
+
+    fn is_sym(&self, sym: &str) -> bool {
+        matches!(self.peek(), Token::Sym(s) if *s == sym)
+    }
+
+    fn is_keyword(&self, word: &str) -> bool {
+        matches!(self.peek(), Token::Ident(s) if s == word)
+    }
+
+    fn eat_sym(&mut self, sym: &str) -> bool {
+        let found = self.is_sym(sym);
+        if found {
+            self.next();
+        }
+        found
+    }
+
+    fn expect_sym(&mut self, sym: &str) -> Result<()> {
+        if self.eat_sym(sym) {
+            Ok(())
+        } else {
+            Err(error(
+                self.line(),
+                messages::script_expected_symbol(sym, &describe(self.peek())),
+            ))
+        }
+    }
+
+    fn ident(&mut self) -> Result<String> {
+        match self.next() {
+            Token::Ident(name) => Ok(name),
+            other => Err(error(
+                self.line(),
+                messages::script_expected_name(&describe(&other)),
+            )),
+        }
+    }
+
+    fn program(&mut self) -> Result<Vec<Stmt>> {
+        let mut stmts = Vec::new();
+        while *self.peek() != Token::Eof {
+            stmts.push(self.stmt()?);
+        }
+        Ok(stmts)
+    }
+
+    fn block(&mut self) -> Result<Vec<Stmt>> {
+        self.expect_sym("{")?;
+        let mut stmts = Vec::new();
+        while !self.eat_sym("}") {
+            if *self.peek() == Token::Eof {
+                return Err(error(self.line(), messages::script_unclosed_block()));
+            }
+            stmts.push(self.stmt()?);
+        }
+        Ok(stmts)
+    }
+
+    fn stmt(&mut self) -> Result<Stmt> {
+        let line = self.line();
+        let kind = if self.is_sym("{") {
+            StmtKind::Block(self.block()?)
+        } else if self.is_keyword("let") {
+            self.next();
+            let name = self.ident()?;
+            self.expect_sym("=")?;
+            let value = self.expr()?;
+            self.expect_sym(";")?;
+            StmtKind::Let(name, value)
+        } else if self.is_keyword("if") {
+            self.if_stmt()?
+        } else if self.is_keyword("for") {
+            self.next();
+            let name = self.ident()?;
+            if !self.is_keyword("in") {
+                return Err(error(self.line(), messages::script_expected_in()));
+            }
+            self.next();
+            let iterable = self.expr()?;
+            StmtKind::For(name, iterable, self.block()?)
+        } else {
+            let expr = self.expr()?;
+            let kind = match (&expr, self.peek()) {
+                (Expr::Var(name), Token::Sym(op @ ("=" | "+=" | "-="))) => {
+                    let (name, op) = (name.clone(), *op);
+                    self.next();
+                    let value = self.expr()?;
+                    let value = match op {
+                        "=" => value,
+                        _ => Expr::Binary(
+                            &op[..1],
+                            Box::new(Expr::Var(name.clone())),
+                            Box::new(value),
+                        ),
+                    };
+                    StmtKind::Assign(name, value)
+                }
+                _ => StmtKind::Expr(expr),
+            };
+            self.expect_sym(";")?;
+            kind
+        };
+        Ok(Stmt { line, kind })
+    }
+
+    fn if_stmt(&mut self) -> Result<StmtKind> {
+        self.next();
+        let condition = self.expr()?;
+        let then = self.block()?;
+        let otherwise = if self.is_keyword("else") {
+            self.next();
+            if self.is_keyword("if") {
+                let line = self.line();
+                vec![Stmt {
+                    line,
+                    kind: self.if_stmt()?,
+                }]
+            } else {
+                self.block()?
+            }
+        } else {
+            Vec::new()
+        };
+        Ok(StmtKind::If(condition, then, otherwise))
+    }
+
+    fn expr(&mut self) -> Result<Expr> {
+        self.binary(0)
+    }
+
+    /// Двоичные операции по уровням приоритета, от слабых к сильным
+    fn binary(&mut self, level: usize) -> Result<Expr> {
+        const LEVELS: &[&[&str]] = &[
+            &["||"],
+            &["&&"],
+            &["==", "!=", "<", "<=", ">", ">="],
+            &["..", "..="],
+            &["+", "-"],
+            &["*", "/", "%"],
+        ];
+        if level == LEVELS.len() {
+            return self.unary();
+        }
+        let mut left = self.binary(level + 1)?;
+        while let Token::Sym(op) = *self.peek() {
+            if !LEVELS[level].contains(&op) {
+                break;
+            }
+            self.next();
+            let right = self.binary(level + 1)?;
+            left = match op {
+                ".." | "..=" => Expr::Range(Box::new(left), Box::new(right), op == "..="),
+                _ => Expr::Binary(op, Box::new(left), Box::new(right)),
+            };
+        }
+        Ok(left)
+    }
+
+    fn unary(&mut self) -> Result<Expr> {
+        for op in ["-", "!"] {
+            if self.eat_sym(op) {
+                return Ok(Expr::Unary(op, Box::new(self.unary()?)));
+            }
+        }
+        self.postfix()
+    }
+
+    fn postfix(&mut self) -> Result<Expr> {
+        let mut expr = self.primary()?;
+        loop {
+            if self.eat_sym(".") {
+                let name = self.ident()?;
+                // Вызов через точку `x.len()` — тот же вызов `len(x)`
+                expr = if self.eat_sym("(") {
+                    let mut args = vec![expr];
+                    args.extend(self.list(")")?);
+                    Expr::Call(name, args)
+                } else {
+                    Expr::Field(Box::new(expr), name)
+                };
+            } else if self.eat_sym("[") {
+                let index = self.expr()?;
+                self.expect_sym("]")?;
+                expr = Expr::Index(Box::new(expr), Box::new(index));
+            } else {
+                return Ok(expr);
+            }
+        }
+    }
+
+    /// Выражения через запятую до `close`
+    fn list(&mut self, close: &str) -> Result<Vec<Expr>> {
+        let mut items = Vec::new();
+        while !self.eat_sym(close) {
+            items.push(self.expr()?);
+            if !self.eat_sym(",") {
+                self.expect_sym(close)?;
+                break;
+            }
+        }
+        Ok(items)
+    }
+
+    fn primary(&mut self) -> Result<Expr> {
+        let line = self.line();
+        match self.next() {
+            Token::Num(n) => Ok(Expr::Lit(Value::Num(n))),
+            Token::Str(s) => Ok(Expr::Lit(Value::Str(s))),
+            Token::Ident(name) if name == "true" || name == "false" => {
+                Ok(Expr::Lit(Value::Bool(name == "true")))
+            }
+            Token::Ident(name) => {
+                if self.eat_sym("(") {
+                    Ok(Expr::Call(name, self.list(")")?))
+                } else {
+                    Ok(Expr::Var(name))
+                }
+            }
+            Token::Sym("(") => {
+                let expr = self.expr()?;
+                self.expect_sym(")")?;
+                Ok(expr)
+            }
+            Token::Sym("[") => Ok(Expr::Array(self.list("]")?)),
+            Token::Sym("#{") => {
+                let mut entries = Vec::new();
+                while !self.eat_sym("}") {
+                    let key = match self.next() {
+                        Token::Ident(key) | Token::Str(key) => key,
+                        other => {
+                            return Err(error(
+                                self.line(),
+                                messages::script_expected_key(&describe(&other)),
+                            ));
+                        }
+                    };
+                    self.expect_sym(":")?;
+                    entries.push((key, self.expr()?));
+                    if !self.eat_sym(",") {
+                        self.expect_sym("}")?;
+                        break;
+                    }
+                }
+                Ok(Expr::Map(entries))
+            }
+            other => Err(error(
+                line,
+                messages::script_expected_expression(&describe(&other)),
+            )),
+        }
+    }
+}
+
+fn describe(token: &Token) -> String {
+    match token {
+        Token::Num(n) => messages::script_token_number(*n),
+        Token::Str(s) => messages::script_token_string(s),
+        Token::Ident(name) => messages::script_token_quoted(name),
+        Token::Sym(sym) => messages::script_token_quoted(sym),
+        Token::Eof => messages::script_end_of_file().into(),
+    }
+}
+
+// ── Выполнение ───────────────────────────────────────────────
+
+/// Сценарий обработки: небольшое подмножество языка Rhai без внешних зависимостей.
+/// Переменные (`let`), `if`/`else`, `for x in ...`, диапазоны `a..b` и `a..=b`, массивы,
+/// словари `#{ключ: значение}`, строки и числа. Параметры канала задаются флагами CLI
+/// через `set`, обработку запускают `process` и `decode`, см. README
+pub struct Script {
+    program: Vec<Stmt>,
+}
+
+impl Script {
+    /// Разбирает текст сценария; синтаксические ошибки сообщают номер строки
+    pub fn parse(source: &str) -> Result<Self> {
+        let mut parser = Parser {
+            tokens: tokenize(source)?,
+            pos: 0,
+        };
+        Ok(Self {
+            program: parser.program()?,
+        })
+    }
+
+    /// Читает и разбирает файл сценария
+    pub fn from_file(path: &str) -> Result<Self> {
+        let source = std::fs::read_to_string(path).map_err(|e| {
+            SstvError::io(messages::file_read_failed(messages::file_script(), path), e)
+        })?;
+        Self::parse(&source)
+    }
+
+    /// Выполняет сценарий; вывод `print` уходит в `print`
+    pub fn run(&self, print: impl FnMut(&str)) -> Result<()> {
+        let mut interpreter = Interpreter {
+            scopes: vec![HashMap::new()],
+            flags: Vec::new(),
+            chain: None,
+            print: Box::new(print),
+        };
+        interpreter.block(&self.program)
+    }
+}
+
+struct Interpreter<'a> {
+    scopes: Vec<HashMap<String, Value>>,
+    /// Флаги CLI, заданные `set`, по порядку: имя и значение (`None` — флаг без значения)
+    flags: Vec<(String, Option<String>)>,
+    /// Явный конвейер `chain([...])`; `None` — стандартный
+    chain: Option<Vec<String>>,
+    print: Box<dyn FnMut(&str) + 'a>,
+}
+
+impl Interpreter<'_> {
+    fn block(&mut self, stmts: &[Stmt]) -> Result<()> {
+        self.scopes.push(HashMap::new());
+        let result = stmts.iter().try_for_each(|stmt| self.stmt(stmt));
+        self.scopes.pop();
+        result
+    }
+
+    fn stmt(&mut self, stmt: &Stmt) -> Result<()> {
+        let line = stmt.line;
+        match &stmt.kind {
+            StmtKind::Let(name, expr) => {
+                let value = self.eval(expr, line)?;
+                if let Some(scope) = self.scopes.last_mut() {
+                    scope.insert(name.clone(), value);
+                }
+            }
+            StmtKind::Assign(name, expr) => {
+                let value = self.eval(expr, line)?;
+                let Some(slot) = self.scopes.iter_mut().rev().find_map(|s| s.get_mut(name)) else {
+                    return Err(error(line, messages::script_undeclared(name)));
+                };
+                *slot = value;
+            }
+            StmtKind::Expr(expr) => {
+                self.eval(expr, line)?;
+            }
+            StmtKind::If(condition, then, otherwise) => match self.eval(condition, line)? {
+                Value::Bool(true) => self.block(then)?,
+                Value::Bool(false) => self.block(otherwise)?,
+                other => {
+                    return Err(error(
+                        line,
+                        messages::script_condition_type(other.type_name()),
+                    ));
+                }
+            },
+            StmtKind::For(name, iterable, body) => {
+                let items = match self.eval(iterable, line)? {
+                    Value::Array(items) => items,
+                    Value::Map(map) => map.into_keys().map(Value::Str).collect(),
+                    other => {
+                        return Err(error(line, messages::script_for_type(other.type_name())));
+                    }
+                };
+                for item in items {
+                    self.scopes.push(HashMap::from([(name.clone(), item)]));
+                    let result = self.block(body);
+                    self.scopes.pop();
+                    result?;
+                }
+            }
+            StmtKind::Block(stmts) => self.block(stmts)?,
+        }
+        Ok(())
+    }
+
+    fn eval(&mut self, expr: &Expr, line: usize) -> Result<Value> {
+        Ok(match expr {
+            Expr::Lit(value) => value.clone(),
+            Expr::Var(name) => self
+                .scopes
+                .iter()
+                .rev()
+                .find_map(|s| s.get(name))
+                .cloned()
+                .ok_or_else(|| error(line, messages::script_undeclared(name)))?,
+            Expr::Array(items) => Value::Array(
+                items
+                    .iter()
+                    .map(|item| self.eval(item, line))
+                    .collect::<Result<_>>()?,
+            ),
+            Expr::Map(entries) => Value::Map(
+                entries
+                    .iter()
+                    .map(|(key, value)| Ok((key.clone(), self.eval(value, line)?)))
+                    .collect::<Result<_>>()?,
+            ),
+            Expr::Unary(op, operand) => match (*op, self.eval(operand, line)?) {
+                ("-", Value::Num(n)) => Value::Num(-n),
+                ("!", Value::Bool(b)) => Value::Bool(!b),
+                (op, value) => {
+                    return Err(error(
+                        line,
+                        messages::script_unary_type(op, value.type_name()),
+                    ));
+                }
+            },
+            Expr::Binary("&&", left, right) => {
+                Value::Bool(self.truth(left, line)? && self.truth(right, line)?)
+            }
+            Expr::Binary("||", left, right) => {
+                Value::Bool(self.truth(left, line)? || self.truth(right, line)?)
+            }
+            Expr::Binary(op, left, right) => {
+                let (left, right) = (self.eval(left, line)?, self.eval(right, line)?);
+                binary(op, left, right).map_err(|reason| error(line, reason))?
+            }
+            Expr::Range(start, end, inclusive) => {
+                let start = number(&self.eval(start, line)?, line)?;
+                let end = number(&self.eval(end, line)?, line)?;
+                let count = range_len(end - start + if *inclusive { 1.0 } else { 0.0 })
+                    .map_err(|reason| error(line, reason))?;
+                Value::Array((0..count).map(|k| Value::Num(start + k as f64)).collect())
+            }
+            Expr::Call(name, args) => {
+                let args = args
+                    .iter()
+                    .map(|arg| self.eval(arg, line))
+                    .collect::<Result<Vec<_>>>()?;
+                self.call(name, args)
+                    .map_err(|e| error(line, format!("{}(): {}", name, e)))?
+            }
+            Expr::Field(target, field) => match self.eval(target, line)? {
+                Value::Map(map) => map
+                    .get(field)
+                    .cloned()
+                    .ok_or_else(|| error(line, messages::script_missing_key(field)))?,
+                other => {
+                    return Err(error(
+                        line,
+                        messages::script_no_field(other.type_name(), field),
+                    ));
+                }
+            },
+            Expr::Index(target, index) => {
+                let (target, index) = (self.eval(target, line)?, self.eval(index, line)?);
+                match (target, index) {
+                    (Value::Array(items), Value::Num(i)) => items
+                        .get(i as usize)
+                        .filter(|_| i >= 0.0)
+                        .cloned()
+                        .ok_or_else(|| error(line, messages::script_index_outside(i)))?,
+                    (Value::Map(map), Value::Str(key)) => map
+                        .get(&key)
+                        .cloned()
+                        .ok_or_else(|| error(line, messages::script_missing_key(&key)))?,
+                    (target, index) => {
+                        return Err(error(
+                            line,
+                            messages::script_index_type(target.type_name(), index.type_name()),
+                        ));
+                    }
+                }
+            }
+        })
+    }
+
+    fn truth(&mut self, expr: &Expr, line: usize) -> Result<bool> {
+        match self.eval(expr, line)? {
+            Value::Bool(b) => Ok(b),
+            other => Err(error(
+                line,
+                messages::script_expected_bool(other.type_name()),
+            )),
+        }
+    }
+
+    /// Встроенные функции; ошибка — текст без номера строки
+    fn call(&mut self, name: &str, args: Vec<Value>) -> std::result::Result<Value, String> {
+        let arity = |n: usize| {
+            if args.len() == n {
+                Ok(())
+            } else {
+                Err(messages::script_arity(n, args.len()))
+            }
+        };
+        let num = |i: usize| match args.get(i) {
+            Some(Value::Num(n)) => Ok(*n),
+            other => Err(messages::script_argument_number(
+                i + 1,
+                other.map_or("()", Value::type_name),
+            )),
+        };
+        let text = |i: usize| match args.get(i) {
+            Some(Value::Str(s)) => Ok(s.clone()),
+            other => Err(messages::script_argument_string(
+                i + 1,
+                other.map_or("()", Value::type_name),
+            )),
+        };
+
+        Ok(match name {
+            "print" => {
+                let line: Vec<String> = args.iter().map(Value::to_string).collect();
+                (self.print)(&line.join(" "));
+                Value::Unit
+            }
+            "to_string" => {
+                arity(1)?;
+                Value::Str(args[0].to_string())
+            }
+            "len" => {
+                arity(1)?;
+                Value::Num(match &args[0] {
+                    Value::Array(items) => items.len(),
+                    Value::Map(map) => map.len(),
+                    Value::Str(s) => s.chars().count(),
+                    other => return Err(messages::script_no_length(other.type_name())),
+                } as f64)
+            }
+            "round" => {
+                arity(1)?;
+                Value::Num(num(0)?.round())
+            }
+            "min" | "max" => {
+                arity(2)?;
+                let (a, b) = (num(0)?, num(1)?);
+                Value::Num(if name == "min" { a.min(b) } else { a.max(b) })
+            }
+            "range" => {
+                let (start, end) = (num(0)?, num(1)?);
+                let step = if args.len() > 2 { num(2)? } else { 1.0 };
+                if step <= 0.0 {
+                    return Err(messages::script_step_not_positive().into());
+                }
+                let count = range_len((end - start) / step)?;
+                Value::Array(
+                    (0..count)
+                        .map(|k| Value::Num(start + k as f64 * step))
+                        .collect(),
+                )
+            }
+            "ramp" => {
+                arity(4)?;
+                let ramp = Ramp {
+                    start: num(0)? as f32,
+                    end: num(1)? as f32,
+                };
+                Value::Num(ramp.value_at(num(2)? as usize, num(3)? as usize) as f64)
+            }
+            "set" => {
+                arity(2)?;
+                let flag = flag_name(&text(0)?);
+                self.flags.retain(|(name, _)| *name != flag);
+                match &args[1] {
+                    Value::Bool(false) | Value::Unit => {}
+                    Value::Bool(true) => self.flags.push((flag, None)),
+                    Value::Array(items) => {
+                        let items: Vec<String> = items.iter().map(Value::to_string).collect();
+                        self.flags.push((flag, Some(items.join(","))));
+                    }
+                    value => self.flags.push((flag, Some(value.to_string()))),
+                }
+                Value::Unit
+            }
+            "unset" => {
+                arity(1)?;
+                let flag = flag_name(&text(0)?);
+                self.flags.retain(|(name, _)| *name != flag);
+                Value::Unit
+            }
+            "reset" => {
+                arity(0)?;
+                self.flags.clear();
+                self.chain = None;
+                Value::Unit
+            }
+            "chain" => {
+                self.chain = match args.first() {
+                    None => None,
+                    Some(Value::Array(items)) if items.is_empty() => None,
+                    Some(Value::Array(items)) => Some(
+                        items
+                            .iter()
+                            .map(|item| match item {
+                                Value::Str(name) if EFFECT_NAMES.contains(&name.as_str()) => {
+                                    Ok(name.clone())
+                                }
+                                other => Err(messages::script_unknown_stage(
+                                    &other.to_string(),
+                                    &EFFECT_NAMES.join(", "),
+                                )),
+                            })
+                            .collect::<std::result::Result<_, _>>()?,
+                    ),
+                    Some(other) => {
+                        return Err(messages::script_expected_stages(other.type_name()));
+                    }
+                };
+                Value::Unit
+            }
+            "files" => {
+                arity(1)?;
+                let dir = text(0)?;
+                let entries = std::fs::read_dir(&dir)
+                    .map_err(|e| format!("{}: {}", messages::read_dir_failed(&dir), e))?;
+                let mut files: Vec<String> = entries
+                    .filter_map(|entry| entry.ok().map(|e| e.path()))
+                    .filter(|path| path.is_file())
+                    .map(|path| path.to_string_lossy().into_owned())
+                    .collect();
+                files.sort();
+                Value::Array(files.into_iter().map(Value::Str).collect())
+            }
+            "process" => {
+                arity(2)?;
+                self.process(&text(0)?, &text(1)?)
+                    .map_err(|e| format!("{:#}", e))?
+            }
+            "decode" => {
+                arity(2)?;
+                self.decode(&text(0)?, &text(1)?)
+                    .map_err(|e| format!("{:#}", e))?
+            }
+            _ => return Err(messages::script_unknown_function().into()),
+        })
+    }
+
+    /// Параметры из флагов `set`, разобранных так же, как в командной строке
+    fn app_params(&self) -> Result<AppParams> {
+        let mut args = vec!["script".to_string()];
+        for (name, value) in &self.flags {
+            args.push(format!("--{}", name));
+            args.extend(value.clone());
+        }
+        let matches = AppParams::augment_args(clap::Command::new("script"))
+            .try_get_matches_from(args)
+            .map_err(|e| SstvError::invalid("script", e.to_string().trim().to_string()))?;
+        let mut params = AppParams::from_arg_matches(&matches)
+            .map_err(|e| SstvError::invalid("script", e.to_string().trim().to_string()))?;
+        params.apply_preset(&matches)?;
+        Ok(params)
+    }
+
+    /// Процессор по флагам `set` и конвейеру `chain`
+    fn processor(&self, app: &AppParams) -> Result<SSTVProcessor> {
+        let params = app.to_params()?;
+        let mut processor = app.processor(params.clone())?;
+        if let Some(chain) = &self.chain {
+            *processor.effects_mut() = chain.iter().map(|name| effect(name, &params)).collect();
+        }
+        Ok(processor)
+    }
+
+    fn process(&mut self, input: &str, output: &str) -> Result<Value> {
+        let app = self.app_params()?;
+        let main_image = ImageReader::open(input)
+            .map_err(|e| SstvError::io(messages::open_failed(input), e))?
+            .decode()
+            .map_err(|e| SstvError::io(messages::decode_failed(input), e))?;
+        let retarder_image = app.read_retarder()?;
+        let mut processor = self.processor(&app)?;
+
+        let samples = processor.render_samples(&main_image, retarder_image.as_ref())?;
+        let report = processor.decode_samples(&samples)?;
+        processor.save_result(&report.image, output)?;
+
+        let sent = processor.params().overlay_params().apply(&main_image)?;
+        let quality = metrics::compare_in(&sent, &report.image, processor.params().metric_space);
+        let mut map = report_map(&report);
+        map.insert("psnr".into(), Value::Num(quality.psnr));
+        map.insert("ssim".into(), Value::Num(quality.ssim));
+        Ok(Value::Map(map))
+    }
+
+    fn decode(&mut self, input: &str, output: &str) -> Result<Value> {
+        let app = self.app_params()?;
+        let mut processor = self.processor(&app)?;
+        let report = processor.decode_wav(input)?;
+        processor.save_result(&report.image, output)?;
+        Ok(Value::Map(report_map(&report)))
+    }
+}
+
+/// Имя флага без `--`, с дефисами вместо подчёркиваний
+fn flag_name(name: &str) -> String {
+    name.trim_start_matches('-').replace('_', "-")
+}
+
+fn number(value: &Value, line: usize) -> Result<f64> {
+    match value {
+        Value::Num(n) => Ok(*n),
+        other => Err(error(
+            line,
+            messages::script_expected_number(other.type_name()),
+        )),
+    }
+}
+
+/// Число элементов диапазона длиной `span`; ошибка, если их больше [`MAX_RANGE_LEN`]
+fn range_len(span: f64) -> std::result::Result<usize, String> {
+    let count = span.ceil().max(0.0);
+    if count > MAX_RANGE_LEN as f64 {
+        return Err(messages::script_range_too_long(MAX_RANGE_LEN));
+    }
+    Ok(count as usize)
+}
+
+fn binary(op: &str, left: Value, right: Value) -> std::result::Result<Value, String> {
+    use Value::{Array, Bool, Num, Str};
+    Ok(match (op, left, right) {
+        ("==", a, b) => Bool(a == b),
+        ("!=", a, b) => Bool(a != b),
+        ("+", Num(a), Num(b)) => Num(a + b),
+        ("+", Array(mut a), Array(b)) => {
+            a.extend(b);
+            Array(a)
+        }
+        ("+", Str(a), b) => Str(format!("{}{}", a, b)),
+        ("+", a, Str(b)) => Str(format!("{}{}", a, b)),
+        ("-", Num(a), Num(b)) => Num(a - b),
+        ("*", Num(a), Num(b)) => Num(a * b),
+        ("/", Num(a), Num(b)) => Num(a / b),
+        ("%", Num(a), Num(b)) => Num(a % b),
+        ("<", Num(a), Num(b)) => Bool(a < b),
+        ("<=", Num(a), Num(b)) => Bool(a <= b),
+        (">", Num(a), Num(b)) => Bool(a > b),
+        (">=", Num(a), Num(b)) => Bool(a >= b),
+        ("<", Str(a), Str(b)) => Bool(a < b),
+        ("<=", Str(a), Str(b)) => Bool(a <= b),
+        (">", Str(a), Str(b)) => Bool(a > b),
+        (">=", Str(a), Str(b)) => Bool(a >= b),
+        (op, a, b) => {
+            return Err(messages::script_binary_type(
+                op,
+                a.type_name(),
+                b.type_name(),
+            ));
+        }
+    })
+}
+
+/// Ступень конвейера по имени из [`EFFECT_NAMES`]
+fn effect(name: &str, params: &ProcessingParams) -> Box<dyn ChannelEffect> {
+    match name {
+        "transmitter" => Box::new(TransceiverProcessor::transmitter(
+            params.transceiver.clone(),
+        )),
+        "retarder" => Box::new(RetarderProcessor::new_with_params(params.retarder.clone())),
+        "multipath" => Box::new(MultipathProcessor::new_with_params(
+            params.multipath.clone(),
+        )),
+        "fading" => Box::new(FadingProcessor::new_with_params(params.fading.clone())),
+        "drift" => Box::new(DriftProcessor::new_with_params(params.drift.clone())),
+        "interference" => Box::new(InterferenceProcessor::new_with_params(
+            params.interference.clone(),
+        )),
+        "noise" => Box::new(NoiseProcessor::new_with_params(params.noise.clone())),
+        "timeline" => Box::new(TimelineProcessor::new_with_params(params.events.clone())),
+        "blanker" => Box::new(BlankerProcessor::new_with_params(params.blanker.clone())),
+        "receiver" => Box::new(TransceiverProcessor::receiver(params.transceiver.clone())),
+        "filter" => Box::new(FilterProcessor::new_with_params(params.filter.clone())),
+        "notch" => Box::new(NotchProcessor::new_with_params(params.notch.clone())),
+        "agc" => Box::new(AgcProcessor::new_with_params(params.agc.clone())),
+        _ => Box::new(DropoutProcessor::new_with_params(params.dropout.clone())),
+    }
+}
+
+/// Поля отчёта декодирования, доступные сценарию
+fn report_map(report: &DecodeReport) -> BTreeMap<String, Value> {
+    let optional = |value: Option<f64>| value.map_or(Value::Unit, Value::Num);
+    BTreeMap::from([
+        ("lines".into(), Value::Num(report.lines_decoded as f64)),
+        ("partial".into(), Value::Bool(report.partial)),
+        (
+            "sync".into(),
+            optional(report.sync_lock_ratio.map(f64::from)),
+        ),
+        ("snr".into(), optional(report.snr_estimate.map(f64::from))),
+        ("slant_ppm".into(), optional(report.slant_estimate)),
+    ])
+}
+
+/// Выполняет файл сценария, печатая вывод `print` в stdout
+pub fn run_file(path: &Path) -> Result<()> {
+    Script::from_file(&path.to_string_lossy())?.run(|line| println!("{}", line))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Выполняет сценарий и возвращает строки `print`
+    fn run(source: &str) -> Result<Vec<String>> {
+        let mut lines = Vec::new();
+        Script::parse(source)?.run(|line| lines.push(line.to_string()))?;
+        Ok(lines)
+    }
+
+    /// Причина ошибки сценария вместе с номером строки
+    fn reason(error: SstvError) -> String {
+        match error {
+            SstvError::InvalidParams {
+                field: "script",
+                reason,
+            } => reason,
+            other => panic!("не ошибка сценария: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn parse_reports_line_of_syntax_error() {
+        assert!(Script::parse("let a = 1;\n// комментарий\nprint(a);").is_ok());
+        let error = Script::parse("let a = 1;\n\nlet b = ;").err().unwrap();
+        let found = messages::script_token_quoted(";");
+        assert_eq!(
+            reason(error),
+            messages::script_error(3, &messages::script_expected_expression(&found))
+        );
+        let error = Script::parse("/* без конца").err().unwrap();
+        assert_eq!(
+            reason(error),
+            messages::script_error(1, messages::script_unclosed_comment())
+        );
+        assert!(Script::parse("let s = \"без кавычки;").is_err());
+        assert!(Script::parse("for x [1] {}").is_err());
+    }
+
+    #[test]
+    fn arithmetic_follows_precedence() {
+        let lines = run(r#"
+            print(1 + 2 * 3, (1 + 2) * 3, 7 % 4, -2 * 3, 10 / 4);
+            print("n=" + 5, 1 < 2 && !(2 < 1), 3 == 3.0);
+            let x = 1;
+            x += 4;
+            x -= 2;
+            print(x, min(x, 2), max(x, 2), round(2.6), len([1, 2, 3]), len("абв"));
+        "#)
+        .unwrap();
+        assert_eq!(lines, ["7 9 3 -6 2.5", "n=5 true true", "3 2 3 3 3 3"]);
+        assert!(run("print(1 + true);").is_err());
+        assert!(run("print(-\"a\");").is_err());
+    }
+
+    #[test]
+    fn loops_cover_ranges_arrays_and_maps() {
+        let lines = run(r#"
+            let sum = 0;
+            for k in 0..5 { sum += k; }
+            for k in 1..=3 { sum += k; }
+            for v in [10, 20] { sum += v; }
+            let keys = "";
+            for key in #{b: 2, a: 1} { keys += key; }
+            print(sum, keys, range(0, 1, 0.25), 3..1);
+        "#)
+        .unwrap();
+        assert_eq!(lines, ["46 ab [0, 0.25, 0.5, 0.75] []"]);
+        assert!(run("for k in 5 {}").is_err());
+        assert!(run("if 1 { print(1); }").is_err());
+    }
+
+    #[test]
+    fn index_errors_name_the_line() {
+        let lines = run("let a = [1, 2]; let m = #{k: 3}; print(a[1], m[\"k\"], m.k);").unwrap();
+        assert_eq!(lines, ["2 3 3"]);
+
+        let error = run("let a = [1, 2];\nprint(a[2]);").err().unwrap();
+        assert_eq!(
+            reason(error),
+            messages::script_error(2, &messages::script_index_outside(2.0))
+        );
+        let error = run("let a = [1, 2];\nprint(a[-1]);").err().unwrap();
+        assert_eq!(
+            reason(error),
+            messages::script_error(2, &messages::script_index_outside(-1.0))
+        );
+        let error = run("let m = #{k: 3};\n\nprint(m[\"x\"]);").err().unwrap();
+        assert_eq!(
+            reason(error),
+            messages::script_error(3, &messages::script_missing_key("x"))
+        );
+        assert!(run("let a = [1]; print(a[\"k\"]);").is_err());
+        assert!(run("print(undeclared);").is_err());
+    }
+
+    #[test]
+    fn long_range_is_an_error_not_an_allocation() {
+        let too_long = messages::script_range_too_long(MAX_RANGE_LEN);
+        let error = run("let a = 0..100000000000;").err().unwrap();
+        assert_eq!(reason(error), messages::script_error(1, &too_long));
+        let error = run("let a = range(0, 1, 0.0000001);").err().unwrap();
+        assert_eq!(
+            reason(error),
+            messages::script_error(1, &format!("range(): {too_long}"))
+        );
+        let lines = run(&format!("print(len(0..{MAX_RANGE_LEN}));")).unwrap();
+        assert_eq!(lines, [MAX_RANGE_LEN.to_string()]);
+    }
+}
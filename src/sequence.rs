@@ -0,0 +1,233 @@
+use crate::error::{Result, SstvError};
+use crate::messages;
+use crate::processor::{ProcessingParams, SSTVProcessor};
+use image::codecs::gif::{GifDecoder, GifEncoder, Repeat};
+use image::{AnimationDecoder, Delay, DynamicImage, ImageFormat, ImageReader};
+use rayon::prelude::*;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Длительность кадра, если вход не задаёт своей (каталог кадров, одиночная картинка), мс
+pub const DEFAULT_FRAME_MS: u32 = 500;
+/// Расширения файлов, которые берутся из каталога кадров
+const FRAME_EXTENSIONS: &[&str] = &[
+    "png", "jpg", "jpeg", "bmp", "gif", "tif", "tiff", "webp", "ppm", "pgm", "pnm",
+];
+
+/// Кадр последовательности
+#[derive(Clone, Debug)]
+pub struct Frame {
+    pub image: DynamicImage,
+    /// Сколько кадр показывается, мс
+    pub delay_ms: u32,
+}
+
+/// Плавное изменение параметра по последовательности: `start` на первом кадре, `end`
+/// на последнем, между ними — линейно. Конец может быть меньше начала
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Ramp {
+    pub start: f32,
+    pub end: f32,
+}
+
+impl Ramp {
+    /// Значение на кадре `index` из `count`
+    pub fn value_at(&self, index: usize, count: usize) -> f32 {
+        if count <= 1 {
+            return self.start;
+        }
+        let t = index as f32 / (count - 1) as f32;
+        self.start + (self.end - self.start) * t
+    }
+}
+
+impl FromStr for Ramp {
+    type Err = SstvError;
+
+    /// Разбирает изменение в формате `начало..конец` или одно постоянное значение
+    fn from_str(s: &str) -> Result<Self> {
+        let parse = |v: &str| -> Result<f32> {
+            v.trim()
+                .parse()
+                .map_err(|_| SstvError::invalid("ramp", messages::ramp_bad_number(v)))
+        };
+        match s.split_once("..") {
+            Some((start, end)) => Ok(Self {
+                start: parse(start)?,
+                end: parse(end)?,
+            }),
+            None => {
+                let value = parse(s)?;
+                Ok(Self {
+                    start: value,
+                    end: value,
+                })
+            }
+        }
+    }
+}
+
+/// Параметры, меняющиеся от кадра к кадру; не заданный берётся из базовых параметров
+#[derive(Clone, Debug, Default)]
+pub struct SequenceRamps {
+    /// Уровень шума 0–100
+    pub noise: Option<Ramp>,
+    /// Уровень ретардера 0–1
+    pub retarder_level: Option<Ramp>,
+    /// Задержка ретардера, мс
+    pub delay_ms: Option<Ramp>,
+}
+
+/// Читает кадры: все изображения каталога по имени, кадры анимированного GIF с их
+/// длительностями или одну картинку. Кадрам без своей длительности ставится `frame_ms`
+pub fn read_frames(path: &Path, frame_ms: u32) -> Result<Vec<Frame>> {
+    let read = |path: &Path| -> Result<DynamicImage> {
+        ImageReader::open(path)
+            .map_err(|e| SstvError::io(messages::open_failed(&path.display().to_string()), e))?
+            .decode()
+            .map_err(|e| SstvError::io(messages::decode_failed(&path.display().to_string()), e))
+    };
+    let frame = |image| Frame {
+        image,
+        delay_ms: frame_ms,
+    };
+
+    if path.is_dir() {
+        let entries = std::fs::read_dir(path).map_err(|e| {
+            SstvError::io(messages::read_dir_failed(&path.display().to_string()), e)
+        })?;
+        let mut files: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|p| {
+                p.is_file()
+                    && p.extension().and_then(|e| e.to_str()).is_some_and(|e| {
+                        FRAME_EXTENSIONS.contains(&e.to_ascii_lowercase().as_str())
+                    })
+            })
+            .collect();
+        files.sort();
+        return files.iter().map(|p| read(p).map(frame)).collect();
+    }
+
+    if ImageFormat::from_path(path).ok() != Some(ImageFormat::Gif) {
+        return Ok(vec![frame(read(path)?)]);
+    }
+    let file = File::open(path)
+        .map_err(|e| SstvError::io(messages::open_failed(&path.display().to_string()), e))?;
+    let decoder = GifDecoder::new(BufReader::new(file))
+        .map_err(|e| SstvError::io(messages::decode_failed(&path.display().to_string()), e))?;
+    decoder
+        .into_frames()
+        .map(|gif_frame| {
+            let gif_frame = gif_frame.map_err(|e| {
+                SstvError::io(messages::decode_failed(&path.display().to_string()), e)
+            })?;
+            let (numer, denom) = gif_frame.delay().numer_denom_ms();
+            let delay_ms = numer / denom.max(1);
+            Ok(Frame {
+                delay_ms: if delay_ms == 0 { frame_ms } else { delay_ms },
+                image: DynamicImage::ImageRgba8(gif_frame.into_buffer()),
+            })
+        })
+        .collect()
+}
+
+/// Пишет кадры анимированным GIF (бесконечный повтор), если у `output` расширение
+/// `.gif`, иначе в каталог `output` файлами `frame_0001.png`, `frame_0002.png`, …
+pub fn write_frames(output: &Path, frames: &[Frame]) -> Result<()> {
+    if ImageFormat::from_path(output).ok() == Some(ImageFormat::Gif) {
+        let file = File::create(output).map_err(|e| {
+            SstvError::io(messages::create_failed(&output.display().to_string()), e)
+        })?;
+        let mut encoder = GifEncoder::new_with_speed(file, 10);
+        let write_error =
+            |e| SstvError::io(messages::gif_write_failed(&output.display().to_string()), e);
+        encoder.set_repeat(Repeat::Infinite).map_err(write_error)?;
+        encoder
+            .encode_frames(frames.iter().map(|frame| {
+                image::Frame::from_parts(
+                    frame.image.to_rgba8(),
+                    0,
+                    0,
+                    Delay::from_numer_denom_ms(frame.delay_ms, 1),
+                )
+            }))
+            .map_err(write_error)
+    } else {
+        std::fs::create_dir_all(output).map_err(|e| {
+            SstvError::io(
+                messages::create_dir_failed(&output.display().to_string()),
+                e,
+            )
+        })?;
+        for (index, frame) in frames.iter().enumerate() {
+            let path = output.join(format!("frame_{:04}.png", index + 1));
+            frame
+                .image
+                .save_with_format(&path, ImageFormat::Png)
+                .map_err(|e| {
+                    SstvError::io(messages::frame_save_failed(&path.display().to_string()), e)
+                })?;
+        }
+        Ok(())
+    }
+}
+
+/// Прогон последовательности: каждый кадр проходит канал со своими параметрами,
+/// см. [`params_at`](Self::params_at)
+pub struct SequenceRunner {
+    /// Параметры, общие для всех кадров
+    pub base: ProcessingParams,
+    pub ramps: SequenceRamps,
+}
+
+impl SequenceRunner {
+    pub fn new(base: ProcessingParams, ramps: SequenceRamps) -> Self {
+        Self { base, ramps }
+    }
+
+    /// Параметры кадра `index` из `count`. При заданном зерне кадр `k` получает зерно
+    /// `seed + k`: шум на кадрах разный, а вся последовательность воспроизводима
+    pub fn params_at(&self, index: usize, count: usize) -> ProcessingParams {
+        let mut params = self.base.clone();
+        if let Some(ramp) = self.ramps.noise {
+            params.noise.level = ramp.value_at(index, count).round().clamp(0.0, 100.0) as u8;
+        }
+        if let Some(ramp) = self.ramps.retarder_level {
+            params.retarder.level = ramp.value_at(index, count).clamp(0.0, 1.0);
+        }
+        if let Some(ramp) = self.ramps.delay_ms {
+            params.retarder.delay_ms = ramp.value_at(index, count).round().max(0.0) as u32;
+        }
+        params.seed = self.base.seed.map(|seed| seed.wrapping_add(index as u64));
+        params
+    }
+
+    /// Обрабатывает кадры параллельно и возвращает искажённые в том же порядке и с теми
+    /// же длительностями. `on_done(index)` вызывается после каждого кадра (из рабочих
+    /// потоков)
+    pub fn run(
+        &self,
+        frames: &[Frame],
+        retarder_image: Option<&DynamicImage>,
+        on_done: impl Fn(usize) + Sync,
+    ) -> Result<Vec<Frame>> {
+        frames
+            .par_iter()
+            .enumerate()
+            .map(|(index, frame)| {
+                let params = self.params_at(index, frames.len());
+                let mut processor = SSTVProcessor::builder().params(params).build()?;
+                let samples = processor.render_samples(&frame.image, retarder_image)?;
+                let image = processor.decode_samples(&samples)?.image;
+                on_done(index);
+                Ok(Frame {
+                    image,
+                    delay_ms: frame.delay_ms,
+                })
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,974 @@
+use crate::error::{Result, SstvError};
+use crate::messages;
+use crate::metrics::{self, QualityReport};
+use crate::processor::{ProcessingParams, SSTVProcessor};
+use crate::retarder::RetarderSource;
+use image::{DynamicImage, ImageFormat, ImageReader};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::borrow::Cow;
+use std::collections::VecDeque;
+use std::io::{BufRead, BufReader, Cursor, ErrorKind, Read, Write};
+use std::net::TcpListener;
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+/// Коды ошибок JSON-RPC 2.0
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+/// Ошибка обработки задания: текст — описание [`SstvError`]
+const JOB_FAILED: i64 = -32000;
+/// Очередь заданий полна, как HTTP 429: повторить позже
+pub const SERVER_BUSY: i64 = -32001;
+/// Запрос или изображение задания больше пределов сервера, как HTTP 413
+pub const JOB_TOO_LARGE: i64 = -32002;
+/// Как часто проверять `shutdown`, пока нет соединений
+const ACCEPT_POLL: Duration = Duration::from_millis(50);
+
+/// Задание `process`: изображение файлом или в base64 и параметры обработки. Пути —
+/// на машине сервера; как они разрешаются, см. [`Server::with_root`]
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServeJob {
+    /// Путь к изображению на машине сервера
+    pub image: Option<String>,
+    /// Само изображение (PNG, JPEG...) в base64, если файла у сервера нет
+    pub image_base64: Option<String>,
+    /// Картинка-призрак ретардера: путь или base64
+    pub retarder: Option<String>,
+    pub retarder_base64: Option<String>,
+    /// Параметры задания; пропущенные поля — по умолчанию. Без них — параметры сервера
+    pub params: Option<ProcessingParams>,
+    /// Куда сохранить результат на машине сервера
+    pub output: Option<String>,
+    /// Вернуть результат в ответе PNG в base64
+    pub return_image: bool,
+}
+
+/// Ответ на задание `process`
+#[derive(Clone, Debug, Serialize)]
+pub struct ServeResult {
+    pub lines_decoded: u32,
+    pub partial: bool,
+    pub snr_estimate: Option<f32>,
+    /// Сравнение результата с отправленным изображением; бесконечный PSNR — `null`
+    pub psnr: f64,
+    pub ssim: f64,
+    pub mse: f64,
+    /// Сигнал кодера взят из кэша: изображение и кодер те же, что у прошлого задания
+    pub encode_cache_hit: bool,
+    /// Время задания, мс
+    pub elapsed_ms: f64,
+    /// Отчёт декодера одной строкой, как в CLI
+    pub report: String,
+    pub output: Option<String>,
+    pub image_base64: Option<String>,
+}
+
+/// Пределы сервера: очередь заданий, размер одного задания и его сигнала. Сколько заданий идёт
+/// одновременно, задаёт число процессоров в [`Server::with_limits`]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ServeLimits {
+    /// Сколько заданий может ждать свободный процессор; следующее получает отказ
+    /// [`SERVER_BUSY`]
+    pub max_queue: usize,
+    /// Наибольшее изображение задания, пикселей: больше — отказ [`JOB_TOO_LARGE`]
+    pub max_pixels: u64,
+    /// Наибольшая строка запроса, байт: с base64 изображения она занимает больше всего
+    pub max_request_bytes: usize,
+    /// Наибольшая частота дискретизации задания, Гц: больше — отказ [`JOB_TOO_LARGE`]
+    pub max_sample_rate: u32,
+    /// Наибольшая длина передачи задания, сэмплов: под неё эффекты выделяют буферы
+    pub max_signal_samples: u64,
+    /// Сколько соединений TCP обслуживается одновременно: каждое держит поток и строку
+    /// запроса до `max_request_bytes`. Следующее получает отказ [`SERVER_BUSY`] и
+    /// закрывается
+    pub max_connections: usize,
+}
+
+impl Default for ServeLimits {
+    fn default() -> Self {
+        Self {
+            max_queue: 16,
+            max_pixels: 16_000_000,
+            max_request_bytes: 64 << 20,
+            max_sample_rate: 192_000,
+            // Martin M1 на 192 кГц — около 22 млн сэмплов
+            max_signal_samples: 32_000_000,
+            max_connections: 32,
+        }
+    }
+}
+
+/// Процессоры без задания и очередь ждущих их заданий
+struct Pool {
+    idle: Vec<SSTVProcessor>,
+    /// Номера ждущих заданий по порядку прихода
+    queue: VecDeque<u64>,
+    next_ticket: u64,
+}
+
+/// Занятое соединение из `max_connections`; освобождается и при панике
+struct Slot<'a>(&'a AtomicUsize);
+
+impl Drop for Slot<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+/// Процессор, взятый заданием; возвращается в пул и при панике
+struct Lease<'a> {
+    server: &'a Server,
+    processor: Option<SSTVProcessor>,
+}
+
+impl Drop for Lease<'_> {
+    fn drop(&mut self) {
+        if let Some(processor) = self.processor.take() {
+            self.server.pool.lock().unwrap().idle.push(processor);
+            self.server.freed.notify_all();
+        }
+    }
+}
+
+/// Строка, прочитанная из потока запросов
+enum Incoming {
+    Line(String),
+    /// Строка длиннее [`ServeLimits::max_request_bytes`]; она пропущена
+    TooLarge,
+    End,
+}
+
+/// Сервер заданий: принимает запросы JSON-RPC 2.0 по одному на строку и отвечает строкой
+/// на каждый. Процессоры живут между заданиями, поэтому кэш кодера и построенные эффекты
+/// не пересоздаются, а запуск программы оплачивается один раз. Заданий идёт столько,
+/// сколько процессоров; остальные ждут в очереди до [`ServeLimits::max_queue`]. Методы:
+/// `process` ([`ServeJob`] → [`ServeResult`]), `ping` и `shutdown`
+pub struct Server {
+    pool: Mutex<Pool>,
+    /// Процессор вернулся в пул или очередь сдвинулась
+    freed: Condvar,
+    workers: usize,
+    limits: ServeLimits,
+    /// Параметры для заданий без своих
+    defaults: ProcessingParams,
+    /// Каталог, внутри которого лежат пути заданий, см. [`with_root`](Self::with_root)
+    root: Option<PathBuf>,
+    jobs: AtomicUsize,
+    stopped: AtomicBool,
+}
+
+impl Server {
+    /// Сервер вокруг процессора `processor`; его параметры берутся для заданий без своих
+    pub fn new(processor: SSTVProcessor) -> Self {
+        Self::with_limits(vec![processor], ServeLimits::default())
+            .expect("один процессор есть всегда")
+    }
+
+    /// Сервер с процессорами `processors`: по заданию на каждый одновременно. Параметры
+    /// первого берутся для заданий без своих
+    pub fn with_limits(processors: Vec<SSTVProcessor>, limits: ServeLimits) -> Result<Self> {
+        if limits.max_connections == 0 {
+            return Err(SstvError::invalid(
+                "max_connections",
+                messages::serve_no_connections(),
+            ));
+        }
+        let defaults = processors
+            .first()
+            .ok_or_else(|| SstvError::invalid("max_jobs", messages::serve_no_processors()))?
+            .params()
+            .clone();
+        Ok(Self {
+            workers: processors.len(),
+            pool: Mutex::new(Pool {
+                idle: processors,
+                queue: VecDeque::new(),
+                next_ticket: 0,
+            }),
+            freed: Condvar::new(),
+            limits,
+            defaults,
+            root: None,
+            jobs: AtomicUsize::new(0),
+            stopped: AtomicBool::new(false),
+        })
+    }
+
+    /// Пути заданий (`image`, `retarder`, `output`, а в параметрах `retarder.source` и
+    /// `overlay.font`) — только относительные, внутри каталога `root` и без `..`. Без
+    /// корня пути принимаются как есть с stdin, где задания шлёт владелец сервера, а по
+    /// TCP отвергаются: изображения туда и обратно — только в base64
+    pub fn with_root(mut self, root: impl AsRef<Path>) -> Result<Self> {
+        let root = root.as_ref();
+        let root = root.canonicalize().map_err(|e| {
+            SstvError::io(messages::serve_root_failed(&root.display().to_string()), e)
+        })?;
+        self.root = Some(root);
+        Ok(self)
+    }
+
+    /// Выполнено заданий `process`
+    pub fn jobs(&self) -> usize {
+        self.jobs.load(Ordering::Relaxed)
+    }
+
+    /// Получен `shutdown`
+    pub fn is_stopped(&self) -> bool {
+        self.stopped.load(Ordering::Relaxed)
+    }
+
+    /// Обрабатывает одну строку запроса и возвращает строку ответа. Уведомления (запросы
+    /// без `id`) выполняются без ответа
+    pub fn handle_line(&self, line: &str) -> Option<String> {
+        self.handle_line_with(line, &mut |_| {})
+    }
+
+    /// Как [`handle_line`](Self::handle_line), но задание, вставшее в очередь, сначала
+    /// отдаёт в `notify` уведомление `queued` со своим местом в ней
+    pub fn handle_line_with(&self, line: &str, notify: &mut dyn FnMut(String)) -> Option<String> {
+        self.handle(line, notify, false)
+    }
+
+    /// Обрабатывает строку запроса; `remote` — она пришла по сети
+    fn handle(&self, line: &str, notify: &mut dyn FnMut(String), remote: bool) -> Option<String> {
+        let request: Value = match serde_json::from_str(line) {
+            Ok(request) => request,
+            Err(e) => return Some(error_reply(Value::Null, PARSE_ERROR, &e.to_string(), None)),
+        };
+        let id = request.get("id").cloned();
+        let reply_id = id.clone().unwrap_or(Value::Null);
+        let Some(method) = request.get("method").and_then(Value::as_str) else {
+            return Some(error_reply(
+                reply_id,
+                INVALID_REQUEST,
+                messages::serve_no_method(),
+                None,
+            ));
+        };
+        let params = request.get("params").cloned().unwrap_or(Value::Null);
+        let mut queued = |position: usize| {
+            notify(
+                json!({
+                    "jsonrpc": "2.0",
+                    "method": "queued",
+                    "params": { "id": reply_id, "position": position },
+                })
+                .to_string(),
+            )
+        };
+        let reply = match self.call(method, params, &mut queued, remote) {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": reply_id, "result": result }),
+            Err((code, message, data)) => {
+                return id.map(|id| error_reply(id, code, &message, data));
+            }
+        };
+        id.map(|_| reply.to_string())
+    }
+
+    fn call(
+        &self,
+        method: &str,
+        params: Value,
+        queued: &mut dyn FnMut(usize),
+        remote: bool,
+    ) -> std::result::Result<Value, (i64, String, Option<Value>)> {
+        match method {
+            "process" => {
+                let job: ServeJob = serde_json::from_value(params)
+                    .map_err(|e| (INVALID_PARAMS, e.to_string(), None))?;
+                let mut lease = self.lease(queued).map_err(|waiting| {
+                    (
+                        SERVER_BUSY,
+                        messages::serve_busy(waiting, self.workers),
+                        Some(json!({ "status": 429, "queued": waiting })),
+                    )
+                })?;
+                let result = self
+                    .process_on(&mut lease, &job, remote)
+                    .map_err(|e| match e {
+                        SstvError::InvalidParams {
+                            field: "max_pixels" | "max_sample_rate" | "max_signal_samples",
+                            ..
+                        } => (JOB_TOO_LARGE, e.to_string(), Some(json!({ "status": 413 }))),
+                        e => (JOB_FAILED, e.to_string(), None),
+                    })?;
+                serde_json::to_value(result).map_err(|e| (JOB_FAILED, e.to_string(), None))
+            }
+            "ping" => {
+                let pool = self.pool.lock().unwrap();
+                Ok(json!({
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "jobs": self.jobs(),
+                    "workers": self.workers,
+                    "running": self.workers - pool.idle.len(),
+                    "queued": pool.queue.len(),
+                    "max_queue": self.limits.max_queue,
+                    "max_connections": self.limits.max_connections,
+                }))
+            }
+            "shutdown" => {
+                self.stopped.store(true, Ordering::Relaxed);
+                Ok(json!({ "jobs": self.jobs() }))
+            }
+            other => Err((
+                METHOD_NOT_FOUND,
+                messages::serve_unknown_method(other),
+                None,
+            )),
+        }
+    }
+
+    /// Берёт процессор из пула, дожидаясь очереди; `Err` с длиной очереди, если она полна
+    fn lease(&self, queued: &mut dyn FnMut(usize)) -> std::result::Result<Lease<'_>, usize> {
+        let mut pool = self.pool.lock().unwrap();
+        if pool.queue.is_empty()
+            && let Some(processor) = pool.idle.pop()
+        {
+            return Ok(Lease {
+                server: self,
+                processor: Some(processor),
+            });
+        }
+        if pool.queue.len() >= self.limits.max_queue {
+            return Err(pool.queue.len());
+        }
+        let ticket = pool.next_ticket;
+        pool.next_ticket += 1;
+        pool.queue.push_back(ticket);
+        let position = pool.queue.len();
+        drop(pool);
+        queued(position);
+
+        let mut pool = self.pool.lock().unwrap();
+        loop {
+            if pool.queue.front() == Some(&ticket)
+                && let Some(processor) = pool.idle.pop()
+            {
+                pool.queue.pop_front();
+                // Следующий в очереди может взять ещё один свободный процессор
+                self.freed.notify_all();
+                return Ok(Lease {
+                    server: self,
+                    processor: Some(processor),
+                });
+            }
+            pool = self.freed.wait(pool).unwrap();
+        }
+    }
+
+    /// Выполняет задание, дождавшись свободного процессора: кодирование, эффекты и
+    /// декодирование. debug.wav не пишется. Ошибка, если очередь полна
+    pub fn process(&self, job: &ServeJob) -> Result<ServeResult> {
+        let mut lease = self.lease(&mut |_| {}).map_err(|waiting| {
+            SstvError::invalid("max_queue", messages::serve_busy(waiting, self.workers))
+        })?;
+        self.process_on(&mut lease, job, false)
+    }
+
+    /// Путь задания `path` из поля `field` на машине сервера, см. [`with_root`](Self::with_root)
+    fn resolve(&self, field: &'static str, path: &str, remote: bool) -> Result<PathBuf> {
+        let Some(root) = &self.root else {
+            if remote {
+                return Err(SstvError::invalid(
+                    field,
+                    messages::serve_path_refused(field),
+                ));
+            }
+            return Ok(PathBuf::from(path));
+        };
+        let relative = Path::new(path);
+        if !relative
+            .components()
+            .all(|c| matches!(c, Component::Normal(_) | Component::CurDir))
+        {
+            return Err(SstvError::invalid(
+                field,
+                messages::serve_path_outside(path),
+            ));
+        }
+        // Ссылка внутри корня может вести наружу, поэтому сверяется настоящий путь; у ещё
+        // не созданного результата — путь его каталога
+        let resolved = root.join(relative);
+        let real = match (resolved.canonicalize(), resolved.parent()) {
+            (Ok(real), _) => Ok(real),
+            (Err(_), Some(dir)) if field == "output" => dir.canonicalize(),
+            (Err(e), _) => Err(e),
+        }
+        .map_err(|e| SstvError::io(messages::serve_open_failed(path), e))?;
+        if !real.starts_with(root) {
+            return Err(SstvError::invalid(
+                field,
+                messages::serve_path_outside(path),
+            ));
+        }
+        Ok(resolved)
+    }
+
+    /// Параметры задания с путями `retarder.source` и `overlay.font`, прошедшими через
+    /// [`resolve`](Self::resolve): иначе чужое задание прочло бы любой файл сервера
+    fn confine<'a>(
+        &self,
+        params: &'a ProcessingParams,
+        remote: bool,
+    ) -> Result<Cow<'a, ProcessingParams>> {
+        let wav = match &params.retarder.source {
+            RetarderSource::WavFile(path) => Some(path),
+            _ => None,
+        };
+        if wav.is_none() && params.overlay.font.is_none() {
+            return Ok(Cow::Borrowed(params));
+        }
+        let resolve = |field, path: &str| {
+            if remote && self.root.is_none() {
+                return Err(SstvError::invalid(
+                    field,
+                    messages::serve_param_path_refused(field),
+                ));
+            }
+            Ok(self
+                .resolve(field, path, remote)?
+                .to_string_lossy()
+                .into_owned())
+        };
+        let mut confined = params.clone();
+        if let Some(path) = wav {
+            confined.retarder.source = RetarderSource::WavFile(resolve("retarder.source", path)?);
+        }
+        if let Some(path) = &params.overlay.font {
+            confined.overlay.font = Some(resolve("overlay.font", path)?);
+        }
+        Ok(Cow::Owned(confined))
+    }
+
+    /// Отказывает заданию, чей сигнал превышает пределы. Проверка идёт по параметрам,
+    /// до того как процессор построит эффекты и займёт память под сигнал
+    fn check_signal(&self, params: &ProcessingParams) -> Result<()> {
+        let (rate, max_rate) = (params.sample_rate, self.limits.max_sample_rate);
+        if rate > max_rate {
+            return Err(SstvError::invalid(
+                "max_sample_rate",
+                messages::serve_sample_rate_too_high(rate, max_rate),
+            ));
+        }
+        let samples = params.encoder.transmission_ms() * rate as f64 / 1000.0;
+        let max_samples = self.limits.max_signal_samples;
+        if samples > max_samples as f64 {
+            return Err(SstvError::invalid(
+                "max_signal_samples",
+                messages::serve_signal_too_long(samples as u64, max_samples),
+            ));
+        }
+        Ok(())
+    }
+
+    fn process_on(
+        &self,
+        lease: &mut Lease<'_>,
+        job: &ServeJob,
+        remote: bool,
+    ) -> Result<ServeResult> {
+        let processor = lease.processor.as_mut().expect("процессор взят из пула");
+        let started = Instant::now();
+        let params = self.confine(job.params.as_ref().unwrap_or(&self.defaults), remote)?;
+        self.check_signal(&params)?;
+        let max_pixels = self.limits.max_pixels;
+        let path = |field, path: &Option<String>| {
+            path.as_deref()
+                .map(|path| self.resolve(field, path, remote))
+                .transpose()
+        };
+        let output = path("output", &job.output)?;
+        let main_image = load_image(
+            path("image", &job.image)?.as_deref(),
+            job.image_base64.as_deref(),
+            max_pixels,
+        )?
+        .ok_or_else(|| SstvError::invalid("image", messages::serve_no_image()))?;
+        let retarder_image = load_image(
+            path("retarder", &job.retarder)?.as_deref(),
+            job.retarder_base64.as_deref(),
+            max_pixels,
+        )?;
+        processor.try_set_params(params.into_owned())?;
+
+        let samples = processor.render_samples(&main_image, retarder_image.as_ref())?;
+        let encode_cache_hit = processor.encode_cache_hit();
+        let report = processor.decode_samples(&samples)?;
+        let sent = processor.params().overlay_params().apply(&main_image)?;
+        let QualityReport {
+            psnr, ssim, mse, ..
+        } = metrics::compare_in(&sent, &report.image, processor.params().metric_space);
+
+        if let Some(path) = &output {
+            processor.save_result(&report.image, &path.to_string_lossy())?;
+        }
+        let image_base64 = if job.return_image {
+            Some(base64_encode(&encode_png(&report.image)?))
+        } else {
+            None
+        };
+        self.jobs.fetch_add(1, Ordering::Relaxed);
+        Ok(ServeResult {
+            lines_decoded: report.lines_decoded,
+            partial: report.partial,
+            snr_estimate: report.snr_estimate,
+            psnr,
+            ssim,
+            mse,
+            encode_cache_hit,
+            elapsed_ms: started.elapsed().as_secs_f64() * 1000.0,
+            report: report.describe(),
+            output: job.output.clone(),
+            image_base64,
+        })
+    }
+
+    /// Читает запросы из `reader` и пишет ответы в `writer`, пока вход не кончится или
+    /// не придёт `shutdown`. Пустые строки пропускаются, а слишком длинные получают отказ
+    /// [`JOB_TOO_LARGE`]. Запросы одного потока выполняются по очереди
+    pub fn serve(&self, reader: impl BufRead, writer: impl Write) -> Result<()> {
+        self.serve_with(reader, writer, false)
+    }
+
+    /// Как [`serve`](Self::serve); `remote` — запросы идут по сети
+    fn serve_with(
+        &self,
+        mut reader: impl BufRead,
+        mut writer: impl Write,
+        remote: bool,
+    ) -> Result<()> {
+        let mut send = |line: String| {
+            writeln!(writer, "{}", line)
+                .and_then(|()| writer.flush())
+                .map_err(|e| SstvError::io(messages::serve_send_failed(), e))
+        };
+        let mut line = Vec::new();
+        while !self.is_stopped() {
+            let incoming = match read_request(&mut reader, &mut line, self.limits.max_request_bytes)
+            {
+                Ok(incoming) => incoming,
+                // Таймаут чтения TCP: строка копится дальше, а `shutdown` успевает остановить
+                Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {
+                    continue;
+                }
+                Err(e) => return Err(SstvError::io(messages::serve_read_failed(), e)),
+            };
+            let text = match incoming {
+                Incoming::Line(text) => text,
+                Incoming::TooLarge => {
+                    send(error_reply(
+                        Value::Null,
+                        JOB_TOO_LARGE,
+                        &messages::serve_request_too_large(self.limits.max_request_bytes),
+                        Some(json!({ "status": 413 })),
+                    ))?;
+                    continue;
+                }
+                Incoming::End => break,
+            };
+            if text.trim().is_empty() {
+                continue;
+            }
+            // Уведомление об очереди уходит сразу, пока задание ждёт процессор
+            let mut failed = None;
+            let mut notify = |notice| {
+                if let Err(e) = send(notice) {
+                    failed.get_or_insert(e);
+                }
+            };
+            let reply = self.handle(&text, &mut notify, remote);
+            if let Some(e) = failed {
+                return Err(e);
+            }
+            if let Some(reply) = reply {
+                send(reply)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Принимает соединения на `addr` (например `127.0.0.1:7300`), каждое в своём потоке:
+    /// задания разных клиентов идут одновременно на свободных процессорах и ждут в общей
+    /// очереди. Соединений не больше `max_connections`, лишнее получает отказ. Работает
+    /// до `shutdown`; обрыв соединения только пишется в журнал. Пути в заданиях — только
+    /// внутри [`with_root`](Self::with_root)
+    pub fn serve_tcp(&self, addr: &str) -> Result<()> {
+        let listener = TcpListener::bind(addr)
+            .map_err(|e| SstvError::io(messages::serve_bind_failed(addr), e))?;
+        // Без блокировки `accept` замечает `shutdown` из другого соединения
+        listener
+            .set_nonblocking(true)
+            .map_err(|e| SstvError::io(messages::serve_bind_failed(addr), e))?;
+        self.serve_listener(&listener);
+        Ok(())
+    }
+
+    /// Цикл приёма [`serve_tcp`](Self::serve_tcp) на открытом неблокирующем `listener`
+    fn serve_listener(&self, listener: &TcpListener) {
+        let active = AtomicUsize::new(0);
+        std::thread::scope(|scope| {
+            while !self.is_stopped() {
+                let mut stream = match listener.accept() {
+                    Ok((stream, _)) => stream,
+                    Err(e) if e.kind() == ErrorKind::WouldBlock => {
+                        std::thread::sleep(ACCEPT_POLL);
+                        continue;
+                    }
+                    Err(e) => {
+                        tracing::warn!("{}", messages::serve_accept_failed(&e.to_string()));
+                        continue;
+                    }
+                };
+                let max = self.limits.max_connections;
+                if active.fetch_add(1, Ordering::AcqRel) >= max {
+                    active.fetch_sub(1, Ordering::AcqRel);
+                    // Отказ короткий и уходит в буфер сокета, не задерживая приём
+                    let busy = error_reply(
+                        Value::Null,
+                        SERVER_BUSY,
+                        &messages::serve_connections_full(max),
+                        Some(json!({ "status": 429, "connections": max })),
+                    );
+                    let _ = stream
+                        .set_nonblocking(true)
+                        .and_then(|()| writeln!(stream, "{}", busy));
+                    continue;
+                }
+                let slot = Slot(&active);
+                let reader = stream
+                    .set_nonblocking(false)
+                    .and_then(|()| stream.set_read_timeout(Some(ACCEPT_POLL * 10)))
+                    .and_then(|()| stream.try_clone());
+                let reader = match reader {
+                    Ok(reader) => BufReader::new(reader),
+                    Err(e) => {
+                        tracing::warn!("{}", messages::serve_accept_failed(&e.to_string()));
+                        continue;
+                    }
+                };
+                scope.spawn(move || {
+                    if let Err(e) = self.serve_with(reader, stream, true) {
+                        tracing::warn!("{}", e);
+                    }
+                    drop(slot);
+                });
+            }
+        });
+    }
+}
+
+/// Читает строку запроса, не больше `limit` байт. `line` копит прочитанное между
+/// вызовами, если чтение прервал таймаут
+fn read_request(
+    reader: &mut impl BufRead,
+    line: &mut Vec<u8>,
+    limit: usize,
+) -> std::io::Result<Incoming> {
+    let room = (limit + 1).saturating_sub(line.len()) as u64;
+    let read = reader.by_ref().take(room).read_until(b'\n', line)?;
+    if line.len() > limit && !line.ends_with(b"\n") {
+        line.clear();
+        reader.skip_until(b'\n')?;
+        return Ok(Incoming::TooLarge);
+    }
+    if read == 0 && line.is_empty() {
+        return Ok(Incoming::End);
+    }
+    let text = String::from_utf8_lossy(line).into_owned();
+    line.clear();
+    Ok(Incoming::Line(text))
+}
+
+fn error_reply(id: Value, code: i64, message: &str, data: Option<Value>) -> String {
+    let mut error = json!({ "code": code, "message": message });
+    if let Some(data) = data {
+        error["data"] = data;
+    }
+    json!({ "jsonrpc": "2.0", "id": id, "error": error }).to_string()
+}
+
+/// Изображение по пути или из base64; `None`, если не задано ни то ни другое. Размер
+/// проверяется по заголовку файла до декодирования: больше `max_pixels` — ошибка
+fn load_image(
+    path: Option<&Path>,
+    base64: Option<&str>,
+    max_pixels: u64,
+) -> Result<Option<DynamicImage>> {
+    let (bytes, name) = match (base64, path) {
+        (Some(data), _) => (base64_decode(data)?, "image_base64".to_string()),
+        (None, Some(path)) => {
+            let name = path.display().to_string();
+            let bytes = std::fs::read(path)
+                .map_err(|e| SstvError::io(messages::serve_open_failed(&name), e))?;
+            (bytes, name)
+        }
+        (None, None) => return Ok(None),
+    };
+    let reader = || {
+        ImageReader::new(Cursor::new(&bytes))
+            .with_guessed_format()
+            .map_err(|e| SstvError::io(messages::serve_decode_failed(&name), e))
+    };
+    let (width, height) = reader()?
+        .into_dimensions()
+        .map_err(|e| SstvError::io(messages::serve_decode_failed(&name), e))?;
+    if width as u64 * height as u64 > max_pixels {
+        return Err(SstvError::invalid(
+            "max_pixels",
+            messages::serve_image_too_large(width, height, max_pixels),
+        ));
+    }
+    reader()?
+        .decode()
+        .map(Some)
+        .map_err(|e| SstvError::io(messages::serve_decode_failed(&name), e))
+}
+
+pub(crate) fn encode_png(image: &DynamicImage) -> Result<Vec<u8>> {
+    let mut png = Vec::new();
+    image
+        .write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+        .map_err(|e| SstvError::io(messages::serve_png_failed(), e))?;
+    Ok(png)
+}
+
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Base64 (RFC 4648) с дополнением `=`
+pub(crate) fn base64_encode(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let b = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let n = (b[0] as u32) << 16 | (b[1] as u32) << 8 | b[2] as u32;
+        for k in 0..4 {
+            if k <= chunk.len() {
+                out.push(BASE64_ALPHABET[(n >> (18 - 6 * k) & 63) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+/// Разбирает base64 с дополнением или без; пробелы и переводы строк пропускаются,
+/// префикс `data:...;base64,` отбрасывается
+fn base64_decode(text: &str) -> Result<Vec<u8>> {
+    let text = text.split_once(";base64,").map_or(text, |(_, data)| data);
+    let mut out = Vec::with_capacity(text.len() / 4 * 3);
+    let (mut acc, mut bits) = (0u32, 0);
+    for c in text
+        .bytes()
+        .filter(|c| !c.is_ascii_whitespace() && *c != b'=')
+    {
+        let value = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' | b'-' => 62,
+            b'/' | b'_' => 63,
+            _ => {
+                return Err(SstvError::invalid(
+                    "image_base64",
+                    messages::serve_bad_base64(c as char),
+                ));
+            }
+        };
+        acc = acc << 6 | value as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((acc >> bits) as u8);
+            acc &= (1 << bits) - 1;
+        }
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpStream;
+
+    #[test]
+    fn job_paths_stay_inside_root() {
+        let refused = |result: Result<PathBuf>| {
+            matches!(result, Err(SstvError::InvalidParams { field: "image", .. }))
+        };
+        let server = Server::new(SSTVProcessor::new());
+        assert!(refused(server.resolve("image", "in/a.png", true)));
+        assert_eq!(
+            server.resolve("image", "in/a.png", false).unwrap(),
+            Path::new("in/a.png")
+        );
+
+        let root = std::env::temp_dir().join(format!("sstv_serve_root_{}", std::process::id()));
+        std::fs::create_dir_all(root.join("in")).unwrap();
+        std::fs::write(root.join("in/a.png"), b"").unwrap();
+        let server = server.with_root(&root).unwrap();
+        let root = root.canonicalize().unwrap();
+        assert_eq!(
+            server.resolve("image", "in/a.png", true).unwrap(),
+            root.join("in/a.png")
+        );
+        // Результата ещё нет: проверяется его каталог
+        assert!(server.resolve("output", "out.png", true).is_ok());
+        for path in ["/etc/passwd", "../a.png", "in/../../a.png"] {
+            assert!(refused(server.resolve("image", path, false)), "{}", path);
+        }
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink("/etc", root.join("etc")).unwrap();
+            assert!(refused(server.resolve("image", "etc/passwd", true)));
+        }
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn job_params_paths_stay_inside_root() {
+        let refused = |result: Result<Cow<ProcessingParams>>, name: &str| matches!(result, Err(SstvError::InvalidParams { field, .. }) if field == name);
+        let mut params = ProcessingParams::default();
+        assert!(matches!(
+            Server::new(SSTVProcessor::new()).confine(&params, true),
+            Ok(Cow::Borrowed(_))
+        ));
+        params.retarder.source = RetarderSource::WavFile("/etc/passwd".into());
+        let server = Server::new(SSTVProcessor::new());
+        assert!(refused(server.confine(&params, true), "retarder.source"));
+        assert!(server.confine(&params, false).is_ok());
+
+        let root = std::env::temp_dir().join(format!("sstv_serve_params_{}", std::process::id()));
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(root.join("ghost.wav"), b"").unwrap();
+        let server = server.with_root(&root).unwrap();
+        assert!(refused(server.confine(&params, false), "retarder.source"));
+        params.retarder.source = RetarderSource::WavFile("ghost.wav".into());
+        params.overlay.font = Some("../font.ttf".into());
+        assert!(refused(server.confine(&params, true), "overlay.font"));
+        params.overlay.font = None;
+        let confined = server.confine(&params, true).unwrap();
+        let expected = root.canonicalize().unwrap().join("ghost.wav");
+        assert_eq!(
+            confined.retarder.source,
+            RetarderSource::WavFile(expected.to_string_lossy().into_owned())
+        );
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    /// Ответ `server` на задание `process` с картинкой 40×40 и параметрами `params`
+    fn process(server: &Server, params: Value) -> Value {
+        let png = encode_png(&DynamicImage::new_rgb8(40, 40)).unwrap();
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "process",
+            "params": { "image_base64": base64_encode(&png), "params": params },
+        });
+        serde_json::from_str(&server.handle_line(&request.to_string()).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn server_refuses_oversized_jobs() {
+        let limits = ServeLimits {
+            max_pixels: 1000,
+            max_request_bytes: 4096,
+            ..Default::default()
+        };
+        assert!(Server::with_limits(Vec::new(), limits).is_err());
+        let processors = vec![SSTVProcessor::new(), SSTVProcessor::new()];
+        let server = Server::with_limits(processors, limits).unwrap();
+        let reply = process(&server, Value::Null);
+        assert_eq!(reply["error"]["code"], JOB_TOO_LARGE);
+        assert_eq!(reply["error"]["data"]["status"], 413);
+
+        // Слишком длинная строка пропускается целиком, следующий запрос читается как обычно
+        let input = format!(
+            "{}\n{}\n",
+            "x".repeat(10_000),
+            r#"{"jsonrpc":"2.0","id":2,"method":"ping"}"#
+        );
+        let mut output = Vec::new();
+        server.serve(input.as_bytes(), &mut output).unwrap();
+        let replies: Vec<Value> = String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(replies.len(), 2);
+        assert_eq!(replies[0]["error"]["code"], JOB_TOO_LARGE);
+        assert_eq!(replies[1]["result"]["workers"], 2);
+        assert_eq!(replies[1]["result"]["running"], 0);
+        assert_eq!(server.jobs(), 0);
+    }
+
+    #[test]
+    fn server_refuses_oversized_signals() {
+        let limits = ServeLimits {
+            max_sample_rate: 48_000,
+            max_signal_samples: 2_000_000,
+            ..Default::default()
+        };
+        let server = Server::with_limits(vec![SSTVProcessor::new()], limits).unwrap();
+
+        // Частота в 4 ГГц заняла бы сотни гигабайт, Martin M1 на 48 кГц — 5,5 млн сэмплов:
+        // обоим отказ до кодирования
+        for params in [
+            json!({ "sample_rate": 4_000_000_000u32 }),
+            json!({ "sample_rate": 48_000 }),
+        ] {
+            let reply = process(&server, params);
+            assert_eq!(reply["error"]["code"], JOB_TOO_LARGE, "{reply}");
+            assert_eq!(reply["error"]["data"]["status"], 413);
+        }
+        // Martin M1 на 11025 Гц — около 1,3 млн сэмплов, в пределах
+        let reply = process(&server, json!({ "sample_rate": 11_025 }));
+        assert!(reply["result"].is_object(), "{reply}");
+        assert_eq!(server.jobs(), 1);
+    }
+
+    #[test]
+    fn server_refuses_connections_over_the_limit() {
+        let limits = |max_connections| ServeLimits {
+            max_connections,
+            ..Default::default()
+        };
+        assert!(Server::with_limits(vec![SSTVProcessor::new()], limits(0)).is_err());
+        let server = Server::with_limits(vec![SSTVProcessor::new()], limits(1)).unwrap();
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        listener.set_nonblocking(true).unwrap();
+        let addr = listener.local_addr().unwrap();
+        let call = |stream: &TcpStream, method: &str| -> Value {
+            writeln!(
+                &*stream,
+                r#"{{"jsonrpc":"2.0","id":1,"method":"{}"}}"#,
+                method
+            )
+            .unwrap();
+            let mut line = String::new();
+            BufReader::new(stream).read_line(&mut line).unwrap();
+            serde_json::from_str(&line).unwrap()
+        };
+        std::thread::scope(|scope| {
+            scope.spawn(|| server.serve_listener(&listener));
+            // Ответ на ping значит, что первое соединение уже принято и занимает место
+            let first = TcpStream::connect(addr).unwrap();
+            assert_eq!(call(&first, "ping")["result"]["max_connections"], 1);
+            let second = TcpStream::connect(addr).unwrap();
+            let reply = call(&second, "ping");
+            assert_eq!(reply["error"]["code"], SERVER_BUSY, "{reply}");
+            assert_eq!(reply["error"]["data"]["connections"], 1);
+            call(&first, "shutdown");
+        });
+    }
+}
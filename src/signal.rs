@@ -0,0 +1,340 @@
+use crate::buffer::SampleBuffer;
+use crate::decoder::{DecoderParams, create_decoder};
+use crate::dsp::ConvolutionBackend;
+use crate::effect::{
+    self, ChannelEffect, ChannelSummary, EffectContext, SanitizeEvent, SanitizeMode,
+};
+use crate::encoder::{self, EncoderParams, LineHook};
+use crate::error::Result;
+use crate::processor::{self, MAX_OUTPUT_GAIN_DB, ProcessingParams, SSTVProcessor};
+use crate::quality::QualityTier;
+use crate::report::{DecodeReport, LevelMeter};
+use crate::sink::ImageSink;
+#[cfg(feature = "wav")]
+use crate::wav;
+use image::DynamicImage;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use rsstv::SAMPLE_RATE;
+use std::cell::RefCell;
+use std::ops::Range;
+use std::sync::atomic::AtomicBool;
+
+/// Звуковой сигнал SSTV вместе с частотой дискретизации и положением тела изображения.
+/// Сигнал кодируется один раз, искажается эффектами и декодируется сколько угодно раз
+/// с разными параметрами декодера
+#[derive(Clone, Debug, PartialEq)]
+pub struct Signal {
+    buffer: SampleBuffer,
+    image_span: Range<usize>,
+}
+
+impl Signal {
+    /// Сигнал без разметки: тело изображения занимает его целиком
+    pub fn new(samples: Vec<f32>, sample_rate: u32) -> Self {
+        Self::from_buffer(SampleBuffer::mono(samples, sample_rate))
+    }
+
+    /// Сигнал из буфера без разметки; многоканальный буфер сводится в моно
+    pub fn from_buffer(buffer: SampleBuffer) -> Self {
+        let buffer = buffer.to_mono();
+        Self {
+            image_span: 0..buffer.len(),
+            buffer,
+        }
+    }
+
+    /// Сигнал с известным положением тела изображения, сэмплов
+    pub fn with_image_span(samples: Vec<f32>, sample_rate: u32, image_span: Range<usize>) -> Self {
+        let end = image_span.end.min(samples.len());
+        Self {
+            image_span: image_span.start.min(end)..end,
+            buffer: SampleBuffer::mono(samples, sample_rate),
+        }
+    }
+
+    /// Читает сигнал из WAV файла (моно/стерео, любая частота)
+    #[cfg(feature = "wav")]
+    pub fn from_wav(path: &str) -> Result<Self> {
+        Ok(Self::from_buffer(wav::read_wav(path)?))
+    }
+
+    /// Записывает сигнал в 16-битный WAV
+    #[cfg(feature = "wav")]
+    pub fn write_wav(&self, path: &str) -> Result<()> {
+        wav::write_wav(path, &self.buffer)
+    }
+
+    pub fn samples(&self) -> &[f32] {
+        &self.buffer
+    }
+
+    /// Изменяемые сэмплы; длина сигнала при этом не меняется
+    pub fn samples_mut(&mut self) -> &mut [f32] {
+        &mut self.buffer
+    }
+
+    pub fn into_samples(self) -> Vec<f32> {
+        self.buffer.data
+    }
+
+    /// Сэмплы вместе с частотой дискретизации
+    pub fn buffer(&self) -> &SampleBuffer {
+        &self.buffer
+    }
+
+    pub fn into_buffer(self) -> SampleBuffer {
+        self.buffer
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.buffer.rate
+    }
+
+    /// Сэмплы тела изображения (без заголовка и добавленных участков)
+    pub fn image_span(&self) -> Range<usize> {
+        self.image_span.clone()
+    }
+
+    pub fn len(&self) -> usize {
+        self.buffer.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.buffer.is_empty()
+    }
+
+    /// Длительность, секунд
+    pub fn duration_secs(&self) -> f64 {
+        self.buffer.duration_secs()
+    }
+
+    /// Сигнал с другой частотой дискретизации; разметка пересчитывается
+    pub fn resampled(&self, sample_rate: u32) -> Self {
+        self.resampled_with(sample_rate, QualityTier::Normal)
+    }
+
+    /// Как [`resampled`](Self::resampled), ядром передискретизации уровня `quality`
+    pub fn resampled_with(&self, sample_rate: u32, quality: QualityTier) -> Self {
+        if sample_rate == self.buffer.rate {
+            return self.clone();
+        }
+        let scale = |i: usize| (i as f64 * sample_rate as f64 / self.buffer.rate as f64) as usize;
+        let buffer = self.buffer.resampled_with(sample_rate, quality);
+        Self::with_image_span(
+            buffer.data,
+            sample_rate,
+            scale(self.image_span.start)..scale(self.image_span.end),
+        )
+    }
+
+    /// Применяет включённые эффекты `effects` по порядку. `retarder` — сигнал
+    /// картинки-призрака, `seed` — зерно случайных эффектов (`None` — новое случайное)
+    pub fn apply(
+        &mut self,
+        effects: &[Box<dyn ChannelEffect>],
+        retarder: Option<&Signal>,
+        seed: Option<u64>,
+    ) -> Result<()> {
+        self.apply_with_progress(
+            effects,
+            retarder,
+            seed,
+            ConvolutionBackend::Auto,
+            false,
+            SanitizeMode::Off,
+            &|_| {},
+            &AtomicBool::new(false),
+        )
+        .map(|_| ())
+    }
+
+    /// Как [`apply`](Self::apply), со способом свёртки, параллельной обработкой кусков
+    /// сигнала, проверкой на NaN, долей выполненных эффектов и отменой, см.
+    /// [`effect::apply_effects_with_progress`]. Возвращает эффекты, после которых
+    /// пришлось исправлять сэмплы
+    #[allow(clippy::too_many_arguments)]
+    pub fn apply_with_progress(
+        &mut self,
+        effects: &[Box<dyn ChannelEffect>],
+        retarder: Option<&Signal>,
+        seed: Option<u64>,
+        convolution: ConvolutionBackend,
+        parallel: bool,
+        sanitize: SanitizeMode,
+        progress: &dyn Fn(f32),
+        cancel: &AtomicBool,
+    ) -> Result<Vec<SanitizeEvent>> {
+        // Призрак смешивается сэмпл в сэмпл, поэтому приводится к частоте сигнала
+        let retarder = retarder.map(|r| r.resampled(self.buffer.rate));
+        let rng = RefCell::new(match seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_os_rng(),
+        });
+        let ctx = EffectContext {
+            retarder: retarder.as_ref().map(|r| r.buffer()),
+            image_span: self.image_span.clone(),
+            rng: &rng,
+            convolution,
+            parallel,
+            sanitize,
+        };
+        effect::apply_effects_with_progress(effects, &mut self.buffer, &ctx, progress, cancel)
+    }
+
+    /// Сводка того, что эффекты `effects` сделали бы с сигналом, без его изменения, см.
+    /// [`effect::analyze_effects`]. `retarder` и `seed` — как у [`apply`](Self::apply)
+    pub fn analyze(
+        &self,
+        effects: &[Box<dyn ChannelEffect>],
+        retarder: Option<&Signal>,
+        seed: Option<u64>,
+    ) -> Result<ChannelSummary> {
+        let retarder = retarder.map(|r| r.resampled(self.buffer.rate));
+        let rng = RefCell::new(match seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_os_rng(),
+        });
+        let ctx = EffectContext {
+            retarder: retarder.as_ref().map(|r| r.buffer()),
+            image_span: self.image_span.clone(),
+            rng: &rng,
+            convolution: ConvolutionBackend::Auto,
+            parallel: false,
+            sanitize: SanitizeMode::Off,
+        };
+        effect::analyze_effects(effects, &self.buffer, &ctx)
+    }
+
+    /// Приводит сигнал к частоте эффектов [`ProcessingParams::signal_rate`] и применяет
+    /// стандартный конвейер эффектов с параметрами `params`, см.
+    /// [`SSTVProcessor::default_effects`], затем выходной каскад
+    /// [`output_stage`](Self::output_stage). Возвращает эффекты, после которых проверка
+    /// [`ProcessingParams::sanitize`] исправляла сэмплы. Без `retarder` призраком
+    /// становится сигнал источника `params.retarder.source`, если он не требует картинки,
+    /// см. [`crate::retarder::RetarderSource::signal_from`]
+    pub fn degrade(
+        &mut self,
+        params: &ProcessingParams,
+        retarder: Option<&Signal>,
+    ) -> Result<Vec<SanitizeEvent>> {
+        processor::check_sample_rate(params.sample_rate)?;
+        processor::check_sample_rate(self.buffer.rate)?;
+        let rate = params.signal_rate();
+        if self.buffer.rate != rate {
+            *self = self.resampled_with(rate, params.quality);
+        }
+        let own = match retarder {
+            Some(_) => None,
+            None => params.retarder.source.signal_from(self)?,
+        };
+        let retarder = retarder
+            .or(own.as_ref())
+            .map(|r| r.resampled_with(rate, params.quality));
+        let sanitized = self.apply_with_progress(
+            &SSTVProcessor::default_effects(params),
+            retarder.as_ref(),
+            params.seed,
+            params.convolution,
+            params.parallel,
+            params.sanitize,
+            &|_| {},
+            &AtomicBool::new(false),
+        )?;
+        self.output_stage(params.output_gain_db);
+        Ok(sanitized)
+    }
+
+    /// Выходной каскад после эффектов: усиление `gain_db`, замер уровня и ограничение
+    /// диапазоном −1…1. Эффекты складывают сигналы без ограничения, поэтому перегрузку
+    /// от суммы призрака, помех и шума можно убрать отрицательным усилением здесь.
+    /// Усиление ограничено [`MAX_OUTPUT_GAIN_DB`]
+    pub fn output_stage(&mut self, gain_db: f32) -> LevelMeter {
+        let gain = output_gain(gain_db);
+        if gain != 1.0 {
+            self.buffer.data.iter_mut().for_each(|x| *x *= gain);
+        }
+        let level = LevelMeter::measure(&self.buffer.data);
+        self.buffer
+            .data
+            .iter_mut()
+            .for_each(|x| *x = x.clamp(-1.0, 1.0));
+        level
+    }
+}
+
+/// Множитель выходного каскада для усиления `gain_db`: NaN — без усиления, остальное
+/// ограничено [`MAX_OUTPUT_GAIN_DB`]
+pub(crate) fn output_gain(gain_db: f32) -> f32 {
+    let gain_db = match gain_db {
+        db if db.is_nan() => 0.0,
+        db => db.clamp(-MAX_OUTPUT_GAIN_DB, MAX_OUTPUT_GAIN_DB),
+    };
+    10f32.powf(gain_db / 20.0)
+}
+
+/// Кодирует изображение в сигнал SSTV
+pub fn encode(image: &DynamicImage, params: &EncoderParams) -> Signal {
+    encode_with_hook(image, params, None)
+}
+
+/// Кодирует изображение, вызывая обработчик для каждой строки, см. [`encoder::encode_with_hook`]
+pub fn encode_with_hook(
+    image: &DynamicImage,
+    params: &EncoderParams,
+    hook: Option<&mut LineHook>,
+) -> Signal {
+    let samples = encoder::encode_with_hook(image, params, hook);
+    let span = encoder::image_span(params, SAMPLE_RATE as u32, samples.len());
+    Signal::with_image_span(samples, SAMPLE_RATE as u32, span)
+}
+
+/// Декодирует сигнал; при другой частоте дискретизации он сначала передискретизируется.
+/// Сигнал не меняется, поэтому его можно декодировать повторно с другими параметрами
+pub fn decode(signal: &Signal, params: &DecoderParams) -> Result<DecodeReport> {
+    decode_with_progress(signal, params, &|_| {}, &AtomicBool::new(false))
+}
+
+/// Декодирует сигнал прямо в `sink`, строку за строкой, без отчёта: получатель видит
+/// выход декодера без маскировки строк и эффектов изображения
+pub fn decode_into(
+    signal: &Signal,
+    params: &DecoderParams,
+    sink: &mut dyn ImageSink,
+    progress: &dyn Fn(f32),
+    cancel: &AtomicBool,
+) -> Result<()> {
+    let resampled;
+    let samples = if signal.sample_rate() == SAMPLE_RATE as u32 {
+        signal.samples()
+    } else {
+        resampled = signal.buffer.resampled(SAMPLE_RATE as u32);
+        &resampled
+    };
+    create_decoder(params).decode_into(samples, sink, progress, cancel)
+}
+
+/// Как [`decode`], сообщая долю выполнения (0–1) и проверяя отмену
+pub fn decode_with_progress(
+    signal: &Signal,
+    params: &DecoderParams,
+    progress: &dyn Fn(f32),
+    cancel: &AtomicBool,
+) -> Result<DecodeReport> {
+    let resampled;
+    let samples = if signal.sample_rate() == SAMPLE_RATE as u32 {
+        signal.samples()
+    } else {
+        resampled = signal.buffer.resampled(SAMPLE_RATE as u32);
+        &resampled
+    };
+
+    let mut decoder = create_decoder(params);
+    let image = decoder.decode_with_progress(samples, progress, cancel)?;
+    Ok(DecodeReport::from_decoder(
+        image,
+        decoder.as_ref(),
+        params,
+        samples,
+    ))
+}
@@ -0,0 +1,280 @@
+use crate::error::{Result, SstvError};
+use crate::messages;
+use image::{ImageFormat, RgbImage};
+use std::io::Write;
+use std::path::PathBuf;
+
+/// Получатель декодированного изображения. Декодер передаёт строки по мере приёма,
+/// сверху вниз, поэтому изображение можно показывать, отдавать по сети или писать в
+/// буфер вызывающего, не дожидаясь конца декодирования. Получатель видит выход
+/// декодера до маскировки строк и эффектов изображения
+/// [`DecoderParams::image_effects`](crate::decoder::DecoderParams::image_effects)
+pub trait ImageSink {
+    /// Перед первой строкой: размеры изображения
+    fn begin(&mut self, width: u32, height: u32) -> Result<()> {
+        let _ = (width, height);
+        Ok(())
+    }
+
+    /// Строка `y`: `width` пикселей RGB по три байта
+    fn write_row(&mut self, y: u32, row: &[u8]) -> Result<()>;
+
+    /// После последней строки; `lines` — сколько строк принято, если сигнал кончился
+    /// раньше изображения, нижние строки не приходят. При ошибке декодирования не вызывается
+    fn finish(&mut self, lines: u32) -> Result<()> {
+        let _ = lines;
+        Ok(())
+    }
+}
+
+impl<S: ImageSink + ?Sized> ImageSink for &mut S {
+    fn begin(&mut self, width: u32, height: u32) -> Result<()> {
+        (**self).begin(width, height)
+    }
+
+    fn write_row(&mut self, y: u32, row: &[u8]) -> Result<()> {
+        (**self).write_row(y, row)
+    }
+
+    fn finish(&mut self, lines: u32) -> Result<()> {
+        (**self).finish(lines)
+    }
+}
+
+impl<S: ImageSink + ?Sized> ImageSink for Box<S> {
+    fn begin(&mut self, width: u32, height: u32) -> Result<()> {
+        (**self).begin(width, height)
+    }
+
+    fn write_row(&mut self, y: u32, row: &[u8]) -> Result<()> {
+        (**self).write_row(y, row)
+    }
+
+    fn finish(&mut self, lines: u32) -> Result<()> {
+        (**self).finish(lines)
+    }
+}
+
+/// Пара получателей: строки идут в оба по порядку
+impl<A: ImageSink, B: ImageSink> ImageSink for (A, B) {
+    fn begin(&mut self, width: u32, height: u32) -> Result<()> {
+        self.0.begin(width, height)?;
+        self.1.begin(width, height)
+    }
+
+    fn write_row(&mut self, y: u32, row: &[u8]) -> Result<()> {
+        self.0.write_row(y, row)?;
+        self.1.write_row(y, row)
+    }
+
+    fn finish(&mut self, lines: u32) -> Result<()> {
+        self.0.finish(lines)?;
+        self.1.finish(lines)
+    }
+}
+
+/// Передаёт готовое изображение в `sink` построчно, как это делал бы декодер
+pub fn write_image(sink: &mut dyn ImageSink, image: &RgbImage) -> Result<()> {
+    sink.begin(image.width(), image.height())?;
+    let stride = image.width() as usize * 3;
+    for (y, row) in image.as_raw().chunks_exact(stride.max(1)).enumerate() {
+        sink.write_row(y as u32, row)?;
+    }
+    sink.finish(image.height())
+}
+
+/// Изображение в памяти; непринятые строки остаются чёрными
+#[derive(Clone, Debug, Default)]
+pub struct MemorySink {
+    image: RgbImage,
+}
+
+impl MemorySink {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn image(&self) -> &RgbImage {
+        &self.image
+    }
+
+    pub fn into_image(self) -> RgbImage {
+        self.image
+    }
+}
+
+impl ImageSink for MemorySink {
+    fn begin(&mut self, width: u32, height: u32) -> Result<()> {
+        self.image = RgbImage::new(width, height);
+        Ok(())
+    }
+
+    fn write_row(&mut self, y: u32, row: &[u8]) -> Result<()> {
+        let stride = self.image.width() as usize * 3;
+        let start = y as usize * stride;
+        let Some(dst) = self.image.get_mut(start..start + stride) else {
+            return Err(SstvError::invalid("y", messages::row_outside(y)));
+        };
+        dst.copy_from_slice(&row[..stride]);
+        Ok(())
+    }
+}
+
+/// Буфер вызывающего: строки пишутся прямо в него, RGB по три байта, без промежуточного
+/// изображения. Буфер не короче `width · height · 3`
+pub struct BufferSink<'a> {
+    buffer: &'a mut [u8],
+    stride: usize,
+}
+
+impl<'a> BufferSink<'a> {
+    pub fn new(buffer: &'a mut [u8]) -> Self {
+        Self { buffer, stride: 0 }
+    }
+}
+
+impl ImageSink for BufferSink<'_> {
+    fn begin(&mut self, width: u32, height: u32) -> Result<()> {
+        let needed = width as usize * height as usize * 3;
+        if self.buffer.len() < needed {
+            return Err(SstvError::invalid(
+                "buffer",
+                messages::buffer_too_small(self.buffer.len(), width, height, needed),
+            ));
+        }
+        self.stride = width as usize * 3;
+        Ok(())
+    }
+
+    fn write_row(&mut self, y: u32, row: &[u8]) -> Result<()> {
+        let start = y as usize * self.stride;
+        self.buffer[start..start + self.stride].copy_from_slice(&row[..self.stride]);
+        Ok(())
+    }
+}
+
+/// Вызывает `f(y, row)` для каждой принятой строки
+pub struct CallbackSink<F: FnMut(u32, &[u8])> {
+    f: F,
+}
+
+impl<F: FnMut(u32, &[u8])> CallbackSink<F> {
+    pub fn new(f: F) -> Self {
+        Self { f }
+    }
+}
+
+impl<F: FnMut(u32, &[u8])> ImageSink for CallbackSink<F> {
+    fn write_row(&mut self, y: u32, row: &[u8]) -> Result<()> {
+        (self.f)(y, row);
+        Ok(())
+    }
+}
+
+/// Двоичный PPM (P6) в поток: заголовок при начале, строки по мере приёма. Подходит
+/// для файла, `TcpStream` или тела HTTP-ответа; непринятые строки в конце дописываются
+/// чёрными, чтобы файл остался целым
+pub struct StreamSink<W: Write> {
+    writer: W,
+    width: u32,
+    height: u32,
+    /// Следующая строка, которую ждёт поток
+    next: u32,
+}
+
+impl<W: Write> StreamSink<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            width: 0,
+            height: 0,
+            next: 0,
+        }
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    /// Дописывает чёрные строки до `y` (не включая)
+    fn fill_to(&mut self, y: u32) -> Result<()> {
+        let black = vec![0; self.width as usize * 3];
+        while self.next < y.min(self.height) {
+            self.write(&black)?;
+            self.next += 1;
+        }
+        Ok(())
+    }
+
+    fn write(&mut self, bytes: &[u8]) -> Result<()> {
+        self.writer
+            .write_all(bytes)
+            .map_err(|e| SstvError::io(messages::image_lines_send_failed(), e))
+    }
+}
+
+impl<W: Write> ImageSink for StreamSink<W> {
+    fn begin(&mut self, width: u32, height: u32) -> Result<()> {
+        self.width = width;
+        self.height = height;
+        self.next = 0;
+        self.write(format!("P6\n{} {}\n255\n", width, height).as_bytes())
+    }
+
+    /// Строки идут сверху вниз; пропущенные заполняются чёрным, повторные не пишутся
+    fn write_row(&mut self, y: u32, row: &[u8]) -> Result<()> {
+        if y < self.next || y >= self.height {
+            return Ok(());
+        }
+        self.fill_to(y)?;
+        self.write(&row[..self.width as usize * 3])?;
+        self.next += 1;
+        Ok(())
+    }
+
+    fn finish(&mut self, _lines: u32) -> Result<()> {
+        self.fill_to(self.height)?;
+        self.writer
+            .flush()
+            .map_err(|e| SstvError::io(messages::image_lines_send_failed(), e))
+    }
+}
+
+/// Файл изображения в формате по расширению (PNG, если расширение не распознано);
+/// пишется целиком в конце. Для файла, растущего по строкам, — [`StreamSink`] над `File`
+pub struct FileSink {
+    path: PathBuf,
+    memory: MemorySink,
+}
+
+impl FileSink {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            memory: MemorySink::new(),
+        }
+    }
+}
+
+impl ImageSink for FileSink {
+    fn begin(&mut self, width: u32, height: u32) -> Result<()> {
+        self.memory.begin(width, height)
+    }
+
+    fn write_row(&mut self, y: u32, row: &[u8]) -> Result<()> {
+        self.memory.write_row(y, row)
+    }
+
+    fn finish(&mut self, _lines: u32) -> Result<()> {
+        let format = ImageFormat::from_path(&self.path).unwrap_or(ImageFormat::Png);
+        self.memory
+            .image()
+            .save_with_format(&self.path, format)
+            .map_err(|e| {
+                SstvError::io(
+                    messages::image_save_failed(&self.path.display().to_string()),
+                    e,
+                )
+            })
+    }
+}
@@ -0,0 +1,123 @@
+use crate::metrics::{self, MetricSpace};
+use crate::modes::MARTIN_M1;
+use crate::overlay;
+use crate::report::DecodeReport;
+use image::{DynamicImage, Rgb, RgbImage};
+
+/// Отступ между панелями и от краёв, пикселей
+const MARGIN: u32 = 12;
+/// Масштаб шрифта 5×7: каждая точка глифа — квадрат этого размера
+const TEXT_SCALE: u32 = 2;
+/// Ширина и высота знакоместа с промежутками, пикселей
+const CELL_WIDTH: u32 = 6 * TEXT_SCALE;
+const CELL_HEIGHT: u32 = 10 * TEXT_SCALE;
+
+const BACKGROUND: Rgb<u8> = Rgb([24, 24, 28]);
+const CAPTION: Rgb<u8> = Rgb([150, 150, 160]);
+const TEXT: Rgb<u8> = Rgb([225, 225, 225]);
+
+/// Снимок сравнения, как во вкладках GUI: исходное, результат и карта разницы в ряд,
+/// под ними метрики и отчёт декодера. Для документации и отчётов об ошибках без окна.
+///
+/// Подписи рисуются встроенным шрифтом 5×7 надписей только с ASCII: знаки вне него заменяются
+/// близкими (`–` → `-`, `×` → `x`) или `?`, поэтому отчёт лучше брать на английском
+pub struct Snapshot {
+    pub original: DynamicImage,
+    pub result: DynamicImage,
+    /// Строки текста под изображениями
+    pub lines: Vec<String>,
+}
+
+impl Snapshot {
+    /// Снимок с теми же строками, что во вкладке «Метрики»: сводка канала, качество,
+    /// отчёт декодера и принятый VIS; качество оценивается в пространстве `space`
+    pub fn new(original: &DynamicImage, report: &DecodeReport, space: MetricSpace) -> Self {
+        let quality = metrics::compare_in(original, &report.image, space);
+        let mut lines: Vec<String> = report.channel.iter().map(|c| c.describe()).collect();
+        lines.extend([quality.describe(), report.describe()]);
+        if let Some(vis) = &report.vis_detected {
+            lines.push(vis.describe());
+        }
+        Self {
+            original: original.clone(),
+            result: report.image.clone(),
+            lines,
+        }
+    }
+
+    /// Рисует снимок: панели в размере режима и текст с переносом по ширине трёх панелей
+    pub fn render(&self) -> RgbImage {
+        let (panel_width, panel_height) = (MARTIN_M1.width, MARTIN_M1.height);
+        let width = 3 * panel_width + 4 * MARGIN;
+        let columns = ((width - 2 * MARGIN) / CELL_WIDTH) as usize;
+        let text: Vec<String> = self
+            .lines
+            .iter()
+            .flat_map(|line| line.lines())
+            .flat_map(|line| wrap(line, columns))
+            .collect();
+        let panels_top = MARGIN + CELL_HEIGHT;
+        let text_top = panels_top + panel_height + MARGIN;
+        let height = text_top + text.len() as u32 * CELL_HEIGHT + MARGIN;
+
+        let mut canvas = RgbImage::from_pixel(width, height, BACKGROUND);
+        let panels = [
+            (
+                "ORIGINAL",
+                metrics::fit(&self.original, panel_width, panel_height),
+            ),
+            (
+                "RESULT",
+                metrics::fit(&self.result, panel_width, panel_height),
+            ),
+            (
+                "DIFF",
+                metrics::diff_image(&self.original, &self.result)
+                    .resize_exact(
+                        panel_width,
+                        panel_height,
+                        image::imageops::FilterType::Nearest,
+                    )
+                    .to_rgb8(),
+            ),
+        ];
+        for (k, (caption, panel)) in panels.iter().enumerate() {
+            let x = MARGIN + k as u32 * (panel_width + MARGIN);
+            draw_text(&mut canvas, x, MARGIN, caption, CAPTION);
+            image::imageops::replace(&mut canvas, panel, x as i64, panels_top as i64);
+        }
+        for (k, line) in text.iter().enumerate() {
+            let y = text_top + k as u32 * CELL_HEIGHT;
+            draw_text(&mut canvas, MARGIN, y, line, TEXT);
+        }
+        canvas
+    }
+}
+
+/// Разбивает строку на куски не длиннее `columns` знаков, по пробелам, где получается
+fn wrap(line: &str, columns: usize) -> Vec<String> {
+    let mut rows = Vec::new();
+    let mut row = String::new();
+    for word in line.split(' ') {
+        let len = row.chars().count();
+        if len > 0 && len + 1 + word.chars().count() > columns {
+            rows.push(std::mem::take(&mut row));
+        }
+        if !row.is_empty() {
+            row.push(' ');
+        }
+        row.push_str(word);
+        while row.chars().count() > columns {
+            let tail = row.chars().skip(columns).collect();
+            row = row.chars().take(columns).collect();
+            rows.push(std::mem::replace(&mut row, tail));
+        }
+    }
+    rows.push(row);
+    rows
+}
+
+/// Пишет `text` с левым верхним углом в (`x`, `y`); что не помещается, обрезается
+fn draw_text(canvas: &mut RgbImage, x: u32, y: u32, text: &str, color: Rgb<u8>) {
+    overlay::draw_pixel_text(canvas, x as i32, y as i32, text, TEXT_SCALE, color);
+}
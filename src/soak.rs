@@ -0,0 +1,344 @@
+use crate::decoder::DecoderBackend;
+use crate::encoder;
+use crate::fading::FadingModel;
+use crate::filters::FilterKind;
+use crate::fuzz::panic_message;
+use crate::messages;
+use crate::metrics;
+use crate::modes::MARTIN_M1;
+use crate::multipath::MultipathTap;
+use crate::processor::{ProcessingParams, SSTVProcessor};
+use image::DynamicImage;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::AtomicBool;
+use std::time::{Duration, Instant};
+
+/// Сколько первых ошибок хранит статистика: за часы одинаковые ошибки повторяются
+const MAX_KEPT_ERRORS: usize = 20;
+
+/// Параметры долгого прогона потокового тракта кодер → канал → декодер
+#[derive(Clone, Debug)]
+pub struct SoakParams {
+    /// Зерно первого цикла; канал цикла `k` строится из зерна `seed + k`
+    pub seed: u64,
+    /// Наименьший PSNR декодированного изображения относительно переданного, дБ, при
+    /// котором приём считается удачным
+    pub min_psnr_db: f64,
+    /// Циклов в начале, после которых память берётся за исходную: кэши, пулы потоков и
+    /// распределитель к этому времени выросли до рабочего размера
+    pub warmup_cycles: usize,
+    /// Наибольший допустимый рост памяти после разогрева, МБ
+    pub max_memory_growth_mb: f64,
+    /// Наименьшая допустимая доля удачных приёмов, 0–1
+    pub min_success_ratio: f32,
+}
+
+impl Default for SoakParams {
+    fn default() -> Self {
+        Self {
+            seed: 0,
+            min_psnr_db: 15.0,
+            warmup_cycles: 3,
+            max_memory_growth_mb: 64.0,
+            min_success_ratio: 0.9,
+        }
+    }
+}
+
+/// Итог одного цикла
+#[derive(Clone, Debug)]
+pub struct SoakCycle {
+    pub seed: u64,
+    /// Декодировано строк; 0 — при ошибке
+    pub lines: u32,
+    /// PSNR декодированного изображения относительно переданного, дБ
+    pub psnr_db: Option<f64>,
+    /// Изображение принято целиком и не хуже [`SoakParams::min_psnr_db`]
+    pub success: bool,
+    /// Ошибка или паника цикла
+    pub error: Option<String>,
+    pub elapsed: Duration,
+    /// Занятая процессом память после цикла, байт, если известна
+    pub resident: Option<u64>,
+}
+
+impl SoakCycle {
+    pub fn describe(&self) -> String {
+        match (&self.error, self.psnr_db) {
+            (Some(error), _) => messages::soak_cycle_error(self.seed, error),
+            (None, psnr) => messages::soak_cycle(
+                self.seed,
+                self.lines,
+                psnr.unwrap_or(f64::NAN),
+                self.elapsed.as_secs_f32(),
+            ),
+        }
+    }
+}
+
+/// Накопленная статистика прогона
+#[derive(Clone, Debug, Default)]
+pub struct SoakStats {
+    pub cycles: usize,
+    /// Приёмов целиком и не хуже порога PSNR
+    pub successes: usize,
+    /// Циклов, закончившихся ошибкой или паникой
+    pub errors: usize,
+    /// Первые ошибки с зёрнами циклов
+    pub first_errors: Vec<(u64, String)>,
+    /// Худший PSNR удачного или неудачного приёма, дБ
+    pub worst_psnr_db: Option<f64>,
+    psnr_sum: f64,
+    psnr_count: usize,
+    /// Память после разогрева, байт
+    pub baseline_memory: Option<u64>,
+    /// Наибольшая память после разогрева, байт
+    pub peak_memory: Option<u64>,
+    /// Память после последнего цикла, байт
+    pub last_memory: Option<u64>,
+    /// Суммарное время циклов
+    pub busy: Duration,
+}
+
+impl SoakStats {
+    /// Учитывает цикл; память до конца разогрева (`warmup` циклов) не считается
+    pub fn record(&mut self, cycle: &SoakCycle, warmup: usize) {
+        self.cycles += 1;
+        self.busy += cycle.elapsed;
+        if cycle.success {
+            self.successes += 1;
+        }
+        if let Some(error) = &cycle.error {
+            self.errors += 1;
+            if self.first_errors.len() < MAX_KEPT_ERRORS {
+                self.first_errors.push((cycle.seed, error.clone()));
+            }
+        }
+        if let Some(psnr) = cycle.psnr_db {
+            self.psnr_sum += psnr;
+            self.psnr_count += 1;
+            self.worst_psnr_db = Some(self.worst_psnr_db.map_or(psnr, |w| w.min(psnr)));
+        }
+        self.last_memory = cycle.resident;
+        if let Some(memory) = cycle.resident
+            && self.cycles >= warmup
+        {
+            self.baseline_memory.get_or_insert(memory);
+            self.peak_memory = Some(self.peak_memory.map_or(memory, |p| p.max(memory)));
+        }
+    }
+
+    /// Доля удачных приёмов, 0–1
+    pub fn success_ratio(&self) -> f32 {
+        self.successes as f32 / self.cycles.max(1) as f32
+    }
+
+    pub fn mean_psnr_db(&self) -> Option<f64> {
+        (self.psnr_count > 0).then(|| self.psnr_sum / self.psnr_count as f64)
+    }
+
+    /// Рост памяти от конца разогрева до последнего цикла, МБ
+    pub fn memory_growth_mb(&self) -> Option<f64> {
+        let (base, last) = (self.baseline_memory?, self.last_memory?);
+        Some((last as f64 - base as f64) / (1024.0 * 1024.0))
+    }
+
+    /// Нарушения пределов `params`: ошибки циклов, рост памяти, доля удачных приёмов.
+    /// Пусто — прогон пройден
+    pub fn problems(&self, params: &SoakParams) -> Vec<String> {
+        let mut problems = Vec::new();
+        if self.errors > 0 {
+            problems.push(messages::soak_errors(self.errors));
+        }
+        if let Some(growth) = self.memory_growth_mb()
+            && growth > params.max_memory_growth_mb
+        {
+            problems.push(messages::soak_memory_grew(
+                growth,
+                params.max_memory_growth_mb,
+            ));
+        }
+        if self.cycles > 0 && self.success_ratio() < params.min_success_ratio {
+            problems.push(messages::soak_low_success(
+                self.success_ratio() * 100.0,
+                params.min_success_ratio * 100.0,
+            ));
+        }
+        problems
+    }
+
+    pub fn describe(&self) -> String {
+        let megabytes = |bytes: Option<u64>| bytes.map_or(f64::NAN, |b| b as f64 / 1048576.0);
+        format!(
+            "{}; {}",
+            messages::soak_describe(
+                self.cycles,
+                self.successes,
+                self.success_ratio() * 100.0,
+                self.errors,
+                self.mean_psnr_db().unwrap_or(f64::NAN),
+            ),
+            messages::soak_memory(
+                megabytes(self.last_memory),
+                megabytes(self.peak_memory),
+                self.memory_growth_mb().unwrap_or(f64::NAN),
+            )
+        )
+    }
+}
+
+/// Случайный канал, который проходит потоковый тракт (см. [`SSTVProcessor::stream`]):
+/// гауссов шум до уровня 30, замирания, расстройка до ±20 Гц с уходом, одно отражение
+/// без сдвига фазы, случайные выпадения, полосовой фильтр и АРУ приёмника. Декодер —
+/// встроенный, другой поток не принимает
+pub fn random_channel(rng: &mut impl Rng, seed: u64) -> ProcessingParams {
+    let mut params = ProcessingParams {
+        seed: Some(seed),
+        ..ProcessingParams::default()
+    };
+    params.decoder.backend = DecoderBackend::Native;
+    // Ретардер по умолчанию ждёт картинку призрака, в долгом прогоне её нет
+    params.retarder.level = 0.0;
+    params.noise.level = rng.random_range(0..=30);
+    if rng.random_bool(0.5) {
+        params.fading.model = FadingModel::ALL[rng.random_range(0..FadingModel::ALL.len())];
+        params.fading.depth = rng.random_range(0.0..=0.5);
+        params.fading.rate_hz = rng.random_range(0.1..=2.0);
+    }
+    if rng.random_bool(0.5) {
+        params.drift.offset_hz = rng.random_range(-20.0..=20.0);
+        params.drift.drift_hz_per_s = rng.random_range(-0.1..=0.1);
+        params.decoder.afc = true;
+    }
+    if rng.random_bool(0.3) {
+        params.multipath.taps = vec![MultipathTap {
+            delay_ms: rng.random_range(0.1..=2.0),
+            gain: rng.random_range(-0.4..=0.4),
+            phase_deg: 0.0,
+        }];
+    }
+    if rng.random_bool(0.3) {
+        params.dropout.rate_per_min = rng.random_range(0.5..=4.0);
+        params.dropout.duration_ms = rng.random_range(20.0..=300.0);
+    }
+    if rng.random_bool(0.3) {
+        params.filter.enabled = true;
+        params.filter.kind = FilterKind::Bandpass;
+        params.filter.freq_hz = rng.random_range(1700.0..=2100.0);
+        params.filter.q = rng.random_range(1.0..=3.0);
+    }
+    params.agc.enabled = rng.random_bool(0.3);
+    params
+}
+
+/// Один цикл: канал из зерна `seed`, потоковый прогон `image` через `processor` и
+/// сравнение принятого с переданным. Ошибки и паники входят в итог; после паники
+/// процессор пересоздаётся, чтобы следующий цикл не унаследовал его состояние
+pub fn run_cycle(
+    processor: &mut SSTVProcessor,
+    image: &DynamicImage,
+    seed: u64,
+    params: &SoakParams,
+) -> SoakCycle {
+    let started = Instant::now();
+    let channel = random_channel(&mut StdRng::seed_from_u64(seed), seed);
+    let outcome = panic::catch_unwind(AssertUnwindSafe(|| {
+        processor.try_set_params(channel)?;
+        processor.process_streaming(image, None, |_, _| {}, &AtomicBool::new(false))
+    }));
+
+    let mut cycle = SoakCycle {
+        seed,
+        lines: 0,
+        psnr_db: None,
+        success: false,
+        error: None,
+        elapsed: Duration::ZERO,
+        resident: None,
+    };
+    match outcome {
+        Ok(Ok(report)) => {
+            let reference = encoder::fit_to_mode(image, &MARTIN_M1);
+            let psnr = metrics::compare(&reference, &report.image).psnr;
+            cycle.lines = report.lines_decoded;
+            cycle.psnr_db = Some(psnr);
+            cycle.success = report.lines_decoded >= MARTIN_M1.height
+                && !report.partial
+                && psnr >= params.min_psnr_db;
+        }
+        Ok(Err(e)) => cycle.error = Some(e.to_string()),
+        Err(payload) => {
+            cycle.error = Some(messages::fuzz_panic(&panic_message(payload.as_ref())));
+            *processor = SSTVProcessor::new();
+        }
+    }
+    cycle.elapsed = started.elapsed();
+    cycle.resident = resident_memory();
+    cycle
+}
+
+/// Гоняет циклы с зёрнами `seed`, `seed + 1`, … на одном процессоре, пока `next`
+/// возвращает `true`. `next` получает каждый цикл и статистику после него
+pub fn run(
+    params: &SoakParams,
+    image: &DynamicImage,
+    mut next: impl FnMut(&SoakCycle, &SoakStats) -> bool,
+) -> SoakStats {
+    let mut processor = SSTVProcessor::new();
+    let mut stats = SoakStats::default();
+    let mut seed = params.seed;
+    loop {
+        let cycle = run_cycle(&mut processor, image, seed, params);
+        stats.record(&cycle, params.warmup_cycles);
+        if !next(&cycle, &stats) {
+            return stats;
+        }
+        seed = seed.wrapping_add(1);
+    }
+}
+
+/// Занятая процессом физическая память, байт (только Linux)
+pub fn resident_memory() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|l| l.starts_with("VmRSS:"))?;
+    let kb: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kb * 1024)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conformance;
+
+    #[test]
+    fn soak_cycles_receive_streamed_chart() {
+        let params = SoakParams {
+            warmup_cycles: 1,
+            ..SoakParams::default()
+        };
+        let image = conformance::test_pattern(&MARTIN_M1);
+        let mut seeds = Vec::new();
+        let stats = run(&params, &image, |cycle, stats| {
+            seeds.push(cycle.seed);
+            stats.cycles < 2
+        });
+        assert_eq!(seeds, [0, 1]);
+        assert_eq!((stats.cycles, stats.errors), (2, 0));
+        assert_eq!(stats.successes, 2, "{}", stats.describe());
+        assert!(stats.problems(&params).is_empty());
+
+        // Треть передач не принята — ниже доли по умолчанию
+        let mut lost = stats.clone();
+        lost.cycles += 1;
+        assert_eq!(lost.problems(&params).len(), 1);
+
+        // Рост памяти сверх предела — нарушение
+        let mut grown = stats.clone();
+        grown.last_memory = grown.baseline_memory.map(|m| m + (100 << 20));
+        if grown.baseline_memory.is_some() {
+            assert_eq!(grown.problems(&params).len(), 1);
+        }
+    }
+}
@@ -0,0 +1,60 @@
+use crate::dsp;
+use image::{DynamicImage, Rgb, RgbImage};
+use std::ops::Range;
+
+/// Водопад сигнала: время идёт сверху вниз, частота растёт слева направо.
+/// Спектр считается БПФ коротких окон, см. [`dsp::spectrogram`]
+#[derive(Clone, Debug)]
+pub struct Spectrogram {
+    /// Число строк изображения (отсчётов времени по всему сигналу)
+    pub lines: usize,
+    /// Число столбцов изображения (полос частот)
+    pub bins: usize,
+    /// Диапазон частот, Гц
+    pub freq: Range<f32>,
+    /// Динамический диапазон от самой мощной точки, дБ
+    pub range_db: f32,
+}
+
+impl Default for Spectrogram {
+    fn default() -> Self {
+        Self {
+            lines: 512,
+            bins: 400,
+            freq: 0.0..4000.0,
+            range_db: 60.0,
+        }
+    }
+}
+
+impl Spectrogram {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Рисует водопад сигнала; шкала «чёрный — красный — жёлтый — белый»
+    pub fn render(&self, samples: &[f32], sample_rate: u32) -> DynamicImage {
+        let power = dsp::spectrogram(
+            samples,
+            sample_rate,
+            self.lines,
+            self.bins,
+            self.freq.clone(),
+        );
+        let max = power.iter().copied().fold(f32::MIN, f32::max);
+        let range_db = self.range_db.max(f32::EPSILON);
+
+        let image = RgbImage::from_fn(self.bins as u32, self.lines as u32, |bin, line| {
+            let db = power[bin as usize * self.lines + line as usize];
+            heat((db - max) / range_db + 1.0)
+        });
+        DynamicImage::ImageRgb8(image)
+    }
+}
+
+/// Цвет тепловой шкалы для уровня 0–1
+pub(crate) fn heat(level: f32) -> Rgb<u8> {
+    let v = level.clamp(0.0, 1.0);
+    let channel = |k: f32| ((3.0 * v - k).clamp(0.0, 1.0) * 255.0) as u8;
+    Rgb([channel(0.0), channel(1.0), channel(2.0)])
+}
@@ -0,0 +1,215 @@
+use crate::dsp::Biquad;
+use crate::effect;
+use crate::messages;
+use crate::modes::LEADER_HZ;
+use serde::{Deserialize, Serialize};
+
+/// Полоса детектора лидер-тона, Гц: с запасом на расстройку приёмника
+const LEADER_BAND_HZ: f32 = 150.0;
+/// Сколько лидер-тон должен держаться над порогом, чтобы шумоподавитель открылся, мс:
+/// случайный всплеск шума в полосе короче
+const CONFIRM_MS: f32 = 100.0;
+
+/// Шумоподавитель приёмника: потоковый декодер получает сигнал, только когда в нём
+/// слышен лидер-тон заголовка, и не рисует изображения из шума диапазона
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SquelchParams {
+    pub enabled: bool,
+    /// Доля мощности в полосе лидер-тона, дБ к мощности всего сигнала, при которой
+    /// шумоподавитель открывается
+    pub open_db: f32,
+    /// Гистерезис, дБ: открытый шумоподавитель держится, пока доля выше
+    /// `open_db - hysteresis_db`
+    pub hysteresis_db: f32,
+    /// Наименьший уровень сигнала, dBFS: тише шумоподавитель не открывается
+    pub min_level_db: f32,
+    /// Сколько ждать начала изображения после пропажи лидер-тона, с: VIS и поиск
+    /// заголовка идут уже без него
+    pub hold_secs: f32,
+    /// Бросать изображения без VIS или с неверной чётностью
+    pub require_vis: bool,
+}
+
+impl Default for SquelchParams {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            open_db: -3.0,
+            hysteresis_db: 6.0,
+            min_level_db: -50.0,
+            hold_secs: 1.5,
+            require_vis: true,
+        }
+    }
+}
+
+impl SquelchParams {
+    /// Порог закрытия, дБ
+    pub fn close_db(&self) -> f32 {
+        self.open_db - self.hysteresis_db.max(0.0)
+    }
+
+    pub fn describe(&self) -> String {
+        if self.enabled {
+            messages::squelch_describe(self.open_db, self.close_db(), self.min_level_db)
+        } else {
+            messages::squelch_off().to_string()
+        }
+    }
+}
+
+/// Детектор лидер-тона с гистерезисом. Считает блоки сигнала на частоте
+/// `sample_rate` и решает, открыт ли шумоподавитель
+#[derive(Clone, Debug)]
+pub struct Squelch {
+    params: SquelchParams,
+    sample_rate: u32,
+    band: Biquad,
+    open: bool,
+    /// Сколько сэмплов подряд лидер-тон над порогом открытия
+    above: usize,
+    /// Сколько сэмплов подряд доля ниже порога закрытия
+    below: usize,
+    /// Доля мощности в полосе лидер-тона в последнем блоке, дБ
+    leader_db: f32,
+}
+
+impl Squelch {
+    pub fn new(params: SquelchParams, sample_rate: u32) -> Self {
+        Self {
+            params,
+            sample_rate,
+            band: Biquad::bandpass(sample_rate, LEADER_HZ, LEADER_HZ / LEADER_BAND_HZ),
+            open: false,
+            above: 0,
+            below: 0,
+            leader_db: effect::power_db(0.0),
+        }
+    }
+
+    pub fn params(&self) -> &SquelchParams {
+        &self.params
+    }
+
+    /// Открыт ли шумоподавитель; выключенный открыт всегда
+    pub fn is_open(&self) -> bool {
+        !self.params.enabled || self.open
+    }
+
+    /// Доля мощности в полосе лидер-тона в последнем блоке, дБ
+    pub fn leader_db(&self) -> f32 {
+        self.leader_db
+    }
+
+    /// Оценивает следующий блок и возвращает, открыт ли шумоподавитель после него.
+    /// `held` — идёт приём изображения: строки не похожи на лидер-тон, но закрываться
+    /// посреди изображения нельзя
+    pub fn update(&mut self, block: &[f32], held: bool) -> bool {
+        if block.is_empty() {
+            return self.is_open();
+        }
+        let total = block.iter().map(|&s| s * s).sum::<f32>() / block.len() as f32;
+        let band = block
+            .iter()
+            .map(|&s| self.band.process(s).powi(2))
+            .sum::<f32>()
+            / block.len() as f32;
+        self.leader_db = if total > 0.0 {
+            effect::power_db(band / total)
+        } else {
+            effect::power_db(0.0)
+        };
+        let loud = effect::power_db(total) >= self.params.min_level_db;
+
+        if !self.open {
+            if loud && self.leader_db >= self.params.open_db {
+                self.above += block.len();
+            } else {
+                self.above = 0;
+            }
+            if self.above as f32 >= CONFIRM_MS * self.sample_rate as f32 / 1000.0 {
+                self.open = true;
+                self.below = 0;
+                tracing::debug!("{}", messages::squelch_opened(self.leader_db));
+            }
+        } else if held || (loud && self.leader_db >= self.params.close_db()) {
+            self.below = 0;
+        } else {
+            self.below += block.len();
+            if self.below as f32 >= self.params.hold_secs.max(0.0) * self.sample_rate as f32 {
+                self.close();
+            }
+        }
+        self.is_open()
+    }
+
+    /// Закрывает шумоподавитель: следующее изображение снова ждёт лидер-тон
+    pub fn close(&mut self) {
+        if self.open {
+            tracing::debug!("{}", messages::squelch_closed());
+        }
+        self.open = false;
+        self.above = 0;
+        self.below = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+    use rsstv::SAMPLE_RATE;
+
+    #[test]
+    fn squelch_opens_on_leader_tone_only() {
+        let rate = SAMPLE_RATE as u32;
+        let block = rate as usize / 20;
+        let mut rng = StdRng::seed_from_u64(11);
+        let mut noise =
+            |len: usize| -> Vec<f32> { (0..len).map(|_| rng.random_range(-0.3f32..0.3)).collect() };
+        let params = SquelchParams {
+            enabled: true,
+            ..Default::default()
+        };
+        let mut squelch = Squelch::new(params, rate);
+        for _ in 0..200 {
+            assert!(!squelch.update(&noise(block), false));
+        }
+
+        // Лидер-тон над шумом открывает шумоподавитель за CONFIRM_MS
+        let mut phase = 0.0f64;
+        let mut tone = |len: usize, noise: Vec<f32>| -> Vec<f32> {
+            noise
+                .into_iter()
+                .take(len)
+                .map(|n| {
+                    phase += 2.0 * std::f64::consts::PI * LEADER_HZ as f64 / rate as f64;
+                    0.5 * phase.sin() as f32 + 0.2 * n
+                })
+                .collect()
+        };
+        let opened = (0..6).any(|_| squelch.update(&tone(block, noise(block)), false));
+        assert!(opened);
+
+        // Приём изображения держит его открытым, без него шум закрывает через hold_secs
+        for _ in 0..100 {
+            assert!(squelch.update(&noise(block), true));
+        }
+        let hold_blocks = (params.hold_secs * 20.0).ceil() as usize;
+        for _ in 0..hold_blocks - 1 {
+            assert!(squelch.update(&noise(block), false));
+        }
+        assert!(!squelch.update(&noise(block), false));
+
+        // Тихий лидер-тон ниже min_level_db не открывает
+        let quiet: Vec<f32> = tone(block, noise(block)).iter().map(|s| s * 1e-3).collect();
+        for _ in 0..10 {
+            assert!(!squelch.update(&quiet, false));
+        }
+
+        let mut off = Squelch::new(SquelchParams::default(), rate);
+        assert!(off.update(&noise(block), false));
+    }
+}
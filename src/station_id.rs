@@ -0,0 +1,362 @@
+use crate::dsp::goertzel::goertzel_power;
+use crate::encoder::{self, Tone};
+use rsstv::SAMPLE_RATE;
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+
+/// Пауза между концом изображения и опознавателем, мс
+const GAP_MS: f64 = 300.0;
+/// Фронты посылок CW, мс: без них жёсткая манипуляция даёт щелчки по всей полосе
+const CW_RAMP_MS: f64 = 5.0;
+/// Длительность бита FSK ID, мс
+const FSK_BIT_MS: f64 = 22.0;
+/// Тоны единицы и нуля FSK ID, Гц
+const FSK_ONE_HZ: f32 = 1900.0;
+const FSK_ZERO_HZ: f32 = 2100.0;
+/// Служебные символы FSK ID: начало посылки и её конец
+const FSK_START: [u8; 2] = [0x20, 0x2A];
+const FSK_END: u8 = 0x01;
+/// Бит FSK ID принимается, если два его тона несут не меньше этой доли мощности окна
+const FSK_MIN_PURITY: f32 = 0.5;
+/// Шаг поиска посылок FSK ID, мс
+const FSK_SEARCH_STEP_MS: f64 = 1.0;
+/// Больше символов в одной посылке не читается: без концевого символа это не FSK ID
+const FSK_MAX_SYMBOLS: usize = 256;
+
+/// Способ передачи позывного
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IdMode {
+    /// Азбука Морзе тоном `tone_hz` со скоростью `wpm`
+    Cw,
+    /// Цифровой опознаватель как в MMSSTV: 6-битные символы, 1900/2100 Гц, 22 мс на бит
+    Fsk,
+}
+
+impl IdMode {
+    pub const ALL: &'static [IdMode] = &[IdMode::Cw, IdMode::Fsk];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            IdMode::Cw => "CW",
+            IdMode::Fsk => "FSK",
+        }
+    }
+}
+
+impl clap::ValueEnum for IdMode {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[Self::Cw, Self::Fsk]
+    }
+
+    fn to_possible_value<'a>(&self) -> Option<clap::builder::PossibleValue> {
+        Some(match self {
+            Self::Cw => clap::builder::PossibleValue::new("cw"),
+            Self::Fsk => clap::builder::PossibleValue::new("fsk"),
+        })
+    }
+}
+
+/// Опознаватель станции, передаваемый после изображения
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IdParams {
+    /// Позывной; пустой — опознаватель не передаётся
+    pub callsign: String,
+    pub mode: IdMode,
+    /// Скорость CW, слов в минуту (PARIS)
+    pub wpm: f32,
+    /// Тон CW, Гц
+    pub tone_hz: f32,
+    /// Текст, передаваемый отдельной посылкой FSK ID после позывного (в любом режиме),
+    /// например метаданные передачи, см. [`Metadata::fsk_text`](crate::Metadata::fsk_text);
+    /// пустой — не передаётся
+    pub text: String,
+}
+
+impl Default for IdParams {
+    fn default() -> Self {
+        Self {
+            callsign: String::new(),
+            mode: IdMode::Cw,
+            wpm: 20.0,
+            tone_hz: 800.0,
+            text: String::new(),
+        }
+    }
+}
+
+impl IdParams {
+    pub fn is_enabled(&self) -> bool {
+        !self.callsign.trim().is_empty()
+    }
+
+    /// После изображения передаётся позывной или текст
+    pub fn is_transmitted(&self) -> bool {
+        self.is_enabled() || !self.text.trim().is_empty()
+    }
+
+    /// Тоны опознавателя вместе с паузой перед ним. Символы, которых нет в азбуке Морзе
+    /// или в 6-битном наборе FSK ID, пропускаются
+    pub fn tones(&self) -> Vec<Tone> {
+        if !self.is_enabled() {
+            return Vec::new();
+        }
+        let callsign = self.callsign.trim().to_ascii_uppercase();
+        let mut tones = vec![silence(GAP_MS)];
+        match self.mode {
+            IdMode::Cw => self.cw_tones(&callsign, &mut tones),
+            IdMode::Fsk => fsk_tones(&callsign, &mut tones),
+        }
+        tones
+    }
+
+    /// Тоны посылки FSK ID с текстом [`text`](Self::text) вместе с паузой перед ней
+    pub fn text_tones(&self) -> Vec<Tone> {
+        let text = self.text.trim().to_ascii_uppercase();
+        if text.is_empty() {
+            return Vec::new();
+        }
+        let mut tones = vec![silence(GAP_MS)];
+        fsk_tones(&text, &mut tones);
+        tones
+    }
+
+    /// Сэмплы опознавателя и посылки с текстом на частоте кодера с амплитудой `amplitude`
+    pub fn synthesize(&self, amplitude: f32) -> Vec<f32> {
+        let mut samples = match self.mode {
+            IdMode::Cw => keyed(&self.tones(), amplitude),
+            IdMode::Fsk => encoder::synthesize(&self.tones(), amplitude),
+        };
+        samples.extend(encoder::synthesize(&self.text_tones(), amplitude));
+        samples
+    }
+
+    /// Длина опознавателя и посылки с текстом на частоте кодера, сэмплов
+    pub fn sample_count(&self) -> usize {
+        let samples = |tones: Vec<Tone>| {
+            let total_us: f64 = tones.iter().map(|t| t.duration_us).sum();
+            (total_us * 1e-6 * SAMPLE_RATE as f64).round() as usize
+        };
+        samples(self.tones()) + samples(self.text_tones())
+    }
+
+    /// Посылки Морзе: точка — одна единица, тире — три, пауза между посылками — одна,
+    /// между знаками — три, между словами — семь. Единица — 1200 / wpm мс; последняя
+    /// пауза в единицу даёт стихнуть фронту последней посылки
+    fn cw_tones(&self, callsign: &str, tones: &mut Vec<Tone>) {
+        let unit_ms = 1200.0 / self.wpm.max(1.0) as f64;
+        let mut first = true;
+        for c in callsign.chars() {
+            if c.is_whitespace() {
+                // Пробел дополняет паузу между знаками до паузы между словами
+                tones.push(silence(4.0 * unit_ms));
+                continue;
+            }
+            let Some(code) = morse(c) else {
+                continue;
+            };
+            if !first {
+                tones.push(silence(3.0 * unit_ms));
+            }
+            first = false;
+            for (k, element) in code.chars().enumerate() {
+                if k > 0 {
+                    tones.push(silence(unit_ms));
+                }
+                let units = if element == '-' { 3.0 } else { 1.0 };
+                tones.push(Tone {
+                    freq_hz: self.tone_hz,
+                    duration_us: units * unit_ms * 1000.0,
+                });
+            }
+        }
+        tones.push(silence(unit_ms));
+    }
+}
+
+/// Символы FSK ID: заголовок, знаки позывного кодом ASCII − 0x20 и концевой символ;
+/// каждый — шесть бит младшим первым
+fn fsk_tones(callsign: &str, tones: &mut Vec<Tone>) {
+    let chars = callsign
+        .bytes()
+        .filter(|b| (0x20..0x60).contains(b))
+        .map(|b| b - 0x20);
+    for symbol in FSK_START.into_iter().chain(chars).chain([FSK_END]) {
+        for bit in 0..6 {
+            let freq_hz = if (symbol >> bit) & 1 == 1 {
+                FSK_ONE_HZ
+            } else {
+                FSK_ZERO_HZ
+            };
+            tones.push(Tone {
+                freq_hz,
+                duration_us: FSK_BIT_MS * 1000.0,
+            });
+        }
+    }
+}
+
+/// Читает посылки FSK ID в сигнале частоты [`SAMPLE_RATE`]: позывные и тексты в порядке
+/// передачи. Окно в бит сдвигается по сигналу с шагом 1 мс; посылка начинается там, где
+/// окна подряд читают заголовок FSK ID с чистыми тонами, и принимается, только если
+/// дочитана до концевого символа
+pub fn read_fsk(samples: &[f32]) -> Vec<String> {
+    let fs = SAMPLE_RATE as f64;
+    let bit_len = (FSK_BIT_MS * 1e-3 * fs).round() as usize;
+    let step = (FSK_SEARCH_STEP_MS * 1e-3 * fs).round().max(1.0) as usize;
+    // Бит окна с началом в `start`: `None`, если тоны FSK в нём не преобладают
+    let bit_at = |start: f64| -> Option<(bool, f32)> {
+        let start = start.round() as usize;
+        let window = samples.get(start..start + bit_len)?;
+        let energy: f32 = window.iter().map(|s| s * s).sum();
+        let one = goertzel_power(window, FSK_ONE_HZ, SAMPLE_RATE as u32);
+        let zero = goertzel_power(window, FSK_ZERO_HZ, SAMPLE_RATE as u32);
+        // Тон амплитуды A даёт (A·N/2)² при энергии окна A²·N/2
+        let purity = (one + zero) / (energy * bit_len as f32 / 2.0).max(1e-12);
+        let contrast = (one - zero).abs() / (one + zero).max(1e-12);
+        (purity >= FSK_MIN_PURITY).then_some((one > zero, contrast))
+    };
+    let bit_step = FSK_BIT_MS * 1e-3 * fs;
+    let symbol_at = |start: f64| -> Option<u8> {
+        (0..6).try_fold(0u8, |symbol, bit| {
+            let (one, _) = bit_at(start + bit as f64 * bit_step)?;
+            Some(symbol | (one as u8) << bit)
+        })
+    };
+    // Согласованность заголовка: сумма контраста его бит, если он читается
+    let header_at = |start: f64| -> Option<f32> {
+        let mut score = 0.0;
+        for (k, &symbol) in FSK_START.iter().enumerate() {
+            for bit in 0..6 {
+                let (one, contrast) = bit_at(start + (k * 6 + bit) as f64 * bit_step)?;
+                if one != ((symbol >> bit) & 1 == 1) {
+                    return None;
+                }
+                score += contrast;
+            }
+        }
+        Some(score)
+    };
+
+    let mut bursts = Vec::new();
+    let mut pos = 0usize;
+    while pos + bit_len <= samples.len() {
+        if header_at(pos as f64).is_none() {
+            pos += step;
+            continue;
+        }
+        // Первое окно с заголовком захватывает край бита: лучшее — в пределах бита дальше
+        let start = (pos..pos + bit_len)
+            .step_by(step)
+            .filter_map(|p| Some((p, header_at(p as f64)?)))
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .map_or(pos, |(p, _)| p) as f64;
+        let mut text = String::new();
+        let mut end = None;
+        for k in FSK_START.len()..FSK_START.len() + FSK_MAX_SYMBOLS {
+            let at = start + (k * 6) as f64 * bit_step;
+            match symbol_at(at) {
+                Some(FSK_END) => {
+                    end = Some(at + 6.0 * bit_step);
+                    break;
+                }
+                Some(symbol) => text.push((symbol + 0x20) as char),
+                None => break,
+            }
+        }
+        match end {
+            Some(end) => {
+                bursts.push(text);
+                pos = end.round() as usize;
+            }
+            None => pos += bit_len,
+        }
+    }
+    bursts
+}
+
+/// Синтез CW: тон с непрерывной фазой, включаемый и выключаемый плавно за `CW_RAMP_MS`
+/// по форме sin², границы посылок — как в [`encoder::synthesize`]
+fn keyed(tones: &[Tone], amplitude: f32) -> Vec<f32> {
+    let fs = SAMPLE_RATE as f64;
+    let ramp_step = 1.0 / (CW_RAMP_MS / 1000.0 * fs).max(1.0);
+    let mut samples = Vec::new();
+    let (mut phase, mut step, mut level) = (0.0f64, 0.0f64, 0.0f64);
+    let mut end_us = 0.0f64;
+    for t in tones {
+        end_us += t.duration_us;
+        let end_sample = (end_us * 1e-6 * fs).round() as usize;
+        let on = t.freq_hz > 0.0;
+        if on {
+            step = 2.0 * PI * t.freq_hz as f64 / fs;
+        }
+        while samples.len() < end_sample {
+            level = if on {
+                (level + ramp_step).min(1.0)
+            } else {
+                (level - ramp_step).max(0.0)
+            };
+            let gain = (level * PI / 2.0).sin().powi(2);
+            samples.push((phase.sin() * gain) as f32 * amplitude);
+            phase = (phase + step) % (2.0 * PI);
+        }
+    }
+    samples
+}
+
+fn silence(ms: f64) -> Tone {
+    Tone {
+        freq_hz: 0.0,
+        duration_us: ms * 1000.0,
+    }
+}
+
+/// Знак азбуки Морзе: точки и тире
+fn morse(c: char) -> Option<&'static str> {
+    Some(match c {
+        'A' => ".-",
+        'B' => "-...",
+        'C' => "-.-.",
+        'D' => "-..",
+        'E' => ".",
+        'F' => "..-.",
+        'G' => "--.",
+        'H' => "....",
+        'I' => "..",
+        'J' => ".---",
+        'K' => "-.-",
+        'L' => ".-..",
+        'M' => "--",
+        'N' => "-.",
+        'O' => "---",
+        'P' => ".--.",
+        'Q' => "--.-",
+        'R' => ".-.",
+        'S' => "...",
+        'T' => "-",
+        'U' => "..-",
+        'V' => "...-",
+        'W' => ".--",
+        'X' => "-..-",
+        'Y' => "-.--",
+        'Z' => "--..",
+        '0' => "-----",
+        '1' => ".----",
+        '2' => "..---",
+        '3' => "...--",
+        '4' => "....-",
+        '5' => ".....",
+        '6' => "-....",
+        '7' => "--...",
+        '8' => "---..",
+        '9' => "----.",
+        '/' => "-..-.",
+        '?' => "..--..",
+        '.' => ".-.-.-",
+        ',' => "--..--",
+        '=' => "-...-",
+        '-' => "-....-",
+        _ => return None,
+    })
+}
@@ -0,0 +1,628 @@
+use crate::effect::{
+    self, BlockProcessor, ChannelEffect, EffectMix, SanitizeEvent, SanitizeMode, StreamContext,
+};
+use crate::encoder::{self, EncoderBackend, EncoderParams, NativeEncoder, Synth};
+use crate::error::{Result, SstvError};
+use crate::messages;
+use crate::modes::MARTIN_M1;
+use crate::report::LevelMeter;
+use crate::signal;
+use image::{DynamicImage, Rgb, RgbImage};
+use rsstv::SAMPLE_RATE;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::ops::Range;
+use std::path::Path;
+
+/// Длина блока потоковой обработки по умолчанию, сэмплов: около 0,1 с на частоте кодера
+pub const DEFAULT_BLOCK_LEN: usize = 4096;
+
+/// Кодер, выдающий сигнал блоками по `block_len` сэмплов (последний — короче) на частоте
+/// кодера [`SAMPLE_RATE`]. Встроенный кодер синтезирует строку за строкой по мере надобности,
+/// поэтому в памяти лежит не больше строки сигнала; rsstv кодирует изображение целиком
+/// и только нарезает готовый сигнал. Длина и тело изображения известны до первого блока.
+///
+/// Сэмплы те же, что у [`encoder::encode`], кроме опознавателя станции: он идёт с
+/// амплитудой кодера, а не с измеренным пиком изображения
+pub struct BlockEncoder {
+    source: Source,
+    block_len: usize,
+    len: usize,
+    image_span: Range<usize>,
+    /// Сэмплов выдано
+    pos: usize,
+    /// Синтезированные, но ещё не выданные сэмплы
+    pending: Vec<f32>,
+}
+
+/// Откуда берутся сэмплы [`BlockEncoder`]
+enum Source {
+    /// Встроенный кодер: изображение в разрешении режима и следующий участок синтеза
+    Native {
+        encoder: NativeEncoder,
+        rgb: RgbImage,
+        synth: Synth,
+        /// Следующая строка; `None` — заголовок ещё не синтезирован
+        next_line: Option<u32>,
+        id_done: bool,
+    },
+    /// Готовый сигнал rsstv
+    Buffered(Vec<f32>),
+}
+
+impl BlockEncoder {
+    /// Кодер изображения `image` с параметрами `params`; `block_len` не меньше сэмпла
+    pub fn new(image: &DynamicImage, params: &EncoderParams, block_len: usize) -> Self {
+        let block_len = block_len.max(1);
+        let source = match params.backend {
+            EncoderBackend::Rsstv => Source::Buffered(encoder::encode(image, params)),
+            EncoderBackend::Native => Source::Native {
+                encoder: NativeEncoder::new_with_params(params.clone()),
+                rgb: encoder::fit_to_mode(image, &MARTIN_M1).to_rgb8(),
+                synth: Synth::new(params.amplitude),
+                next_line: None,
+                id_done: false,
+            },
+        };
+        let len = match &source {
+            Source::Buffered(samples) => samples.len(),
+            Source::Native { encoder, .. } => native_len(encoder),
+        };
+        Self {
+            image_span: encoder::image_span(params, SAMPLE_RATE as u32, len),
+            source,
+            block_len,
+            len,
+            pos: 0,
+            pending: Vec::new(),
+        }
+    }
+
+    /// Длина всего сигнала, сэмплов
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Сэмплы тела изображения, как у [`encoder::image_span`]
+    pub fn image_span(&self) -> Range<usize> {
+        self.image_span.clone()
+    }
+
+    /// Сколько сэмплов уже выдано
+    pub fn position(&self) -> usize {
+        self.pos
+    }
+
+    /// СКЗ сигнала для [`StreamContext::signal_rms`]: у встроенного кодера — СКЗ синусоиды
+    /// его амплитуды, у rsstv — измеренное по готовому сигналу
+    pub fn signal_rms(&self) -> f32 {
+        match &self.source {
+            Source::Native { encoder, .. } => encoder.params().amplitude / std::f32::consts::SQRT_2,
+            Source::Buffered(samples) => {
+                let energy: f32 = samples.iter().map(|x| x * x).sum();
+                (energy / samples.len().max(1) as f32).sqrt()
+            }
+        }
+    }
+
+    /// Синтезирует следующий участок встроенного кодера: заголовок, строку или
+    /// опознаватель. `false`, если синтезировать больше нечего
+    fn refill(&mut self) -> bool {
+        let Source::Native {
+            encoder,
+            rgb,
+            synth,
+            next_line,
+            id_done,
+        } = &mut self.source
+        else {
+            return false;
+        };
+        match *next_line {
+            None => {
+                if encoder.params().header {
+                    for tone in encoder.header_tones() {
+                        synth.push(&tone, &mut self.pending);
+                    }
+                    // Заголовок выдаётся первым, поэтому его сэмплы — сэмплы сигнала
+                    if encoder.params().vis_attenuation_db != 0.0 {
+                        let gain = 10f32.powf(-encoder.params().vis_attenuation_db / 20.0);
+                        let span = encoder.vis_span();
+                        let end = span.end.min(self.pending.len());
+                        let start = span.start.min(end);
+                        self.pending[start..end].iter_mut().for_each(|s| *s *= gain);
+                    }
+                }
+                *next_line = Some(0);
+            }
+            Some(y) if y < MARTIN_M1.height => {
+                let line = encoder::image_line(rgb, y);
+                for tone in encoder.line_tones(&line) {
+                    synth.push(&tone, &mut self.pending);
+                }
+                *next_line = Some(y + 1);
+            }
+            Some(_) if !*id_done => {
+                let id = &encoder.params().id;
+                if id.is_transmitted() {
+                    self.pending
+                        .extend(id.synthesize(encoder.params().amplitude));
+                }
+                *id_done = true;
+            }
+            Some(_) => return false,
+        }
+        true
+    }
+}
+
+impl Iterator for BlockEncoder {
+    type Item = Vec<f32>;
+
+    fn next(&mut self) -> Option<Vec<f32>> {
+        if let Source::Buffered(samples) = &self.source {
+            let end = (self.pos + self.block_len).min(samples.len());
+            if self.pos >= end {
+                return None;
+            }
+            let block = samples[self.pos..end].to_vec();
+            self.pos = end;
+            return Some(block);
+        }
+        while self.pending.len() < self.block_len && self.refill() {}
+        if self.pending.is_empty() {
+            return None;
+        }
+        let take = self.block_len.min(self.pending.len());
+        let block: Vec<f32> = self.pending.drain(..take).collect();
+        self.pos += block.len();
+        Some(block)
+    }
+}
+
+/// Длина сигнала встроенного кодера: те же накопленные длительности тонов, что у синтеза,
+/// без самих пикселей — длительности от них не зависят
+fn native_len(encoder: &NativeEncoder) -> usize {
+    let mut end_us = 0.0f64;
+    if encoder.params().header {
+        for tone in encoder.header_tones() {
+            end_us += tone.duration_us;
+        }
+    }
+    let line = encoder.line_tones(&vec![Rgb([0, 0, 0]); MARTIN_M1.width as usize]);
+    for _ in 0..MARTIN_M1.height {
+        for tone in &line {
+            end_us += tone.duration_us;
+        }
+    }
+    (end_us * 1e-6 * SAMPLE_RATE as f64).round() as usize + encoder.params().id.sample_count()
+}
+
+/// Эффект конвейера в потоке: его обработчик блоков, доли смешивания и линия задержки
+/// необработанной доли на задержку обработчика
+struct StreamStage {
+    name: &'static str,
+    processor: Box<dyn BlockProcessor>,
+    mix: EffectMix,
+    dry_delay: VecDeque<f32>,
+}
+
+impl StreamStage {
+    /// Необработанная доля блока, задержанная так же, как выход обработчика
+    fn dry(&mut self, block: &[f32]) -> Vec<f32> {
+        if self.dry_delay.is_empty() {
+            return block.to_vec();
+        }
+        block
+            .iter()
+            .map(|&x| {
+                self.dry_delay.push_back(x);
+                self.dry_delay.pop_front().unwrap_or(0.0)
+            })
+            .collect()
+    }
+}
+
+/// Обработчики включённых эффектов по порядку конвейера и выходной каскад (усиление и
+/// ограничение −1…1, как у [`Signal::output_stage`](crate::signal::Signal::output_stage)).
+/// Блоки приходят откуда угодно: от [`BlockEncoder`] в [`SampleStream`] или со звуковой
+/// карты в живом канале. В памяти держатся только состояние эффектов и текущий блок
+pub struct BlockChain {
+    stages: Vec<StreamStage>,
+    gain: f32,
+    sanitize: SanitizeMode,
+    sanitized: Vec<SanitizeEvent>,
+    level: LevelTotals,
+    position: usize,
+}
+
+impl BlockChain {
+    /// Цепочка из включённых эффектов `effects`. Ошибка, если какой-то из них не умеет
+    /// работать блоками, см. [`ChannelEffect::block_processor`]
+    pub fn new(
+        effects: &[Box<dyn ChannelEffect>],
+        ctx: &StreamContext,
+        output_gain_db: f32,
+        sanitize: SanitizeMode,
+    ) -> Result<Self> {
+        let stages = effects
+            .iter()
+            .filter(|e| e.is_enabled())
+            .map(|e| {
+                let processor = e.block_processor(ctx)?;
+                let mix = e.mix();
+                let dry_delay = if mix.is_identity() {
+                    VecDeque::new()
+                } else {
+                    VecDeque::from(vec![0.0; processor.latency()])
+                };
+                Ok(StreamStage {
+                    name: e.name(),
+                    processor,
+                    mix,
+                    dry_delay,
+                })
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self {
+            stages,
+            gain: signal::output_gain(output_gain_db),
+            sanitize,
+            sanitized: Vec::new(),
+            level: LevelTotals::default(),
+            position: 0,
+        })
+    }
+
+    /// Названия эффектов цепочки по порядку
+    pub fn effect_names(&self) -> Vec<&'static str> {
+        self.stages.iter().map(|stage| stage.name).collect()
+    }
+
+    /// Задержка выхода цепочки относительно входа, сэмплов: сумма задержек обработчиков,
+    /// см. [`BlockProcessor::latency`]. Первые сэмплы выхода — тишина, хвост входа на
+    /// эту длину остаётся внутри цепочки
+    pub fn latency(&self) -> usize {
+        self.stages
+            .iter()
+            .map(|stage| stage.processor.latency())
+            .sum()
+    }
+
+    /// Уровень выхода по уже обработанным блокам
+    pub fn level(&self) -> LevelMeter {
+        self.level.meter()
+    }
+
+    /// Эффекты, после которых проверка исправила испорченные сэмплы; счётчики сложены
+    /// по всем блокам
+    pub fn take_sanitized(&mut self) -> Vec<SanitizeEvent> {
+        std::mem::take(&mut self.sanitized)
+    }
+
+    /// Первый сэмпл следующего блока: сколько сэмплов уже прошло через цепочку
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Контрольная точка между блоками, см. [`BlockProcessor::save_state`]. Генераторы
+    /// эффектов засеваются заново, так что цепочка, восстановленная из точки через
+    /// [`restore_state`](Self::restore_state), дальше выдаёт те же сэмплы, что и эта
+    pub fn save_state(&mut self) -> Result<ChainState> {
+        let stages = self
+            .stages
+            .iter_mut()
+            .map(|stage| {
+                Ok(StageState {
+                    effect: stage.name.to_string(),
+                    processor: stage.processor.save_state()?,
+                    dry_delay: stage.dry_delay.iter().copied().collect(),
+                })
+            })
+            .collect::<Result<_>>()?;
+        Ok(ChainState {
+            position: self.position,
+            stages,
+            level: self.level.clone(),
+        })
+    }
+
+    /// Продолжает с контрольной точки `state`. Цепочка должна быть только что построена
+    /// из тех же эффектов с теми же настройками и контекстом, что и сохранившая точку;
+    /// следующий блок начинается с [`ChainState::position`]
+    pub fn restore_state(&mut self, state: &ChainState) -> Result<()> {
+        if state.stages.len() != self.stages.len() {
+            return Err(SstvError::invalid(
+                "checkpoint",
+                messages::checkpoint_stages(state.stages.len(), self.stages.len()),
+            ));
+        }
+        for (stage, saved) in self.stages.iter_mut().zip(&state.stages) {
+            if saved.dry_delay.len() != stage.dry_delay.len() {
+                return Err(SstvError::invalid(
+                    "checkpoint",
+                    messages::checkpoint_mix_delay(
+                        stage.name,
+                        saved.dry_delay.len(),
+                        stage.dry_delay.len(),
+                    ),
+                ));
+            }
+            stage.processor.restore_state(&saved.processor)?;
+            stage.dry_delay = saved.dry_delay.iter().copied().collect();
+        }
+        self.level = state.level.clone();
+        self.position = state.position;
+        Ok(())
+    }
+
+    /// Пропускает блок, начинающийся с сэмпла `start`, через эффекты и выходной каскад.
+    /// Блоки должны идти подряд, без пропусков
+    pub fn process(&mut self, block: &mut [f32], start: usize) -> Result<()> {
+        for stage in &mut self.stages {
+            let dry = (!stage.mix.is_identity()).then(|| stage.dry(block));
+            stage.processor.process_block(block, start)?;
+            if let Some(dry) = dry {
+                stage.mix.blend(block, &dry);
+            }
+            if self.sanitize == SanitizeMode::Off {
+                continue;
+            }
+            let repair = self.sanitize == SanitizeMode::Repair;
+            let Some(mut event) = effect::sanitize(block, stage.name, repair) else {
+                continue;
+            };
+            event.first += start;
+            if !repair {
+                return Err(SstvError::invalid(
+                    event.effect,
+                    messages::effect_corrupted(&event.describe()),
+                ));
+            }
+            match self.sanitized.iter_mut().find(|e| e.effect == event.effect) {
+                Some(known) => {
+                    known.nan += event.nan;
+                    known.infinite += event.infinite;
+                }
+                None => {
+                    tracing::warn!("{}", event.describe());
+                    self.sanitized.push(event);
+                }
+            }
+        }
+        if self.gain != 1.0 {
+            block.iter_mut().for_each(|x| *x *= self.gain);
+        }
+        self.level.add(block);
+        block.iter_mut().for_each(|x| *x = x.clamp(-1.0, 1.0));
+        self.position = start + block.len();
+        Ok(())
+    }
+}
+
+/// Сигнал канала блоками: кодер и [`BlockChain`] включённых эффектов. В памяти держатся
+/// только текущий блок и состояние эффектов
+pub struct SampleStream {
+    encoder: BlockEncoder,
+    chain: BlockChain,
+}
+
+impl SampleStream {
+    /// Поток из кодера `encoder` через включённые эффекты `effects`. Ошибка, если
+    /// какой-то из них не умеет работать блоками, см. [`ChannelEffect::block_processor`]
+    pub fn new(
+        encoder: BlockEncoder,
+        effects: &[Box<dyn ChannelEffect>],
+        ctx: &StreamContext,
+        output_gain_db: f32,
+        sanitize: SanitizeMode,
+    ) -> Result<Self> {
+        Ok(Self {
+            encoder,
+            chain: BlockChain::new(effects, ctx, output_gain_db, sanitize)?,
+        })
+    }
+
+    /// Длина всего сигнала, сэмплов
+    pub fn len(&self) -> usize {
+        self.encoder.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.encoder.is_empty()
+    }
+
+    /// Частота дискретизации блоков, Гц
+    pub fn sample_rate(&self) -> u32 {
+        SAMPLE_RATE as u32
+    }
+
+    /// Сэмплы тела изображения
+    pub fn image_span(&self) -> Range<usize> {
+        self.encoder.image_span()
+    }
+
+    /// Сколько сэмплов уже выдано
+    pub fn position(&self) -> usize {
+        self.encoder.position()
+    }
+
+    /// Задержка эффектов, сэмплов, см. [`BlockChain::latency`]: сигнал выходит сдвинутым
+    /// на неё, длина потока та же
+    pub fn latency(&self) -> usize {
+        self.chain.latency()
+    }
+
+    /// Уровень выхода по уже выданным блокам
+    pub fn level(&self) -> LevelMeter {
+        self.chain.level()
+    }
+
+    /// Эффекты, после которых проверка исправила испорченные сэмплы; счётчики сложены
+    /// по всем блокам
+    pub fn take_sanitized(&mut self) -> Vec<SanitizeEvent> {
+        self.chain.take_sanitized()
+    }
+}
+
+impl Iterator for SampleStream {
+    type Item = Result<Vec<f32>>;
+
+    fn next(&mut self) -> Option<Result<Vec<f32>>> {
+        let start = self.encoder.position();
+        let mut block = self.encoder.next()?;
+        Some(self.chain.process(&mut block, start).map(|()| block))
+    }
+}
+
+/// Контрольная точка [`BlockChain`]: с какого сэмпла продолжать, состояние обработчиков
+/// и задержки смешивания по эффектам, накопленный уровень выхода. Хранится в JSON
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ChainState {
+    /// Первый сэмпл блока, с которого продолжается обработка
+    pub position: usize,
+    stages: Vec<StageState>,
+    level: LevelTotals,
+}
+
+/// Состояние одного эффекта цепочки; название — только для читающего файл человека
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct StageState {
+    effect: String,
+    processor: serde_json::Value,
+    dry_delay: Vec<f32>,
+}
+
+impl ChainState {
+    /// Записывает контрольную точку в JSON
+    pub fn to_file(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let text = serde_json::to_string(self)
+            .map_err(|e| SstvError::invalid("checkpoint", e.to_string()))?;
+        std::fs::write(path, text).map_err(|e| {
+            SstvError::io(
+                messages::checkpoint_write_failed(&path.display().to_string()),
+                e,
+            )
+        })
+    }
+
+    /// Читает контрольную точку, записанную [`to_file`](Self::to_file)
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path).map_err(|e| {
+            SstvError::io(
+                messages::checkpoint_read_failed(&path.display().to_string()),
+                e,
+            )
+        })?;
+        serde_json::from_str(&text).map_err(|e| {
+            SstvError::invalid(
+                "checkpoint",
+                messages::checkpoint_invalid(&path.display().to_string(), &e.to_string()),
+            )
+        })
+    }
+}
+
+/// Накопленные по блокам величины для [`LevelMeter`]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+struct LevelTotals {
+    peak: f32,
+    energy: f64,
+    clipped: usize,
+    count: usize,
+}
+
+impl LevelTotals {
+    fn add(&mut self, samples: &[f32]) {
+        for &x in samples {
+            self.peak = self.peak.max(x.abs());
+            self.energy += (x as f64) * (x as f64);
+            if x.abs() > 1.0 {
+                self.clipped += 1;
+            }
+        }
+        self.count += samples.len();
+    }
+
+    fn meter(&self) -> LevelMeter {
+        LevelMeter::from_totals(self.peak, self.energy, self.clipped, self.count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::drift::{DriftParams, DriftProcessor};
+    use crate::dropout::{DropoutParams, DropoutProcessor};
+    use crate::fading::{FadingParams, FadingProcessor};
+    use crate::filters::{FilterKind, FilterParams, FilterProcessor};
+    use crate::noise::{NoiseColor, NoiseParams, NoiseProcessor};
+    use crate::testing::with_stream_context;
+
+    /// Цепочка из эффектов со случайным и накопленным состоянием; `seed` — зерно общего
+    /// генератора
+    fn checkpoint_chain(len: usize, seed: u64) -> BlockChain {
+        let effects: Vec<Box<dyn ChannelEffect>> = vec![
+            Box::new(DriftProcessor::new_with_params(DriftParams {
+                offset_hz: 40.0,
+                ..DriftParams::default()
+            })),
+            Box::new(FadingProcessor::new_with_params(FadingParams {
+                depth: 0.8,
+                rate_hz: 20.0,
+                ..FadingParams::default()
+            })),
+            Box::new(NoiseProcessor::new_with_params(NoiseParams {
+                level: 30,
+                color: NoiseColor::Pink,
+                ..NoiseParams::default()
+            })),
+            Box::new(FilterProcessor::new_with_params(FilterParams {
+                enabled: true,
+                kind: FilterKind::Bandpass,
+                ..FilterParams::default()
+            })),
+            Box::new(DropoutProcessor::new_with_params(DropoutParams {
+                rate_per_min: 600.0,
+                duration_ms: 5.0,
+                ..DropoutParams::default()
+            })),
+        ];
+        with_stream_context(len, seed, |ctx| {
+            BlockChain::new(&effects, ctx, 0.0, SanitizeMode::Off)
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn resumed_chain_matches_checkpointed_run() {
+        let samples: Vec<f32> = (0..6000).map(|i| 0.4 * (i as f32 * 0.13).sin()).collect();
+        let run = |chain: &mut BlockChain, range: Range<usize>| {
+            let mut out = samples[range.clone()].to_vec();
+            let mut start = range.start;
+            for block in out.chunks_mut(500) {
+                chain.process(block, start).unwrap();
+                start += block.len();
+            }
+            out
+        };
+        let mut original = checkpoint_chain(samples.len(), 5);
+        run(&mut original, 0..2500);
+        let state = serde_json::to_string(&original.save_state().unwrap()).unwrap();
+        let expected = run(&mut original, 2500..samples.len());
+
+        // Другое зерно: всё случайное должно прийти из контрольной точки
+        let mut resumed = checkpoint_chain(samples.len(), 6);
+        resumed
+            .restore_state(&serde_json::from_str(&state).unwrap())
+            .unwrap();
+        assert_eq!(resumed.position(), 2500);
+        assert_eq!(run(&mut resumed, 2500..samples.len()), expected);
+    }
+}
@@ -0,0 +1,365 @@
+use crate::command::{self, CommandPaths};
+use crate::error::{Result, SstvError};
+use crate::messages;
+use crate::metrics;
+use crate::processor::{ProcessingParams, SSTVProcessor};
+use crate::quality::QualityTier;
+use image::DynamicImage;
+use rayon::prelude::*;
+use serde::Serialize;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+/// Подкаталог результатов грубого прохода
+const PREVIEW_DIR: &str = "preview";
+
+/// Диапазон значений параметра: от `start` до `end` включительно с шагом `step`
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SweepRange {
+    pub start: f32,
+    pub end: f32,
+    pub step: f32,
+}
+
+impl SweepRange {
+    /// Диапазон из одного значения
+    pub fn single(value: f32) -> Self {
+        Self {
+            start: value,
+            end: value,
+            step: 1.0,
+        }
+    }
+
+    /// Значения диапазона по порядку
+    pub fn values(&self) -> Vec<f32> {
+        // Счётчик целый, чтобы погрешность шага не теряла последнее значение
+        let count = ((self.end - self.start) / self.step + 1e-4)
+            .floor()
+            .max(0.0) as usize
+            + 1;
+        (0..count)
+            .map(|k| self.start + k as f32 * self.step)
+            .collect()
+    }
+}
+
+impl FromStr for SweepRange {
+    type Err = SstvError;
+
+    /// Разбирает диапазон в формате `начало..конец[:шаг]` или одно значение
+    fn from_str(s: &str) -> Result<Self> {
+        let parse = |v: &str| -> Result<f32> {
+            v.trim()
+                .parse()
+                .map_err(|_| SstvError::invalid("range", messages::range_bad_number(v)))
+        };
+
+        let Some((start, rest)) = s.split_once("..") else {
+            return Ok(Self::single(parse(s)?));
+        };
+        let (end, step) = match rest.split_once(':') {
+            Some((end, step)) => (parse(end)?, parse(step)?),
+            None => (parse(rest)?, 1.0),
+        };
+
+        let range = Self {
+            start: parse(start)?,
+            end,
+            step,
+        };
+        if range.step <= 0.0 {
+            return Err(SstvError::invalid("range", messages::range_bad_step(s)));
+        }
+        if range.end < range.start {
+            return Err(SstvError::invalid("range", messages::range_reversed(s)));
+        }
+        Ok(range)
+    }
+}
+
+/// Формат описи результатов перебора
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ManifestFormat {
+    Csv,
+    Json,
+}
+
+impl ManifestFormat {
+    pub const ALL: &'static [ManifestFormat] = &[ManifestFormat::Csv, ManifestFormat::Json];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ManifestFormat::Csv => "CSV",
+            ManifestFormat::Json => "JSON",
+        }
+    }
+
+    /// Расширение файла описи
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ManifestFormat::Csv => "csv",
+            ManifestFormat::Json => "json",
+        }
+    }
+}
+
+impl clap::ValueEnum for ManifestFormat {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[Self::Csv, Self::Json]
+    }
+
+    fn to_possible_value<'a>(&self) -> Option<clap::builder::PossibleValue> {
+        Some(match self {
+            Self::Csv => clap::builder::PossibleValue::new("csv"),
+            Self::Json => clap::builder::PossibleValue::new("json"),
+        })
+    }
+}
+
+/// Перебираемые параметры; не заданная ось берёт значение из базовых параметров
+#[derive(Clone, Debug, Default)]
+pub struct SweepAxes {
+    /// Уровень шума 0–100
+    pub noise: Option<SweepRange>,
+    /// Уровень ретардера 0–1
+    pub retarder_level: Option<SweepRange>,
+    /// Задержка ретардера, мс
+    pub delay_ms: Option<SweepRange>,
+}
+
+/// Одна точка перебора в описи
+#[derive(Clone, Debug, Serialize)]
+pub struct SweepEntry {
+    /// Номер точки в сетке; в грубом и основном проходах у одной точки он общий
+    pub index: usize,
+    /// Качество прохода, в котором получена точка
+    pub quality: QualityTier,
+    /// Имя файла результата относительно выходного каталога
+    pub file: String,
+    pub noise: u8,
+    pub retarder_level: f32,
+    pub delay_ms: u32,
+    /// PSNR результата относительно исходного изображения, дБ
+    pub psnr: f64,
+    /// SSIM результата относительно исходного изображения
+    pub ssim: f64,
+    /// Команда CLI, повторяющая эту точку
+    pub command: String,
+}
+
+/// Перебор параметров: обрабатывает изображение для каждого сочетания значений осей
+/// и сохраняет результаты с описью
+pub struct SweepRunner {
+    /// Параметры, общие для всех точек
+    pub base: ProcessingParams,
+    pub axes: SweepAxes,
+    pub output_dir: PathBuf,
+    pub format: ManifestFormat,
+    /// Сохранять искажённый сигнал каждой точки в WAV (только со сборкой `wav`)
+    pub save_wav: bool,
+    /// Перед основным проходом быстро пройти всю сетку с качеством
+    /// [`QualityTier::Preview`]; её результаты пишутся в подкаталог `preview`
+    pub coarse: bool,
+}
+
+impl SweepRunner {
+    pub fn new(base: ProcessingParams, axes: SweepAxes, output_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            base,
+            axes,
+            output_dir: output_dir.into(),
+            format: ManifestFormat::Csv,
+            save_wav: false,
+            coarse: false,
+        }
+    }
+
+    /// Все сочетания значений осей; шум меняется медленнее всего, задержка — быстрее
+    pub fn combinations(&self) -> Vec<ProcessingParams> {
+        let axis = |range: &Option<SweepRange>, base: f32| {
+            range.map_or_else(|| vec![base], |r| r.values())
+        };
+        let noises = axis(&self.axes.noise, self.base.noise.level as f32);
+        let levels = axis(&self.axes.retarder_level, self.base.retarder.level);
+        let delays = axis(&self.axes.delay_ms, self.base.retarder.delay_ms as f32);
+
+        let mut points = Vec::with_capacity(noises.len() * levels.len() * delays.len());
+        for &noise in &noises {
+            for &level in &levels {
+                for &delay in &delays {
+                    let mut params = self.base.clone();
+                    params.noise.level = noise.round().clamp(0.0, 100.0) as u8;
+                    params.retarder.level = level.clamp(0.0, 1.0);
+                    params.retarder.delay_ms = delay.round().max(0.0) as u32;
+                    points.push(params);
+                }
+            }
+        }
+        points
+    }
+
+    /// Проходы по порядку: при `coarse` перед основным идёт та же сетка с качеством
+    /// предпросмотра
+    pub fn passes(&self) -> Vec<Vec<ProcessingParams>> {
+        let grid = self.combinations();
+        let mut passes = Vec::new();
+        if self.coarse && self.base.quality != QualityTier::Preview {
+            let preview = grid
+                .iter()
+                .map(|params| ProcessingParams {
+                    quality: QualityTier::Preview,
+                    ..params.clone()
+                })
+                .collect();
+            passes.push(preview);
+        }
+        passes.push(grid);
+        passes
+    }
+
+    /// Обрабатывает точки каждого прохода параллельно, проходы — по очереди, и пишет общую
+    /// опись `manifest.<ext>` в выходной каталог. `paths` нужны для команд CLI в описи; путь
+    /// результата подставляется для каждой точки. `on_done` вызывается после каждой точки
+    /// (из рабочих потоков)
+    pub fn run(
+        &self,
+        main_image: &DynamicImage,
+        retarder_image: Option<&DynamicImage>,
+        paths: &CommandPaths,
+        on_done: impl Fn(&SweepEntry) + Sync,
+    ) -> Result<Vec<SweepEntry>> {
+        std::fs::create_dir_all(&self.output_dir).map_err(|e| {
+            SstvError::io(
+                messages::create_dir_failed(&self.output_dir.display().to_string()),
+                e,
+            )
+        })?;
+
+        let mut entries = Vec::new();
+        for pass in self.passes() {
+            let done = pass
+                .into_par_iter()
+                .enumerate()
+                .map(|(index, params)| {
+                    let entry = self.run_point(index, params, main_image, retarder_image, paths)?;
+                    on_done(&entry);
+                    Ok(entry)
+                })
+                .collect::<Result<Vec<_>>>()?;
+            entries.extend(done);
+        }
+
+        self.write_manifest(&entries)?;
+        Ok(entries)
+    }
+
+    /// Путь к файлу описи
+    pub fn manifest_path(&self) -> PathBuf {
+        self.output_dir
+            .join("manifest")
+            .with_extension(self.format.extension())
+    }
+
+    fn run_point(
+        &self,
+        index: usize,
+        params: ProcessingParams,
+        main_image: &DynamicImage,
+        retarder_image: Option<&DynamicImage>,
+        paths: &CommandPaths,
+    ) -> Result<SweepEntry> {
+        let mut file = format!(
+            "sweep_{:04}_n{}_r{:.2}_d{}.png",
+            index, params.noise.level, params.retarder.level, params.retarder.delay_ms
+        );
+        // Точки грубого прохода лежат отдельно, под теми же именами
+        if params.quality != self.base.quality {
+            let dir = self.output_dir.join(PREVIEW_DIR);
+            std::fs::create_dir_all(&dir).map_err(|e| {
+                SstvError::io(messages::create_dir_failed(&dir.display().to_string()), e)
+            })?;
+            file = format!("{}/{}", PREVIEW_DIR, file);
+        }
+        let output = self.output_dir.join(&file);
+        let output_str = output.to_string_lossy().to_string();
+
+        let mut processor = SSTVProcessor::builder().params(params.clone()).build()?;
+        let samples = processor.render_samples(main_image, retarder_image)?;
+        let result = processor.decode_samples(&samples)?.image;
+        processor.save_result(&result, &output_str)?;
+
+        #[cfg(feature = "wav")]
+        if self.save_wav {
+            let wav_path = output.with_extension("wav");
+            processor.write_wav(&wav_path.to_string_lossy(), &samples)?;
+        }
+
+        let quality = metrics::compare_in(
+            &*params.overlay_params().apply(main_image)?,
+            &result,
+            params.metric_space,
+        );
+        Ok(SweepEntry {
+            index,
+            quality: params.quality,
+            file,
+            noise: params.noise.level,
+            retarder_level: params.retarder.level,
+            delay_ms: params.retarder.delay_ms,
+            psnr: quality.psnr,
+            ssim: quality.ssim,
+            command: command::cli_command(
+                &params,
+                &CommandPaths {
+                    output: output_str.clone(),
+                    ..paths.clone()
+                },
+            ),
+        })
+    }
+
+    fn write_manifest(&self, entries: &[SweepEntry]) -> Result<()> {
+        let text = match self.format {
+            ManifestFormat::Json => serde_json::to_string_pretty(entries)
+                .map_err(|e| SstvError::io(messages::manifest_serialize_failed(), e))?,
+            ManifestFormat::Csv => manifest_csv(entries),
+        };
+        let path = self.manifest_path();
+        std::fs::write(&path, text).map_err(|e| {
+            SstvError::io(
+                messages::manifest_write_failed(&path.display().to_string()),
+                e,
+            )
+        })
+    }
+}
+
+fn manifest_csv(entries: &[SweepEntry]) -> String {
+    let mut csv =
+        String::from("index,quality,file,noise,retarder_level,delay_ms,psnr,ssim,command\n");
+    for e in entries {
+        csv.push_str(&format!(
+            "{},{},{},{},{},{},{:.2},{:.4},{}\n",
+            e.index,
+            e.quality.name(),
+            csv_field(&e.file),
+            e.noise,
+            e.retarder_level,
+            e.delay_ms,
+            e.psnr,
+            e.ssim,
+            csv_field(&e.command)
+        ));
+    }
+    csv
+}
+
+/// Экранирует поле CSV по RFC 4180
+pub(crate) fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
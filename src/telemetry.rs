@@ -0,0 +1,264 @@
+use crate::encoder::fit_to_mode;
+use crate::error::{Result, SstvError};
+use crate::messages;
+use crate::modes::MARTIN_M1;
+use image::{DynamicImage, Rgb, RgbImage};
+use std::str::FromStr;
+
+/// Уровней яркости на канал: 2 бита на канал, 6 бит на блок
+const LEVELS: u8 = 4;
+const BITS_PER_CHANNEL: usize = 2;
+/// Служебные байты кадра: длина и CRC-8
+const FRAME_OVERHEAD: usize = 2;
+
+/// Машиночитаемая полоса телеметрии в верхних строках изображения.
+///
+/// Кадр `[длина][данные…][CRC-8]` раскладывается по 2 бита в каждый цветовой канал
+/// блоков одной полосы; при извлечении уровни усредняются по середине блока,
+/// поэтому полоса переживает умеренный шум канала.
+#[derive(Clone, Debug)]
+pub struct TelemetryStrip {
+    /// Высота полосы, строк
+    pub lines: u32,
+    /// Ширина одного блока, пикселей
+    pub block_width: u32,
+}
+
+impl Default for TelemetryStrip {
+    fn default() -> Self {
+        Self {
+            lines: 8,
+            block_width: 8,
+        }
+    }
+}
+
+impl TelemetryStrip {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Максимальная длина полезных данных в байтах для изображения режима
+    pub fn capacity(&self) -> usize {
+        let blocks = (MARTIN_M1.width / self.block_width.max(1)) as usize;
+        (blocks * 3 * BITS_PER_CHANNEL / 8)
+            .saturating_sub(FRAME_OVERHEAD)
+            .min(u8::MAX as usize)
+    }
+
+    /// Цвета блоков полосы для заданных данных
+    pub fn blocks(&self, payload: &[u8]) -> Result<Vec<Rgb<u8>>> {
+        if payload.len() > self.capacity() {
+            return Err(SstvError::EncodeFailed(messages::telemetry_too_long(
+                payload.len(),
+                self.capacity(),
+            )));
+        }
+
+        let mut frame = Vec::with_capacity(payload.len() + FRAME_OVERHEAD);
+        frame.push(payload.len() as u8);
+        frame.extend_from_slice(payload);
+        frame.push(crc8(&frame));
+
+        let symbols: Vec<u8> = frame
+            .iter()
+            .flat_map(|&byte| (0..4).rev().map(move |i| (byte >> (i * 2)) & 0b11))
+            .collect();
+
+        let step = 255 / (LEVELS - 1);
+        let blocks = (MARTIN_M1.width / self.block_width.max(1)) as usize;
+        Ok((0..blocks)
+            .map(|b| {
+                let channel = |c: usize| symbols.get(b * 3 + c).copied().unwrap_or(0) * step;
+                Rgb([channel(0), channel(1), channel(2)])
+            })
+            .collect())
+    }
+
+    /// Рисует полосу поверх верхних строк изображения (в разрешении режима)
+    pub fn render(&self, image: &DynamicImage, payload: &[u8]) -> Result<DynamicImage> {
+        let blocks = self.blocks(payload)?;
+        let mut rgb = fit_to_mode(image, &MARTIN_M1).to_rgb8();
+        for y in 0..self.lines.min(rgb.height()) {
+            for x in 0..rgb.width() {
+                rgb.put_pixel(x, y, self.pixel(&blocks, x));
+            }
+        }
+        Ok(DynamicImage::ImageRgb8(rgb))
+    }
+
+    /// Обработчик строк для кодера: рисует полосу по мере кодирования, не трогая остальное
+    pub fn line_hook(
+        &self,
+        payload: &[u8],
+    ) -> Result<impl FnMut(&mut [Rgb<u8>], usize) + Send + 'static> {
+        let blocks = self.blocks(payload)?;
+        let strip = self.clone();
+        Ok(move |pixels: &mut [Rgb<u8>], line: usize| {
+            if line < strip.lines as usize {
+                for (x, pixel) in pixels.iter_mut().enumerate() {
+                    *pixel = strip.pixel(&blocks, x as u32);
+                }
+            }
+        })
+    }
+
+    /// Извлекает данные из полосы декодированного изображения
+    pub fn extract(&self, image: &DynamicImage) -> Result<Vec<u8>> {
+        let rgb = fit_to_mode(image, &MARTIN_M1).to_rgb8();
+        let blocks = (MARTIN_M1.width / self.block_width.max(1)) as usize;
+
+        let symbols: Vec<u8> = (0..blocks)
+            .flat_map(|b| {
+                let mean = self.block_mean(&rgb, b as u32);
+                mean.into_iter().map(|v| {
+                    let step = 255.0 / (LEVELS - 1) as f32;
+                    (v / step).round().clamp(0.0, (LEVELS - 1) as f32) as u8
+                })
+            })
+            .collect();
+
+        let bytes: Vec<u8> = symbols
+            .chunks_exact(4)
+            .map(|s| s.iter().fold(0u8, |acc, &sym| (acc << 2) | sym))
+            .collect();
+
+        let len = *bytes
+            .first()
+            .ok_or_else(|| SstvError::DecodeNotFound(messages::telemetry_empty().into()))?
+            as usize;
+        if len > self.capacity() {
+            return Err(SstvError::DecodeNotFound(messages::telemetry_bad_length(
+                len,
+            )));
+        }
+        let (frame, crc) = (&bytes[..len + 1], bytes[len + 1]);
+        if crc8(frame) != crc {
+            return Err(SstvError::DecodeNotFound(
+                messages::telemetry_bad_crc().into(),
+            ));
+        }
+
+        Ok(frame[1..].to_vec())
+    }
+
+    fn pixel(&self, blocks: &[Rgb<u8>], x: u32) -> Rgb<u8> {
+        blocks
+            .get((x / self.block_width.max(1)) as usize)
+            .copied()
+            .unwrap_or(Rgb([0, 0, 0]))
+    }
+
+    /// Средний цвет середины блока: края размываются соседями при декодировании
+    fn block_mean(&self, rgb: &RgbImage, block: u32) -> [f32; 3] {
+        let bw = self.block_width.max(1);
+        let x0 = block * bw + bw / 4;
+        let x1 = (block * bw + bw - bw / 4).max(x0 + 1);
+        let y0 = (self.lines > 2) as u32;
+        let y1 = self.lines.saturating_sub(y0).max(y0 + 1).min(rgb.height());
+
+        let mut sum = [0.0f32; 3];
+        let mut count = 0.0;
+        for y in y0..y1 {
+            for x in x0..x1.min(rgb.width()) {
+                let p = rgb.get_pixel(x, y).0;
+                for c in 0..3 {
+                    sum[c] += p[c] as f32;
+                }
+                count += 1.0;
+            }
+        }
+        sum.map(|s| s / f32::max(count, 1.0))
+    }
+}
+
+/// Телеметрия зонда: температура и координаты
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Telemetry {
+    pub temperature_c: f32,
+    pub latitude: f64,
+    pub longitude: f64,
+    pub altitude_m: f32,
+}
+
+impl Telemetry {
+    /// Длина упакованной записи, байт
+    pub const ENCODED_LEN: usize = 14;
+
+    /// Упаковывает телеметрию: температура 0.1 °C, координаты 1e-7°, высота 1 м (big-endian)
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(Self::ENCODED_LEN);
+        bytes.extend(((self.temperature_c * 10.0).round() as i16).to_be_bytes());
+        bytes.extend(((self.latitude * 1e7).round() as i32).to_be_bytes());
+        bytes.extend(((self.longitude * 1e7).round() as i32).to_be_bytes());
+        bytes.extend((self.altitude_m.round() as i32).to_be_bytes());
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() != Self::ENCODED_LEN {
+            return Err(SstvError::DecodeNotFound(
+                messages::telemetry_record_length(bytes.len(), Self::ENCODED_LEN),
+            ));
+        }
+        let i32_at = |i: usize| i32::from_be_bytes(bytes[i..i + 4].try_into().unwrap());
+        Ok(Self {
+            temperature_c: i16::from_be_bytes([bytes[0], bytes[1]]) as f32 / 10.0,
+            latitude: i32_at(2) as f64 / 1e7,
+            longitude: i32_at(6) as f64 / 1e7,
+            altitude_m: i32_at(10) as f32,
+        })
+    }
+}
+
+impl FromStr for Telemetry {
+    type Err = SstvError;
+
+    /// Разбирает телеметрию в формате `температура,широта,долгота,высота`
+    fn from_str(s: &str) -> Result<Self> {
+        let parts: Vec<&str> = s.split(',').map(str::trim).collect();
+        if parts.len() != 4 {
+            return Err(SstvError::invalid(
+                "telemetry",
+                messages::expected_format(messages::format_telemetry(), s),
+            ));
+        }
+        let parse = |i: usize| -> Result<f64> {
+            parts[i]
+                .parse()
+                .map_err(|_| SstvError::invalid("telemetry", messages::invalid_number(parts[i])))
+        };
+        Ok(Self {
+            temperature_c: parse(0)? as f32,
+            latitude: parse(1)?,
+            longitude: parse(2)?,
+            altitude_m: parse(3)? as f32,
+        })
+    }
+}
+
+impl std::fmt::Display for Telemetry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&messages::telemetry_describe(
+            self.temperature_c,
+            self.latitude,
+            self.longitude,
+            self.altitude_m,
+        ))
+    }
+}
+
+/// CRC-8 с полиномом 0x07
+fn crc8(data: &[u8]) -> u8 {
+    data.iter().fold(0u8, |mut crc, &byte| {
+        crc ^= byte;
+        for _ in 0..8 {
+            crc = if crc & 0x80 != 0 {
+                (crc << 1) ^ 0x07
+            } else {
+                crc << 1
+            };
+        }
+        crc
+    })
+}
@@ -0,0 +1,42 @@
+use crate::effect::StreamContext;
+use crate::encoder::EncoderParams;
+use image::{DynamicImage, Rgb, RgbImage};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::cell::RefCell;
+
+/// Частота дискретизации потоковых проверок, Гц
+pub(crate) const STREAM_RATE: u32 = 44100;
+
+/// Вызывает `f` с контекстом потока на [`STREAM_RATE`] длиной `len` сэмплов: весь сигнал —
+/// тело изображения, кодер по умолчанию, общий генератор засеян `seed`
+pub(crate) fn with_stream_context<T>(
+    len: usize,
+    seed: u64,
+    f: impl FnOnce(&StreamContext) -> T,
+) -> T {
+    let encoder = EncoderParams::default();
+    let rng = RefCell::new(StdRng::seed_from_u64(seed));
+    f(&StreamContext {
+        sample_rate: STREAM_RATE,
+        len,
+        image_span: 0..len,
+        signal_rms: 0.3,
+        encoder: &encoder,
+        retarder_image: None,
+        rng: &rng,
+    })
+}
+
+/// Изображение `width`×`height` из случайных пикселей, одно и то же для одного `seed`
+pub(crate) fn random_image(width: u32, height: u32, seed: u64) -> DynamicImage {
+    let mut rng = StdRng::seed_from_u64(seed);
+    DynamicImage::ImageRgb8(RgbImage::from_fn(width, height, |_, _| Rgb(rng.random())))
+}
+
+/// Синус частоты `freq_hz` полной шкалы, `len` сэмплов на частоте `rate`
+pub(crate) fn tone(freq_hz: f32, rate: u32, len: usize) -> Vec<f32> {
+    (0..len)
+        .map(|i| (std::f32::consts::TAU * freq_hz * i as f32 / rate as f32).sin())
+        .collect()
+}
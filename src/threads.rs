@@ -0,0 +1,157 @@
+use crate::error::{Result, SstvError};
+use crate::messages;
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+
+/// Приоритет рабочих потоков rayon у планировщика системы. Пониженный оставляет ядра
+/// живому приёму и звуковым потокам на той же машине, пока идёт большой перебор
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ThreadPriority {
+    /// Как у остальных потоков процесса
+    #[default]
+    Normal,
+    /// Ниже обычного: nice 10 в Linux, THREAD_PRIORITY_LOWEST в Windows
+    Low,
+    /// Только когда ядро свободно: nice 19 в Linux, THREAD_PRIORITY_IDLE в Windows
+    Idle,
+}
+
+impl ThreadPriority {
+    pub const ALL: &'static [ThreadPriority] = &[
+        ThreadPriority::Normal,
+        ThreadPriority::Low,
+        ThreadPriority::Idle,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ThreadPriority::Normal => messages::thread_priority_normal(),
+            ThreadPriority::Low => messages::thread_priority_low(),
+            ThreadPriority::Idle => messages::thread_priority_idle(),
+        }
+    }
+}
+
+impl clap::ValueEnum for ThreadPriority {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[Self::Normal, Self::Low, Self::Idle]
+    }
+
+    fn to_possible_value<'a>(&self) -> Option<clap::builder::PossibleValue> {
+        Some(match self {
+            Self::Normal => clap::builder::PossibleValue::new("normal"),
+            Self::Low => clap::builder::PossibleValue::new("low"),
+            Self::Idle => clap::builder::PossibleValue::new("idle"),
+        })
+    }
+}
+
+/// Потоки всех параллельных путей: общего пула rayon, где идут параллельные эффекты и
+/// передискретизация, и пулов пакета, перебора, последовательности и поиска передач
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ThreadParams {
+    /// Потоков в пуле (0 — по числу ядер). Пул команды с явным `-j` берёт его число
+    pub threads: usize,
+    pub priority: ThreadPriority,
+}
+
+/// Настройки, заданные [`configure`]
+static PARAMS: Mutex<ThreadParams> = Mutex::new(ThreadParams {
+    threads: 0,
+    priority: ThreadPriority::Normal,
+});
+
+/// Настраивает потоки на весь процесс: создаёт общий пул rayon и запоминает настройки для
+/// пулов из [`pool`]. Вызывается до первой параллельной работы — общий пул создаётся
+/// однажды, и после неё это ошибка
+pub fn configure(params: ThreadParams) -> Result<()> {
+    *PARAMS.lock().unwrap() = params;
+    builder(params.threads, params.priority)
+        .build_global()
+        .map_err(|e| SstvError::io(messages::thread_pool_global_failed(), e))?;
+    if params.priority != ThreadPriority::Normal && !priority_supported() {
+        tracing::warn!(
+            "{}",
+            messages::thread_priority_unsupported(params.priority.name())
+        );
+    }
+    Ok(())
+}
+
+/// Настройки, заданные [`configure`]; по умолчанию — по потоку на ядро, обычный приоритет
+pub fn thread_params() -> ThreadParams {
+    *PARAMS.lock().unwrap()
+}
+
+/// Отдельный пул на `threads` потоков (0 — число из [`configure`]) с приоритетом из него
+pub fn pool(threads: usize) -> Result<rayon::ThreadPool> {
+    let params = thread_params();
+    let threads = if threads == 0 {
+        params.threads
+    } else {
+        threads
+    };
+    builder(threads, params.priority)
+        .build()
+        .map_err(|e| SstvError::io(messages::thread_pool_failed(), e))
+}
+
+fn builder(threads: usize, priority: ThreadPriority) -> rayon::ThreadPoolBuilder {
+    let builder = rayon::ThreadPoolBuilder::new().num_threads(threads);
+    match priority {
+        ThreadPriority::Normal => builder,
+        priority => builder.start_handler(move |_| set_current_priority(priority)),
+    }
+}
+
+/// Умеет ли система менять приоритет отдельного потока
+fn priority_supported() -> bool {
+    cfg!(any(target_os = "linux", windows))
+}
+
+/// Меняет приоритет вызывающего потока; где это не поддерживается, ничего не делает
+#[cfg(target_os = "linux")]
+fn set_current_priority(priority: ThreadPriority) {
+    // В Linux у каждого потока своё значение nice: PRIO_PROCESS с нулём меняет только
+    // вызывающий поток
+    unsafe extern "C" {
+        fn setpriority(which: i32, who: u32, prio: i32) -> i32;
+    }
+    const PRIO_PROCESS: i32 = 0;
+    let nice = match priority {
+        ThreadPriority::Normal => return,
+        ThreadPriority::Low => 10,
+        ThreadPriority::Idle => 19,
+    };
+    // SAFETY: системный вызов без указателей
+    unsafe {
+        setpriority(PRIO_PROCESS, 0, nice);
+    }
+}
+
+#[cfg(windows)]
+fn set_current_priority(priority: ThreadPriority) {
+    use std::ffi::c_void;
+    unsafe extern "system" {
+        fn GetCurrentThread() -> *mut c_void;
+        fn SetThreadPriority(thread: *mut c_void, priority: i32) -> i32;
+    }
+    const THREAD_PRIORITY_LOWEST: i32 = -2;
+    const THREAD_PRIORITY_IDLE: i32 = -15;
+    let level = match priority {
+        ThreadPriority::Normal => return,
+        ThreadPriority::Low => THREAD_PRIORITY_LOWEST,
+        ThreadPriority::Idle => THREAD_PRIORITY_IDLE,
+    };
+    // SAFETY: псевдодескриптор текущего потока закрывать не нужно
+    unsafe {
+        SetThreadPriority(GetCurrentThread(), level);
+    }
+}
+
+/// В macOS и других Unix nice меняет весь процесс, вместе с потоками, которые
+/// пониженный приоритет как раз должен беречь
+#[cfg(not(any(target_os = "linux", windows)))]
+fn set_current_priority(_priority: ThreadPriority) {}
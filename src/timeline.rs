@@ -0,0 +1,260 @@
+use crate::blanker::{BlankerParams, BlankerProcessor};
+use crate::buffer::SampleBuffer;
+use crate::drift::{DriftParams, DriftProcessor};
+use crate::dropout::{DropoutParams, DropoutProcessor};
+use crate::effect::{self, ChannelEffect, EffectContext};
+use crate::envelope::EnvelopeAnchor;
+use crate::error::Result;
+use crate::fading::{FadingParams, FadingProcessor};
+use crate::interference::{InterferenceParams, InterferenceProcessor};
+use crate::messages;
+use crate::multipath::{MultipathParams, MultipathProcessor};
+use crate::noise::{NoiseParams, NoiseProcessor};
+use crate::preset;
+use crate::processor::ProcessingParams;
+use crate::retarder::{RetarderParams, RetarderProcessor};
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+use std::path::Path;
+
+/// Фронты события по умолчанию, мс: без них включение эффекта слышно щелчком
+const DEFAULT_RAMP_MS: f32 = 10.0;
+
+fn default_ramp_ms() -> f32 {
+    DEFAULT_RAMP_MS
+}
+
+/// Эффект события со своими параметрами. В файле — `effect = "fading"` и таблица
+/// `params`, пропущенные параметры берутся по умолчанию
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "effect", content = "params", rename_all = "snake_case")]
+pub enum EventEffect {
+    Retarder(RetarderParams),
+    Multipath(MultipathParams),
+    Fading(FadingParams),
+    Drift(DriftParams),
+    Interference(InterferenceParams),
+    Noise(NoiseParams),
+    Blanker(BlankerParams),
+    Dropout(DropoutParams),
+}
+
+impl EventEffect {
+    pub fn name(&self) -> &'static str {
+        match self {
+            EventEffect::Retarder(_) => messages::retarder(),
+            EventEffect::Multipath(_) => messages::multipath(),
+            EventEffect::Fading(_) => messages::fading(),
+            EventEffect::Drift(_) => messages::drift(),
+            EventEffect::Interference(_) => messages::interference(),
+            EventEffect::Noise(_) => messages::noise(),
+            EventEffect::Blanker(_) => messages::blanker(),
+            EventEffect::Dropout(_) => messages::dropout(),
+        }
+    }
+
+    /// Имя эффекта в файле сценария, поле `effect`
+    pub fn key(&self) -> &'static str {
+        match self {
+            EventEffect::Retarder(_) => "retarder",
+            EventEffect::Multipath(_) => "multipath",
+            EventEffect::Fading(_) => "fading",
+            EventEffect::Drift(_) => "drift",
+            EventEffect::Interference(_) => "interference",
+            EventEffect::Noise(_) => "noise",
+            EventEffect::Blanker(_) => "blanker",
+            EventEffect::Dropout(_) => "dropout",
+        }
+    }
+
+    /// Эффект канала с параметрами события
+    pub fn processor(&self) -> Box<dyn ChannelEffect> {
+        match self {
+            EventEffect::Retarder(p) => Box::new(RetarderProcessor::new_with_params(p.clone())),
+            EventEffect::Multipath(p) => Box::new(MultipathProcessor::new_with_params(p.clone())),
+            EventEffect::Fading(p) => Box::new(FadingProcessor::new_with_params(p.clone())),
+            EventEffect::Drift(p) => Box::new(DriftProcessor::new_with_params(p.clone())),
+            EventEffect::Interference(p) => {
+                Box::new(InterferenceProcessor::new_with_params(p.clone()))
+            }
+            EventEffect::Noise(p) => Box::new(NoiseProcessor::new_with_params(p.clone())),
+            EventEffect::Blanker(p) => Box::new(BlankerProcessor::new_with_params(p.clone())),
+            EventEffect::Dropout(p) => Box::new(DropoutProcessor::new_with_params(p.clone())),
+        }
+    }
+}
+
+/// Цветовой канал строки Martin M1, которым можно ограничить событие
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ScanChannel {
+    Red,
+    Green,
+    Blue,
+}
+
+impl ScanChannel {
+    /// Индекс канала в разметке режима: R=0, G=1, B=2
+    pub fn index(&self) -> usize {
+        match self {
+            ScanChannel::Red => 0,
+            ScanChannel::Green => 1,
+            ScanChannel::Blue => 2,
+        }
+    }
+}
+
+/// Эффект, включённый на отрезок передачи
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EffectEvent {
+    /// Начало, секунд от начала звука или тела изображения, см. `anchor`
+    pub start_s: f32,
+    /// Длительность, секунд
+    pub duration_s: f32,
+    /// От чего отсчитывается `start_s`: весь звук или тело изображения
+    #[serde(default = "default_anchor")]
+    pub anchor: EnvelopeAnchor,
+    /// Только в развёртке этого канала каждой строки, например при зелёной развёртке
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub channel: Option<ScanChannel>,
+    /// Длительность линейных фронтов включения и выключения, мс
+    #[serde(default = "default_ramp_ms")]
+    pub ramp_ms: f32,
+    #[serde(flatten)]
+    pub effect: EventEffect,
+}
+
+fn default_anchor() -> EnvelopeAnchor {
+    EnvelopeAnchor::Audio
+}
+
+impl EffectEvent {
+    /// Сэмплы события в сигнале длиной `len` с частотой `rate` и телом изображения
+    /// `image`; пустой диапазон, если событие целиком вне сигнала
+    pub fn window(&self, len: usize, rate: u32, image: &Range<usize>) -> Range<usize> {
+        let origin = match self.anchor {
+            EnvelopeAnchor::Audio => 0.0,
+            EnvelopeAnchor::Image => image.start as f64,
+        };
+        let at = |s: f32| (origin + s as f64 * rate as f64).clamp(0.0, len as f64) as usize;
+        at(self.start_s)..at(self.start_s + self.duration_s.max(0.0))
+    }
+
+    pub fn describe(&self) -> String {
+        messages::timeline_event(
+            self.effect.name(),
+            self.start_s,
+            self.start_s + self.duration_s,
+        )
+    }
+}
+
+/// Сценарий событий из файла TOML или JSON: массив таблиц `[[events]]`
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Timeline {
+    pub events: Vec<EffectEvent>,
+}
+
+impl Timeline {
+    /// Читает сценарий; формат выбирается по расширению `.toml` или `.json`. Пресет
+    /// с разделом `[[events]]` тоже читается как сценарий
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        preset::read_file(path.as_ref(), "timeline", messages::file_timeline())
+    }
+}
+
+/// События по времени: каждое событие прогоняет свой эффект по копии всего сигнала,
+/// чтобы задержки и фильтры видели сигнал до окна, и вставляет результат только в своё
+/// окно с фронтами. События применяются по порядку, перекрывающиеся — друг поверх друга
+pub struct TimelineProcessor {
+    #[deprecated(since = "0.1.0", note = "используйте `events()` и `set_events()`")]
+    pub events: Vec<EffectEvent>,
+}
+
+impl Default for TimelineProcessor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[allow(deprecated)]
+impl TimelineProcessor {
+    pub fn new() -> Self {
+        Self { events: Vec::new() }
+    }
+
+    pub fn new_with_params(events: Vec<EffectEvent>) -> Self {
+        Self { events }
+    }
+
+    pub fn events(&self) -> &[EffectEvent] {
+        &self.events
+    }
+
+    pub fn set_events(&mut self, events: Vec<EffectEvent>) {
+        self.events = events;
+    }
+
+    fn apply_event(
+        &self,
+        event: &EffectEvent,
+        buffer: &mut SampleBuffer,
+        ctx: &EffectContext,
+    ) -> Result<()> {
+        let window = event.window(buffer.len(), buffer.rate, &ctx.image_span);
+        let effect = event.effect.processor();
+        if window.is_empty() || !effect.is_enabled() {
+            return Ok(());
+        }
+        let mut wet = buffer.clone();
+        effect::apply_effects(std::slice::from_ref(&effect), &mut wet, ctx)?;
+
+        let ramp =
+            ((event.ramp_ms.max(0.0) * 1e-3 * buffer.rate as f32) as usize).min(window.len() / 2);
+        let timing = event.channel.map(|_| ctx.timing());
+        for i in window.clone() {
+            if let (Some(channel), Some(timing)) = (event.channel, &timing)
+                && timing.sample_to_pixel(i).map(|(_, _, c)| c) != Some(channel.index())
+            {
+                continue;
+            }
+            let edge = (i - window.start).min(window.end - 1 - i);
+            let weight = if edge < ramp {
+                (edge as f32 + 0.5) / ramp as f32
+            } else {
+                1.0
+            };
+            let dry = buffer.data[i];
+            buffer.data[i] = dry + weight * (wet.data[i] - dry);
+        }
+        Ok(())
+    }
+}
+
+#[allow(deprecated)]
+impl ChannelEffect for TimelineProcessor {
+    fn name(&self) -> &'static str {
+        messages::timeline()
+    }
+
+    fn apply(&self, buffer: &mut SampleBuffer, ctx: &EffectContext) -> Result<()> {
+        for event in &self.events {
+            self.apply_event(event, buffer, ctx)?;
+        }
+        Ok(())
+    }
+
+    fn is_enabled(&self) -> bool {
+        !self.events.is_empty()
+    }
+
+    fn describe(&self) -> String {
+        let events: Vec<String> = self.events.iter().map(EffectEvent::describe).collect();
+        messages::timeline_describe(&events.join(", "))
+    }
+
+    fn sync_params(&mut self, params: &ProcessingParams) {
+        self.events = params.events.clone();
+    }
+}
@@ -0,0 +1,301 @@
+use crate::buffer::SampleBuffer;
+use crate::dsp::fir::{self, Window};
+use crate::dsp::ssb::{self, Sideband};
+use crate::dsp::{self, ConvolutionBackend};
+use crate::effect::{ChannelEffect, EffectContext, EffectEstimate};
+use crate::error::Result;
+use crate::messages;
+use crate::processor::ProcessingParams;
+use serde::{Deserialize, Serialize};
+
+/// Длина КИХ-фильтра полосы, мс. С окном Блэкмана переходная полоса ≈ 5.5 / длина,
+/// то есть около 275 Гц — как у кварцевого фильтра SSB
+const FILTER_MS: f64 = 20.0;
+/// Наибольшая перегрузка ограничителя, дБ: выход уже прямоугольный
+pub const MAX_DRIVE_DB: f32 = 120.0;
+/// Частота, не ниже которой работает ограничитель: на меньшей частоте сигнала он
+/// считается с передискретизацией, иначе гармоники ограничения от верхних тонов
+/// (третья от 2300 Гц — 6900 Гц) заворачиваются в полосу SSTV
+const CLIP_MIN_RATE: u32 = 44100;
+
+/// Вид сигнала, который трансивер отдаёт в WAV
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TransceiverOutput {
+    /// Звук на выходе приёмника
+    #[default]
+    Mono,
+    /// Квадратуры I/Q комплексной огибающей вокруг подавленной несущей (стерео WAV)
+    Iq,
+}
+
+impl TransceiverOutput {
+    pub const ALL: &'static [TransceiverOutput] = &[TransceiverOutput::Mono, TransceiverOutput::Iq];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            TransceiverOutput::Mono => "Mono",
+            TransceiverOutput::Iq => "IQ",
+        }
+    }
+}
+
+impl clap::ValueEnum for TransceiverOutput {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[Self::Mono, Self::Iq]
+    }
+
+    fn to_possible_value<'a>(&self) -> Option<clap::builder::PossibleValue> {
+        Some(match self {
+            Self::Mono => clap::builder::PossibleValue::new("mono"),
+            Self::Iq => clap::builder::PossibleValue::new("iq"),
+        })
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TransceiverParams {
+    /// Пропускать сигнал через тракт SSB-трансивера
+    pub enabled: bool,
+    /// Нижняя граница звуковой полосы, Гц
+    pub low_hz: f32,
+    /// Верхняя граница звуковой полосы, Гц
+    pub high_hz: f32,
+    /// Усиление перед мягким ограничителем, дБ: перегрузка микрофонного входа
+    pub drive_db: f32,
+    /// Время восстановления АРМ (ALC) усилителя мощности, мс; 0 — без АРМ
+    pub alc_release_ms: f32,
+    /// Остаток несущей относительно пика сигнала 0–1: разбаланс балансного модулятора
+    pub carrier_leak: f32,
+    /// Разбаланс квадратур по амплитуде, дБ
+    pub iq_gain_db: f32,
+    /// Разбаланс квадратур по фазе, градусов
+    pub iq_phase_deg: f32,
+    /// Боковая полоса передатчика: определяет знак частот в IQ
+    pub sideband: Sideband,
+    pub output: TransceiverOutput,
+}
+
+impl Default for TransceiverParams {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            low_hz: 300.0,
+            high_hz: 2700.0,
+            drive_db: 6.0,
+            alc_release_ms: 100.0,
+            carrier_leak: 0.0,
+            iq_gain_db: 0.0,
+            iq_phase_deg: 0.0,
+            sideband: Sideband::Upper,
+            output: TransceiverOutput::Mono,
+        }
+    }
+}
+
+impl TransceiverParams {
+    /// Комплексная огибающая сигнала `samples` (звук приёмника) относительно подавленной
+    /// несущей: боковая полоса `sideband` (SSB-модулятор фазовым методом), остаток
+    /// несущей на нулевой частоте и разбаланс квадратур, дающий зеркальную полосу. Пары (I, Q)
+    pub fn to_iq(&self, samples: &[f32], convolution: ConvolutionBackend) -> Vec<(f32, f32)> {
+        let gain = 10f32.powf(self.iq_gain_db / 20.0);
+        let (sin, cos) = (self.iq_phase_deg as f64).to_radians().sin_cos();
+        let (sin, cos) = (sin as f32, cos as f32);
+        ssb::to_iq(samples, self.sideband, convolution)
+            .into_iter()
+            .map(|(i, q)| {
+                let i = i + self.carrier_leak;
+                (i, gain * (q * cos + i * sin))
+            })
+            .collect()
+    }
+}
+
+/// Половина тракта SSB-трансивера
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum TransceiverStage {
+    /// Ограничитель, фильтр боковой полосы и АРМ передатчика; стоит до эффектов канала
+    Transmit,
+    /// Фильтр приёмника; стоит после эффектов канала
+    Receive,
+}
+
+/// Тракт SSB-трансивера: передатчик ограничивает и сжимает сигнал и срезает его полосой
+/// фильтра, приёмник снова срезает полосу вместе с шумом и помехами вне неё
+pub struct TransceiverProcessor {
+    #[deprecated(since = "0.1.0", note = "используйте `params()` и `set_params()`")]
+    pub params: TransceiverParams,
+    stage: TransceiverStage,
+}
+
+#[allow(deprecated)]
+impl TransceiverProcessor {
+    pub fn new_with_params(params: TransceiverParams, stage: TransceiverStage) -> Self {
+        Self { params, stage }
+    }
+
+    pub fn params(&self) -> &TransceiverParams {
+        &self.params
+    }
+
+    pub fn set_params(&mut self, params: TransceiverParams) {
+        self.params = params;
+    }
+
+    /// Передающая половина тракта
+    pub fn transmitter(params: TransceiverParams) -> Self {
+        Self::new_with_params(params, TransceiverStage::Transmit)
+    }
+
+    /// Приёмная половина тракта
+    pub fn receiver(params: TransceiverParams) -> Self {
+        Self::new_with_params(params, TransceiverStage::Receive)
+    }
+
+    pub fn stage(&self) -> TransceiverStage {
+        self.stage
+    }
+
+    /// Пропускает сэмплы через свою половину тракта
+    pub fn apply_transceiver(
+        &self,
+        buffer: &mut SampleBuffer,
+        convolution: ConvolutionBackend,
+    ) -> Result<()> {
+        let sample_rate = buffer.rate;
+        let samples = &mut buffer.data;
+        if !self.is_enabled() || samples.is_empty() {
+            return Ok(());
+        }
+
+        if self.stage == TransceiverStage::Transmit {
+            self.clip(samples, sample_rate);
+        }
+        let filtered = self.bandpass(samples, sample_rate, convolution);
+        samples.copy_from_slice(&filtered);
+        if self.stage == TransceiverStage::Transmit {
+            self.alc(samples, sample_rate);
+        }
+        Ok(())
+    }
+
+    /// Мягкое ограничение: пик входа приводится к 1 (уровень, на который выставлено
+    /// микрофонное усиление), затем усиливается на `drive_db` и сжимается гиперболическим
+    /// тангенсом. Перегрузка выше [`MAX_DRIVE_DB`] уже ничего не меняет и ограничивается,
+    /// чтобы усиление оставалось конечным. Ниже [`CLIP_MIN_RATE`] сигнал на время
+    /// ограничения передискретизируется вверх в целое число раз, а гармоники выше
+    /// исходной частоты Найквиста срезаются при возврате
+    fn clip(&self, samples: &mut [f32], sample_rate: u32) {
+        let peak = samples.iter().fold(0.0f32, |m, &v| m.max(v.abs()));
+        if peak == 0.0 {
+            return;
+        }
+        let drive_db = self.params.drive_db.min(MAX_DRIVE_DB) as f64;
+        let gain = 10f64.powf(drive_db / 20.0) / peak as f64;
+        let shape = |sample: &mut f32| *sample = (*sample as f64 * gain).tanh() as f32;
+
+        let factor = CLIP_MIN_RATE.div_ceil(sample_rate.max(1));
+        if factor <= 1 {
+            samples.iter_mut().for_each(shape);
+            return;
+        }
+        let high_rate = sample_rate * factor;
+        let mut oversampled = dsp::resample(samples, sample_rate, high_rate);
+        oversampled.iter_mut().for_each(shape);
+        let clipped = dsp::resample(&oversampled, high_rate, sample_rate);
+        for (sample, &c) in samples.iter_mut().zip(&clipped) {
+            *sample = c;
+        }
+    }
+
+    /// АРМ: пиковый детектор с мгновенной атакой и экспоненциальным восстановлением;
+    /// усиление снижается, пока огибающая выше полной шкалы
+    fn alc(&self, samples: &mut [f32], sample_rate: u32) {
+        if self.params.alc_release_ms <= 0.0 {
+            return;
+        }
+        let release = (-1000.0 / (self.params.alc_release_ms * sample_rate as f32)).exp();
+        let mut envelope = 0.0f32;
+        for sample in samples.iter_mut() {
+            envelope = sample.abs().max(envelope * release);
+            *sample /= envelope.max(1.0);
+        }
+    }
+
+    /// Полосовой КИХ-фильтр `low_hz`–`high_hz`: разность двух ФНЧ sinc с окном Блэкмана
+    fn bandpass(
+        &self,
+        samples: &[f32],
+        sample_rate: u32,
+        convolution: ConvolutionBackend,
+    ) -> Vec<f32> {
+        let fs = sample_rate as f64;
+        let nyquist = fs / 2.0;
+        let low = (self.params.low_hz as f64).clamp(0.0, nyquist) / fs;
+        let high = (self.params.high_hz as f64).clamp(0.0, nyquist) / fs;
+        let half = (FILTER_MS / 1000.0 * fs / 2.0).round() as usize;
+        let kernel: Vec<f32> = fir::bandpass(low, high, 2 * half + 1, Window::Blackman)
+            .into_iter()
+            .map(|tap| tap as f32)
+            .collect();
+        dsp::convolve(samples, &kernel, half, convolution)
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.params.enabled && self.params.high_hz > self.params.low_hz
+    }
+
+    /// Возвращает описание текущих настроек тракта
+    pub fn describe(&self) -> String {
+        if !self.is_enabled() {
+            return messages::transceiver_off().to_string();
+        }
+        let p = &self.params;
+        match self.stage {
+            TransceiverStage::Transmit => messages::transmitter_describe(
+                p.low_hz,
+                p.high_hz,
+                p.drive_db,
+                p.alc_release_ms,
+                p.carrier_leak,
+            ),
+            TransceiverStage::Receive => {
+                messages::receiver_describe(p.sideband.name(), p.low_hz, p.high_hz, p.output.name())
+            }
+        }
+    }
+}
+
+#[allow(deprecated)]
+impl ChannelEffect for TransceiverProcessor {
+    fn name(&self) -> &'static str {
+        match self.stage {
+            TransceiverStage::Transmit => messages::transmitter(),
+            TransceiverStage::Receive => messages::receiver(),
+        }
+    }
+
+    fn apply(&self, buffer: &mut SampleBuffer, ctx: &EffectContext) -> Result<()> {
+        self.apply_transceiver(buffer, ctx.convolution)
+    }
+
+    fn is_enabled(&self) -> bool {
+        TransceiverProcessor::is_enabled(self)
+    }
+
+    fn describe(&self) -> String {
+        TransceiverProcessor::describe(self)
+    }
+
+    fn sync_params(&mut self, params: &ProcessingParams) {
+        self.params = params.transceiver.clone();
+    }
+
+    fn analyze(&self, _buffer: &SampleBuffer, _ctx: &EffectContext) -> EffectEstimate {
+        EffectEstimate {
+            passband_hz: Some((self.params.low_hz, self.params.high_hz)),
+            ..EffectEstimate::new(self.name(), self.describe())
+        }
+    }
+}
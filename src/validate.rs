@@ -0,0 +1,156 @@
+use crate::advice;
+use crate::effect::MixTarget;
+use crate::messages;
+use crate::noise::{NoiseColor, NoiseKind, level_snr_db};
+use crate::processor::{MAX_OUTPUT_GAIN_DB, ProcessingParams};
+
+/// Замечание к параметрам: значение, которое эффект молча поправит или при котором
+/// прогон теряет смысл. Обработку не останавливает
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParamWarning {
+    /// Поле с путём как в пресете, например `retarder.delay_ms`
+    pub field: &'static str,
+    /// Что не так и что сделать
+    pub message: String,
+}
+
+impl std::fmt::Display for ParamWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.field, self.message)
+    }
+}
+
+impl ProcessingParams {
+    /// Проверяет бессмысленные сочетания параметров: неположительный повтор огибающей,
+    /// задержку длиннее всей передачи, С/Ш ниже порога декодера, уровни и доли смешивания
+    /// вне 0–1 и выходное усиление за пределом, которые обрезались бы без предупреждения,
+    /// несущие и частоты фильтров выше частоты Найквиста сигнала и надпись не в
+    /// ASCII без своего шрифта
+    pub fn validate(&self) -> Vec<ParamWarning> {
+        let mut warnings = Vec::new();
+        let mut warn = |field, message| warnings.push(ParamWarning { field, message });
+
+        if self.noise.repeat <= 0.0 {
+            warn("noise.repeat", messages::validate_repeat(self.noise.repeat));
+        }
+        if self.retarder.repeat <= 0.0 {
+            warn(
+                "retarder.repeat",
+                messages::validate_repeat(self.retarder.repeat),
+            );
+        }
+
+        let transmission_ms = self.encoder.transmission_ms();
+        if self.retarder.delay_ms as f64 > transmission_ms {
+            warn(
+                "retarder.delay_ms",
+                messages::validate_delay(self.retarder.delay_ms as f32, transmission_ms),
+            );
+        }
+        if let Some(tap) = self
+            .multipath
+            .taps
+            .iter()
+            .find(|t| t.delay_ms as f64 > transmission_ms)
+        {
+            warn(
+                "multipath.taps",
+                messages::validate_delay(tap.delay_ms, transmission_ms),
+            );
+        }
+
+        let limit = advice::noise_limit(self.decoder.backend);
+        if self.noise.kind == NoiseKind::Gaussian && self.noise.level > limit {
+            warn(
+                "noise.level",
+                messages::validate_snr(
+                    level_snr_db(self.noise.level),
+                    level_snr_db(limit),
+                    self.decoder.backend.name(),
+                ),
+            );
+        }
+
+        let mut unit_ranges = vec![
+            ("retarder.level", self.retarder.level),
+            ("fading.depth", self.fading.depth),
+        ];
+        for &target in MixTarget::ALL {
+            let (wet, dry) = target.fields();
+            let mix = self.mix(target);
+            unit_ranges.extend([(wet, mix.wet), (dry, mix.dry)]);
+        }
+        for (field, value) in unit_ranges {
+            if !(0.0..=1.0).contains(&value) {
+                warn(
+                    field,
+                    messages::validate_unit_range(value, value.clamp(0.0, 1.0)),
+                );
+            }
+        }
+
+        // Эффекты работают на частоте сигнала: выше её половины ничего не проходит
+        let nyquist = self.signal_rate() as f32 / 2.0;
+        if let Some(carrier) = self
+            .interference
+            .carriers
+            .iter()
+            .find(|c| c.level != 0.0 && c.freq_hz >= nyquist)
+        {
+            warn(
+                "interference.carriers",
+                messages::validate_carrier_nyquist(carrier.freq_hz, nyquist),
+            );
+        }
+        if self.transceiver.enabled && self.transceiver.high_hz > nyquist {
+            warn(
+                "transceiver.high_hz",
+                messages::validate_filter_nyquist(self.transceiver.high_hz, nyquist),
+            );
+        }
+        if self.filter.enabled && self.filter.freq_hz > nyquist {
+            warn(
+                "filter.freq_hz",
+                messages::validate_filter_nyquist(self.filter.freq_hz, nyquist),
+            );
+        }
+        if self.notch.enabled && self.notch.freq_hz > nyquist {
+            warn(
+                "notch.freq_hz",
+                messages::validate_filter_nyquist(self.notch.freq_hz, nyquist),
+            );
+        }
+        if let NoiseColor::BandLimited { high_hz, .. } = self.noise.color
+            && self.noise.level > 0
+            && high_hz > nyquist
+        {
+            warn(
+                "noise.color",
+                messages::validate_filter_nyquist(high_hz, nyquist),
+            );
+        }
+
+        if self.output_gain_db.abs() > MAX_OUTPUT_GAIN_DB {
+            warn(
+                "output_gain_db",
+                messages::validate_output_gain(self.output_gain_db, MAX_OUTPUT_GAIN_DB),
+            );
+        }
+        if self.loudness.enabled() && self.loudness.true_peak_db > 0.0 {
+            warn(
+                "loudness.true_peak_db",
+                messages::validate_true_peak(self.loudness.true_peak_db),
+            );
+        }
+
+        // Встроенный шрифт надписи знает только ASCII, остальное станет знаками «?»
+        let overlay = self.overlay_params();
+        if overlay.font.is_none()
+            && let Some(line) = overlay.lines.iter().find(|line| !line.is_ascii())
+        {
+            warn("overlay.lines", messages::validate_overlay_ascii(line));
+        }
+
+        warnings
+    }
+}
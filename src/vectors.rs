@@ -0,0 +1,342 @@
+use crate::buffer::SampleBuffer;
+use crate::conformance;
+use crate::drift::{DriftParams, DriftProcessor};
+use crate::dsp::{ConvolutionBackend, Resampler};
+use crate::encoder::EncoderBackend;
+use crate::error::{Result, SstvError};
+use crate::messages;
+use crate::metadata;
+use crate::modes::ModeSpec;
+use crate::sweep::{self, ManifestFormat};
+use crate::wav;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use rand_distr::{Distribution, Normal};
+use rayon::prelude::*;
+use rsstv::SAMPLE_RATE;
+use serde::Serialize;
+use std::path::PathBuf;
+
+/// Версия набора: меняется при любом изменении таблицы [`CASES`] или способа искажения,
+/// чтобы результаты разных версий не сравнивали между собой
+pub const SUITE: &str = "rust-sstv-vectors/1";
+/// Зерно шума по умолчанию
+pub const DEFAULT_SEED: u64 = 2311;
+/// Тишина с шумом до и после передачи, с: декодер должен сам найти её начало
+pub const LEAD_SECS: f64 = 1.0;
+/// Пик каждого файла после нормировки, доля полной шкалы (−1 dBFS)
+const PEAK: f32 = 0.891;
+/// Полоса, к которой в описи пересчитывается С/Ш: так его принято указывать для SSB
+pub const REFERENCE_BANDWIDTH_HZ: f64 = 2500.0;
+/// Знаменатель передискретизации для наклона: частоты 10⁶ и 10⁶ + ppm дают наклон ровно
+/// в целое число ppm
+const SLANT_SCALE: u32 = 1_000_000;
+
+/// Одно искажение набора. С/Ш — отношение мощности сигнала к мощности белого шума во
+/// всей полосе файла; расстройка сдвигает весь спектр; наклон — ошибка часов передатчика
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct VectorCase {
+    /// Имя искажения в имени файла
+    pub name: &'static str,
+    /// С/Ш, дБ; `None` — без шума
+    pub snr_db: Option<f32>,
+    /// Расстройка приёмника, Гц: положительная повышает все тоны
+    pub offset_hz: f32,
+    /// Ошибка часов передатчика, ppm: положительная удлиняет строки
+    pub slant_ppm: i32,
+}
+
+const fn case(
+    name: &'static str,
+    snr_db: Option<f32>,
+    offset_hz: f32,
+    slant_ppm: i32,
+) -> VectorCase {
+    VectorCase {
+        name,
+        snr_db,
+        offset_hz,
+        slant_ppm,
+    }
+}
+
+/// Стандартный набор искажений; каждое пишется для каждого режима из
+/// [`conformance::MODES`]. Оси меняются по одной, последние два — вместе
+pub const CASES: &[VectorCase] = &[
+    case("clean", None, 0.0, 0),
+    case("snr_30db", Some(30.0), 0.0, 0),
+    case("snr_20db", Some(20.0), 0.0, 0),
+    case("snr_15db", Some(15.0), 0.0, 0),
+    case("snr_10db", Some(10.0), 0.0, 0),
+    case("snr_6db", Some(6.0), 0.0, 0),
+    case("snr_3db", Some(3.0), 0.0, 0),
+    case("snr_0db", Some(0.0), 0.0, 0),
+    case("snr_m3db", Some(-3.0), 0.0, 0),
+    case("offset_p50hz", None, 50.0, 0),
+    case("offset_m50hz", None, -50.0, 0),
+    case("offset_p150hz", None, 150.0, 0),
+    case("offset_m150hz", None, -150.0, 0),
+    case("offset_p300hz", None, 300.0, 0),
+    case("slant_p100ppm", None, 0.0, 100),
+    case("slant_m100ppm", None, 0.0, -100),
+    case("slant_p500ppm", None, 0.0, 500),
+    case("slant_m500ppm", None, 0.0, -500),
+    case("slant_p2000ppm", None, 0.0, 2000),
+    case("slant_m2000ppm", None, 0.0, -2000),
+    case("mixed_a", Some(10.0), 100.0, 300),
+    case("mixed_b", Some(6.0), -100.0, -300),
+];
+
+/// Файл набора в описи
+#[derive(Clone, Debug, Serialize)]
+pub struct VectorEntry {
+    pub suite: &'static str,
+    /// Имя WAV относительно выходного каталога
+    pub file: String,
+    pub mode: &'static str,
+    pub vis_code: u8,
+    pub case: &'static str,
+    /// С/Ш во всей полосе файла, дБ; `None` — без шума
+    pub snr_db: Option<f32>,
+    /// Тот же С/Ш, пересчитанный в полосу [`REFERENCE_BANDWIDTH_HZ`], дБ
+    pub snr_2500_db: Option<f32>,
+    pub offset_hz: f32,
+    pub slant_ppm: i32,
+    /// Зерно шума файла
+    pub seed: u64,
+    pub sample_rate: u32,
+    /// Начало передачи (лидер-тона) в файле, с
+    pub start_secs: f64,
+    pub duration_secs: f64,
+    /// Изображение, которое передаётся, — PNG тестовой таблицы режима
+    pub source: String,
+    /// CRC-32 файла WAV, шестнадцатеричный: для проверки скачанного набора
+    pub crc32: String,
+}
+
+/// Набор тестовых сигналов для сторонних декодеров: тестовая таблица каждого режима,
+/// закодированная встроенным кодером и искажённая по таблице [`CASES`]. Шум берётся из
+/// генератора с зерном, поэтому тот же `seed` той же версией даёт те же файлы
+pub struct VectorSuite {
+    pub output_dir: PathBuf,
+    pub format: ManifestFormat,
+    pub seed: u64,
+}
+
+impl VectorSuite {
+    pub fn new(output_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            output_dir: output_dir.into(),
+            format: ManifestFormat::Json,
+            seed: DEFAULT_SEED,
+        }
+    }
+
+    /// Путь к файлу описи
+    pub fn manifest_path(&self) -> PathBuf {
+        self.output_dir
+            .join("manifest")
+            .with_extension(self.format.extension())
+    }
+
+    /// Пишет таблицу каждого режима в PNG, все искажения в WAV и опись `manifest.<ext>`.
+    /// Файлы считаются параллельно; `on_done` вызывается после каждого (из рабочих
+    /// потоков)
+    pub fn write(&self, on_done: impl Fn(&VectorEntry) + Sync) -> Result<Vec<VectorEntry>> {
+        std::fs::create_dir_all(&self.output_dir).map_err(|e| {
+            SstvError::io(
+                messages::create_dir_failed(&self.output_dir.display().to_string()),
+                e,
+            )
+        })?;
+
+        let mut entries = Vec::new();
+        for (m, &spec) in conformance::MODES.iter().enumerate() {
+            let stem = mode_stem(spec);
+            let source = format!("{}_source.png", stem);
+            let path = self.output_dir.join(&source);
+            conformance::test_pattern(spec).save(&path).map_err(|e| {
+                SstvError::io(messages::save_failed(&path.display().to_string()), e)
+            })?;
+
+            let clean = conformance::reference_signal(spec, EncoderBackend::Native);
+            let done = CASES
+                .par_iter()
+                .enumerate()
+                .map(|(c, case)| {
+                    let seed = self.seed.wrapping_add((m * CASES.len() + c) as u64);
+                    let entry = self.write_case(spec, case, &clean, seed, &source)?;
+                    on_done(&entry);
+                    Ok(entry)
+                })
+                .collect::<Result<Vec<_>>>()?;
+            entries.extend(done);
+        }
+
+        self.write_manifest(&entries)?;
+        Ok(entries)
+    }
+
+    fn write_case(
+        &self,
+        spec: &'static ModeSpec,
+        case: &VectorCase,
+        clean: &[f32],
+        seed: u64,
+        source: &str,
+    ) -> Result<VectorEntry> {
+        let buffer = impair(clean, SAMPLE_RATE as u32, case, seed)?;
+        let file = format!("{}_{}.wav", mode_stem(spec), case.name);
+        let path = self.output_dir.join(&file);
+        let path_str = path.to_string_lossy();
+        wav::write_wav(&path_str, &buffer)?;
+        let bytes =
+            std::fs::read(&path).map_err(|e| SstvError::io(messages::read_failed(&path_str), e))?;
+
+        let nyquist = buffer.rate as f64 / 2.0;
+        Ok(VectorEntry {
+            suite: SUITE,
+            file,
+            mode: spec.name,
+            vis_code: spec.vis_code,
+            case: case.name,
+            snr_db: case.snr_db,
+            snr_2500_db: case
+                .snr_db
+                .map(|snr| snr + (10.0 * (nyquist / REFERENCE_BANDWIDTH_HZ).log10()) as f32),
+            offset_hz: case.offset_hz,
+            slant_ppm: case.slant_ppm,
+            seed,
+            sample_rate: buffer.rate,
+            start_secs: LEAD_SECS,
+            duration_secs: buffer.duration_secs(),
+            source: source.to_string(),
+            crc32: format!("{:08x}", metadata::crc32(&bytes)),
+        })
+    }
+
+    fn write_manifest(&self, entries: &[VectorEntry]) -> Result<()> {
+        let text = match self.format {
+            ManifestFormat::Json => serde_json::to_string_pretty(entries)
+                .map_err(|e| SstvError::io(messages::manifest_serialize_failed(), e))?,
+            ManifestFormat::Csv => manifest_csv(entries),
+        };
+        let path = self.manifest_path();
+        std::fs::write(&path, text).map_err(|e| {
+            SstvError::io(
+                messages::manifest_write_failed(&path.display().to_string()),
+                e,
+            )
+        })
+    }
+}
+
+/// Искажает чистый сигнал `clean` частоты `sample_rate` по `case`: наклон часов
+/// передатчика, тишина [`LEAD_SECS`] по краям, расстройка приёмника, белый шум с зерном
+/// `seed` и нормировка пика. Один и тот же вход даёт один и тот же выход
+pub fn impair(
+    clean: &[f32],
+    sample_rate: u32,
+    case: &VectorCase,
+    seed: u64,
+) -> Result<SampleBuffer> {
+    // Медленные часы передатчика растягивают сигнал: больше сэмплов на строку
+    let slanted = Resampler::new(
+        SLANT_SCALE,
+        SLANT_SCALE.saturating_add_signed(case.slant_ppm),
+    )
+    .process(clean);
+    let signal_power =
+        slanted.iter().map(|&s| s as f64 * s as f64).sum::<f64>() / slanted.len().max(1) as f64;
+
+    let lead = vec![0.0; (LEAD_SECS * sample_rate as f64).round() as usize];
+    let mut buffer = SampleBuffer::mono([&lead, &slanted[..], &lead].concat(), sample_rate);
+
+    DriftProcessor::new_with_params(DriftParams {
+        offset_hz: case.offset_hz,
+        ..Default::default()
+    })
+    .apply_drift(&mut buffer, ConvolutionBackend::default())?;
+
+    if let Some(snr_db) = case.snr_db {
+        let sigma = (signal_power / 10f64.powf(snr_db as f64 / 10.0)).sqrt() as f32;
+        let normal = Normal::new(0.0, sigma).map_err(|e| {
+            SstvError::invalid("snr_db", messages::noise_level_invalid(&e.to_string()))
+        })?;
+        let mut rng = StdRng::seed_from_u64(seed);
+        buffer
+            .data
+            .iter_mut()
+            .for_each(|s| *s += normal.sample(&mut rng));
+    }
+
+    let peak = buffer.data.iter().fold(0.0f32, |peak, s| peak.max(s.abs()));
+    if peak > 0.0 {
+        let gain = PEAK / peak;
+        buffer.data.iter_mut().for_each(|s| *s *= gain);
+    }
+    Ok(buffer)
+}
+
+/// Имя режима в именах файлов: `martin_m1`
+fn mode_stem(spec: &ModeSpec) -> String {
+    spec.name.to_lowercase().replace(' ', "_")
+}
+
+fn manifest_csv(entries: &[VectorEntry]) -> String {
+    let mut csv = String::from(
+        "suite,file,mode,vis_code,case,snr_db,snr_2500_db,offset_hz,slant_ppm,seed,sample_rate,start_secs,duration_secs,source,crc32\n",
+    );
+    let optional = |v: Option<f32>| v.map_or_else(String::new, |v| format!("{:.2}", v));
+    for e in entries {
+        csv.push_str(&format!(
+            "{},{},{},{},{},{},{},{},{},{},{},{},{:.6},{},{}\n",
+            e.suite,
+            sweep::csv_field(&e.file),
+            sweep::csv_field(e.mode),
+            e.vis_code,
+            e.case,
+            optional(e.snr_db),
+            optional(e.snr_2500_db),
+            e.offset_hz,
+            e.slant_ppm,
+            e.seed,
+            e.sample_rate,
+            e.start_secs,
+            e.duration_secs,
+            sweep::csv_field(&e.source),
+            e.crc32
+        ));
+    }
+    csv
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::tone;
+
+    #[test]
+    fn vectors_are_reproducible_at_their_snr() {
+        let rate = 8000;
+        let tone = tone(1900.0, rate, rate as usize);
+        let case = CASES.iter().find(|case| case.name == "snr_10db").unwrap();
+        let a = impair(&tone, rate, case, 5).unwrap();
+        let b = impair(&tone, rate, case, 5).unwrap();
+        assert_eq!(a.data, b.data);
+        assert_ne!(a.data, impair(&tone, rate, case, 6).unwrap().data);
+
+        // Тишина по краям — чистый шум, середина — сигнал с шумом
+        let lead = (LEAD_SECS * rate as f64) as usize;
+        let power =
+            |s: &[f32]| s.iter().map(|&v| v as f64 * v as f64).sum::<f64>() / s.len() as f64;
+        let noise = power(&a.data[..lead]);
+        let signal = power(&a.data[lead..lead + tone.len()]) - noise;
+        let snr = 10.0 * (signal / noise).log10();
+        assert!((snr - 10.0).abs() < 0.3, "{snr}");
+
+        let slanted = CASES.iter().find(|case| case.slant_ppm == 2000).unwrap();
+        let stretched = impair(&tone, rate, slanted, 5).unwrap();
+        assert_eq!(stretched.data.len(), 2 * lead + tone.len() * 1002 / 1000);
+    }
+}
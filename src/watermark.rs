@@ -0,0 +1,248 @@
+use crate::encoder::fit_to_mode;
+use crate::error::{Result, SstvError};
+use crate::messages;
+use crate::modes::MARTIN_M1;
+use crate::processor::SSTVProcessor;
+use image::{DynamicImage, Rgb, RgbImage};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+/// Псевдослучайная метка для проверки стойкости канала: биты метки разнесены по
+/// клеткам изображения, каждая клетка чуть светлее или темнее. Сколько бит удаётся
+/// прочитать после канала, почти не зависит от самого изображения
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WatermarkParams {
+    /// Зерно: из него строятся биты метки, знаки клеток и их раскладка
+    pub seed: u64,
+    /// Бит в метке
+    pub bits: usize,
+    /// Сторона клетки, пикселей: строка SSTV размывает мелкие детали, поэтому клетка
+    /// крупнее пикселя
+    pub cell: u32,
+    /// Сдвиг яркости клетки, уровней 0–255
+    pub strength: f32,
+}
+
+impl Default for WatermarkParams {
+    fn default() -> Self {
+        Self {
+            seed: 0,
+            bits: 64,
+            cell: 8,
+            strength: 8.0,
+        }
+    }
+}
+
+/// Сколько метки пережило канал
+#[derive(Copy, Clone, Debug, PartialEq, Serialize)]
+pub struct WatermarkReport {
+    pub bits: usize,
+    /// Неверно прочитанных бит
+    pub errors: usize,
+    /// Доля верно прочитанных бит 0–1; 0,5 — угадывание
+    pub recovery: f64,
+    /// Нормированная корреляция остатка изображения с меткой, −1…1
+    pub correlation: f64,
+}
+
+impl WatermarkReport {
+    /// Стойкость одним числом 0–1: 0 — метка не читается лучше угадывания, 1 — прочитана
+    /// целиком
+    pub fn robustness(&self) -> f64 {
+        (2.0 * self.recovery - 1.0).clamp(0.0, 1.0)
+    }
+
+    pub fn describe(&self) -> String {
+        messages::watermark_describe(
+            self.robustness(),
+            self.bits - self.errors,
+            self.bits,
+            self.correlation,
+        )
+    }
+}
+
+/// Метка для изображений разрешения режима, построенная из [`WatermarkParams`]
+#[derive(Clone, Debug)]
+pub struct Watermark {
+    params: WatermarkParams,
+    payload: Vec<bool>,
+    /// Для каждой клетки по строкам: номер бита и знак ±1
+    cells: Vec<(usize, f32)>,
+    columns: u32,
+}
+
+impl Watermark {
+    pub fn new(params: WatermarkParams) -> Result<Self> {
+        if params.cell == 0 {
+            return Err(SstvError::invalid("cell", messages::watermark_no_cell()));
+        }
+        let columns = MARTIN_M1.width / params.cell;
+        let rows = MARTIN_M1.height / params.cell;
+        let count = (columns * rows) as usize;
+        if params.bits == 0 || params.bits > count {
+            return Err(SstvError::invalid(
+                "bits",
+                messages::watermark_bad_bits(count),
+            ));
+        }
+        if !(params.strength > 0.0 && params.strength <= 255.0) {
+            return Err(SstvError::invalid(
+                "strength",
+                messages::watermark_bad_strength(),
+            ));
+        }
+
+        let mut rng = StdRng::seed_from_u64(params.seed);
+        let payload = (0..params.bits).map(|_| rng.random_bool(0.5)).collect();
+        // Каждому биту поровну клеток, разбросанных по всему кадру
+        let mut bits: Vec<usize> = (0..count).map(|k| k % params.bits).collect();
+        bits.shuffle(&mut rng);
+        let cells = bits
+            .into_iter()
+            .map(|bit| (bit, if rng.random_bool(0.5) { 1.0 } else { -1.0 }))
+            .collect();
+        Ok(Self {
+            params,
+            payload,
+            cells,
+            columns,
+        })
+    }
+
+    /// Биты метки
+    pub fn payload(&self) -> &[bool] {
+        &self.payload
+    }
+
+    /// Сдвиг яркости клетки `index` в метке
+    fn shift(&self, index: usize) -> f32 {
+        let (bit, chip) = self.cells[index];
+        if self.payload[bit] { chip } else { -chip }
+    }
+
+    /// Клетка пикселя (x, y); `None` за краем сетки
+    fn cell_at(&self, x: u32, y: u32) -> Option<usize> {
+        let (column, row) = (x / self.params.cell, y / self.params.cell);
+        let index = (row * self.columns + column) as usize;
+        (column < self.columns && index < self.cells.len()).then_some(index)
+    }
+
+    /// Изображение `image`, приведённое к разрешению режима, с меткой
+    pub fn embed(&self, image: &DynamicImage) -> RgbImage {
+        let mut marked = fit_to_mode(image, &MARTIN_M1).to_rgb8();
+        for (x, y, pixel) in marked.enumerate_pixels_mut() {
+            let Some(index) = self.cell_at(x, y) else {
+                continue;
+            };
+            let shift = self.params.strength * self.shift(index);
+            *pixel = Rgb(pixel
+                .0
+                .map(|c| (c as f32 + shift).round().clamp(0.0, 255.0) as u8));
+        }
+        marked
+    }
+
+    /// Читает метку из `decoded` по разнице с `reference` — тем же изображением без
+    /// метки. Общий сдвиг яркости разницы не мешает: он вычитается
+    pub fn extract(&self, reference: &RgbImage, decoded: &RgbImage) -> WatermarkReport {
+        let reference = fit_to_mode(&DynamicImage::ImageRgb8(reference.clone()), &MARTIN_M1);
+        let decoded = fit_to_mode(&DynamicImage::ImageRgb8(decoded.clone()), &MARTIN_M1);
+        let (reference, decoded) = (reference.to_rgb8(), decoded.to_rgb8());
+
+        let mut sums = vec![(0.0f64, 0usize); self.cells.len()];
+        for ((x, y, a), b) in reference.enumerate_pixels().zip(decoded.pixels()) {
+            if let Some(index) = self.cell_at(x, y) {
+                sums[index].0 += luma(*b) - luma(*a);
+                sums[index].1 += 1;
+            }
+        }
+        let residual: Vec<f64> = sums.iter().map(|&(sum, n)| sum / n.max(1) as f64).collect();
+        let mean = residual.iter().sum::<f64>() / residual.len() as f64;
+
+        let mut soft = vec![0.0f64; self.params.bits];
+        let (mut dot, mut energy) = (0.0, 0.0);
+        for (index, &r) in residual.iter().enumerate() {
+            let r = r - mean;
+            let (bit, chip) = self.cells[index];
+            soft[bit] += r * chip as f64;
+            dot += r * self.shift(index) as f64;
+            energy += r * r;
+        }
+        let errors = soft
+            .iter()
+            .zip(&self.payload)
+            .filter(|&(&s, &bit)| (s > 0.0) != bit)
+            .count();
+        WatermarkReport {
+            bits: self.params.bits,
+            errors,
+            recovery: 1.0 - errors as f64 / self.params.bits as f64,
+            correlation: if energy > 0.0 {
+                dot / (energy * residual.len() as f64).sqrt()
+            } else {
+                0.0
+            },
+        }
+    }
+}
+
+/// Яркость по BT.601
+fn luma(Rgb([r, g, b]): Rgb<u8>) -> f64 {
+    0.299 * r as f64 + 0.587 * g as f64 + 0.114 * b as f64
+}
+
+/// Вносит метку в `image`, прогоняет его через канал `processor` и читает метку из
+/// декодированного изображения. Опорой служит то же изображение без метки с наложенным
+/// текстом процессора
+pub fn measure(
+    processor: &mut SSTVProcessor,
+    image: &DynamicImage,
+    params: &WatermarkParams,
+) -> Result<WatermarkReport> {
+    let watermark = Watermark::new(*params)?;
+    let fitted = fit_to_mode(image, &MARTIN_M1);
+    let marked = DynamicImage::ImageRgb8(watermark.embed(&fitted));
+    let report = processor.process(&marked, None)?;
+    let reference = processor.params().overlay_params().apply(&fitted)?;
+    Ok(watermark.extract(&reference.to_rgb8(), &report.image.to_rgb8()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::random_image;
+
+    #[test]
+    fn watermark_survives_clean_copy_only() {
+        let params = WatermarkParams::default();
+        let watermark = Watermark::new(params).unwrap();
+        let flat = DynamicImage::ImageRgb8(RgbImage::from_pixel(64, 64, Rgb([128, 128, 128])));
+        let busy = random_image(320, 256, 3);
+        for image in [&flat, &busy] {
+            let reference = fit_to_mode(image, &MARTIN_M1).to_rgb8();
+            let marked = watermark.embed(image);
+            let report = watermark.extract(&reference, &marked);
+            assert_eq!(report.errors, 0);
+            assert_eq!(report.robustness(), 1.0);
+            assert!(report.correlation > 0.9, "{:?}", report);
+            // Без метки читается не лучше угадывания
+            let report = watermark.extract(&reference, &reference);
+            assert!(report.robustness() < 0.5, "{:?}", report);
+        }
+
+        let other = Watermark::new(WatermarkParams { seed: 1, ..params }).unwrap();
+        assert_ne!(other.payload(), watermark.payload());
+        assert!(
+            Watermark::new(WatermarkParams {
+                bits: 10_000,
+                ..params
+            })
+            .is_err()
+        );
+    }
+}
@@ -0,0 +1,164 @@
+use crate::buffer::SampleBuffer;
+use crate::error::{Result, SstvError};
+use crate::messages;
+use hound::{SampleFormat, WavReader, WavSpec, WavWriter};
+use rand::Rng;
+use std::collections::BTreeMap;
+use std::fs::OpenOptions;
+use std::io::{Read, Seek, SeekFrom, Write};
+
+/// Читает WAV файл в моно-буфер с сэмплами в диапазоне −1…1 и исходной частотой дискретизации
+pub fn read_wav(path: &str) -> Result<SampleBuffer> {
+    let mut reader =
+        WavReader::open(path).map_err(|e| SstvError::io(messages::wav_open_failed(path), e))?;
+    let spec = reader.spec();
+    let channels = spec.channels as usize;
+    if channels == 0 {
+        return Err(SstvError::io(
+            messages::wav_read_failed(path),
+            messages::wav_no_channels(),
+        ));
+    }
+
+    let interleaved: Vec<f32> = match spec.sample_format {
+        SampleFormat::Float => reader
+            .samples::<f32>()
+            .collect::<Result<_, hound::Error>>()
+            .map_err(|e| SstvError::io(messages::wav_samples_failed(path), e))?,
+        SampleFormat::Int => {
+            let scale = (1i64 << (spec.bits_per_sample - 1)) as f32;
+            reader
+                .samples::<i32>()
+                .map(|s| s.map(|v| v as f32 / scale))
+                .collect::<Result<_, hound::Error>>()
+                .map_err(|e| SstvError::io(messages::wav_samples_failed(path), e))?
+        }
+    };
+
+    Ok(SampleBuffer::mono(
+        downmix(&interleaved, channels),
+        spec.sample_rate,
+    ))
+}
+
+/// Записывает буфер в 16-битный WAV с его частотой и числом каналов, ограничивая
+/// сэмплы диапазоном −1…1
+pub fn write_wav(path: &str, buffer: &SampleBuffer) -> Result<()> {
+    write_i16(
+        path,
+        buffer.channels,
+        buffer.rate,
+        buffer.iter().map(|&s| quantize(s)),
+    )
+}
+
+/// Как [`write_wav`], но с треугольным (TPDF) дизерингом в ±1 младший разряд: ошибка
+/// округления становится ровным шумом, а не искажением, зависящим от сигнала
+pub fn write_wav_dithered(path: &str, buffer: &SampleBuffer, rng: &mut impl Rng) -> Result<()> {
+    let quantized: Vec<i16> = buffer.iter().map(|&s| quantize_dithered(s, rng)).collect();
+    write_i16(path, buffer.channels, buffer.rate, quantized)
+}
+
+/// Записывает квадратуры в стерео 16-битный WAV: I — левый канал, Q — правый
+pub fn write_iq_wav(path: &str, iq: &[(f32, f32)], sample_rate: u32) -> Result<()> {
+    let interleaved = iq.iter().flat_map(|&(i, q)| [quantize(i), quantize(q)]);
+    write_i16(path, 2, sample_rate, interleaved)
+}
+
+/// Как [`write_iq_wav`], с дизерингом каждого канала, см. [`write_wav_dithered`]
+pub fn write_iq_wav_dithered(
+    path: &str,
+    iq: &[(f32, f32)],
+    sample_rate: u32,
+    rng: &mut impl Rng,
+) -> Result<()> {
+    let quantized: Vec<i16> = iq
+        .iter()
+        .flat_map(|&(i, q)| [quantize_dithered(i, rng), quantize_dithered(q, rng)])
+        .collect();
+    write_i16(path, 2, sample_rate, quantized)
+}
+
+/// Дописывает в WAV `path` блок LIST INFO с парами `fields` в комментарии ICMT
+/// (`ключ=значение; …`) и поправляет размер RIFF. Без пар файл не меняется
+pub fn write_info(path: &str, fields: &BTreeMap<String, String>) -> Result<()> {
+    if fields.is_empty() {
+        return Ok(());
+    }
+    let comment: Vec<String> = fields.iter().map(|(k, v)| format!("{}={}", k, v)).collect();
+    let mut text = comment.join("; ").into_bytes();
+    text.push(0);
+    let mut info = b"INFO".to_vec();
+    info.extend_from_slice(b"ICMT");
+    info.extend_from_slice(&(text.len() as u32).to_le_bytes());
+    info.extend_from_slice(&text);
+    // Блоки RIFF выравниваются на чётный байт
+    if text.len() % 2 == 1 {
+        info.push(0);
+    }
+    let mut chunk = b"LIST".to_vec();
+    chunk.extend_from_slice(&(info.len() as u32).to_le_bytes());
+    chunk.extend_from_slice(&info);
+
+    let error = |e| SstvError::io(messages::wav_metadata_failed(path), e);
+    let mut file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(path)
+        .map_err(error)?;
+    let mut riff_size = [0u8; 4];
+    file.seek(SeekFrom::Start(4)).map_err(error)?;
+    file.read_exact(&mut riff_size).map_err(error)?;
+    let riff_size = u32::from_le_bytes(riff_size) + chunk.len() as u32;
+    file.seek(SeekFrom::End(0)).map_err(error)?;
+    file.write_all(&chunk).map_err(error)?;
+    file.seek(SeekFrom::Start(4)).map_err(error)?;
+    file.write_all(&riff_size.to_le_bytes()).map_err(error)?;
+    Ok(())
+}
+
+fn quantize(sample: f32) -> i16 {
+    (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16
+}
+
+fn quantize_dithered(sample: f32, rng: &mut impl Rng) -> i16 {
+    let max = i16::MAX as f32;
+    let dither = rng.random::<f32>() - rng.random::<f32>();
+    (sample.clamp(-1.0, 1.0) * max + dither)
+        .round()
+        .clamp(i16::MIN as f32, max) as i16
+}
+
+fn write_i16(
+    path: &str,
+    channels: u16,
+    sample_rate: u32,
+    samples: impl IntoIterator<Item = i16>,
+) -> Result<()> {
+    let spec = WavSpec {
+        channels,
+        sample_rate,
+        bits_per_sample: 16,
+        sample_format: SampleFormat::Int,
+    };
+
+    let mut writer = WavWriter::create(path, spec)
+        .map_err(|e| SstvError::io(messages::create_failed(path), e))?;
+    let write_error = |e| SstvError::io(messages::write_failed(path), e);
+    for s in samples {
+        writer.write_sample(s).map_err(write_error)?;
+    }
+    writer.finalize().map_err(write_error)?;
+    Ok(())
+}
+
+/// Сводит перемежающиеся каналы в моно усреднением
+pub fn downmix(interleaved: &[f32], channels: usize) -> Vec<f32> {
+    if channels <= 1 {
+        return interleaved.to_vec();
+    }
+    interleaved
+        .chunks_exact(channels)
+        .map(|frame| frame.iter().sum::<f32>() / channels as f32)
+        .collect()
+}
@@ -0,0 +1,252 @@
+use crate::error::{Result, SstvError};
+use crate::messages;
+use crate::metadata;
+use crate::monitor::ReceivedImage;
+use image::DynamicImage;
+use reqwest::blocking::Client;
+use reqwest::blocking::multipart::{Form, Part};
+use serde_json::json;
+use std::sync::mpsc::{self, Sender};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+/// Попыток отправить одно изображение
+const ATTEMPTS: u32 = 3;
+/// Пауза после первой неудачной попытки, с; каждая следующая вдвое длиннее
+const RETRY_SECS: u64 = 5;
+/// Сколько ждать ответа сервера, с
+const REQUEST_TIMEOUT_SECS: u64 = 60;
+/// Самая длинная подпись: Telegram принимает до 1024 знаков, Discord — до 2000
+const MAX_CAPTION: usize = 1000;
+
+/// Куда и в каком виде отправляются изображения
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum WebhookKind {
+    /// По виду адреса: `discord.com/api/webhooks` — Discord, `api.telegram.org` — Telegram,
+    /// иначе — обычный
+    #[default]
+    Auto,
+    /// Вебхук канала Discord: подпись в `payload_json`, изображение в `files[0]`
+    Discord,
+    /// Метод `sendPhoto` бота Telegram: `chat_id`, `caption` и `photo`
+    Telegram,
+    /// Обычный multipart: `image`, `caption` и пары `metadata` в JSON
+    Generic,
+}
+
+impl WebhookKind {
+    pub const ALL: &'static [WebhookKind] = &[
+        WebhookKind::Auto,
+        WebhookKind::Discord,
+        WebhookKind::Telegram,
+        WebhookKind::Generic,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            WebhookKind::Auto => "auto",
+            WebhookKind::Discord => "discord",
+            WebhookKind::Telegram => "telegram",
+            WebhookKind::Generic => "generic",
+        }
+    }
+
+    /// Вид для адреса `url`: [`WebhookKind::Auto`] решается по нему, остальные — как есть
+    pub fn resolve(self, url: &str) -> Self {
+        if self != WebhookKind::Auto {
+            return self;
+        }
+        if url.contains("discord.com/api/webhooks") || url.contains("discordapp.com/api/webhooks") {
+            WebhookKind::Discord
+        } else if url.contains("api.telegram.org/bot") {
+            WebhookKind::Telegram
+        } else {
+            WebhookKind::Generic
+        }
+    }
+}
+
+impl clap::ValueEnum for WebhookKind {
+    fn value_variants<'a>() -> &'a [Self] {
+        Self::ALL
+    }
+
+    fn to_possible_value<'a>(&self) -> Option<clap::builder::PossibleValue> {
+        Some(clap::builder::PossibleValue::new(self.name()))
+    }
+}
+
+/// Вебхук для принятых изображений
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct WebhookParams {
+    /// Адрес вебхука Discord, метода `sendPhoto` бота Telegram
+    /// (`https://api.telegram.org/bot<TOKEN>/sendPhoto`) или своего сервера
+    pub url: String,
+    pub kind: WebhookKind,
+    /// Чат Telegram, куда бот шлёт изображения
+    pub chat_id: Option<String>,
+}
+
+impl WebhookParams {
+    /// Адрес — http(s), для Telegram задан чат
+    pub fn validate(&self) -> Result<()> {
+        let url = reqwest::Url::parse(&self.url).map_err(|e| {
+            SstvError::invalid("webhook.url", messages::webhook_bad_url(&e.to_string()))
+        })?;
+        if !matches!(url.scheme(), "http" | "https") {
+            return Err(SstvError::invalid(
+                "webhook.url",
+                messages::webhook_bad_scheme(url.scheme()),
+            ));
+        }
+        if self.kind.resolve(&self.url) == WebhookKind::Telegram && self.chat_id.is_none() {
+            return Err(SstvError::invalid(
+                "webhook.chat_id",
+                messages::webhook_no_chat(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Сервер вебхука без пути: в адресе бывает токен, и в журнал он не попадает
+    pub fn host(&self) -> String {
+        reqwest::Url::parse(&self.url)
+            .ok()
+            .and_then(|url| url.host_str().map(str::to_string))
+            .unwrap_or_default()
+    }
+}
+
+/// Отправка принятых изображений на вебхук: клуб видит каждую передачу, например с МКС,
+/// без участия оператора. Отправляет фоновый поток по очереди, так что приём не ждёт
+/// сети; неудачная отправка повторяется [`ATTEMPTS`] раз и пишется в журнал
+pub struct Notifier {
+    queue: Option<Sender<Post>>,
+    worker: Option<JoinHandle<()>>,
+}
+
+/// Готовое к отправке изображение
+struct Post {
+    png: Vec<u8>,
+    caption: String,
+    metadata: serde_json::Value,
+}
+
+impl Notifier {
+    /// Проверяет параметры и запускает поток отправки
+    pub fn start(params: WebhookParams) -> Result<Self> {
+        params.validate()?;
+        let client = Client::builder()
+            .timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS))
+            .build()
+            .map_err(|e| SstvError::io(messages::webhook_client_failed(), e))?;
+        let (queue, posts) = mpsc::channel::<Post>();
+        let worker = std::thread::spawn(move || {
+            for post in posts {
+                if let Err(e) = deliver(&client, &params, &post) {
+                    tracing::warn!("{}", e);
+                }
+            }
+        });
+        Ok(Self {
+            queue: Some(queue),
+            worker: Some(worker),
+        })
+    }
+
+    /// Ставит изображение в очередь на отправку с подписью из отметки приёма, VIS и
+    /// метаданных
+    pub fn notify(&self, received: &ReceivedImage) -> Result<()> {
+        let image = DynamicImage::ImageRgb8(received.image.clone());
+        let fields = received.text_fields();
+        let png = metadata::png_with_text(&image, &fields)
+            .map_err(|e| SstvError::io(messages::webhook_png_failed(), e))?;
+        let post = Post {
+            png,
+            caption: received.describe().chars().take(MAX_CAPTION).collect(),
+            metadata: json!(fields),
+        };
+        if let Some(queue) = &self.queue {
+            // Поток отправки живёт, пока жива очередь
+            let _ = queue.send(post);
+        }
+        Ok(())
+    }
+
+    /// Дожидается отправки всего, что в очереди
+    pub fn finish(mut self) {
+        self.close();
+    }
+
+    fn close(&mut self) {
+        self.queue = None;
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+impl Drop for Notifier {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
+
+/// Отправляет изображение, повторяя неудачные попытки с растущей паузой
+fn deliver(client: &Client, params: &WebhookParams, post: &Post) -> Result<()> {
+    let host = params.host();
+    let mut pause = Duration::from_secs(RETRY_SECS);
+    for attempt in 1..=ATTEMPTS {
+        let result = client
+            .post(&params.url)
+            .multipart(form(params, post)?)
+            .send()
+            .and_then(|response| response.error_for_status());
+        match result {
+            Ok(_) => {
+                tracing::info!("{}", messages::webhook_sent(&host));
+                return Ok(());
+            }
+            Err(e) if attempt < ATTEMPTS => {
+                tracing::warn!(
+                    "{}",
+                    messages::webhook_retry(&host, attempt, &e.without_url().to_string())
+                );
+                std::thread::sleep(pause);
+                pause *= 2;
+            }
+            Err(e) => {
+                return Err(SstvError::io(
+                    messages::webhook_send_failed(&host),
+                    e.without_url(),
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Тело запроса в виде, которого ждёт вебхук
+fn form(params: &WebhookParams, post: &Post) -> Result<Form> {
+    let image = Part::bytes(post.png.clone())
+        .file_name("received.png")
+        .mime_str("image/png")
+        .map_err(|e| SstvError::io(messages::webhook_form_failed(), e))?;
+    Ok(match params.kind.resolve(&params.url) {
+        WebhookKind::Discord => {
+            // Подпись — текст с эфира: упоминания @everyone и ролей в ней не срабатывают
+            let payload = json!({ "content": post.caption, "allowed_mentions": { "parse": [] } });
+            Form::new()
+                .text("payload_json", payload.to_string())
+                .part("files[0]", image)
+        }
+        WebhookKind::Telegram => Form::new()
+            .text("chat_id", params.chat_id.clone().unwrap_or_default())
+            .text("caption", post.caption.clone())
+            .part("photo", image),
+        WebhookKind::Generic | WebhookKind::Auto => Form::new()
+            .text("caption", post.caption.clone())
+            .text("metadata", post.metadata.to_string())
+            .part("image", image),
+    })
+}